@@ -72,6 +72,16 @@ export interface GranularParams {
 export interface ConvolutionParams {
     /** Dry/wet mix (0 = dry, 1 = wet) */
     dryWet: number;
+    /** Wet signal predelay in ms (0-200) */
+    predelayMs: number;
+    /** Early/late split point in ms (0-1000, snaps to a partition boundary) */
+    earlyLateSplitMs: number;
+    /** Early reflections gain (0-2, 1 = unity) */
+    earlyGain: number;
+    /** Reverb tail gain (0-2, 1 = unity) */
+    lateGain: number;
+    /** Blend from the slot 0 IR (0) to the slot 1 IR (1) */
+    irBlend: number;
 }
 
 /**
@@ -82,6 +92,23 @@ export interface SpectralParams {
     freezeAmount: number;
     /** Frequency shift in semitones (-24 to +24) */
     frequencyShift: number;
+    /**
+     * Capture slot to freeze to (0-3, fractional values blend neighbouring
+     * slots). -1, or a position without captures, freezes the spectrum at
+     * the moment freezeAmount rises above 0.
+     */
+    freezeSlot: number;
+    /** Mix between dry (0) and wet (1); the dry signal is delayed to match (default 1) */
+    spectralDryWet: number;
+    /** Resynthesis: 0 = normal, 1 = robot (monotone), 2 = whisper (random phases) */
+    spectralMode: number;
+    /** Keep the spectral envelope in place while shifting (avoids chipmunk voices) */
+    formantPreserve: boolean;
+    /**
+     * Linear frequency shift in Hz after the semitone shift, for inharmonic
+     * (Bode-style) tones. Components shifted below 0Hz are dropped.
+     */
+    frequencyShiftHz: number;
 }
 
 // ============================================================================
@@ -92,13 +119,50 @@ export type WasmDspEventType =
     | 'initialized'
     | 'error'
     | 'granular-source-loaded'
-    | 'ir-loaded';
+    | 'ir-loaded'
+    | 'memory-report'
+    | 'gain-reduction'
+    | 'latency'
+    | 'spectral-slots';
+
+/** Region capacities and usage of the WASM engine (see dsp_memory_report) */
+export interface WasmMemoryReport {
+    /** Byte offset of the granular source region */
+    granularSourceOffset: number;
+    /** Granular source region capacity in samples */
+    granularSourceCapacity: number;
+    /** Samples of the loaded granular source */
+    granularSourceUsed: number;
+    /** Byte offset of the IR region */
+    irOffset: number;
+    /** IR region capacity in samples */
+    irCapacity: number;
+    /** Samples of the loaded IR */
+    irUsed: number;
+    /** End of the engine's memory layout in bytes */
+    layoutEnd: number;
+    /** Bytes of the engine buffers allocated so far */
+    highWaterOffset: number;
+    /** 64KB pages taken by the engine buffers */
+    requiredPages: number;
+    /** 64KB pages currently allocated */
+    currentPages: number;
+}
 
 export interface WasmDspEvent {
     type: WasmDspEventType;
     error?: string;
     length?: number;
     channels?: number;
+    /** Source or IR was cut to fit its memory region */
+    truncated?: boolean;
+    report?: WasmMemoryReport;
+    /** Output limiter gain reduction of the last block in dB (0 = none) */
+    gainReductionDb?: number;
+    /** Processing latency of the active effect and limiter in samples */
+    latencySamples?: number;
+    /** Whether each spectral freeze slot holds a capture */
+    capturedSlots?: boolean[];
 }
 
 export type WasmDspEventHandler = (event: WasmDspEvent) => void;
@@ -149,7 +213,7 @@ export class WasmDspNode {
             
             // Create the AudioWorkletNode
             this.node = new AudioWorkletNode(this.context, 'wasm-dsp-processor', {
-                numberOfInputs: 1,
+                numberOfInputs: 2, // Audio in, ducking sidechain
                 numberOfOutputs: 1,
                 outputChannelCount: [2], // Stereo output
             });
@@ -224,6 +288,16 @@ export class WasmDspNode {
         this.sendMessage('set-effect', { effect });
     }
     
+    /**
+     * Set how long switching effects crossfades, in processed blocks
+     * (0-64, 0 = cut). Both effects run during the crossfade.
+     * 
+     * @param blocks - Crossfade length (default 8, about 21ms at 48kHz)
+     */
+    setEffectSwitchBlocks(blocks = 8): void {
+        this.sendMessage('set-effect-switch-blocks', { blocks });
+    }
+    
     /**
      * Get the current effect type.
      */
@@ -231,6 +305,18 @@ export class WasmDspNode {
         return this.currentEffect;
     }
     
+    /**
+     * Clear effect state (reverb tails, grains, frozen spectra), e.g. when
+     * the transport stops. Each effect fades out over its next block before
+     * it's cleared, so this is safe during playback. Loaded sources, IRs
+     * and parameters are kept.
+     * 
+     * @param target - Effect to clear (default all)
+     */
+    reset(target: 'granular' | 'spectral' | 'convolution' | 'all' = 'all'): void {
+        this.sendMessage(`reset-${target}`);
+    }
+    
     // ========================================================================
     // PARAMETER CONTROL
     // ========================================================================
@@ -250,6 +336,56 @@ export class WasmDspNode {
         this.sendMessage('set-params', { params });
     }
     
+    /**
+     * Connect a ducking sidechain (e.g. drums) to the node's second input.
+     * 
+     * @param source - Node whose (left channel) level ducks the reverb
+     */
+    connectSidechain(source: AudioNode): void {
+        source.connect(this.getNode(), 0, 1);
+    }
+    
+    /**
+     * Duck the convolution wet signal by the sidechain level. Has no
+     * effect until a sidechain is connected (see `connectSidechain`).
+     * 
+     * @param amount - Ducking depth (0-1, 0 = off)
+     * @param attackMs - Envelope attack time (0.1-5000ms, default 10)
+     * @param releaseMs - Envelope release time (0.1-5000ms, default 250)
+     * @param detector - 'peak' follows transients, 'rms' loudness
+     */
+    setDucking(amount: number, attackMs = 10, releaseMs = 250, detector: 'peak' | 'rms' = 'peak'): void {
+        this.sendMessage('set-ducking', { amount, attackMs, releaseMs, detector: detector === 'rms' ? 1 : 0 });
+    }
+    
+    /**
+     * Compress the convolution wet signal to even out its level.
+     * 
+     * @param thresholdDb - Level above which the wet signal is compressed (-60-0dBFS)
+     * @param ratio - Compression ratio (1-20, 1 = bypass)
+     * @param attackMs - Attack time (1-500ms, default 10)
+     * @param releaseMs - Release time (10-5000ms, default 200)
+     * @param makeupDb - Gain after compression (0-24dB, default 0)
+     * @param detector - 'peak' follows transients, 'rms' loudness
+     */
+    setConvolutionCompressor(
+        thresholdDb: number,
+        ratio: number,
+        attackMs = 10,
+        releaseMs = 200,
+        makeupDb = 0,
+        detector: 'peak' | 'rms' = 'rms'
+    ): void {
+        this.sendMessage('set-convolution-compressor', {
+            thresholdDb,
+            ratio,
+            attackMs,
+            releaseMs,
+            makeupDb,
+            detector: detector === 'rms' ? 1 : 0,
+        });
+    }
+    
     /**
      * Update spectral effect parameters.
      */
@@ -257,6 +393,247 @@ export class WasmDspNode {
         this.sendMessage('set-params', { params });
     }
     
+    /**
+     * Set the FFT size and overlap of the spectral effects. Smaller sizes
+     * keep transients tight, larger ones resolve low frequencies better;
+     * the latency is size - 1. Clears frozen spectra and freeze slots.
+     * 
+     * @param size - FFT size in samples (256-8192, power of two, default 2048)
+     * @param overlap - Frames overlapping each sample (4 or 8, default 4)
+     */
+    setSpectralFft(size = 2048, overlap = 4): void {
+        this.sendMessage('set-spectral-fft', { size, overlap });
+    }
+    
+    /**
+     * Set the spectral freeze effect's input gate, which attenuates quiet
+     * bins before they are frozen (e.g. the noise floor of a field
+     * recording).
+     * 
+     * @param thresholdDb - Bin level below which bins are gated, in dBFS
+     *   (-Infinity = open, the default)
+     * @param reductionDb - Attenuation of gated bins (0-120dB)
+     * @param releaseMs - Time for a gated bin to close (1-5000ms, default 100)
+     */
+    setSpectralGate(thresholdDb: number, reductionDb = 20, releaseMs = 100): void {
+        this.sendMessage('set-spectral-gate', { thresholdDb, reductionDb, releaseMs });
+    }
+    
+    /**
+     * Set how the spectral freeze engages and moves.
+     * 
+     * @param fadeFrames - Frames the freeze crossfades in over after each
+     *   capture (0-64, 0 = snap, default 4)
+     * @param phaseDrift - Share of the live phase frozen bins keep (0-1,
+     *   0 = fully static, default 0.1)
+     */
+    setSpectralFreeze(fadeFrames = 4, phaseDrift = 0.1): void {
+        this.sendMessage('set-spectral-freeze', { fadeFrames, phaseDrift });
+    }
+    
+    /**
+     * Let the frozen spectrum fade and wander.
+     * 
+     * @param decayDbPerSecond - Fade of the frozen spectrum from each
+     *   capture (0-120dB per second, 0 = held, default 0)
+     * @param drift - Random walk of the frozen bins for subtle motion (0-1,
+     *   0 = static, default 0)
+     */
+    setSpectralFreezeMotion(decayDbPerSecond = 0, drift = 0): void {
+        this.sendMessage('set-spectral-freeze-motion', { decayDbPerSecond, drift });
+    }
+    
+    /**
+     * Tilt the freeze/shift output spectrum about a pivot frequency.
+     * 
+     * @param dbPerOctave - Tilt (-12 to +12dB per octave, positive = brighter, 0 = off)
+     * @param pivotHz - Frequency the tilt leaves unchanged (default 1000)
+     */
+    setSpectralTilt(dbPerOctave: number, pivotHz = 1000): void {
+        this.sendMessage('set-spectral-tilt', { dbPerOctave, pivotHz });
+    }
+    
+    /**
+     * Set the low and high shelves of the freeze/shift output spectrum.
+     * Each shelf has half its gain at its corner frequency.
+     * 
+     * @param lowDb - Low shelf gain (±24dB, 0 = off)
+     * @param highDb - High shelf gain (±24dB, 0 = off)
+     * @param lowHz - Low shelf corner (default 200)
+     * @param highHz - High shelf corner (default 4000)
+     */
+    setSpectralShelves(lowDb: number, highDb: number, lowHz = 200, highHz = 4000): void {
+        this.sendMessage('set-spectral-shelves', { lowHz, lowDb, highHz, highDb });
+    }
+    
+    /**
+     * Lock the freeze/shift phases around spectral peaks (phase-locked
+     * vocoder) to keep shifted transients tight. Off by default.
+     */
+    setSpectralPhaseLocking(enabled: boolean): void {
+        this.sendMessage('set-spectral-phase-locking', { enabled });
+    }
+    
+    /**
+     * Feed the freeze/shift output back into its input (shimmer): each
+     * trip is shifted again, so with +12 semitones the tail climbs in
+     * octaves. Off (0) by default.
+     * @param amount - Feedback gain (0-0.9)
+     */
+    setSpectralFeedback(amount: number): void {
+        this.sendMessage('set-spectral-feedback', { amount });
+    }
+    
+    /**
+     * Set a harmonizer voice of the freeze/shift: a copy of the spectrum
+     * shifted by its own interval (e.g. +3, +7, +12 for a minor chord).
+     * Voices sound once enabled with setSpectralVoiceCount.
+     * @param index - Voice (0-3)
+     * @param semitones - Interval (-24 to +24)
+     * @param gain - Linear gain (0-1, default 1)
+     */
+    setSpectralVoice(index: number, semitones: number, gain = 1): void {
+        this.sendMessage('set-spectral-voice', { index, semitones, gain });
+    }
+    
+    /**
+     * Set how many harmonizer voices sound (0-4, 0 = off, the default).
+     * Each voice adds about a quarter of the freeze/shift's CPU cost.
+     */
+    setSpectralVoiceCount(count: number): void {
+        this.sendMessage('set-spectral-voice-count', { count });
+    }
+    
+    /**
+     * Carve the freeze/shift spectrum with a comb: bands around the
+     * harmonics of the fundamental pass, the bins between them are
+     * attenuated. Applies after the freeze, so a frozen pad can be carved
+     * and restored.
+     * @param fundamentalHz - Fundamental whose harmonics pass (20Hz-Nyquist)
+     * @param widthBins - Width of each pass band at half gain (0.5-64 bins, default 1)
+     * @param depth - Attenuation between the bands (0-1, 0 = off, default 1)
+     */
+    setSpectralComb(fundamentalHz: number, widthBins = 1, depth = 1): void {
+        this.sendMessage('set-spectral-comb', { fundamentalHz, widthBins, depth });
+    }
+    
+    /**
+     * Load a per-bin gain mask for the freeze/shift spectrum (0 = bin
+     * removed, 1 = passed; one gain per bin from DC up, stretched when
+     * the count doesn't match the FFT size). Multiplies the comb.
+     * @param gains - Mask gains (up to 4097; empty clears the mask)
+     */
+    loadSpectralMask(gains: Float32Array): void {
+        this.sendMessage('load-spectral-mask', { gains });
+    }
+    
+    /**
+     * Replace the frozen spectrum with the next analysis frame while
+     * frozen (auto-capture; slots are captured with captureSpectrum).
+     */
+    recaptureSpectrum(): void {
+        this.sendMessage('spectral-recapture');
+    }
+    
+    /**
+     * Capture the spectrum into a freeze slot (0-3), replacing its previous
+     * capture. Takes effect on the spectral effect's next analysis frame.
+     */
+    captureSpectrum(slot: number): void {
+        this.sendMessage('spectral-capture', { slot });
+    }
+    
+    /**
+     * Empty a freeze slot (0-3).
+     */
+    releaseSpectrum(slot: number): void {
+        this.sendMessage('spectral-release', { slot });
+    }
+    
+    /**
+     * Request which spectral freeze slots hold a capture.
+     * The result arrives as a 'spectral-slots' event.
+     */
+    requestSpectralSlots(): void {
+        this.sendMessage('spectral-slots');
+    }
+    
+    /**
+     * Select how IRs are normalized on load, so switching IRs keeps the
+     * wet level consistent. A loaded IR is rebuilt with the new mode.
+     * 
+     * @param mode - 0 = off, 1 = unit energy (equal wet loudness), 2 = peak
+     *   frequency response at 0dB, 3 = peak sample at ±1
+     */
+    setIrNormalization(mode: number): void {
+        this.sendMessage('set-ir-normalization', { mode });
+    }
+    
+    /**
+     * Shorten the loaded IR with a decay fade and/or a hard trim.
+     * 
+     * @param amount - Decay fade (0-1, 1 = -60dB at the IR end)
+     * @param trimSeconds - Length to cut the IR to (0 = no trim)
+     */
+    setIrDecay(amount: number, trimSeconds = 0): void {
+        this.sendMessage('set-ir-decay', { amount, trimSeconds });
+    }
+    
+    /**
+     * Filter the loaded IRs with a low cut and a high cut (applied once,
+     * not per block).
+     * 
+     * @param lowCutHz - High-pass cutoff (20-500Hz, 20 = off)
+     * @param highCutHz - Low-pass cutoff (1k-20kHz, 20k = off)
+     */
+    setIrFilters(lowCutHz = 20, highCutHz = 20000): void {
+        this.sendMessage('set-ir-filters', { lowCutHz, highCutHz });
+    }
+    
+    /**
+     * Set the reverb's input block size. A size that divides the render
+     * quantum (128) adds no latency; larger sizes cost less CPU. Clears the
+     * reverb tail, so set it before playback.
+     * 
+     * @param size - Block size in samples (64-1024, power of two, default 256)
+     */
+    setConvolutionBlock(size = 256): void {
+        this.sendMessage('set-convolution-block', { size });
+    }
+    
+    /**
+     * Enable or disable the DC blocker on granular and convolution output.
+     */
+    setDcBlocker(enabled: boolean): void {
+        this.sendMessage('set-dc-blocker', { enabled });
+    }
+    
+    /**
+     * Configure the output limiter (adds 3ms of latency while enabled).
+     * 
+     * @param thresholdDb - Output ceiling in dBFS (-24 to 0)
+     */
+    setLimiter(thresholdDb: number, enabled: boolean): void {
+        this.sendMessage('set-limiter', { thresholdDb, enabled });
+    }
+    
+    /**
+     * Request the output limiter's gain reduction for metering.
+     * The result arrives as a 'gain-reduction' event.
+     */
+    requestGainReduction(): void {
+        this.sendMessage('gain-reduction');
+    }
+    
+    /**
+     * Request the processing latency of the active effect (including the
+     * limiter) for latency compensation.
+     * The result arrives as a 'latency' event.
+     */
+    requestLatency(): void {
+        this.sendMessage('latency');
+    }
+    
     // ========================================================================
     // AUDIO DATA LOADING
     // ========================================================================
@@ -277,9 +654,11 @@ export class WasmDspNode {
         const samples = this.audioBufferToInterleaved(audioBuffer);
         
         // Transferable would be ideal but Float32Array.buffer may be shared
+        // Source rate lets the engine resample buffers recorded at another rate
         this.sendMessage('load-granular-source', {
             samples,
             channels: audioBuffer.numberOfChannels,
+            sampleRate: audioBuffer.sampleRate,
         });
     }
     
@@ -287,20 +666,53 @@ export class WasmDspNode {
      * Load audio buffer as impulse response for convolution.
      * 
      * @param audioBuffer - The AudioBuffer to use as IR
+     * @param slot - IR slot (0 or 1); `irBlend` crossfades from slot 0 to 1
      */
-    async loadIR(audioBuffer: AudioBuffer): Promise<void> {
+    async loadIR(audioBuffer: AudioBuffer, slot: 0 | 1 = 0): Promise<void> {
         if (!this.isInitialized) {
             throw new Error('WasmDspNode not initialized');
         }
         
         const samples = this.audioBufferToInterleaved(audioBuffer);
         
+        // Source rate lets the engine resample IRs recorded at another rate
         this.sendMessage('load-ir', {
             samples,
             channels: audioBuffer.numberOfChannels,
+            slot,
+            sampleRate: audioBuffer.sampleRate,
         });
     }
     
+    /**
+     * Load a long impulse response without an audio dropout.
+     * 
+     * The worklet writes it into WASM memory a chunk per render quantum and
+     * the current IR keeps playing until it is complete ('ir-loaded' event).
+     * 
+     * @param audioBuffer - The AudioBuffer to use as IR
+     */
+    async streamIR(audioBuffer: AudioBuffer): Promise<void> {
+        if (!this.isInitialized) {
+            throw new Error('WasmDspNode not initialized');
+        }
+        
+        const samples = this.audioBufferToInterleaved(audioBuffer);
+        
+        this.sendMessage('stream-ir', {
+            samples,
+            channels: audioBuffer.numberOfChannels,
+        });
+    }
+    
+    /**
+     * Request a memory report from the worklet.
+     * The result arrives as a 'memory-report' event.
+     */
+    requestMemoryReport(): void {
+        this.sendMessage('memory-report');
+    }
+    
     /**
      * Convert AudioBuffer to interleaved Float32Array.
      * 
@@ -127,6 +127,36 @@ fn bench_fft(c: &mut Criterion) {
                 })
             },
         );
+        
+        // Per-block cost when plans are looked up every block (old
+        // convolution/spectral behavior) vs. plans and scratch cached in state
+        group.bench_with_input(
+            BenchmarkId::new("roundtrip_replan", size),
+            &size,
+            |b, &size| {
+                b.iter(|| {
+                    let fft = planner.plan_fft_forward(size);
+                    let ifft = planner.plan_fft_inverse(size);
+                    fft.process(black_box(&mut buffer));
+                    ifft.process(black_box(&mut buffer));
+                })
+            },
+        );
+        
+        let mut scratch = vec![
+            Complex::new(0.0, 0.0);
+            fft.get_inplace_scratch_len().max(ifft.get_inplace_scratch_len())
+        ];
+        group.bench_with_input(
+            BenchmarkId::new("roundtrip_cached_scratch", size),
+            &size,
+            |b, _| {
+                b.iter(|| {
+                    fft.process_with_scratch(black_box(&mut buffer), &mut scratch);
+                    ifft.process_with_scratch(black_box(&mut buffer), &mut scratch);
+                })
+            },
+        );
     }
     
     group.finish();
@@ -260,12 +290,25 @@ fn bench_granular_simulation(c: &mut Criterion) {
         grain.rate = 1.0 + (i as f32 - 25.0) * 0.02;
     }
     
-    for buffer_size in [128, 256] {
+    // Raised cosine computed per sample vs looked up like granular.rs
+    // (simd_utils::envelope_lookup over a 1024-entry table)
+    const ENVELOPE_TABLE_SIZE: usize = 1024;
+    let envelope_table: Vec<f32> = (0..ENVELOPE_TABLE_SIZE)
+        .map(|i| 0.5 - 0.5 * (i as f32 / ENVELOPE_TABLE_SIZE as f32 * std::f32::consts::PI * 2.0).cos())
+        .collect();
+    let cos_envelope = |phase: f32| 0.5 - 0.5 * (phase * std::f32::consts::PI * 2.0).cos();
+    let table_envelope = |phase: f32| {
+        envelope_table[(phase.clamp(0.0, 0.9999) * ENVELOPE_TABLE_SIZE as f32) as usize]
+    };
+    let envelopes: [(&str, &dyn Fn(f32) -> f32); 2] =
+        [("50_grains", &cos_envelope), ("50_grains_table", &table_envelope)];
+    
+    for ((name, envelope), buffer_size) in envelopes.iter().flat_map(|e| [128, 256].map(|size| (e, size))) {
         let mut output_l = vec![0.0f32; buffer_size];
         let mut output_r = vec![0.0f32; buffer_size];
         
         group.bench_with_input(
-            BenchmarkId::new("50_grains", buffer_size),
+            BenchmarkId::new(*name, buffer_size),
             &buffer_size,
             |b, &size| {
                 b.iter(|| {
@@ -289,7 +332,7 @@ fn bench_granular_simulation(c: &mut Criterion) {
                             };
                             
                             // Envelope (raised cosine)
-                            let env = 0.5 - 0.5 * (grain.phase * std::f32::consts::PI * 2.0).cos();
+                            let env = envelope(grain.phase);
                             let out = sample * env * grain.amp;
                             
                             output_l[sample_idx] += out * 0.7;
@@ -298,13 +341,145 @@ fn bench_granular_simulation(c: &mut Criterion) {
                             // Advance
                             grain.pos += grain.rate / SOURCE_LEN as f32;
                             grain.phase += 1.0 / 256.0; // grain size
+                            // Restart the envelope like a respawned grain
+                            if grain.phase >= 1.0 {
+                                grain.phase -= 1.0;
+                            }
                         }
                     }
                 })
             },
         );
     }
-    
+
+    // High-density clouds: spawn loop included, pool sized like granular.rs
+    const HIGH_DENSITY_POOL: usize = 256;
+    const SAMPLE_RATE: f32 = 44100.0;
+
+    for (density, grain_size) in [(500.0f32, 4096.0f32), (20000.0, 64.0)] {
+        let mut pool: Vec<Grain> = (0..HIGH_DENSITY_POOL)
+            .map(|_| Grain {
+                active: false,
+                pos: 0.0,
+                phase: 0.0,
+                rate: 1.0,
+                amp: 1.0,
+            })
+            .collect();
+        let mut spawn_acc = 0.0f32;
+        let spawn_interval = SAMPLE_RATE / density;
+        let mut output_l = vec![0.0f32; 128];
+        let mut output_r = vec![0.0f32; 128];
+
+        group.bench_with_input(
+            BenchmarkId::new("high_density", format!("{}gps_{}", density, grain_size)),
+            &density,
+            |b, _| {
+                b.iter(|| {
+                    output_l.fill(0.0);
+                    output_r.fill(0.0);
+
+                    for sample_idx in 0..128 {
+                        // Spawn (possibly several grains per sample)
+                        spawn_acc += 1.0;
+                        while spawn_acc >= spawn_interval {
+                            spawn_acc -= spawn_interval;
+                            match pool.iter_mut().find(|g| !g.active) {
+                                Some(grain) => {
+                                    grain.active = true;
+                                    grain.pos = 0.5;
+                                    grain.phase = spawn_acc / grain_size;
+                                }
+                                None => {
+                                    spawn_acc %= spawn_interval;
+                                    break;
+                                }
+                            }
+                        }
+
+                        for grain in pool.iter_mut() {
+                            if !grain.active {
+                                continue;
+                            }
+
+                            let source_idx = (grain.pos * SOURCE_LEN as f32) as usize;
+                            let sample = if source_idx < SOURCE_LEN {
+                                source[source_idx]
+                            } else {
+                                0.0
+                            };
+
+                            let env = 0.5 - 0.5 * (grain.phase * std::f32::consts::PI * 2.0).cos();
+                            let out = sample * env * grain.amp;
+
+                            output_l[sample_idx] += out * 0.7;
+                            output_r[sample_idx] += out * 0.7;
+
+                            grain.pos += grain.rate / SOURCE_LEN as f32;
+                            grain.phase += 1.0 / grain_size;
+                            if grain.phase >= 1.0 {
+                                grain.active = false;
+                            }
+                        }
+                    }
+                })
+            },
+        );
+    }
+
+    // Source interpolation quality: 100 grains pitched up an octave
+    const INTERP_GRAINS: usize = 100;
+    let ramp: Vec<f32> = (0..SOURCE_LEN).map(|i| ((i as f32) * 0.01).sin()).collect();
+
+    for cubic in [false, true] {
+        let mut positions: Vec<f32> = (0..INTERP_GRAINS)
+            .map(|i| i as f32 * (SOURCE_LEN / 2 / INTERP_GRAINS) as f32 + 0.37)
+            .collect();
+        let mut output = vec![0.0f32; 128];
+
+        group.bench_with_input(
+            BenchmarkId::new("interpolation_100_grains", if cubic { "cubic" } else { "linear" }),
+            &cubic,
+            |b, &cubic| {
+                b.iter(|| {
+                    output.fill(0.0);
+
+                    for out in output.iter_mut() {
+                        for pos in positions.iter_mut() {
+                            let idx = *pos as usize;
+                            let frac = *pos - idx as f32;
+
+                            let sample = if cubic {
+                                // Catmull-Rom, edge taps clamped
+                                let y0 = ramp[idx.saturating_sub(1)];
+                                let y1 = ramp[idx];
+                                let y2 = ramp[idx + 1];
+                                let y3 = ramp[(idx + 2).min(SOURCE_LEN - 1)];
+                                let c1 = 0.5 * (y2 - y0);
+                                let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+                                let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+                                ((c3 * frac + c2) * frac + c1) * frac + y1
+                            } else {
+                                ramp[idx] + (ramp[idx + 1] - ramp[idx]) * frac
+                            };
+
+                            *out += sample;
+                            *pos += 2.0;
+                        }
+                    }
+
+                    // Keep grains inside the source between iterations
+                    for pos in positions.iter_mut() {
+                        if *pos >= (SOURCE_LEN - 128 * 2 - 4) as f32 {
+                            *pos -= (SOURCE_LEN / 2) as f32;
+                        }
+                    }
+                    black_box(&output);
+                })
+            },
+        );
+    }
+
     group.finish();
 }
 
@@ -356,6 +531,198 @@ fn bench_convolution_simulation(c: &mut Criterion) {
         })
     });
     
+    // 5s IR @ 48kHz, one channel: uniform 256-sample partitions vs the
+    // non-uniform segments of convolution.rs (FFT 512 head, then 4096 and
+    // 8192). One iteration is 16 input blocks, a full cycle of the largest
+    // segment, so the tail work is included at its real rate.
+    const IR_FRAMES: usize = 48000 * 5;
+    
+    struct Segment {
+        fft: std::sync::Arc<dyn rustfft::Fft<f32>>,
+        ifft: std::sync::Arc<dyn rustfft::Fft<f32>>,
+        partition_len: usize,
+        partitions: Vec<Vec<Complex<f32>>>,
+        fdl: Vec<Vec<Complex<f32>>>,
+        fdl_pos: usize,
+        accumulator: Vec<Complex<f32>>,
+        input: Vec<f32>,
+        gathered: usize,
+    }
+    
+    impl Segment {
+        fn new(planner: &mut FftPlanner<f32>, partition_len: usize, num_partitions: usize) -> Self {
+            let fft_size = 2 * partition_len;
+            Self {
+                fft: planner.plan_fft_forward(fft_size),
+                ifft: planner.plan_fft_inverse(fft_size),
+                partition_len,
+                partitions: vec![vec![Complex::new(0.1, 0.05); fft_size]; num_partitions],
+                fdl: vec![vec![Complex::new(0.0, 0.0); fft_size]; num_partitions],
+                fdl_pos: 0,
+                accumulator: vec![Complex::new(0.0, 0.0); fft_size],
+                input: vec![0.0; partition_len],
+                gathered: 0,
+            }
+        }
+        
+        fn push(&mut self, block: &[f32], overlap: &mut [f32], scratch: &mut [Complex<f32>]) {
+            self.input[self.gathered..self.gathered + block.len()].copy_from_slice(block);
+            self.gathered += block.len();
+            if self.gathered < self.partition_len {
+                return;
+            }
+            self.gathered = 0;
+            
+            let n = self.fdl.len();
+            let spectrum = &mut self.fdl[self.fdl_pos];
+            for (i, bin) in spectrum.iter_mut().enumerate() {
+                *bin = Complex::new(self.input.get(i).copied().unwrap_or(0.0), 0.0);
+            }
+            self.fft.process_with_scratch(spectrum, scratch);
+            
+            self.accumulator.fill(Complex::new(0.0, 0.0));
+            for (p, ir) in self.partitions.iter().enumerate() {
+                let x = &self.fdl[(self.fdl_pos + n - p) % n];
+                for ((acc, x), h) in self.accumulator.iter_mut().zip(x).zip(ir) {
+                    *acc += x * h;
+                }
+            }
+            self.ifft.process_with_scratch(&mut self.accumulator, scratch);
+            
+            let scale = 1.0 / self.accumulator.len() as f32;
+            for (out, y) in overlap.iter_mut().zip(&self.accumulator) {
+                *out += y.re * scale;
+            }
+            self.fdl_pos = (self.fdl_pos + 1) % n;
+        }
+    }
+    
+    // (partition length, partitions) per segment; head segments end at
+    // frames 1792 and 3840
+    let layouts = [
+        ("uniform_5s_ir", vec![(BLOCK_SIZE, IR_FRAMES.div_ceil(BLOCK_SIZE))]),
+        ("non_uniform_5s_ir", vec![(BLOCK_SIZE, 7), (2048, 1), (4096, (IR_FRAMES - 3840).div_ceil(4096))]),
+    ];
+    let block = vec![0.5f32; BLOCK_SIZE];
+    for (name, layout) in layouts {
+        let mut segments: Vec<Segment> = layout
+            .iter()
+            .map(|&(len, count)| Segment::new(&mut planner, len, count))
+            .collect();
+        let mut scratch = vec![Complex::new(0.0, 0.0); 8192];
+        let mut overlap = vec![0.0f32; 8192];
+        
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for _ in 0..16 {
+                    for segment in segments.iter_mut() {
+                        segment.push(black_box(&block), &mut overlap, &mut scratch);
+                    }
+                }
+                black_box(&overlap);
+            })
+        });
+    }
+    
+    group.finish();
+}
+
+// ============================================================================
+// CONVOLUTION MODULE BENCHMARK
+// ============================================================================
+
+fn bench_convolution_module(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convolution_module");
+    
+    // The real convolution engine driven through the exports. One iteration
+    // is 32 host blocks of 128 samples, a full cycle of the largest segment.
+    // Native builds compile the SIMD paths out, so "simd" and "scalar" only
+    // differ on a wasm32 simd128 build.
+    const BLOCK: usize = 128;
+    const SAMPLE_RATE: usize = 48000;
+    
+    for seconds in [2, 5] {
+        // A fresh engine per IR, so the load isn't crossfaded from the last one
+        let handle = dsp_core::dsp_init(SAMPLE_RATE as f32, BLOCK as u32);
+        assert!(handle >= 0, "no free engine");
+        let handle = handle as u32;
+        
+        let frames = SAMPLE_RATE * seconds;
+        let mut seed = 1u32;
+        unsafe {
+            let ir = std::slice::from_raw_parts_mut(dsp_core::dsp_get_ir_ptr(handle), frames);
+            for (i, x) in ir.iter_mut().enumerate() {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                *x = noise * (-(i as f32) / SAMPLE_RATE as f32).exp();
+            }
+            for channel in 0..2 {
+                let input = std::slice::from_raw_parts_mut(dsp_core::dsp_get_input_ptr(handle, channel), BLOCK);
+                for (i, x) in input.iter_mut().enumerate() {
+                    *x = (i as f32 * 0.05).sin() * 0.5;
+                }
+            }
+        }
+        dsp_core::dsp_load_ir(handle, std::ptr::null(), frames as u32, 1, 0.0);
+        
+        for (label, simd) in [("simd", 1), ("scalar", 0)] {
+            dsp_core::dsp_set_simd_enabled(simd);
+            group.bench_function(BenchmarkId::new(label, format!("{seconds}s_ir_32_blocks")), |b| {
+                b.iter(|| {
+                    for _ in 0..32 {
+                        dsp_core::dsp_process_convolution(handle, black_box(1.0), 0.0);
+                    }
+                })
+            });
+        }
+        
+        dsp_core::dsp_set_simd_enabled(1);
+        dsp_core::dsp_cleanup(handle);
+    }
+    
+    group.finish();
+}
+
+// ============================================================================
+// SPECTRAL MODULE BENCHMARK
+// ============================================================================
+
+fn bench_spectral_module(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spectral_module");
+    
+    // The freeze/shift driven through the exports with 0-4 harmonizer
+    // voices. One iteration is 16 host blocks of 128 samples, a full frame
+    // at the default FFT size (4 hops).
+    const BLOCK: usize = 128;
+    
+    let handle = dsp_core::dsp_init(48000.0, BLOCK as u32);
+    assert!(handle >= 0, "no free engine");
+    let handle = handle as u32;
+    unsafe {
+        for channel in 0..2 {
+            let input = std::slice::from_raw_parts_mut(dsp_core::dsp_get_input_ptr(handle, channel), BLOCK);
+            for (i, x) in input.iter_mut().enumerate() {
+                *x = (i as f32 * 0.05).sin() * 0.5;
+            }
+        }
+    }
+    for (index, semitones) in [3.0, 7.0, 12.0, -12.0].into_iter().enumerate() {
+        dsp_core::dsp_set_spectral_voice(handle, index as u32, semitones, 0.5);
+    }
+    
+    for voices in [0, 1, 2, 4] {
+        dsp_core::dsp_set_spectral_voice_count(handle, voices);
+        group.bench_function(BenchmarkId::new("voices", format!("{voices}_16_blocks")), |b| {
+            b.iter(|| {
+                for _ in 0..16 {
+                    dsp_core::dsp_process_spectral(handle, black_box(0.5), 0.0, -1.0, 1.0, 0, 0, 0.0);
+                }
+            })
+        });
+    }
+    
+    dsp_core::dsp_set_spectral_voice_count(handle, 0);
+    dsp_core::dsp_cleanup(handle);
     group.finish();
 }
 
@@ -420,6 +787,8 @@ criterion_group!(
     bench_delay,
     bench_granular_simulation,
     bench_convolution_simulation,
+    bench_convolution_module,
+    bench_spectral_module,
     bench_full_block_budget,
 );
 
@@ -0,0 +1,230 @@
+//! Effect Bypass
+//!
+//! Per-effect enable flags. A disabled effect isn't processed at all: the
+//! input is copied straight to the output, so switching an effect off in
+//! the UI saves its whole cost (FFTs included).
+//!
+//! # Transitions
+//! Toggling crossfades between the effect output and the dry input over
+//! BYPASS_FADE_MS, so it doesn't click. The effect keeps running until it
+//! has faded out, and resumes from the state it stopped in.
+
+use crate::memory;
+use crate::simd_utils;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Number of effect IDs with an enable flag
+pub const MAX_EFFECTS: usize = 12;
+
+/// Length of the bypass crossfade in milliseconds
+const BYPASS_FADE_MS: f32 = 10.0;
+
+// ============================================================================
+// BYPASS STATE
+// ============================================================================
+
+/// Enable flags and crossfade positions of one engine's effects
+struct Bypass {
+    enabled: [bool; MAX_EFFECTS],
+    /// Share of the effect in the output (0 = dry input, 1 = effect only),
+    /// moving towards the enable flag
+    mix: [f32; MAX_EFFECTS],
+}
+
+impl Bypass {
+    const fn new() -> Self {
+        Self {
+            enabled: [true; MAX_EFFECTS],
+            mix: [1.0; MAX_EFFECTS],
+        }
+    }
+}
+
+/// Bypass state of every engine in the pool
+static mut STATES: [Bypass; memory::MAX_ENGINES] =
+    [const { Bypass::new() }; memory::MAX_ENGINES];
+
+/// Bypass state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Bypass {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Copy the selected engine's input buffers to its output buffers
+pub fn passthrough() {
+    if !memory::is_initialized() {
+        return;
+    }
+    for channel in 0..2 {
+        unsafe {
+            // SAFETY: Single-threaded WASM context; input and output
+            // buffers don't overlap
+            simd_utils::copy_buffer(memory::input_slice(channel), memory::output_slice_mut(channel));
+        }
+    }
+}
+
+/// Run one block of an effect, honoring its enable flag
+/// 
+/// `process` renders the effect into the output buffers. It isn't called
+/// once the effect is disabled and faded out; the input is passed through
+/// instead. Unknown IDs always process.
+/// 
+/// # Arguments
+/// * `effect_id` - Effect ID (the `EFFECT_*` values in lib.rs)
+/// * `process` - Renders one block of the effect
+pub fn process(effect_id: u32, process: impl FnOnce()) {
+    let id = effect_id as usize;
+    if id >= MAX_EFFECTS || !memory::is_initialized() {
+        process();
+        return;
+    }
+    
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the bypass state
+        let st = state();
+        let target = if (*st).enabled[id] { 1.0 } else { 0.0 };
+        let mix = (*st).mix[id];
+        if mix == 0.0 && target == 0.0 {
+            passthrough();
+            return;
+        }
+        
+        process();
+        if mix == target {
+            return;
+        }
+        
+        // Crossfade the effect output with the (unmodified) input
+        let step = 1000.0 / (BYPASS_FADE_MS * memory::sample_rate());
+        for channel in 0..2 {
+            let input = memory::input_slice(channel);
+            let output = memory::output_slice_mut(channel);
+            let mut channel_mix = mix;
+            for (y, &x) in output.iter_mut().zip(input) {
+                channel_mix = if target > channel_mix {
+                    (channel_mix + step).min(target)
+                } else {
+                    (channel_mix - step).max(target)
+                };
+                *y = x + (*y - x) * channel_mix;
+            }
+            (*st).mix[id] = channel_mix;
+        }
+    }
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+/// Enable or disable an effect (unknown IDs are ignored)
+/// 
+/// The change crossfades in over the next blocks of that effect.
+pub fn set_enabled(effect_id: u32, enabled: bool) {
+    let id = effect_id as usize;
+    if id < MAX_EFFECTS {
+        unsafe {
+            // SAFETY: Single-threaded WASM context
+            (*state()).enabled[id] = enabled;
+        }
+    }
+}
+
+/// Whether an effect currently contributes to its output (enabled, or
+/// still fading out)
+pub fn is_active(effect_id: u32) -> bool {
+    let id = effect_id as usize;
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        id >= MAX_EFFECTS || (*st).enabled[id] || (*st).mix[id] > 0.0
+    }
+}
+
+/// Enable every effect again, without a fade
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = Bypass::new();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const BLOCK: usize = 128;
+    
+    /// Run one block of a fake effect that outputs a constant 1.0 from an
+    /// input of 0.5; returns the left output
+    fn run(effect_id: u32, calls: &mut usize) -> Vec<f32> {
+        unsafe {
+            for channel in 0..2 {
+                memory::output_slice_mut(channel).fill(0.0);
+                for i in 0..BLOCK {
+                    *memory::get_input_buffer(channel).add(i) = 0.5;
+                }
+            }
+        }
+        process(effect_id, || {
+            *calls += 1;
+            unsafe {
+                memory::output_slice_mut(0).fill(1.0);
+                memory::output_slice_mut(1).fill(1.0);
+            }
+        });
+        unsafe { memory::output_slice_mut(0).to_vec() }
+    }
+    
+    #[test]
+    fn test_disabled_effect_is_skipped_after_a_fade() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, BLOCK as u32);
+        set_enabled(1, true);
+        let mut calls = 0;
+        
+        assert!(run(1, &mut calls).iter().all(|&y| y == 1.0));
+        assert_eq!(calls, 1);
+        
+        // Disabling fades to the input over 480 samples (a few blocks),
+        // then stops calling the effect
+        set_enabled(1, false);
+        let mut output = Vec::new();
+        for _ in 0..6 {
+            output.extend(run(1, &mut calls));
+        }
+        assert_eq!(calls, 5);
+        assert!(!is_active(1));
+        let max_step = output.windows(2).fold(0.0f32, |max, pair| max.max((pair[1] - pair[0]).abs()));
+        assert!(max_step < 0.002, "bypass stepped by {max_step}");
+        assert!(output[480..].iter().all(|&y| y == 0.5));
+        
+        // Other effects are unaffected, and enabling fades back in
+        assert!(run(2, &mut calls).iter().all(|&y| y == 1.0));
+        set_enabled(1, true);
+        let output = run(1, &mut calls);
+        assert!(output[0] > 0.5 && output[0] < 0.51 && output[BLOCK - 1] < 1.0);
+        assert!(is_active(1));
+        
+        for _ in 0..4 {
+            run(1, &mut calls);
+        }
+    }
+}
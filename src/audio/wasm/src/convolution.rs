@@ -13,29 +13,682 @@
 //!
 //! # Partitioned Convolution
 //! For long IRs, the IR is split into partitions to reduce latency.
-//! Each partition is the same size as the input block.
+//!
+//! Partitioning is non-uniform: the head of the IR uses partitions the size
+//! of the input block (FFT 512 by default), which sets the latency. Later segments use
+//! larger partitions (FFT 4096, then 8192) that are computed once enough
+//! input blocks have been gathered, so a 5s IR needs ~60 large partitions
+//! instead of ~940 small ones per block. Each segment starts late enough in
+//! the IR that its output is ready by the time it is due.
+//!
+//! # Head Block Size
+//! The input block defaults to 256 samples. `set_block_size` picks another
+//! power of two (64-1024) to match the host: a block that divides the
+//! host buffer adds no latency, and a larger one costs fewer FFTs per
+//! sample. The segments are rebuilt for the new size (FFT plans, delay
+//! lines, buses) and the loaded IRs are partitioned again right away.
+//!
+//! # Early/Late Split
+//! Partitions starting before the split point are summed into an "early"
+//! wet bus and the rest into a "late" bus, each with its own gain. The
+//! split snaps to the nearest partition boundary, so a segment holding the
+//! split runs one extra IFFT; the others feed a single bus.
+//!
+//! # Decay and Trim
+//! `set_ir_decay` fades and trims a copy of the raw IR (still in the IR
+//! region) and rebuilds the partitions from it a few FFTs per block. The
+//! finished set is swapped in whole; the delay lines hold input spectra
+//! only, so they carry over unchanged. The delay lines keep the size of
+//! the untrimmed IR, so a trim can be relaxed again later.
+//!
+//! # IR Filters
+//! `set_ir_filters` runs the raw IR through a Butterworth high-pass
+//! (low cut) and low-pass (high cut) before the decay fade, and rebuilds
+//! the partitions like a decay change. Filtering the IR once takes the mud
+//! out of the wet signal without any runtime cost. The filters start from
+//! silence on every rebuild, so the result only depends on the settings.
+//!
+//! # IR Swaps
+//! Loading an IR while audio runs doesn't clear anything: the new
+//! partitions are computed a few FFTs per block (like a decay rebuild)
+//! while the old IR keeps playing. Once they are complete, both sets
+//! convolve the same delay lines into separate buses for IR_CROSSFADE_MS
+//! and the wet output crossfades from the old IR to the new one. The delay
+//! lines keep their recent spectra when the partition count changes. Only
+//! the new IR's response to the last partition of input before the swap
+//! is missing, which the fade-in covers.
+//!
+//! # Streamed Loading
+//! A long IR can arrive in chunks (`begin_ir_load`, `append_ir_chunk`,
+//! `finish_ir_load`), so JS never copies the whole IR in one audio callback.
+//! The previous IR keeps playing throughout; once the load finishes, the
+//! partitions are computed over the following blocks like an IR swap, even
+//! if no IR was loaded before.
+//!
+//! # IR Blend
+//! A second IR can be loaded into slot B (its own memory region). Its
+//! partitions convolve the same delay lines into separate buses, and
+//! `blend` crossfades the wet output from slot A (0) to slot B (1). By
+//! linearity this equals convolving with the interpolated IR, and the end
+//! points are exactly the output of either IR alone. Both slots run every
+//! block while slot B holds an IR, so unload it when not blending. Decay,
+//! trim and normalization apply to each slot as if it were loaded alone.
+//! A slot B load is prepared over the next blocks and swapped in whole,
+//! like a decay rebuild; move the blend away from it first for a smooth
+//! change.
+//!
+//! # Dry/Wet Mix
+//! The dry and wet signals are mixed with an equal-power law by default,
+//! which keeps a reverb (uncorrelated with its input) at a constant
+//! loudness across the mix. A linear law is available for hosts that
+//! automate the mix expecting linear gains.
+//!
+//! An optional compressor (see `dynamics`) levels the wet signal before
+//! the mix, evening out IRs of very different energy; it is bypassed at
+//! ratio 1 (the default).
+//!
+//! The wet signal is also scaled by the sidechain ducking gains (see
+//! `ducking`), which leave it untouched while no sidechain is loaded.
+//!
+//! # Stereo IRs
+//! A stereo IR keeps a partition set per channel: input L is convolved
+//! with IR L and input R with IR R. A mono IR has a single set that both
+//! channels share.
 //!
 //! # Note on Memory
 //! This module uses Vec for FFT buffers since rustfft requires heap allocation.
 //! The buffers are allocated once during load_ir and reused.
 
-use crate::memory;
+use crate::ducking;
+use crate::dynamics::Compressor;
+use crate::filters::{Biquad, OnePole};
+use crate::memory::{self, LOAD_OK, LOAD_REJECTED, LOAD_TRUNCATED};
+use crate::modulation::FollowerMode;
 use crate::simd_utils;
-use rustfft::{FftPlanner, num_complex::Complex};
+use crate::smoothing::{SmoothedParam, DEFAULT_SMOOTHING_MS};
+use crate::utils;
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use std::sync::Arc;
 use core::ptr::addr_of_mut;
 
 // ============================================================================
 // CONSTANTS
 // ============================================================================
 
-/// FFT size (must be power of 2, at least 2x block size for linear convolution)
-const FFT_SIZE: usize = 512;
+/// Default input block size: samples are gathered into blocks of this
+/// length before the head segment convolves them
+const DEFAULT_HEAD_BLOCK_SIZE: usize = 256;
+
+/// Head block size range (powers of two)
+const MIN_HEAD_BLOCK_SIZE: usize = 64;
+const MAX_HEAD_BLOCK_SIZE: usize = 1024;
+
+/// Number of IR segments
+const NUM_SEGMENTS: usize = 3;
+
+/// FFT size of each IR segment for a head block size, head first
+/// 
+/// Segments are uniformly partitioned with partitions of half the FFT size
+/// (at least 2x the partition for linear convolution). Each size must be a
+/// power-of-two multiple of the head FFT size.
+const fn segment_fft_sizes(head_block: usize) -> [usize; NUM_SEGMENTS] {
+    [head_block * 2, 4096, 8192]
+}
+
+// For every head block size, segments gather whole input blocks and grow
+// in partition length
+const _: () = {
+    let mut head_block = MIN_HEAD_BLOCK_SIZE;
+    while head_block <= MAX_HEAD_BLOCK_SIZE {
+        let sizes = segment_fft_sizes(head_block);
+        assert!(sizes[0] / 2 == head_block);
+        let mut k = 1;
+        while k < NUM_SEGMENTS {
+            assert!((sizes[k] / 2).is_multiple_of(head_block) && sizes[k] > sizes[k - 1]);
+            k += 1;
+        }
+        head_block *= 2;
+    }
+};
+
+/// Maximum IR length in frames per channel (affects memory usage)
+/// 
+/// A stereo IR holds two partition sets, so its spectra take twice the
+/// memory of a mono IR of the same length (about 3.9MB per set at the limit).
+const MAX_IR_FRAMES: usize = 48000 * 5; // 5 seconds @ 48kHz
+
+// A full-length stereo IR fits the IR region
+const _: () = assert!(MAX_IR_FRAMES * 2 <= memory::MAX_IR_SAMPLES);
+
+/// Maximum per-channel wet gain (+6dB)
+const MAX_WET_GAIN: f32 = 2.0;
+
+/// Maximum wet stereo width (side signal at +6dB)
+const MAX_WET_WIDTH: f32 = 2.0;
+
+/// Maximum wet predelay in milliseconds
+const MAX_PREDELAY_MS: f32 = 200.0;
+
+/// Predelay line length: the maximum predelay at the highest supported
+/// sample rate (192kHz), plus the current sample
+const PREDELAY_CAPACITY: usize = (MAX_PREDELAY_MS as usize) * 192 + 1;
+
+/// Crossfade time between the old and new tap when the predelay changes
+const PREDELAY_CROSSFADE_MS: f32 = 20.0;
+
+/// Maximum early/late split point in milliseconds
+const MAX_SPLIT_MS: f32 = 1000.0;
+
+/// Default early/late split point in milliseconds
+const DEFAULT_SPLIT_MS: f32 = 80.0;
+
+/// Attenuation the decay control reaches at the end of the IR at full
+/// decay (amount 1)
+const DECAY_RANGE_DB: f32 = 60.0;
+
+/// Fade-out at a trimmed IR's end, so the cut doesn't click
+const TRIM_FADE_MS: f32 = 5.0;
+
+/// IR low-cut range in Hz (the minimum or below turns it off)
+const MIN_IR_LOW_CUT_HZ: f32 = 20.0;
+const MAX_IR_LOW_CUT_HZ: f32 = 500.0;
+
+/// IR high-cut range in Hz (the maximum or above turns it off)
+const MIN_IR_HIGH_CUT_HZ: f32 = 1000.0;
+const MAX_IR_HIGH_CUT_HZ: f32 = 20000.0;
+
+/// FFT points computed per processed block while rebuilding partitions
+/// (at least the largest segment FFT)
+const REBUILD_BUDGET: usize = 16384;
+
+const _: () = assert!(REBUILD_BUDGET >= segment_fft_sizes(MAX_HEAD_BLOCK_SIZE)[NUM_SEGMENTS - 1]);
+
+/// Crossfade time from the playing IR to a newly loaded one
+const IR_CROSSFADE_MS: f32 = 50.0;
+
+/// Default level below which the reverb tail counts as silent (dBFS)
+const DEFAULT_TAIL_THRESHOLD_DB: f32 = -80.0;
+
+/// Maximum IR normalization gain (+60dB), so near-silent IRs aren't blown up
+const MAX_NORMALIZATION_GAIN: f32 = 1000.0;
+
+/// How `load_ir` scales an IR before computing its partitions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IrNormalization {
+    /// Use the IR as loaded
+    Off,
+    /// Scale to unit energy (sum of squares 1, averaged over channels).
+    /// Broadband input then gives a wet signal at the input's RMS level,
+    /// however long or dense the IR: wet gain compensation.
+    Energy,
+    /// Scale so the loudest frequency of the response is at 0dB
+    PeakResponse,
+    /// Scale so the largest sample is at ±1
+    Peak,
+}
+
+impl IrNormalization {
+    /// Mode from its export index (unknown values fall back to Off)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => IrNormalization::Energy,
+            2 => IrNormalization::PeakResponse,
+            3 => IrNormalization::Peak,
+            _ => IrNormalization::Off,
+        }
+    }
+}
+
+// ============================================================================
+// PREDELAY
+// ============================================================================
+
+/// Stereo delay line on the wet path
+/// 
+/// Delay changes crossfade from the old tap to the new one instead of
+/// jumping. With a delay of 0 and no fade running the output is the input.
+struct Predelay {
+    /// Ring buffers (PREDELAY_CAPACITY samples per channel, heap allocated once)
+    buffer_l: Vec<f32>,
+    buffer_r: Vec<f32>,
+    /// Slot the next sample is written to
+    write_pos: usize,
+    /// Current and previous tap delay in samples
+    delay: usize,
+    previous_delay: usize,
+    /// Crossfade progress from the previous to the current tap (1 = done)
+    fade: f32,
+}
+
+impl Predelay {
+    fn new() -> Self {
+        Self {
+            buffer_l: vec![0.0; PREDELAY_CAPACITY],
+            buffer_r: vec![0.0; PREDELAY_CAPACITY],
+            write_pos: 0,
+            delay: 0,
+            previous_delay: 0,
+            fade: 1.0,
+        }
+    }
+    
+    /// Move the tap, crossfading from wherever the output currently is
+    fn set_delay(&mut self, samples: usize) {
+        let samples = samples.min(PREDELAY_CAPACITY - 1);
+        if samples != self.delay {
+            self.previous_delay = self.delay;
+            self.delay = samples;
+            self.fade = 0.0;
+        }
+    }
+    
+    /// Delay one stereo sample
+    /// 
+    /// # Arguments
+    /// * `fade_step` - Crossfade progress per sample
+    #[inline]
+    fn process(&mut self, left: f32, right: f32, fade_step: f32) -> (f32, f32) {
+        self.buffer_l[self.write_pos] = left;
+        self.buffer_r[self.write_pos] = right;
+        let tap = |delay: usize| (self.write_pos + PREDELAY_CAPACITY - delay) % PREDELAY_CAPACITY;
+        
+        let current = tap(self.delay);
+        let mut out = (self.buffer_l[current], self.buffer_r[current]);
+        if self.fade < 1.0 {
+            let previous = tap(self.previous_delay);
+            let fade = self.fade;
+            out.0 = self.buffer_l[previous] * (1.0 - fade) + out.0 * fade;
+            out.1 = self.buffer_r[previous] * (1.0 - fade) + out.1 * fade;
+            self.fade = (fade + fade_step).min(1.0);
+        }
+        
+        self.write_pos = (self.write_pos + 1) % PREDELAY_CAPACITY;
+        out
+    }
+    
+    /// Silence the delay line (the delay setting is kept)
+    fn clear(&mut self) {
+        self.buffer_l.fill(0.0);
+        self.buffer_r.fill(0.0);
+        self.fade = 1.0;
+    }
+}
+
+// ============================================================================
+// IR SEGMENTS
+// ============================================================================
+
+/// First IR frame of each segment
+/// 
+/// A segment with partition length P computes its output once P input
+/// samples have arrived, i.e. P - head_block samples after the head
+/// segment would have; it can't cover any earlier part of the IR. Each
+/// segment ends where the next begins, rounded up to whole partitions.
+const fn segment_offsets(head_block: usize) -> [usize; NUM_SEGMENTS] {
+    let sizes = segment_fft_sizes(head_block);
+    let mut offsets = [0; NUM_SEGMENTS];
+    let mut k = 1;
+    while k < NUM_SEGMENTS {
+        let earliest = sizes[k] / 2 - head_block;
+        let previous_partition = sizes[k - 1] / 2;
+        let mut offset = offsets[k - 1];
+        while offset < earliest {
+            offset += previous_partition;
+        }
+        offsets[k] = offset;
+        k += 1;
+    }
+    offsets
+}
+
+/// Snap an IR frame to the nearest partition boundary
+/// 
+/// Boundaries are those of the segment the frame falls in; segment starts
+/// are boundaries of both neighbours.
+fn snap_to_partition(segments: &[Segment], frame: usize) -> usize {
+    let k = segments.iter().rposition(|s| s.ir_offset <= frame).unwrap_or(0);
+    let (ir_offset, partition_len) = (segments[k].ir_offset, segments[k].partition_len);
+    let partitions = (frame - ir_offset + partition_len / 2) / partition_len;
+    ir_offset + partitions * partition_len
+}
+
+/// Build the segments for a head block size, planning their FFTs
+fn build_segments(head_block: usize) -> Vec<Segment> {
+    let mut planner = FftPlanner::new();
+    let offsets = segment_offsets(head_block);
+    segment_fft_sizes(head_block)
+        .iter()
+        .enumerate()
+        .map(|(k, &fft_size)| {
+            // The last segment takes the rest
+            let ir_end = offsets.get(k + 1).copied().unwrap_or(MAX_IR_FRAMES);
+            Segment::new(fft_size, offsets[k], ir_end, head_block, &mut planner)
+        })
+        .collect()
+}
+
+/// One uniformly partitioned piece of the IR
+/// 
+/// Collects head blocks until a partition's worth of input has arrived,
+/// then convolves it with all of its partitions and overlap-adds the
+/// result into the output.
+struct Segment {
+    /// Partition length in frames (half the FFT size)
+    partition_len: usize,
+    /// First IR frame covered by the segment, and the first one past it
+    ir_offset: usize,
+    ir_end: usize,
+    /// Where the segment's output goes in the buses, relative to the head
+    /// segment's output for the same input block
+    output_offset: usize,
+    /// Forward/inverse FFT plans (planned once at initialization)
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    /// IR partitions in frequency domain (complex); the mono IR, or the
+    /// left channel of a stereo IR
+    ir_partitions_l: Vec<Vec<Complex<f32>>>,
+    /// Right channel IR partitions (empty for a mono IR)
+    ir_partitions_r: Vec<Vec<Complex<f32>>>,
+    /// Partitions of the IR being crossfaded out (empty outside a swap)
+    outgoing_partitions_l: Vec<Vec<Complex<f32>>>,
+    outgoing_partitions_r: Vec<Vec<Complex<f32>>>,
+    /// Partitions of the slot B IR (empty while the slot is unloaded)
+    ir_b_partitions_l: Vec<Vec<Complex<f32>>>,
+    ir_b_partitions_r: Vec<Vec<Complex<f32>>>,
+    /// Number of FDL slots: the partitions of the longer untrimmed IR (0 =
+    /// segment unused). Decay trims can leave fewer IR partitions.
+    num_partitions: usize,
+    /// Partitions summed into the early bus (the rest go to the late bus)
+    early_partitions: usize,
+    /// Input gathered so far (one partition per channel)
+    input_l: Vec<f32>,
+    input_r: Vec<f32>,
+    input_pos: usize,
+    /// Frequency-domain delay lines of past input spectra (one per channel,
+    /// independent of the IR channel count)
+    fdl_l: Vec<Vec<Complex<f32>>>,
+    fdl_r: Vec<Vec<Complex<f32>>>,
+    /// Peak of the input block in each FDL slot (both channels)
+    fdl_peaks: Vec<f32>,
+    /// Current FDL position
+    fdl_pos: usize,
+    /// Spectrum accumulators (per channel, so channels never share
+    /// transient data)
+    accumulator_l: Vec<Complex<f32>>,
+    accumulator_r: Vec<Complex<f32>>,
+}
+
+impl Segment {
+    fn new(fft_size: usize, ir_offset: usize, ir_end: usize, head_block: usize, planner: &mut FftPlanner<f32>) -> Self {
+        let partition_len = fft_size / 2;
+        Self {
+            partition_len,
+            ir_offset,
+            ir_end,
+            output_offset: ir_offset + head_block - partition_len,
+            fft: planner.plan_fft_forward(fft_size),
+            ifft: planner.plan_fft_inverse(fft_size),
+            ir_partitions_l: Vec::new(),
+            ir_partitions_r: Vec::new(),
+            outgoing_partitions_l: Vec::new(),
+            outgoing_partitions_r: Vec::new(),
+            ir_b_partitions_l: Vec::new(),
+            ir_b_partitions_r: Vec::new(),
+            num_partitions: 0,
+            early_partitions: 0,
+            input_l: vec![0.0; partition_len],
+            input_r: vec![0.0; partition_len],
+            input_pos: 0,
+            fdl_l: Vec::new(),
+            fdl_r: Vec::new(),
+            fdl_peaks: Vec::new(),
+            fdl_pos: 0,
+            accumulator_l: vec![Complex::new(0.0, 0.0); fft_size],
+            accumulator_r: vec![Complex::new(0.0, 0.0); fft_size],
+        }
+    }
+    
+    /// Silence the delay lines and drop gathered input
+    fn clear(&mut self) {
+        for fdl in self.fdl_l.iter_mut().chain(self.fdl_r.iter_mut()) {
+            fdl.fill(Complex::new(0.0, 0.0));
+        }
+        self.fdl_peaks.fill(0.0);
+        self.input_pos = 0;
+        self.fdl_pos = 0;
+    }
+    
+    /// Resize the delay lines to `slots` partitions, keeping the most
+    /// recent input spectra (missing history reads as silence)
+    fn resize_fdl(&mut self, slots: usize) {
+        if slots == self.num_partitions {
+            return;
+        }
+        let fft_size = 2 * self.partition_len;
+        let (old_slots, fdl_pos) = (self.num_partitions, self.fdl_pos);
+        for fdl in [&mut self.fdl_l, &mut self.fdl_r] {
+            let mut resized = vec![vec![Complex::new(0.0, 0.0); fft_size]; slots];
+            // The spectrum `age` blocks old sits `age` slots behind the
+            // write position, which restarts at 0
+            for age in 1..=old_slots.min(slots) {
+                let old = &mut fdl[(fdl_pos + old_slots - age) % old_slots];
+                core::mem::swap(&mut resized[slots - age], old);
+            }
+            *fdl = resized;
+        }
+        let mut peaks = vec![0.0; slots];
+        for age in 1..=old_slots.min(slots) {
+            peaks[slots - age] = self.fdl_peaks[(fdl_pos + old_slots - age) % old_slots];
+        }
+        self.fdl_peaks = peaks;
+        if old_slots == 0 {
+            // The segment was idle, so its gathered input is stale
+            self.input_pos = 0;
+        }
+        self.num_partitions = slots;
+        self.fdl_pos = 0;
+    }
+    
+    /// Peak of the input still in the delay lines
+    fn input_peak(&self) -> f32 {
+        self.fdl_peaks.iter().fold(0.0, |peak, &x| peak.max(x))
+    }
+    
+    /// FDL slots the segment needs for untrimmed IRs of up to `frames`
+    /// frames, and for every partition set still installed
+    fn required_slots(&self, frames: usize) -> usize {
+        [&self.ir_partitions_l, &self.outgoing_partitions_l, &self.ir_b_partitions_l]
+            .iter()
+            .map(|set| set.len())
+            .fold(self.partitions_for(frames), usize::max)
+    }
+    
+    /// Number of partitions the segment needs for an IR of `frames` frames
+    fn partitions_for(&self, frames: usize) -> usize {
+        self.ir_end.min(frames).saturating_sub(self.ir_offset).div_ceil(self.partition_len)
+    }
+}
+
+/// IR partitions of the right channel (a mono IR's set serves both)
+fn right_partitions<'a>(left: &'a [Vec<Complex<f32>>], right: &'a [Vec<Complex<f32>>]) -> &'a [Vec<Complex<f32>>] {
+    if right.is_empty() { left } else { right }
+}
+
+/// Partition sets being computed from a shaped IR
+/// 
+/// `step` computes partitions until its budget of FFT points runs out, so
+/// a rebuild can be spread over several blocks.
+struct Rebuild {
+    /// Shaped interleaved IR
+    samples: Vec<f32>,
+    channels: usize,
+    /// Normalization gain of the shaped IR
+    gain: f32,
+    /// Partition sets per segment (left or mono, right)
+    partitions: Vec<[Vec<Vec<Complex<f32>>>; 2]>,
+    /// Segment being computed
+    segment: usize,
+    /// Whether to crossfade from the playing partitions (a newly loaded
+    /// IR) instead of swapping them out (a reshaped one)
+    crossfade: bool,
+    /// Whether the partitions are for slot B (always swapped in whole)
+    slot_b: bool,
+}
+
+impl Rebuild {
+    fn new(samples: Vec<f32>, channels: usize, normalization: IrNormalization, crossfade: bool) -> Self {
+        let gain = normalization_gain_for(&samples, channels, normalization);
+        Self {
+            samples,
+            channels,
+            gain,
+            partitions: (0..NUM_SEGMENTS).map(|_| [Vec::new(), Vec::new()]).collect(),
+            segment: 0,
+            crossfade,
+            slot_b: false,
+        }
+    }
+    
+    /// Compute partitions for up to `budget` FFT points; true when done
+    fn step(&mut self, segments: &[Segment], scratch: &mut [Complex<f32>], mut budget: usize) -> bool {
+        let frames = self.samples.len() / self.channels;
+        while let Some(segment) = segments.get(self.segment) {
+            let count = segment.partitions_for(frames);
+            let fft_size = 2 * segment.partition_len;
+            for channel in 0..self.channels {
+                let set = &mut self.partitions[self.segment][channel];
+                while set.len() < count {
+                    if budget < fft_size {
+                        return false;
+                    }
+                    budget -= fft_size;
+                    let start = segment.ir_offset + set.len() * segment.partition_len;
+                    set.push(fft_partition(
+                        &self.samples,
+                        self.channels,
+                        channel,
+                        start,
+                        segment.partition_len,
+                        self.gain,
+                        &*segment.fft,
+                        scratch,
+                    ));
+                }
+            }
+            self.segment += 1;
+        }
+        true
+    }
+    
+    /// Swap the finished partition sets into the segments
+    /// 
+    /// With `crossfade` the replaced sets become the outgoing ones and the
+    /// crossfade starts; the delay lines grow to cover both IRs meanwhile.
+    fn install(self, state: &mut ConvolutionState) {
+        let frames = state.ir_frames.max(state.ir_b_frames);
+        for (segment, [left, right]) in state.segments.iter_mut().zip(self.partitions) {
+            if self.slot_b {
+                segment.ir_b_partitions_l = left;
+                segment.ir_b_partitions_r = right;
+            } else if self.crossfade {
+                segment.outgoing_partitions_l = core::mem::replace(&mut segment.ir_partitions_l, left);
+                segment.outgoing_partitions_r = core::mem::replace(&mut segment.ir_partitions_r, right);
+            } else {
+                segment.ir_partitions_l = left;
+                segment.ir_partitions_r = right;
+            }
+            segment.resize_fdl(segment.required_slots(frames));
+        }
+        if self.slot_b {
+            state.ir_b_loaded = state.ir_b_frames > 0;
+            if !state.ir_b_loaded {
+                state.buses_b.clear();
+            }
+            return;
+        }
+        state.normalization_gain = self.gain;
+        state.ir_loaded = state.ir_frames > 0;
+        
+        if self.crossfade {
+            // The old IR's pending output fades out from the outgoing buses
+            core::mem::swap(&mut state.buses, &mut state.outgoing_buses);
+            state.buses.clear();
+            state.crossfade_len = ((IR_CROSSFADE_MS * 0.001 * memory::sample_rate()) as usize).max(1);
+            state.crossfade_pos = 0;
+        }
+    }
+}
+
+/// Progress of a streamed IR load
+struct IrStream {
+    /// Frames announced by `begin_ir_load` (capped at MAX_IR_FRAMES)
+    frames: usize,
+    channels: usize,
+    /// Frames appended so far
+    written: usize,
+    /// Whether frames were dropped to fit MAX_IR_FRAMES
+    truncated: bool,
+}
+
+// ============================================================================
+// WET BUSES
+// ============================================================================
 
-/// Maximum IR length in samples (affects memory usage)
-const MAX_IR_SAMPLES: usize = 48000 * 5; // 5 seconds @ 48kHz
+/// Early and late overlap-add buffers of the wet signal
+/// 
+/// Each has room for the latest-ending segment output plus MAX_BUFFER_SIZE
+/// per channel, so a full host block can always be read and shifted out
+/// (and a block completing partway into one written after its start).
+struct WetBuses {
+    early_l: Vec<f32>,
+    early_r: Vec<f32>,
+    late_l: Vec<f32>,
+    late_r: Vec<f32>,
+}
 
-/// Maximum number of IR partitions
-const MAX_PARTITIONS: usize = MAX_IR_SAMPLES / (FFT_SIZE / 2);
+impl WetBuses {
+    fn new(len: usize) -> Self {
+        Self {
+            early_l: vec![0.0; len],
+            early_r: vec![0.0; len],
+            late_l: vec![0.0; len],
+            late_r: vec![0.0; len],
+        }
+    }
+    
+    /// Early and late buses of one channel from `offset` on
+    fn channel(&mut self, channel: usize, offset: usize) -> [&mut [f32]; 2] {
+        if channel == 0 {
+            [&mut self.early_l[offset..], &mut self.late_l[offset..]]
+        } else {
+            [&mut self.early_r[offset..], &mut self.late_r[offset..]]
+        }
+    }
+    
+    /// Stereo sample `i` of the weighted bus sum
+    #[inline]
+    fn sample(&self, i: usize, early_gain: f32, late_gain: f32) -> (f32, f32) {
+        (
+            self.early_l[i] * early_gain + self.late_l[i] * late_gain,
+            self.early_r[i] * early_gain + self.late_r[i] * late_gain,
+        )
+    }
+    
+    /// Drop the first `shift` samples (the block just read)
+    fn shift(&mut self, shift: usize) {
+        for bus in [&mut self.early_l, &mut self.early_r, &mut self.late_l, &mut self.late_r] {
+            let len = bus.len();
+            bus.copy_within(shift.., 0);
+            bus[len - shift..].fill(0.0);
+        }
+    }
+    
+    fn clear(&mut self) {
+        for bus in [&mut self.early_l, &mut self.early_r, &mut self.late_l, &mut self.late_r] {
+            bus.fill(0.0);
+        }
+    }
+}
 
 // ============================================================================
 // CONVOLUTION STATE
@@ -43,191 +696,718 @@ const MAX_PARTITIONS: usize = MAX_IR_SAMPLES / (FFT_SIZE / 2);
 
 /// FFT-based convolution reverb state
 struct ConvolutionState {
-    /// FFT planner (cached)
-    planner: FftPlanner<f32>,
-    /// IR partitions in frequency domain (complex)
-    ir_partitions: Vec<Vec<Complex<f32>>>,
-    /// Number of active IR partitions
-    num_partitions: usize,
-    /// Input buffer (accumulates samples until FFT_SIZE/2)
+    /// Input block size the segments were built for
+    head_block: usize,
+    /// IR segments, head first
+    segments: Vec<Segment>,
+    /// Scratch for in-place FFTs (sized for every plan)
+    fft_scratch: Vec<Complex<f32>>,
+    /// Input buffer (accumulates samples until `head_block`)
     input_buffer_l: Vec<f32>,
     input_buffer_r: Vec<f32>,
     /// Position in input buffer
     input_pos: usize,
-    /// Overlap-add buffer (FFT_SIZE samples per channel)
-    overlap_l: Vec<f32>,
-    overlap_r: Vec<f32>,
-    /// FFT scratch buffers
-    fft_input: Vec<Complex<f32>>,
-    fft_output: Vec<Complex<f32>>,
-    fft_temp: Vec<Complex<f32>>,
-    /// Frequency-domain accumulator for partition convolution
-    fdl_l: Vec<Vec<Complex<f32>>>,
-    fdl_r: Vec<Vec<Complex<f32>>>,
-    /// Current FDL position
-    fdl_pos: usize,
+    /// Wet output of the loaded IR
+    buses: WetBuses,
+    /// Wet output of the IR being crossfaded out
+    outgoing_buses: WetBuses,
+    /// Wet output of the slot B IR
+    buses_b: WetBuses,
+    /// Crossfade length and progress in samples (length 0 = no crossfade)
+    crossfade_len: usize,
+    crossfade_pos: usize,
     /// IR loaded flag
     ir_loaded: bool,
+    /// Whether blocks were processed since the last reset (a new IR then
+    /// crossfades in)
+    running: bool,
+    /// Frames and channels of the loaded IR (to re-normalize it)
+    ir_frames: usize,
+    ir_channels: usize,
+    /// Normalization applied on load, and the gain it computed
+    normalization: IrNormalization,
+    normalization_gain: f32,
+    /// Decay, trim and filters applied to the raw IRs
+    ir_shape: IrShape,
+    /// Partitions being rebuilt after a decay change or IR load
+    rebuild: Option<Rebuild>,
+    /// IR being streamed into the IR region (None outside a streamed load)
+    stream: Option<IrStream>,
+    /// Slot B: loaded flag, raw IR frames and channels, pending rebuild
+    ir_b_loaded: bool,
+    ir_b_frames: usize,
+    ir_b_channels: usize,
+    rebuild_b: Option<Rebuild>,
+    /// Blend from slot A (0) to slot B (1) reached at the end of the last
+    /// block
+    blend: f32,
+    /// Dry/wet mix, smoothed per sample once the first block has set it
+    dry_wet: SmoothedParam,
+    dry_wet_primed: bool,
+    /// Whether dry/wet is a linear crossfade instead of equal-power
+    linear_mix: bool,
+    /// Per-channel wet trims (applied on top of dry/wet)
+    wet_gain_l: f32,
+    wet_gain_r: f32,
+    /// Stereo width of the wet signal (0 = mono, 1 = unchanged)
+    wet_width: f32,
+    /// Wet signal compressor (bypassed at ratio 1)
+    wet_compressor: Compressor,
+    /// Peak of |L + R| over the last processed block
+    mono_sum_peak: f32,
+    /// Peak of the wet signal (before and after the predelay) over the
+    /// last processed block
+    tail_peak: f32,
+    /// Level below which the tail counts as silent (linear)
+    tail_threshold: f32,
+    /// Early/late split point setting in ms (snapped to a partition
+    /// boundary at the engine rate each block)
+    split_ms: f32,
+    /// Early and late bus gains
+    early_gain: f32,
+    late_gain: f32,
+    /// Wet path predelay
+    predelay: Predelay,
+    /// Predelay setting in ms (converted at the engine rate each block)
+    predelay_ms: f32,
+    /// Output DC blockers (used when enabled via `memory::set_dc_blocker`)
+    dc_blocker_l: OnePole,
+    dc_blocker_r: OnePole,
 }
 
-/// Global convolution state
-static mut STATE: Option<ConvolutionState> = None;
+/// Per-engine convolution state, created on first use
+static mut STATES: [Option<ConvolutionState>; memory::MAX_ENGINES] = [const { None }; memory::MAX_ENGINES];
 
 // ============================================================================
 // INITIALIZATION
 // ============================================================================
 
-/// Initialize convolution state (called once)
+/// Initialize the selected engine's convolution state (once per engine)
 fn ensure_state() -> &'static mut ConvolutionState {
     unsafe {
         // SAFETY: Single-threaded WASM context, using raw pointer for Rust 2024
-        let state_ptr = addr_of_mut!(STATE);
+        let state_ptr = addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]);
         if (*state_ptr).is_none() {
+            let segments = build_segments(DEFAULT_HEAD_BLOCK_SIZE);
+            let scratch_len = fft_scratch_len(&segments);
+            let overlap_len = overlap_len(&segments);
+            
             *state_ptr = Some(ConvolutionState {
-                planner: FftPlanner::new(),
-                ir_partitions: Vec::new(),
-                num_partitions: 0,
-                input_buffer_l: vec![0.0; FFT_SIZE / 2],
-                input_buffer_r: vec![0.0; FFT_SIZE / 2],
+                head_block: DEFAULT_HEAD_BLOCK_SIZE,
+                segments,
+                fft_scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+                input_buffer_l: vec![0.0; DEFAULT_HEAD_BLOCK_SIZE],
+                input_buffer_r: vec![0.0; DEFAULT_HEAD_BLOCK_SIZE],
                 input_pos: 0,
-                overlap_l: vec![0.0; FFT_SIZE],
-                overlap_r: vec![0.0; FFT_SIZE],
-                fft_input: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-                fft_output: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-                fft_temp: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-                fdl_l: Vec::new(),
-                fdl_r: Vec::new(),
-                fdl_pos: 0,
+                buses: WetBuses::new(overlap_len),
+                outgoing_buses: WetBuses::new(overlap_len),
+                buses_b: WetBuses::new(overlap_len),
+                crossfade_len: 0,
+                crossfade_pos: 0,
                 ir_loaded: false,
+                running: false,
+                ir_frames: 0,
+                ir_channels: 1,
+                normalization: IrNormalization::Off,
+                normalization_gain: 1.0,
+                ir_shape: IrShape::NONE,
+                rebuild: None,
+                stream: None,
+                ir_b_loaded: false,
+                ir_b_frames: 0,
+                ir_b_channels: 1,
+                rebuild_b: None,
+                blend: 0.0,
+                dry_wet: SmoothedParam::new(0.0),
+                dry_wet_primed: false,
+                linear_mix: false,
+                wet_gain_l: 1.0,
+                wet_gain_r: 1.0,
+                wet_width: 1.0,
+                wet_compressor: Compressor::new(),
+                mono_sum_peak: 0.0,
+                tail_peak: 0.0,
+                tail_threshold: utils::db_to_linear(DEFAULT_TAIL_THRESHOLD_DB),
+                split_ms: DEFAULT_SPLIT_MS,
+                early_gain: 1.0,
+                late_gain: 1.0,
+                predelay: Predelay::new(),
+                predelay_ms: 0.0,
+                dc_blocker_l: OnePole::new(),
+                dc_blocker_r: OnePole::new(),
             });
         }
         (*state_ptr).as_mut().unwrap()
     }
 }
 
+/// Scratch length for in-place FFTs with every plan of the segments
+fn fft_scratch_len(segments: &[Segment]) -> usize {
+    segments
+        .iter()
+        .map(|s| s.fft.get_inplace_scratch_len().max(s.ifft.get_inplace_scratch_len()))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Wet bus length for the segments: the latest-ending segment output plus
+/// a full host block
+fn overlap_len(segments: &[Segment]) -> usize {
+    segments
+        .iter()
+        .map(|s| s.output_offset + 2 * s.partition_len)
+        .max()
+        .unwrap_or(0)
+        + memory::MAX_BUFFER_SIZE
+}
+
+/// Set the input block size of the head segment
+/// 
+/// Rebuilds the segments for the new size and partitions the loaded IRs
+/// for them right away; the delay lines, buses and gathered input are
+/// cleared. Ignored while an IR is being streamed in.
+/// 
+/// # Arguments
+/// * `size` - Block size in samples (64-1024, rounded up to a power of two)
+/// 
+/// # Returns
+/// The block size in effect
+pub fn set_block_size(size: u32) -> u32 {
+    let state = ensure_state();
+    let head_block = (size as usize).clamp(MIN_HEAD_BLOCK_SIZE, MAX_HEAD_BLOCK_SIZE).next_power_of_two();
+    if head_block == state.head_block || state.stream.is_some() {
+        return state.head_block as u32;
+    }
+    
+    end_crossfade(state);
+    state.segments = build_segments(head_block);
+    state.fft_scratch = vec![Complex::new(0.0, 0.0); fft_scratch_len(&state.segments)];
+    state.input_buffer_l = vec![0.0; head_block];
+    state.input_buffer_r = vec![0.0; head_block];
+    state.input_pos = 0;
+    let overlap_len = overlap_len(&state.segments);
+    state.buses = WetBuses::new(overlap_len);
+    state.outgoing_buses = WetBuses::new(overlap_len);
+    state.buses_b = WetBuses::new(overlap_len);
+    state.head_block = head_block;
+    
+    // Partition both slots' IRs again (a pending load is built from its
+    // raw IR too), without a crossfade since the old output is gone
+    reshape_irs(state);
+    for mut rebuild in [state.rebuild.take(), state.rebuild_b.take()].into_iter().flatten() {
+        rebuild.crossfade = false;
+        rebuild.step(&state.segments, &mut state.fft_scratch, usize::MAX);
+        rebuild.install(state);
+    }
+    clear_delay_lines(state);
+    head_block as u32
+}
+
 // ============================================================================
 // IR LOADING
 // ============================================================================
 
 /// Load impulse response for convolution
 /// 
+/// An IR recorded at another sample rate is converted to the engine rate
+/// in place (once, at load), so the reverb keeps its timing. Converted
+/// output beyond MAX_IR_FRAMES is truncated.
+/// 
 /// # Arguments
 /// * `_ptr` - Pointer (not used, samples are at IR_OFFSET)
-/// * `length` - Number of sample frames
-/// * `channels` - Number of channels (1 or 2)
+/// * `length` - Number of sample frames (at `source_rate`)
+/// * `channels` - Number of channels (1 or 2); stereo IRs are interleaved
+///   and keep separate left/right responses
+/// * `source_rate` - Sample rate the IR was recorded at; 0 or the engine
+///   rate loads it unconverted
+/// 
+/// # Returns
+/// LOAD_OK, LOAD_TRUNCATED if the IR (before or after conversion) exceeds
+/// MAX_IR_FRAMES, or LOAD_REJECTED for an invalid channel count (the
+/// current IR is kept). A length of 0 unloads the IR.
 /// 
 /// # Note
 /// The actual samples are written to WASM memory by JavaScript at
 /// IR_OFFSET before calling this function.
-pub fn load_ir(_ptr: *const f32, length: u32, channels: u32) {
+pub fn load_ir(_ptr: *const f32, length: u32, channels: u32, source_rate: f32) -> u32 {
+    if !(1..=2).contains(&channels) {
+        return LOAD_REJECTED;
+    }
+    let (length, truncated) = convert_ir_rate(memory::get_ir_ptr(), length, channels as usize, source_rate);
+    let status = install_ir(length, channels);
+    if truncated { LOAD_TRUNCATED } else { status }
+}
+
+/// Build and install the IR at IR_OFFSET (already at the engine rate)
+fn install_ir(length: u32, channels: u32) -> u32 {
     let state = ensure_state();
+    // The region is being rewritten, so a streamed load is abandoned
+    state.stream = None;
     
+    let channels = channels as usize;
+    let requested = length as usize;
+    let length = requested.min(MAX_IR_FRAMES);
     let ir_samples = unsafe {
         std::slice::from_raw_parts(
-            memory::IR_OFFSET as *const f32,
-            (length * channels) as usize
+            memory::get_ir_ptr() as *const f32,
+            length * channels
         )
     };
     
-    let block_size = FFT_SIZE / 2;
-    let num_partitions = ((length as usize) + block_size - 1) / block_size;
-    let num_partitions = num_partitions.min(MAX_PARTITIONS);
-    
-    // Pre-compute FFT of each IR partition
-    state.ir_partitions.clear();
-    state.ir_partitions.reserve(num_partitions);
+    // While an IR is playing the new one is built over the next blocks
+    // and crossfaded in; otherwise it replaces the old one right away
+    let crossfade = state.ir_loaded && state.running && length > 0 && memory::is_ir_ready();
     
-    let fft = state.planner.plan_fft_forward(FFT_SIZE);
+    // Pre-compute the FFT of each partition of the shaped IR, one set per
+    // IR channel. Level normalization is folded into the partitions (no
+    // runtime cost).
+    let shaped = shape_ir(ir_samples, channels, state.ir_shape, memory::sample_rate());
+    let mut rebuild = Rebuild::new(shaped, channels, state.normalization, crossfade);
+    state.ir_frames = length;
+    state.ir_channels = channels;
+    unsafe {
+        memory::set_ir_len((length * channels) as u32);
+    }
     
-    for p in 0..num_partitions {
-        let start = p * block_size;
-        let mut partition = vec![Complex::new(0.0, 0.0); FFT_SIZE];
+    if crossfade {
+        state.rebuild = Some(rebuild);
+    } else {
+        // Delay lines are sized for the untrimmed IR
+        end_crossfade(state);
+        rebuild.step(&state.segments, &mut state.fft_scratch, usize::MAX);
+        rebuild.install(state);
+        state.rebuild = None;
         
-        // Copy IR samples to partition (zero-pad rest)
-        for i in 0..block_size {
-            let idx = start + i;
-            if idx < length as usize {
-                let sample = if channels == 2 {
-                    // Average stereo to mono for IR
-                    (ir_samples[idx * 2] + ir_samples[idx * 2 + 1]) * 0.5
-                } else {
-                    ir_samples[idx]
-                };
-                partition[i] = Complex::new(sample, 0.0);
-            }
+        // Clear the delay lines, buses and the old IR's tail in the
+        // predelay. A playing slot B IR shares the delay lines and the
+        // predelay, so only the buses are cleared then.
+        state.buses.clear();
+        if !(state.ir_b_loaded && state.running) {
+            clear_delay_lines(state);
         }
         
-        // FFT the partition
-        fft.process(&mut partition);
-        state.ir_partitions.push(partition);
+        // An empty IR leaves convolution bypassed (unless slot B holds one)
+        state.ir_loaded = length > 0;
     }
     
-    state.num_partitions = num_partitions;
-    
-    // Initialize frequency-domain delay lines
-    state.fdl_l.clear();
-    state.fdl_r.clear();
-    for _ in 0..num_partitions {
-        state.fdl_l.push(vec![Complex::new(0.0, 0.0); FFT_SIZE]);
-        state.fdl_r.push(vec![Complex::new(0.0, 0.0); FFT_SIZE]);
+    if length < requested { LOAD_TRUNCATED } else { LOAD_OK }
+}
+
+/// Start a streamed IR load (see "Streamed Loading")
+/// 
+/// Restarts a streamed load already in progress. The playing IR is kept
+/// until `finish_ir_load`.
+/// 
+/// # Arguments
+/// * `frames` - Total sample frames that will be appended
+/// * `channels` - Number of channels (1 or 2), interleaved
+/// 
+/// # Returns
+/// LOAD_OK, LOAD_TRUNCATED if `frames` exceeds MAX_IR_FRAMES (the rest
+/// will be dropped), or LOAD_REJECTED for an invalid channel count
+pub fn begin_ir_load(frames: u32, channels: u32) -> u32 {
+    if !(1..=2).contains(&channels) {
+        return LOAD_REJECTED;
+    }
+    let requested = frames as usize;
+    let frames = requested.min(MAX_IR_FRAMES);
+    ensure_state().stream = Some(IrStream {
+        frames,
+        channels: channels as usize,
+        written: 0,
+        truncated: frames < requested,
+    });
+    if frames < requested { LOAD_TRUNCATED } else { LOAD_OK }
+}
+
+/// Append a chunk to the streamed IR
+/// 
+/// # Arguments
+/// * `_ptr` - Pointer (not used, samples are in the IR region)
+/// * `frames` - Sample frames in the chunk
+/// 
+/// # Returns
+/// LOAD_OK, LOAD_TRUNCATED if the chunk goes past the announced length
+/// (the excess is dropped), or LOAD_REJECTED without a streamed load
+/// 
+/// # Note
+/// JavaScript writes the chunk to the IR region at the frames appended
+/// so far (sample offset `appended frames * channels`) before calling this.
+pub fn append_ir_chunk(_ptr: *const f32, frames: u32) -> u32 {
+    let Some(stream) = ensure_state().stream.as_mut() else {
+        return LOAD_REJECTED;
+    };
+    let accepted = (frames as usize).min(stream.frames - stream.written);
+    stream.written += accepted;
+    if accepted < frames as usize {
+        stream.truncated = true;
+        LOAD_TRUNCATED
+    } else {
+        LOAD_OK
+    }
+}
+
+/// Finish a streamed IR load
+/// 
+/// While audio is running the partitions are computed over the next
+/// blocks and then swapped in, crossfading from the playing IR if there
+/// is one; otherwise the IR is installed right away. Frames that were
+/// announced but never appended are left out.
+/// 
+/// # Returns
+/// LOAD_OK, LOAD_TRUNCATED if frames were dropped, or LOAD_REJECTED
+/// without a streamed load
+pub fn finish_ir_load() -> u32 {
+    let state = ensure_state();
+    let Some(stream) = state.stream.take() else {
+        return LOAD_REJECTED;
+    };
+    let status = if stream.truncated { LOAD_TRUNCATED } else { LOAD_OK };
+    let (length, channels) = (stream.written, stream.channels);
+    if !state.running || length == 0 {
+        install_ir(length as u32, channels as u32);
+        return status;
     }
-    state.fdl_pos = 0;
     
-    // Clear overlap buffers
-    state.overlap_l.fill(0.0);
-    state.overlap_r.fill(0.0);
-    state.input_pos = 0;
+    let ir_samples = unsafe {
+        std::slice::from_raw_parts(memory::get_ir_ptr() as *const f32, length * channels)
+    };
+    let shaped = shape_ir(ir_samples, channels, state.ir_shape, memory::sample_rate());
+    state.rebuild = Some(Rebuild::new(shaped, channels, state.normalization, state.ir_loaded));
+    state.ir_frames = length;
+    state.ir_channels = channels;
+    unsafe {
+        memory::set_ir_len((length * channels) as u32);
+    }
+    status
+}
+
+/// Load the slot B impulse response (see "IR Blend")
+/// 
+/// Same arguments, sample-rate conversion and status codes as `load_ir`.
+/// While audio is running the partitions are prepared over the next blocks
+/// and then swapped in.
+/// 
+/// # Note
+/// The samples are written to WASM memory by JavaScript at IR_B_OFFSET
+/// before calling this function.
+pub fn load_ir_b(length: u32, channels: u32, source_rate: f32) -> u32 {
+    if !(1..=2).contains(&channels) {
+        return LOAD_REJECTED;
+    }
+    let (length, truncated) = convert_ir_rate(memory::get_ir_b_ptr(), length, channels as usize, source_rate);
+    let status = install_ir_b(length, channels);
+    if truncated { LOAD_TRUNCATED } else { status }
+}
+
+/// Build and install the IR at IR_B_OFFSET (already at the engine rate)
+fn install_ir_b(length: u32, channels: u32) -> u32 {
+    let state = ensure_state();
     
-    state.ir_loaded = true;
+    let channels = channels as usize;
+    let requested = length as usize;
+    let length = requested.min(MAX_IR_FRAMES);
+    let ir_samples = unsafe {
+        std::slice::from_raw_parts(memory::get_ir_b_ptr() as *const f32, length * channels)
+    };
     
+    let shaped = shape_ir(ir_samples, channels, state.ir_shape, memory::sample_rate());
+    let mut rebuild = Rebuild {
+        slot_b: true,
+        ..Rebuild::new(shaped, channels, state.normalization, false)
+    };
+    state.ir_b_frames = length;
+    state.ir_b_channels = channels;
     unsafe {
-        memory::set_ir_len(length);
+        memory::set_ir_b_len((length * channels) as u32);
+    }
+    
+    if state.running {
+        state.rebuild_b = Some(rebuild);
+    } else {
+        rebuild.step(&state.segments, &mut state.fft_scratch, usize::MAX);
+        rebuild.install(state);
+        state.rebuild_b = None;
+        state.buses_b.clear();
     }
+    
+    if length < requested { LOAD_TRUNCATED } else { LOAD_OK }
 }
 
-// ============================================================================
-// PROCESSING
-// ============================================================================
+/// Convert an IR region written at `source_rate` to the engine rate in place
+/// 
+/// # Returns
+/// The IR length in frames after conversion, and whether the input or
+/// the converted IR had to be truncated. A source rate of 0 or the engine
+/// rate leaves the region and length as they are.
+fn convert_ir_rate(ptr: *mut f32, length: u32, channels: usize, source_rate: f32) -> (u32, bool) {
+    let ratio = memory::sample_rate() / source_rate.max(1.0);
+    if source_rate <= 0.0 || ratio == 1.0 {
+        return (length, false);
+    }
+    let requested = (length as usize).saturating_mul(channels);
+    let samples = requested.min(memory::MAX_IR_SAMPLES);
+    
+    // SAFETY: Single-threaded WASM context; the slot's region is not
+    // referenced elsewhere while loading
+    let original = unsafe { std::slice::from_raw_parts(ptr as *const f32, samples).to_vec() };
+    let region = unsafe { std::slice::from_raw_parts_mut(ptr, MAX_IR_FRAMES * channels) };
+    let resampler = utils::Resampler::new(ratio);
+    let frames = resampler.process(&original, region, channels);
+    let truncated = samples < requested || frames < resampler.output_frames(samples / channels);
+    (frames as u32, truncated)
+}
 
-/// Process convolution reverb
+/// Silence the delay lines and the predelay, and drop gathered input
+fn clear_delay_lines(state: &mut ConvolutionState) {
+    for segment in &mut state.segments {
+        segment.clear();
+    }
+    state.predelay.clear();
+    state.input_pos = 0;
+}
+
+/// FFT one partition of one channel of an interleaved IR
 /// 
 /// # Arguments
-/// * `dry_wet` - Mix between dry (0) and wet (1) signal
-pub fn process(dry_wet: f32) {
-    let state = ensure_state();
+/// * `ir_samples` - Interleaved IR samples
+/// * `channels` - Channel count of `ir_samples`
+/// * `channel` - Channel to take the partition from
+/// * `start` - IR frame the partition starts at
+/// * `partition_len` - Frames per partition (the FFT is twice this)
+/// * `gain` - Scale applied to the IR samples
+#[allow(clippy::too_many_arguments)]
+fn fft_partition(
+    ir_samples: &[f32],
+    channels: usize,
+    channel: usize,
+    start: usize,
+    partition_len: usize,
+    gain: f32,
+    fft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
+) -> Vec<Complex<f32>> {
+    let length = ir_samples.len() / channels;
+    let mut partition = vec![Complex::new(0.0, 0.0); 2 * partition_len];
     
-    if !state.ir_loaded || state.num_partitions == 0 {
-        // No IR loaded - pass through dry signal using SIMD
-        unsafe {
-            let input_l = memory::input_slice(0);
-            let input_r = memory::input_slice(1);
-            let output_l = memory::output_slice_mut(0);
-            let output_r = memory::output_slice_mut(1);
-            
-            simd_utils::copy_buffer(input_l, output_l);
-            simd_utils::copy_buffer(input_r, output_r);
+    // Copy IR samples to partition (zero-pad rest)
+    for (i, bin) in partition.iter_mut().take(partition_len).enumerate() {
+        let idx = start + i;
+        if idx < length {
+            *bin = Complex::new(ir_samples[idx * channels + channel] * gain, 0.0);
         }
-        return;
     }
     
-    let dry_wet = dry_wet.clamp(0.0, 1.0);
-    let dry = 1.0 - dry_wet;
-    let wet = dry_wet;
-    
-    unsafe {
-        let buffer_size = memory::buffer_size() as usize;
-        let input_l = memory::input_slice(0);
-        let input_r = memory::input_slice(1);
+    // FFT the partition
+    fft.process_with_scratch(&mut partition, scratch);
+    partition
+}
+
+/// Processing applied to a raw IR before partitioning
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct IrShape {
+    /// Fade amount (0-1): an exponential fade reaching
+    /// -DECAY_RANGE_DB * decay at the end of the (trimmed) IR
+    decay: f32,
+    /// Length to cut the IR to (0 = keep it all); the cut fades out over
+    /// TRIM_FADE_MS
+    trim_seconds: f32,
+    /// High-pass and low-pass cutoffs in Hz (None = off)
+    low_cut_hz: Option<f32>,
+    high_cut_hz: Option<f32>,
+}
+
+impl IrShape {
+    /// The raw IR unchanged
+    const NONE: Self = Self {
+        decay: 0.0,
+        trim_seconds: 0.0,
+        low_cut_hz: None,
+        high_cut_hz: None,
+    };
+}
+
+/// Copy of an interleaved IR with the filters, decay fade and trim applied
+/// 
+/// # Arguments
+/// * `ir_samples` - Interleaved raw IR samples
+/// * `channels` - Channel count of `ir_samples`
+/// * `shape` - Processing to apply
+/// * `sample_rate` - Engine sample rate
+fn shape_ir(ir_samples: &[f32], channels: usize, shape: IrShape, sample_rate: f32) -> Vec<f32> {
+    let raw_frames = ir_samples.len() / channels;
+    let frames = if shape.trim_seconds > 0.0 {
+        raw_frames.min((shape.trim_seconds * sample_rate).round() as usize)
+    } else {
+        raw_frames
+    };
+    let fade_len = if frames < raw_frames {
+        ((TRIM_FADE_MS * 0.001 * sample_rate) as usize).clamp(1, frames.max(1))
+    } else {
+        0
+    };
+    
+    let mut shaped = ir_samples[..frames * channels].to_vec();
+    
+    // Fresh filters per channel, so a rebuild doesn't depend on the last.
+    // The high cut stays below Nyquist at low sample rates.
+    let q = core::f32::consts::FRAC_1_SQRT_2;
+    for channel in 0..channels {
+        let mut filters = [
+            shape.low_cut_hz.map(|freq| Biquad::highpass(freq, q, sample_rate)),
+            shape.high_cut_hz.map(|freq| Biquad::lowpass(freq.min(0.45 * sample_rate), q, sample_rate)),
+        ];
+        for filter in filters.iter_mut().flatten() {
+            for sample in shaped.iter_mut().skip(channel).step_by(channels) {
+                *sample = filter.process(*sample);
+            }
+        }
+    }
+    
+    // Decay per frame, in nepers
+    let rate = shape.decay * DECAY_RANGE_DB / 20.0 * core::f32::consts::LN_10 / frames.max(1) as f32;
+    for (i, frame) in shaped.chunks_mut(channels).enumerate() {
+        let mut gain = libm::expf(-rate * i as f32);
+        if i + fade_len >= frames {
+            gain *= (frames - i) as f32 / (fade_len + 1) as f32;
+        }
+        for sample in frame {
+            *sample *= gain;
+        }
+    }
+    shaped
+}
+
+/// Gain that normalizes an interleaved IR
+/// 
+/// Silent IRs keep unity gain; the gain is capped at MAX_NORMALIZATION_GAIN.
+fn normalization_gain_for(ir_samples: &[f32], channels: usize, mode: IrNormalization) -> f32 {
+    let level = match mode {
+        IrNormalization::Off => return 1.0,
+        IrNormalization::Energy => {
+            (ir_samples.iter().map(|x| x * x).sum::<f32>() / channels as f32).sqrt()
+        }
+        IrNormalization::PeakResponse => peak_response(ir_samples, channels),
+        IrNormalization::Peak => ir_samples.iter().fold(0.0f32, |peak, x| peak.max(x.abs())),
+    };
+    if level > 0.0 {
+        (1.0 / level).min(MAX_NORMALIZATION_GAIN)
+    } else {
+        1.0
+    }
+}
+
+/// Largest magnitude of the frequency response over all IR channels
+/// 
+/// Runs one FFT over the whole IR per channel (load time only).
+fn peak_response(ir_samples: &[f32], channels: usize) -> f32 {
+    let frames = ir_samples.len() / channels;
+    let size = frames.next_power_of_two().max(2);
+    let fft = FftPlanner::new().plan_fft_forward(size);
+    let mut spectrum = vec![Complex::new(0.0, 0.0); size];
+    
+    let mut peak = 0.0f32;
+    for channel in 0..channels {
+        for (i, bin) in spectrum.iter_mut().enumerate() {
+            let sample = if i < frames { ir_samples[i * channels + channel] } else { 0.0 };
+            *bin = Complex::new(sample, 0.0);
+        }
+        fft.process(&mut spectrum);
+        peak = spectrum[..=size / 2].iter().fold(peak, |peak, bin| peak.max(bin.norm()));
+    }
+    peak
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process convolution reverb
+/// 
+/// # Arguments
+/// * `dry_wet` - Mix between dry (0) and wet (1) signal; changes glide over
+///   DEFAULT_SMOOTHING_MS
+/// * `blend` - Wet signal from the slot A (0) to the slot B (1) IR; changes
+///   ramp over the block
+pub fn process(dry_wet: f32, blend: f32) {
+    let state = ensure_state();
+    // The sidechain follower advances every block, IR or not
+    let ducking = ducking::block_gains(memory::buffer_size() as usize);
+    let dry_wet = dry_wet.clamp(0.0, 1.0);
+    // NaN (e.g. an omitted argument from JS) reads as slot A
+    let blend = if blend.is_nan() { 0.0 } else { blend.clamp(0.0, 1.0) };
+    
+    // Advance a pending rebuild (slot A first); the old partitions play
+    // until it's done. A new IR arriving during a crossfade waits for it to
+    // finish. A streamed IR is built here even before any IR is loaded.
+    state.running = true;
+    let pending = if state.rebuild.is_some() && state.crossfade_len == 0 {
+        state.rebuild.take()
+    } else {
+        state.rebuild_b.take()
+    };
+    if let Some(mut rebuild) = pending {
+        if rebuild.step(&state.segments, &mut state.fft_scratch, REBUILD_BUDGET) {
+            rebuild.install(state);
+        } else if rebuild.slot_b {
+            state.rebuild_b = Some(rebuild);
+        } else {
+            state.rebuild = Some(rebuild);
+        }
+    }
+    
+    let loaded = state.ir_loaded || state.ir_b_loaded;
+    
+    // The first block after a reset starts at its mix, and while no IR is
+    // loaded the mix is unused, so a newly loaded IR starts at the current one
+    if !state.dry_wet_primed || !loaded {
+        state.dry_wet.set_instant(dry_wet);
+        state.blend = blend;
+        state.dry_wet_primed = true;
+    }
+    
+    if !loaded {
+        // No IR loaded - pass through dry signal using SIMD
+        unsafe {
+            let input_l = memory::input_slice(0);
+            let input_r = memory::input_slice(1);
+            let output_l = memory::output_slice_mut(0);
+            let output_r = memory::output_slice_mut(1);
+            
+            simd_utils::copy_buffer(input_l, output_l);
+            simd_utils::copy_buffer(input_r, output_r);
+            block_dc(state, output_l, output_r);
+            state.mono_sum_peak = peak_of_sum(output_l, output_r);
+        }
+        state.tail_peak = 0.0;
+        return;
+    }
+    
+    let sample_rate = memory::sample_rate();
+    state.dry_wet.set_time(DEFAULT_SMOOTHING_MS, sample_rate);
+    state.dry_wet.set_target(dry_wet);
+    let (wet_gain_l, wet_gain_r) = (state.wet_gain_l, state.wet_gain_r);
+    state.predelay.set_delay((state.predelay_ms * 0.001 * sample_rate).round() as usize);
+    let fade_step = 1000.0 / (PREDELAY_CROSSFADE_MS * sample_rate);
+    set_early_partitions(state, sample_rate);
+    let (early_gain, late_gain) = (state.early_gain, state.late_gain);
+    
+    unsafe {
+        let buffer_size = memory::buffer_size() as usize;
+        let input_l = memory::input_slice(0);
+        let input_r = memory::input_slice(1);
         let output_l = memory::output_slice_mut(0);
         let output_r = memory::output_slice_mut(1);
         
-        let block_size = FFT_SIZE / 2;
-        
-        // Process samples in chunks
+        // Process samples in chunks. A head block completing at
+        // `sample_idx` starts its output `sample_idx - alignment` into the
+        // buses, so every block has the same latency whichever host
+        // sample completes it.
+        let alignment = block_alignment(buffer_size, state.head_block);
         let mut sample_idx = 0;
         while sample_idx < buffer_size {
             // Fill input buffer
-            while state.input_pos < block_size && sample_idx < buffer_size {
+            while state.input_pos < state.head_block && sample_idx < buffer_size {
                 state.input_buffer_l[state.input_pos] = input_l[sample_idx];
                 state.input_buffer_r[state.input_pos] = input_r[sample_idx];
                 state.input_pos += 1;
@@ -235,153 +1415,1592 @@ pub fn process(dry_wet: f32) {
             }
             
             // Process when input buffer is full
-            if state.input_pos >= block_size {
-                process_block(state);
+            if state.input_pos >= state.head_block {
+                process_block(state, sample_idx - alignment);
                 state.input_pos = 0;
             }
         }
         
-        // Read output from overlap buffer
+        // Read the wet signal from the buses, fading from the outgoing IR's
+        // and blending towards slot B's
+        let wet_l = &mut memory::work_buffer_1()[..buffer_size];
+        let wet_r = &mut memory::work_buffer_2()[..buffer_size];
+        let start_blend = state.blend;
+        let mut tail_peak = 0.0f32;
         for i in 0..buffer_size {
-            let wet_l = state.overlap_l[i];
-            let wet_r = state.overlap_r[i];
-            
-            output_l[i] = input_l[i] * dry + wet_l * wet;
-            output_r[i] = input_r[i] * dry + wet_r * wet;
+            let (mut bus_l, mut bus_r) = state.buses.sample(i, early_gain, late_gain);
+            if state.crossfade_pos < state.crossfade_len {
+                let fade = state.crossfade_pos as f32 / state.crossfade_len as f32;
+                let (old_l, old_r) = state.outgoing_buses.sample(i, early_gain, late_gain);
+                bus_l = old_l + (bus_l - old_l) * fade;
+                bus_r = old_r + (bus_r - old_r) * fade;
+                state.crossfade_pos += 1;
+            }
+            // Weighted so that blend 0 and 1 give exactly one slot's output
+            let t = (i + 1) as f32 / buffer_size as f32;
+            let mix = if start_blend == blend { blend } else { start_blend * (1.0 - t) + blend * t };
+            if mix != 0.0 {
+                let (b_l, b_r) = state.buses_b.sample(i, early_gain, late_gain);
+                bus_l = bus_l * (1.0 - mix) + b_l * mix;
+                bus_r = bus_r * (1.0 - mix) + b_r * mix;
+            }
+            (wet_l[i], wet_r[i]) = state.predelay.process(bus_l, bus_r, fade_step);
+            tail_peak = tail_peak.max(bus_l.abs().max(bus_r.abs())).max(wet_l[i].abs().max(wet_r[i].abs()));
         }
+        state.blend = blend;
+        state.tail_peak = tail_peak;
+        simd_utils::stereo_width(wet_l, wet_r, state.wet_width);
+        state.wet_compressor.process(wet_l, wet_r, sample_rate);
         
-        // Shift overlap buffer
-        let shift = buffer_size;
-        for i in 0..(FFT_SIZE - shift) {
-            state.overlap_l[i] = state.overlap_l[i + shift];
-            state.overlap_r[i] = state.overlap_r[i + shift];
+        for i in 0..buffer_size {
+            let mix = state.dry_wet.next();
+            let (dry, wet) = if state.linear_mix { (1.0 - mix, mix) } else { utils::equal_power_gains(mix) };
+            let duck = ducking.map_or(1.0, |gains| gains[i]);
+            output_l[i] = input_l[i] * dry + wet_l[i] * wet * wet_gain_l * duck;
+            output_r[i] = input_r[i] * dry + wet_r[i] * wet * wet_gain_r * duck;
         }
-        for i in (FFT_SIZE - shift)..FFT_SIZE {
-            state.overlap_l[i] = 0.0;
-            state.overlap_r[i] = 0.0;
+        block_dc(state, output_l, output_r);
+        state.mono_sum_peak = peak_of_sum(output_l, output_r);
+        
+        // Shift overlap buffers
+        state.buses.shift(buffer_size);
+        if state.ir_b_loaded {
+            state.buses_b.shift(buffer_size);
+        }
+        if state.crossfade_len > 0 {
+            if state.crossfade_pos < state.crossfade_len {
+                state.outgoing_buses.shift(buffer_size);
+            } else {
+                end_crossfade(state);
+            }
+        }
+    }
+}
+
+/// Drop the outgoing IR of a finished (or abandoned) crossfade
+/// 
+/// The delay lines shrink back to the loaded IR's partitions.
+fn end_crossfade(state: &mut ConvolutionState) {
+    if state.crossfade_len == 0 {
+        return;
+    }
+    let frames = state.ir_frames.max(state.ir_b_frames);
+    for segment in state.segments.iter_mut() {
+        segment.outgoing_partitions_l = Vec::new();
+        segment.outgoing_partitions_r = Vec::new();
+        segment.resize_fdl(segment.required_slots(frames));
+    }
+    state.outgoing_buses.clear();
+    state.crossfade_len = 0;
+    state.crossfade_pos = 0;
+}
+
+/// Hand one input block to every segment
+/// 
+/// Segments convolve once they have gathered a full partition, so larger
+/// segments run at a fraction of the block rate.
+/// 
+/// # Arguments
+/// * `bus_offset` - Where the head segment's output for this block starts
+///   in the buses
+fn process_block(state: &mut ConvolutionState, bus_offset: usize) {
+    let head_block = state.head_block;
+    for segment in state.segments.iter_mut().filter(|s| s.num_partitions > 0) {
+        let start = segment.input_pos;
+        segment.input_l[start..start + head_block].copy_from_slice(&state.input_buffer_l);
+        segment.input_r[start..start + head_block].copy_from_slice(&state.input_buffer_r);
+        segment.input_pos += head_block;
+        if segment.input_pos == segment.partition_len {
+            segment.input_pos = 0;
+            process_segment(
+                segment,
+                [&mut state.buses, &mut state.outgoing_buses, &mut state.buses_b],
+                &mut state.fft_scratch,
+                bus_offset,
+            );
         }
     }
 }
 
-/// Process one block of FFT convolution
-fn process_block(state: &mut ConvolutionState) {
-    let block_size = FFT_SIZE / 2;
-    let fft = state.planner.plan_fft_forward(FFT_SIZE);
-    let ifft = state.planner.plan_fft_inverse(FFT_SIZE);
+/// Convolve a segment's gathered input block
+/// 
+/// Each channel has its own FDL and accumulator; the only state the
+/// channels share is the FDL position and, for a mono IR, the (read-only)
+/// IR spectrum. During a crossfade the outgoing partitions convolve the
+/// same FDL into the outgoing buses, and slot B's into its own buses.
+/// 
+/// # Arguments
+/// * `buses` - Buses of the loaded, the outgoing and the slot B IR
+/// * `bus_offset` - Where the head segment's output for the block that
+///   completed this one starts in the buses
+fn process_segment(
+    segment: &mut Segment,
+    [buses, outgoing_buses, buses_b]: [&mut WetBuses; 3],
+    scratch: &mut [Complex<f32>],
+    bus_offset: usize,
+) {
+    let output_offset = segment.output_offset + bus_offset;
+    segment.fdl_peaks[segment.fdl_pos] = segment.input_l.iter()
+        .chain(&segment.input_r)
+        .fold(0.0f32, |peak, x| peak.max(x.abs()));
     
     // Process left channel
     process_channel_block(
-        &state.input_buffer_l,
-        &state.ir_partitions,
-        &mut state.fdl_l,
-        state.fdl_pos,
-        state.num_partitions,
-        &mut state.fft_input,
-        &mut state.fft_output,
-        &mut state.fft_temp,
-        &mut state.overlap_l,
-        &*fft,
-        &*ifft,
-        block_size,
+        &segment.input_l,
+        [
+            (&segment.ir_partitions_l, buses.channel(0, output_offset)),
+            (&segment.outgoing_partitions_l, outgoing_buses.channel(0, output_offset)),
+            (&segment.ir_b_partitions_l, buses_b.channel(0, output_offset)),
+        ],
+        &mut segment.fdl_l,
+        segment.fdl_pos,
+        &mut segment.accumulator_l,
+        segment.early_partitions,
+        &*segment.fft,
+        &*segment.ifft,
+        scratch,
     );
     
     // Process right channel
     process_channel_block(
-        &state.input_buffer_r,
-        &state.ir_partitions,
-        &mut state.fdl_r,
-        state.fdl_pos,
-        state.num_partitions,
-        &mut state.fft_input,
-        &mut state.fft_output,
-        &mut state.fft_temp,
-        &mut state.overlap_r,
-        &*fft,
-        &*ifft,
-        block_size,
+        &segment.input_r,
+        [
+            (
+                right_partitions(&segment.ir_partitions_l, &segment.ir_partitions_r),
+                buses.channel(1, output_offset),
+            ),
+            (
+                right_partitions(&segment.outgoing_partitions_l, &segment.outgoing_partitions_r),
+                outgoing_buses.channel(1, output_offset),
+            ),
+            (
+                right_partitions(&segment.ir_b_partitions_l, &segment.ir_b_partitions_r),
+                buses_b.channel(1, output_offset),
+            ),
+        ],
+        &mut segment.fdl_r,
+        segment.fdl_pos,
+        &mut segment.accumulator_r,
+        segment.early_partitions,
+        &*segment.fft,
+        &*segment.ifft,
+        scratch,
     );
     
     // Advance FDL position
-    state.fdl_pos = (state.fdl_pos + 1) % state.num_partitions;
+    segment.fdl_pos = (segment.fdl_pos + 1) % segment.num_partitions;
 }
 
+/// One channel's partition set and the [early, late] buses it feeds
+type BusFeed<'a> = (&'a [Vec<Complex<f32>>], [&'a mut [f32]; 2]);
+
 /// Process one channel block
+/// 
+/// The zero-padded input is transformed straight into the FDL slot, then
+/// for each partition set the spectra of the early and the late partitions
+/// are each accumulated and transformed back into their bus (skipped when
+/// a bus gets none, so an empty set costs nothing).
+/// 
+/// # Arguments
+/// * `sets` - Partition sets with the buses they feed
 #[allow(clippy::too_many_arguments)]
 fn process_channel_block(
     input: &[f32],
-    ir_partitions: &[Vec<Complex<f32>>],
+    sets: [BusFeed<'_>; 3],
     fdl: &mut [Vec<Complex<f32>>],
     fdl_pos: usize,
-    num_partitions: usize,
-    fft_input: &mut [Complex<f32>],
-    fft_output: &mut [Complex<f32>],
-    fft_temp: &mut [Complex<f32>],
-    overlap: &mut [f32],
-    fft: &dyn rustfft::Fft<f32>,
-    ifft: &dyn rustfft::Fft<f32>,
-    block_size: usize,
+    accumulator: &mut [Complex<f32>],
+    early_partitions: usize,
+    fft: &dyn Fft<f32>,
+    ifft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
 ) {
-    // Prepare input: copy to fft_input, zero-pad
-    for i in 0..FFT_SIZE {
-        fft_input[i] = if i < block_size {
-            Complex::new(input[i], 0.0)
-        } else {
-            Complex::new(0.0, 0.0)
-        };
-    }
-    
-    // FFT input
-    fft.process(fft_input);
-    
-    // Store in FDL at current position
-    fdl[fdl_pos].copy_from_slice(fft_input);
+    let fft_size = accumulator.len();
+    let num_partitions = fdl.len();
     
-    // Clear accumulator
-    for c in fft_output.iter_mut() {
-        *c = Complex::new(0.0, 0.0);
+    // Prepare input: copy to the FDL slot, zero-pad, FFT in place
+    let spectrum = &mut fdl[fdl_pos];
+    for (i, bin) in spectrum.iter_mut().enumerate() {
+        *bin = Complex::new(input.get(i).copied().unwrap_or(0.0), 0.0);
     }
+    fft.process_with_scratch(spectrum, scratch);
     
-    // Convolve: sum over all partitions
-    for p in 0..num_partitions {
-        let fdl_idx = (fdl_pos + num_partitions - p) % num_partitions;
-        let ir = &ir_partitions[p];
-        let input_spectrum = &fdl[fdl_idx];
-        
-        // Complex multiply and accumulate
-        for i in 0..FFT_SIZE {
-            fft_output[i] += input_spectrum[i] * ir[i];
+    let scale = 1.0 / fft_size as f32;
+    for (ir_partitions, overlaps) in sets {
+        let split = early_partitions.min(ir_partitions.len());
+        for (partitions, overlap) in [0..split, split..ir_partitions.len()].into_iter().zip(overlaps) {
+            if partitions.is_empty() {
+                continue;
+            }
+            
+            // Convolve: sum over the bus's partitions
+            accumulator.fill(Complex::new(0.0, 0.0));
+            for p in partitions {
+                let input_spectrum = &fdl[(fdl_pos + num_partitions - p) % num_partitions];
+                
+                // Complex multiply and accumulate (two bins per v128)
+                simd_utils::complex_multiply_accumulate(accumulator, input_spectrum, &ir_partitions[p]);
+            }
+            
+            // IFFT, normalize and overlap-add
+            ifft.process_with_scratch(accumulator, scratch);
+            for (out, y) in overlap.iter_mut().zip(accumulator.iter()) {
+                *out += y.re * scale;
+            }
         }
     }
-    
-    // IFFT
-    fft_temp.copy_from_slice(fft_output);
-    ifft.process(fft_temp);
-    
-    // Normalize and overlap-add
-    let scale = 1.0 / FFT_SIZE as f32;
-    for i in 0..FFT_SIZE {
-        overlap[i] += fft_temp[i].re * scale;
-    }
 }
 
 // ============================================================================
 // UTILITY
 // ============================================================================
 
+/// Set per-channel wet gains
+/// 
+/// # Arguments
+/// * `left_gain` - Left wet gain (0-2, 1 = unity)
+/// * `right_gain` - Right wet gain (0-2, 1 = unity)
+pub fn set_wet_gains(left_gain: f32, right_gain: f32) {
+    let state = ensure_state();
+    state.wet_gain_l = left_gain.clamp(0.0, MAX_WET_GAIN);
+    state.wet_gain_r = right_gain.clamp(0.0, MAX_WET_GAIN);
+}
+
+/// Set the stereo width of the wet signal
+/// 
+/// Mid/side scaling of the wet L/R before the mix; the dry signal is
+/// untouched. Capped at MAX_WET_WIDTH, where out-of-phase content gains
+/// at most 6dB.
+/// 
+/// # Arguments
+/// * `width` - 0 = mono wet, 1 = unchanged (default), 2 = twice as wide
+pub fn set_wet_width(width: f32) {
+    ensure_state().wet_width = width.clamp(0.0, MAX_WET_WIDTH);
+}
+
+/// Set the wet signal compressor
+/// 
+/// Compresses the wet signal after the width and before the dry/wet mix.
+/// 
+/// # Arguments
+/// * `threshold_db` - Threshold in dBFS (-60-0)
+/// * `ratio` - Compression ratio (1-20, 1 = bypass)
+/// * `attack_ms` - Attack time (1-500ms)
+/// * `release_ms` - Release time (10-5000ms)
+/// * `makeup_db` - Makeup gain (0-24dB)
+/// * `detector` - Peak or RMS level detection
+pub fn set_wet_compressor(
+    threshold_db: f32,
+    ratio: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    makeup_db: f32,
+    detector: FollowerMode,
+) {
+    ensure_state().wet_compressor.set(threshold_db, ratio, attack_ms, release_ms, makeup_db, detector);
+}
+
+/// Select the dry/wet mix law
+/// 
+/// # Arguments
+/// * `linear` - true = linear crossfade, false = equal-power (default)
+pub fn set_linear_mix(linear: bool) {
+    ensure_state().linear_mix = linear;
+}
+
+/// Set the early/late split point
+/// 
+/// Partitions starting before the split feed the early bus. The split
+/// snaps to the nearest partition boundary; IRs aren't re-rendered.
+/// 
+/// # Arguments
+/// * `ms` - Split point in milliseconds (0-1000, 0 = all late)
+pub fn set_split(ms: f32) {
+    let state = ensure_state();
+    state.split_ms = ms.clamp(0.0, MAX_SPLIT_MS);
+    set_early_partitions(state, memory::sample_rate());
+}
+
+/// Split point in effect, in milliseconds (after snapping)
+pub fn split_ms() -> f32 {
+    let state = ensure_state();
+    let frame = split_frame(&state.segments, state.split_ms, memory::sample_rate());
+    frame as f32 * 1000.0 / memory::sample_rate()
+}
+
+/// Set the early and late bus gains
+/// 
+/// # Arguments
+/// * `early_gain` - Early reflections gain (0-2, 1 = unity)
+/// * `late_gain` - Tail gain (0-2, 1 = unity)
+pub fn set_early_late_gains(early_gain: f32, late_gain: f32) {
+    let state = ensure_state();
+    state.early_gain = early_gain.clamp(0.0, MAX_WET_GAIN);
+    state.late_gain = late_gain.clamp(0.0, MAX_WET_GAIN);
+}
+
+/// Split point in IR frames, snapped to a partition boundary
+fn split_frame(segments: &[Segment], ms: f32, sample_rate: f32) -> usize {
+    snap_to_partition(segments, (ms * 0.001 * sample_rate).round() as usize)
+}
+
+/// Hand each segment its share of early partitions
+fn set_early_partitions(state: &mut ConvolutionState, sample_rate: f32) {
+    let split = split_frame(&state.segments, state.split_ms, sample_rate);
+    for segment in &mut state.segments {
+        segment.early_partitions = split.saturating_sub(segment.ir_offset) / segment.partition_len;
+    }
+}
+
+/// Peak of the mono sum (L + R) of the last processed output block
+/// 
+/// A peak far below the channel peaks means the channels largely cancel
+/// when summed to mono (e.g. a near-anti-phase IR).
+pub fn mono_sum_peak() -> f32 {
+    ensure_state().mono_sum_peak
+}
+
+/// Whether the reverb tail is still audible
+/// 
+/// True while the wet output of the last processed block, or the input
+/// the IR has yet to respond to (gathered or still in the delay lines),
+/// peaks above the tail threshold. Once input stops, this turns false
+/// after at most the IR length plus the predelay. Always false without a
+/// loaded IR.
+pub fn tail_active() -> bool {
+    let state = ensure_state();
+    if !state.ir_loaded && !state.ir_b_loaded {
+        return false;
+    }
+    let gathered = state.input_buffer_l[..state.input_pos]
+        .iter()
+        .chain(&state.input_buffer_r[..state.input_pos])
+        .fold(0.0f32, |peak, x| peak.max(x.abs()));
+    gathered.max(state.tail_peak) > state.tail_threshold
+        || state.segments.iter().any(|segment| segment.input_peak() > state.tail_threshold)
+}
+
+/// Set the level below which the reverb tail counts as silent
+/// 
+/// # Arguments
+/// * `threshold_db` - Threshold in dBFS (default -80)
+pub fn set_tail_threshold(threshold_db: f32) {
+    ensure_state().tail_threshold = utils::db_to_linear(threshold_db.min(0.0));
+}
+
+/// Set the wet predelay
+/// 
+/// Moves to the new delay with a short crossfade.
+/// 
+/// # Arguments
+/// * `ms` - Predelay in milliseconds (0-200, 0 = none)
+pub fn set_predelay(ms: f32) {
+    let state = ensure_state();
+    state.predelay_ms = ms.clamp(0.0, MAX_PREDELAY_MS);
+    state.predelay.set_delay((state.predelay_ms * 0.001 * memory::sample_rate()).round() as usize);
+}
+
+/// Select how IRs are normalized on load
+/// 
+/// Loaded IRs are reloaded with the new mode (crossfading if audio is
+/// running). Each slot is normalized on its own.
+pub fn set_ir_normalization(mode: IrNormalization) {
+    let state = ensure_state();
+    if state.normalization != mode {
+        state.normalization = mode;
+        // A streamed IR picks the mode up when it finishes
+        if state.ir_frames > 0 && state.stream.is_none() {
+            let (frames, channels) = (state.ir_frames, state.ir_channels);
+            install_ir(frames as u32, channels as u32);
+        }
+        if state.ir_b_frames > 0 {
+            let (frames, channels) = (state.ir_b_frames, state.ir_b_channels);
+            install_ir_b(frames as u32, channels as u32);
+        }
+    }
+}
+
+/// Set the IR decay fade and trim
+/// 
+/// The partitions of both slots are rebuilt from the raw IRs over the next
+/// blocks and swapped in once complete; the old ones keep playing meanwhile.
+/// 
+/// # Arguments
+/// * `amount` - Decay fade (0-1, 0 = none, 1 = -60dB at the IR end)
+/// * `trim_seconds` - Length to cut the IR to (0 = no trim)
+pub fn set_ir_decay(amount: f32, trim_seconds: f32) {
+    let state = ensure_state();
+    state.ir_shape.decay = amount.clamp(0.0, 1.0);
+    state.ir_shape.trim_seconds = trim_seconds.max(0.0);
+    reshape_irs(state);
+}
+
+/// Set the IR low-cut and high-cut filters
+/// 
+/// The partitions of both slots are rebuilt like for `set_ir_decay`.
+/// 
+/// # Arguments
+/// * `low_cut_hz` - High-pass cutoff (20-500Hz; 20 or below = off)
+/// * `high_cut_hz` - Low-pass cutoff (1k-20kHz; 20k or above = off)
+pub fn set_ir_filters(low_cut_hz: f32, high_cut_hz: f32) {
+    let state = ensure_state();
+    state.ir_shape.low_cut_hz = (low_cut_hz > MIN_IR_LOW_CUT_HZ).then(|| low_cut_hz.min(MAX_IR_LOW_CUT_HZ));
+    state.ir_shape.high_cut_hz = (high_cut_hz < MAX_IR_HIGH_CUT_HZ).then(|| high_cut_hz.max(MIN_IR_HIGH_CUT_HZ));
+    reshape_irs(state);
+}
+
+/// Rebuild the partitions of both slots from the raw IRs with the current
+/// shape
+fn reshape_irs(state: &mut ConvolutionState) {
+    // A streamed IR is shaped when it finishes
+    if state.ir_frames > 0 && state.stream.is_none() {
+        let ir_samples = unsafe {
+            std::slice::from_raw_parts(
+                memory::get_ir_ptr() as *const f32,
+                state.ir_frames * state.ir_channels
+            )
+        };
+        let shaped = shape_ir(ir_samples, state.ir_channels, state.ir_shape, memory::sample_rate());
+        // Reshaping an IR that is still being loaded keeps its crossfade
+        let crossfade = state.rebuild.as_ref().is_some_and(|rebuild| rebuild.crossfade);
+        state.rebuild = Some(Rebuild::new(shaped, state.ir_channels, state.normalization, crossfade));
+    }
+    if state.ir_b_frames > 0 {
+        let ir_samples = unsafe {
+            std::slice::from_raw_parts(
+                memory::get_ir_b_ptr() as *const f32,
+                state.ir_b_frames * state.ir_b_channels
+            )
+        };
+        let shaped = shape_ir(ir_samples, state.ir_b_channels, state.ir_shape, memory::sample_rate());
+        state.rebuild_b = Some(Rebuild {
+            slot_b: true,
+            ..Rebuild::new(shaped, state.ir_b_channels, state.normalization, false)
+        });
+    }
+}
+
+/// Gain the normalization applied to the loaded IR (1.0 when off)
+/// 
+/// Reports the gain of an IR still being built, if any.
+pub fn normalization_gain() -> f32 {
+    let state = ensure_state();
+    state.rebuild.as_ref().map_or(state.normalization_gain, |rebuild| rebuild.gain)
+}
+
+/// Peak of |L + R| over a block
+fn peak_of_sum(left: &[f32], right: &[f32]) -> f32 {
+    left.iter()
+        .zip(right)
+        .fold(0.0f32, |peak, (&l, &r)| peak.max((l + r).abs()))
+}
+
+/// DC block the output buffers if enabled (resets the filters otherwise)
+fn block_dc(state: &mut ConvolutionState, left: &mut [f32], right: &mut [f32]) {
+    if memory::is_dc_blocker_enabled() {
+        let sample_rate = memory::sample_rate();
+        state.dc_blocker_l.set_dc_blocker(sample_rate);
+        state.dc_blocker_r.set_dc_blocker(sample_rate);
+        state.dc_blocker_l.process_buffer(left);
+        state.dc_blocker_r.process_buffer(right);
+    } else {
+        state.dc_blocker_l.reset();
+        state.dc_blocker_r.reset();
+    }
+}
+
+/// Spacing of the host block positions at which head blocks complete
+/// 
+/// Head blocks end at multiples of `head_block`, which fall on multiples
+/// of gcd(buffer_size, head_block) within a host block. The earliest of
+/// those positions sets the latency.
+fn block_alignment(buffer_size: usize, head_block: usize) -> usize {
+    let (mut a, mut b) = (buffer_size, head_block);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Latency of the wet path in samples
+/// 
+/// Input is gathered into head blocks, and a block's output starts in the
+/// host block that completes it, at the same delay for every block. Host
+/// blocks that are a multiple of the head block add no latency; others
+/// add the head block minus their alignment (e.g. 128 for 128-sample
+/// blocks, 192 for 192-sample blocks with the default 256-sample head
+/// block).
+pub fn latency_samples() -> u32 {
+    let head_block = ensure_state().head_block;
+    (head_block - block_alignment(memory::buffer_size() as usize, head_block)) as u32
+}
+
 /// Reset convolution state
 pub fn reset() {
     // SAFETY: Single-threaded WASM context
-    let state_ptr = unsafe { addr_of_mut!(STATE) };
+    let state_ptr = unsafe { addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]) };
     if let Some(state) = unsafe { (*state_ptr).as_mut() } {
-        state.overlap_l.fill(0.0);
-        state.overlap_r.fill(0.0);
-        for fdl in &mut state.fdl_l {
-            fdl.fill(Complex::new(0.0, 0.0));
+        // Nothing is playing, so pending IRs are finished without a crossfade
+        for mut rebuild in [state.rebuild.take(), state.rebuild_b.take()].into_iter().flatten() {
+            rebuild.step(&state.segments, &mut state.fft_scratch, usize::MAX);
+            rebuild.install(state);
         }
-        for fdl in &mut state.fdl_r {
-            fdl.fill(Complex::new(0.0, 0.0));
+        end_crossfade(state);
+        state.running = false;
+        
+        state.buses.clear();
+        state.buses_b.clear();
+        clear_delay_lines(state);
+        state.mono_sum_peak = 0.0;
+        state.tail_peak = 0.0;
+        state.dry_wet_primed = false;
+        state.dc_blocker_l.reset();
+        state.dc_blocker_r.reset();
+        state.wet_compressor.reset();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// First IR frame of each segment at the default head block size
+    const SEGMENT_OFFSETS: [usize; NUM_SEGMENTS] = segment_offsets(DEFAULT_HEAD_BLOCK_SIZE);
+    
+    /// Deterministic test signal in [-1, 1)
+    fn signal(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_identical_channels_give_bit_identical_output() {
+        let _guard = memory::test_lock();
+        
+        for buffer_size in [128u32, 100] {
+            memory::init_engine(48000.0, buffer_size);
+            
+            // Lengths around the partition size, plus a stereo IR with equal channels
+            for (ir_len, ir_channels) in [(1, 1), (100, 1), (256, 1), (257, 1), (1000, 2), (5000, 1)] {
+                let ir: Vec<f32> = signal(ir_len, 7)
+                    .into_iter()
+                    .flat_map(|x| std::iter::repeat_n(x, ir_channels))
+                    .collect();
+                unsafe {
+                    std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len())
+                        .copy_from_slice(&ir);
+                }
+                load_ir(core::ptr::null(), ir_len as u32, ir_channels as u32, 0.0);
+                reset();
+                
+                let input = signal(buffer_size as usize * 40, 99);
+                for block in input.chunks(buffer_size as usize) {
+                    unsafe {
+                        for (i, &x) in block.iter().enumerate() {
+                            *memory::get_input_buffer(0).add(i) = x;
+                            *memory::get_input_buffer(1).add(i) = x;
+                        }
+                    }
+                    process(1.0, 0.0);
+                    
+                    let (left, right) = unsafe {
+                        (memory::output_slice_mut(0).to_vec(), memory::output_slice_mut(1).to_vec())
+                    };
+                    assert_eq!(left, right, "L/R differ (IR {ir_len}x{ir_channels}, block {buffer_size})");
+                }
+                
+                let wet = unsafe { memory::output_slice_mut(0) };
+                assert!(wet.iter().any(|&x| x != 0.0), "no wet output for IR {ir_len}");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_zero_left_wet_gain_mutes_left_channel() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        let ir = signal(1000, 7);
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len())
+                .copy_from_slice(&ir);
+        }
+        load_ir(core::ptr::null(), ir.len() as u32, 1, 0.0);
+        reset();
+        set_wet_gains(0.0, 1.0);
+        
+        let input = signal(128 * 20, 99);
+        let mut right_energy = 0.0;
+        for block in input.chunks(128) {
+            unsafe {
+                for (i, &x) in block.iter().enumerate() {
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            
+            let (left, right) = unsafe { (memory::output_slice_mut(0), memory::output_slice_mut(1)) };
+            assert!(left.iter().all(|&x| x == 0.0), "left wet should be muted");
+            right_energy += right.iter().map(|x| x * x).sum::<f32>();
+            
+            let expected_peak = right.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+            assert_eq!(mono_sum_peak(), expected_peak);
+        }
+        assert!(right_energy > 0.0, "right wet should be audible");
+        
+        set_wet_gains(1.0, 1.0);
+    }
+    
+    /// Wet output of both channels for the same input on L and R
+    fn render_wet(ir: &[f32], ir_channels: u32, blocks: usize) -> (Vec<f32>, Vec<f32>) {
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len()).copy_from_slice(ir);
+        }
+        load_ir(core::ptr::null(), ir.len() as u32 / ir_channels, ir_channels, 0.0);
+        reset();
+        render_blend(blocks, 0.0)
+    }
+    
+    /// Fully wet output of `blocks` blocks of test signal at an IR blend
+    fn render_blend(blocks: usize, blend: f32) -> (Vec<f32>, Vec<f32>) {
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for block in signal(128 * blocks, 99).chunks(128) {
+            unsafe {
+                for (i, &x) in block.iter().enumerate() {
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, blend);
+            unsafe {
+                left.extend_from_slice(memory::output_slice_mut(0));
+                right.extend_from_slice(memory::output_slice_mut(1));
+            }
+        }
+        (left, right)
+    }
+    
+    #[test]
+    fn test_ir_blend_end_points_match_single_ir() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        set_ir_normalization(IrNormalization::Energy);
+        load_ir_b(0, 1, 0.0);
+        
+        // A short mono IR and a stereo one reaching into the second segment,
+        // each normalized on its own
+        let ir_a: Vec<f32> = signal(2000, 7).iter().map(|x| x * 0.5).collect();
+        let ir_b = signal(2 * (SEGMENT_OFFSETS[1] + 3000), 11);
+        let blocks = (SEGMENT_OFFSETS[1] + 8192) / 128;
+        let only_a = render_wet(&ir_a, 1, blocks);
+        let only_b = render_wet(&ir_b, 2, blocks);
+        
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_ir_b_ptr(), ir_b.len()).copy_from_slice(&ir_b);
+        }
+        assert_eq!(load_ir_b(ir_b.len() as u32 / 2, 2, 0.0), LOAD_OK);
+        assert_eq!(render_wet(&ir_a, 1, blocks), only_a);
+        reset();
+        assert_eq!(render_blend(blocks, 1.0), only_b);
+        
+        // In between, the wet signal is the weighted sum of both
+        reset();
+        let (mid_l, mid_r) = render_blend(blocks, 0.25);
+        for (mid, (a, b)) in [(mid_l, (only_a.0, only_b.0)), (mid_r, (only_a.1, only_b.1))] {
+            for (i, &y) in mid.iter().enumerate() {
+                let expected = 0.75 * a[i] + 0.25 * b[i];
+                assert!((y - expected).abs() < 1e-4, "sample {i}: {y} vs {expected}");
+            }
+        }
+        
+        load_ir_b(0, 1, 0.0);
+        set_ir_normalization(IrNormalization::Off);
+    }
+    
+    #[test]
+    fn test_streamed_ir_load() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        assert_eq!(append_ir_chunk(core::ptr::null(), 100), LOAD_REJECTED);
+        assert_eq!(finish_ir_load(), LOAD_REJECTED);
+        assert_eq!(begin_ir_load(100, 3), LOAD_REJECTED);
+        
+        let scaled = |len, seed| signal(len, seed).iter().map(|x| x * 0.1).collect::<Vec<f32>>();
+        let old_ir = scaled(SEGMENT_OFFSETS[1] + 20000, 5);
+        let new_ir = scaled(SEGMENT_OFFSETS[1] + 10000, 9);
+        let blocks = 400;
+        let (old_left, _) = render_wet(&old_ir, 1, blocks);
+        let (new_left, _) = render_wet(&new_ir, 1, blocks);
+        let input = signal(128 * blocks, 99);
+        let run_block = |block: usize| {
+            unsafe {
+                for (i, &x) in input[block * 128..(block + 1) * 128].iter().enumerate() {
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            unsafe { memory::output_slice_mut(0).to_vec() }
+        };
+        
+        // The old IR plays on, unchanged, while the new one streams in
+        render_wet(&old_ir, 1, 0);
+        let chunk = new_ir.len() / 4;
+        assert_eq!(begin_ir_load(new_ir.len() as u32, 1), LOAD_OK);
+        for (block, samples) in new_ir.chunks(chunk).enumerate() {
+            unsafe {
+                let region = std::slice::from_raw_parts_mut(memory::get_ir_ptr(), new_ir.len());
+                region[block * chunk..][..samples.len()].copy_from_slice(samples);
+            }
+            assert_eq!(append_ir_chunk(core::ptr::null(), samples.len() as u32), LOAD_OK);
+            assert_eq!(run_block(block), old_left[block * 128..(block + 1) * 128]);
+        }
+        assert_eq!(finish_ir_load(), LOAD_OK);
+        
+        // The partitions are built over several blocks, then crossfaded to
+        let first = new_ir.len().div_ceil(chunk);
+        let mut swapped = None;
+        let mut output = Vec::new();
+        for block in first..blocks {
+            output.extend(run_block(block));
+            let state = ensure_state();
+            if swapped.is_none() && state.rebuild.is_none() {
+                swapped = Some(block);
+            }
+        }
+        let swapped = swapped.expect("streamed IR never installed");
+        assert!(swapped > first, "streamed IR was built in a single block");
+        let tail = (blocks - 50) * 128;
+        for (i, (&y, &expected)) in output[tail - first * 128..].iter().zip(&new_left[tail..]).enumerate() {
+            assert!((y - expected).abs() < 1e-4, "sample {i}: {y} vs {expected}");
+        }
+        
+        // A first IR streamed while audio runs isn't built in one go either
+        load_ir(core::ptr::null(), 0, 1, 0.0);
+        run_block(0);
+        begin_ir_load(new_ir.len() as u32, 1);
+        append_ir_chunk(core::ptr::null(), new_ir.len() as u32 + 10);
+        assert_eq!(finish_ir_load(), LOAD_TRUNCATED);
+        assert!(!ensure_state().ir_loaded);
+        run_block(1);
+        assert!(!ensure_state().ir_loaded);
+        for block in 2..10 {
+            run_block(block);
+        }
+        assert!(ensure_state().ir_loaded);
+    }
+    
+    #[test]
+    fn test_tail_activity_follows_ring_out() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        set_tail_threshold(DEFAULT_TAIL_THRESHOLD_DB);
+        
+        // 0.3s noise IR decaying by 60dB
+        let ir_len = 14400;
+        let ir: Vec<f32> = signal(ir_len, 3)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * 0.05 * libm::powf(10.0, -3.0 * i as f32 / ir_len as f32))
+            .collect();
+        render_wet(&ir, 1, 0);
+        assert!(!tail_active());
+        
+        // A burst of noise, then silence
+        let burst = signal(128 * 20, 8);
+        let mut active = Vec::new();
+        let mut peaks = Vec::new();
+        for block in 0..300 {
+            unsafe {
+                for i in 0..128 {
+                    let x = burst.get(block * 128 + i).copied().unwrap_or(0.0);
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            active.push(tail_active());
+            peaks.push(unsafe { memory::output_slice_mut(0) }.iter().fold(0.0f32, |peak, x| peak.max(x.abs())));
+        }
+        
+        // Active through the burst and the audible tail, then inactive for
+        // good once the output stays below the threshold
+        let threshold = utils::db_to_linear(DEFAULT_TAIL_THRESHOLD_DB);
+        let ring_out = active.iter().position(|&a| !a).expect("tail never went inactive");
+        assert!(ring_out > 20 && ring_out * 128 <= 20 * 128 + ir_len + 2 * 4096, "inactive at block {ring_out}");
+        assert!(active[..ring_out].iter().all(|&a| a));
+        assert!(active[ring_out..].iter().all(|&a| !a));
+        assert!(peaks[ring_out..].iter().all(|&p| p <= threshold));
+        assert!(peaks[ring_out - 1] > 0.0);
+        
+        // A burst below a raised threshold never counts as a tail
+        reset();
+        set_tail_threshold(-40.0);
+        for block in 0..40 {
+            unsafe {
+                for i in 0..128 {
+                    let x = burst.get(block * 128 + i).copied().unwrap_or(0.0) * 0.001;
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            assert!(!tail_active());
+        }
+        set_tail_threshold(DEFAULT_TAIL_THRESHOLD_DB);
+    }
+    
+    #[test]
+    fn test_resampled_ir_keeps_timing() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        reset();
+        
+        // Half a second of 1kHz at 44.1kHz, in either slot
+        let source_rate = 44100.0;
+        let freq = 1000.0;
+        let original: Vec<f32> = (0..22050)
+            .map(|i| (2.0 * core::f32::consts::PI * freq * i as f32 / source_rate).sin())
+            .collect();
+        for slot in [0, 1] {
+            let ptr = if slot == 0 { memory::get_ir_ptr() } else { memory::get_ir_b_ptr() };
+            unsafe {
+                std::slice::from_raw_parts_mut(ptr, original.len()).copy_from_slice(&original);
+            }
+            let status = if slot == 0 {
+                load_ir(core::ptr::null(), original.len() as u32, 1, source_rate)
+            } else {
+                load_ir_b(original.len() as u32, 1, source_rate)
+            };
+            assert_eq!(status, LOAD_OK);
+            let state = ensure_state();
+            assert_eq!(if slot == 0 { state.ir_frames } else { state.ir_b_frames }, 24000);
+            
+            // Away from the edges the sine is reproduced at the engine rate
+            let ir = unsafe { std::slice::from_raw_parts(ptr as *const f32, 24000) };
+            for (i, &x) in ir.iter().enumerate().skip(100).take(23800) {
+                let expected = (2.0 * core::f32::consts::PI * freq * i as f32 / 48000.0).sin();
+                assert!((x - expected).abs() < 2e-3, "slot {slot}, frame {i}: {x} vs {expected}");
+            }
+        }
+        load_ir_b(0, 1, 0.0);
+        
+        // Upsampling a full region stops at MAX_IR_FRAMES, and the stereo
+        // region is not written past it
+        unsafe {
+            let region = std::slice::from_raw_parts_mut(memory::get_ir_ptr(), memory::MAX_IR_SAMPLES);
+            region.fill(0.25);
+            *memory::get_ir_b_ptr() = 7.0;
+        }
+        let frames = memory::MAX_IR_SAMPLES / 2;
+        assert_eq!(load_ir(core::ptr::null(), frames as u32, 2, source_rate), LOAD_TRUNCATED);
+        assert_eq!(ensure_state().ir_frames, MAX_IR_FRAMES);
+        assert_eq!(unsafe { *memory::get_ir_b_ptr() }, 7.0);
+        
+        // Matching rates load the samples unchanged; bad channel counts are rejected
+        assert_eq!(load_ir(core::ptr::null(), 1000, 1, 48000.0), LOAD_OK);
+        assert_eq!(ensure_state().ir_frames, 1000);
+        assert_eq!(load_ir(core::ptr::null(), 1000, 3, 44100.0), LOAD_REJECTED);
+        reset();
+    }
+    
+    #[test]
+    fn test_stereo_ir_keeps_channels_separate() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        // IR only on the left: the right wet signal is silent
+        let response = signal(1000, 7);
+        let left_only: Vec<f32> = response.iter().flat_map(|&x| [x, 0.0]).collect();
+        let (left, right) = render_wet(&left_only, 2, 20);
+        assert!(left.iter().any(|&x| x != 0.0), "left IR should be audible");
+        assert!(right.iter().all(|&x| x == 0.0), "right IR is silent");
+        
+        // Left and right responses are applied to their own channels
+        let mirrored: Vec<f32> = response.iter().flat_map(|&x| [0.0, x]).collect();
+        let (mirrored_left, mirrored_right) = render_wet(&mirrored, 2, 20);
+        assert!(mirrored_left.iter().all(|&x| x == 0.0), "left IR is silent");
+        assert_eq!(mirrored_right, left);
+        
+        // A mono IR matches the stereo IR with that response on both channels
+        let (mono_left, mono_right) = render_wet(&response, 1, 20);
+        assert_eq!(mono_left, left);
+        assert_eq!(mono_right, left);
+    }
+    
+    #[test]
+    fn test_ir_normalization_evens_out_levels() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        let response = signal(2000, 7);
+        let quiet: Vec<f32> = response.iter().map(|x| x * 0.1).collect();
+        
+        // Without normalization a 20dB quieter IR gives a 20dB quieter wet signal
+        set_ir_normalization(IrNormalization::Off);
+        let (loud_wet, _) = render_wet(&response, 1, 20);
+        let (quiet_wet, _) = render_wet(&quiet, 1, 20);
+        assert_eq!(normalization_gain(), 1.0);
+        let energy = |x: &[f32]| x.iter().map(|s| s * s).sum::<f32>();
+        assert!((energy(&loud_wet) / energy(&quiet_wet) - 100.0).abs() < 0.1);
+        
+        // With it both IRs land on the same level
+        for mode in [IrNormalization::Energy, IrNormalization::PeakResponse, IrNormalization::Peak] {
+            set_ir_normalization(mode);
+            let (loud_wet, _) = render_wet(&response, 1, 20);
+            let loud_gain = normalization_gain();
+            let (quiet_wet, _) = render_wet(&quiet, 1, 20);
+            assert!((normalization_gain() / loud_gain - 10.0).abs() < 1e-3, "{mode:?}");
+            for (a, b) in loud_wet.iter().zip(&quiet_wet) {
+                assert!((a - b).abs() < 1e-4, "{mode:?}: {a} vs {b}");
+            }
+        }
+        
+        // Unit energy and 0dB peak response for simple IRs
+        set_ir_normalization(IrNormalization::Energy);
+        render_wet(&[0.0, 0.5, 0.0, 0.5], 2, 1);
+        assert!((normalization_gain() - 2.0).abs() < 1e-6);
+        set_ir_normalization(IrNormalization::PeakResponse);
+        render_wet(&[0.25, 0.0, 0.0], 1, 1);
+        assert!((normalization_gain() - 4.0).abs() < 1e-6);
+        set_ir_normalization(IrNormalization::Peak);
+        render_wet(&[0.1, -0.5, 0.25, 0.2], 2, 1);
+        assert!((normalization_gain() - 2.0).abs() < 1e-6);
+        
+        // A long, dense hall IR is far hotter than a unit impulse; unit
+        // energy compensates for it
+        let hall: Vec<f32> = signal(48000, 13)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * (-(i as f32) / 14400.0).exp())
+            .collect();
+        let level = |ir: &[f32]| {
+            let (wet, _) = render_wet(ir, 1, 450);
+            let settled = &wet[wet.len() - 128 * 50..];
+            (energy(settled) / settled.len() as f32).sqrt()
+        };
+        set_ir_normalization(IrNormalization::Off);
+        assert!(level(&hall) / level(&[1.0]) > 10.0);
+        set_ir_normalization(IrNormalization::Energy);
+        let ratio_db = 20.0 * (level(&hall) / level(&[1.0])).log10();
+        assert!(ratio_db.abs() < 1.0, "hall {ratio_db}dB vs unit impulse");
+        
+        // Changing the mode rebuilds the loaded IR
+        set_ir_normalization(IrNormalization::Off);
+        assert_eq!(normalization_gain(), 1.0);
+    }
+    
+    #[test]
+    fn test_predelay_shifts_wet_signal() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        let impulse = [1.0];
+        
+        set_predelay(0.0);
+        let (undelayed, _) = render_wet(&impulse, 1, 20);
+        
+        // 10ms at 48kHz: the same wet signal, 480 samples later
+        set_predelay(10.0);
+        let (delayed, _) = render_wet(&impulse, 1, 20);
+        assert!(delayed[..480].iter().all(|&x| x == 0.0));
+        assert_eq!(&delayed[480..], &undelayed[..undelayed.len() - 480]);
+        
+        // Changing predelay while running crossfades instead of jumping.
+        // 256-sample blocks match the partition size, so the undelayed wet
+        // signal is itself continuous.
+        memory::init_engine(48000.0, 256);
+        set_predelay(0.0);
+        render_wet(&impulse, 1, 1);
+        reset();
+        let mut previous = 0.0f32;
+        let mut max_step = 0.0f32;
+        for block in 0..50 {
+            if block == 25 {
+                set_predelay(MAX_PREDELAY_MS);
+            }
+            unsafe {
+                for i in 0..256 {
+                    let t = (block * 256 + i) as f32 / 48000.0;
+                    let x = 0.5 * (2.0 * core::f32::consts::PI * 440.0 * t).sin();
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            for &y in unsafe { memory::output_slice_mut(0).iter() } {
+                max_step = max_step.max((y - previous).abs());
+                previous = y;
+            }
+        }
+        // A 440Hz sine at 0.5 moves at most ~0.029 per sample
+        assert!(max_step < 0.05, "predelay change glitched: step {max_step}");
+        
+        set_predelay(0.0);
+        memory::init_engine(48000.0, 128);
+    }
+    
+    #[test]
+    fn test_early_late_split() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        let ir: Vec<f32> = signal(12000, 3)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * (-(i as f32) / 4000.0).exp())
+            .collect();
+        
+        // 60ms (2880 frames) snaps to the 3840-frame segment boundary
+        set_split(60.0);
+        assert_eq!(split_ms(), 80.0);
+        set_split(40.0);
+        assert_eq!(split_ms(), 1792.0 / 48.0);
+        set_split(60.0);
+        
+        let (full, _) = render_wet(&ir, 1, 200);
+        set_early_late_gains(1.0, 0.0);
+        let (early, _) = render_wet(&ir, 1, 200);
+        set_early_late_gains(0.0, 1.0);
+        let (late, _) = render_wet(&ir, 1, 200);
+        set_early_late_gains(1.0, 1.0);
+        
+        // The buses split the response without losing or doubling anything
+        let peak = simd_utils::find_peak(&full);
+        for n in 0..full.len() {
+            assert!((early[n] + late[n] - full[n]).abs() < peak * 1e-5, "bus sum differs at {n}");
+        }
+        
+        // The early bus is the IR up to the snapped split
+        let (truncated, _) = render_wet(&ir[..3840], 1, 200);
+        for n in 0..full.len() {
+            assert!((early[n] - truncated[n]).abs() < peak * 1e-5, "early bus differs at {n}");
+        }
+        
+        // Gains scale each bus independently
+        set_early_late_gains(0.5, 2.0);
+        let (mixed, _) = render_wet(&ir, 1, 200);
+        for n in 0..full.len() {
+            let expected = 0.5 * early[n] + 2.0 * late[n];
+            assert!((mixed[n] - expected).abs() < peak * 1e-5, "bus gains wrong at {n}");
+        }
+        
+        set_early_late_gains(1.0, 1.0);
+        set_split(DEFAULT_SPLIT_MS);
+    }
+    
+    #[test]
+    fn test_ir_filters() {
+        let sample_rate = 48000.0;
+        let mut shape = IrShape::NONE;
+        
+        // Stereo IR: DC on the left, Nyquist on the right
+        let frames = 4800;
+        let ir: Vec<f32> = (0..frames).flat_map(|i| [1.0, if i % 2 == 0 { 1.0 } else { -1.0 }]).collect();
+        let tail_level = |shaped: &[f32], channel: usize| {
+            shaped.iter().skip(channel).step_by(2).skip(frames / 2).fold(0.0f32, |max, x| max.max(x.abs()))
+        };
+        
+        // The low cut removes DC but keeps Nyquist, the high cut the reverse
+        shape.low_cut_hz = Some(MAX_IR_LOW_CUT_HZ);
+        let shaped = shape_ir(&ir, 2, shape, sample_rate);
+        assert!(tail_level(&shaped, 0) < 1e-3 && tail_level(&shaped, 1) > 0.99);
+        shape.low_cut_hz = None;
+        shape.high_cut_hz = Some(MIN_IR_HIGH_CUT_HZ);
+        let shaped = shape_ir(&ir, 2, shape, sample_rate);
+        assert!(tail_level(&shaped, 0) > 0.99 && tail_level(&shaped, 1) < 1e-3);
+        
+        // Filters start from silence, so shaping is repeatable
+        assert_eq!(shape_ir(&ir, 2, shape, sample_rate), shaped);
+        
+        // Through the engine: extreme cutoffs leave the partitions (and so
+        // the output) bit-identical to no filtering
+        let _guard = memory::test_lock();
+        memory::init_engine(sample_rate, 128);
+        let ir = signal(6000, 41);
+        let render = |low_cut_hz: f32, high_cut_hz: f32| {
+            unsafe {
+                std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len()).copy_from_slice(&ir);
+            }
+            set_ir_filters(low_cut_hz, high_cut_hz);
+            load_ir(core::ptr::null(), ir.len() as u32, 1, 0.0);
+            reset();
+            let mut output = Vec::new();
+            for block in signal(128 * 60, 3).chunks(128) {
+                unsafe {
+                    for (i, &x) in block.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(1.0, 0.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            output
+        };
+        set_ir_filters(0.0, f32::INFINITY);
+        assert_eq!(ensure_state().ir_shape, IrShape::NONE);
+        let unfiltered = render(0.0, f32::INFINITY);
+        assert_eq!(render(MIN_IR_LOW_CUT_HZ, MAX_IR_HIGH_CUT_HZ), unfiltered);
+        assert_ne!(render(200.0, 5000.0), unfiltered);
+        assert_eq!(render(f32::NAN, f32::NAN), unfiltered);
+    }
+    
+    #[test]
+    fn test_ir_decay_rebuilds_without_interrupting_audio() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        let ir: Vec<f32> = signal(48000 * 2, 11)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * (-(i as f32) / 20000.0).exp())
+            .collect();
+        
+        // Shaping: trimmed to 0.5s with a 5ms fade, and faded to -60dB
+        let shape = IrShape { decay: 1.0, trim_seconds: 0.5, ..IrShape::NONE };
+        let shaped = shape_ir(&ir, 1, shape, 48000.0);
+        assert_eq!(shaped.len(), 24000);
+        assert_eq!(shaped[0], ir[0]);
+        assert!((shaped[12000] / ir[12000] - 10f32.powf(-1.5)).abs() < 1e-3);
+        assert!(shaped[23999].abs() < ir[23999].abs() / 200.0);
+        assert_eq!(shape_ir(&ir, 1, IrShape::NONE, 48000.0), ir);
+        
+        // The same run with and without a decay change part way through
+        let input = signal(128 * 200, 99);
+        let run = |change_at: Option<usize>| {
+            set_ir_decay(0.0, 0.0);
+            render_wet(&ir, 1, 0);
+            let (mut output, mut swap_block) = (Vec::new(), None);
+            for (block, samples) in input.chunks(128).enumerate() {
+                if Some(block) == change_at {
+                    set_ir_decay(1.0, 0.5);
+                }
+                unsafe {
+                    for (i, &x) in samples.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                let pending = ensure_state().rebuild.is_some();
+                process(1.0, 0.0);
+                if pending && ensure_state().rebuild.is_none() {
+                    swap_block = Some(block);
+                }
+                unsafe { output.extend_from_slice(memory::output_slice_mut(0)) };
+            }
+            (output, swap_block)
+        };
+        let (unchanged, _) = run(None);
+        let (changed, swap_block) = run(Some(20));
+        
+        // The rebuild is spread over several blocks, and the old IR plays
+        // on untouched until the swap
+        let swap_block = swap_block.expect("rebuild never finished");
+        assert!(swap_block > 21, "rebuilt in one block");
+        assert_eq!(&changed[..swap_block * 128], &unchanged[..swap_block * 128]);
+        
+        // Afterwards the wet signal is the shaped IR's
+        set_ir_decay(0.0, 0.0);
+        let (shaped_wet, _) = render_wet(&shaped, 1, 200);
+        set_ir_decay(1.0, 0.5);
+        let (rebuilt_wet, _) = render_wet(&ir, 1, 200);
+        let peak = simd_utils::find_peak(&shaped_wet);
+        for (a, b) in rebuilt_wet.iter().zip(&shaped_wet) {
+            assert!((a - b).abs() < peak * 1e-4, "{a} vs {b}");
         }
-        state.input_pos = 0;
-        state.fdl_pos = 0;
+        
+        set_ir_decay(0.0, 0.0);
+    }
+    
+    #[test]
+    fn test_ir_swap_crossfades_without_gap() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        let decaying = |len: usize, seed: u32, tau: f32| -> Vec<f32> {
+            signal(len, seed).iter().enumerate().map(|(i, x)| x * (-(i as f32) / tau).exp()).collect()
+        };
+        // The new IR is shorter, so the delay lines shrink after the swap
+        let ir_a = decaying(24000, 21, 6000.0);
+        let ir_b = decaying(12000, 22, 3000.0);
+        
+        // Left output with `first` loaded, switching to `second` at a block
+        let input = signal(128 * 400, 99);
+        let run = |first: &[f32], second: Option<(usize, &[f32])>| {
+            render_wet(first, 1, 0);
+            let (mut output, mut install_block) = (Vec::new(), None);
+            for (block, samples) in input.chunks(128).enumerate() {
+                if let Some((at, ir)) = second.filter(|&(at, _)| at == block) {
+                    unsafe {
+                        std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len()).copy_from_slice(ir);
+                    }
+                    assert_eq!(load_ir(core::ptr::null(), ir.len() as u32, 1, 0.0), LOAD_OK);
+                    assert!(ensure_state().rebuild.is_some(), "load at block {at} wasn't deferred");
+                }
+                unsafe {
+                    for (i, &x) in samples.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(1.0, 0.0);
+                if install_block.is_none() && ensure_state().crossfade_len > 0 {
+                    install_block = Some(block);
+                }
+                unsafe { output.extend_from_slice(memory::output_slice_mut(0)) };
+            }
+            (output, install_block)
+        };
+        let (only_a, _) = run(&ir_a, None);
+        let (only_b, _) = run(&ir_b, None);
+        let (swapped, install_block) = run(&ir_a, Some((50, &ir_b)));
+        
+        // The old IR plays on untouched while the new one is built
+        let install_block = install_block.expect("new IR never installed");
+        assert!(install_block > 50, "built in one block");
+        assert_eq!(&swapped[..install_block * 128], &only_a[..install_block * 128]);
+        
+        // No gap: every block around the swap keeps most of the wet level
+        let rms = |x: &[f32]| (x.iter().map(|y| y * y).sum::<f32>() / x.len() as f32).sqrt();
+        for block in install_block - 2..install_block + 40 {
+            let range = block * 128..(block + 1) * 128;
+            let expected = rms(&only_a[range.clone()]).min(rms(&only_b[range.clone()]));
+            let level = rms(&swapped[range]);
+            assert!(level > 0.5 * expected, "block {block}: wet level {level} vs {expected}");
+        }
+        
+        // Once the fade is over the delay lines fit the new IR, and after
+        // the old input has passed through it the output is the new IR's
+        let state = ensure_state();
+        assert_eq!(state.crossfade_len, 0);
+        for segment in &state.segments {
+            assert_eq!(segment.num_partitions, segment.partitions_for(ir_b.len()));
+            assert!(segment.outgoing_partitions_l.is_empty());
+        }
+        let settled = (install_block + 150) * 128;
+        let peak = simd_utils::find_peak(&only_b[settled..]);
+        for (a, b) in swapped[settled..].iter().zip(&only_b[settled..]) {
+            assert!((a - b).abs() < peak * 1e-4, "{a} vs {b}");
+        }
+    }
+    
+    #[test]
+    fn test_dry_wet_automation_ramps() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        render_wet(&[1.0], 1, 0);
+        
+        // Left wet muted: with a constant input the left output is the dry
+        // gain, cos(dry_wet·π/2)
+        set_wet_gains(0.0, 1.0);
+        let run = |dry_wet: f32| {
+            unsafe {
+                for i in 0..128 {
+                    *memory::get_input_buffer(0).add(i) = 1.0;
+                    *memory::get_input_buffer(1).add(i) = 1.0;
+                }
+            }
+            process(dry_wet, 0.0);
+            unsafe { memory::output_slice_mut(0).to_vec() }
+        };
+        
+        // The first block after a reset starts at its mix
+        assert!(run(0.0).iter().all(|&y| y == 1.0));
+        
+        // Jumping from 0 to 1 in one block glides instead of stepping
+        let output = run(1.0);
+        let mut previous = 1.0;
+        for &y in &output {
+            assert!(y < previous && previous - y < 0.01, "stepped {previous} -> {y}");
+            previous = y;
+        }
+        let value = ensure_state().dry_wet.value();
+        assert!(value > 0.1 && value < 0.5, "mix after one block {value}");
+        
+        // And keeps going until it arrives
+        for _ in 0..100 {
+            run(1.0);
+        }
+        assert!(ensure_state().dry_wet.value() > 0.999);
+        
+        set_wet_gains(1.0, 1.0);
+    }
+    
+    #[test]
+    fn test_wet_width() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        // Stereo IR with different channels (interleaved)
+        let ir = signal(2 * 2000, 11);
+        let (left, right) = render_wet(&ir, 2, 40);
+        
+        // Width 1 is bit-exact
+        set_wet_width(1.0);
+        assert_eq!(render_wet(&ir, 2, 40), (left.clone(), right.clone()));
+        
+        // Width 0 collapses the wet signal to its mid
+        set_wet_width(0.0);
+        let (mono_l, mono_r) = render_wet(&ir, 2, 40);
+        assert_eq!(mono_l, mono_r);
+        for i in 0..left.len() {
+            assert!((mono_l[i] - (left[i] + right[i]) * 0.5).abs() < 1e-5);
+        }
+        
+        // Extreme widths are capped at twice the side signal
+        set_wet_width(100.0);
+        let (wide_l, wide_r) = render_wet(&ir, 2, 40);
+        for i in 0..left.len() {
+            assert!(((wide_l[i] - wide_r[i]) - 2.0 * (left[i] - right[i])).abs() < 1e-4);
+        }
+        
+        set_wet_width(1.0);
+    }
+    
+    #[test]
+    fn test_wet_compressor_reduces_dynamic_range() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        // Fully wet through a unit impulse: 200 blocks of a loud 1kHz sine,
+        // then 200 blocks 20dB quieter
+        let render = || {
+            unsafe { *memory::get_ir_ptr() = 1.0 };
+            load_ir(core::ptr::null(), 1, 1, 0.0);
+            reset();
+            let mut output = Vec::new();
+            for block in 0..400 {
+                let level = if block < 200 { 0.5 } else { 0.05 };
+                unsafe {
+                    for i in 0..128 {
+                        let x = level * (core::f32::consts::TAU * 1000.0 * (block * 128 + i) as f32 / 48000.0).sin();
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(1.0, 0.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            output
+        };
+        let rms_db = |samples: &[f32]| {
+            utils::linear_to_db((samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt())
+        };
+        let uncompressed = render();
+        
+        // Ratio 1 is bit-exact
+        set_wet_compressor(-40.0, 1.0, 5.0, 50.0, 6.0, FollowerMode::Peak);
+        assert_eq!(render(), uncompressed);
+        
+        // Both levels are above the threshold, so 4:1 turns the 20dB step
+        // into 5dB
+        for detector in [FollowerMode::Peak, FollowerMode::Rms] {
+            set_wet_compressor(-40.0, 4.0, 5.0, 50.0, 0.0, detector);
+            let compressed = render();
+            let loud = rms_db(&compressed[150 * 128..200 * 128]);
+            let quiet = rms_db(&compressed[350 * 128..400 * 128]);
+            assert!((loud - quiet - 5.0).abs() < 1.0, "{detector:?}: {}dB range", loud - quiet);
+            
+            // Past the attack on the first onset, the gain follows the drop
+            // to the quiet level smoothly (at the release rate, no jumps)
+            let gains: Vec<f32> = compressed
+                .chunks(128)
+                .zip(uncompressed.chunks(128))
+                .map(|(block, open)| rms_db(block) - rms_db(open))
+                .collect();
+            for (block, pair) in gains.windows(2).enumerate().skip(16) {
+                assert!((pair[1] - pair[0]).abs() < 1.0, "{detector:?}: gain jumps after block {block}");
+            }
+        }
+        
+        set_wet_compressor(0.0, 1.0, 10.0, 200.0, 0.0, FollowerMode::Peak);
+    }
+    
+    #[test]
+    fn test_mix_laws_with_correlated_signals() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        // Unit impulse IR and a sine with a 64-sample period: the wet signal
+        // is the input delayed by 128 samples, in phase with the dry one
+        let sine = |i: usize| (2.0 * core::f32::consts::PI * i as f32 / 64.0).sin();
+        let rms = |dry_wet: f32| {
+            render_wet(&[1.0], 1, 0);
+            let mut block_rms = 0.0;
+            for block in 0..20 {
+                unsafe {
+                    for i in 0..128 {
+                        *memory::get_input_buffer(0).add(i) = sine(block * 128 + i);
+                        *memory::get_input_buffer(1).add(i) = sine(block * 128 + i);
+                    }
+                }
+                process(dry_wet, 0.0);
+                let output = unsafe { memory::output_slice_mut(0) };
+                block_rms = (output.iter().map(|y| y * y).sum::<f32>() / 128.0).sqrt();
+            }
+            block_rms
+        };
+        let sine_rms = core::f32::consts::FRAC_1_SQRT_2;
+        
+        // Equal-power: correlated signals add up to +3dB at the center
+        // (uncorrelated ones would stay level)
+        for (dry_wet, gain) in [(0.0, 1.0), (0.5, core::f32::consts::SQRT_2), (1.0, 1.0)] {
+            let level = rms(dry_wet);
+            assert!((level - gain * sine_rms).abs() < 1e-3, "equal-power mix {dry_wet}: rms {level}");
+        }
+        
+        // Linear: correlated signals stay level across the mix
+        set_linear_mix(true);
+        for dry_wet in [0.0, 0.5, 1.0] {
+            let level = rms(dry_wet);
+            assert!((level - sine_rms).abs() < 1e-3, "linear mix {dry_wet}: rms {level}");
+        }
+        set_linear_mix(false);
+    }
+    
+    #[test]
+    fn test_ir_load_status_and_empty_ir() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        // Fill the region and poison what follows it, so reading past
+        // MAX_IR_FRAMES would turn the output into NaN
+        let region_end = memory::IR_OFFSET + memory::MAX_IR_SAMPLES * 4;
+        let poison_len = (memory::FFT_OFFSET - region_end) / 4;
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_ir_ptr(), memory::MAX_IR_SAMPLES).fill(0.0);
+            *memory::get_ir_ptr() = 1.0;
+            std::slice::from_raw_parts_mut(memory::region_ptr(region_end) as *mut f32, poison_len)
+                .fill(f32::NAN);
+        }
+        
+        assert_eq!(load_ir(core::ptr::null(), u32::MAX, 2, 0.0), LOAD_TRUNCATED);
+        assert_eq!(unsafe { memory::ir_slice() }.len(), memory::MAX_IR_SAMPLES);
+        for block in signal(128 * 4, 99).chunks(128) {
+            unsafe {
+                for (i, &x) in block.iter().enumerate() {
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(1.0, 0.0);
+            let (left, right) = unsafe { (memory::output_slice_mut(0), memory::output_slice_mut(1)) };
+            assert!(left.iter().chain(right.iter()).all(|x| x.is_finite()), "IR load read past its region");
+        }
+        render_wet(&[1.0, 1.0], 2, 1);
+        
+        // Invalid channel counts keep the loaded IR
+        assert_eq!(load_ir(core::ptr::null(), 100, 0, 0.0), LOAD_REJECTED);
+        assert_eq!(load_ir(core::ptr::null(), 100, 3, 0.0), LOAD_REJECTED);
+        assert!(memory::is_ir_ready());
+        assert_eq!(unsafe { memory::ir_slice() }.len(), 2);
+        
+        // An empty IR unloads convolution, which then passes input through
+        assert_eq!(load_ir(core::ptr::null(), 0, 1, 0.0), LOAD_OK);
+        assert!(!memory::is_ir_ready());
+        unsafe {
+            for i in 0..128 {
+                *memory::get_input_buffer(0).add(i) = i as f32;
+                *memory::get_input_buffer(1).add(i) = -(i as f32);
+            }
+        }
+        process(1.0, 0.0);
+        unsafe {
+            assert_eq!(memory::output_slice_mut(0), memory::input_slice(0));
+            assert_eq!(memory::output_slice_mut(1), memory::input_slice(1));
+            std::slice::from_raw_parts_mut(memory::region_ptr(region_end) as *mut f32, poison_len)
+                .fill(0.0);
+        }
+    }
+    
+    #[test]
+    fn test_matches_direct_convolution() {
+        let _guard = memory::test_lock();
+        
+        // Host blocks shorter than, longer than and not dividing a head
+        // block, at the default and other head block sizes; each must give
+        // the direct convolution at a constant latency
+        for (head_block, buffer_size, expected_latency) in [
+            (DEFAULT_HEAD_BLOCK_SIZE, 128, 128),
+            (DEFAULT_HEAD_BLOCK_SIZE, 192, 192),
+            (DEFAULT_HEAD_BLOCK_SIZE, DEFAULT_HEAD_BLOCK_SIZE, 0),
+            (DEFAULT_HEAD_BLOCK_SIZE, 512, 0),
+            (128, 128, 0),
+            (512, 192, 448),
+        ] {
+            // A decaying IR reaching well into the last segment, so every
+            // segment boundary is crossed
+            let ir_len = segment_offsets(head_block)[NUM_SEGMENTS - 1] + 5000;
+            let ir: Vec<f32> = signal(ir_len, 7)
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x * (-(i as f32) / 3000.0).exp())
+                .collect();
+            
+            // The IR is loaded before the block size changes, so it has to
+            // be partitioned again for the new segments
+            memory::init_engine(48000.0, buffer_size as u32);
+            unsafe {
+                std::slice::from_raw_parts_mut(memory::get_ir_ptr(), ir.len()).copy_from_slice(&ir);
+            }
+            assert_eq!(load_ir(core::ptr::null(), ir_len as u32, 1, 0.0), LOAD_OK);
+            assert_eq!(set_block_size(head_block as u32), head_block as u32);
+            assert!(
+                ensure_state().segments.iter().all(|s| s.num_partitions > 0),
+                "IR should use every segment"
+            );
+            reset();
+            let latency = latency_samples() as usize;
+            assert_eq!(latency, expected_latency);
+            
+            // Different input per channel through the shared mono IR
+            let len = (ir_len + 4096).next_multiple_of(buffer_size);
+            let input_l = signal(len, 99);
+            let input_r = signal(len, 5);
+            let (mut wet_l, mut wet_r) = (Vec::new(), Vec::new());
+            for (block_l, block_r) in input_l.chunks(buffer_size).zip(input_r.chunks(buffer_size)) {
+                unsafe {
+                    for i in 0..buffer_size {
+                        *memory::get_input_buffer(0).add(i) = block_l[i];
+                        *memory::get_input_buffer(1).add(i) = block_r[i];
+                    }
+                }
+                process(1.0, 0.0);
+                unsafe {
+                    wet_l.extend_from_slice(memory::output_slice_mut(0));
+                    wet_r.extend_from_slice(memory::output_slice_mut(1));
+                }
+            }
+            
+            for (input, wet) in [(&input_l, &wet_l), (&input_r, &wet_r)] {
+                assert!(wet[..latency].iter().all(|&y| y == 0.0), "wet before latency ({buffer_size})");
+                let mut max_error = 0.0f64;
+                let mut peak = 0.0f64;
+                for (n, &y) in wet[latency..].iter().enumerate() {
+                    let expected: f64 = (0..=n.min(ir_len - 1))
+                        .map(|k| input[n - k] as f64 * ir[k] as f64)
+                        .sum();
+                    max_error = max_error.max((y as f64 - expected).abs());
+                    peak = peak.max(expected.abs());
+                }
+                assert!(
+                    max_error < peak * 1e-4,
+                    "max error {max_error} (peak {peak}, block {buffer_size}, head {head_block})"
+                );
+            }
+        }
+        
+        // Sizes round up to a power of two within range
+        assert_eq!(set_block_size(100), 128);
+        assert_eq!(set_block_size(0), MIN_HEAD_BLOCK_SIZE as u32);
+        assert_eq!(set_block_size(5000), MAX_HEAD_BLOCK_SIZE as u32);
+        assert_eq!(set_block_size(DEFAULT_HEAD_BLOCK_SIZE as u32), DEFAULT_HEAD_BLOCK_SIZE as u32);
     }
 }
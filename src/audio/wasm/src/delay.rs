@@ -2,8 +2,10 @@
 //! 
 //! Implements various delay-based effects:
 //! - Simple delay with feedback
-//! - Comb filters (feedforward and feedback)
+//! - Comb filters (feedforward and feedback), tunable to fractional delays
 //! - All-pass filters (for diffusion)
+//! - All-pass diffuser: a stereo chain of all-passes that smears
+//!   transients into a dense wash (pre-reverb or standalone)
 //! - Stereo ping-pong delay
 //!
 //! # Zero-Allocation Design
@@ -11,6 +13,8 @@
 //! Maximum delay time is determined by MAX_DELAY_SAMPLES constant.
 
 use crate::filters::OnePole;
+use crate::smoothing::SmoothedParam;
+use crate::utils;
 
 // ============================================================================
 // CONSTANTS
@@ -22,6 +26,34 @@ const MAX_DELAY_SAMPLES: usize = 96000;
 /// Maximum delay for all-pass (shorter for memory efficiency)
 const MAX_ALLPASS_SAMPLES: usize = 4096;
 
+/// Maximum comb filter feedback magnitude (close to 1, so a tuned comb can
+/// ring for seconds)
+const MAX_COMB_FEEDBACK: f32 = 0.9999;
+
+/// Most all-pass stages in a diffuser
+pub const MAX_DIFFUSER_STAGES: usize = 8;
+
+/// Diffuser all-pass delays per channel in samples at 48kHz (primes,
+/// different per channel so the channels smear differently)
+const DIFFUSER_DELAYS: [[usize; MAX_DIFFUSER_STAGES]; 2] = [
+    [113, 149, 197, 257, 337, 443, 577, 751],
+    [109, 157, 191, 269, 331, 449, 571, 761],
+];
+
+/// Sample rate the diffuser delays are tuned at
+const DIFFUSER_TUNING_RATE: f32 = 48000.0;
+
+/// Diffuser size range (delay scale)
+const MIN_DIFFUSER_SIZE: f32 = 0.1;
+const MAX_DIFFUSER_SIZE: f32 = 2.0;
+
+/// Room left below MAX_ALLPASS_SAMPLES for rounding the longest diffuser
+/// delay up to a prime
+const DIFFUSER_PRIME_HEADROOM: usize = 64;
+
+/// Highest diffuser coefficient
+const MAX_DIFFUSION: f32 = 0.9;
+
 // ============================================================================
 // SIMPLE DELAY LINE
 // ============================================================================
@@ -33,12 +65,13 @@ const MAX_ALLPASS_SAMPLES: usize = 4096;
 /// - Feedback with damping filter
 /// - Dry/wet mix control
 /// - Linear interpolation for fractional delays
+/// - Feedback and mix changes are smoothed per sample
 pub struct DelayLine {
     buffer: [f32; MAX_DELAY_SAMPLES],
     write_pos: usize,
     delay_samples: f32,
-    feedback: f32,
-    mix: f32,
+    feedback: SmoothedParam,
+    mix: SmoothedParam,
     damping: OnePole,
 }
 
@@ -55,8 +88,8 @@ impl DelayLine {
             buffer: [0.0; MAX_DELAY_SAMPLES],
             write_pos: 0,
             delay_samples: 1000.0,
-            feedback: 0.5,
-            mix: 0.5,
+            feedback: SmoothedParam::new(0.5),
+            mix: SmoothedParam::new(0.5),
             damping: OnePole::new(),
         }
     }
@@ -74,12 +107,28 @@ impl DelayLine {
     
     /// Set feedback amount (0-1, can be slightly higher for resonance)
     pub fn set_feedback(&mut self, feedback: f32) {
-        self.feedback = feedback.clamp(0.0, 0.99);
+        self.feedback.set_target(feedback.clamp(0.0, 0.99));
     }
     
     /// Set dry/wet mix (0 = dry, 1 = wet)
     pub fn set_mix(&mut self, mix: f32) {
-        self.mix = mix.clamp(0.0, 1.0);
+        self.mix.set_target(mix.clamp(0.0, 1.0));
+    }
+    
+    /// Set how fast feedback and mix glide to new settings
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Smoothing time constant (0 = change instantly)
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn set_smoothing_time(&mut self, time_ms: f32, sample_rate: f32) {
+        self.feedback.set_time(time_ms, sample_rate);
+        self.mix.set_time(time_ms, sample_rate);
+    }
+    
+    /// Jump straight to the latest feedback and mix (for initialization)
+    pub fn snap_params(&mut self) {
+        self.feedback.snap();
+        self.mix.snap();
     }
     
     /// Set damping filter frequency
@@ -105,13 +154,14 @@ impl DelayLine {
         let delayed_damped = self.damping.process(delayed);
         
         // Write to buffer with feedback
-        self.buffer[self.write_pos] = input + delayed_damped * self.feedback;
+        self.buffer[self.write_pos] = input + delayed_damped * self.feedback.next();
         
         // Advance write position
         self.write_pos = (self.write_pos + 1) % MAX_DELAY_SAMPLES;
         
         // Mix dry and wet signals
-        input * (1.0 - self.mix) + delayed * self.mix
+        let mix = self.mix.next();
+        input * (1.0 - mix) + delayed * mix
     }
     
     /// Clear the delay buffer
@@ -127,61 +177,138 @@ impl DelayLine {
 
 /// Feedback comb filter
 /// 
-/// Used in reverb algorithms (Schroeder reverb, etc.)
+/// Used in reverb algorithms (Schroeder reverb, etc.) and, tuned to a
+/// pitch, as a plucked-string resonator.
 /// y[n] = x[n] + g * y[n-M]
-pub struct CombFilter {
-    buffer: [f32; MAX_DELAY_SAMPLES],
+/// 
+/// `set_delay_samples_frac` tunes the loop to a fractional delay: an
+/// all-pass interpolator on the read tap adds the fraction without the
+/// high frequency loss of linear interpolation, which would compound on
+/// every trip around the loop. `N` is the buffer length in samples (~2
+/// seconds by default).
+pub struct CombFilter<const N: usize = MAX_DELAY_SAMPLES> {
+    buffer: [f32; N],
     write_pos: usize,
     delay_samples: usize,
     feedback: f32,
     damping: OnePole,
+    /// Fractional part of the delay (used after `set_delay`)
+    interpolator: AllPassInterpolator,
+    interpolate: bool,
+    /// Loop period set by `set_delay_samples_frac`, kept to retune when
+    /// the damping changes
+    tuned_delay: Option<f32>,
 }
 
-impl Default for CombFilter {
+impl<const N: usize> Default for CombFilter<N> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CombFilter {
+impl<const N: usize> CombFilter<N> {
     /// Create a new comb filter
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
-            buffer: [0.0; MAX_DELAY_SAMPLES],
+            buffer: [0.0; N],
             write_pos: 0,
-            delay_samples: 1000,
+            delay_samples: if N > 1000 { 1000 } else { N / 2 },
             feedback: 0.5,
             damping: OnePole::new(),
+            interpolator: AllPassInterpolator::new(),
+            interpolate: false,
+            tuned_delay: None,
         }
     }
     
     /// Set delay time in samples
     pub fn set_delay_samples(&mut self, samples: usize) {
-        self.delay_samples = samples.min(MAX_DELAY_SAMPLES - 1).max(1);
+        self.delay_samples = samples.min(N - 1).max(1);
+        self.interpolate = false;
+        self.tuned_delay = None;
+    }
+    
+    /// Set a fractional delay time in samples, tuning the loop's
+    /// fundamental to `sample_rate / samples`
+    /// 
+    /// The damping filter's phase delay at the fundamental is taken out
+    /// of the buffer delay, so the loop stays in tune at any damping
+    /// (`set_damping` retunes it).
+    /// 
+    /// # Arguments
+    /// * `samples` - Loop period in samples (2 to N - 1)
+    pub fn set_delay_samples_frac(&mut self, samples: f32) {
+        let samples = samples.clamp(2.0, (N - 1) as f32);
+        self.tune(samples);
+        self.tuned_delay = Some(samples);
+    }
+    
+    fn tune(&mut self, samples: f32) {
+        let omega = core::f32::consts::TAU / samples;
+        let (_, damping_delay) = self.damping.response(omega);
+        self.set_delay(samples - damping_delay, omega);
+    }
+    
+    /// Set a fractional delay time in samples
+    /// 
+    /// The whole samples come from the buffer and the rest (0.5-1.5
+    /// samples) from the all-pass interpolator, whose delay is exact at
+    /// `omega`. An all-pass delay drifts with frequency, so pass the
+    /// frequency the comb is tuned to.
+    /// 
+    /// # Arguments
+    /// * `delay` - Delay in samples (1.5 to N - 1)
+    /// * `omega` - Frequency the fraction is exact at, in radians per
+    ///   sample (0 = low-frequency approximation)
+    pub fn set_delay(&mut self, delay: f32, omega: f32) {
+        let delay = delay.clamp(1.5, (N - 1) as f32);
+        let whole = (delay - 0.5).floor();
+        self.delay_samples = whole as usize;
+        self.interpolator.set_delay(delay - whole, omega);
+        self.interpolate = true;
+        self.tuned_delay = None;
     }
     
     /// Set feedback coefficient
     pub fn set_feedback(&mut self, feedback: f32) {
-        self.feedback = feedback.clamp(-0.99, 0.99);
+        self.feedback = feedback.clamp(-MAX_COMB_FEEDBACK, MAX_COMB_FEEDBACK);
+    }
+    
+    /// Feedback coefficient in effect (after clamping)
+    pub fn feedback(&self) -> f32 {
+        self.feedback
     }
     
     /// Set damping frequency (lowpass on feedback path)
     pub fn set_damping(&mut self, freq: f32, sample_rate: f32) {
         self.damping.set_lowpass(freq, sample_rate);
+        if let Some(samples) = self.tuned_delay {
+            self.tune(samples);
+            self.tuned_delay = Some(samples);
+        }
+    }
+    
+    /// Gain and phase delay of the damping filter at `omega` (radians per
+    /// sample), to compensate the loop for them
+    pub fn damping_response(&self, omega: f32) -> (f32, f32) {
+        self.damping.response(omega)
     }
     
     /// Process a single sample
     #[inline]
     pub fn process(&mut self, input: f32) -> f32 {
-        let read_pos = (self.write_pos + MAX_DELAY_SAMPLES - self.delay_samples) % MAX_DELAY_SAMPLES;
-        let delayed = self.buffer[read_pos];
+        let read_pos = (self.write_pos + N - self.delay_samples) % N;
+        let mut delayed = self.buffer[read_pos];
+        if self.interpolate {
+            delayed = self.interpolator.process(delayed);
+        }
         
         // Apply damping to feedback
         let feedback_signal = self.damping.process(delayed) * self.feedback;
         
         // Write input + feedback to buffer
         self.buffer[self.write_pos] = input + feedback_signal;
-        self.write_pos = (self.write_pos + 1) % MAX_DELAY_SAMPLES;
+        self.write_pos = (self.write_pos + 1) % N;
         
         delayed
     }
@@ -190,6 +317,63 @@ impl CombFilter {
     pub fn clear(&mut self) {
         self.buffer.fill(0.0);
         self.damping.reset();
+        self.interpolator.reset();
+    }
+}
+
+// ============================================================================
+// ALL-PASS INTERPOLATOR
+// ============================================================================
+
+/// First-order all-pass fractional delay
+/// 
+/// y[n] = a*x[n] + x[n-1] - a*y[n-1]
+/// 
+/// Unity gain at every frequency, so it can sit inside a feedback loop.
+/// Its delay varies with frequency; `set_delay` makes it exact at one.
+#[derive(Clone, Copy)]
+pub struct AllPassInterpolator {
+    coefficient: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl AllPassInterpolator {
+    pub const fn new() -> Self {
+        Self {
+            coefficient: 0.0,
+            x1: 0.0,
+            y1: 0.0,
+        }
+    }
+    
+    /// Set the delay
+    /// 
+    /// # Arguments
+    /// * `delay` - Delay in samples (best kept within 0.5-1.5, where the
+    ///   coefficient stays small)
+    /// * `omega` - Frequency the delay is exact at, in radians per sample
+    ///   (0 = the low-frequency approximation `(1 - d) / (1 + d)`)
+    pub fn set_delay(&mut self, delay: f32, omega: f32) {
+        self.coefficient = if omega > 0.0 {
+            (0.5 * omega * (1.0 - delay)).sin() / (0.5 * omega * (1.0 + delay)).sin()
+        } else {
+            (1.0 - delay) / (1.0 + delay)
+        };
+    }
+    
+    /// Process a single sample
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.coefficient * (x - self.y1) + self.x1;
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+    
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.y1 = 0.0;
     }
 }
 
@@ -217,7 +401,7 @@ impl Default for AllPassFilter {
 
 impl AllPassFilter {
     /// Create a new all-pass filter
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             buffer: [0.0; MAX_ALLPASS_SAMPLES],
             write_pos: 0,
@@ -256,6 +440,132 @@ impl AllPassFilter {
     }
 }
 
+// ============================================================================
+// DIFFUSER
+// ============================================================================
+
+/// Stereo all-pass diffuser
+/// 
+/// A series of all-passes per channel turns each input sample into a dense
+/// cloud of echoes over a few to tens of milliseconds, without colouring
+/// the spectrum: the chain is all-pass, so its magnitude response is flat
+/// and it keeps the input's energy. The stage delays are mutually prime,
+/// so no two stages' echoes pile up on the same sample.
+pub struct Diffuser {
+    allpasses: [[AllPassFilter; MAX_DIFFUSER_STAGES]; 2],
+    /// Stages in use
+    stages: usize,
+    /// All-pass coefficient
+    amount: f32,
+    /// Size and sample rate the delays are set for (0 = not yet)
+    size: f32,
+    sample_rate: f32,
+}
+
+impl Default for Diffuser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Diffuser {
+    /// Create a four-stage diffuser (call `set_size` before processing)
+    pub const fn new() -> Self {
+        Self {
+            allpasses: [const { [const { AllPassFilter::new() }; MAX_DIFFUSER_STAGES] }; 2],
+            stages: 4,
+            amount: 0.5,
+            size: 0.0,
+            sample_rate: 0.0,
+        }
+    }
+    
+    /// Set the number of all-pass stages (1-8, more is denser)
+    /// 
+    /// Stages switched back in start from silence.
+    pub fn set_stages(&mut self, stages: usize) {
+        let stages = stages.clamp(1, MAX_DIFFUSER_STAGES);
+        for channel in self.allpasses.iter_mut() {
+            for allpass in channel[self.stages.min(stages)..stages].iter_mut() {
+                allpass.clear();
+            }
+        }
+        self.stages = stages;
+    }
+    
+    /// Set the all-pass coefficient (0-0.9, 0 = plain delay, higher
+    /// smears longer)
+    pub fn set_amount(&mut self, amount: f32) {
+        let amount = if amount.is_nan() { 0.0 } else { amount.clamp(0.0, MAX_DIFFUSION) };
+        if amount == self.amount {
+            return;
+        }
+        self.amount = amount;
+        for allpass in self.allpasses.iter_mut().flatten() {
+            allpass.set_coefficient(amount);
+        }
+    }
+    
+    /// Scale the stage delays
+    /// 
+    /// The delays are scaled to the sample rate and rounded to distinct
+    /// primes. At high sample rates the scale is capped so the longest
+    /// delay fits MAX_ALLPASS_SAMPLES.
+    /// 
+    /// # Arguments
+    /// * `size` - Delay scale (0.1-2, 1 = about 2-16ms)
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn set_size(&mut self, size: f32, sample_rate: f32) {
+        let size = if size.is_nan() { 1.0 } else { size.clamp(MIN_DIFFUSER_SIZE, MAX_DIFFUSER_SIZE) };
+        if size == self.size && sample_rate == self.sample_rate {
+            return;
+        }
+        self.size = size;
+        self.sample_rate = sample_rate;
+        
+        let longest = DIFFUSER_DELAYS.iter().flatten().copied().max().unwrap_or(1);
+        let max_scale = (MAX_ALLPASS_SAMPLES - DIFFUSER_PRIME_HEADROOM) as f32 / longest as f32;
+        let scale = (size * sample_rate / DIFFUSER_TUNING_RATE).min(max_scale);
+        for (channel, delays) in self.allpasses.iter_mut().zip(DIFFUSER_DELAYS) {
+            let mut used = [0; MAX_DIFFUSER_STAGES];
+            for (stage, (allpass, delay)) in channel.iter_mut().zip(delays).enumerate() {
+                // Distinct primes are mutually prime
+                let mut samples = ((delay as f32 * scale).round() as usize).max(2);
+                while !is_prime(samples) || used[..stage].contains(&samples) {
+                    samples += 1;
+                }
+                used[stage] = samples;
+                allpass.set_delay_samples(samples);
+            }
+        }
+    }
+    
+    /// Process stereo samples
+    #[inline]
+    pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let [left_stages, right_stages] = &mut self.allpasses;
+        let mut l = left;
+        let mut r = right;
+        for (left_allpass, right_allpass) in left_stages[..self.stages].iter_mut().zip(&mut right_stages[..self.stages]) {
+            l = left_allpass.process(l);
+            r = right_allpass.process(r);
+        }
+        (l, r)
+    }
+    
+    /// Clear all stages
+    pub fn clear(&mut self) {
+        for allpass in self.allpasses.iter_mut().flatten() {
+            allpass.clear();
+        }
+    }
+}
+
+/// Whether `n` is prime (trial division, for the few delay lengths)
+fn is_prime(n: usize) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
+
 // ============================================================================
 // STEREO PING-PONG DELAY
 // ============================================================================
@@ -360,27 +670,29 @@ impl PingPongDelay {
 /// Modulated delay line for chorus/flanger effects
 /// 
 /// Uses fractional delay with cubic interpolation for smooth modulation.
-pub struct ModulatedDelay {
-    buffer: [f32; MAX_DELAY_SAMPLES],
+/// `N` is the buffer length in samples (short for flangers, ~2 seconds by
+/// default).
+pub struct ModulatedDelay<const N: usize = MAX_DELAY_SAMPLES> {
+    buffer: [f32; N],
     write_pos: usize,
     base_delay: f32,
     mod_depth: f32,
     feedback: f32,
 }
 
-impl Default for ModulatedDelay {
+impl<const N: usize> Default for ModulatedDelay<N> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl ModulatedDelay {
+impl<const N: usize> ModulatedDelay<N> {
     /// Create a new modulated delay
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
-            buffer: [0.0; MAX_DELAY_SAMPLES],
+            buffer: [0.0; N],
             write_pos: 0,
-            base_delay: 500.0,
+            base_delay: if N > 1000 { 500.0 } else { (N / 2) as f32 },
             mod_depth: 100.0,
             feedback: 0.0,
         }
@@ -388,7 +700,7 @@ impl ModulatedDelay {
     
     /// Set base delay time in samples
     pub fn set_base_delay(&mut self, samples: f32) {
-        self.base_delay = samples.clamp(1.0, (MAX_DELAY_SAMPLES - 100) as f32);
+        self.base_delay = samples.clamp(2.0, N.saturating_sub(100).max(2) as f32);
     }
     
     /// Set modulation depth in samples
@@ -404,35 +716,23 @@ impl ModulatedDelay {
     /// Process with modulation input (typically LFO, range -1 to 1)
     #[inline]
     pub fn process(&mut self, input: f32, mod_signal: f32) -> f32 {
-        // Calculate modulated delay
+        // Calculate modulated delay (at least 2 samples, so the newest
+        // tap is already written, and 3 short of the buffer for the oldest)
         let delay = self.base_delay + mod_signal * self.mod_depth;
-        let delay = delay.clamp(1.0, (MAX_DELAY_SAMPLES - 1) as f32);
+        let delay = delay.clamp(2.0, (N - 3) as f32);
         
-        // Cubic interpolation for smooth modulation
+        // Cubic interpolation for smooth modulation: taps at delays
+        // d - 1, d, d + 1, d + 2, weighted towards the older side by frac
         let delay_int = delay as usize;
         let frac = delay - delay_int as f32;
-        
-        let idx0 = (self.write_pos + MAX_DELAY_SAMPLES - delay_int - 1) % MAX_DELAY_SAMPLES;
-        let idx1 = (self.write_pos + MAX_DELAY_SAMPLES - delay_int) % MAX_DELAY_SAMPLES;
-        let idx2 = (self.write_pos + MAX_DELAY_SAMPLES - delay_int + 1) % MAX_DELAY_SAMPLES;
-        let idx3 = (self.write_pos + MAX_DELAY_SAMPLES - delay_int + 2) % MAX_DELAY_SAMPLES;
-        
-        let y0 = self.buffer[idx0];
-        let y1 = self.buffer[idx1];
-        let y2 = self.buffer[idx2];
-        let y3 = self.buffer[idx3];
+        let tap = |offset: usize| self.buffer[(self.write_pos + N + 1 - delay_int - offset) % N];
         
         // Cubic interpolation (Catmull-Rom spline)
-        let c0 = y1;
-        let c1 = 0.5 * (y2 - y0);
-        let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
-        let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
-        
-        let delayed = ((c3 * frac + c2) * frac + c1) * frac + c0;
+        let delayed = utils::cubic_interp(tap(0), tap(1), tap(2), tap(3), frac);
         
         // Write with feedback
         self.buffer[self.write_pos] = input + delayed * self.feedback;
-        self.write_pos = (self.write_pos + 1) % MAX_DELAY_SAMPLES;
+        self.write_pos = (self.write_pos + 1) % N;
         
         delayed
     }
@@ -442,3 +742,139 @@ impl ModulatedDelay {
         self.buffer.fill(0.0);
     }
 }
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustfft::{num_complex::Complex, FftPlanner};
+    use std::f64::consts::PI;
+    
+    const SAMPLE_RATE: f32 = 44100.0;
+    
+    /// Impulse response of a comb
+    fn impulse_response(comb: &mut CombFilter, len: usize) -> Vec<f32> {
+        (0..len).map(|n| comb.process(if n == 0 { 1.0 } else { 0.0 })).collect()
+    }
+    
+    /// Frequency of the strongest resonance within ±10Hz of `freq`, by
+    /// golden-section search on the DTFT magnitude
+    fn resonant_peak(response: &[f32], freq: f64) -> f64 {
+        let magnitude = |f: f64| {
+            let omega = 2.0 * PI * f / SAMPLE_RATE as f64;
+            let (re, im) = response.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, &x)| {
+                let (sin, cos) = (omega * n as f64).sin_cos();
+                (re + x as f64 * cos, im - x as f64 * sin)
+            });
+            f64::hypot(re, im)
+        };
+        let (mut low, mut high) = (freq - 10.0, freq + 10.0);
+        let golden = (5.0f64.sqrt() - 1.0) / 2.0;
+        while high - low > 1e-3 {
+            let a = high - golden * (high - low);
+            let b = low + golden * (high - low);
+            if magnitude(a) > magnitude(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        0.5 * (low + high)
+    }
+    
+    #[test]
+    fn test_fractional_comb_tuning() {
+        for damping in [None, Some(5000.0)] {
+            for freq in [440.0f32, 1760.0] {
+                let period = SAMPLE_RATE / freq;
+                let mut comb = CombFilter::new();
+                comb.set_feedback(0.99);
+                comb.set_delay_samples_frac(period);
+                // Changing the damping after tuning keeps the loop in tune
+                if let Some(cutoff) = damping {
+                    comb.set_damping(cutoff, SAMPLE_RATE);
+                }
+                let peak = resonant_peak(&impulse_response(&mut comb, 16384), freq as f64);
+                let error = (peak - freq as f64).abs();
+                assert!(error < 1.0, "{freq}Hz, damping {damping:?}: peak at {peak}Hz");
+                
+                // The integer delay misses by more
+                comb.clear();
+                comb.set_delay_samples(period.round() as usize);
+                let rounded = resonant_peak(&impulse_response(&mut comb, 16384), freq as f64);
+                let rounded_error = (rounded - freq as f64).abs();
+                assert!(rounded_error > 2.0 * error, "{freq}Hz, damping {damping:?}: rounded peak at {rounded}Hz");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_diffuser_smears_impulse_without_colouring() {
+        const LEN: usize = 1 << 16;
+        for stages in [4, 8] {
+            let mut diffuser = Diffuser::new();
+            diffuser.set_stages(stages);
+            diffuser.set_amount(0.7);
+            diffuser.set_size(1.0, 48000.0);
+            let (left, right): (Vec<f32>, Vec<f32>) =
+                (0..LEN).map(|n| if n == 0 { diffuser.process_stereo(1.0, 1.0) } else { diffuser.process_stereo(0.0, 0.0) }).unzip();
+            
+            for response in [&left, &right] {
+                // All of the impulse's energy comes out
+                let energy: f32 = response.iter().map(|x| x * x).sum();
+                assert!((energy - 1.0).abs() < 1e-3, "{stages} stages: energy {energy}");
+                
+                // Spread over many echoes within the first 50ms, none of
+                // them dominant
+                let dense = response[..2400].iter().filter(|x| x.abs() > 1e-3).count();
+                let peak = response.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+                assert!(dense > 150 * stages, "{stages} stages: {dense} echoes");
+                assert!(peak < 0.5, "{stages} stages: peak {peak}");
+                
+                // Flat magnitude response
+                let mut spectrum: Vec<Complex<f32>> = response.iter().map(|&x| Complex::new(x, 0.0)).collect();
+                FftPlanner::new().plan_fft_forward(LEN).process(&mut spectrum);
+                for (bin, c) in spectrum[..LEN / 2].iter().enumerate() {
+                    let db = 10.0 * c.norm_sqr().log10();
+                    assert!(db.abs() < 0.1, "{stages} stages: bin {bin} at {db}dB");
+                }
+            }
+            // The channels smear differently
+            let correlation: f32 = left.iter().zip(&right).map(|(l, r)| l * r).sum();
+            assert!(correlation.abs() < 0.5, "{stages} stages: correlation {correlation}");
+        }
+    }
+    
+    #[test]
+    fn test_diffuser_delays_fit_and_are_mutually_prime() {
+        let gcd = |mut a: usize, mut b: usize| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        };
+        for sample_rate in [8000.0, 44100.0, 48000.0, 96000.0, 192000.0] {
+            for size in [0.0, 0.1, 0.5, 1.0, 2.0, 10.0] {
+                let mut diffuser = Diffuser::new();
+                diffuser.set_size(size, sample_rate);
+                for channel in &diffuser.allpasses {
+                    let delays: Vec<usize> = channel.iter().map(|allpass| allpass.delay_samples).collect();
+                    for (i, &a) in delays.iter().enumerate() {
+                        assert!(a < MAX_ALLPASS_SAMPLES, "{sample_rate}Hz size {size}: delay {a}");
+                        for &b in &delays[i + 1..] {
+                            assert_eq!(gcd(a, b), 1, "{sample_rate}Hz size {size}: {delays:?}");
+                        }
+                    }
+                }
+                // Scaled by the sample rate until the longest delay is capped
+                if size == 1.0 && sample_rate <= 96000.0 {
+                    let longest = diffuser.allpasses[0][MAX_DIFFUSER_STAGES - 1].delay_samples as f32;
+                    assert!((longest - 751.0 * sample_rate / 48000.0).abs() < 20.0, "{sample_rate}Hz: longest {longest}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! All-pass Diffuser
+//!
+//! Smears the input into a dense wash of echoes a few to tens of
+//! milliseconds long, without colouring its spectrum. Useful on its own
+//! for softening transients, or in front of a reverb (or the convolution
+//! engine) to thicken its early reflections.
+//!
+//! # Algorithm
+//! Per channel, up to eight all-passes in series with mutually prime
+//! delays (see `delay::Diffuser`). The left and right chains use
+//! different delays, so a mono input comes out decorrelated.
+
+use crate::delay::Diffuser;
+use crate::memory;
+use crate::utils;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Diffuser of every engine in the pool
+static mut STATES: [Diffuser; memory::MAX_ENGINES] = [const { Diffuser::new() }; memory::MAX_ENGINES];
+
+/// Diffuser of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Diffuser {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process the diffuser
+/// 
+/// # Arguments
+/// * `amount` - All-pass coefficient (0-0.9, higher smears longer)
+/// * `size` - Delay scale (0.1-2)
+/// * `stages` - All-passes per channel (1-8)
+/// * `mix` - Mix between dry (0) and wet (1), equal power
+pub fn process(amount: f32, size: f32, stages: u32, mix: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the diffuser state
+        let diffuser = &mut *state();
+        diffuser.set_amount(amount);
+        diffuser.set_size(size, memory::sample_rate());
+        diffuser.set_stages(stages as usize);
+        let (dry_gain, wet_gain) = utils::equal_power_gains(mix);
+        
+        let input = [memory::input_slice(0), memory::input_slice(1)];
+        let [output_l, output_r] = [memory::output_slice_mut(0), memory::output_slice_mut(1)];
+        for (i, (y_l, y_r)) in output_l.iter_mut().zip(output_r.iter_mut()).enumerate() {
+            let (x_l, x_r) = (input[0][i], input[1][i]);
+            let (wet_l, wet_r) = diffuser.process_stereo(x_l, x_r);
+            *y_l = x_l * dry_gain + wet_l * wet_gain;
+            *y_r = x_r * dry_gain + wet_r * wet_gain;
+        }
+    }
+}
+
+/// Silence the diffuser
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).clear();
+    }
+}
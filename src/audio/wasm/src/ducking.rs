@@ -0,0 +1,245 @@
+//! Sidechain Ducking
+//!
+//! Turns the wet signal down while a sidechain signal (e.g. a kick or a
+//! voice) is loud, so the reverb tail makes room for it.
+//!
+//! # Sidechain
+//! The host writes each block's mono sidechain samples to the sidechain
+//! region and calls `load` before processing the block. The samples are
+//! used by the next processed block only; a block without a loaded
+//! sidechain follows silence, so the envelope releases.
+//!
+//! # Gain
+//! An envelope follower (peak or RMS, with attack and release) tracks the
+//! sidechain, and the wet signal is scaled by `1 - amount * envelope`
+//! (envelope capped at 1, so full scale with amount 1 mutes the wet
+//! signal). While the envelope is 0 or the amount is 0, `block_gains`
+//! returns None and the wet signal is left untouched: with no sidechain
+//! loaded ducking is an exact no-op.
+
+use crate::memory;
+use crate::modulation::{EnvelopeFollower, FollowerMode};
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default follower times in milliseconds
+const DEFAULT_ATTACK_MS: f32 = 10.0;
+const DEFAULT_RELEASE_MS: f32 = 250.0;
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+struct Ducking {
+    follower: EnvelopeFollower,
+    /// Depth (0 = off, 1 = full scale sidechain mutes the wet signal)
+    amount: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    /// Sample rate the follower times were computed for (0 = not yet)
+    sample_rate: f32,
+    /// Sidechain samples loaded for the next block
+    pending: usize,
+    /// Wet gain per sample of the current block
+    gains: [f32; memory::MAX_BUFFER_SIZE],
+}
+
+impl Ducking {
+    const fn new() -> Self {
+        Self {
+            follower: EnvelopeFollower::new(),
+            amount: 0.0,
+            attack_ms: DEFAULT_ATTACK_MS,
+            release_ms: DEFAULT_RELEASE_MS,
+            sample_rate: 0.0,
+            pending: 0,
+            gains: [1.0; memory::MAX_BUFFER_SIZE],
+        }
+    }
+}
+
+/// Ducking state of every engine in the pool
+static mut STATES: [Ducking; memory::MAX_ENGINES] = [const { Ducking::new() }; memory::MAX_ENGINES];
+
+/// Ducking state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Ducking {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// CONTROL
+// ============================================================================
+
+/// Set the ducking depth and the sidechain envelope follower
+/// 
+/// # Arguments
+/// * `amount` - Depth (0-1, 0 = off)
+/// * `attack_ms` - Follower attack time (0.1-5000ms)
+/// * `release_ms` - Follower release time (0.1-5000ms)
+/// * `detector` - Follower detector
+pub fn set(amount: f32, attack_ms: f32, release_ms: f32, detector: FollowerMode) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let ducking = &mut *state();
+        ducking.amount = if amount.is_nan() { 0.0 } else { amount.clamp(0.0, 1.0) };
+        ducking.attack_ms = attack_ms;
+        ducking.release_ms = release_ms;
+        ducking.sample_rate = 0.0;
+        ducking.follower.set_mode(detector);
+    }
+}
+
+/// Mark the sidechain region as holding the next block's sidechain
+/// 
+/// # Arguments
+/// * `length` - Samples written to the sidechain region (0 = none)
+/// 
+/// # Returns
+/// LOAD_OK, or LOAD_TRUNCATED when `length` exceeds the buffer size (the
+/// samples past it are ignored)
+pub fn load(length: u32) -> u32 {
+    let buffer_size = memory::buffer_size() as usize;
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).pending = (length as usize).min(buffer_size);
+    }
+    if length as usize > buffer_size { memory::LOAD_TRUNCATED } else { memory::LOAD_OK }
+}
+
+/// Follow the loaded sidechain over one block and return the wet gains
+/// 
+/// Consumes the loaded sidechain; call once per processed block.
+/// 
+/// # Returns
+/// Wet gain per sample, or None when ducking leaves the block untouched
+pub fn block_gains(len: usize) -> Option<&'static [f32]> {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the sidechain region doesn't
+        // overlap the ducking state
+        let ducking: &'static mut Ducking = &mut *state();
+        let pending = core::mem::take(&mut ducking.pending).min(len);
+        if pending == 0 && ducking.follower.envelope() == 0.0 {
+            return None;
+        }
+        let sample_rate = memory::sample_rate();
+        if sample_rate != ducking.sample_rate {
+            ducking.follower.set_times(ducking.attack_ms, ducking.release_ms, sample_rate);
+            ducking.sample_rate = sample_rate;
+        }
+        
+        // The follower runs even at amount 0, so raising the amount picks up
+        // the current envelope
+        let sidechain = memory::sidechain_slice(pending);
+        for (i, gain) in ducking.gains[..len].iter_mut().enumerate() {
+            let envelope = ducking.follower.process(sidechain.get(i).copied().unwrap_or(0.0));
+            *gain = 1.0 - ducking.amount * envelope.min(1.0);
+        }
+        if ducking.amount == 0.0 {
+            return None;
+        }
+        Some(&ducking.gains[..len])
+    }
+}
+
+/// Drop the envelope and any loaded sidechain
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let ducking = &mut *state();
+        ducking.follower.reset();
+        ducking.pending = 0;
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convolution;
+    use core::f32::consts::TAU;
+    
+    const BLOCK: usize = 128;
+    
+    /// Left output of fully wet convolution with a unit impulse IR, the
+    /// input a 440Hz sine, and per block an optional sidechain
+    fn render(blocks: usize, sidechain: impl Fn(usize) -> Option<f32>) -> Vec<f32> {
+        unsafe { *memory::get_ir_ptr() = 1.0 };
+        convolution::load_ir(core::ptr::null(), 1, 1, 0.0);
+        convolution::reset();
+        reset();
+        let mut output = Vec::with_capacity(blocks * BLOCK);
+        for block in 0..blocks {
+            unsafe {
+                for i in 0..BLOCK {
+                    let x = libm::sinf(TAU * 440.0 * (block * BLOCK + i) as f32 / 48000.0);
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            if let Some(level) = sidechain(block) {
+                let region = unsafe { std::slice::from_raw_parts_mut(memory::get_sidechain_ptr(), BLOCK) };
+                region.fill(level);
+                assert_eq!(load(BLOCK as u32), memory::LOAD_OK);
+            }
+            convolution::process(1.0, 0.0);
+            output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+        }
+        output
+    }
+    
+    fn block_rms(output: &[f32]) -> Vec<f32> {
+        output
+            .chunks(BLOCK)
+            .map(|block| (block.iter().map(|x| x * x).sum::<f32>() / BLOCK as f32).sqrt())
+            .collect()
+    }
+    
+    #[test]
+    fn test_wet_output_dips_with_sidechain_pulses() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, BLOCK as u32);
+        
+        // Without a sidechain the output is bit-identical to ducking off
+        set(0.0, 1.0, 5.0, FollowerMode::Peak);
+        let reference = render(80, |_| None);
+        set(0.8, 1.0, 5.0, FollowerMode::Peak);
+        assert_eq!(render(80, |_| None), reference);
+        assert!(reference.iter().any(|&x| x != 0.0));
+        
+        // A full-scale pulse for 2 of every 16 blocks (about 23Hz apart)
+        let pulse = |block: usize| block % 16 < 2;
+        let ducked = render(80, |block| Some(if pulse(block) { 1.0 } else { 0.0 }));
+        let ratios: Vec<f32> = block_rms(&ducked)
+            .iter()
+            .zip(block_rms(&reference))
+            .map(|(level, open)| level / open)
+            .collect();
+        for (block, &ratio) in ratios.iter().enumerate().skip(16) {
+            if block % 16 == 1 {
+                // Down by the amount (-14dB) once the attack has settled
+                assert!(ratio < 0.3, "block {block} not ducked ({ratio})");
+            } else if block % 16 >= 10 {
+                // Back up after the release
+                assert!(ratio > 0.95, "block {block} still ducked ({ratio})");
+            }
+        }
+        // The dip starts in the block of the pulse, not later
+        assert!(ratios[16] < 0.7);
+        assert!(ratios[15] > 0.95);
+        
+        // A sidechain longer than the block is truncated
+        assert_eq!(load(BLOCK as u32 + 1), memory::LOAD_TRUNCATED);
+        reset();
+        set(0.0, DEFAULT_ATTACK_MS, DEFAULT_RELEASE_MS, FollowerMode::Peak);
+    }
+}
@@ -0,0 +1,149 @@
+//! Dynamics Processors
+//!
+//! Feed-forward compressor for leveling effect signals (e.g. the
+//! convolution wet path, whose level swings with the IR's energy).
+//!
+//! # Algorithm
+//! 1. Detection: the stereo-linked level (peak of |L|, |R|, or their mean
+//!    square) runs through an envelope follower with the attack and
+//!    release times
+//! 2. Gain computer: levels above the threshold are reduced by the ratio
+//!    (hard knee), plus the makeup gain. The per-sample dB conversions use
+//!    the table-based `utils::fast_log2`/`fast_exp2`.
+//! 3. Both channels are scaled by the same gain
+//!
+//! The gain only moves as fast as the follower's level, so the attack and
+//! release are floored (MIN_ATTACK_MS, MIN_RELEASE_MS) to keep gain
+//! changes from modulating the signal audibly.
+//!
+//! # Bypass
+//! At ratio 1 the compressor leaves the signal untouched (bit-exact,
+//! makeup gain included) and its detector stays at rest.
+
+use crate::modulation::{EnvelopeFollower, FollowerMode};
+use crate::utils;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Threshold range in dBFS
+const MIN_THRESHOLD_DB: f32 = -60.0;
+const MAX_THRESHOLD_DB: f32 = 0.0;
+
+/// Highest ratio (20:1 is close enough to limiting)
+const MAX_RATIO: f32 = 20.0;
+
+/// Attack and release time ranges in milliseconds
+const MIN_ATTACK_MS: f32 = 1.0;
+const MAX_ATTACK_MS: f32 = 500.0;
+const MIN_RELEASE_MS: f32 = 10.0;
+const MAX_RELEASE_MS: f32 = 5000.0;
+
+/// Highest makeup gain in dB
+const MAX_MAKEUP_DB: f32 = 24.0;
+
+// ============================================================================
+// COMPRESSOR
+// ============================================================================
+
+/// Stereo-linked feed-forward compressor
+pub struct Compressor {
+    detector: EnvelopeFollower,
+    /// Threshold in dBFS
+    threshold_db: f32,
+    /// Compression ratio (1 = bypassed)
+    ratio: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    /// Makeup gain (linear)
+    makeup: f32,
+    /// Sample rate the detector times were computed for (0 = not yet)
+    sample_rate: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Compressor {
+    /// Create a bypassed compressor (ratio 1)
+    pub const fn new() -> Self {
+        Self {
+            detector: EnvelopeFollower::new(),
+            threshold_db: MAX_THRESHOLD_DB,
+            ratio: 1.0,
+            attack_ms: 10.0,
+            release_ms: 200.0,
+            makeup: 1.0,
+            sample_rate: 0.0,
+        }
+    }
+    
+    /// Set the compressor parameters
+    /// 
+    /// # Arguments
+    /// * `threshold_db` - Level above which the signal is compressed (-60-0dBFS)
+    /// * `ratio` - Compression ratio (1-20, 1 = bypass)
+    /// * `attack_ms` - Attack time (1-500ms)
+    /// * `release_ms` - Release time (10-5000ms)
+    /// * `makeup_db` - Gain after compression (0-24dB)
+    /// * `detector` - Peak or RMS level detection
+    pub fn set(
+        &mut self,
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        makeup_db: f32,
+        detector: FollowerMode,
+    ) {
+        self.threshold_db = threshold_db.clamp(MIN_THRESHOLD_DB, MAX_THRESHOLD_DB);
+        self.ratio = if ratio.is_nan() { 1.0 } else { ratio.clamp(1.0, MAX_RATIO) };
+        self.attack_ms = attack_ms.clamp(MIN_ATTACK_MS, MAX_ATTACK_MS);
+        self.release_ms = release_ms.clamp(MIN_RELEASE_MS, MAX_RELEASE_MS);
+        self.makeup = utils::db_to_linear(makeup_db.clamp(0.0, MAX_MAKEUP_DB));
+        self.sample_rate = 0.0;
+        self.detector.set_mode(detector);
+    }
+    
+    /// Whether the compressor changes the signal (ratio above 1)
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.ratio > 1.0
+    }
+    
+    /// Compress a stereo block in place
+    pub fn process(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: f32) {
+        if !self.is_active() {
+            self.reset();
+            return;
+        }
+        if sample_rate != self.sample_rate {
+            self.detector.set_times(self.attack_ms, self.release_ms, sample_rate);
+            self.sample_rate = sample_rate;
+        }
+        
+        let slope = 1.0 - 1.0 / self.ratio;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            // The RMS detector squares its input, so it gets the root of
+            // the channels' mean square
+            let level = match self.detector.mode() {
+                FollowerMode::Peak => l.abs().max(r.abs()),
+                FollowerMode::Rms => (0.5 * (*l * *l + *r * *r)).sqrt(),
+            };
+            let over_db = utils::fast_linear_to_db(self.detector.process(level)) - self.threshold_db;
+            let gain = if over_db > 0.0 { utils::fast_db_to_linear(-over_db * slope) } else { 1.0 };
+            let gain = gain * self.makeup;
+            *l *= gain;
+            *r *= gain;
+        }
+    }
+    
+    /// Drop the detector level
+    pub fn reset(&mut self) {
+        self.detector.reset();
+    }
+}
@@ -0,0 +1,313 @@
+//! Effect Switching
+//!
+//! Crossfades from one effect to another when the host switches the active
+//! effect (`dsp_set_active_effect`), instead of cutting between their
+//! outputs.
+//!
+//! Every process export hands its arguments to `process` as an
+//! `EffectBlock`, and the last block is kept. When the active effect
+//! changes, the outgoing effect keeps running with its last arguments for
+//! the transition: for `blocks` blocks both effects render, the outgoing
+//! output ramping down as the incoming one ramps up. Outside a transition
+//! only the processed effect runs.
+//!
+//! Switching again mid-transition reverses it when switching back to the
+//! outgoing effect; a third effect takes over from whichever of the two is
+//! louder at that point.
+
+use crate::{
+    bypass, convolution, diffuser, flanger, granular, memory, resonator, saturation, shimmer, simd_utils,
+    soft_reset, spectral,
+};
+use crate::{
+    EFFECT_BYPASS, EFFECT_CONVOLUTION, EFFECT_DIFFUSER, EFFECT_FLANGER, EFFECT_GRANULAR, EFFECT_PITCH_SHIFT,
+    EFFECT_RESONATOR, EFFECT_SATURATION, EFFECT_SHIMMER, EFFECT_SPECTRAL, EFFECT_SPECTRAL_GATE, EFFECT_VOCODER,
+};
+use core::ptr::addr_of_mut;
+use soft_reset::Processor;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default transition length in blocks (21ms at 128 samples and 48kHz)
+const DEFAULT_SWITCH_BLOCKS: u32 = 8;
+
+/// Longest transition in blocks
+const MAX_SWITCH_BLOCKS: u32 = 64;
+
+// ============================================================================
+// EFFECT BLOCKS
+// ============================================================================
+
+/// Arguments of one block of an effect's process export
+#[derive(Clone, Copy)]
+pub enum EffectBlock {
+    Passthrough,
+    Granular { grain_size: u32, density: f32, pitch_spread: f32, position: f32, spray: f32 },
+    Convolution { dry_wet: f32, blend: f32 },
+    Spectral {
+        freeze_amount: f32,
+        shift: f32,
+        slot: f32,
+        dry_wet: f32,
+        mode: spectral::SpectralMode,
+        formant_preserve: bool,
+        shift_hz: f32,
+    },
+    Flanger { rate: f32, depth: f32, feedback: f32, mix: f32 },
+    Saturation { drive: f32, curve: saturation::SaturationCurve, mix: f32 },
+    Resonator { freq: f32, decay: f32, damping: f32, mix: f32 },
+    Shimmer { size: f32, shift_semitones: f32, shimmer_amount: f32, dry_wet: f32 },
+    Diffuser { amount: f32, size: f32, stages: u32, dry_wet: f32 },
+    Vocoder { bands: f32, formant_shift: f32 },
+    PitchShift { semitones: f32, formant_preserve: bool },
+    SpectralGate { threshold_db: f32, reduction_db: f32 },
+}
+
+impl EffectBlock {
+    /// Effect ID (the `EFFECT_*` values in lib.rs)
+    pub fn effect_id(&self) -> u32 {
+        match self {
+            EffectBlock::Passthrough => EFFECT_BYPASS,
+            EffectBlock::Granular { .. } => EFFECT_GRANULAR,
+            EffectBlock::Convolution { .. } => EFFECT_CONVOLUTION,
+            EffectBlock::Spectral { .. } => EFFECT_SPECTRAL,
+            EffectBlock::Flanger { .. } => EFFECT_FLANGER,
+            EffectBlock::Saturation { .. } => EFFECT_SATURATION,
+            EffectBlock::Resonator { .. } => EFFECT_RESONATOR,
+            EffectBlock::Shimmer { .. } => EFFECT_SHIMMER,
+            EffectBlock::Diffuser { .. } => EFFECT_DIFFUSER,
+            EffectBlock::Vocoder { .. } => EFFECT_VOCODER,
+            EffectBlock::PitchShift { .. } => EFFECT_PITCH_SHIFT,
+            EffectBlock::SpectralGate { .. } => EFFECT_SPECTRAL_GATE,
+        }
+    }
+    
+    /// Render the block into the output buffers, honoring the effect's
+    /// enable flag and pending soft reset
+    fn render(self) {
+        match self {
+            EffectBlock::Passthrough => bypass::passthrough(),
+            EffectBlock::Granular { grain_size, density, pitch_spread, position, spray } => {
+                bypass::process(EFFECT_GRANULAR, || {
+                    granular::process(grain_size, density, pitch_spread, position, spray);
+                    soft_reset::apply(Processor::Granular);
+                });
+            }
+            EffectBlock::Convolution { dry_wet, blend } => {
+                bypass::process(EFFECT_CONVOLUTION, || {
+                    convolution::process(dry_wet, blend);
+                    soft_reset::apply(Processor::Convolution);
+                });
+            }
+            EffectBlock::Spectral { freeze_amount, shift, slot, dry_wet, mode, formant_preserve, shift_hz } => {
+                bypass::process(EFFECT_SPECTRAL, || {
+                    spectral::process(freeze_amount, shift, slot, dry_wet, mode, formant_preserve, shift_hz);
+                    soft_reset::apply(Processor::Spectral);
+                });
+            }
+            EffectBlock::Flanger { rate, depth, feedback, mix } => {
+                bypass::process(EFFECT_FLANGER, || {
+                    flanger::process(rate, depth, feedback, mix);
+                    soft_reset::apply(Processor::Flanger);
+                });
+            }
+            EffectBlock::Saturation { drive, curve, mix } => {
+                bypass::process(EFFECT_SATURATION, || saturation::process(drive, curve, mix));
+            }
+            EffectBlock::Resonator { freq, decay, damping, mix } => {
+                bypass::process(EFFECT_RESONATOR, || {
+                    resonator::process(freq, decay, damping, mix);
+                    soft_reset::apply(Processor::Resonator);
+                });
+            }
+            EffectBlock::Shimmer { size, shift_semitones, shimmer_amount, dry_wet } => {
+                bypass::process(EFFECT_SHIMMER, || {
+                    shimmer::process(size, shift_semitones, shimmer_amount, dry_wet);
+                    soft_reset::apply(Processor::Shimmer);
+                });
+            }
+            EffectBlock::Diffuser { amount, size, stages, dry_wet } => {
+                bypass::process(EFFECT_DIFFUSER, || {
+                    diffuser::process(amount, size, stages, dry_wet);
+                    soft_reset::apply(Processor::Diffuser);
+                });
+            }
+            EffectBlock::Vocoder { bands, formant_shift } => {
+                bypass::process(EFFECT_VOCODER, || {
+                    spectral::process_vocoder(bands, formant_shift);
+                    soft_reset::apply(Processor::Spectral);
+                });
+            }
+            EffectBlock::PitchShift { semitones, formant_preserve } => {
+                bypass::process(EFFECT_PITCH_SHIFT, || {
+                    spectral::process_pitch_shift(semitones, formant_preserve);
+                    soft_reset::apply(Processor::Spectral);
+                });
+            }
+            EffectBlock::SpectralGate { threshold_db, reduction_db } => {
+                bypass::process(EFFECT_SPECTRAL_GATE, || {
+                    spectral::process_spectral_gate(threshold_db, reduction_db);
+                    soft_reset::apply(Processor::Spectral);
+                });
+            }
+        }
+    }
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+struct EffectSwitch {
+    /// Effect set by the host (None until the first switch)
+    active: Option<u32>,
+    /// Last block processed
+    last: Option<EffectBlock>,
+    /// Last block of the effect fading out, during a transition
+    outgoing: Option<EffectBlock>,
+    /// Blocks of the transition done
+    position: u32,
+    /// Transition length in blocks (0 = cut)
+    blocks: u32,
+    /// Outgoing effect output of the current block
+    fade_l: [f32; memory::MAX_BUFFER_SIZE],
+    fade_r: [f32; memory::MAX_BUFFER_SIZE],
+}
+
+impl EffectSwitch {
+    const fn new() -> Self {
+        Self {
+            active: None,
+            last: None,
+            outgoing: None,
+            position: 0,
+            blocks: DEFAULT_SWITCH_BLOCKS,
+            fade_l: [0.0; memory::MAX_BUFFER_SIZE],
+            fade_r: [0.0; memory::MAX_BUFFER_SIZE],
+        }
+    }
+}
+
+/// Effect switch state of every engine in the pool
+static mut STATES: [EffectSwitch; memory::MAX_ENGINES] =
+    [const { EffectSwitch::new() }; memory::MAX_ENGINES];
+
+/// Effect switch state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut EffectSwitch {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// CONTROL
+// ============================================================================
+
+/// Switch the active effect, crossfading from the last processed one
+/// 
+/// # Arguments
+/// * `effect_id` - Effect ID (the `EFFECT_*` values in lib.rs)
+pub fn set_active(effect_id: u32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &mut *state();
+        if st.active == Some(effect_id) {
+            return;
+        }
+        st.active = Some(effect_id);
+        
+        match st.outgoing {
+            // Switching back: run the transition in reverse from where it is
+            Some(outgoing) if outgoing.effect_id() == effect_id => {
+                st.outgoing = st.last;
+                st.position = st.blocks.saturating_sub(st.position);
+            }
+            // A third effect: fade out the louder of the two
+            Some(outgoing) => {
+                if 2 * st.position < st.blocks {
+                    st.outgoing = Some(outgoing);
+                } else {
+                    st.outgoing = st.last;
+                }
+                st.position = 0;
+            }
+            None => {
+                st.outgoing = st.last.filter(|last| last.effect_id() != effect_id);
+                st.position = 0;
+            }
+        }
+        if st.blocks == 0 {
+            st.outgoing = None;
+        }
+    }
+}
+
+/// Set the transition length
+/// 
+/// # Arguments
+/// * `blocks` - Transition length in blocks (0 = cut, up to
+///   MAX_SWITCH_BLOCKS)
+pub fn set_blocks(blocks: u32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &mut *state();
+        st.blocks = blocks.min(MAX_SWITCH_BLOCKS);
+        if st.position >= st.blocks {
+            st.outgoing = None;
+        }
+    }
+}
+
+/// Forget the active and last effects and restore the default transition
+/// length
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = EffectSwitch::new();
+    }
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Render one block of an effect into the output buffers
+/// 
+/// During a transition to this effect the outgoing effect is rendered too,
+/// and the two are crossfaded.
+pub fn process(block: EffectBlock) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the switch state
+        let st = &mut *state();
+        match st.outgoing {
+            Some(outgoing) if st.active == Some(block.effect_id()) && memory::is_initialized() => {
+                outgoing.render();
+                let len = memory::buffer_size() as usize;
+                simd_utils::copy_buffer(memory::output_slice_mut(0), &mut st.fade_l[..len]);
+                simd_utils::copy_buffer(memory::output_slice_mut(1), &mut st.fade_r[..len]);
+                block.render();
+                
+                let start = st.position as f32 / st.blocks as f32;
+                let end = (st.position + 1) as f32 / st.blocks as f32;
+                for (channel, fade) in [(0, &mut st.fade_l), (1, &mut st.fade_r)] {
+                    let output = memory::output_slice_mut(channel);
+                    simd_utils::apply_gain_ramp(output, start, end);
+                    simd_utils::apply_gain_ramp(&mut fade[..len], 1.0 - start, 1.0 - end);
+                    simd_utils::mix_buffer(output, &fade[..len], 1.0);
+                }
+                
+                st.position += 1;
+                if st.position >= st.blocks {
+                    st.outgoing = None;
+                }
+            }
+            _ => block.render(),
+        }
+        st.last = Some(block);
+    }
+}
@@ -11,7 +11,13 @@
 //! # Zero-Allocation Design
 //! All filter state is stored in the struct. Coefficients are computed
 //! once when parameters change, not per-sample.
+//!
+//! # Coefficient Smoothing
+//! Biquad coefficients glide to new settings (see `SmoothedParam`), so
+//! cutoff and gain changes don't zipper. The stable coefficient region is
+//! convex, so every intermediate filter between two stable ones is stable.
 
+use crate::smoothing::SmoothedParam;
 use core::f32::consts::PI;
 
 // ============================================================================
@@ -46,12 +52,12 @@ pub enum FilterType {
 /// ```
 #[derive(Clone, Copy)]
 pub struct Biquad {
-    // Coefficients (normalized by a0)
-    b0: f32,
-    b1: f32,
-    b2: f32,
-    a1: f32,
-    a2: f32,
+    // Coefficients (normalized by a0), smoothed per sample
+    b0: SmoothedParam,
+    b1: SmoothedParam,
+    b2: SmoothedParam,
+    a1: SmoothedParam,
+    a2: SmoothedParam,
     
     // State (delay line)
     x1: f32,
@@ -70,11 +76,11 @@ impl Biquad {
     /// Create a new biquad filter (passthrough by default)
     pub const fn new() -> Self {
         Self {
-            b0: 1.0,
-            b1: 0.0,
-            b2: 0.0,
-            a1: 0.0,
-            a2: 0.0,
+            b0: SmoothedParam::new(1.0),
+            b1: SmoothedParam::new(0.0),
+            b2: SmoothedParam::new(0.0),
+            a1: SmoothedParam::new(0.0),
+            a2: SmoothedParam::new(0.0),
             x1: 0.0,
             x2: 0.0,
             y1: 0.0,
@@ -91,6 +97,7 @@ impl Biquad {
     pub fn lowpass(freq: f32, q: f32, sample_rate: f32) -> Self {
         let mut filter = Self::new();
         filter.set_lowpass(freq, q, sample_rate);
+        filter.snap_coefficients();
         filter
     }
     
@@ -98,6 +105,7 @@ impl Biquad {
     pub fn highpass(freq: f32, q: f32, sample_rate: f32) -> Self {
         let mut filter = Self::new();
         filter.set_highpass(freq, q, sample_rate);
+        filter.snap_coefficients();
         filter
     }
     
@@ -105,6 +113,7 @@ impl Biquad {
     pub fn bandpass(freq: f32, q: f32, sample_rate: f32) -> Self {
         let mut filter = Self::new();
         filter.set_bandpass(freq, q, sample_rate);
+        filter.snap_coefficients();
         filter
     }
     
@@ -238,15 +247,33 @@ impl Biquad {
         self.set_coefficients(b0, b1, b2, a0, a1, a2);
     }
     
-    /// Set raw coefficients (normalized by a0)
+    /// Set raw coefficients (normalized by a0); the filter glides to them
     fn set_coefficients(&mut self, b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) {
         // Normalize by a0
         let inv_a0 = 1.0 / a0;
-        self.b0 = b0 * inv_a0;
-        self.b1 = b1 * inv_a0;
-        self.b2 = b2 * inv_a0;
-        self.a1 = a1 * inv_a0;
-        self.a2 = a2 * inv_a0;
+        self.b0.set_target(b0 * inv_a0);
+        self.b1.set_target(b1 * inv_a0);
+        self.b2.set_target(b2 * inv_a0);
+        self.a1.set_target(a1 * inv_a0);
+        self.a2.set_target(a2 * inv_a0);
+    }
+    
+    /// Jump straight to the latest settings (for initialization)
+    pub fn snap_coefficients(&mut self) {
+        for coefficient in [&mut self.b0, &mut self.b1, &mut self.b2, &mut self.a1, &mut self.a2] {
+            coefficient.snap();
+        }
+    }
+    
+    /// Set how fast the coefficients glide to new settings
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Smoothing time constant (0 = change instantly)
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn set_smoothing_time(&mut self, time_ms: f32, sample_rate: f32) {
+        for coefficient in [&mut self.b0, &mut self.b1, &mut self.b2, &mut self.a1, &mut self.a2] {
+            coefficient.set_time(time_ms, sample_rate);
+        }
     }
     
     /// Process a single sample through the filter
@@ -255,8 +282,8 @@ impl Biquad {
     #[inline]
     pub fn process(&mut self, x: f32) -> f32 {
         // Direct Form I (simple but less stable at low frequencies)
-        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
-              - self.a1 * self.y1 - self.a2 * self.y2;
+        let y = self.b0.next() * x + self.b1.next() * self.x1 + self.b2.next() * self.x2
+              - self.a1.next() * self.y1 - self.a2.next() * self.y2;
         
         // Update state
         self.x2 = self.x1;
@@ -280,13 +307,16 @@ impl Biquad {
 // ONE-POLE FILTER
 // ============================================================================
 
-/// Simple one-pole lowpass filter
+/// Simple one-pole filter
 /// 
-/// Good for smoothing control signals and simple DC blocking.
+/// Good for smoothing control signals (lowpass) and DC blocking
+/// (one-pole/one-zero highpass).
 #[derive(Clone, Copy)]
 pub struct OnePole {
     a0: f32,
+    a1: f32,
     b1: f32,
+    x1: f32,
     y1: f32,
 }
 
@@ -301,7 +331,9 @@ impl OnePole {
     pub const fn new() -> Self {
         Self {
             a0: 1.0,
+            a1: 0.0,
             b1: 0.0,
+            x1: 0.0,
             y1: 0.0,
         }
     }
@@ -315,25 +347,54 @@ impl OnePole {
         let w0 = 2.0 * PI * freq / sample_rate;
         self.b1 = (-w0).exp();
         self.a0 = 1.0 - self.b1;
+        self.a1 = 0.0;
     }
     
-    /// Set as DC blocker (highpass at very low frequency)
+    /// Set as DC blocker: `y = x - x1 + R * y1`
+    /// 
+    /// A zero at DC and a pole just inside it give unity gain everywhere
+    /// but the lowest few Hz.
     pub fn set_dc_blocker(&mut self, sample_rate: f32) {
         // ~5 Hz cutoff
-        self.set_lowpass(5.0, sample_rate);
-        // Invert for highpass behavior
-        self.a0 = -self.a0;
+        let w0 = 2.0 * PI * 5.0 / sample_rate;
+        self.a0 = 1.0;
+        self.a1 = -1.0;
+        self.b1 = (-w0).exp();
+    }
+    
+    /// Gain and phase delay (in samples) at a frequency
+    /// 
+    /// # Arguments
+    /// * `omega` - Frequency in radians per sample (0 < omega < π)
+    pub fn response(&self, omega: f32) -> (f32, f32) {
+        // H = (a0 + a1 z^-1) / (1 - b1 z^-1)
+        let (sin_w, cos_w) = omega.sin_cos();
+        let (num_re, num_im) = (self.a0 + self.a1 * cos_w, -self.a1 * sin_w);
+        let (den_re, den_im) = (1.0 - self.b1 * cos_w, self.b1 * sin_w);
+        let gain = (num_re.hypot(num_im)) / den_re.hypot(den_im);
+        let phase = num_im.atan2(num_re) - den_im.atan2(den_re);
+        (gain, -phase / omega)
     }
     
     /// Process a single sample
     #[inline]
     pub fn process(&mut self, x: f32) -> f32 {
-        self.y1 = self.a0 * x + self.b1 * self.y1;
+        self.y1 = self.a0 * x + self.a1 * self.x1 + self.b1 * self.y1;
+        self.x1 = x;
         self.y1
     }
     
+    /// Process a buffer in place
+    #[inline]
+    pub fn process_buffer(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+    
     /// Reset filter state
     pub fn reset(&mut self) {
+        self.x1 = 0.0;
         self.y1 = 0.0;
     }
 }
@@ -0,0 +1,340 @@
+//! Flanger
+//!
+//! Sweeps a short modulated delay (0.5-10ms) with an LFO (sine by default)
+//! and mixes it with the input, moving a comb of notches up and down the
+//! spectrum.
+//!
+//! # Feedback
+//! The swept delay line feeds back into itself (-0.95 to 0.95). Negative
+//! feedback gives the hollow sound. The cubic interpolation never has a
+//! gain above 1, so the loop gain stays below 1 and the comb can ring but
+//! not run away.
+//!
+//! # Through-Zero
+//! A plain flanger's delay can't reach 0, so the notches never sweep up to
+//! infinity and back. In through-zero mode the input goes through two
+//! delays swept in opposite directions around a fixed center; their
+//! relative delay passes through zero (and changes sign) each LFO half
+//! cycle. Both paths are delayed, so the output is `latency_samples()`
+//! (the center delay) late.
+
+use crate::delay::ModulatedDelay;
+use crate::memory;
+use crate::modulation::{Lfo, LfoShape};
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Shortest and longest swept delay in milliseconds
+const MIN_DELAY_MS: f32 = 0.5;
+const MAX_DELAY_MS: f32 = 10.0;
+
+/// Delay line length: MAX_DELAY_MS at the highest supported sample rate
+/// (192kHz), plus the interpolation taps
+const DELAY_CAPACITY: usize = 2048;
+
+const _: () = assert!((MAX_DELAY_MS as usize) * 192 + 3 < DELAY_CAPACITY);
+
+/// LFO rate range in Hz
+const MIN_RATE_HZ: f32 = 0.01;
+const MAX_RATE_HZ: f32 = 10.0;
+
+// ============================================================================
+// FLANGER STATE
+// ============================================================================
+
+/// Stereo flanger (one LFO drives both channels)
+struct Flanger {
+    lfo: Lfo,
+    /// Swept delays (these carry the feedback)
+    swept_l: ModulatedDelay<DELAY_CAPACITY>,
+    swept_r: ModulatedDelay<DELAY_CAPACITY>,
+    /// Counter-swept delays of through-zero mode
+    counter_l: ModulatedDelay<DELAY_CAPACITY>,
+    counter_r: ModulatedDelay<DELAY_CAPACITY>,
+    through_zero: bool,
+}
+
+impl Flanger {
+    const fn new() -> Self {
+        Self {
+            lfo: Lfo::new(),
+            swept_l: ModulatedDelay::new(),
+            swept_r: ModulatedDelay::new(),
+            counter_l: ModulatedDelay::new(),
+            counter_r: ModulatedDelay::new(),
+            through_zero: false,
+        }
+    }
+    
+    /// Silence the delay lines and restart the LFO
+    fn clear(&mut self) {
+        self.swept_l.clear();
+        self.swept_r.clear();
+        self.counter_l.clear();
+        self.counter_r.clear();
+        self.lfo.set_phase(0.0);
+    }
+}
+
+/// Flanger state of every engine in the pool
+static mut STATES: [Flanger; memory::MAX_ENGINES] =
+    [const { Flanger::new() }; memory::MAX_ENGINES];
+
+/// Flanger state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Flanger {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+/// Center delay of through-zero mode in samples
+fn through_zero_center(sample_rate: f32) -> f32 {
+    MAX_DELAY_MS * 0.0005 * sample_rate
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process the flanger
+/// 
+/// # Arguments
+/// * `rate` - LFO rate in Hz (0.01-10)
+/// * `depth` - Sweep depth (0-1, 1 = the full delay range)
+/// * `feedback` - Feedback (-0.95 to 0.95, negative = hollow)
+/// * `mix` - Mix between dry (0) and swept (1) signal; 0.5 gives the
+///   deepest notches
+pub fn process(rate: f32, depth: f32, feedback: f32, mix: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the flanger state
+        let st = &mut *state();
+        let sample_rate = memory::sample_rate();
+        let depth = depth.clamp(0.0, 1.0);
+        let mix = mix.clamp(0.0, 1.0);
+        st.lfo.set_frequency(rate.clamp(MIN_RATE_HZ, MAX_RATE_HZ), sample_rate);
+        
+        let min_delay = MIN_DELAY_MS * 0.001 * sample_rate;
+        let max_delay = MAX_DELAY_MS * 0.001 * sample_rate;
+        let (center, sweep) = if st.through_zero {
+            // Counter-swept paths stay above the minimum delay
+            let center = through_zero_center(sample_rate);
+            (center, (center - min_delay) * depth)
+        } else {
+            ((min_delay + max_delay) * 0.5, (max_delay - min_delay) * 0.5 * depth)
+        };
+        for delay in [&mut st.swept_l, &mut st.swept_r, &mut st.counter_l, &mut st.counter_r] {
+            delay.set_base_delay(center);
+            delay.set_mod_depth(sweep);
+        }
+        st.swept_l.set_feedback(feedback);
+        st.swept_r.set_feedback(feedback);
+        
+        let buffer_size = memory::buffer_size() as usize;
+        let input_l = memory::input_slice(0);
+        let input_r = memory::input_slice(1);
+        let output_l = memory::output_slice_mut(0);
+        let output_r = memory::output_slice_mut(1);
+        
+        for i in 0..buffer_size {
+            let modulation = st.lfo.process();
+            let (x_l, x_r) = (input_l[i], input_r[i]);
+            let swept_l = st.swept_l.process(x_l, modulation);
+            let swept_r = st.swept_r.process(x_r, modulation);
+            
+            // The reference is the dry input, or in through-zero mode the
+            // counter-swept path
+            let (reference_l, reference_r) = if st.through_zero {
+                (st.counter_l.process(x_l, -modulation), st.counter_r.process(x_r, -modulation))
+            } else {
+                (x_l, x_r)
+            };
+            
+            output_l[i] = reference_l * (1.0 - mix) + swept_l * mix;
+            output_r[i] = reference_r * (1.0 - mix) + swept_r * mix;
+        }
+    }
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+/// Switch through-zero mode on or off
+/// 
+/// Changing the mode clears the delay lines.
+pub fn set_through_zero(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &mut *state();
+        if st.through_zero != enabled {
+            st.through_zero = enabled;
+            st.clear();
+        }
+    }
+}
+
+/// Select the LFO waveform
+/// 
+/// A triangle sweeps the notches at a constant rate; square and
+/// sample-and-hold jump between delays (smoothed by the LFO's PolyBLEP).
+pub fn set_lfo_shape(shape: LfoShape) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).lfo.set_shape(shape);
+    }
+}
+
+/// Delay the flanger adds to the output, in samples (the through-zero
+/// center delay; 0 otherwise)
+pub fn latency_samples() -> u32 {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        if (*state()).through_zero {
+            through_zero_center(memory::sample_rate()).round() as u32
+        } else {
+            0
+        }
+    }
+}
+
+/// Reset flanger state
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).clear();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    const BLOCK: usize = 128;
+    /// LFO rate: the sweep peaks at 1s, crosses its center at 2s and is at
+    /// its minimum at 3s
+    const RATE: f32 = 0.25;
+    
+    /// Run `input` through the flanger; returns the left channel
+    fn render(input: &[f32], depth: f32, feedback: f32, mix: f32) -> Vec<f32> {
+        let mut out = Vec::with_capacity(input.len());
+        for block in input.chunks(BLOCK) {
+            unsafe {
+                for (i, &x) in block.iter().enumerate() {
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(RATE, depth, feedback, mix);
+            unsafe { out.extend_from_slice(memory::output_slice_mut(0)) };
+        }
+        out
+    }
+    
+    /// Gain of the flanger for a sine at `freq` around each of `times` (s)
+    fn sine_gains(freq: f32, times: &[f32]) -> Vec<f32> {
+        reset();
+        let len = (3.2 * SAMPLE_RATE) as usize;
+        let input: Vec<f32> = (0..len)
+            .map(|i| (2.0 * core::f32::consts::PI * freq * i as f32 / SAMPLE_RATE).sin())
+            .collect();
+        let output = render(&input, 1.0, 0.0, 0.5);
+        times
+            .iter()
+            .map(|&t| {
+                // 20ms around t, where the delay is near its extreme
+                let center = (t * SAMPLE_RATE) as usize;
+                let window = &output[center - 480..center + 480];
+                let rms = (window.iter().map(|y| y * y).sum::<f32>() / window.len() as f32).sqrt();
+                rms * core::f32::consts::SQRT_2
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_notches_sweep_with_the_lfo() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_through_zero(false);
+        
+        // At 1s the delay is 10ms (first notch at 50Hz), at 3s it is 0.5ms
+        // (first notch at 1kHz)
+        let gains_50 = sine_gains(50.0, &[1.0, 3.0]);
+        let gains_1k = sine_gains(1000.0, &[1.0, 3.0]);
+        assert!(gains_50[0] < 0.05 && gains_50[1] > 0.95, "50Hz gains {gains_50:?}");
+        assert!(gains_1k[0] > 0.95 && gains_1k[1] < 0.05, "1kHz gains {gains_1k:?}");
+    }
+    
+    #[test]
+    fn test_lfo_shape_changes_the_sweep() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_through_zero(false);
+        
+        // A square LFO holds the 10ms delay (50Hz notch) for the whole first
+        // half cycle; the sine only reaches it at 1s
+        set_lfo_shape(LfoShape::Square);
+        let square = sine_gains(50.0, &[0.5, 1.5]);
+        set_lfo_shape(LfoShape::Sine);
+        let sine = sine_gains(50.0, &[0.5, 1.5]);
+        assert!(square.iter().all(|&g| g < 0.05), "square gains {square:?}");
+        assert!(sine.iter().all(|&g| g > 0.15), "sine gains {sine:?}");
+    }
+    
+    #[test]
+    fn test_through_zero_sweep() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_through_zero(true);
+        assert_eq!(latency_samples(), 240);
+        
+        // The paths are 9ms apart at the sweep extremes (first notch at
+        // 55.6Hz, either sign) and line up at 2s, where nothing cancels
+        let gains = sine_gains(1000.0 / 18.0, &[1.0, 2.0, 3.0]);
+        assert!(gains[0] < 0.05 && gains[2] < 0.05, "extreme gains {gains:?}");
+        assert!(gains[1] > 0.95, "gain at zero crossing {}", gains[1]);
+        
+        set_through_zero(false);
+        assert_eq!(latency_samples(), 0);
+    }
+    
+    #[test]
+    fn test_extreme_feedback_stays_bounded() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..(4.0 * SAMPLE_RATE) as usize)
+            .map(|_| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                (seed as f32 / u32::MAX as f32) * 2.0 - 1.0
+            })
+            .collect();
+        
+        for through_zero in [false, true] {
+            set_through_zero(through_zero);
+            for feedback in [0.95, -0.95, 5.0, -5.0] {
+                reset();
+                let output = render(&noise, 1.0, feedback, 1.0);
+                assert!(output.iter().all(|y| y.is_finite()));
+                
+                // Loop gain of at most 0.95: 1 / (1 - 0.95) = 20x at worst,
+                // and the level doesn't creep up over time
+                let peak = |part: &[f32]| part.iter().fold(0.0f32, |max, y| max.max(y.abs()));
+                let (first, last) = output.split_at(output.len() / 2);
+                assert!(peak(&output) < 20.0, "feedback {feedback} peak {}", peak(&output));
+                assert!(peak(last) < 1.5 * peak(first), "feedback {feedback} level creeping up");
+            }
+        }
+        set_through_zero(false);
+    }
+}
@@ -2,26 +2,55 @@
 //! 
 //! Implements real-time granular synthesis with:
 //! - Variable grain size (64-4096 samples)
-//! - Density control (grains per second)
-//! - Pitch spreading with random variation
+//! - Density control (grains per second, up to MAX_DENSITY)
+//! - Spawn time jitter: onsets moved randomly off the spawn interval at
+//!   the same average density
+//! - Clock sync: a grain (or a burst of grains) on every tick of a tempo
+//!   clock instead of spawning by density
+//! - Global transpose with random pitch spread around it
 //! - Position spray for texture variation
+//! - Scan: the base position advances through the source independently of
+//!   grain pitch (0 = frozen, 1 = real time)
+//! - Source region (sub-range of the source grains are drawn from), which
+//!   the scan stops at, loops through or ping-pongs within
+//! - Live mode: granulate the recent input history instead of a source
 //! - Raised cosine envelope for smooth grain transitions
+//! - Linear or 4-point cubic (Catmull-Rom) source interpolation
+//! - Mono-mix or stereo grains (stereo keeps the source's L/R per grain)
+//! - Optional one-pole lowpass per grain, its cutoff randomized per grain
+//! - Optional zero-crossing alignment of grain starts (for percussive
+//!   sources, where a grain starting mid-transient clicks)
+//! - Optional sample-rate conversion of the source on load
+//! - One-pole smoothing of position, spray and density, advanced per sample
+//!   so automation doesn't move the cloud in block-sized steps
 //!
 //! # Algorithm
-//! 1. Maintain pool of N grains (max 100)
+//! 1. Maintain pool of N grains (max MAX_GRAINS)
 //! 2. Each grain tracks: position, phase, rate, amplitude
 //! 3. Per audio block:
-//!    - Spawn new grains based on density
+//!    - Spawn new grains based on density (several per sample if the
+//!      spawn interval drops below one sample), or on clock ticks
 //!    - Sum active grains with envelope
 //!    - Remove finished grains
 //!
+//! # Live Mode
+//! The input buffers are recorded into a circular history ring
+//! (LIVE_HISTORY_OFFSET) and grains read backwards in time from the write
+//! head, with `position` in seconds into the past. Reads wrap around the
+//! ring end. Freezing stops recording so the captured history can be
+//! granulated indefinitely.
+//!
 //! # Zero-Allocation Design
 //! All grain state is pre-allocated in static arrays.
-//! No heap allocation occurs during process().
+//! No heap allocation occurs during process(). Resampled loads keep a
+//! heap copy of the original source (allocated at load time only) so the
+//! source can be converted again if the engine sample rate changes.
 
-use crate::memory;
+use crate::filters::OnePole;
+use crate::memory::{self, LOAD_OK, LOAD_REJECTED, LOAD_TRUNCATED};
 use crate::simd_utils;
-use core::ptr::{addr_of, addr_of_mut};
+use crate::utils;
+use core::ptr::addr_of_mut;
 
 // Note: PI constant no longer needed - envelope uses lookup table
 
@@ -30,7 +59,13 @@ use core::ptr::{addr_of, addr_of_mut};
 // ============================================================================
 
 /// Maximum number of simultaneous grains
-const MAX_GRAINS: usize = 100;
+const MAX_GRAINS: usize = 256;
+
+/// Maximum spawn density in grains per second
+/// 
+/// High enough that the spawn interval can fall below one sample; in
+/// practice the grain pool (MAX_GRAINS) bounds how many grains overlap.
+const MAX_DENSITY: f32 = 100_000.0;
 
 /// Minimum grain size in samples
 const MIN_GRAIN_SIZE: u32 = 64;
@@ -38,6 +73,141 @@ const MIN_GRAIN_SIZE: u32 = 64;
 /// Maximum grain size in samples
 const MAX_GRAIN_SIZE: u32 = 4096;
 
+/// Minimum source region width (normalized)
+const MIN_REGION_WIDTH: f32 = 0.001;
+
+/// Maximum scan speed in either direction (multiple of real time)
+const MAX_SCAN_SPEED: f32 = 4.0;
+
+/// Default parameter smoothing time constant in milliseconds
+const DEFAULT_SMOOTHING_MS: f32 = 50.0;
+
+/// Maximum parameter smoothing time constant in milliseconds
+const MAX_SMOOTHING_MS: f32 = 2000.0;
+
+/// Initial RNG state of every engine
+const DEFAULT_SEED: u32 = 12345;
+
+/// Time constant of the running overlap estimate used for output normalization
+const OVERLAP_SMOOTHING_MS: f32 = 100.0;
+
+/// Output limiter ceiling (linear)
+const LIMITER_CEILING: f32 = 1.0;
+
+/// Output limiter attack time in milliseconds
+const LIMITER_ATTACK_MS: f32 = 1.0;
+
+/// Output limiter release time in milliseconds
+const LIMITER_RELEASE_MS: f32 = 100.0;
+
+/// Largest random offset of a grain's filter cutoff in octaves
+const MAX_FILTER_SPREAD: f32 = 4.0;
+
+/// Lowest grain filter cutoff in Hz
+const MIN_FILTER_CUTOFF: f32 = 20.0;
+
+/// Grain clock tempo range in beats per minute
+const MIN_CLOCK_BPM: f32 = 1.0;
+const MAX_CLOCK_BPM: f32 = 1000.0;
+
+/// Grain clock tick range as a note value (1 = whole note)
+const MAX_CLOCK_DIVISION: f32 = 64.0;
+
+/// Most grains spawned per clock tick
+const MAX_CLOCK_BURST: u32 = 16;
+
+/// Zero-crossing alignment: frames searched on either side of a new
+/// grain's start
+const ZERO_CROSSING_SEARCH: usize = 64;
+
+/// Harmonic pitch mode: non-unison ratios grains pick from
+const HARMONIC_RATIOS: [f32; 4] = [0.5, 2.0 / 3.0, 1.5, 2.0];
+
+/// How `pitch_spread` randomizes grain pitch
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PitchMode {
+    /// Continuous offset, uniform within ±spread octaves
+    Continuous,
+    /// -12, 0 or +12 semitones; spread is the chance of leaving unison
+    Octaves,
+    /// Ratios 1/2, 2/3, 3/2 or 2; spread is the chance of leaving unison
+    Harmonic,
+}
+
+impl PitchMode {
+    /// Mode from its export index (unknown values fall back to Continuous)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => PitchMode::Octaves,
+            2 => PitchMode::Harmonic,
+            _ => PitchMode::Continuous,
+        }
+    }
+}
+
+/// Default stereo width (random pans within ±0.7)
+const DEFAULT_STEREO_WIDTH: f32 = 0.7;
+
+/// How new grains are placed in the stereo field
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PanMode {
+    /// Uniform random pan within ±width
+    Random,
+    /// Successive grains alternate between -width and +width (ping-pong)
+    Alternate,
+}
+
+impl PanMode {
+    /// Mode from its export index (unknown values fall back to Random)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => PanMode::Alternate,
+            _ => PanMode::Random,
+        }
+    }
+}
+
+/// What the scan does at the source region bounds
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LoopMode {
+    /// Stop at the bound
+    Off,
+    /// Wrap around to the other bound
+    Forward,
+    /// Reverse direction at each bound
+    PingPong,
+}
+
+impl LoopMode {
+    /// Mode from its export index (unknown values fall back to Off)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => LoopMode::Forward,
+            2 => LoopMode::PingPong,
+            _ => LoopMode::Off,
+        }
+    }
+}
+
+/// How grains read between source frames
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InterpMode {
+    /// Linear interpolation between the two nearest frames
+    Linear,
+    /// 4-point cubic (Catmull-Rom) interpolation
+    Cubic,
+}
+
+impl InterpMode {
+    /// Mode from its export index (unknown values fall back to Linear)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => InterpMode::Cubic,
+            _ => InterpMode::Linear,
+        }
+    }
+}
+
 // ============================================================================
 // GRAIN STATE
 // ============================================================================
@@ -62,6 +232,10 @@ struct Grain {
     size_samples: u32,
     /// Pan position (-1.0 = left, 0.0 = center, 1.0 = right)
     pan: f32,
+    /// Whether the grain runs through its lowpass (false = bypassed)
+    filtered: bool,
+    /// Lowpass per channel (a mono grain uses the first)
+    filter: [OnePole; 2],
 }
 
 impl Default for Grain {
@@ -74,36 +248,190 @@ impl Default for Grain {
             amp: 1.0,
             size_samples: 256,
             pan: 0.0,
+            filtered: false,
+            filter: [OnePole::new(); 2],
         }
     }
 }
 
 // ============================================================================
-// GLOBAL STATE (Pre-allocated)
+// GRANULAR STATE (Pre-allocated, one per engine)
 // ============================================================================
 
-/// Pre-allocated grain pool - no runtime allocation
-static mut GRAINS: [Grain; MAX_GRAINS] = [Grain {
-    active: false,
-    source_pos: 0.0,
-    phase: 0.0,
-    rate: 1.0,
-    amp: 1.0,
-    size_samples: 256,
-    pan: 0.0,
-}; MAX_GRAINS];
-
-/// Random number generator state (LCG for determinism and speed)
-static mut RNG_STATE: u32 = 12345;
+/// State of one engine's granular voice
+struct GranularState {
+    /// Pre-allocated grain pool - no runtime allocation
+    grains: [Grain; MAX_GRAINS],
+    /// Random number generator state (LCG for determinism and speed)
+    rng_state: u32,
+    /// Seed `reset` restores the RNG to (None = the sequence carries on)
+    reset_seed: Option<u32>,
+    /// Length of loaded source in samples (interleaved)
+    source_len: usize,
+    /// Number of channels in source (1 or 2)
+    source_channels: u32,
+    /// Accumulator for grain spawn timing
+    spawn_accumulator: f32,
+    /// Random spawn time offset (± share of the spawn interval)
+    time_jitter: f32,
+    /// Onset offsets of the last spawned and the next grain from their
+    /// spawn-interval grid points (in intervals)
+    last_onset_jitter: f32,
+    next_onset_jitter: f32,
+    /// Samples between grain clock ticks (0 = spawn by density)
+    clock_interval: f32,
+    /// Samples since the last clock tick (a tick is due at the interval)
+    clock_accumulator: f32,
+    /// Grains spawned per clock tick
+    clock_burst: u32,
+    /// Slot index where the next free-grain search starts (round-robin)
+    spawn_cursor: usize,
+    /// Source region start (normalized, 0.0 - 1.0)
+    region_start: f32,
+    /// Source region end (normalized, always > `region_start`)
+    region_end: f32,
+    /// Whether grains that run past `region_end` wrap back to `region_start`
+    region_loop: bool,
+    /// Scan speed (source frames per output sample, negative = backwards)
+    scan_speed: f32,
+    /// Distance the scan has moved the base position (normalized to the
+    /// region; 0..2 in ping-pong mode, where 1..2 is the way back)
+    scan_offset: f32,
+    /// What the scan does at the region bounds
+    loop_mode: LoopMode,
+    /// Whether grains read from the live input history instead of the source
+    live_mode: bool,
+    /// Whether live recording is paused (history kept, grains keep playing)
+    live_frozen: bool,
+    /// Next frame written in the live history ring
+    live_write_pos: usize,
+    /// Interpolation of source reads
+    interp: InterpMode,
+    /// Pan law of mono grains
+    pan_law: utils::PanLaw,
+    /// Whether grains keep the source's left/right channels instead of
+    /// reading a mono mix
+    stereo_grains: bool,
+    /// Whether new grains start at the nearest zero crossing of the source
+    zero_crossing_align: bool,
+    /// Base playback rate of new grains (from the transpose setting)
+    transpose_rate: f32,
+    /// Pitch randomization mode of new grains
+    pitch_mode: PitchMode,
+    /// Pan spread of new grains (0 = mono, 1 = full ±1 pan)
+    stereo_width: f32,
+    /// Pan placement mode of new grains
+    pan_mode: PanMode,
+    /// Side of the next grain in alternate pan mode (-1 = left, 1 = right)
+    next_pan_side: f32,
+    /// Lowpass cutoff of new grains in Hz (infinite = no filter)
+    filter_cutoff: f32,
+    /// Random offset of each new grain's cutoff (±octaves)
+    filter_spread: f32,
+    /// Parameter smoothing time constant in milliseconds (0 = disabled)
+    smoothing_ms: f32,
+    /// Smoothed position, spray and density (persist across blocks)
+    smooth_position: f32,
+    smooth_spray: f32,
+    smooth_density: f32,
+    /// Whether the smoothers hold a value (false = snap to the next targets)
+    smoothing_primed: bool,
+    /// Running estimate of the number of overlapping grains (at least 1)
+    overlap_estimate: f32,
+    /// Whether the output limiter is active
+    limiter_enabled: bool,
+    /// Output limiter peak envelope (stereo-linked)
+    limiter_envelope: f32,
+    /// Output DC blockers (used when enabled via `memory::set_dc_blocker`)
+    dc_blocker_l: OnePole,
+    dc_blocker_r: OnePole,
+    /// Original (pre-resampling) source samples, interleaved
+    original_source: Vec<f32>,
+    /// Sample rate of `original_source` (0 = source was loaded without resampling)
+    original_rate: f32,
+    /// Channel count of `original_source`
+    original_channels: u32,
+    /// Engine sample rate the loaded source was resampled for
+    resampled_for_rate: f32,
+}
 
-/// Length of loaded source in samples (interleaved)
-static mut SOURCE_LEN: usize = 0;
+impl GranularState {
+    const fn new() -> Self {
+        Self {
+            grains: [Grain {
+                active: false,
+                source_pos: 0.0,
+                phase: 0.0,
+                rate: 1.0,
+                amp: 1.0,
+                size_samples: 256,
+                pan: 0.0,
+                filtered: false,
+                filter: [OnePole::new(); 2],
+            }; MAX_GRAINS],
+            rng_state: DEFAULT_SEED,
+            reset_seed: None,
+            source_len: 0,
+            source_channels: 1,
+            spawn_accumulator: 0.0,
+            time_jitter: 0.0,
+            last_onset_jitter: 0.0,
+            next_onset_jitter: 0.0,
+            clock_interval: 0.0,
+            clock_accumulator: 0.0,
+            clock_burst: 1,
+            spawn_cursor: 0,
+            region_start: 0.0,
+            region_end: 1.0,
+            region_loop: false,
+            scan_speed: 0.0,
+            scan_offset: 0.0,
+            loop_mode: LoopMode::Off,
+            live_mode: false,
+            live_frozen: false,
+            live_write_pos: 0,
+            interp: InterpMode::Linear,
+            pan_law: utils::PanLaw::ConstantPower,
+            stereo_grains: false,
+            zero_crossing_align: false,
+            transpose_rate: 1.0,
+            pitch_mode: PitchMode::Continuous,
+            stereo_width: DEFAULT_STEREO_WIDTH,
+            pan_mode: PanMode::Random,
+            next_pan_side: -1.0,
+            filter_cutoff: f32::INFINITY,
+            filter_spread: 0.0,
+            smoothing_ms: DEFAULT_SMOOTHING_MS,
+            smooth_position: 0.0,
+            smooth_spray: 0.0,
+            smooth_density: 1.0,
+            smoothing_primed: false,
+            overlap_estimate: 1.0,
+            limiter_enabled: true,
+            limiter_envelope: 0.0,
+            dc_blocker_l: OnePole::new(),
+            dc_blocker_r: OnePole::new(),
+            original_source: Vec::new(),
+            original_rate: 0.0,
+            original_channels: 1,
+            resampled_for_rate: 0.0,
+        }
+    }
+}
 
-/// Number of channels in source (1 or 2)
-static mut SOURCE_CHANNELS: u32 = 1;
+/// Granular state of every engine in the pool
+static mut STATES: [GranularState; memory::MAX_ENGINES] =
+    [const { GranularState::new() }; memory::MAX_ENGINES];
 
-/// Accumulator for grain spawn timing
-static mut SPAWN_ACCUMULATOR: f32 = 0.0;
+/// Granular state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only; use the pointer for place expressions
+/// rather than holding references across calls.
+#[inline]
+unsafe fn state() -> *mut GranularState {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
 
 // ============================================================================
 // RANDOM NUMBER GENERATION
@@ -115,7 +443,7 @@ static mut SPAWN_ACCUMULATOR: f32 = 0.0;
 unsafe fn random_f32() -> f32 {
     // Linear Congruential Generator (Numerical Recipes parameters)
     // SAFETY: Single-threaded WASM context, using raw pointer to avoid static mut ref
-    let state_ptr = addr_of_mut!(RNG_STATE);
+    let state_ptr = addr_of_mut!((*state()).rng_state);
     let state = (*state_ptr).wrapping_mul(1664525).wrapping_add(1013904223);
     *state_ptr = state;
     // Convert to float in [0, 1)
@@ -128,6 +456,39 @@ unsafe fn random_bipolar() -> f32 {
     random_f32() * 2.0 - 1.0
 }
 
+/// Random playback-rate multiplier for a new grain
+/// 
+/// # Arguments
+/// * `pitch_spread` - Spread amount (0-1), interpreted per PITCH_MODE
+#[inline]
+unsafe fn random_pitch_ratio(pitch_spread: f32) -> f32 {
+    match (*state()).pitch_mode {
+        // pitch_spread of 1.0 = ±1 octave
+        PitchMode::Continuous => 2.0_f32.powf(random_bipolar() * pitch_spread),
+        PitchMode::Octaves => {
+            // Leave unison with probability `pitch_spread`, up or down evenly
+            let r = random_f32();
+            if r < pitch_spread * 0.5 {
+                0.5
+            } else if r < pitch_spread {
+                2.0
+            } else {
+                1.0
+            }
+        }
+        PitchMode::Harmonic => {
+            // Leave unison with probability `pitch_spread`, any ratio evenly
+            let r = random_f32();
+            if r < pitch_spread {
+                let idx = (r / pitch_spread * HARMONIC_RATIOS.len() as f32) as usize;
+                HARMONIC_RATIOS[idx.min(HARMONIC_RATIOS.len() - 1)]
+            } else {
+                1.0
+            }
+        }
+    }
+}
+
 // ============================================================================
 // ENVELOPE
 // ============================================================================
@@ -151,6 +512,302 @@ fn envelope(phase: f32) -> f32 {
     simd_utils::envelope_lookup(phase)
 }
 
+// ============================================================================
+// GRAIN SPAWNING
+// ============================================================================
+
+/// Start a new grain in the next free pool slot
+/// 
+/// # Arguments
+/// * `grain_size` - Grain duration in samples
+/// * `pitch_spread` - Random pitch variation amount (0-1)
+/// * `position` - Base playback position within the source region (0-1),
+///   or seconds into the past in live mode
+/// * `spray` - Position randomization amount (0-1, relative to the region),
+///   or maximum extra delay in seconds in live mode
+/// * `onset_offset` - Samples elapsed since the grain's ideal onset
+/// * `source` - Source samples (or the live history ring), interleaved
+/// * `source_channels` - Number of channels in `source`
+/// 
+/// # Returns
+/// `false` if every grain slot is in use
+/// 
+/// # Safety
+/// Mutates the global grain pool and RNG state.
+unsafe fn spawn_grain(
+    grain_size: u32,
+    pitch_spread: f32,
+    position: f32,
+    spray: f32,
+    onset_offset: f32,
+    source: &[f32],
+    source_channels: u32,
+) -> bool {
+    let st = state();
+    let source_frames = source.len() / source_channels as usize;
+    let grains_ptr = addr_of_mut!((*st).grains);
+    let cursor_ptr = addr_of_mut!((*st).spawn_cursor);
+    
+    // Round-robin search so dense clouds don't rescan the busy head of the pool
+    for i in 0..MAX_GRAINS {
+        let slot = (*cursor_ptr + i) % MAX_GRAINS;
+        let grain = &mut (*grains_ptr)[slot];
+        if grain.active {
+            continue;
+        }
+        
+        let pos_offset = random_bipolar() * spray;
+        
+        // Calculate randomized pitch around the transposed base rate
+        let grain_rate = (*st).transpose_rate * random_pitch_ratio(pitch_spread);
+        
+        let live = (*st).live_mode;
+        let mut grain_pos = if live {
+            // Spray only adds delay so grains never start ahead of the write head
+            live_start_pos(position + pos_offset.abs(), grain_size, grain_rate)
+        } else {
+            // Randomized position (kept in range like the scan), mapped
+            // into the source region
+            let region_start = (*st).region_start;
+            let region_width = (*st).region_end - region_start;
+            let pos = match (*st).loop_mode {
+                LoopMode::Off => (position + pos_offset).clamp(0.0, 1.0),
+                LoopMode::Forward => wrap_unit(position + pos_offset),
+                LoopMode::PingPong => fold_unit(position + pos_offset),
+            };
+            region_start + pos * region_width
+        };
+        
+        // Nudge the start onto a zero crossing, once per grain (live
+        // starts leave room for the search on both sides)
+        if (*st).zero_crossing_align {
+            let frames = source_frames as f32;
+            let bounds = if live {
+                (0, source_frames)
+            } else {
+                (((*st).region_start * frames) as usize, ((*st).region_end * frames) as usize)
+            };
+            if let Some(crossing) = nearest_zero_crossing(source, source_channels, grain_pos * frames, bounds, live) {
+                grain_pos = crossing / frames;
+            }
+        }
+        
+        // Pan position within the stereo width
+        let width = (*st).stereo_width;
+        let grain_pan = match (*st).pan_mode {
+            PanMode::Random => random_bipolar() * width,
+            PanMode::Alternate => {
+                let side_ptr = addr_of_mut!((*st).next_pan_side);
+                let side = *side_ptr;
+                *side_ptr = -side;
+                side * width
+            }
+        };
+        
+        // Random amplitude variation (80-100%)
+        let grain_amp = 0.8 + random_f32() * 0.2;
+        
+        // Lowpass at a random offset from the cutoff; a cutoff at or above
+        // Nyquist leaves the grain unfiltered (the RNG is only drawn with
+        // the filter on, so seeded sequences don't change without it)
+        let mut cutoff = (*st).filter_cutoff;
+        let nyquist = memory::sample_rate() * 0.5;
+        if cutoff < nyquist && (*st).filter_spread > 0.0 {
+            cutoff *= libm::exp2f(random_bipolar() * (*st).filter_spread);
+        }
+        grain.filtered = cutoff < nyquist;
+        if grain.filtered {
+            for filter in grain.filter.iter_mut() {
+                filter.reset();
+                filter.set_lowpass(cutoff, memory::sample_rate());
+            }
+        }
+        
+        // Initialize grain, advanced by however late it starts
+        grain.active = true;
+        grain.source_pos = grain_pos + grain_rate * onset_offset / source_frames as f32;
+        if grain.source_pos >= 1.0 {
+            // Only reachable in live mode, where positions wrap around the ring
+            grain.source_pos -= 1.0;
+        }
+        grain.phase = onset_offset / grain_size as f32;
+        grain.rate = grain_rate;
+        grain.amp = grain_amp;
+        grain.size_samples = grain_size;
+        grain.pan = grain_pan;
+        
+        *cursor_ptr = (slot + 1) % MAX_GRAINS;
+        return true;
+    }
+    
+    false
+}
+
+// ============================================================================
+// SOURCE READING
+// ============================================================================
+
+/// Read a mono sample at a fractional frame position (linear interpolation)
+/// 
+/// Stereo sources are averaged to mono. With `wrap` the last frame
+/// interpolates into the first (circular buffer); otherwise positions past
+/// the last frame read as 0.0.
+#[inline]
+fn read_source(source: &[f32], channels: u32, pos: f32, wrap: bool) -> f32 {
+    let frames = source.len() / channels as usize;
+    interp_linear(frames, pos, wrap, |frame| source_frame(source, channels, frame))
+}
+
+/// Read a mono sample at a fractional frame position (Catmull-Rom)
+/// 
+/// Same addressing as `read_source`. The outer taps wrap with `wrap`;
+/// otherwise they repeat the first/last frame so the curve stays bounded
+/// at the source edges.
+#[inline]
+fn read_source_cubic(source: &[f32], channels: u32, pos: f32, wrap: bool) -> f32 {
+    let frames = source.len() / channels as usize;
+    interp_cubic(frames, pos, wrap, |frame| source_frame(source, channels, frame))
+}
+
+/// Read a left/right pair at a fractional frame position
+/// 
+/// Each channel is interpolated on its own (same addressing as
+/// `read_source`); mono sources are duplicated to both channels.
+#[inline]
+fn read_source_stereo(source: &[f32], channels: u32, pos: f32, wrap: bool, interp: InterpMode) -> (f32, f32) {
+    let frames = source.len() / channels as usize;
+    let read = |channel: usize| {
+        let sample = |frame: usize| source[frame * channels as usize + channel];
+        match interp {
+            InterpMode::Linear => interp_linear(frames, pos, wrap, sample),
+            InterpMode::Cubic => interp_cubic(frames, pos, wrap, sample),
+        }
+    };
+    
+    if channels == 2 {
+        (read(0), read(1))
+    } else {
+        let mono = read(0);
+        (mono, mono)
+    }
+}
+
+/// Linear interpolation between the frames around `pos`
+#[inline]
+fn interp_linear(frames: usize, pos: f32, wrap: bool, sample: impl Fn(usize) -> f32) -> f32 {
+    let idx = pos as usize;
+    let mut next_idx = idx + 1;
+    if wrap && next_idx == frames {
+        next_idx = 0;
+    }
+    if next_idx >= frames {
+        return 0.0;
+    }
+    
+    let frac = pos - idx as f32;
+    let s0 = sample(idx);
+    let s1 = sample(next_idx);
+    s0 + (s1 - s0) * frac
+}
+
+/// Catmull-Rom interpolation over the four frames around `pos`
+#[inline]
+fn interp_cubic(frames: usize, pos: f32, wrap: bool, sample: impl Fn(usize) -> f32) -> f32 {
+    let idx = pos as usize;
+    if !wrap && idx + 1 >= frames {
+        return 0.0;
+    }
+    
+    let tap = |offset: isize| {
+        let i = idx as isize + offset;
+        let i = if wrap {
+            i.rem_euclid(frames as isize)
+        } else {
+            i.clamp(0, frames as isize - 1)
+        };
+        sample(i as usize)
+    };
+    utils::cubic_interp(tap(-1), tap(0), tap(1), tap(2), pos - idx as f32)
+}
+
+/// Mono sample of one source frame (stereo frames are averaged)
+#[inline]
+fn source_frame(source: &[f32], channels: u32, frame: usize) -> f32 {
+    if channels == 2 {
+        (source[frame * 2] + source[frame * 2 + 1]) * 0.5
+    } else {
+        source[frame]
+    }
+}
+
+/// Fractional frame position of the source zero crossing nearest `start`
+/// 
+/// Looks for a sign change of the mono mix between neighboring frames up to
+/// ZERO_CROSSING_SEARCH frames either side of `start`, and places the
+/// crossing between the two by linear interpolation. Only frames within
+/// `bounds` (first frame, end frame) are read; with `wrap` the search
+/// wraps around the source end instead (circular buffer).
+/// 
+/// # Returns
+/// None if the source doesn't cross zero within the window
+fn nearest_zero_crossing(
+    source: &[f32],
+    channels: u32,
+    start: f32,
+    bounds: (usize, usize),
+    wrap: bool,
+) -> Option<f32> {
+    let frames = source.len() / channels as usize;
+    let (first, end) = bounds;
+    let base = start as isize;
+    let mut nearest: Option<f32> = None;
+    
+    // Frame pairs (i, i + 1) outward from the one `start` falls in; pairs
+    // `step` away can't hold a crossing nearer than `step - 1` frames
+    for step in 0..=ZERO_CROSSING_SEARCH as isize {
+        if nearest.is_some_and(|n| (n - start).abs() < (step - 1) as f32) {
+            break;
+        }
+        for i in [base + step, base - step] {
+            let (a, b) = if wrap {
+                (i.rem_euclid(frames as isize), (i + 1).rem_euclid(frames as isize))
+            } else if i >= first as isize && i + 1 < end as isize {
+                (i, i + 1)
+            } else {
+                continue;
+            };
+            let s0 = source_frame(source, channels, a as usize);
+            let s1 = source_frame(source, channels, b as usize);
+            if (s0 < 0.0) == (s1 < 0.0) && s0 != 0.0 {
+                continue;
+            }
+            let crossing = i as f32 + if s0 == s1 { 0.0 } else { s0 / (s0 - s1) };
+            if nearest.is_none_or(|n| (crossing - start).abs() < (n - start).abs()) {
+                nearest = Some(crossing);
+            }
+        }
+    }
+    
+    nearest.map(|crossing| if wrap { crossing.rem_euclid(frames as f32) } else { crossing })
+}
+
+/// Place a stereo grain in the field by rotating rather than collapsing it
+/// 
+/// At pan 0 the pair passes unchanged. Panning right rotates the left
+/// channel towards the right output (by up to 90 degrees at pan 1) while
+/// the right channel stays put, and vice versa. Each source channel keeps
+/// its power, so a full-width stereo grain doesn't get quieter off-center.
+#[inline]
+fn rotate_stereo(left: f32, right: f32, pan: f32) -> (f32, f32) {
+    let angle = pan.abs() * core::f32::consts::FRAC_PI_2;
+    let (sin, cos) = (libm::sinf(angle), libm::cosf(angle));
+    if pan >= 0.0 {
+        (left * cos, right + left * sin)
+    } else {
+        (left + right * sin, right * cos)
+    }
+}
+
 // ============================================================================
 // MAIN PROCESSING
 // ============================================================================
@@ -159,13 +816,16 @@ fn envelope(phase: f32) -> f32 {
 /// 
 /// # Arguments
 /// * `grain_size` - Grain duration in samples (64-4096)
-/// * `density` - Grains spawned per second (1-100)
+/// * `density` - Grains spawned per second (1-MAX_DENSITY)
 /// * `pitch_spread` - Random pitch variation amount (0-1)
-/// * `position` - Base playback position in source (0-1)
-/// * `spray` - Position randomization amount (0-1)
+/// * `position` - Base playback position within the source region (0-1,
+///   moved by the scan), or seconds into the past in live mode
+/// * `spray` - Position randomization amount (0-1, relative to the region),
+///   or maximum extra delay in seconds in live mode
 /// 
 /// # Safety
-/// Reads from WASM linear memory at GRANULAR_SOURCE_OFFSET.
+/// Reads from WASM linear memory at GRANULAR_SOURCE_OFFSET
+/// (LIVE_HISTORY_OFFSET in live mode).
 /// Writes to output buffers via memory module.
 pub fn process(
     grain_size: u32,
@@ -173,12 +833,33 @@ pub fn process(
     pitch_spread: f32,
     position: f32,
     spray: f32,
+) {
+    process_with_envelope(grain_size, density, pitch_spread, position, spray, envelope);
+}
+
+/// `process` with the grain envelope passed in
+/// 
+/// Lets tests render the same cloud with the exact raised cosine.
+#[inline]
+fn process_with_envelope(
+    grain_size: u32,
+    density: f32,
+    pitch_spread: f32,
+    position: f32,
+    spray: f32,
+    envelope: impl Fn(f32) -> f32,
 ) {
     unsafe {
-        // Early exit if no source loaded
         // SAFETY: Single-threaded WASM context
-        let source_len = *addr_of!(SOURCE_LEN);
-        if source_len == 0 {
+        let st = state();
+        let live = (*st).live_mode;
+        if live && !(*st).live_frozen {
+            record_live_input();
+        }
+        
+        // Early exit if no source loaded
+        let source_len = (*st).source_len;
+        if !live && source_len == 0 {
             // Clear output buffers using SIMD
             let output_l = memory::output_slice_mut(0);
             let output_r = memory::output_slice_mut(1);
@@ -192,9 +873,13 @@ pub fn process(
         
         // Clamp parameters to valid ranges
         let grain_size = grain_size.clamp(MIN_GRAIN_SIZE, MAX_GRAIN_SIZE);
-        let density = density.clamp(1.0, 100.0);
+        let density = density.clamp(1.0, MAX_DENSITY);
         let pitch_spread = pitch_spread.clamp(0.0, 1.0);
-        let position = position.clamp(0.0, 1.0);
+        let position = if live {
+            position.clamp(0.0, live_history_seconds(sample_rate))
+        } else {
+            position.clamp(0.0, 1.0)
+        };
         let spray = spray.clamp(0.0, 1.0);
         
         // Get output buffer slices
@@ -205,56 +890,137 @@ pub fn process(
         simd_utils::clear_buffer(output_l);
         simd_utils::clear_buffer(output_r);
         
-        // Get source buffer
-        let source = get_source_slice();
-        let source_channels = *addr_of!(SOURCE_CHANNELS);
-        let source_frames = source_len / source_channels as usize;
+        // Get source buffer (the live history ring is always stereo)
+        let (source, source_channels) = if live {
+            (&*memory::live_history_slice_mut(), 2)
+        } else {
+            (get_source_slice(), (*st).source_channels)
+        };
+        let source_frames = source.len() / source_channels as usize;
+        let interp = (*st).interp;
+        let pan_law = (*st).pan_law;
+        let stereo_grains = (*st).stereo_grains;
+        
+        // Parameter smoothers start at their targets after a reset
+        let position_ptr = addr_of_mut!((*st).smooth_position);
+        let spray_ptr = addr_of_mut!((*st).smooth_spray);
+        let density_ptr = addr_of_mut!((*st).smooth_density);
+        if !(*st).smoothing_primed {
+            *position_ptr = position;
+            *spray_ptr = spray;
+            *density_ptr = density;
+            (*st).smoothing_primed = true;
+        }
+        let smoothing_coeff = smoothing_coeff(sample_rate);
+        let overlap_coeff = 1.0 - libm::expf(-1000.0 / (OVERLAP_SMOOTHING_MS * sample_rate));
+        let overlap_ptr = addr_of_mut!((*st).overlap_estimate);
+        
+        // Source region (fixed for the block)
+        let region_start = (*st).region_start;
+        let region_end = (*st).region_end;
+        let region_loop = (*st).region_loop;
         
-        // Calculate spawn interval (samples between grains)
-        let spawn_interval = sample_rate / density;
+        // Scan advance per sample, normalized to the region (live mode has
+        // no scan: its position is relative to the write head)
+        let scan_step = if live {
+            0.0
+        } else {
+            (*st).scan_speed / (source_frames as f32 * (region_end - region_start))
+        };
+        let loop_mode = (*st).loop_mode;
+        let scan_ptr = addr_of_mut!((*st).scan_offset);
         
         // Process each sample in the block
         for sample_idx in 0..buffer_size {
+            // ================================================================
+            // PARAMETER SMOOTHING
+            // ================================================================
+            
+            *position_ptr += (position - *position_ptr) * smoothing_coeff;
+            *spray_ptr += (spray - *spray_ptr) * smoothing_coeff;
+            *density_ptr += (density - *density_ptr) * smoothing_coeff;
+            
+            // Advance the scan and keep it within the region bounds
+            let base_position = if live {
+                *position_ptr
+            } else {
+                match loop_mode {
+                    LoopMode::Off => {
+                        *scan_ptr = (*scan_ptr + scan_step).clamp(-*position_ptr, 1.0 - *position_ptr);
+                        *position_ptr + *scan_ptr
+                    }
+                    LoopMode::Forward => {
+                        *scan_ptr = wrap_unit(*scan_ptr + scan_step);
+                        wrap_unit(*position_ptr + *scan_ptr)
+                    }
+                    LoopMode::PingPong => {
+                        // Folding a continuous offset reverses the direction
+                        // at the bounds without a jump
+                        *scan_ptr = (*scan_ptr + scan_step).rem_euclid(2.0);
+                        fold_unit(*position_ptr + *scan_ptr)
+                    }
+                }
+            };
+            
+            // Calculate spawn interval (samples between grains)
+            let spawn_interval = sample_rate / *density_ptr;
+            
             // ================================================================
             // GRAIN SPAWNING
             // ================================================================
             
             // SAFETY: Single-threaded WASM, using raw pointers for Rust 2024 compatibility
-            let spawn_acc_ptr = addr_of_mut!(SPAWN_ACCUMULATOR);
-            *spawn_acc_ptr += 1.0;
+            let spawn_acc_ptr = addr_of_mut!((*st).spawn_accumulator);
+            let clock_acc_ptr = addr_of_mut!((*st).clock_accumulator);
             
-            if *spawn_acc_ptr >= spawn_interval {
-                *spawn_acc_ptr -= spawn_interval;
+            // On the clock a burst spawns at each tick and the density
+            // accumulator stands still
+            let clock_interval = (*st).clock_interval;
+            if clock_interval > 0.0 {
+                if *clock_acc_ptr >= clock_interval {
+                    *clock_acc_ptr -= clock_interval;
+                    let onset_offset = *clock_acc_ptr;
+                    for _ in 0..(*st).clock_burst {
+                        if !spawn_grain(grain_size, pitch_spread, base_position, *spray_ptr, onset_offset, source, source_channels) {
+                            break;
+                        }
+                    }
+                }
+                *clock_acc_ptr += 1.0;
+            } else {
+                *spawn_acc_ptr += 1.0;
                 
-                // Find an inactive grain slot
-                let grains_ptr = addr_of_mut!(GRAINS);
-                for grain in (*grains_ptr).iter_mut() {
-                    if !grain.active {
-                        // Calculate randomized position
-                        let pos_offset = random_bipolar() * spray;
-                        let grain_pos = (position + pos_offset).clamp(0.0, 1.0);
-                        
-                        // Calculate randomized pitch
-                        // pitch_spread of 1.0 = ±1 octave
-                        let pitch_offset = random_bipolar() * pitch_spread;
-                        let grain_rate = 2.0_f32.powf(pitch_offset);
-                        
-                        // Random pan position
-                        let grain_pan = random_bipolar() * 0.7; // ±70% pan spread
-                        
-                        // Random amplitude variation (80-100%)
-                        let grain_amp = 0.8 + random_f32() * 0.2;
-                        
-                        // Initialize grain
-                        grain.active = true;
-                        grain.source_pos = grain_pos;
-                        grain.phase = 0.0;
-                        grain.rate = grain_rate;
-                        grain.amp = grain_amp;
-                        grain.size_samples = grain_size;
-                        grain.pan = grain_pan;
-                        
-                        break; // Only spawn one grain per interval
+                // At very high densities the interval drops below one
+                // sample, so several grains may be due within the same
+                // sample. With time jitter each grain is moved off its grid
+                // point, and the grid itself stays on the interval, so the
+                // average density holds.
+                loop {
+                    let threshold = spawn_interval * (1.0 + (*st).next_onset_jitter - (*st).last_onset_jitter);
+                    if *spawn_acc_ptr < threshold {
+                        break;
+                    }
+                    *spawn_acc_ptr -= threshold;
+                    (*st).last_onset_jitter = (*st).next_onset_jitter;
+                    (*st).next_onset_jitter = if (*st).time_jitter > 0.0 { random_bipolar() * (*st).time_jitter } else { 0.0 };
+                    
+                    // Samples elapsed since this grain's ideal onset. Grains
+                    // due in the same sample get staggered start phases
+                    // instead of all starting in lockstep.
+                    let onset_offset = *spawn_acc_ptr;
+                    
+                    if !spawn_grain(
+                        grain_size,
+                        pitch_spread,
+                        base_position,
+                        *spray_ptr,
+                        onset_offset,
+                        source,
+                        source_channels,
+                    ) {
+                        // Pool exhausted - drop the remaining spawns for this sample
+                        *spawn_acc_ptr %= spawn_interval;
+                        break;
                     }
                 }
             }
@@ -263,48 +1029,49 @@ pub fn process(
             // GRAIN PROCESSING
             // ================================================================
             
-            let grains_ptr = addr_of_mut!(GRAINS);
+            let mut active_grains = 0usize;
+            let grains_ptr = addr_of_mut!((*st).grains);
             for grain in (*grains_ptr).iter_mut() {
                 if !grain.active {
                     continue;
                 }
+                active_grains += 1;
                 
-                // Calculate source position in samples
+                // Read sample from source (the live ring wraps at its end)
                 let source_sample_pos = grain.source_pos * source_frames as f32;
-                let source_idx = source_sample_pos as usize;
+                let gain = envelope(grain.phase) * grain.amp;
                 
-                // Read sample from source with linear interpolation
-                let sample = if source_idx < source_frames - 1 {
-                    let frac = source_sample_pos - source_idx as f32;
-                    
-                    if source_channels == 2 {
-                        // Stereo source: average L+R for mono grain
-                        let idx = source_idx * 2;
-                        let s0 = (source[idx] + source[idx + 1]) * 0.5;
-                        let s1 = (source[idx + 2] + source[idx + 3]) * 0.5;
-                        s0 + (s1 - s0) * frac
+                if stereo_grains {
+                    // Keep the source's own L/R and rotate it by the pan
+                    let (left, right) = read_source_stereo(
+                        source,
+                        source_channels,
+                        source_sample_pos,
+                        live,
+                        interp,
+                    );
+                    let (left, right) = if grain.filtered {
+                        (grain.filter[0].process(left), grain.filter[1].process(right))
                     } else {
-                        // Mono source
-                        let s0 = source[source_idx];
-                        let s1 = source[source_idx + 1];
-                        s0 + (s1 - s0) * frac
-                    }
+                        (left, right)
+                    };
+                    let (left, right) = rotate_stereo(left, right, grain.pan);
+                    output_l[sample_idx] += left * gain;
+                    output_r[sample_idx] += right * gain;
                 } else {
-                    0.0
-                };
-                
-                // Apply envelope
-                let env = envelope(grain.phase);
-                let out = sample * env * grain.amp;
-                
-                // Apply stereo pan (constant power)
-                // pan: -1 = left, 0 = center, 1 = right
-                let pan_norm = (grain.pan + 1.0) * 0.5; // 0 to 1
-                let left_gain = (1.0 - pan_norm).sqrt();
-                let right_gain = pan_norm.sqrt();
-                
-                output_l[sample_idx] += out * left_gain;
-                output_r[sample_idx] += out * right_gain;
+                    let sample = match interp {
+                        InterpMode::Linear => read_source(source, source_channels, source_sample_pos, live),
+                        InterpMode::Cubic => read_source_cubic(source, source_channels, source_sample_pos, live),
+                    };
+                    let sample = if grain.filtered { grain.filter[0].process(sample) } else { sample };
+                    let out = sample * gain;
+                    
+                    // Apply stereo pan
+                    let (left_gain, right_gain) = utils::pan_gains(grain.pan, pan_law);
+                    
+                    output_l[sample_idx] += out * left_gain;
+                    output_r[sample_idx] += out * right_gain;
+                }
                 
                 // Advance grain playback position
                 // rate affects how fast we move through source
@@ -313,21 +1080,81 @@ pub fn process(
                 // Advance envelope phase
                 grain.phase += 1.0 / grain.size_samples as f32;
                 
+                // Live grains wrap around the ring; source grains running
+                // past the region end wrap or stop
+                if live {
+                    if grain.source_pos >= 1.0 {
+                        grain.source_pos -= 1.0;
+                    }
+                } else if grain.source_pos >= region_end {
+                    if region_loop {
+                        grain.source_pos -= region_end - region_start;
+                    } else {
+                        grain.active = false;
+                    }
+                }
+                
                 // Deactivate finished grains
-                if grain.phase >= 1.0 || grain.source_pos >= 1.0 {
+                if grain.phase >= 1.0 {
                     grain.active = false;
                 }
             }
+            
+            // ================================================================
+            // OUTPUT NORMALIZATION
+            // ================================================================
+            
+            // Normalize by a running estimate of the grains actually playing
+            // (uncorrelated grains sum in power, hence the sqrt). Slewing the
+            // estimate per sample keeps density sweeps free of gain steps.
+            *overlap_ptr += ((active_grains.max(1) as f32) - *overlap_ptr) * overlap_coeff;
+            let output_gain = 1.0 / (*overlap_ptr).sqrt();
+            output_l[sample_idx] *= output_gain;
+            output_r[sample_idx] *= output_gain;
         }
         
-        // Apply output gain to prevent clipping from overlapping grains
-        // Normalize by approximate number of overlapping grains
-        let overlap_estimate = (density * grain_size as f32 / sample_rate).max(1.0);
-        let output_gain = 1.0 / overlap_estimate.sqrt();
+        // Remove DC drift from asymmetric grain overlaps before limiting
+        if memory::is_dc_blocker_enabled() {
+            (*st).dc_blocker_l.set_dc_blocker(sample_rate);
+            (*st).dc_blocker_r.set_dc_blocker(sample_rate);
+            (*st).dc_blocker_l.process_buffer(output_l);
+            (*st).dc_blocker_r.process_buffer(output_r);
+        } else {
+            (*st).dc_blocker_l.reset();
+            (*st).dc_blocker_r.reset();
+        }
+        
+        if (*st).limiter_enabled {
+            limit_output(output_l, output_r, sample_rate);
+        }
+    }
+}
+
+/// Stereo-linked peak limiter for the granular output
+/// 
+/// A peak envelope with 1ms attack and 100ms release sets the gain
+/// reduction. With no lookahead, the attack can't catch a sudden peak in
+/// time, so samples still above the ceiling are clamped to it.
+/// 
+/// # Safety
+/// Mutates the global limiter envelope.
+unsafe fn limit_output(output_l: &mut [f32], output_r: &mut [f32], sample_rate: f32) {
+    let attack = 1.0 - libm::expf(-1000.0 / (LIMITER_ATTACK_MS * sample_rate));
+    let release = 1.0 - libm::expf(-1000.0 / (LIMITER_RELEASE_MS * sample_rate));
+    let env_ptr = addr_of_mut!((*state()).limiter_envelope);
+    
+    for (l, r) in output_l.iter_mut().zip(output_r.iter_mut()) {
+        let peak = l.abs().max(r.abs());
+        let coeff = if peak > *env_ptr { attack } else { release };
+        *env_ptr += (peak - *env_ptr) * coeff;
         
-        // Apply output gain using SIMD
-        simd_utils::scale_buffer(output_l, output_gain);
-        simd_utils::scale_buffer(output_r, output_gain);
+        if *env_ptr > LIMITER_CEILING {
+            let gain = LIMITER_CEILING / *env_ptr;
+            *l *= gain;
+            *r *= gain;
+        }
+        *l = l.clamp(-LIMITER_CEILING, LIMITER_CEILING);
+        *r = r.clamp(-LIMITER_CEILING, LIMITER_CEILING);
     }
 }
 
@@ -337,61 +1164,1695 @@ pub fn process(
 
 /// Load source audio buffer for granular synthesis
 /// 
+/// A source recorded at another sample rate is converted to the engine
+/// rate in place, so grains play back at the original pitch; converted
+/// output that would exceed MAX_GRANULAR_SOURCE_SAMPLES is truncated. The
+/// original is kept so `resample_source_for_engine_rate` can redo the
+/// conversion after a re-init at another rate.
+/// 
 /// # Arguments
 /// * `ptr` - Pointer to source samples in WASM memory (not used directly,
 ///           samples are at GRANULAR_SOURCE_OFFSET)
-/// * `length` - Number of sample frames
+/// * `length` - Number of sample frames (at `source_rate`)
 /// * `channels` - Number of channels (1 or 2)
+/// * `source_rate` - Sample rate the source was recorded at; 0 loads it
+///   unconverted (already at the engine rate)
+/// 
+/// # Returns
+/// LOAD_OK, LOAD_TRUNCATED if the source (before or after conversion)
+/// exceeds MAX_GRANULAR_SOURCE_SAMPLES, or LOAD_REJECTED for an invalid
+/// channel count
 /// 
 /// # Note
 /// The actual samples are written to WASM memory by JavaScript at
 /// GRANULAR_SOURCE_OFFSET before calling this function.
-pub fn load_source(_ptr: *const f32, length: u32, channels: u32) {
+pub fn load_source(_ptr: *const f32, length: u32, channels: u32, source_rate: f32) -> u32 {
+    if !(1..=2).contains(&channels) {
+        return LOAD_REJECTED;
+    }
+    if source_rate <= 0.0 {
+        unsafe {
+            // SAFETY: Single-threaded WASM context
+            // A plain load replaces any resampled source
+            (*state()).original_rate = 0.0;
+            return set_source(length, channels);
+        }
+    }
+    let requested = (length as usize).saturating_mul(channels as usize);
+    let samples = requested.min(memory::MAX_GRANULAR_SOURCE_SAMPLES);
+    
     unsafe {
-        // Store metadata about the loaded source
-        // SAFETY: Single-threaded WASM context, using raw pointers for Rust 2024
-        *addr_of_mut!(SOURCE_LEN) = (length * channels) as usize;
-        *addr_of_mut!(SOURCE_CHANNELS) = channels.clamp(1, 2);
+        // SAFETY: Single-threaded WASM context; the source region is not
+        // referenced elsewhere while loading
+        let st = state();
+        let original = &mut (*st).original_source;
+        original.clear();
+        original.extend_from_slice(std::slice::from_raw_parts(
+            memory::get_granular_source_ptr() as *const f32,
+            samples,
+        ));
+        (*st).original_rate = source_rate.max(1.0);
+        (*st).original_channels = channels;
         
-        // Reset all grains when loading new source
-        let grains_ptr = addr_of_mut!(GRAINS);
-        for grain in (*grains_ptr).iter_mut() {
-            grain.active = false;
+        let status = resample_original();
+        if samples < requested { LOAD_TRUNCATED } else { status }
+    }
+}
+
+/// Redo source sample-rate conversion if the engine rate has changed
+/// 
+/// Called after engine (re-)initialization. Does nothing unless the source
+/// was loaded with a source rate.
+pub fn resample_source_for_engine_rate() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        if (*st).original_rate > 0.0 && (*st).resampled_for_rate != memory::sample_rate() {
+            resample_original();
         }
-        
-        // Reset spawn accumulator
-        *addr_of_mut!(SPAWN_ACCUMULATOR) = 0.0;
-        
-        // Update engine state flags
-        memory::set_granular_source_len(length);
     }
 }
 
-/// Get a slice reference to the granular source buffer
+/// Convert ORIGINAL_SOURCE to the engine rate into the source region
+/// 
+/// Returns LOAD_TRUNCATED if the converted source didn't fit the region.
 /// 
 /// # Safety
-/// Source must be loaded (SOURCE_LEN > 0)
-#[inline]
-unsafe fn get_source_slice() -> &'static [f32] {
-    std::slice::from_raw_parts(
-        memory::GRANULAR_SOURCE_OFFSET as *const f32,
-        *addr_of!(SOURCE_LEN)
-    )
+/// Writes the granular source region; no other references to it may exist.
+unsafe fn resample_original() -> u32 {
+    let st = state();
+    let original = &(*st).original_source;
+    let channels = (*st).original_channels;
+    let engine_rate = memory::sample_rate();
+    let ratio = engine_rate / (*st).original_rate;
+    
+    let region = std::slice::from_raw_parts_mut(
+        memory::get_granular_source_ptr(),
+        memory::MAX_GRANULAR_SOURCE_SAMPLES,
+    );
+    let original_frames = original.len() / channels as usize;
+    let (frames, expected_frames) = if ratio == 1.0 {
+        region[..original.len()].copy_from_slice(original);
+        (original_frames, original_frames)
+    } else {
+        let resampler = utils::Resampler::new(ratio);
+        (resampler.process(original, region, channels as usize), resampler.output_frames(original_frames))
+    };
+    
+    (*st).resampled_for_rate = engine_rate;
+    let status = set_source(frames as u32, channels);
+    if frames < expected_frames { LOAD_TRUNCATED } else { status }
 }
 
-// ============================================================================
-// UTILITY
-// ============================================================================
+/// Record source metadata and reset playback for a newly loaded source
+/// 
+/// Sources longer than the granular source region are truncated so grains
+/// can never read past it.
+/// 
+/// # Returns
+/// LOAD_OK, or LOAD_TRUNCATED if `length` frames didn't fit
+/// 
+/// # Safety
+/// Mutates the selected engine's granular state.
+unsafe fn set_source(length: u32, channels: u32) -> u32 {
+    let st = state();
+    let channels = channels.clamp(1, 2);
+    let max_frames = (memory::MAX_GRANULAR_SOURCE_SAMPLES / channels as usize) as u32;
+    let frames = length.min(max_frames);
+    
+    // Store metadata about the loaded source
+    (*st).source_len = frames as usize * channels as usize;
+    (*st).source_channels = channels;
+    
+    // Reset all grains when loading new source
+    let grains_ptr = addr_of_mut!((*st).grains);
+    for grain in (*grains_ptr).iter_mut() {
+        grain.active = false;
+    }
+    
+    // Reset spawn accumulator
+    (*st).spawn_accumulator = 0.0;
+    (*st).last_onset_jitter = 0.0;
+    (*st).next_onset_jitter = 0.0;
+    (*st).clock_accumulator = (*st).clock_interval;
+    (*st).spawn_cursor = 0;
+    
+    // A new source starts with the full region and unsmoothed parameters
+    (*st).region_start = 0.0;
+    (*st).region_end = 1.0;
+    (*st).smoothing_primed = false;
+    (*st).overlap_estimate = 1.0;
+    (*st).limiter_envelope = 0.0;
+    
+    // Update engine state flags
+    memory::set_granular_source_len(frames * channels);
+    
+    if frames < length { LOAD_TRUNCATED } else { LOAD_OK }
+}
 
-/// Reset granular engine state
-/// Called when switching effects or stopping playback
-pub fn reset() {
+/// Number of source frames accepted by the last load (0 = no source)
+pub fn source_frames() -> u32 {
     unsafe {
         // SAFETY: Single-threaded WASM context
-        let grains_ptr = addr_of_mut!(GRAINS);
-        for grain in (*grains_ptr).iter_mut() {
-            grain.active = false;
-        }
-        *addr_of_mut!(SPAWN_ACCUMULATOR) = 0.0;
+        let st = state();
+        ((*st).source_len / (*st).source_channels as usize) as u32
+    }
+}
+
+/// Channel count of the loaded source (0 = no source)
+pub fn source_channels() -> u32 {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        if (*st).source_len == 0 { 0 } else { (*st).source_channels }
+    }
+}
+
+/// Duration of the loaded source in milliseconds at the engine sample rate
+/// 
+/// Resampled sources are stored at the engine rate, so this is the real
+/// playback duration either way.
+pub fn source_duration_ms() -> f32 {
+    source_frames() as f32 * 1000.0 / memory::sample_rate()
+}
+
+/// Restrict grains to a sub-region of the source
+/// 
+/// `position` and `spray` map into the region, and the scan loops within
+/// it according to the loop mode. Inverted bounds are swapped
+/// and regions narrower than MIN_REGION_WIDTH are widened. The region
+/// persists until changed or until a new source is loaded.
+/// 
+/// # Arguments
+/// * `start` - Region start (normalized, 0-1)
+/// * `end` - Region end (normalized, 0-1)
+pub fn set_region(start: f32, end: f32) {
+    let mut start = start.clamp(0.0, 1.0);
+    let mut end = end.clamp(0.0, 1.0);
+    if end < start {
+        core::mem::swap(&mut start, &mut end);
+    }
+    if end - start < MIN_REGION_WIDTH {
+        end = (start + MIN_REGION_WIDTH).min(1.0);
+        start = end - MIN_REGION_WIDTH;
+    }
+    
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        (*st).region_start = start;
+        (*st).region_end = end;
+    }
+}
+
+/// Set whether grains running past the region end wrap to its start
+/// 
+/// # Arguments
+/// * `enabled` - true = wrap, false = grain stops at the region end
+pub fn set_region_loop(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).region_loop = enabled;
+    }
+}
+
+/// Set how fast the base position scans through the source
+/// 
+/// Decoupled from grain pitch: near 0 (with little spray) grains keep
+/// reading the same spot, stretching the sound indefinitely; at 1 the scan
+/// moves at the source's own speed. Setting 0 freezes the scan where it is.
+/// 
+/// # Arguments
+/// * `speed` - Multiple of real time (-4 to 4, negative = backwards)
+pub fn set_scan_speed(speed: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).scan_speed = speed.clamp(-MAX_SCAN_SPEED, MAX_SCAN_SPEED);
+    }
+}
+
+/// Set what the scan does at the region bounds
+/// 
+/// Spray is kept within the region the same way: clamped, wrapped or
+/// reflected at the bounds.
+/// 
+/// # Arguments
+/// * `mode` - Stop at the bound, wrap around, or ping-pong
+pub fn set_loop_mode(mode: LoopMode) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        if (*st).loop_mode != mode {
+            // Offsets of one mode don't carry over to another (ping-pong
+            // uses 0..2)
+            (*st).loop_mode = mode;
+            (*st).scan_offset = 0.0;
+        }
+    }
+}
+
+/// Set the smoothing time of the position, spray and density parameters
+/// 
+/// # Arguments
+/// * `time_ms` - One-pole time constant in milliseconds (0 to 2000);
+///   0 disables smoothing for hosts that already smooth their automation
+pub fn set_smoothing_time(time_ms: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).smoothing_ms = time_ms.clamp(0.0, MAX_SMOOTHING_MS);
+    }
+}
+
+/// Per-sample one-pole coefficient for the current smoothing time
+/// 
+/// Returns 1.0 (follow the target immediately) when smoothing is disabled.
+#[inline]
+fn smoothing_coeff(sample_rate: f32) -> f32 {
+    // SAFETY: Single-threaded WASM context
+    let time_ms = unsafe { (*state()).smoothing_ms };
+    if time_ms <= 0.0 {
+        1.0
+    } else {
+        1.0 - libm::expf(-1000.0 / (time_ms * sample_rate))
+    }
+}
+
+/// Transpose the grain cloud
+/// 
+/// Sets the base playback rate that `pitch_spread` randomizes around. Only
+/// grains spawned afterwards use the new rate; grains already playing keep
+/// theirs and fade out naturally, so changes glide rather than jump.
+/// 
+/// # Arguments
+/// * `semitones` - Transpose in semitones (-24 to +24)
+pub fn set_transpose(semitones: f32) {
+    let rate = 2.0_f32.powf(semitones.clamp(-24.0, 24.0) / 12.0);
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).transpose_rate = rate;
+    }
+}
+
+/// Enable or disable the output limiter
+/// 
+/// The limiter (on by default) keeps dense or correlated clouds within
+/// ±1.0; disabling it passes the normalized grain sum through untouched.
+pub fn set_limiter_enabled(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        (*st).limiter_enabled = enabled;
+        if !enabled {
+            (*st).limiter_envelope = 0.0;
+        }
+    }
+}
+
+/// Set the stereo width of the grain cloud
+/// 
+/// Only affects grains spawned afterwards. Panning stays constant-power.
+/// 
+/// # Arguments
+/// * `width` - 0 = all grains centered, 1 = pans across the full ±1 range
+pub fn set_stereo_width(width: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).stereo_width = width.clamp(0.0, 1.0);
+    }
+}
+
+/// Set the lowpass filter of new grains
+/// 
+/// Each grain spawned afterwards gets a one-pole lowpass at the cutoff
+/// moved by a random ±`spread` octaves. Grains whose cutoff lands at or
+/// above Nyquist play unfiltered, so a cutoff at Nyquist turns the filter
+/// off.
+/// 
+/// # Arguments
+/// * `cutoff` - Cutoff in Hz (20 up; infinite or NaN = off)
+/// * `spread` - Random cutoff offset per grain in octaves (0-4)
+pub fn set_grain_filter(cutoff: f32, spread: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        (*st).filter_cutoff = if cutoff.is_nan() { f32::INFINITY } else { cutoff.max(MIN_FILTER_CUTOFF) };
+        (*st).filter_spread = spread.clamp(0.0, MAX_FILTER_SPREAD);
+    }
+}
+
+/// Set the random spawn time offset of new grains
+/// 
+/// Each grain spawns up to ±`amount` of the spawn interval away from its
+/// regular onset, for a less metronomic cloud at low densities. The
+/// average density stays the same.
+/// 
+/// # Arguments
+/// * `amount` - Largest offset as a share of the spawn interval (0-1)
+pub fn set_time_jitter(amount: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).time_jitter = if amount.is_nan() { 0.0 } else { amount.clamp(0.0, 1.0) };
+    }
+}
+
+/// Spawn grains on a tempo clock instead of by density
+/// 
+/// Each tick spawns `burst` grains at once (the density and time jitter
+/// are ignored). Switching between the clock and density restarts the
+/// clock on a tick and the density spawning from a full interval, so
+/// neither picks up timing left over from the other.
+/// 
+/// # Arguments
+/// * `bpm` - Tempo in beats per minute (1-1000, 0 = off, spawn by density)
+/// * `division` - Note value of one tick (1-64: 4 = quarter notes,
+///   8 = eighths, 12 = eighth triplets)
+/// * `burst` - Grains per tick (1-16)
+/// * `sample_rate` - Sample rate in Hz the tick length is computed for
+pub fn set_grain_clock(bpm: f32, division: f32, burst: u32, sample_rate: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        let clock_interval = if bpm > 0.0 && sample_rate > 0.0 {
+            // A whole note is 4 beats
+            let bpm = bpm.clamp(MIN_CLOCK_BPM, MAX_CLOCK_BPM);
+            let division = if division.is_nan() { 4.0 } else { division.clamp(1.0, MAX_CLOCK_DIVISION) };
+            240.0 * sample_rate / (bpm * division)
+        } else {
+            0.0
+        };
+        if (clock_interval > 0.0) != ((*st).clock_interval > 0.0) {
+            (*st).spawn_accumulator = 0.0;
+            (*st).last_onset_jitter = 0.0;
+            (*st).next_onset_jitter = 0.0;
+            (*st).clock_accumulator = clock_interval;
+        }
+        (*st).clock_interval = clock_interval;
+        (*st).clock_burst = burst.clamp(1, MAX_CLOCK_BURST);
+    }
+}
+
+/// Select how new grains are placed in the stereo field
+pub fn set_pan_mode(mode: PanMode) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).pan_mode = mode;
+    }
+}
+
+/// Select the pan law of mono grains
+/// 
+/// Sets the level of a centered grain on each side: constant power suits
+/// uncorrelated grains, linear keeps mono-summed grains at unity. Stereo
+/// grains rotate the source field and ignore it.
+/// 
+/// # Arguments
+/// * `law` - Pan law
+pub fn set_pan_law(law: utils::PanLaw) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).pan_law = law;
+    }
+}
+
+/// Select how `pitch_spread` randomizes grain pitch
+/// 
+/// Like transpose, only affects grains spawned afterwards.
+pub fn set_pitch_mode(mode: PitchMode) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).pitch_mode = mode;
+    }
+}
+
+/// Seed the grain RNG
+/// 
+/// The same seed, source and parameters give the same sequence of grain
+/// positions, pitches and pans, so a texture can be reproduced (e.g. for
+/// A/B comparisons or a saved snapshot).
+/// 
+/// # Arguments
+/// * `seed` - New RNG state
+/// * `restore_on_reset` - Whether `reset` goes back to this seed, so every
+///   playback restarts the same texture
+pub fn set_seed(seed: u32, restore_on_reset: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).rng_state = seed;
+        (*state()).reset_seed = restore_on_reset.then_some(seed);
+    }
+}
+
+/// Select the interpolation used for source reads
+/// 
+/// Cubic (Catmull-Rom) keeps more top end and aliases less when grains are
+/// pitched up, at a higher per-sample cost.
+/// 
+/// # Arguments
+/// * `mode` - Interpolation mode
+pub fn set_interpolation(mode: InterpMode) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).interp = mode;
+    }
+}
+
+/// Select whether grains read the source in stereo
+/// 
+/// Stereo grains interpolate each source channel separately and the grain
+/// pan rotates the stereo field instead of panning a mono mix. Mono sources
+/// are duplicated to both channels. Applies to playing grains from the next
+/// block.
+/// 
+/// # Arguments
+/// * `enabled` - true = stereo grains, false = mono mix (default)
+pub fn set_stereo_grains(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).stereo_grains = enabled;
+    }
+}
+
+/// Select whether new grains start at a zero crossing of the source
+/// 
+/// On percussive sources a grain starting mid-transient clicks even under
+/// the envelope. Aligned grains move their start to the nearest zero
+/// crossing of the source (mono mix) within ZERO_CROSSING_SEARCH frames,
+/// searched once when the grain spawns; starts with no crossing nearby
+/// stay put. Playing grains are not moved.
+/// 
+/// # Arguments
+/// * `enabled` - true = align grain starts, false = start anywhere (default)
+pub fn set_zero_crossing_align(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).zero_crossing_align = enabled;
+    }
+}
+
+// ============================================================================
+// LIVE INPUT
+// ============================================================================
+
+/// Switch between granulating the loaded source and the live input
+/// 
+/// Active grains are cleared since their positions refer to the old buffer.
+/// 
+/// # Arguments
+/// * `enabled` - true = granulate live input history
+pub fn set_live_mode(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let live_ptr = addr_of_mut!((*state()).live_mode);
+        if *live_ptr != enabled {
+            *live_ptr = enabled;
+            reset();
+        }
+    }
+}
+
+/// Freeze or resume live recording
+/// 
+/// While frozen the history ring is left untouched and grains keep
+/// reading from it.
+/// 
+/// # Arguments
+/// * `frozen` - true = stop recording, false = resume
+pub fn set_live_freeze(frozen: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).live_frozen = frozen;
+    }
+}
+
+/// Length of the live history in seconds at the given sample rate
+#[inline]
+fn live_history_seconds(sample_rate: f32) -> f32 {
+    memory::MAX_LIVE_HISTORY_FRAMES as f32 / sample_rate
+}
+
+/// Append the current input block to the live history ring
+/// 
+/// # Safety
+/// Engine must be initialized (reads the input buffers).
+unsafe fn record_live_input() {
+    let history = memory::live_history_slice_mut();
+    let input_l = memory::input_slice(0);
+    let input_r = memory::input_slice(1);
+    let write_ptr = addr_of_mut!((*state()).live_write_pos);
+    
+    for (l, r) in input_l.iter().zip(input_r) {
+        let idx = *write_ptr * 2;
+        history[idx] = *l;
+        history[idx + 1] = *r;
+        *write_ptr = (*write_ptr + 1) % memory::MAX_LIVE_HISTORY_FRAMES;
+    }
+}
+
+/// Normalized ring position for a live grain starting `delay` seconds ago
+/// 
+/// The delay is kept long enough that a grain playing faster than real time
+/// can't overtake the write head, and short enough that the recording can't
+/// overwrite the grain before it finishes. With zero-crossing alignment
+/// both bounds leave room for the start to move by the search window.
+/// 
+/// # Safety
+/// Reads the global live write position.
+unsafe fn live_start_pos(delay: f32, grain_size: u32, rate: f32) -> f32 {
+    let frames = memory::MAX_LIVE_HISTORY_FRAMES as f32;
+    let grain_frames = grain_size as f32;
+    let search = if (*state()).zero_crossing_align { ZERO_CROSSING_SEARCH as f32 } else { 0.0 };
+    let min_delay = (rate - 1.0).max(0.0) * grain_frames + 2.0 + search;
+    let max_delay = frames - grain_frames - memory::MAX_BUFFER_SIZE as f32 - search;
+    let delay_frames = (delay * memory::sample_rate()).clamp(min_delay, max_delay);
+    
+    let start = (*state()).live_write_pos as f32 - delay_frames;
+    (if start < 0.0 { start + frames } else { start }) / frames
+}
+
+/// Get a slice reference to the granular source buffer
+/// 
+/// # Safety
+/// Source must be loaded (SOURCE_LEN > 0)
+#[inline]
+unsafe fn get_source_slice() -> &'static [f32] {
+    std::slice::from_raw_parts(
+        memory::get_granular_source_ptr() as *const f32,
+        (*state()).source_len
+    )
+}
+
+// ============================================================================
+// UTILITY
+// ============================================================================
+
+/// Wrap a normalized position into [0, 1)
+#[inline]
+fn wrap_unit(x: f32) -> f32 {
+    let wrapped = x.rem_euclid(1.0);
+    // rem_euclid rounds tiny negative values up to exactly 1.0
+    if wrapped >= 1.0 { 0.0 } else { wrapped }
+}
+
+/// Reflect a normalized position into [0, 1] (a triangle wave of period 2)
+#[inline]
+fn fold_unit(x: f32) -> f32 {
+    let folded = x.rem_euclid(2.0);
+    if folded > 1.0 { 2.0 - folded } else { folded }
+}
+
+/// Reset granular engine state
+/// Called when switching effects or stopping playback
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        let grains_ptr = addr_of_mut!((*st).grains);
+        for grain in (*grains_ptr).iter_mut() {
+            grain.active = false;
+        }
+        (*st).spawn_accumulator = 0.0;
+        (*st).last_onset_jitter = 0.0;
+        (*st).next_onset_jitter = 0.0;
+        (*st).clock_accumulator = (*st).clock_interval;
+        (*st).spawn_cursor = 0;
+        (*st).scan_offset = 0.0;
+        (*st).smoothing_primed = false;
+        (*st).overlap_estimate = 1.0;
+        (*st).limiter_envelope = 0.0;
+        (*st).dc_blocker_l.reset();
+        (*st).dc_blocker_r.reset();
+        if let Some(seed) = (*st).reset_seed {
+            (*st).rng_state = seed;
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    const BLOCK: usize = 128;
+    
+    /// Fill both input buffers for one block
+    fn write_input(f: impl Fn(usize) -> f32) {
+        unsafe {
+            for i in 0..BLOCK {
+                *memory::get_input_buffer(0).add(i) = f(i);
+                *memory::get_input_buffer(1).add(i) = f(i);
+            }
+        }
+    }
+    
+    /// Energy of the left output for the current block
+    fn output_energy() -> f32 {
+        unsafe { memory::output_slice_mut(0).iter().map(|x| x * x).sum() }
+    }
+    
+    #[test]
+    fn test_ring_read_interpolates_across_wrap() {
+        // Stereo ring: last frame 1.0, first frame 0.0
+        let mut ring = vec![0.5; 16];
+        ring[14] = 1.0;
+        ring[15] = 1.0;
+        ring[0] = 0.0;
+        ring[1] = 0.0;
+        
+        let wrapped = read_source(&ring, 2, 7.5, true);
+        assert!((wrapped - 0.5).abs() < 1e-6, "wrapped read gave {wrapped}");
+        // A one-shot source stops at its last frame instead
+        assert_eq!(read_source(&ring, 2, 7.5, false), 0.0);
+        assert!((read_source(&ring, 2, 6.5, false) - 0.75).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_cubic_read_matches_source_and_edges() {
+        // Cubic passes through a parabola's samples exactly and stays
+        // close between them
+        let mono: Vec<f32> = (0..32).map(|i| (i as f32 * 0.1).powi(2)).collect();
+        for i in 0..31 {
+            let x = i as f32;
+            assert!((read_source_cubic(&mono, 1, x, false) - mono[i]).abs() < 1e-6);
+            let mid = read_source_cubic(&mono, 1, x + 0.5, false);
+            let expected = ((x + 0.5) * 0.1).powi(2);
+            if i > 0 && i < 30 {
+                assert!((mid - expected).abs() < 1e-4, "frame {i}: {mid} vs {expected}");
+            }
+        }
+        
+        // Edges: bounded by the neighboring samples, then silent past the end
+        let first = read_source_cubic(&mono, 1, 0.5, false);
+        assert!(first >= mono[0] && first <= mono[1]);
+        assert_eq!(read_source_cubic(&mono, 1, 31.0, false), 0.0);
+        
+        // Stereo frames are averaged, like the linear path
+        let stereo: Vec<f32> = mono.iter().flat_map(|&x| [x, x * 3.0]).collect();
+        for pos in [0.25, 7.5, 30.75] {
+            let m = read_source_cubic(&mono, 1, pos, false) * 2.0;
+            let s = read_source_cubic(&stereo, 2, pos, false);
+            assert!((m - s).abs() < 1e-5, "stereo read at {pos}: {s} vs {m}");
+        }
+        
+        // Wrapped reads interpolate across the ring end
+        let ring = vec![0.5; 16];
+        assert!((read_source_cubic(&ring, 2, 7.5, true) - 0.5).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_cubic_lowers_distortion_of_pitched_down_grain() {
+        // A 4.8kHz sine (10 frames per cycle) read an octave down, the way
+        // a grain at rate 0.5 steps through its source
+        let source: Vec<f32> = (0..1000).map(|i| (2.0 * core::f32::consts::PI * i as f32 / 10.0).sin()).collect();
+        let thd = |read: fn(&[f32], u32, f32, bool) -> f32| {
+            let output: Vec<f32> = (0..1960).map(|n| read(&source, 1, 10.0 + n as f32 * 0.5, false)).collect();
+            
+            // Everything but the fundamental (98 whole cycles) is distortion
+            let omega = 2.0 * core::f32::consts::PI / 20.0;
+            let (mut sin_sum, mut cos_sum) = (0.0, 0.0);
+            for (n, &y) in output.iter().enumerate() {
+                sin_sum += y * (omega * n as f32).sin();
+                cos_sum += y * (omega * n as f32).cos();
+            }
+            let (a, b) = (2.0 * sin_sum / output.len() as f32, 2.0 * cos_sum / output.len() as f32);
+            let residual: f32 = output
+                .iter()
+                .enumerate()
+                .map(|(n, &y)| (y - a * (omega * n as f32).sin() - b * (omega * n as f32).cos()).powi(2))
+                .sum();
+            (residual / output.len() as f32).sqrt() / ((a * a + b * b) * 0.5).sqrt()
+        };
+        
+        let linear = thd(read_source);
+        let cubic = thd(read_source_cubic);
+        assert!(linear > 0.01, "linear THD {linear}");
+        assert!(cubic < linear / 5.0, "cubic THD {cubic} vs linear {linear}");
+    }
+    
+    #[test]
+    fn test_table_envelope_matches_cosine() {
+        // A grain's envelope, stepped the way the grain loop advances it,
+        // against the exact raised cosine. Truncating to a table entry is
+        // off by at most the steepest slope (π) times the table step
+        let resolution = core::f32::consts::PI / simd_utils::ENVELOPE_TABLE_SIZE as f32;
+        for grain_size in [64, 256, 4096, 44100] {
+            let (mut error_energy, mut energy) = (0.0f32, 0.0f32);
+            for n in 0..grain_size {
+                let phase = n as f32 / grain_size as f32;
+                let exact = 0.5 - 0.5 * (phase * core::f32::consts::TAU).cos();
+                let error = envelope(phase) - exact;
+                assert!(error.abs() <= resolution, "{grain_size}: phase {phase} off by {error}");
+                error_energy += error * error;
+                energy += exact * exact;
+            }
+            // Error stays 50dB under the grain
+            let error_db = 10.0 * (error_energy / energy).log10();
+            assert!(error_db < -50.0, "{grain_size}: error at {error_db}dB");
+        }
+        
+        // A whole grain cloud rendered through `process` with either
+        // envelope under the same seed: the same grains spawn, and the
+        // outputs differ by no more than the envelopes do
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        let source: Vec<f32> = (0..48000)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                0.5 * (core::f32::consts::TAU * 220.0 * t).sin() + 0.3 * (core::f32::consts::TAU * 1330.0 * t).sin()
+            })
+            .collect();
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), source.len()).copy_from_slice(&source);
+        }
+        load_source(core::ptr::null(), source.len() as u32, 1, 0.0);
+        let render = |envelope: fn(f32) -> f32| {
+            set_seed(7, false);
+            reset();
+            let mut output = Vec::new();
+            for _ in 0..200 {
+                process_with_envelope(1024, 200.0, 0.5, 0.5, 0.4, envelope);
+                unsafe {
+                    output.extend_from_slice(memory::output_slice_mut(0));
+                    output.extend_from_slice(memory::output_slice_mut(1));
+                }
+            }
+            output
+        };
+        let exact = render(|phase| 0.5 - 0.5 * libm::cosf(phase * core::f32::consts::TAU));
+        let table = render(envelope);
+        
+        let energy: f32 = exact.iter().map(|y| y * y).sum();
+        let error_energy: f32 = exact.iter().zip(&table).map(|(a, b)| (a - b) * (a - b)).sum();
+        let peak = simd_utils::find_peak(&exact);
+        let max_error = exact.iter().zip(&table).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        assert!(peak > 0.1, "no grains rendered");
+        let error_db = 10.0 * (error_energy / energy).log10();
+        assert!(error_db < -50.0, "cloud error at {error_db}dB");
+        assert!(max_error < peak * 0.01, "cloud off by {max_error} (peak {peak})");
+        set_seed(DEFAULT_SEED, false);
+    }
+    
+    #[test]
+    fn test_resampled_source_keeps_pitch() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, BLOCK as u32);
+        
+        // One second of 441Hz at 44.1kHz
+        let source_rate = 44100.0;
+        let freq = 441.0;
+        let original: Vec<f32> = (0..44100)
+            .map(|i| (2.0 * core::f32::consts::PI * freq * i as f32 / source_rate).sin())
+            .collect();
+        let write_source = |samples: &[f32]| unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), samples.len())
+                .copy_from_slice(samples);
+        };
+        write_source(&original);
+        load_source(core::ptr::null(), original.len() as u32, 1, source_rate);
+        
+        unsafe {
+            assert_eq!((*state()).source_len, 48000);
+            let source = get_source_slice();
+            // Away from the edges the sine is reproduced at the engine rate
+            for (i, &x) in source.iter().enumerate().skip(100).take(47800) {
+                let expected = (2.0 * core::f32::consts::PI * freq * i as f32 / 48000.0).sin();
+                assert!((x - expected).abs() < 2e-3, "frame {i}: {x} vs {expected}");
+            }
+        }
+        
+        // Re-init at the source rate converts back from the original
+        memory::init_engine(source_rate, BLOCK as u32);
+        resample_source_for_engine_rate();
+        unsafe {
+            assert_eq!((*state()).source_len, 44100);
+            assert_eq!(get_source_slice(), &original[..]);
+        }
+        
+        // Output longer than the source region is truncated
+        let long = vec![0.25; memory::MAX_GRANULAR_SOURCE_SAMPLES];
+        write_source(&long);
+        memory::init_engine(48000.0, BLOCK as u32);
+        let status = load_source(core::ptr::null(), (long.len() / 2) as u32, 2, source_rate);
+        assert_eq!(status, LOAD_TRUNCATED);
+        unsafe {
+            assert_eq!((*state()).source_len, memory::MAX_GRANULAR_SOURCE_SAMPLES);
+        }
+        
+        // A plain load drops the resampling metadata
+        load_source(core::ptr::null(), 100, 1, 0.0);
+        memory::init_engine(44100.0, BLOCK as u32);
+        resample_source_for_engine_rate();
+        unsafe {
+            assert_eq!((*state()).source_len, 100);
+        }
+    }
+    
+    #[test]
+    fn test_oversized_source_never_reads_past_region() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // Source fills the region; everything after it up to (and into) the
+        // IR region is poisoned so any out-of-bounds read shows up as NaN
+        let region_end = memory::GRANULAR_SOURCE_OFFSET + memory::MAX_GRANULAR_SOURCE_SAMPLES * 4;
+        let poison_len = (memory::IR_OFFSET + 4096 - region_end) / 4;
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), memory::MAX_GRANULAR_SOURCE_SAMPLES)
+                .fill(0.25);
+            std::slice::from_raw_parts_mut(memory::region_ptr(region_end) as *mut f32, poison_len)
+                .fill(f32::NAN);
+        }
+        
+        let frames = memory::MAX_GRANULAR_SOURCE_SAMPLES as u32;
+        assert_eq!(load_source(core::ptr::null(), frames, 2, 0.0), LOAD_TRUNCATED);
+        assert_eq!(source_frames(), frames / 2);
+        unsafe {
+            let source = get_source_slice();
+            let end = source.as_ptr_range().end as usize;
+            assert!(end <= memory::region_ptr(region_end) as usize, "source slice ends past its region");
+        }
+        
+        // Grains at the very end of the source, fast and slow, looping or not
+        set_transpose(24.0);
+        for region_loop in [false, true] {
+            set_region_loop(region_loop);
+            for _ in 0..200 {
+                process(4096, 500.0, 1.0, 1.0, 1.0);
+                let out = unsafe { memory::output_slice_mut(0) };
+                assert!(out.iter().all(|x| x.is_finite()), "grain read outside the source region");
+            }
+        }
+        
+        // Invalid channel counts are rejected and keep the current source
+        assert_eq!(load_source(core::ptr::null(), 100, 3, 0.0), LOAD_REJECTED);
+        assert_eq!(source_frames(), frames / 2);
+        assert_eq!(load_source(core::ptr::null(), 100, 1, 0.0), LOAD_OK);
+        assert_eq!(source_frames(), 100);
+        assert_eq!(source_channels(), 1);
+        
+        // Metadata reflects what was accepted
+        assert_eq!(load_source(core::ptr::null(), 24000, 2, 0.0), LOAD_OK);
+        assert_eq!((source_frames(), source_channels()), (24000, 2));
+        assert_eq!(source_duration_ms(), 500.0);
+        
+        set_transpose(0.0);
+        set_region_loop(false);
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::region_ptr(region_end) as *mut f32, poison_len)
+                .fill(0.0);
+        }
+    }
+    
+    #[test]
+    fn test_over_capacity_loads_leave_ir_region_intact() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        let sentinel_len = 4096;
+        let ir_region = || unsafe {
+            std::slice::from_raw_parts_mut(memory::get_ir_ptr(), sentinel_len)
+        };
+        ir_region().fill(0.5);
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), memory::MAX_GRANULAR_SOURCE_SAMPLES)
+                .fill(0.25);
+        }
+        
+        // Lengths far past the region, including ones that overflow a
+        // frames * channels product, are truncated
+        assert_eq!(load_source(core::ptr::null(), u32::MAX, 2, 0.0), LOAD_TRUNCATED);
+        assert_eq!(source_frames() as usize, memory::MAX_GRANULAR_SOURCE_SAMPLES / 2);
+        
+        // Upsampling a full region would need twice the space
+        let frames = (memory::MAX_GRANULAR_SOURCE_SAMPLES / 2) as u32;
+        assert_eq!(load_source(core::ptr::null(), frames, 2, SAMPLE_RATE / 2.0), LOAD_TRUNCATED);
+        assert_eq!(source_frames() as usize, memory::MAX_GRANULAR_SOURCE_SAMPLES / 2);
+        assert_eq!(load_source(core::ptr::null(), u32::MAX, 1, SAMPLE_RATE), LOAD_TRUNCATED);
+        assert!(ir_region().iter().all(|&x| x == 0.5), "granular load wrote into the IR region");
+        
+        // An empty source is accepted and leaves the engine unloaded
+        assert_eq!(load_source(core::ptr::null(), 0, 1, 0.0), LOAD_OK);
+        assert_eq!((source_frames(), source_channels()), (0, 0));
+        assert!(!memory::is_granular_ready());
+        process(BLOCK as u32, 50.0, 1.0, 1.0, 1.0);
+        let out = unsafe { memory::output_slice_mut(0) };
+        assert!(out.iter().all(|&x| x == 0.0));
+        
+        assert_eq!(load_source(core::ptr::null(), 100, 1, 0.0), LOAD_OK);
+        assert!(memory::is_granular_ready());
+        ir_region().fill(0.0);
+    }
+    
+    #[test]
+    fn test_pitch_mode_distributions() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_transpose(0.0);
+        
+        const SPAWNS: usize = 4000;
+        let spawn_rates = |mode: PitchMode, spread: f32| -> Vec<f32> {
+            set_pitch_mode(mode);
+            (0..SPAWNS)
+                .map(|_| unsafe {
+                    reset();
+                    assert!(spawn_grain(256, spread, 0.5, 0.0, 0.0, get_source_slice(), 1));
+                    (*state()).grains[0].rate
+                })
+                .collect()
+        };
+        let share = |rates: &[f32], ratio: f32| {
+            rates.iter().filter(|&&r| (r - ratio).abs() < 1e-6).count() as f32 / rates.len() as f32
+        };
+        
+        // Continuous: uniform in ±spread octaves
+        let octaves: Vec<f32> = spawn_rates(PitchMode::Continuous, 0.5).iter().map(|r| r.log2()).collect();
+        assert!(octaves.iter().all(|o| o.abs() <= 0.5 + 1e-6));
+        let below_quarter = octaves.iter().filter(|o| o.abs() < 0.25).count() as f32 / SPAWNS as f32;
+        assert!((below_quarter - 0.5).abs() < 0.03, "{below_quarter}");
+        let mean = octaves.iter().sum::<f32>() / SPAWNS as f32;
+        assert!(mean.abs() < 0.02, "{mean}");
+        
+        // Octaves: spread is the chance of leaving unison, split evenly up/down
+        let rates = spawn_rates(PitchMode::Octaves, 0.6);
+        let (down, unison, up) = (share(&rates, 0.5), share(&rates, 1.0), share(&rates, 2.0));
+        assert!((down + unison + up - 1.0).abs() < 1e-6, "unexpected ratio drawn");
+        assert!((down - 0.3).abs() < 0.03 && (up - 0.3).abs() < 0.03, "{down} {up}");
+        assert_eq!(share(&spawn_rates(PitchMode::Octaves, 0.0), 1.0), 1.0);
+        
+        // Harmonic: spread is the chance of leaving unison, ratios evenly
+        let rates = spawn_rates(PitchMode::Harmonic, 0.8);
+        let unison = share(&rates, 1.0);
+        assert!((unison - 0.2).abs() < 0.03, "{unison}");
+        let mut total = unison;
+        for ratio in HARMONIC_RATIOS {
+            let p = share(&rates, ratio);
+            assert!((p - 0.2).abs() < 0.03, "ratio {ratio}: {p}");
+            total += p;
+        }
+        assert!((total - 1.0).abs() < 1e-6, "unexpected ratio drawn");
+        
+        set_pitch_mode(PitchMode::Continuous);
+    }
+    
+    #[test]
+    fn test_seed_reproduces_grain_sequence() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        
+        // Position, pitch and pan of a run of spawned grains
+        let spawn_sequence = || -> Vec<(f32, f32, f32)> {
+            (0..64)
+                .map(|_| unsafe {
+                    let st = state();
+                    for grain in (*st).grains.iter_mut() {
+                        grain.active = false;
+                    }
+                    assert!(spawn_grain(256, 0.5, 0.5, 0.3, 0.0, get_source_slice(), 1));
+                    let grain = (*st).grains.iter().find(|g| g.active).unwrap();
+                    (grain.source_pos, grain.rate, grain.pan)
+                })
+                .collect()
+        };
+        
+        set_seed(42, false);
+        let first = spawn_sequence();
+        set_seed(42, false);
+        assert_eq!(spawn_sequence(), first);
+        set_seed(43, false);
+        assert_ne!(spawn_sequence(), first);
+        
+        // Without restore_on_reset the sequence carries on across a reset;
+        // with it, every reset starts over
+        set_seed(42, false);
+        spawn_sequence();
+        reset();
+        assert_ne!(spawn_sequence(), first);
+        set_seed(42, true);
+        spawn_sequence();
+        reset();
+        assert_eq!(spawn_sequence(), first);
+        
+        set_seed(DEFAULT_SEED, false);
+    }
+    
+    #[test]
+    fn test_zero_crossing_align_moves_starts_onto_crossings() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        
+        // Start frames of a run of sprayed grains over the loaded source
+        let grain_starts = |source: &[f32], align: bool| -> Vec<f32> {
+            unsafe {
+                std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), source.len()).copy_from_slice(source);
+            }
+            load_source(core::ptr::null(), source.len() as u32, 1, 0.0);
+            set_zero_crossing_align(align);
+            set_seed(11, true);
+            reset();
+            (0..200)
+                .map(|_| unsafe {
+                    let st = state();
+                    for grain in (*st).grains.iter_mut() {
+                        grain.active = false;
+                    }
+                    assert!(spawn_grain(256, 0.0, 0.5, 0.4, 0.0, get_source_slice(), 1));
+                    (*st).grains.iter().find(|g| g.active).unwrap().source_pos * 48000.0
+                })
+                .collect()
+        };
+        
+        // A square wave flipping every 50 frames crosses zero halfway
+        // between frames 49 and 50, 99 and 100, ...
+        let square: Vec<f32> = (0..48000).map(|i| if (i / 50) % 2 == 0 { 0.8 } else { -0.8 }).collect();
+        let off_crossing = |start: f32| {
+            let phase = (start - 49.5).rem_euclid(50.0);
+            phase.min(50.0 - phase)
+        };
+        let free = grain_starts(&square, false);
+        assert!(free.iter().any(|&start| off_crossing(start) > 10.0));
+        let aligned = grain_starts(&square, true);
+        for (&start, &from) in aligned.iter().zip(&free) {
+            assert!(off_crossing(start) < 0.01, "grain starts {} frames off a crossing", off_crossing(start));
+            // The nearest crossing, and the RNG sequence is unchanged
+            assert!((start - from).abs() <= 25.01, "moved {} frames", start - from);
+        }
+        
+        // Without a crossing in the window the start stays put
+        let dc = vec![0.5; 48000];
+        assert_eq!(grain_starts(&dc, true), grain_starts(&dc, false));
+        
+        set_zero_crossing_align(false);
+        set_seed(DEFAULT_SEED, false);
+    }
+    
+    #[test]
+    fn test_grain_filter_darkens_grains() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        
+        // Output energy of one centered 4096-sample grain of a sine
+        let grain_energy = |freq: f32, cutoff: f32| {
+            let source: Vec<f32> = (0..48000).map(|i| (core::f32::consts::TAU * freq * i as f32 / SAMPLE_RATE).sin()).collect();
+            unsafe {
+                std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), source.len()).copy_from_slice(&source);
+            }
+            load_source(core::ptr::null(), source.len() as u32, 1, 0.0);
+            set_grain_filter(cutoff, 0.0);
+            set_seed(7, true);
+            reset();
+            set_stereo_width(0.0);
+            let mut output = Vec::new();
+            unsafe {
+                assert!(spawn_grain(4096, 0.0, 0.5, 0.0, 0.0, get_source_slice(), 1));
+                // One grain per second: nothing else spawns for the grain's length
+                for _ in 0..4096 / BLOCK {
+                    process(4096, 1.0, 0.0, 0.5, 0.0);
+                    output.extend_from_slice(memory::output_slice_mut(0));
+                }
+            }
+            output
+        };
+        let energy = |output: &[f32]| output.iter().map(|x| x * x).sum::<f32>();
+        
+        // A 500Hz one-pole takes a 12kHz grain down by ~27dB and leaves a
+        // 200Hz grain nearly untouched
+        let bright = energy(&grain_energy(12000.0, f32::INFINITY));
+        assert!(bright > 1.0);
+        let dark = energy(&grain_energy(12000.0, 500.0));
+        assert!(dark < bright * 0.01, "12kHz grain at {}dB", 10.0 * (dark / bright).log10());
+        let low = energy(&grain_energy(200.0, 500.0)) / energy(&grain_energy(200.0, f32::INFINITY));
+        assert!(low > 0.7, "200Hz grain at {}dB", 10.0 * low.log10());
+        
+        // A cutoff at Nyquist is a true bypass
+        assert_eq!(grain_energy(12000.0, SAMPLE_RATE * 0.5), grain_energy(12000.0, f32::INFINITY));
+        
+        set_grain_filter(f32::INFINITY, 0.0);
+        set_stereo_width(DEFAULT_STEREO_WIDTH);
+        set_seed(DEFAULT_SEED, false);
+    }
+    
+    #[test]
+    fn test_limiter_keeps_dense_cloud_within_full_scale() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        
+        // Full-scale DC source: every grain is perfectly correlated
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), 48000).fill(1.0);
+        }
+        
+        let render_peak = |limited: bool| {
+            set_limiter_enabled(limited);
+            load_source(core::ptr::null(), 48000, 1, 0.0);
+            let mut peak = 0.0f32;
+            for _ in 0..400 {
+                process(4096, 100.0, 0.0, 0.5, 0.5);
+                unsafe {
+                    peak = peak
+                        .max(simd_utils::find_peak(memory::output_slice_mut(0)))
+                        .max(simd_utils::find_peak(memory::output_slice_mut(1)));
+                }
+            }
+            peak
+        };
+        
+        let raw_peak = render_peak(false);
+        assert!(raw_peak > 1.0, "test case should overload without the limiter ({raw_peak})");
+        let limited_peak = render_peak(true);
+        assert!(limited_peak <= 1.0, "limited peak {limited_peak}");
+        assert!(limited_peak > 0.5, "limiter shouldn't squash the output ({limited_peak})");
+    }
+    
+    #[test]
+    fn test_stereo_width_and_alternate_pan() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        
+        // Pans of `count` grains spawned into a fresh pool
+        let spawn_pans = |count: usize| -> Vec<f32> {
+            reset();
+            (0..count)
+                .map(|i| unsafe {
+                    assert!(spawn_grain(256, 0.0, 0.5, 0.0, 0.0, get_source_slice(), 1));
+                    (*state()).grains[i].pan
+                })
+                .collect()
+        };
+        
+        set_pan_mode(PanMode::Random);
+        set_stereo_width(0.0);
+        assert!(spawn_pans(100).iter().all(|&p| p == 0.0));
+        
+        set_stereo_width(1.0);
+        let pans = spawn_pans(200);
+        assert!(pans.iter().all(|p| p.abs() <= 1.0));
+        assert!(pans.iter().any(|&p| p < -0.9) && pans.iter().any(|&p| p > 0.9));
+        
+        // Alternate: hard left/right in turn at full width
+        set_pan_mode(PanMode::Alternate);
+        let pans = spawn_pans(10);
+        for pair in pans.windows(2) {
+            assert_eq!(pair[0].abs(), 1.0);
+            assert_eq!(pair[1], -pair[0]);
+        }
+        
+        // Width changes leave playing grains alone
+        set_stereo_width(0.5);
+        let before = unsafe { (*state()).grains[0].pan };
+        unsafe { assert!(spawn_grain(256, 0.0, 0.5, 0.0, 0.0, get_source_slice(), 1)) };
+        unsafe {
+            assert_eq!((*state()).grains[0].pan, before);
+            assert_eq!((*state()).grains[10].pan.abs(), 0.5);
+        }
+        
+        set_pan_mode(PanMode::Random);
+        set_stereo_width(DEFAULT_STEREO_WIDTH);
+    }
+    
+    #[test]
+    fn test_pan_law_sets_center_level() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        // SAFETY: One second of DC fits the granular source region
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), 48000).fill(0.5);
+        }
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_stereo_width(0.0);
+        
+        // Left energy of the same centered grains under a law
+        let render = |law: utils::PanLaw| {
+            set_pan_law(law);
+            set_seed(7, false);
+            reset();
+            (0..100)
+                .map(|_| {
+                    process(1024, 40.0, 0.0, 0.5, 0.2);
+                    output_energy()
+                })
+                .sum::<f32>()
+        };
+        
+        // -6dB instead of -3dB at center halves the energy
+        let power = render(utils::PanLaw::ConstantPower);
+        let linear = render(utils::PanLaw::Linear);
+        assert!(power > 1.0 && (linear / power - 0.5).abs() < 1e-3, "linear / constant power {}", linear / power);
+        
+        set_pan_law(utils::PanLaw::ConstantPower);
+        set_stereo_width(DEFAULT_STEREO_WIDTH);
+    }
+    
+    #[test]
+    fn test_stereo_grains_keep_channel_separation() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        
+        // Energy of the left and right outputs over ~1s
+        let render = || {
+            reset();
+            let (mut left, mut right) = (0.0, 0.0);
+            for _ in 0..400 {
+                process(1024, 40.0, 0.0, 0.5, 0.2);
+                unsafe {
+                    left += memory::output_slice_mut(0).iter().map(|x| x * x).sum::<f32>();
+                    right += memory::output_slice_mut(1).iter().map(|x| x * x).sum::<f32>();
+                }
+            }
+            (left, right)
+        };
+        
+        // Hard-panned stereo source: a sine on the left, silence on the right
+        let source: Vec<f32> = (0..48000)
+            .flat_map(|i| [(2.0 * core::f32::consts::PI * 220.0 * i as f32 / SAMPLE_RATE).sin(), 0.0])
+            .collect();
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), source.len())
+                .copy_from_slice(&source);
+        }
+        load_source(core::ptr::null(), 48000, 2, 0.0);
+        
+        // Centered grains: the mono mix lands on both sides, stereo grains
+        // stay on the left
+        set_stereo_width(0.0);
+        set_stereo_grains(false);
+        let (left, right) = render();
+        assert!(left > 1.0 && (left - right).abs() < 1e-3 * left, "mono mix {left} / {right}");
+        set_stereo_grains(true);
+        let (left, right) = render();
+        assert!(left > 1.0 && right < 1e-9, "stereo grains {left} / {right}");
+        
+        // Grains alternating at ±0.5 rotate the left channel by 45 degrees
+        // every other grain: 3/4 of the energy stays on the left
+        set_pan_mode(PanMode::Alternate);
+        set_stereo_width(0.5);
+        let (left, right) = render();
+        assert!((left / right - 3.0).abs() < 0.3, "rotated grains {left} / {right}");
+        set_pan_mode(PanMode::Random);
+        
+        // Mono sources are duplicated to both channels
+        set_stereo_width(0.0);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        let (left, right) = render();
+        assert!(left > 1.0 && (left - right).abs() < 1e-3 * left, "mono source {left} / {right}");
+        
+        set_stereo_grains(false);
+        set_stereo_width(DEFAULT_STEREO_WIDTH);
+    }
+    
+    #[test]
+    fn test_scan_speed_moves_position_independently_of_pitch() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_transpose(12.0);
+        
+        // Start positions of the playing grains (normalized)
+        let grain_starts = || unsafe {
+            (*state())
+                .grains
+                .iter()
+                .filter(|g| g.active)
+                .map(|g| g.source_pos - g.phase * g.size_samples as f32 * g.rate / 48000.0)
+                .collect::<Vec<f32>>()
+        };
+        
+        // Frozen scan: grains keep reading around the same spot, even
+        // though they play an octave up
+        set_scan_speed(0.0);
+        reset();
+        for _ in 0..200 {
+            process(1024, 100.0, 0.0, 0.25, 0.0);
+            assert!(grain_starts().iter().all(|p| (p - 0.25).abs() < 1e-3), "{:?}", grain_starts());
+        }
+        
+        // Real-time scan: the position moves one source frame per sample
+        // (half a second after 187.5 blocks), regardless of transpose
+        let scan = |blocks: usize| {
+            reset();
+            for _ in 0..blocks {
+                process(1024, 100.0, 0.0, 0.25, 0.0);
+            }
+            0.25 + unsafe { (*state()).scan_offset }
+        };
+        set_scan_speed(1.0);
+        set_loop_mode(LoopMode::Off);
+        assert!((scan(187) - (0.25 + 187.0 * 128.0 / 48000.0)).abs() < 1e-3);
+        
+        // Past the end the scan stops there, or wraps to the start
+        assert_eq!(scan(1000), 1.0);
+        set_loop_mode(LoopMode::Forward);
+        let wrapped = scan(500);
+        let expected = 0.25 + 500.0 * 128.0 / 48000.0 - 1.0;
+        assert!((wrapped - expected).abs() < 1e-3, "wrapped scan at {wrapped}");
+        assert!(grain_starts().iter().all(|p| (0.0..1.0).contains(p)));
+        
+        set_loop_mode(LoopMode::Off);
+        set_scan_speed(0.0);
+        set_transpose(0.0);
+        reset();
+    }
+    
+    #[test]
+    fn test_ping_pong_scan_stays_in_region() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_region(0.4, 0.45);
+        set_scan_speed(MAX_SCAN_SPEED);
+        set_loop_mode(LoopMode::PingPong);
+        reset();
+        
+        // Start positions of the playing grains (source frames advance at
+        // the unity grain rate)
+        let grain_starts = || unsafe {
+            (*state())
+                .grains
+                .iter()
+                .filter(|g| g.active)
+                .map(|g| g.source_pos - g.phase * g.size_samples as f32 / 48000.0)
+                .collect::<Vec<f32>>()
+        };
+        
+        // The region is crossed every ~5 blocks; the scan turns around at
+        // each bound instead of jumping back
+        let max_step = MAX_SCAN_SPEED * BLOCK as f32 / (48000.0 * 0.05);
+        let mut previous = 0.25;
+        let (mut rising, mut falling) = (0, 0);
+        for _ in 0..100 {
+            process(256, 2000.0, 0.0, 0.25, 0.3);
+            let base = fold_unit(0.25 + unsafe { (*state()).scan_offset });
+            let step = base - previous;
+            assert!(step.abs() <= max_step + 1e-4, "scan jumped by {step}");
+            if step > 0.0 { rising += 1 } else { falling += 1 }
+            previous = base;
+            
+            let starts = grain_starts();
+            assert!(!starts.is_empty());
+            assert!(
+                starts.iter().all(|p| (0.4 - 1e-4..=0.45 + 1e-4).contains(p)),
+                "grain outside the region: {starts:?}"
+            );
+        }
+        assert!(rising > 30 && falling > 30, "{rising} blocks up, {falling} down");
+        
+        set_loop_mode(LoopMode::Off);
+        set_scan_speed(0.0);
+        set_region(0.0, 1.0);
+        reset();
+    }
+    
+    #[test]
+    fn test_transpose_octave_doubles_source_consumption() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        
+        // Source frames consumed per output sample by the oldest active grain
+        let consumption = || unsafe {
+            let grains = &(*state()).grains;
+            let slot = grains.iter().position(|g| g.active).expect("no active grain");
+            let (pos, phase) = (grains[slot].source_pos, grains[slot].phase);
+            process(4096, 1000.0, 0.0, 0.1, 0.0);
+            let grain = &(*state()).grains[slot];
+            let samples = (grain.phase - phase) * grain.size_samples as f32;
+            (grain.source_pos - pos) * 48000.0 / samples
+        };
+        
+        set_transpose(0.0);
+        reset();
+        process(4096, 1000.0, 0.0, 0.1, 0.0);
+        let base = consumption();
+        
+        set_transpose(12.0);
+        reset();
+        process(4096, 1000.0, 0.0, 0.1, 0.0);
+        let octave = consumption();
+        
+        assert!((base - 1.0).abs() < 1e-2, "untransposed rate {base}");
+        assert!((octave / base - 2.0).abs() < 1e-2, "octave rate ratio {}", octave / base);
+        
+        // Grains already playing keep their rate when transpose changes
+        set_transpose(0.0);
+        let still_octave = consumption();
+        assert!((still_octave - octave).abs() < 1e-2);
+    }
+    
+    #[test]
+    fn test_position_smoothing_glides_between_blocks() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        let smoothed_position = || unsafe { (*state()).smooth_position };
+        
+        // First block after a load snaps to the target
+        set_smoothing_time(50.0);
+        process(256, 100.0, 0.0, 0.2, 0.0);
+        assert_eq!(smoothed_position(), 0.2);
+        
+        // A step moves part of the way per block, reaching ~63% after one
+        // time constant (50ms = 2400 samples)
+        process(256, 100.0, 0.0, 0.8, 0.0);
+        let after_one_block = smoothed_position();
+        assert!(after_one_block > 0.2 && after_one_block < 0.3, "{after_one_block}");
+        for _ in 1..(2400 / BLOCK) {
+            process(256, 100.0, 0.0, 0.8, 0.0);
+        }
+        let after_tau = (smoothed_position() - 0.2) / 0.6;
+        assert!((after_tau - 0.63).abs() < 0.03, "{after_tau}");
+        
+        // Disabled smoothing follows the target immediately
+        set_smoothing_time(0.0);
+        process(256, 100.0, 0.0, 0.5, 0.0);
+        assert_eq!(smoothed_position(), 0.5);
+        
+        // Loading a source resets the smoother
+        set_smoothing_time(DEFAULT_SMOOTHING_MS);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        process(256, 100.0, 0.0, 0.9, 0.0);
+        assert_eq!(smoothed_position(), 0.9);
+    }
+    
+    #[test]
+    fn test_density_sweep_has_no_gain_steps() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        // SAFETY: One second of DC fits the granular source region
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_granular_source_ptr(), 48000).fill(0.5);
+        }
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        reset();
+        
+        // 1s sweep from 5 to 80 grains/sec, a hold, 1s back down, then
+        // instant jumps (with the default parameter smoothing)
+        let blocks = SAMPLE_RATE as usize / BLOCK;
+        let sweep = (0..blocks).map(|b| 5.0 + 75.0 * b as f32 / blocks as f32);
+        let densities = sweep
+            .clone()
+            .chain(std::iter::repeat_n(80.0, blocks))
+            .chain(sweep.rev())
+            .chain(std::iter::repeat_n(80.0, blocks))
+            .chain(std::iter::repeat_n(5.0, blocks));
+        
+        let mut levels = Vec::new();
+        for density in densities {
+            process(2048, density, 0.0, 0.5, 0.0);
+            levels.push((output_energy() / BLOCK as f32).sqrt());
+        }
+        
+        // The block RMS of a lone grain moves by up to 20% of the peak level
+        // as its envelope rises; a gain recomputed per block from the
+        // density steps by over 40% on the jumps
+        let peak = levels.iter().fold(0.0f32, |peak, &x| peak.max(x));
+        let max_step = levels.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0f32, f32::max);
+        assert!(max_step < 0.3 * peak, "output RMS stepped {max_step} between blocks (peak {peak})");
+    }
+    
+    #[test]
+    fn test_time_jitter_keeps_average_density() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_smoothing_time(0.0);
+        
+        // Onsets of 20s of 20 grains/sec (a 2400 sample interval); a slot
+        // holds a new grain when it turned active or its phase went back
+        let onsets = |jitter: f32| -> Vec<f32> {
+            set_time_jitter(jitter);
+            reset();
+            let mut previous = [None; MAX_GRAINS];
+            let mut onsets = Vec::new();
+            for b in 0..20 * SAMPLE_RATE as usize / BLOCK {
+                process(1024, 20.0, 0.0, 0.5, 0.0);
+                let block_end = ((b + 1) * BLOCK) as f32;
+                for (grain, last_phase) in unsafe { (*state()).grains.iter() }.zip(previous.iter_mut()) {
+                    let phase = grain.active.then_some(grain.phase);
+                    if let Some(phase) = phase {
+                        if last_phase.is_none_or(|last| phase < last) {
+                            onsets.push(block_end - phase * grain.size_samples as f32);
+                        }
+                    }
+                    *last_phase = phase;
+                }
+            }
+            onsets.sort_by(f32::total_cmp);
+            onsets
+        };
+        let interval_stats = |onsets: &[f32]| {
+            let intervals: Vec<f32> = onsets.windows(2).map(|pair| pair[1] - pair[0]).collect();
+            let mean = intervals.iter().sum::<f32>() / intervals.len() as f32;
+            let variance = intervals.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / intervals.len() as f32;
+            (mean, variance.sqrt())
+        };
+        
+        // Regular spawning is metronomic
+        let (mean, deviation) = interval_stats(&onsets(0.0));
+        assert!((mean - 2400.0).abs() < 1.0, "mean interval {mean}");
+        assert!(deviation < 1.0, "interval deviation {deviation}");
+        
+        // Jittered onsets spread out around the same average interval
+        let jittered = onsets(0.5);
+        let (mean, deviation) = interval_stats(&jittered);
+        assert!((jittered.len() as i32 - 400).abs() <= 2, "{} grains", jittered.len());
+        assert!((mean - 2400.0).abs() < 24.0, "jittered mean interval {mean}");
+        assert!(deviation > 500.0, "jittered interval deviation {deviation}");
+        
+        set_time_jitter(0.0);
+        set_smoothing_time(DEFAULT_SMOOTHING_MS);
+    }
+    
+    #[test]
+    fn test_clock_spawns_on_ticks() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_live_mode(false);
+        load_source(core::ptr::null(), 48000, 1, 0.0);
+        set_smoothing_time(0.0);
+        
+        // Onsets over `blocks` blocks of 20 grains/sec density, `setup`
+        // called before each block; a slot holds a new grain when it
+        // turned active or its phase went back
+        let onsets = |blocks: usize, mut setup: Box<dyn FnMut(usize)>| -> Vec<f32> {
+            reset();
+            let mut previous = [None; MAX_GRAINS];
+            let mut onsets = Vec::new();
+            for b in 0..blocks {
+                setup(b);
+                process(1024, 20.0, 0.0, 0.5, 0.0);
+                let block_end = ((b + 1) * BLOCK) as f32;
+                for (grain, last_phase) in unsafe { (*state()).grains.iter() }.zip(previous.iter_mut()) {
+                    let phase = grain.active.then_some(grain.phase);
+                    if let Some(phase) = phase {
+                        if last_phase.is_none_or(|last| phase < last) {
+                            onsets.push(block_end - phase * grain.size_samples as f32);
+                        }
+                    }
+                    *last_phase = phase;
+                }
+            }
+            onsets.sort_by(f32::total_cmp);
+            onsets
+        };
+        
+        // 120 BPM eighth notes tick every 0.25s (12000 samples) from the
+        // first sample, a burst of grains on each
+        let ten_seconds = 10 * SAMPLE_RATE as usize / BLOCK;
+        for burst in [1, 3] {
+            let ticks = onsets(ten_seconds, Box::new(move |b| {
+                if b == 0 {
+                    set_grain_clock(120.0, 8.0, burst, SAMPLE_RATE);
+                }
+            }));
+            assert_eq!(ticks.len(), 40 * burst as usize, "burst {burst}");
+            for (i, chunk) in ticks.chunks(burst as usize).enumerate() {
+                for &onset in chunk {
+                    assert!((onset - 12000.0 * i as f32).abs() < 0.5, "burst {burst}: onset {onset} off tick {i}");
+                }
+            }
+        }
+        
+        // Density, then the clock from 0.55s, then density again from 1.1s:
+        // the clock starts on a tick at the switch, and the density grid
+        // starts over a full interval after it instead of resuming where
+        // it stopped
+        let to_clock = 550 * SAMPLE_RATE as usize / 1000 / BLOCK;
+        let to_density = 2 * to_clock;
+        set_grain_clock(0.0, 8.0, 1, SAMPLE_RATE);
+        let switched = onsets(3 * to_clock, Box::new(move |b| {
+            if b == to_clock {
+                set_grain_clock(120.0, 8.0, 1, SAMPLE_RATE);
+            } else if b == to_density {
+                set_grain_clock(0.0, 8.0, 1, SAMPLE_RATE);
+            }
+        }));
+        let (clock_at, density_at) = ((to_clock * BLOCK) as f32, (to_density * BLOCK) as f32);
+        let clocked: Vec<f32> = switched.iter().copied().filter(|onset| (clock_at..density_at).contains(onset)).collect();
+        assert_eq!(clocked, [clock_at, clock_at + 12000.0, clock_at + 24000.0]);
+        let density: Vec<f32> = switched.iter().copied().filter(|&onset| onset >= density_at).collect();
+        assert!((density[0] - density_at - 2400.0).abs() < 1.5, "first density onset {}", density[0] - density_at);
+        for pair in density.windows(2) {
+            assert!((pair[1] - pair[0] - 2400.0).abs() < 1.0, "density interval {}", pair[1] - pair[0]);
+        }
+        
+        set_grain_clock(0.0, 8.0, 1, SAMPLE_RATE);
+        set_smoothing_time(DEFAULT_SMOOTHING_MS);
+    }
+    
+    #[test]
+    fn test_live_freeze_keeps_history() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // Returns output energy of the last block after the input goes silent
+        let run = |freeze: bool| {
+            set_live_mode(false);
+            set_live_mode(true);
+            set_live_freeze(false);
+            unsafe { memory::live_history_slice_mut().fill(0.0) };
+            
+            for b in 0..200 {
+                write_input(|i| {
+                    let t = (b * BLOCK + i) as f32 / SAMPLE_RATE;
+                    (2.0 * core::f32::consts::PI * 440.0 * t).sin() * 0.5
+                });
+                process(1024, 40.0, 0.0, 0.1, 0.0);
+            }
+            
+            set_live_freeze(freeze);
+            for _ in 0..200 {
+                write_input(|_| 0.0);
+                process(1024, 40.0, 0.0, 0.1, 0.0);
+            }
+            output_energy()
+        };
+        
+        let frozen = run(true);
+        let live = run(false);
+        set_live_freeze(false);
+        set_live_mode(false);
+        
+        assert!(frozen > 1e-2, "frozen history went silent ({frozen})");
+        assert!(live < 1e-9, "unfrozen history still sounding ({live})");
     }
 }
@@ -8,6 +8,12 @@
 //! JavaScript writes input samples directly to memory, calls process
 //! functions, then reads output samples.
 //!
+//! # Engine Handles
+//! `dsp_init` returns a handle into a small pool of independent engines,
+//! each with its own buffers and processor state. Every other export except
+//! `dsp_set_simd_enabled`, `dsp_required_memory_pages` and the math
+//! utilities takes the handle as its first argument.
+//!
 //! # Thread Safety
 //! This module is NOT thread-safe. It's designed for single-threaded
 //! use within an AudioWorkletProcessor.
@@ -19,17 +25,52 @@
 
 #![allow(clippy::missing_safety_doc)]
 
+mod bypass;
 mod granular;
 mod convolution;
+mod ducking;
+mod dynamics;
 mod spectral;
 mod oscillators;
 mod filters;
 mod envelopes;
 mod delay;
+mod diffuser;
+mod effect_switch;
+mod modulation;
+mod noise;
+mod flanger;
+mod saturation;
+mod resonator;
+mod shimmer;
+mod soft_reset;
+mod limiter;
+mod oversampling;
+mod profiler;
 mod simd_utils;
 mod memory;
+mod smoothing;
 mod utils;
 
+use effect_switch::EffectBlock;
+use soft_reset::Processor;
+
+/// Effect IDs (match `EffectType` in the worklet)
+const EFFECT_BYPASS: u32 = 0;
+const EFFECT_GRANULAR: u32 = 1;
+const EFFECT_CONVOLUTION: u32 = 2;
+const EFFECT_SPECTRAL: u32 = 3;
+const EFFECT_FLANGER: u32 = 4;
+const EFFECT_VOCODER: u32 = 5;
+const EFFECT_PITCH_SHIFT: u32 = 6;
+const EFFECT_SPECTRAL_GATE: u32 = 7;
+const EFFECT_SATURATION: u32 = 8;
+const EFFECT_RESONATOR: u32 = 9;
+const EFFECT_SHIMMER: u32 = 10;
+const EFFECT_DIFFUSER: u32 = 11;
+
+const _: () = assert!((EFFECT_DIFFUSER as usize) < bypass::MAX_EFFECTS);
+
 // ============================================================================
 // EXPORTED FUNCTIONS
 // ============================================================================
@@ -41,102 +82,2688 @@ mod utils;
 /// * `buffer_size` - Number of samples per process block (e.g., 128, 256)
 /// 
 /// # Returns
-/// Pointer to allocated state struct, or 0 on failure
+/// Handle of the new engine (0 for the first), or -1 on failure (invalid
+/// parameters or all engines in use). Pass the handle to every other export.
+/// 
+/// Every call creates another engine; to change the sample rate or buffer
+/// size of a running one use `dsp_reinit`. A slot freed with `dsp_cleanup`
+/// is reused; a granular source loaded into it with a source rate is
+/// converted again if the sample rate changed.
+#[no_mangle]
+pub extern "C" fn dsp_init(sample_rate: f32, buffer_size: u32) -> i32 {
+    let handle = memory::create_engine(sample_rate, buffer_size);
+    if handle >= 0 {
+        granular::resample_source_for_engine_rate();
+    }
+    handle
+}
+
+/// Re-initialize a running engine with a new sample rate and buffer size
+/// 
+/// Same as `dsp_cleanup` followed by `dsp_init`, but keeps the handle (and
+/// the engine's memory) instead of taking the first free slot. Effect tails
+/// are cleared; a granular source loaded with a source rate is converted to
+/// the new rate.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `sample_rate` - Audio sample rate (e.g., 44100, 48000)
+/// * `buffer_size` - Number of samples per process block (e.g., 128, 256)
+/// 
+/// # Returns
+/// The handle, or -1 if it isn't a running engine or the parameters are
+/// invalid (the engine is then left released)
 #[no_mangle]
-pub extern "C" fn dsp_init(sample_rate: f32, buffer_size: u32) -> u32 {
-    memory::init_engine(sample_rate, buffer_size)
+pub extern "C" fn dsp_reinit(handle: u32, sample_rate: f32, buffer_size: u32) -> i32 {
+    if !memory::select_engine(handle) || !memory::is_initialized() {
+        return -1;
+    }
+    dsp_cleanup(handle);
+    if !memory::init_engine(sample_rate, buffer_size) {
+        return -1;
+    }
+    granular::resample_source_for_engine_rate();
+    handle as i32
 }
 
 /// Get pointer to input buffer for writing samples from JavaScript
 /// 
 /// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
 /// * `channel` - Channel index (0 = left, 1 = right)
 /// 
 /// # Returns
 /// Pointer to f32 buffer of length `buffer_size`
 #[no_mangle]
-pub extern "C" fn dsp_get_input_ptr(channel: u32) -> *mut f32 {
+pub extern "C" fn dsp_get_input_ptr(handle: u32, channel: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
     memory::get_input_buffer(channel)
 }
 
 /// Get pointer to output buffer for reading samples from JavaScript
 /// 
 /// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
 /// * `channel` - Channel index (0 = left, 1 = right)
 /// 
 /// # Returns
 /// Pointer to f32 buffer of length `buffer_size`
 #[no_mangle]
-pub extern "C" fn dsp_get_output_ptr(channel: u32) -> *const f32 {
+pub extern "C" fn dsp_get_output_ptr(handle: u32, channel: u32) -> *const f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null();
+    }
     memory::get_output_buffer(channel)
 }
 
+/// Get pointer to the granular source region for writing source samples
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_GRANULAR_SOURCE_SAMPLES samples
+#[no_mangle]
+pub extern "C" fn dsp_get_granular_source_ptr(handle: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    memory::get_granular_source_ptr()
+}
+
+/// Get pointer to the IR region for writing impulse response samples
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_IR_SAMPLES samples
+#[no_mangle]
+pub extern "C" fn dsp_get_ir_ptr(handle: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    memory::get_ir_ptr()
+}
+
+/// Get pointer to the IR region of a convolution slot
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `slot` - 0 = the `dsp_get_ir_ptr` region, 1 = slot B's own region
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_IR_SAMPLES samples (null for an invalid
+/// slot)
+#[no_mangle]
+pub extern "C" fn dsp_get_ir_slot_ptr(handle: u32, slot: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    match slot {
+        0 => memory::get_ir_ptr(),
+        1 => memory::get_ir_b_ptr(),
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Get pointer to the vocoder carrier region for writing carrier samples
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_VOCODER_CARRIER_SAMPLES samples
+#[no_mangle]
+pub extern "C" fn dsp_get_vocoder_carrier_ptr(handle: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    memory::get_vocoder_carrier_ptr()
+}
+
+/// Get the RMS level of the last processed output block
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `channel` - Channel index (0 = left, 1 = right)
+/// 
+/// # Returns
+/// RMS of the output buffer, or 0.0 if the engine isn't initialized
+/// or the channel is invalid
+#[no_mangle]
+pub extern "C" fn dsp_get_output_rms(handle: u32, channel: u32) -> f32 {
+    if !memory::select_engine(handle) || !memory::is_initialized() || channel > 1 {
+        return 0.0;
+    }
+    // SAFETY: Engine is initialized and channel is valid
+    unsafe { simd_utils::rms(memory::output_slice_mut(channel)) }
+}
+
+/// Get the processing latency of an effect, for host latency compensation
+/// 
+/// Includes the output limiter's lookahead while it is enabled. Bypass and
+/// disabled effects pass the input through and add only that.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `effect_id` - 0 = bypass, 1 = granular, 2 = convolution, 3 = spectral,
+///   4 = flanger, 5 = vocoder, 6 = pitch shift, 7 = spectral gate,
+///   8 = saturation, 9 = resonator, 10 = shimmer, 11 = diffuser (the
+///   spectral effects share one framing)
+/// 
+/// # Returns
+/// Latency in samples at the engine's current buffer size, or 0 for an
+/// unknown effect or invalid handle
+#[no_mangle]
+pub extern "C" fn dsp_get_latency_samples(handle: u32, effect_id: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    let effect_latency = match effect_id {
+        _ if !bypass::is_active(effect_id) => 0,
+        // Grains are rendered in the block their input arrives
+        EFFECT_BYPASS | EFFECT_GRANULAR => 0,
+        EFFECT_CONVOLUTION => convolution::latency_samples(),
+        EFFECT_SPECTRAL | EFFECT_VOCODER | EFFECT_PITCH_SHIFT | EFFECT_SPECTRAL_GATE => spectral::latency_samples(),
+        EFFECT_FLANGER => flanger::latency_samples(),
+        EFFECT_SATURATION => saturation::latency_samples(),
+        // The comb delay is the pitch, not latency to compensate
+        EFFECT_RESONATOR => 0,
+        // The pitch shifter only delays the feedback
+        EFFECT_SHIMMER => 0,
+        // The smear starts at the input sample
+        EFFECT_DIFFUSER => 0,
+        _ => return 0,
+    };
+    effect_latency + limiter::latency_samples()
+}
+
+/// Enable or disable the output DC blocker
+/// 
+/// A ~5Hz one-pole/one-zero highpass on the final granular and convolution
+/// outputs removes DC drift from asymmetric grain overlaps or offset IRs.
+/// Off by default.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = block DC, 0 = bypass
+#[no_mangle]
+pub extern "C" fn dsp_set_dc_blocker(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    memory::set_dc_blocker(enabled != 0);
+}
+
+/// Configure the output limiter
+/// 
+/// A stereo-linked lookahead limiter applied to the output buffers at the
+/// end of every process call. While enabled it delays the output by its
+/// 3ms lookahead; disabled (the default) it is a true passthrough.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `threshold_db` - Output ceiling in dBFS (-24 to 0)
+/// * `enabled` - 1 = limit, 0 = bypass
+#[no_mangle]
+pub extern "C" fn dsp_set_limiter(handle: u32, threshold_db: f32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    limiter::set_limiter(threshold_db, enabled != 0);
+}
+
+/// Get the output limiter's gain reduction for metering
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Deepest gain reduction of the last processed block in dB (0.0 = none,
+/// -6.0 = 6dB); 0.0 while the limiter is disabled
+#[no_mangle]
+pub extern "C" fn dsp_get_gain_reduction_db(handle: u32) -> f32 {
+    if !memory::select_engine(handle) {
+        return 0.0;
+    }
+    limiter::gain_reduction_db()
+}
+
+/// Pass the input through to the output unprocessed (bypass)
+/// 
+/// Only the output limiter runs, so the signal chain stays the same as
+/// with an effect.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_process_passthrough(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Passthrough);
+        limiter::process_output();
+    });
+}
+
+/// Generate noise into the output buffers (the input is ignored)
+/// 
+/// A source rather than an effect: copy the output back to the input to
+/// run it through an effect. The channels are uncorrelated.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `noise_type` - 0 = white, 1 = pink, 2 = brown (unknown values use white)
+/// * `gain` - Output gain (0-1; every type is about -4.8dBFS RMS at 1)
+#[no_mangle]
+pub extern "C" fn dsp_process_noise(handle: u32, noise_type: u32, gain: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    let noise_type = noise::NoiseType::from_index(noise_type);
+    profiler::measure(|| {
+        noise::process(noise_type, gain);
+        limiter::process_output();
+    });
+}
+
+/// Enable or disable an effect
+/// 
+/// A disabled effect's process export passes the input through without
+/// running the effect, after a 10ms crossfade (enabling fades back in).
+/// All effects are enabled by default.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `effect_id` - 1 = granular, 2 = convolution, 3 = spectral, 4 = flanger,
+///   5 = vocoder, 6 = pitch shift, 7 = spectral gate, 8 = saturation,
+///   9 = resonator, 10 = shimmer, 11 = diffuser
+/// * `enabled` - 1 = process, 0 = pass through
+#[no_mangle]
+pub extern "C" fn dsp_set_effect_enabled(handle: u32, effect_id: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    bypass::set_enabled(effect_id, enabled != 0);
+}
+
+/// Switch to another effect with a crossfade
+/// 
+/// Call when the host starts calling a different process export (e.g.
+/// `dsp_process_convolution` instead of `dsp_process_granular`). For the
+/// next blocks of the new effect (see `dsp_set_effect_switch_blocks`), the
+/// previous effect keeps running with the arguments of its last block and
+/// is crossfaded into the new one; after that only the new effect runs.
+/// The first call only selects the effect.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `effect_id` - 0 = passthrough, otherwise as for
+///   `dsp_set_effect_enabled` (unknown IDs are ignored)
+#[no_mangle]
+pub extern "C" fn dsp_set_active_effect(handle: u32, effect_id: u32) {
+    if !memory::select_engine(handle) || effect_id as usize >= bypass::MAX_EFFECTS {
+        return;
+    }
+    effect_switch::set_active(effect_id);
+}
+
+/// Set the length of the crossfade between effects
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `blocks` - Crossfade length in processed blocks (0-64, 0 = cut;
+///   default 8)
+#[no_mangle]
+pub extern "C" fn dsp_set_effect_switch_blocks(handle: u32, blocks: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    effect_switch::set_blocks(blocks);
+}
+
 /// Process granular synthesis
 /// 
 /// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
 /// * `grain_size` - Grain size in samples (64-4096)
-/// * `density` - Grains per second (1-100)
+/// * `density` - Grains per second (1-100000, bounded in practice by the grain pool)
 /// * `pitch_spread` - Random pitch variation (0-1)
 /// * `position` - Playback position in source (0-1)
 /// * `spray` - Position randomization (0-1)
 #[no_mangle]
 pub extern "C" fn dsp_process_granular(
+    handle: u32,
     grain_size: u32,
     density: f32,
     pitch_spread: f32,
     position: f32,
     spray: f32,
 ) {
-    granular::process(grain_size, density, pitch_spread, position, spray);
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Granular { grain_size, density, pitch_spread, position, spray });
+        limiter::process_output();
+    });
+}
+
+/// Restrict granular playback to a region of the source
+/// 
+/// `position` and `spray` of `dsp_process_granular` then map into the region.
+/// Reset to the full source when a new source is loaded.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `start` - Region start (normalized, 0-1)
+/// * `end` - Region end (normalized, 0-1)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_region(handle: u32, start: f32, end: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_region(start, end);
+}
+
+/// Set whether grains running past the region end wrap to its start
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = wrap, 0 = stop at the region end
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_region_loop(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_region_loop(enabled != 0);
+}
+
+/// Set granular parameter smoothing
+/// 
+/// `position`, `spray` and `density` glide towards new values with a
+/// one-pole smoother advanced per sample (default 50ms).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `time_ms` - Smoothing time constant in ms (0-2000, 0 = disabled)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_smoothing(handle: u32, time_ms: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_smoothing_time(time_ms);
+}
+
+/// Transpose granular playback
+/// 
+/// Sets the base pitch that `pitch_spread` randomizes around. Only newly
+/// spawned grains are affected; playing grains keep their pitch.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `semitones` - Transpose in semitones (-24 to +24)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_transpose(handle: u32, semitones: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_transpose(semitones);
+}
+
+/// Enable or disable the granular output limiter
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = limit output to ±1.0 (default), 0 = bypass
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_limiter(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_limiter_enabled(enabled != 0);
+}
+
+/// Set the stereo width of the grain cloud
+/// 
+/// Only newly spawned grains are affected.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `width` - 0 = mono (all grains centered), 1 = full ±1 pan (default 0.7)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_stereo_width(handle: u32, width: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_stereo_width(width);
+}
+
+/// Set the lowpass filter of newly spawned grains
+/// 
+/// Each grain gets a one-pole lowpass at the cutoff moved by a random
+/// ±spread octaves, for darker textures. A cutoff at or above Nyquist
+/// (the default) bypasses the filter.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `cutoff` - Cutoff frequency in Hz (20 up)
+/// * `spread` - Random cutoff offset per grain in octaves (0-4)
+#[no_mangle]
+pub extern "C" fn dsp_set_grain_filter(handle: u32, cutoff: f32, spread: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_grain_filter(cutoff, spread);
+}
+
+/// Set the random spawn time offset of new grains
+/// 
+/// Moves each grain's onset by up to ±amount of the spawn interval, for an
+/// irregular cloud at the same average density.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `amount` - Largest offset as a share of the spawn interval (0-1,
+///   0 = regular spawning, the default)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_time_jitter(handle: u32, amount: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_time_jitter(amount);
+}
+
+/// Spawn grains on a tempo clock instead of by density
+/// 
+/// Each tick spawns a burst of grains, for rhythmic granular textures;
+/// the density argument of `dsp_process_granular` is ignored meanwhile.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `bpm` - Tempo in beats per minute (1-1000, 0 = spawn by density, the
+///   default)
+/// * `division` - Note value of one tick (1-64: 4 = quarter notes,
+///   8 = eighths, 12 = eighth triplets)
+/// * `burst` - Grains spawned per tick (1-16)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_clock(handle: u32, bpm: f32, division: f32, burst: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_grain_clock(bpm, division, burst, memory::sample_rate());
+}
+
+/// Select how grains are placed in the stereo field
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `mode` - 0 = random within the width, 1 = alternate left/right (ping-pong)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_pan_mode(handle: u32, mode: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_pan_mode(granular::PanMode::from_index(mode));
+}
+
+/// Select the granular pan law
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `law` - Level of a centered grain: 0 = -3dB (constant power, default),
+///   1 = -4.5dB, 2 = -6dB (linear); unknown values use constant power
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_pan_law(handle: u32, law: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_pan_law(utils::PanLaw::from_index(law));
+}
+
+/// Select how `pitch_spread` randomizes grain pitch
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `mode` - 0 = continuous (±spread octaves), 1 = octaves (-12/0/+12
+///   semitones), 2 = harmonic (1/2, 2/3, 1, 3/2, 2). In modes 1 and 2
+///   `pitch_spread` is the probability of leaving unison.
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_pitch_mode(handle: u32, mode: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_pitch_mode(granular::PitchMode::from_index(mode));
+}
+
+/// Seed the granular RNG
+/// 
+/// The same seed, source and parameters reproduce the same texture: grain
+/// positions, pitches and pans follow the same sequence.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `seed` - RNG seed
+/// * `restore_on_reset` - Nonzero to go back to the seed on every reset
+///   (e.g. each time playback starts), 0 to let the sequence carry on
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_seed(handle: u32, seed: u32, restore_on_reset: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_seed(seed, restore_on_reset != 0);
+}
+
+/// Select granular source interpolation
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `mode` - 0 = linear, 1 = 4-point cubic (Catmull-Rom); unknown
+///   values use linear
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_interpolation(handle: u32, mode: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_interpolation(granular::InterpMode::from_index(mode));
+}
+
+/// Set how fast the granular base position scans through the source
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `speed` - Multiple of real time (-4 to 4); 0 holds the position
+///   (time-stretch freeze), 1 plays through at normal speed. Independent
+///   of grain pitch.
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_scan_speed(handle: u32, speed: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_scan_speed(speed);
+}
+
+/// Select what the granular scan does at the region bounds
+/// 
+/// The loop region is the one set with `dsp_set_granular_region`.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `mode` - 0 = stop at the bound (default), 1 = wrap around (forward
+///   loop), 2 = reverse direction at each bound (ping-pong)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_loop_mode(handle: u32, mode: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_loop_mode(granular::LoopMode::from_index(mode));
+}
+
+/// Select mono-mix or stereo granular grains
+/// 
+/// Stereo grains keep the source's left/right channels and the grain pan
+/// rotates the stereo field; mono sources are duplicated to both channels.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = stereo grains, 0 = mono mix (default)
+#[no_mangle]
+pub extern "C" fn dsp_set_stereo_grains(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_stereo_grains(enabled != 0);
+}
+
+/// Align new granular grains to zero crossings of the source
+/// 
+/// Each new grain's start moves to the nearest zero crossing within 64
+/// frames, which keeps grains on percussive sources from clicking.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = align grain starts, 0 = start anywhere (default)
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_zero_crossing_align(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_zero_crossing_align(enabled != 0);
+}
+
+/// Granulate the live input instead of the loaded source
+/// 
+/// In live mode `position` of `dsp_process_granular` is seconds into the
+/// past (up to the history length) and `spray` is extra random delay in seconds.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - Non-zero = live input, 0 = loaded source
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_live_mode(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_live_mode(enabled != 0);
+}
+
+/// Freeze live input recording for granulation
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `frozen` - Non-zero = stop recording and keep granulating the history
+#[no_mangle]
+pub extern "C" fn dsp_set_granular_live_freeze(handle: u32, frozen: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    granular::set_live_freeze(frozen != 0);
+}
+
+/// Stop every playing grain and restart the spawn timing
+/// 
+/// Takes effect over the next `dsp_process_granular` block, which fades
+/// out before the reset; the block after it fades back in. Safe to call
+/// during playback. The source and parameters are kept.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_reset_granular(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    soft_reset::request(Processor::Granular);
 }
 
 /// Process convolution reverb
 /// 
 /// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
 /// * `dry_wet` - Dry/wet mix (0 = dry, 1 = wet)
+/// * `blend` - Wet signal from the slot 0 IR (0) to the slot 1 IR (1); see
+///   `dsp_load_ir_slot`
 #[no_mangle]
-pub extern "C" fn dsp_process_convolution(dry_wet: f32) {
-    convolution::process(dry_wet);
+pub extern "C" fn dsp_process_convolution(handle: u32, dry_wet: f32, blend: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Convolution { dry_wet, blend });
+        limiter::process_output();
+    });
 }
 
-/// Process spectral freeze
+/// Get pointer to the ducking sidechain buffer
 /// 
 /// # Arguments
-/// * `freeze_amount` - Amount of spectral freeze (0-1)
-/// * `shift` - Frequency shift in semitones (-24 to +24)
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_BUFFER_SIZE mono samples
 #[no_mangle]
-pub extern "C" fn dsp_process_spectral(freeze_amount: f32, shift: f32) {
-    spectral::process(freeze_amount, shift);
+pub extern "C" fn dsp_get_sidechain_ptr(handle: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    memory::get_sidechain_ptr()
 }
 
-/// Load impulse response for convolution
+/// Load the sidechain for the next processed block
+/// 
+/// Call after writing the block's mono sidechain samples to the
+/// `dsp_get_sidechain_ptr` region. Each loaded sidechain drives the
+/// ducking of one block; blocks without one follow silence.
 /// 
 /// # Arguments
-/// * `ir_ptr` - Pointer to IR sample data
-/// * `ir_length` - Number of samples in IR
-/// * `ir_channels` - Number of channels (1 or 2)
+/// * `handle` - Engine handle from `dsp_init`
+/// * `_sidechain_ptr` - Pointer to sidechain data (must be the sidechain region)
+/// * `length` - Number of samples (up to the buffer size)
+/// 
+/// # Returns
+/// Load status: 0 = ok, 1 = truncated to the buffer size
 #[no_mangle]
-pub extern "C" fn dsp_load_ir(ir_ptr: *const f32, ir_length: u32, ir_channels: u32) {
-    convolution::load_ir(ir_ptr, ir_length, ir_channels);
+pub extern "C" fn dsp_load_sidechain(handle: u32, _sidechain_ptr: *const f32, length: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    ducking::load(length)
 }
 
-/// Load source buffer for granular synthesis
+/// Set sidechain ducking of the convolution wet signal
+/// 
+/// The wet signal is scaled by 1 - amount * envelope of the loaded
+/// sidechain. With no sidechain loaded the output is unchanged.
 /// 
 /// # Arguments
-/// * `source_ptr` - Pointer to source sample data
-/// * `source_length` - Number of samples
-/// * `source_channels` - Number of channels (1 or 2)
+/// * `handle` - Engine handle from `dsp_init`
+/// * `amount` - Ducking depth (0-1, 0 = off)
+/// * `attack_ms` - Envelope attack time (0.1-5000ms)
+/// * `release_ms` - Envelope release time (0.1-5000ms)
+/// * `detector` - 0 = peak, 1 = RMS
 #[no_mangle]
-pub extern "C" fn dsp_load_granular_source(
-    source_ptr: *const f32,
-    source_length: u32,
-    source_channels: u32,
-) {
-    granular::load_source(source_ptr, source_length, source_channels);
+pub extern "C" fn dsp_set_ducking(handle: u32, amount: f32, attack_ms: f32, release_ms: f32, detector: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    ducking::set(amount, attack_ms, release_ms, modulation::FollowerMode::from_index(detector));
+}
+
+/// Set per-channel convolution wet gains
+/// 
+/// Trims applied to each wet channel on top of the `dsp_process_convolution` mix.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `left_gain` - Left wet gain (0-2, 1 = unity)
+/// * `right_gain` - Right wet gain (0-2, 1 = unity)
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_wet(handle: u32, left_gain: f32, right_gain: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_wet_gains(left_gain, right_gain);
 }
 
-/// Free all allocated memory (call on AudioWorklet disposal)
+/// Set the convolution wet signal compressor
+/// 
+/// Levels the wet signal before the dry/wet mix, so IRs of very different
+/// energy give similar tail levels. Ratio 1 (the default) bypasses it.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `threshold_db` - Threshold in dBFS (-60-0)
+/// * `ratio` - Compression ratio (1-20, 1 = bypass)
+/// * `attack_ms` - Attack time (1-500ms)
+/// * `release_ms` - Release time (10-5000ms)
+/// * `makeup_db` - Makeup gain (0-24dB)
+/// * `detector` - 0 = peak, 1 = RMS
 #[no_mangle]
-pub extern "C" fn dsp_cleanup() {
-    memory::cleanup();
+pub extern "C" fn dsp_set_convolution_compressor(
+    handle: u32,
+    threshold_db: f32,
+    ratio: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    makeup_db: f32,
+    detector: u32,
+) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    let detector = modulation::FollowerMode::from_index(detector);
+    convolution::set_wet_compressor(threshold_db, ratio, attack_ms, release_ms, makeup_db, detector);
+}
+
+/// Set the stereo width of the convolution wet signal
+/// 
+/// Mid/side processing of the wet L/R before the dry/wet mix; the dry
+/// signal keeps its image.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `width` - 0 = mono wet, 1 = unchanged (default), up to 2 = widened
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_width(handle: u32, width: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_wet_width(width);
+}
+
+/// Set the convolution reverb predelay
+/// 
+/// Delays the wet signal only; changes crossfade over 20ms.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `ms` - Predelay in milliseconds (0-200, 0 = none)
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_predelay(handle: u32, ms: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_predelay(ms);
+}
+
+/// Set the convolution reverb early/late split point
+/// 
+/// IR partitions starting before the split feed the early bus, the rest
+/// the late bus. The split snaps to the nearest partition boundary.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `ms` - Split point in milliseconds (0-1000, 0 = all late)
+/// 
+/// # Returns
+/// The snapped split point in milliseconds
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_split(handle: u32, ms: f32) -> f32 {
+    if !memory::select_engine(handle) {
+        return 0.0;
+    }
+    convolution::set_split(ms);
+    convolution::split_ms()
+}
+
+/// Set the convolution reverb's input block size
+/// 
+/// The block size sets the reverb's latency and cost: a block that divides
+/// the host buffer adds no latency (e.g. 128 for 128-sample host blocks),
+/// while a larger one runs fewer FFTs. The loaded IRs are partitioned
+/// again for the new size and the reverb tail is cleared, so set it
+/// before playback. `dsp_get_latency_samples` reports the new latency.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `size` - Block size in samples (64-1024, rounded up to a power of
+///   two; default 256)
+/// 
+/// # Returns
+/// The block size in effect, or 0 for an invalid handle
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_block(handle: u32, size: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    convolution::set_block_size(size)
+}
+
+/// Set the convolution reverb early and late bus gains
+/// 
+/// Balances early reflections against the tail without reloading the IR.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `early_gain` - Early reflections gain (0-2, 1 = unity)
+/// * `late_gain` - Tail gain (0-2, 1 = unity)
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_early_late(handle: u32, early_gain: f32, late_gain: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_early_late_gains(early_gain, late_gain);
+}
+
+/// Select the convolution dry/wet mix law
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `linear` - 1 = linear crossfade, 0 = equal-power (default; keeps
+///   the perceived level constant across the mix)
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_linear_mix(handle: u32, linear: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_linear_mix(linear != 0);
+}
+
+/// Peak of the mono sum (L + R) of the last convolution output block
+/// 
+/// Compare against the channel levels to detect phase cancellation
+/// when the output is summed to mono.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_convolution_mono_sum_peak(handle: u32) -> f32 {
+    if !memory::select_engine(handle) {
+        return 0.0;
+    }
+    convolution::mono_sum_peak()
+}
+
+/// Clear the convolution reverb tail at once
+/// 
+/// Call after stopping the transport so the old tail doesn't play when
+/// audio resumes; during playback, use `dsp_reset_convolution`, which
+/// doesn't click. The loaded IR is kept. Does nothing before an IR is
+/// loaded.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_convolution_reset(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::reset();
+}
+
+/// Clear the convolution reverb tail without a click
+/// 
+/// Takes effect over the next `dsp_process_convolution` block, which fades
+/// out before the tail is cleared; the block after it fades back in. The
+/// loaded IR is kept.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_reset_convolution(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    soft_reset::request(Processor::Convolution);
+}
+
+/// Whether the convolution reverb tail is still audible
+/// 
+/// Lets a host keep processing a bypassed reverb until its tail has rung
+/// out. Reflects the last processed block: 1 while the wet output or the
+/// input the IR has yet to respond to is above the tail threshold, 0 once
+/// it has decayed (or without an IR).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_convolution_tail_active(handle: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    convolution::tail_active() as u32
+}
+
+/// Set the level below which the convolution tail counts as silent
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `threshold_db` - Threshold in dBFS (default -80)
+#[no_mangle]
+pub extern "C" fn dsp_set_convolution_tail_threshold(handle: u32, threshold_db: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_tail_threshold(threshold_db);
+}
+
+/// Process spectral freeze
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `freeze_amount` - Amount of spectral freeze (0-1)
+/// * `shift` - Frequency shift in semitones (-24 to +24)
+/// * `slot` - Freeze slot to freeze to (0-3; fractional values blend the
+///   neighbouring slots, e.g. 1.5 = halfway between slots 1 and 2).
+///   Negative, or a position without captures, freezes the spectrum at
+///   the moment `freeze_amount` rises above 0.
+/// * `dry_wet` - Mix between dry (0) and wet (1) signal. The dry signal is
+///   delayed by the latency to stay phase-aligned with the wet one.
+/// * `mode` - Resynthesis: 0 = normal, 1 = robot (constant phase per frame,
+///   a monotone voice), 2 = whisper (random phases)
+/// * `formant_preserve` - Non-zero keeps the spectral envelope fixed
+///   while the shift moves the harmonics
+/// * `shift_hz` - Linear (Bode-style) frequency shift in Hz, applied after
+///   the semitone shift; components moved below 0Hz or past Nyquist are
+///   dropped
+#[no_mangle]
+pub extern "C" fn dsp_process_spectral(
+    handle: u32,
+    freeze_amount: f32,
+    shift: f32,
+    slot: f32,
+    dry_wet: f32,
+    mode: u32,
+    formant_preserve: u32,
+    shift_hz: f32,
+) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    let mode = spectral::SpectralMode::from_index(mode);
+    let formant_preserve = formant_preserve != 0;
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Spectral { freeze_amount, shift, slot, dry_wet, mode, formant_preserve, shift_hz });
+        limiter::process_output();
+    });
+}
+
+/// Set the FFT size and overlap of the spectral effects
+/// 
+/// Applies to spectral freeze, vocoder, pitch shift, spectral gate and the
+/// shimmer's pitch shifter. Smaller frames keep transients tighter (e.g.
+/// 1024 for percussive material), larger ones resolve low frequencies
+/// better (e.g. 4096 for drones). Phases, frozen spectra and freeze slots
+/// are cleared. `dsp_get_latency_samples` reports the new latency
+/// (FFT size - 1).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `size` - FFT size in samples (256-8192, rounded up to a power of two;
+///   default 2048)
+/// * `overlap` - Frames overlapping each sample (4 or 8, hop = size /
+///   overlap; default 4)
+/// 
+/// # Returns
+/// The FFT size in effect, or 0 for an invalid handle
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_fft(handle: u32, size: u32, overlap: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    spectral::set_fft(size, overlap)
+}
+
+/// Set the spectral freeze effect's input gate
+/// 
+/// Attenuates quiet bins of the analysis before they are frozen or
+/// shifted, e.g. to clean the noise floor out of a field recording.
+/// Applies to `dsp_process_spectral` only (`dsp_process_spectral_gate` is
+/// the standalone gate effect).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `threshold_db` - Bin level below which bins are gated, in dBFS
+///   (-Infinity = gate open, the default)
+/// * `reduction_db` - Attenuation of gated bins in dB (0-120)
+/// * `release_ms` - Time for a gated bin to close (1-5000ms, default 100);
+///   bins open within an analysis frame
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_gate(handle: u32, threshold_db: f32, reduction_db: f32, release_ms: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_input_gate(threshold_db, reduction_db, release_ms);
+}
+
+/// Set how the spectral freeze engages and moves
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `fade_frames` - Analysis frames the freeze crossfades in over after
+///   each capture (0-64, 0 = snap; default 4)
+/// * `phase_drift` - Share of the live phase frozen bins keep (0-1,
+///   0 = fully static; default 0.1)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_freeze(handle: u32, fade_frames: u32, phase_drift: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_freeze(fade_frames, phase_drift);
+}
+
+/// Set how the spectral freeze fades and wanders
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `decay_db_per_second` - Frozen magnitude decay from each capture, so
+///   the freeze slowly fades (0-120dB per second, 0 = held; default 0)
+/// * `drift` - Random walk of the frozen bins each frame for subtle motion
+///   (0-1, 0 = static; default 0)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_freeze_motion(handle: u32, decay_db_per_second: f32, drift: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_freeze_motion(decay_db_per_second, drift);
+}
+
+/// Tilt the spectral freeze/shift output spectrum
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `db_per_octave` - Tilt about the pivot (-12 to +12dB per octave,
+///   positive = brighter; 0 = off, the default)
+/// * `pivot_hz` - Frequency the tilt leaves unchanged (default 1000Hz)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_tilt(handle: u32, db_per_octave: f32, pivot_hz: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_tilt(db_per_octave, pivot_hz);
+}
+
+/// Set the low and high shelves of the spectral freeze/shift output
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `low_hz` - Low shelf corner, where it has half its gain (default 200Hz)
+/// * `low_db` - Low shelf gain (±24dB, 0 = off, the default)
+/// * `high_hz` - High shelf corner, where it has half its gain (default 4000Hz)
+/// * `high_db` - High shelf gain (±24dB, 0 = off, the default)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_shelves(handle: u32, low_hz: f32, low_db: f32, high_hz: f32, high_db: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_shelves(low_hz, low_db, high_hz, high_db);
+}
+
+/// Select the phase vocoder of the spectral freeze/shift
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 to lock the phases around spectral peaks (fewer
+///   smeared transients), 0 for independent bin phases (the default)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_phase_locking(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_phase_locking(enabled != 0);
+}
+
+/// Set the shimmer feedback of the spectral freeze/shift
+/// 
+/// Feeds the wet output back into the analysis input, so the tail is
+/// shifted again on every trip (about a frame long): with a +12 semitone
+/// shift it climbs in octaves. The feedback is soft-clipped, and at 0 the
+/// loop drains out. Off by default.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `amount` - Feedback gain (0-0.9, 0 = off)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_feedback(handle: u32, amount: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_feedback(amount);
+}
+
+/// Set a harmonizer voice of the spectral freeze/shift
+/// 
+/// Each sounding voice (see `dsp_set_spectral_voice_count`) adds a copy
+/// of the frozen/live spectrum shifted by its own interval, e.g. +3, +7
+/// and +12 for a minor chord over a drone. Voices default to unison at
+/// unity gain.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `index` - Voice (0-3)
+/// * `semitones` - Interval of the voice (-24 to +24), independent of the
+///   freeze/shift's own shift
+/// * `gain` - Linear gain of the voice (0-1)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_voice(handle: u32, index: u32, semitones: f32, gain: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_voice(index, semitones, gain);
+}
+
+/// Set how many harmonizer voices of the spectral freeze/shift sound
+/// 
+/// Each voice adds per-bin work to every frame but no FFTs (about a
+/// quarter of the freeze/shift's cost natively).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `count` - Voices 0 to count - 1 sound (0-4, 0 = off, the default)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_voice_count(handle: u32, count: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_voice_count(count);
+}
+
+/// Set the comb of the spectral freeze bin mask
+/// 
+/// Bands around the harmonics of the fundamental pass and the bins between
+/// them are attenuated, after the freeze blend: freeze a pad, then carve
+/// its harmonics out.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `fundamental_hz` - Fundamental whose harmonics pass (20Hz-Nyquist)
+/// * `width_bins` - Width of each pass band at half gain (0.5-64 bins)
+/// * `depth` - Attenuation between the bands (0-1, 0 = off)
+#[no_mangle]
+pub extern "C" fn dsp_set_spectral_comb(handle: u32, fundamental_hz: f32, width_bins: f32, depth: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::set_comb(fundamental_hz, width_bins, depth);
+}
+
+/// Get pointer to the spectral mask buffer
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to f32 buffer of MAX_SPECTRAL_MASK_BINS per-bin gains
+#[no_mangle]
+pub extern "C" fn dsp_get_spectral_mask_ptr(handle: u32) -> *mut f32 {
+    if !memory::select_engine(handle) {
+        return core::ptr::null_mut();
+    }
+    memory::get_spectral_mask_ptr()
+}
+
+/// Load the per-bin gain mask of the spectral freeze
+/// 
+/// Call after writing the gains (0-1, one per bin from DC up) to the
+/// `dsp_get_spectral_mask_ptr` region. The mask multiplies the comb and
+/// applies after the freeze blend; a mask with another bin count than
+/// the FFT is stretched across its bins.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `_mask_ptr` - Pointer to mask data (must be the spectral mask region)
+/// * `num_bins` - Number of gains (0 clears the mask)
+/// 
+/// # Returns
+/// Load status: 0 = ok, 1 = truncated to MAX_SPECTRAL_MASK_BINS
+#[no_mangle]
+pub extern "C" fn dsp_load_spectral_mask(handle: u32, _mask_ptr: *const f32, num_bins: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    spectral::load_mask(num_bins)
+}
+
+/// Take a new frozen snapshot while the spectral freeze is engaged
+/// 
+/// The next analysis frame replaces the auto-captured frozen spectrum and
+/// crossfades in like a new freeze. Freeze slots are captured with
+/// `dsp_spectral_capture` instead.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_spectral_recapture(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::recapture();
+}
+
+/// Capture the current spectrum into a freeze slot
+/// 
+/// The next analysis frame of `dsp_process_spectral` is stored, replacing
+/// the slot's previous capture.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `slot` - Freeze slot (0-3; others are ignored)
+#[no_mangle]
+pub extern "C" fn dsp_spectral_capture(handle: u32, slot: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::capture(slot);
+}
+
+/// Empty a freeze slot
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `slot` - Freeze slot (0-3; others are ignored)
+#[no_mangle]
+pub extern "C" fn dsp_spectral_release(handle: u32, slot: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::release(slot);
+}
+
+/// Whether a freeze slot holds a capture (e.g. to light up its button)
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `slot` - Freeze slot (0-3)
+/// 
+/// # Returns
+/// 1 if the slot holds a capture, 0 otherwise
+#[no_mangle]
+pub extern "C" fn dsp_spectral_slot_captured(handle: u32, slot: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    spectral::slot_captured(slot) as u32
+}
+
+/// Clear the spectral effects' frames, frozen spectrum and phases at once
+/// 
+/// Call after stopping the transport so the overlap-add buffers don't
+/// spill the old audio into the next playback; during playback, use
+/// `dsp_reset_spectral`, which doesn't click. Freeze slots and parameters
+/// are kept. Does nothing before a spectral effect has run.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_spectral_reset(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::reset();
+}
+
+/// Latency of the spectral effects in samples
+/// 
+/// FFT size - 1 (see `dsp_set_spectral_fft`): a frame's output starts at
+/// the sample that completes it. Unlike `dsp_get_latency_samples`, doesn't
+/// include the output limiter's lookahead or depend on the enable flags.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_spectral_latency(handle: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    spectral::latency_samples()
+}
+
+/// Clear the spectral effects' frames, frozen spectrum and feedback
+/// 
+/// Shared by the spectral freeze/shift, vocoder, pitch shift and spectral
+/// gate. Takes effect over the next block of whichever of them runs first,
+/// which fades out before the reset; the block after it fades back in.
+/// Freeze slots and parameters are kept.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_reset_spectral(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    soft_reset::request(Processor::Spectral);
+}
+
+/// Process flanger
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `rate` - LFO rate in Hz (0.01-10)
+/// * `depth` - Sweep depth (0-1)
+/// * `feedback` - Feedback (-0.95 to 0.95, negative = hollow)
+/// * `mix` - Dry (0) to swept (1) mix; 0.5 gives the deepest notches
+#[no_mangle]
+pub extern "C" fn dsp_process_flanger(handle: u32, rate: f32, depth: f32, feedback: f32, mix: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Flanger { rate, depth, feedback, mix });
+        limiter::process_output();
+    });
+}
+
+/// Process saturation
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `drive` - Linear pre-gain (1-100); the output is compensated by
+///   `1 / sqrt(drive)`
+/// * `curve_id` - 0 = tanh, 1 = cubic, 2 = arctan, 3 = hard clip,
+///   4 = foldback (unknown values use tanh)
+/// * `mix` - Dry (0) to saturated (1) mix
+#[no_mangle]
+pub extern "C" fn dsp_process_saturation(handle: u32, drive: f32, curve_id: u32, mix: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    let curve = saturation::SaturationCurve::from_index(curve_id);
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Saturation { drive, curve, mix });
+        limiter::process_output();
+    });
+}
+
+/// Use the table-based tanh in the saturation curves
+/// 
+/// Cheaper per sample, within 1e-5 of the exact curve (far below audible
+/// distortion). Off by default.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - Non-zero for the table-based curves
+#[no_mangle]
+pub extern "C" fn dsp_set_saturation_fast_math(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    saturation::set_fast_math(enabled != 0);
+}
+
+/// Set the saturation oversampling factor
+/// 
+/// Running the curves at 2x or 4x the sample rate keeps their harmonics
+/// from aliasing, at 23 or 35 samples of latency (see
+/// `dsp_get_latency_samples`). Off (1x) by default.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `factor` - 1, 2 or 4 (other values use 1)
+#[no_mangle]
+pub extern "C" fn dsp_set_saturation_oversampling(handle: u32, factor: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    saturation::set_oversampling(factor);
+}
+
+/// Process the comb resonator
+/// 
+/// A comb filter tuned to `freq` rings like a string, excited by the input
+/// and by `dsp_pluck_resonator`.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `freq` - Fundamental in Hz (20-5000), tuned to a fraction of a sample
+/// * `decay` - Time for the fundamental to fall by 60dB (0.05-30s)
+/// * `damping` - How much faster the harmonics decay (0 = bright, 1 = dark)
+/// * `mix` - Dry (0) to resonator (1) mix
+#[no_mangle]
+pub extern "C" fn dsp_process_resonator(handle: u32, freq: f32, decay: f32, damping: f32, mix: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Resonator { freq, decay, damping, mix });
+        limiter::process_output();
+    });
+}
+
+/// Process the shimmer reverb
+/// 
+/// A reverb whose tail is pitch shifted and fed back into it, so it keeps
+/// rising by the shift as it decays. Shares the spectral effects' pitch
+/// shifter, so don't run it alongside `dsp_process_pitch_shift`.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `size` - Reverb size (0-1, about 1-10s decay)
+/// * `shift_semitones` - Pitch shift per trip around the loop (-24 to +24,
+///   12 = classic octave shimmer; no feedback within 0.1 of 0)
+/// * `shimmer_amount` - Feedback of the shifted tail (0 = plain reverb,
+///   1 = loop gain 0.8)
+/// * `dry_wet` - Dry (0) to wet (1) mix, equal power
+#[no_mangle]
+pub extern "C" fn dsp_process_shimmer(handle: u32, size: f32, shift_semitones: f32, shimmer_amount: f32, dry_wet: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Shimmer { size, shift_semitones, shimmer_amount, dry_wet });
+        limiter::process_output();
+    });
+}
+
+/// Process the all-pass diffuser
+/// 
+/// Smears the input into a dense cloud of echoes without colouring it,
+/// e.g. to soften transients or in front of a reverb.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `amount` - All-pass coefficient (0-0.9, 0 = plain delay, higher
+///   smears longer)
+/// * `size` - Delay scale (0.1-2, 1 = stage delays of about 2-16ms)
+/// * `stages` - All-passes in series per channel (1-8, more is denser)
+/// * `dry_wet` - Dry (0) to wet (1) mix, equal power
+#[no_mangle]
+pub extern "C" fn dsp_process_diffuser(handle: u32, amount: f32, size: f32, stages: u32, dry_wet: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Diffuser { amount, size, stages, dry_wet });
+        limiter::process_output();
+    });
+}
+
+/// Pluck the resonator with a one-period noise burst
+/// 
+/// The burst plays from the next `dsp_process_resonator` block.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_pluck_resonator(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    resonator::pluck();
+}
+
+/// Enable or disable through-zero flanging
+/// 
+/// Through-zero mode sweeps two delays against each other so the notches
+/// pass through zero delay. It delays the whole output by 5ms.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - Non-zero for through-zero mode
+#[no_mangle]
+pub extern "C" fn dsp_set_flanger_through_zero(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    flanger::set_through_zero(enabled != 0);
+}
+
+/// Select the flanger LFO waveform
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `shape` - 0 = sine (default), 1 = triangle, 2 = saw, 3 = square,
+///   4 = sample and hold; unknown values use sine
+#[no_mangle]
+pub extern "C" fn dsp_set_flanger_lfo_shape(handle: u32, shape: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    flanger::set_lfo_shape(modulation::LfoShape::from_index(shape));
+}
+
+/// Process channel vocoder
+/// 
+/// The input signal is the modulator; the carrier is loaded with
+/// `dsp_load_vocoder_carrier` and loops when shorter than the input stream.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `bands` - Envelope resolution (4-1025, fewer = smoother envelope)
+/// * `formant_shift` - Modulator envelope shift in semitones (-12 to +12)
+#[no_mangle]
+pub extern "C" fn dsp_process_vocoder(handle: u32, bands: f32, formant_shift: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::Vocoder { bands, formant_shift });
+        limiter::process_output();
+    });
+}
+
+/// Process duration-preserving pitch shift
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `semitones` - Pitch shift in semitones (-24 to +24)
+/// * `formant_preserve` - Non-zero keeps the spectral envelope fixed
+#[no_mangle]
+pub extern "C" fn dsp_process_pitch_shift(handle: u32, semitones: f32, formant_preserve: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::PitchShift { semitones, formant_preserve: formant_preserve != 0 });
+        limiter::process_output();
+    });
+}
+
+/// Process spectral noise gate
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `threshold_db` - Bin level below which bins are gated, in dBFS
+///   (-Infinity = gate fully open)
+/// * `reduction_db` - Attenuation of gated bins in dB (0-120)
+#[no_mangle]
+pub extern "C" fn dsp_process_spectral_gate(handle: u32, threshold_db: f32, reduction_db: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::measure(|| {
+        effect_switch::process(EffectBlock::SpectralGate { threshold_db, reduction_db });
+        limiter::process_output();
+    });
+}
+
+/// Load impulse response for convolution
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `ir_ptr` - Pointer to IR sample data
+/// * `ir_length` - Number of samples in IR (per channel)
+/// * `ir_channels` - Number of channels (1 or 2). A stereo IR convolves
+///   each input channel with its own response; a mono IR is shared.
+/// * `source_rate` - Sample rate of the IR data in Hz. An IR at another
+///   rate than the engine is resampled once, at load, so its timing is
+///   kept; 0 loads it unconverted.
+/// 
+/// # Returns
+/// 0 = loaded, 1 = truncated to the IR region (5 seconds at 48kHz, after
+/// any conversion), 2 = rejected (invalid channel count or handle,
+/// previous IR kept). A length of 0 unloads the IR and convolution passes
+/// the input through.
+/// 
+/// While audio is running the new IR is prepared over the next blocks and
+/// crossfaded in over 50ms; the old one keeps playing until then.
+#[no_mangle]
+pub extern "C" fn dsp_load_ir(
+    handle: u32,
+    ir_ptr: *const f32,
+    ir_length: u32,
+    ir_channels: u32,
+    source_rate: f32,
+) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    convolution::load_ir(ir_ptr, ir_length, ir_channels, source_rate)
+}
+
+/// Load an impulse response into a convolution slot
+/// 
+/// Slot 0 is the IR of `dsp_load_ir`; slot 1 is a second IR that the
+/// `blend` argument of `dsp_process_convolution` crossfades to. Both slots
+/// convolve every block while slot 1 holds an IR (unload it with a length
+/// of 0 when not blending). Blend 0 and 1 sound exactly like the slot's IR
+/// loaded alone. A slot 1 IR is swapped in without a crossfade, so load it
+/// while the blend is at 0.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `slot` - 0 or 1; samples are read from `dsp_get_ir_slot_ptr(slot)`
+/// * `ir_ptr` - Pointer to IR sample data
+/// * `ir_length` - Number of samples in IR (per channel, at `source_rate`)
+/// * `ir_channels` - Number of channels (1 or 2)
+/// * `source_rate` - Sample rate of the IR data in Hz (0 = engine rate),
+///   as for `dsp_load_ir`
+/// 
+/// # Returns
+/// Same status codes as `dsp_load_ir`; an invalid slot is rejected.
+#[no_mangle]
+pub extern "C" fn dsp_load_ir_slot(
+    handle: u32,
+    slot: u32,
+    ir_ptr: *const f32,
+    ir_length: u32,
+    ir_channels: u32,
+    source_rate: f32,
+) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    match slot {
+        0 => convolution::load_ir(ir_ptr, ir_length, ir_channels, source_rate),
+        1 => convolution::load_ir_b(ir_length, ir_channels, source_rate),
+        _ => memory::LOAD_REJECTED,
+    }
+}
+
+/// Start loading an impulse response in chunks
+/// 
+/// For long IRs: JS writes one chunk per audio callback instead of the
+/// whole IR at once, and the partitions are computed over the blocks after
+/// `dsp_finish_ir_load`. The playing IR is kept until then.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `total_frames` - Number of samples per channel that will be appended
+/// * `ir_channels` - Number of channels (1 or 2)
+/// 
+/// # Returns
+/// 0 = ok, 1 = longer than the IR region (the excess will be dropped),
+/// 2 = rejected (invalid channel count or handle)
+#[no_mangle]
+pub extern "C" fn dsp_begin_ir_load(handle: u32, total_frames: u32, ir_channels: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    convolution::begin_ir_load(total_frames, ir_channels)
+}
+
+/// Append a chunk to an IR started with `dsp_begin_ir_load`
+/// 
+/// The chunk's interleaved samples must already be in the IR region,
+/// right after the previous chunks (sample offset = frames appended so far
+/// * channels).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `chunk_ptr` - Pointer to the chunk (not used, see above)
+/// * `frames` - Number of samples per channel in the chunk
+/// 
+/// # Returns
+/// 0 = ok, 1 = past the announced length (the excess is dropped),
+/// 2 = rejected (no load in progress or invalid handle)
+#[no_mangle]
+pub extern "C" fn dsp_append_ir_chunk(handle: u32, chunk_ptr: *const f32, frames: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    convolution::append_ir_chunk(chunk_ptr, frames)
+}
+
+/// Finish an IR started with `dsp_begin_ir_load`
+/// 
+/// While audio is running the IR is prepared over the next blocks and then
+/// crossfaded in (or switched in, if no IR was playing).
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// 0 = loaded, 1 = truncated, 2 = rejected (no load in progress or
+/// invalid handle)
+#[no_mangle]
+pub extern "C" fn dsp_finish_ir_load(handle: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    convolution::finish_ir_load()
+}
+
+/// Select IR level normalization
+/// 
+/// Applied when an IR is loaded (a loaded IR is rebuilt right away), so
+/// swapping IRs keeps the wet level consistent at no runtime cost.
+/// 
+/// Unit energy is wet gain compensation: it targets equal wet loudness, so
+/// a unit impulse and a long hall IR sound equally loud. Peak response
+/// targets equal peak gain instead, which leaves dense IRs quieter; peak
+/// sample scales the time-domain IR to a largest sample of ±1. Read the
+/// applied gain with `dsp_ir_normalization_gain`; mode 0 disables it and
+/// leaves the IR as loaded.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `mode` - 0 = off, 1 = unit energy, 2 = peak frequency response at
+///   0dB, 3 = peak sample at ±1
+#[no_mangle]
+pub extern "C" fn dsp_set_ir_normalization(handle: u32, mode: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_ir_normalization(convolution::IrNormalization::from_index(mode));
+}
+
+/// Set the IR decay fade and trim
+/// 
+/// Shortens the reverb by fading and/or cutting the loaded IR, which also
+/// saves the CPU of the trimmed partitions. The IR is rebuilt from the raw
+/// samples over the next blocks without interrupting the audio.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `amount` - Decay fade (0-1, 0 = none, 1 = -60dB at the IR end)
+/// * `trim_seconds` - Length to cut the IR to (0 = no trim)
+#[no_mangle]
+pub extern "C" fn dsp_set_ir_decay(handle: u32, amount: f32, trim_seconds: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_ir_decay(amount, trim_seconds);
+}
+
+/// Set the IR low-cut and high-cut filters
+/// 
+/// Filters the loaded IRs once instead of the wet signal every block, e.g.
+/// to take the rumble out of a muddy IR. The IRs are rebuilt like for
+/// `dsp_set_ir_decay`.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `low_cut_hz` - High-pass cutoff (20-500Hz; 20 or below = off)
+/// * `high_cut_hz` - Low-pass cutoff (1k-20kHz; 20k or above = off)
+#[no_mangle]
+pub extern "C" fn dsp_set_ir_filters(handle: u32, low_cut_hz: f32, high_cut_hz: f32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    convolution::set_ir_filters(low_cut_hz, high_cut_hz);
+}
+
+/// Gain applied to the loaded IR by normalization
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Linear gain (1.0 when normalization is off)
+#[no_mangle]
+pub extern "C" fn dsp_ir_normalization_gain(handle: u32) -> f32 {
+    if !memory::select_engine(handle) {
+        return 0.0;
+    }
+    convolution::normalization_gain()
+}
+
+/// Load source buffer for granular synthesis
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `source_ptr` - Pointer to source sample data
+/// * `source_length` - Number of samples (per channel, at `source_rate`)
+/// * `source_channels` - Number of channels (1 or 2)
+/// * `source_rate` - Sample rate of the source data in Hz. A source at
+///   another rate than the engine is resampled so it plays at its original
+///   pitch (and again after a re-init at another rate); 0 loads it
+///   unconverted.
+/// 
+/// # Returns
+/// 0 = loaded, 1 = truncated to the source region (after any conversion),
+/// 2 = rejected (invalid channel count or handle). Query the accepted
+/// length with `dsp_granular_source_frames`; a length of 0 leaves the
+/// source unloaded.
+#[no_mangle]
+pub extern "C" fn dsp_load_granular_source(
+    handle: u32,
+    source_ptr: *const f32,
+    source_length: u32,
+    source_channels: u32,
+    source_rate: f32,
+) -> u32 {
+    if !memory::select_engine(handle) {
+        return memory::LOAD_REJECTED;
+    }
+    granular::load_source(source_ptr, source_length, source_channels, source_rate)
+}
+
+/// Get the length of the loaded granular source
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Number of sample frames accepted by the last load (at the engine rate),
+/// or 0 if no source is loaded
+#[no_mangle]
+pub extern "C" fn dsp_granular_source_frames(handle: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    granular::source_frames()
+}
+
+/// Get the channel count of the loaded granular source
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// 1 or 2, or 0 if no source is loaded
+#[no_mangle]
+pub extern "C" fn dsp_granular_source_channels(handle: u32) -> u32 {
+    if !memory::select_engine(handle) {
+        return 0;
+    }
+    granular::source_channels()
+}
+
+/// Get the duration of the loaded granular source
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Duration in milliseconds at the engine sample rate
+#[no_mangle]
+pub extern "C" fn dsp_granular_source_duration_ms(handle: u32) -> f32 {
+    if !memory::select_engine(handle) {
+        return 0.0;
+    }
+    granular::source_duration_ms()
+}
+
+/// Load carrier signal for the channel vocoder
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `carrier_ptr` - Pointer to carrier sample data
+/// * `carrier_length` - Number of samples per channel
+/// * `carrier_channels` - Number of channels (1 or 2)
+#[no_mangle]
+pub extern "C" fn dsp_load_vocoder_carrier(
+    handle: u32,
+    carrier_ptr: *const f32,
+    carrier_length: u32,
+    carrier_channels: u32,
+) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    spectral::load_vocoder_carrier(carrier_ptr, carrier_length, carrier_channels);
+}
+
+/// Switch block profiling on or off
+/// 
+/// While enabled, every process call is timed with the host clock (the
+/// `env.dsp_now_ms` import). Enabling starts fresh statistics. Off by
+/// default; disabled it costs one flag check per block.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// * `enabled` - 1 = time blocks, 0 = stop (statistics stay readable)
+#[no_mangle]
+pub extern "C" fn dsp_set_profiling(handle: u32, enabled: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    profiler::set_enabled(enabled != 0);
+}
+
+/// Report the block time statistics of an engine
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to a `ProfileReport` (u32 block count, then last/min/avg/max
+/// block time in ms as f32), valid until the next call; null for an
+/// invalid handle
+#[no_mangle]
+pub extern "C" fn dsp_profile_report(handle: u32) -> *const profiler::ProfileReport {
+    if !memory::select_engine(handle) {
+        return core::ptr::null();
+    }
+    profiler::profile_report()
+}
+
+/// Report region capacities and usage of an engine
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+/// 
+/// # Returns
+/// Pointer to eight u32 values, valid until the next call: granular source
+/// offset, capacity, used; IR offset, capacity, used (offsets in bytes,
+/// capacities and usage in samples); end of the engine layout; bytes
+/// of the engine regions allocated so far. Null for an invalid handle.
+#[no_mangle]
+pub extern "C" fn dsp_memory_report(handle: u32) -> *const memory::MemoryReport {
+    if !memory::select_engine(handle) {
+        return core::ptr::null();
+    }
+    memory::memory_report()
+}
+
+/// Number of 64KB WASM pages taken by the engine regions
+/// 
+/// Each engine slot's region is allocated by the first `dsp_init` that
+/// uses it, so this grows with the number of engines created.
+#[no_mangle]
+pub extern "C" fn dsp_required_memory_pages() -> u32 {
+    memory::required_memory_pages()
+}
+
+/// Enable or disable SIMD buffer operations at runtime
+/// 
+/// Scalar fallbacks run while disabled. Ignored by builds without SIMD,
+/// which always run scalar.
+/// 
+/// # Arguments
+/// * `enabled` - 1 = SIMD (default), 0 = scalar
+#[no_mangle]
+pub extern "C" fn dsp_set_simd_enabled(enabled: u32) {
+    simd_utils::set_simd_enabled(enabled != 0);
+}
+
+/// Check whether buffer operations take the SIMD path
+/// 
+/// # Returns
+/// 1 if SIMD is compiled in and enabled, 0 otherwise (always 0 in builds
+/// without SIMD)
+#[no_mangle]
+pub extern "C" fn dsp_simd_enabled() -> u32 {
+    simd_utils::simd_enabled() as u32
+}
+
+/// Clear the state of every effect (tails, grains, frozen spectra)
+/// 
+/// E.g. when playback stops, so nothing bleeds into the next session. Each
+/// effect fades out over its next block before it's cleared and fades
+/// back in over the block after. Loaded sources, IRs and parameters are
+/// kept.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_reset_all(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    soft_reset::request_all();
+}
+
+/// Release an engine (call on AudioWorklet disposal)
+/// 
+/// The handle may be returned again by a later `dsp_init`.
+/// 
+/// # Arguments
+/// * `handle` - Engine handle from `dsp_init`
+#[no_mangle]
+pub extern "C" fn dsp_cleanup(handle: u32) {
+    if !memory::select_engine(handle) {
+        return;
+    }
+    // Every module's state outlives the engine; don't leave tails, grains
+    // or statistics for the next `dsp_init` on this handle to pick up
+    convolution::reset();
+    spectral::reset();
+    granular::reset();
+    flanger::reset();
+    saturation::reset();
+    resonator::reset();
+    shimmer::reset();
+    diffuser::reset();
+    limiter::reset();
+    noise::reset();
+    profiler::reset();
+    ducking::reset();
+    bypass::reset();
+    soft_reset::reset();
+    effect_switch::reset();
+    memory::cleanup();
+}
+
+// ============================================================================
+// MATH UTILITIES
+// ============================================================================
+// The curves the DSP uses internally, so UI controls can match them exactly
+// (e.g. a fader showing the gain it will apply). Inputs are clamped exactly
+// as in internal use.
+
+/// Linear interpolation between two values
+/// 
+/// # Arguments
+/// * `a` - Start value
+/// * `b` - End value
+/// * `t` - Interpolation factor (0.0 to 1.0, not clamped)
+#[no_mangle]
+pub extern "C" fn dsp_lerp(a: f32, b: f32, t: f32) -> f32 {
+    utils::lerp(a, b, t)
+}
+
+/// Convert decibels to linear amplitude
+#[no_mangle]
+pub extern "C" fn dsp_db_to_linear(db: f32) -> f32 {
+    utils::db_to_linear(db)
+}
+
+/// Convert linear amplitude to decibels (amplitudes below 1e-10 read as
+/// -200dB)
+#[no_mangle]
+pub extern "C" fn dsp_linear_to_db(linear: f32) -> f32 {
+    utils::linear_to_db(linear)
+}
+
+/// Convert a MIDI note number (69 = A4 = 440Hz, fractional notes allowed)
+/// to frequency in Hz
+#[no_mangle]
+pub extern "C" fn dsp_midi_to_freq(note: f32) -> f32 {
+    utils::midi_to_freq(note)
+}
+
+/// Soft clip a value to (-1, 1) using tanh
+#[no_mangle]
+pub extern "C" fn dsp_soft_clip(x: f32) -> f32 {
+    utils::soft_clip(x)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const BLOCK: usize = 128;
+    
+    /// Output of one granular block on an engine, both channels
+    fn render_granular(handle: u32) -> Vec<f32> {
+        dsp_process_granular(handle, 2048, 200.0, 0.0, 0.5, 0.2);
+        (0..2)
+            .flat_map(|channel| {
+                let ptr = dsp_get_output_ptr(handle, channel);
+                // SAFETY: Output buffers hold BLOCK samples for this engine
+                unsafe { std::slice::from_raw_parts(ptr, BLOCK) }.to_vec()
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_math_exports_match_internal_functions() {
+        let inputs = [
+            f32::NEG_INFINITY, -1000.0, -96.0, -6.0, -1.5, -1e-12, 0.0, 1e-12, 1e-10, 0.25, 0.5,
+            1.0, 2.0, 6.0, 69.0, 127.0, 1000.0, f32::INFINITY, f32::NAN,
+        ];
+        for &x in &inputs {
+            assert_eq!(dsp_db_to_linear(x).to_bits(), utils::db_to_linear(x).to_bits(), "db_to_linear({x})");
+            assert_eq!(dsp_linear_to_db(x).to_bits(), utils::linear_to_db(x).to_bits(), "linear_to_db({x})");
+            assert_eq!(dsp_midi_to_freq(x).to_bits(), utils::midi_to_freq(x).to_bits(), "midi_to_freq({x})");
+            assert_eq!(dsp_soft_clip(x).to_bits(), utils::soft_clip(x).to_bits(), "soft_clip({x})");
+            for t in [0.0, 0.3, 1.0, 1.5] {
+                assert_eq!(dsp_lerp(x, 2.0, t).to_bits(), utils::lerp(x, 2.0, t).to_bits(), "lerp({x}, 2, {t})");
+            }
+        }
+        
+        // The clamps and reference points JS relies on
+        assert_eq!(dsp_linear_to_db(0.0), -200.0);
+        assert_eq!(dsp_linear_to_db(-1.0), -200.0);
+        assert!((dsp_db_to_linear(-6.0) - 0.501).abs() < 1e-3);
+        assert!((dsp_midi_to_freq(69.0) - 440.0).abs() < 1e-3);
+        assert!((dsp_midi_to_freq(60.0) - 261.626).abs() < 1e-2);
+        assert!(dsp_soft_clip(100.0) <= 1.0);
+    }
+    
+    #[test]
+    fn test_engines_are_independent() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        let a = dsp_init(48000.0, BLOCK as u32);
+        let b = dsp_init(48000.0, BLOCK as u32);
+        assert_eq!((a, b), (0, 1));
+        let (a, b) = (a as u32, b as u32);
+        
+        // Each engine gets its own regions
+        assert_ne!(dsp_get_input_ptr(a, 0), dsp_get_input_ptr(b, 0));
+        assert_ne!(dsp_get_output_ptr(a, 0), dsp_get_output_ptr(b, 0));
+        assert_ne!(dsp_get_granular_source_ptr(a), dsp_get_granular_source_ptr(b));
+        
+        // A: one second of mono DC, B: a short stereo source of silence
+        // SAFETY: Both lengths fit the granular source region
+        unsafe {
+            std::slice::from_raw_parts_mut(dsp_get_granular_source_ptr(a), 48000).fill(0.5);
+            std::slice::from_raw_parts_mut(dsp_get_granular_source_ptr(b), 2 * 4800).fill(0.0);
+        }
+        assert_eq!(dsp_load_granular_source(a, core::ptr::null(), 48000, 1, 0.0), memory::LOAD_OK);
+        assert_eq!(dsp_load_granular_source(b, core::ptr::null(), 4800, 2, 0.0), memory::LOAD_OK);
+        assert_eq!((dsp_granular_source_frames(a), dsp_granular_source_channels(a)), (48000, 1));
+        assert_eq!((dsp_granular_source_frames(b), dsp_granular_source_channels(b)), (4800, 2));
+        
+        // Interleaved processing: A sounds, B stays silent
+        let mut peak_a = 0.0f32;
+        for _ in 0..50 {
+            peak_a = render_granular(a).iter().fold(peak_a, |peak, x| peak.max(x.abs()));
+            assert!(render_granular(b).iter().all(|&x| x == 0.0), "engine B picked up engine A's source");
+        }
+        assert!(peak_a > 0.1, "engine A produced no output");
+        
+        // Releasing one engine leaves the other running
+        dsp_cleanup(b);
+        assert_eq!(dsp_granular_source_frames(a), 48000);
+        assert!(render_granular(a).iter().any(|&x| x != 0.0));
+        
+        // Re-initializing keeps the handle and its memory; released handles are refused
+        assert_eq!(dsp_reinit(b, 48000.0, BLOCK as u32), -1);
+        let input = dsp_get_input_ptr(a, 0);
+        assert_eq!(dsp_reinit(a, 44100.0, BLOCK as u32), a as i32);
+        assert_eq!(dsp_get_input_ptr(a, 0), input);
+        assert_eq!(memory::sample_rate(), 44100.0);
+        
+        // The pool is bounded and invalid handles are ignored
+        let others: Vec<i32> = (1..memory::MAX_ENGINES).map(|_| dsp_init(48000.0, BLOCK as u32)).collect();
+        assert!(others.iter().all(|&handle| handle > 0));
+        assert_eq!(dsp_init(48000.0, BLOCK as u32), -1);
+        assert!(dsp_get_output_ptr(memory::MAX_ENGINES as u32, 0).is_null());
+        dsp_process_granular(memory::MAX_ENGINES as u32, 2048, 200.0, 0.0, 0.5, 0.2);
+        
+        for handle in others {
+            dsp_cleanup(handle as u32);
+        }
+        memory::select_engine(0);
+    }
+    
+    /// 0.2 amplitude 1kHz sine on a +0.3 DC offset
+    fn offset_sine(i: usize) -> f32 {
+        0.3 + 0.2 * (2.0 * core::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin()
+    }
+    
+    /// Mean of the left output over the second half of two seconds of blocks
+    fn settled_mean(mut render_block: impl FnMut(usize) -> Vec<f32>) -> f32 {
+        let blocks = 2 * 48000 / BLOCK;
+        let tail: Vec<f32> = (0..blocks).flat_map(&mut render_block).skip(blocks / 2 * BLOCK).collect();
+        tail.iter().sum::<f32>() / tail.len() as f32
+    }
+    
+    #[test]
+    fn test_dc_blocker_removes_offset() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+        
+        // Convolution with a unit impulse IR: input straight through
+        // SAFETY: The IR and input regions hold these lengths
+        unsafe { *dsp_get_ir_ptr(handle) = 1.0; }
+        assert_eq!(dsp_load_ir(handle, core::ptr::null(), 1, 1, 0.0), memory::LOAD_OK);
+        let render_convolution = |block: usize| {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    unsafe { *input.add(i) = offset_sine(block * BLOCK + i) };
+                }
+            }
+            dsp_process_convolution(handle, 1.0, 0.0);
+            unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec()
+        };
+        
+        // Granular cloud over the same signal as its source
+        unsafe {
+            let source = std::slice::from_raw_parts_mut(dsp_get_granular_source_ptr(handle), 48000);
+            for (i, x) in source.iter_mut().enumerate() {
+                *x = offset_sine(i);
+            }
+        }
+        assert_eq!(dsp_load_granular_source(handle, core::ptr::null(), 48000, 1, 0.0), memory::LOAD_OK);
+        let render_granular_left = |_| render_granular(handle)[..BLOCK].to_vec();
+        
+        let raw = (settled_mean(render_convolution), settled_mean(render_granular_left));
+        assert!(raw.0 > 0.25 && raw.1 > 0.1, "test signal should carry DC ({raw:?})");
+        
+        dsp_set_dc_blocker(handle, 1);
+        let blocked = (settled_mean(render_convolution), settled_mean(render_granular_left));
+        assert!(blocked.0.abs() < 0.005, "convolution output mean {}", blocked.0);
+        assert!(blocked.1.abs() < 0.005, "granular output mean {}", blocked.1);
+        
+        // The audio itself passes: 0.2 amplitude sine RMS is ~0.141
+        let rms = dsp_get_output_rms(handle, 0);
+        assert!(rms > 0.01, "granular output vanished ({rms})");
+        render_convolution(0);
+        let rms = dsp_get_output_rms(handle, 0);
+        assert!((rms - 0.141).abs() < 0.01, "convolution output RMS {rms}");
+        
+        dsp_cleanup(handle);
+    }
+    
+    /// Input sample the test impulse is at (past the first spectral frame,
+    /// whose window is 0 at its start)
+    const IMPULSE_AT: usize = 4096;
+    
+    /// Left output for an impulse at input sample IMPULSE_AT
+    fn impulse_response(handle: u32, effect: u32) -> Vec<f32> {
+        let mut output = Vec::new();
+        for block in 0..(2 * IMPULSE_AT / BLOCK) {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    let x = if block * BLOCK + i == IMPULSE_AT { 0.5 } else { 0.0 };
+                    unsafe { *input.add(i) = x };
+                }
+            }
+            match effect {
+                EFFECT_GRANULAR => dsp_process_granular(handle, 2048, 200.0, 0.0, 0.5, 0.2),
+                EFFECT_CONVOLUTION => dsp_process_convolution(handle, 1.0, 0.0),
+                _ => dsp_process_spectral(handle, 0.0, 0.0, -1.0, 1.0, 0, 0, 0.0),
+            }
+            output.extend_from_slice(unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) });
+        }
+        output
+    }
+    
+    /// Delay from the impulse to the largest output sample
+    fn peak_delay(output: &[f32]) -> usize {
+        let peak = (0..output.len()).fold(0, |best, i| if output[i].abs() > output[best].abs() { i } else { best });
+        peak - IMPULSE_AT
+    }
+    
+    #[test]
+    fn test_reported_latency_matches_impulse_arrival() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+        
+        // Unit impulse IR: the wet signal is the input, delayed
+        // SAFETY: The IR region holds one sample
+        unsafe { *dsp_get_ir_ptr(handle) = 1.0; }
+        dsp_load_ir(handle, core::ptr::null(), 1, 1, 0.0);
+        
+        for limiter in [false, true] {
+            dsp_set_limiter(handle, 0.0, limiter as u32);
+            let limiter_latency = if limiter { 143 } else { 0 };
+            
+            // Granular renders grains (from an empty source) without delay,
+            // and bypass only goes through the limiter
+            assert_eq!(dsp_get_latency_samples(handle, EFFECT_GRANULAR), limiter_latency);
+            assert_eq!(dsp_get_latency_samples(handle, EFFECT_BYPASS), limiter_latency);
+            
+            let latency = dsp_get_latency_samples(handle, EFFECT_CONVOLUTION);
+            assert_eq!(latency, (256 - BLOCK as u32) + limiter_latency);
+            assert_eq!(peak_delay(&impulse_response(handle, EFFECT_CONVOLUTION)), latency as usize);
+            
+            // The windowed spectral frames put the impulse back together
+            // with its peak at the reported latency
+            spectral::reset();
+            let latency = dsp_get_latency_samples(handle, EFFECT_SPECTRAL);
+            assert_eq!(latency, 2047 + limiter_latency);
+            assert_eq!(peak_delay(&impulse_response(handle, EFFECT_SPECTRAL)), latency as usize);
+        }
+        
+        assert_eq!(dsp_get_latency_samples(handle, 99), 0);
+        
+        dsp_set_limiter(handle, 0.0, 0);
+        dsp_cleanup(handle);
+    }
+    
+    #[test]
+    fn test_disabled_convolution_passes_input_through() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+        
+        // Unit impulse IR, fully wet: the output is the input 128 samples late
+        // SAFETY: The IR region holds one sample
+        unsafe { *dsp_get_ir_ptr(handle) = 1.0; }
+        dsp_load_ir(handle, core::ptr::null(), 1, 1, 0.0);
+        let render = |block: usize, amplitude: f32| {
+            let input: Vec<f32> = (0..BLOCK).map(|i| amplitude * offset_sine(block * BLOCK + i)).collect();
+            for channel in 0..2 {
+                let ptr = dsp_get_input_ptr(handle, channel);
+                unsafe { std::slice::from_raw_parts_mut(ptr, BLOCK).copy_from_slice(&input) };
+            }
+            dsp_process_convolution(handle, 1.0, 0.0);
+            let output = unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec();
+            (input, output)
+        };
+        let mut previous = 0.0;
+        for block in 0..4 {
+            previous = render(block, 1.0).1[BLOCK - 1];
+        }
+        assert!(dsp_convolution_mono_sum_peak(handle) > 0.5);
+        
+        // The toggle crossfades without a jump...
+        dsp_set_effect_enabled(handle, EFFECT_CONVOLUTION, 0);
+        for block in 4..8 {
+            for y in render(block, 1.0).1 {
+                assert!((y - previous).abs() < 0.05, "bypass clicked: {previous} -> {y}");
+                previous = y;
+            }
+        }
+        assert_eq!(dsp_get_latency_samples(handle, EFFECT_CONVOLUTION), 0);
+        
+        // ...then the convolution stops running: the input comes straight
+        // through and the output peak the convolution measures is stale
+        let peak = dsp_convolution_mono_sum_peak(handle);
+        for block in 8..20 {
+            let (input, output) = render(block, 0.5);
+            assert_eq!(input, output);
+        }
+        assert_eq!(dsp_convolution_mono_sum_peak(handle), peak);
+        
+        // The passthrough export does the same without an effect
+        let (input, _) = render(20, 0.25);
+        dsp_process_passthrough(handle);
+        assert_eq!(unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 1), BLOCK) }, &input[..]);
+        
+        dsp_set_effect_enabled(handle, EFFECT_CONVOLUTION, 1);
+        dsp_cleanup(handle);
+    }
+    
+    #[test]
+    fn test_convolution_reset_clears_tail() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        // Safe before init and before an IR is loaded
+        dsp_convolution_reset(memory::MAX_ENGINES as u32);
+        let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+        dsp_convolution_reset(handle);
+        
+        // Half a second of constant IR: an impulse rings for 24000 samples
+        // SAFETY: The IR region holds 24000 samples
+        unsafe { std::slice::from_raw_parts_mut(dsp_get_ir_ptr(handle), 24000).fill(0.01) };
+        dsp_load_ir(handle, core::ptr::null(), 24000, 1, 0.0);
+        let render = |impulse: bool| {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    unsafe { *input.add(i) = if impulse && i == 0 { 1.0 } else { 0.0 } };
+                }
+            }
+            dsp_process_convolution(handle, 1.0, 0.0);
+            unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec()
+        };
+        
+        render(true);
+        let tail: Vec<f32> = (0..8).flat_map(|_| render(false)).collect();
+        assert!(tail.iter().any(|&x| x != 0.0), "no reverb tail to clear");
+        
+        dsp_convolution_reset(handle);
+        assert!((0..50).flat_map(|_| render(false)).all(|x| x == 0.0), "tail survived the reset");
+        
+        // The IR is kept, and cleanup clears the tail for the next engine
+        render(true);
+        assert!((0..8).flat_map(|_| render(false)).any(|x| x != 0.0), "IR lost by the reset");
+        dsp_cleanup(handle);
+        assert_eq!(dsp_init(48000.0, BLOCK as u32), handle as i32);
+        assert!((0..50).flat_map(|_| render(false)).all(|x| x == 0.0), "tail survived cleanup");
+        
+        dsp_cleanup(handle);
+    }
+    
+    #[test]
+    fn test_cleanup_silences_a_reused_handle() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        let fill_input = |handle: u32, block: usize, tone: bool| {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    let x = if tone { offset_sine(block * BLOCK + i) } else { 0.0 };
+                    unsafe { *input.add(i) = x };
+                }
+            }
+        };
+        // Each effect is left ringing, with the limiter holding a lookahead
+        // window and the profiler running; a new engine on the same handle
+        // starts from silence (the granular cloud, granulating the live
+        // input, spawns no grain of its own within 40 blocks at 1 grain/s)
+        type Process = fn(u32, bool);
+        let effects: [(&str, Process); 4] = [
+            ("spectral", |handle, _| dsp_process_spectral(handle, 0.0, 0.0, -1.0, 1.0, 0, 0, 0.0)),
+            ("granular", |handle, ringing| {
+                dsp_process_granular(handle, 4096, if ringing { 200.0 } else { 1.0 }, 0.0, 0.05, 0.0)
+            }),
+            ("flanger", |handle, _| dsp_process_flanger(handle, 0.5, 0.5, 0.9, 0.5)),
+            ("saturation", |handle, _| dsp_process_saturation(handle, 4.0, 0, 0.5)),
+        ];
+        for (name, process) in effects {
+            let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+            dsp_set_limiter(handle, -12.0, 1);
+            dsp_set_saturation_oversampling(handle, 4);
+            dsp_set_profiling(handle, 1);
+            dsp_set_granular_live_mode(handle, 1);
+            for block in 0..40 {
+                fill_input(handle, block, true);
+                process(handle, true);
+            }
+            
+            dsp_cleanup(handle);
+            assert_eq!(dsp_init(48000.0, BLOCK as u32), handle as i32);
+            for block in 0..40 {
+                fill_input(handle, block, false);
+                process(handle, false);
+                let output = unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) };
+                assert!(output.iter().all(|&y| y == 0.0), "{name}: stale audio after cleanup");
+            }
+            assert_eq!(unsafe { (*dsp_profile_report(handle)).blocks }, 0, "{name}: profiler still running");
+            dsp_set_limiter(handle, 0.0, 0);
+            dsp_set_granular_live_mode(handle, 0);
+            dsp_cleanup(handle);
+        }
+        
+        // Noise restarts from the same seeds as an engine never used
+        let render_noise = |handle: u32| {
+            dsp_process_noise(handle, 1, 1.0);
+            unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec()
+        };
+        let a = dsp_init(48000.0, BLOCK as u32) as u32;
+        render_noise(a);
+        dsp_cleanup(a);
+        assert_eq!(dsp_init(48000.0, BLOCK as u32), a as i32);
+        let b = dsp_init(48000.0, BLOCK as u32) as u32;
+        assert_eq!(render_noise(a), render_noise(b));
+        dsp_cleanup(a);
+        dsp_cleanup(b);
+    }
+    
+    #[test]
+    fn test_effect_switch_crossfades_without_a_jump() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        // Fully dry spectral output is the input delayed by 2047 samples, so
+        // a cut between it and the passthrough jumps between two phases of
+        // a 440Hz sine (whose own steps stay below 0.03)
+        let render = |switch_blocks: u32| {
+            let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+            dsp_set_effect_switch_blocks(handle, switch_blocks);
+            let mut output = Vec::new();
+            for block in 0..120 {
+                for channel in 0..2 {
+                    let input = dsp_get_input_ptr(handle, channel);
+                    for i in 0..BLOCK {
+                        let n = (block * BLOCK + i) as f32;
+                        unsafe { *input.add(i) = 0.5 * (2.0 * core::f32::consts::PI * 440.0 * n / 48000.0).sin() };
+                    }
+                }
+                match block {
+                    0 => dsp_set_active_effect(handle, EFFECT_SPECTRAL),
+                    40 => dsp_set_active_effect(handle, EFFECT_BYPASS),
+                    80 => dsp_set_active_effect(handle, EFFECT_CONVOLUTION),
+                    _ => {}
+                }
+                match block {
+                    0..40 => dsp_process_spectral(handle, 0.0, 0.0, -1.0, 0.0, 0, 0, 0.0),
+                    40..80 => dsp_process_passthrough(handle),
+                    _ => dsp_process_convolution(handle, 0.5, 0.0),
+                }
+                output.extend_from_slice(unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) });
+            }
+            dsp_spectral_reset(handle);
+            dsp_cleanup(handle);
+            output
+        };
+        let max_step = |output: &[f32]| output.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f32::max);
+        
+        let faded = render(8);
+        assert!(max_step(&faded) < 0.05, "switch jumped by {}", max_step(&faded));
+        let cut = render(0);
+        assert!(max_step(&cut[40 * BLOCK - 1..40 * BLOCK + 1]) > 0.3);
+        
+        // Once a transition is over the output is the new effect's alone
+        assert_eq!(faded[48 * BLOCK..80 * BLOCK], cut[48 * BLOCK..80 * BLOCK]);
+        assert_eq!(faded[88 * BLOCK..], cut[88 * BLOCK..]);
+    }
+    
+    #[test]
+    fn test_spectral_reset_clears_overlap_and_reports_latency() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        // Safe before init
+        dsp_spectral_reset(memory::MAX_ENGINES as u32);
+        assert_eq!(dsp_spectral_latency(memory::MAX_ENGINES as u32), 0);
+        let handle = dsp_init(48000.0, BLOCK as u32) as u32;
+        let render = |block: usize, tone: bool| {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    let x = if tone { offset_sine(block * BLOCK + i) } else { 0.0 };
+                    unsafe { *input.add(i) = x };
+                }
+            }
+            dsp_process_spectral(handle, 0.0, 0.0, -1.0, 1.0, 0, 0, 0.0);
+            unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec()
+        };
+        
+        for (size, overlap) in [(2048, 4), (1024, 8)] {
+            assert_eq!(dsp_set_spectral_fft(handle, size, overlap), size);
+            assert_eq!(dsp_spectral_latency(handle), size - 1);
+            
+            // Stopped mid-tone, the overlap buffers still hold the frames
+            // in flight; the reset drops them at once
+            for block in 0..40 {
+                render(block, true);
+            }
+            dsp_spectral_reset(handle);
+            assert!((0..40).flat_map(|block| render(block, false)).all(|y| y == 0.0), "{size}: stale audio after the reset");
+        }
+        
+        dsp_set_spectral_fft(handle, 2048, 4);
+        dsp_cleanup(handle);
+    }
+    
+    #[test]
+    fn test_soft_reset_fades_to_silence() {
+        let _guard = memory::test_lock();
+        for handle in 0..memory::MAX_ENGINES as u32 {
+            dsp_cleanup(handle);
+        }
+        
+        // Two identical engines; only the first is reset, so the second
+        // shows what the reset block would have been
+        let handles = [dsp_init(48000.0, BLOCK as u32) as u32, dsp_init(48000.0, BLOCK as u32) as u32];
+        for handle in handles {
+            // SAFETY: The IR region holds 24000 samples
+            unsafe { std::slice::from_raw_parts_mut(dsp_get_ir_ptr(handle), 24000).fill(0.01) };
+            dsp_load_ir(handle, core::ptr::null(), 24000, 1, 0.0);
+        }
+        let render = |handle: u32, block: usize, tone: bool, process: &dyn Fn(u32, usize)| {
+            for channel in 0..2 {
+                let input = dsp_get_input_ptr(handle, channel);
+                for i in 0..BLOCK {
+                    let x = if tone { offset_sine(block * BLOCK + i) } else { 0.0 };
+                    unsafe { *input.add(i) = x };
+                }
+            }
+            process(handle, block);
+            unsafe { std::slice::from_raw_parts(dsp_get_output_ptr(handle, 0), BLOCK) }.to_vec()
+        };
+        
+        let check = |name: &str, process: &dyn Fn(u32, usize), reset: extern "C" fn(u32)| {
+            // Start both engines from the same (cleared) state
+            for handle in handles {
+                dsp_reset_all(handle);
+                render(handle, 0, false, process);
+                render(handle, 0, false, process);
+                for block in 0..40 {
+                    render(handle, block, true, process);
+                }
+            }
+            reset(handles[0]);
+            
+            // The next block fades out from where the output stood...
+            let faded = render(handles[0], 40, false, process);
+            let open = render(handles[1], 40, false, process);
+            assert!(open.iter().any(|x| x.abs() > 0.01), "{name}: nothing to reset");
+            for (i, (&y, &x)) in faded.iter().zip(&open).enumerate() {
+                let gain = 1.0 - (i + 1) as f32 / BLOCK as f32;
+                assert!((y - x * gain).abs() < 1e-6, "{name}: sample {i} not faded ({y} vs {x})");
+            }
+            
+            // ...and from then on silence stays silent
+            for block in 41..100 {
+                assert!(render(handles[0], block, false, process).iter().all(|&y| y == 0.0), "{name}: tail left in block {block}");
+            }
+            assert!(render(handles[1], 41, false, process).iter().any(|&y| y != 0.0), "{name}: other engine reset");
+        };
+        let convolution = |handle: u32, _| dsp_process_convolution(handle, 1.0, 0.0);
+        check("convolution", &convolution, dsp_reset_convolution);
+        check("all", &convolution, dsp_reset_all);
+        
+        // The freeze engages halfway through the tone and holds it over the
+        // silence; after the reset it freezes silence
+        let freeze = |handle: u32, block: usize| {
+            let amount = if block < 24 { 0.0 } else { 1.0 };
+            dsp_process_spectral(handle, amount, 0.0, -1.0, 1.0, 0, 0, 0.0)
+        };
+        check("spectral", &freeze, dsp_reset_spectral);
+        
+        for handle in handles {
+            dsp_cleanup(handle);
+        }
+    }
 }
@@ -0,0 +1,347 @@
+//! Output Limiter
+//!
+//! Lookahead peak limiter on the final output buffers. Every process export
+//! runs it last, so nothing above the threshold reaches JavaScript.
+//!
+//! # Algorithm
+//! 1. Detection: `find_peak` over the stereo peaks of the last LOOKAHEAD
+//!    samples gives the gain that keeps all of them at the threshold
+//! 2. Attack: that gain is averaged over the lookahead window, so gain
+//!    reduction ramps in over the lookahead and is complete when the peak
+//!    leaves the delay line
+//! 3. Release: recovery towards unity is smoothed with a one-pole
+//! 4. Output: the input delayed by LOOKAHEAD - 1 samples, times the gain
+//!
+//! Every gain in the averaging window was computed from a peak window that
+//! contains the delayed sample, so the average never exceeds the gain that
+//! sample needs: peaks are held at the threshold without clipping.
+//!
+//! # Latency
+//! While enabled the output is delayed by `latency_samples()`. Disabled,
+//! the limiter leaves the buffers untouched (no delay, bit-exact).
+
+use crate::memory;
+use crate::simd_utils;
+use crate::utils;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Lookahead (and attack) time in milliseconds
+const LOOKAHEAD_MS: f32 = 3.0;
+
+/// Release time in milliseconds
+const RELEASE_MS: f32 = 80.0;
+
+/// Lookahead window capacity: LOOKAHEAD_MS at the highest supported
+/// sample rate (192kHz)
+const MAX_LOOKAHEAD: usize = (LOOKAHEAD_MS as usize) * 192;
+
+/// Threshold range in dBFS
+const MIN_THRESHOLD_DB: f32 = -24.0;
+const MAX_THRESHOLD_DB: f32 = 0.0;
+
+// ============================================================================
+// LIMITER STATE
+// ============================================================================
+
+/// Stereo-linked lookahead limiter
+struct Limiter {
+    /// Whether the limiter processes the output
+    enabled: bool,
+    /// Threshold (linear)
+    threshold: f32,
+    /// Lookahead window length in samples (0 = not primed yet)
+    lookahead: usize,
+    /// Delayed input (ring of `lookahead` samples per channel)
+    delay_l: [f32; MAX_LOOKAHEAD],
+    delay_r: [f32; MAX_LOOKAHEAD],
+    /// Stereo peak of each sample in the lookahead window
+    peaks: [f32; MAX_LOOKAHEAD],
+    /// Gain each of those samples asked for, and their sum (attack average)
+    gains: [f32; MAX_LOOKAHEAD],
+    gain_sum: f32,
+    /// Ring position shared by all windows
+    pos: usize,
+    /// Smoothed gain applied to the output
+    gain: f32,
+    /// Lowest gain of the last processed block (for metering)
+    block_min_gain: f32,
+}
+
+impl Limiter {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            threshold: 1.0,
+            lookahead: 0,
+            delay_l: [0.0; MAX_LOOKAHEAD],
+            delay_r: [0.0; MAX_LOOKAHEAD],
+            peaks: [0.0; MAX_LOOKAHEAD],
+            gains: [1.0; MAX_LOOKAHEAD],
+            gain_sum: 0.0,
+            pos: 0,
+            gain: 1.0,
+            block_min_gain: 1.0,
+        }
+    }
+    
+    /// Start over with an empty delay line and no gain reduction
+    fn prime(&mut self, lookahead: usize) {
+        self.lookahead = lookahead;
+        self.delay_l.fill(0.0);
+        self.delay_r.fill(0.0);
+        self.peaks.fill(0.0);
+        self.gains.fill(1.0);
+        self.gain_sum = lookahead as f32;
+        self.pos = 0;
+        self.gain = 1.0;
+        self.block_min_gain = 1.0;
+    }
+    
+    /// Limit a stereo block in place
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], sample_rate: f32) {
+        let lookahead = lookahead_samples(sample_rate);
+        if lookahead != self.lookahead {
+            self.prime(lookahead);
+        }
+        let release = 1.0 - libm::expf(-1000.0 / (RELEASE_MS * sample_rate));
+        let mut block_min_gain = 1.0f32;
+        
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let pos = self.pos;
+            
+            // Gain that keeps every sample in the window at the threshold
+            self.peaks[pos] = l.abs().max(r.abs());
+            let window_peak = simd_utils::find_peak(&self.peaks[..lookahead]);
+            let held = if window_peak > self.threshold { self.threshold / window_peak } else { 1.0 };
+            
+            // Attack: average over the window; release: smoothed recovery
+            self.gain_sum += held - self.gains[pos];
+            self.gains[pos] = held;
+            let target = self.gain_sum / lookahead as f32;
+            self.gain = if target < self.gain {
+                target
+            } else {
+                self.gain + (target - self.gain) * release
+            };
+            block_min_gain = block_min_gain.min(self.gain);
+            
+            // Output the oldest sample in the window
+            self.delay_l[pos] = *l;
+            self.delay_r[pos] = *r;
+            let oldest = (pos + 1) % lookahead;
+            *l = self.delay_l[oldest] * self.gain;
+            *r = self.delay_r[oldest] * self.gain;
+            
+            self.pos = oldest;
+            if oldest == 0 {
+                // Re-sum once per window so rounding can't accumulate
+                self.gain_sum = self.gains[..lookahead].iter().sum();
+            }
+        }
+        self.block_min_gain = block_min_gain;
+    }
+}
+
+/// Limiter state of every engine in the pool
+static mut STATES: [Limiter; memory::MAX_ENGINES] =
+    [const { Limiter::new() }; memory::MAX_ENGINES];
+
+/// Limiter state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Limiter {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+/// Lookahead window length at a sample rate
+fn lookahead_samples(sample_rate: f32) -> usize {
+    ((LOOKAHEAD_MS * 0.001 * sample_rate).round() as usize).clamp(1, MAX_LOOKAHEAD)
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Limit the selected engine's output buffers in place
+/// 
+/// Does nothing while the limiter is disabled.
+pub fn process_output() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the output buffers don't
+        // overlap the limiter state
+        let st = state();
+        if !(*st).enabled || !memory::is_initialized() {
+            return;
+        }
+        let output_l = memory::output_slice_mut(0);
+        let output_r = memory::output_slice_mut(1);
+        (*st).process(output_l, output_r, memory::sample_rate());
+    }
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+/// Configure the output limiter
+/// 
+/// Enabling starts from an empty lookahead window; disabling makes the
+/// limiter a passthrough.
+/// 
+/// # Arguments
+/// * `threshold_db` - Output ceiling in dBFS (-24 to 0)
+/// * `enabled` - Whether the limiter processes the output
+pub fn set_limiter(threshold_db: f32, enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        (*st).threshold = utils::db_to_linear(threshold_db.clamp(MIN_THRESHOLD_DB, MAX_THRESHOLD_DB));
+        if enabled && !(*st).enabled {
+            (*st).lookahead = 0;
+        }
+        (*st).enabled = enabled;
+        (*st).block_min_gain = 1.0;
+    }
+}
+
+/// Gain reduction of the last processed block in dB (0 = none, -6 = 6dB)
+pub fn gain_reduction_db() -> f32 {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = state();
+        if (*st).enabled { utils::linear_to_db((*st).block_min_gain) } else { 0.0 }
+    }
+}
+
+/// Delay the limiter adds to the output, in samples (0 when disabled)
+pub fn latency_samples() -> u32 {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        if (*state()).enabled { lookahead_samples(memory::sample_rate()) as u32 - 1 } else { 0 }
+    }
+}
+
+/// Reset the limiter state
+/// 
+/// Clears the lookahead window; the threshold and enable flag are kept.
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).lookahead = 0;
+        (*state()).block_min_gain = 1.0;
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    const BLOCK: usize = 128;
+    
+    /// Run `input` through the limiter block by block as if an effect had
+    /// written it to the output buffers; returns the left channel
+    fn render(input: &[f32]) -> Vec<f32> {
+        let mut out = Vec::with_capacity(input.len());
+        for block in input.chunks(BLOCK) {
+            unsafe {
+                memory::output_slice_mut(0).copy_from_slice(block);
+                memory::output_slice_mut(1).copy_from_slice(block);
+            }
+            process_output();
+            unsafe {
+                assert_eq!(memory::output_slice_mut(0), memory::output_slice_mut(1));
+                out.extend_from_slice(memory::output_slice_mut(0));
+            }
+        }
+        out
+    }
+    
+    /// 1kHz sine at 0.25 with a +6dBFS (amplitude 2) burst in the middle
+    fn burst_signal() -> Vec<f32> {
+        (0..BLOCK * 300)
+            .map(|i| {
+                let amplitude = if (BLOCK * 80..BLOCK * 120).contains(&i) { 2.0 } else { 0.25 };
+                amplitude * (2.0 * core::f32::consts::PI * 1000.0 * i as f32 / SAMPLE_RATE).sin()
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_burst_limited_smoothly_below_threshold() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        let input = burst_signal();
+        
+        // Disabled: a true passthrough with no delay
+        set_limiter(-1.0, false);
+        assert_eq!(latency_samples(), 0);
+        assert_eq!(render(&input), input);
+        assert_eq!(gain_reduction_db(), 0.0);
+        
+        set_limiter(-1.0, true);
+        let delay = latency_samples() as usize;
+        assert_eq!(delay, 143);
+        let output = render(&input);
+        let threshold = utils::db_to_linear(-1.0);
+        
+        let peak = simd_utils::find_peak(&output);
+        assert!(peak <= threshold * 1.0001, "output peak {peak} above threshold {threshold}");
+        assert!(peak > threshold * 0.95, "burst squashed well below the threshold ({peak})");
+        
+        // The output is the delayed input times a smooth gain: no clipping
+        let (mut previous_gain, mut previous_n) = (1.0f32, delay);
+        for n in delay..output.len() {
+            let x = input[n - delay];
+            if x.abs() < 0.1 {
+                continue;
+            }
+            let gain = output[n] / x;
+            assert!(gain > 0.0 && gain <= 1.0 + 1e-6, "gain {gain} at {n}");
+            let step = (gain - previous_gain).abs() / (n - previous_n).max(1) as f32;
+            assert!(step < 0.01, "gain jumped {previous_gain} -> {gain} at {n}");
+            (previous_gain, previous_n) = (gain, n);
+        }
+        
+        // Before the burst the input passes unchanged, delayed by exactly
+        // the reported latency
+        for n in delay..BLOCK * 80 {
+            assert_eq!(output[n], input[n - delay]);
+        }
+        
+        set_limiter(-1.0, false);
+    }
+    
+    #[test]
+    fn test_gain_reduction_metering() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_limiter(-1.0, true);
+        
+        let input = burst_signal();
+        let mut reductions = Vec::new();
+        for block in input.chunks(BLOCK) {
+            render(block);
+            reductions.push(gain_reduction_db());
+        }
+        
+        // About 7dB while the burst plays (+6dBFS into -1dBFS), none before
+        assert!(reductions[..80].iter().all(|&db| db == 0.0), "{:?}", &reductions[..80]);
+        let deepest = reductions.iter().fold(0.0f32, |min, &db| min.min(db));
+        assert!((deepest + 7.0).abs() < 0.1, "deepest reduction {deepest} dB");
+        assert!(reductions[299] > -0.1, "limiter didn't release ({} dB)", reductions[299]);
+        
+        set_limiter(-1.0, false);
+        assert_eq!(gain_reduction_db(), 0.0);
+    }
+}
@@ -8,16 +8,30 @@
 //! # Memory Layout
 //! ```text
 //! 0x0000: Engine State (256 bytes)
-//! 0x0100: Input Buffer L (512 samples = 2KB)
-//! 0x0300: Input Buffer R (512 samples = 2KB)
-//! 0x0500: Output Buffer L (512 samples = 2KB)
-//! 0x0700: Output Buffer R (512 samples = 2KB)
-//! 0x0900: Work Buffer 1 (512 samples = 2KB)
-//! 0x1100: Work Buffer 2 (512 samples = 2KB)
-//! 0x1900: Granular Source Buffer (up to 3.5MB)
+//! 0x0100: Input Buffer L (2048 samples = 8KB)
+//! 0x2100: Input Buffer R (2048 samples = 8KB)
+//! 0x4100: Output Buffer L (2048 samples = 8KB)
+//! 0x6100: Output Buffer R (2048 samples = 8KB)
+//! 0x8100: Work Buffer 1 (2048 samples = 8KB)
+//! 0xA100: Work Buffer 2 (2048 samples = 8KB)
+//! 0xC100: Granular Source Buffer (up to 3.5MB)
 //! 0x380000: IR Buffer (up to 1.9MB)
 //! 0x560000: FFT Buffers
+//! 0x570000: Vocoder Carrier Buffer (up to 1.9MB)
+//! 0x750000: Live History Ring (4s stereo @ 48kHz = 1.5MB)
+//! 0x8C7000: IR Slot B Buffer (up to 1.9MB)
+//! 0xA9BC00: Sidechain Buffer (2048 samples = 8KB)
+//! 0xA9DC00: Spectral Mask Buffer (4097 bins = 16KB)
 //! ```
+//!
+//! # Engine Instances
+//! Up to MAX_ENGINES engines each own a copy of the layout above. The
+//! offsets are relative to the engine's region, a zeroed ENGINE_REGION_SIZE
+//! block allocated from the heap the first time the engine is created, so
+//! memory grows one engine at a time. Regions are kept after cleanup and
+//! reused by the next engine created in the same slot.
+//! Exports select an engine by handle before touching any state; always
+//! resolve offsets through `region_ptr` rather than casting them.
 
 use std::ptr;
 use core::ptr::{addr_of, addr_of_mut};
@@ -31,27 +45,30 @@ pub const STATE_OFFSET: usize = 0x0000;
 /// Size of engine state struct
 pub const STATE_SIZE: usize = 256;
 
+/// Maximum buffer size in samples
+/// 
+/// Covers the 128-sample worklet quantum as well as hosts that deliver
+/// larger blocks (offline rendering, some worklet configurations).
+pub const MAX_BUFFER_SIZE: usize = 2048;
+/// Buffer size in bytes (f32 = 4 bytes)
+pub const BUFFER_BYTES: usize = MAX_BUFFER_SIZE * 4;
+
 /// Offset for input buffer left channel
 pub const INPUT_L_OFFSET: usize = 0x0100;
 /// Offset for input buffer right channel
-pub const INPUT_R_OFFSET: usize = 0x0300;
+pub const INPUT_R_OFFSET: usize = INPUT_L_OFFSET + BUFFER_BYTES;
 /// Offset for output buffer left channel  
-pub const OUTPUT_L_OFFSET: usize = 0x0500;
+pub const OUTPUT_L_OFFSET: usize = INPUT_R_OFFSET + BUFFER_BYTES;
 /// Offset for output buffer right channel
-pub const OUTPUT_R_OFFSET: usize = 0x0700;
-
-/// Maximum buffer size in samples
-pub const MAX_BUFFER_SIZE: usize = 512;
-/// Buffer size in bytes (f32 = 4 bytes)
-pub const BUFFER_BYTES: usize = MAX_BUFFER_SIZE * 4;
+pub const OUTPUT_R_OFFSET: usize = OUTPUT_L_OFFSET + BUFFER_BYTES;
 
 /// Offset for work buffers
-pub const WORK1_OFFSET: usize = 0x0900;
-pub const WORK2_OFFSET: usize = 0x1100;
-pub const WORK_BUFFER_SIZE: usize = 512;
+pub const WORK1_OFFSET: usize = OUTPUT_R_OFFSET + BUFFER_BYTES;
+pub const WORK2_OFFSET: usize = WORK1_OFFSET + BUFFER_BYTES;
+pub const WORK_BUFFER_SIZE: usize = MAX_BUFFER_SIZE;
 
 /// Offset for granular source buffer
-pub const GRANULAR_SOURCE_OFFSET: usize = 0x1900;
+pub const GRANULAR_SOURCE_OFFSET: usize = WORK2_OFFSET + BUFFER_BYTES;
 /// Maximum granular source: 10 seconds @ 44.1kHz stereo
 pub const MAX_GRANULAR_SOURCE_SAMPLES: usize = 44100 * 10 * 2;
 
@@ -65,6 +82,112 @@ pub const FFT_OFFSET: usize = 0x560000;
 /// FFT size
 pub const FFT_SIZE: usize = 4096;
 
+/// Offset for vocoder carrier buffer
+pub const VOCODER_CARRIER_OFFSET: usize = 0x570000;
+/// Maximum vocoder carrier: 10 seconds @ 48kHz mono (or 5s stereo)
+pub const MAX_VOCODER_CARRIER_SAMPLES: usize = 48000 * 10;
+
+/// Offset for the live input history ring (interleaved stereo)
+pub const LIVE_HISTORY_OFFSET: usize = 0x750000;
+/// Live history capacity: 4 seconds @ 48kHz (frames, 2 samples each)
+pub const MAX_LIVE_HISTORY_FRAMES: usize = 48000 * 4;
+
+/// Offset for the second impulse response buffer (convolution slot B,
+/// same capacity as the IR buffer)
+pub const IR_B_OFFSET: usize = LIVE_HISTORY_OFFSET + MAX_LIVE_HISTORY_FRAMES * 2 * 4;
+
+/// Offset for the ducking sidechain buffer (one block of mono samples)
+pub const SIDECHAIN_OFFSET: usize = IR_B_OFFSET + MAX_IR_SAMPLES * 4;
+
+/// Offset for the spectral freeze bin mask (one gain per bin)
+pub const SPECTRAL_MASK_OFFSET: usize = SIDECHAIN_OFFSET + BUFFER_BYTES;
+/// Spectral mask capacity: the bins of an 8192-point FFT
+pub const MAX_SPECTRAL_MASK_BINS: usize = 8192 / 2 + 1;
+
+/// End of the memory layout (first byte past the last region)
+pub const MEMORY_END: usize = SPECTRAL_MASK_OFFSET + MAX_SPECTRAL_MASK_BINS * 4;
+
+// Fixed-offset regions must not run into each other
+const _: () = assert!(STATE_OFFSET + STATE_SIZE <= INPUT_L_OFFSET);
+const _: () = assert!(GRANULAR_SOURCE_OFFSET + MAX_GRANULAR_SOURCE_SAMPLES * 4 <= IR_OFFSET);
+const _: () = assert!(IR_OFFSET + MAX_IR_SAMPLES * 4 <= FFT_OFFSET);
+const _: () = assert!(VOCODER_CARRIER_OFFSET + MAX_VOCODER_CARRIER_SAMPLES * 4 <= LIVE_HISTORY_OFFSET);
+
+/// Maximum number of independent engine instances
+pub const MAX_ENGINES: usize = 4;
+/// Size of one engine's region (the layout rounded up to 64KB pages)
+pub const ENGINE_REGION_SIZE: usize = (MEMORY_END + 0xFFFF) & !0xFFFF;
+
+// ============================================================================
+// REGION ADDRESSING
+// ============================================================================
+
+/// Engine whose layout `region_ptr` currently resolves into
+static mut CURRENT_ENGINE: usize = 0;
+
+/// Base address of each engine's region (0 until the engine is first created)
+static mut REGIONS: [usize; MAX_ENGINES] = [0; MAX_ENGINES];
+
+/// Select the engine that subsequent calls operate on
+/// 
+/// # Arguments
+/// * `handle` - Engine handle returned by `create_engine`
+/// 
+/// # Returns
+/// false (leaving the selection unchanged) if the handle is out of range or
+/// was never created
+#[inline]
+pub fn select_engine(handle: u32) -> bool {
+    let index = handle as usize;
+    // SAFETY: Single-threaded WASM context
+    if index >= MAX_ENGINES || unsafe { (*addr_of!(REGIONS))[index] } == 0 {
+        return false;
+    }
+    set_current_engine(index);
+    true
+}
+
+/// Select an engine slot whether or not its region exists yet
+#[inline]
+fn set_current_engine(index: usize) {
+    // SAFETY: Single-threaded WASM context
+    unsafe { *addr_of_mut!(CURRENT_ENGINE) = index; }
+}
+
+/// Index of the currently selected engine
+#[inline]
+pub fn current_engine() -> usize {
+    // SAFETY: Single-threaded WASM context
+    unsafe { *addr_of!(CURRENT_ENGINE) }
+}
+
+/// Resolve a layout offset to a raw pointer in the selected engine's region
+#[inline]
+pub fn region_ptr(offset: usize) -> *mut u8 {
+    // SAFETY: Single-threaded WASM context
+    unsafe { ((*addr_of!(REGIONS))[current_engine()] + offset) as *mut u8 }
+}
+
+/// Allocate the selected engine's region if it doesn't have one yet
+/// 
+/// # Returns
+/// false if the allocator could not provide the region
+fn reserve_engine_memory() -> bool {
+    // SAFETY: Single-threaded WASM context
+    let region = unsafe { &mut (*addr_of_mut!(REGIONS))[current_engine()] };
+    if *region == 0 {
+        let layout = std::alloc::Layout::from_size_align(ENGINE_REGION_SIZE, 16)
+            .expect("memory layout size is valid");
+        // SAFETY: Layout has non-zero size; the region is never freed
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return false;
+        }
+        *region = ptr as usize;
+    }
+    true
+}
+
 // ============================================================================
 // ENGINE STATE
 // ============================================================================
@@ -86,12 +209,20 @@ pub struct EngineState {
     pub granular_source_len: u32,
     /// IR length in samples
     pub ir_len: u32,
+    /// Slot B IR length in samples
+    pub ir_b_len: u32,
     /// Reserved for future use
-    _reserved: [u8; 232],
+    _reserved: [u8; 228],
 }
 
-/// Global engine state pointer
-static mut ENGINE: *mut EngineState = ptr::null_mut();
+/// Engine state pointers, null for engines that aren't initialized
+static mut ENGINES: [*mut EngineState; MAX_ENGINES] = [ptr::null_mut(); MAX_ENGINES];
+
+/// State pointer of the selected engine (null if not initialized)
+#[inline]
+unsafe fn engine() -> *mut EngineState {
+    (*addr_of!(ENGINES))[current_engine()]
+}
 
 /// Flag: Engine is initialized
 pub const FLAG_INITIALIZED: u32 = 1 << 0;
@@ -99,56 +230,93 @@ pub const FLAG_INITIALIZED: u32 = 1 << 0;
 pub const FLAG_GRANULAR_READY: u32 = 1 << 1;
 /// Flag: IR loaded
 pub const FLAG_IR_READY: u32 = 1 << 2;
+/// Flag: DC blocker on the granular and convolution outputs
+pub const FLAG_DC_BLOCKER: u32 = 1 << 3;
+
+/// Load status: loaded in full
+pub const LOAD_OK: u32 = 0;
+/// Load status: truncated to fit the region
+pub const LOAD_TRUNCATED: u32 = 1;
+/// Load status: rejected (invalid channel count or engine), previous data kept
+pub const LOAD_REJECTED: u32 = 2;
 
 // ============================================================================
 // INITIALIZATION
 // ============================================================================
 
-/// Initialize the DSP engine
+/// Create a new engine instance
+/// 
+/// Takes the first free slot of the pool, selects it and initializes it,
+/// allocating the slot's region on first use. The first engine created
+/// gets handle 0.
 /// 
 /// # Arguments
 /// * `sample_rate` - Audio sample rate (e.g., 44100.0)
 /// * `buffer_size` - Samples per process block (e.g., 128)
 /// 
 /// # Returns
-/// Pointer to engine state (as u32 offset), or 0 on failure
-/// 
-/// # Safety
-/// Must be called before any other DSP functions.
-/// Must only be called once per engine lifetime.
+/// Engine handle, or -1 if the pool is full or the parameters are invalid
 /// 
 /// # Example (from JS)
 /// ```javascript
-/// const statePtr = exports.dsp_init(44100.0, 128);
-/// if (statePtr === 0) throw new Error('Init failed');
+/// const handle = exports.dsp_init(44100.0, 128);
+/// if (handle < 0) throw new Error('Init failed');
 /// ```
-pub fn init_engine(sample_rate: f32, buffer_size: u32) -> u32 {
+pub fn create_engine(sample_rate: f32, buffer_size: u32) -> i32 {
+    // SAFETY: Single-threaded WASM context
+    let free = unsafe { (*addr_of!(ENGINES)).iter().position(|engine| engine.is_null()) };
+    let Some(handle) = free else {
+        return -1;
+    };
+    set_current_engine(handle);
+    if init_engine(sample_rate, buffer_size) {
+        handle as i32
+    } else {
+        -1
+    }
+}
+
+/// Initialize the selected engine
+/// 
+/// Re-initializing an engine that is already running keeps its slot and
+/// its region.
+/// 
+/// # Arguments
+/// * `sample_rate` - Audio sample rate (e.g., 44100.0)
+/// * `buffer_size` - Samples per process block (e.g., 128)
+/// 
+/// # Returns
+/// true on success, false if the parameters are invalid or memory for
+/// the engine's layout is unavailable
+pub fn init_engine(sample_rate: f32, buffer_size: u32) -> bool {
     unsafe {
         // Validate inputs
         // Sample rate must be reasonable (8kHz to 192kHz)
         if sample_rate < 8000.0 || sample_rate > 192000.0 {
-            return 0;
+            return false;
         }
         // Buffer size must be power-of-two-ish and within limits
         if buffer_size < 32 || buffer_size > MAX_BUFFER_SIZE as u32 {
-            return 0;
+            return false;
         }
-
-        // Get pointer to state at fixed offset
-        // In WASM, memory starts at 0 and we use fixed offsets
+        if !reserve_engine_memory() {
+            return false;
+        }
+        
+        // Get pointer to state at the engine's fixed offset
         // SAFETY: Single-threaded WASM context, using raw pointer for Rust 2024
-        let engine_ptr = addr_of_mut!(ENGINE);
-        *engine_ptr = STATE_OFFSET as *mut EngineState;
+        let engine = region_ptr(STATE_OFFSET) as *mut EngineState;
+        (*addr_of_mut!(ENGINES))[current_engine()] = engine;
         
         // Initialize state struct
-        let engine = *engine_ptr;
         (*engine).sample_rate = sample_rate;
         (*engine).buffer_size = buffer_size;
         (*engine).flags = FLAG_INITIALIZED;
         (*engine).granular_source_len = 0;
         (*engine).ir_len = 0;
-        (*engine)._reserved = [0u8; 232];
-
+        (*engine).ir_b_len = 0;
+        (*engine)._reserved = [0u8; 228];
+        
         // Zero all I/O buffers to prevent garbage on first process
         zero_buffer(INPUT_L_OFFSET, BUFFER_BYTES);
         zero_buffer(INPUT_R_OFFSET, BUFFER_BYTES);
@@ -156,9 +324,8 @@ pub fn init_engine(sample_rate: f32, buffer_size: u32) -> u32 {
         zero_buffer(OUTPUT_R_OFFSET, BUFFER_BYTES);
         zero_buffer(WORK1_OFFSET, WORK_BUFFER_SIZE * 4);
         zero_buffer(WORK2_OFFSET, WORK_BUFFER_SIZE * 4);
-
-        // Return state pointer as success indicator
-        STATE_OFFSET as u32
+        
+        true
     }
 }
 
@@ -168,7 +335,7 @@ pub fn init_engine(sample_rate: f32, buffer_size: u32) -> u32 {
 /// Caller must ensure offset and size are valid memory regions.
 #[inline]
 unsafe fn zero_buffer(offset: usize, size: usize) {
-    ptr::write_bytes(offset as *mut u8, 0, size);
+    ptr::write_bytes(region_ptr(offset), 0, size);
 }
 
 // ============================================================================
@@ -192,8 +359,8 @@ unsafe fn zero_buffer(offset: usize, size: usize) {
 #[inline]
 pub fn get_input_buffer(channel: u32) -> *mut f32 {
     match channel {
-        0 => INPUT_L_OFFSET as *mut f32,
-        1 => INPUT_R_OFFSET as *mut f32,
+        0 => region_ptr(INPUT_L_OFFSET) as *mut f32,
+        1 => region_ptr(INPUT_R_OFFSET) as *mut f32,
         _ => ptr::null_mut(),
     }
 }
@@ -214,8 +381,8 @@ pub fn get_input_buffer(channel: u32) -> *mut f32 {
 #[inline]
 pub fn get_output_buffer(channel: u32) -> *const f32 {
     match channel {
-        0 => OUTPUT_L_OFFSET as *const f32,
-        1 => OUTPUT_R_OFFSET as *const f32,
+        0 => region_ptr(OUTPUT_L_OFFSET) as *const f32,
+        1 => region_ptr(OUTPUT_R_OFFSET) as *const f32,
         _ => ptr::null(),
     }
 }
@@ -230,7 +397,7 @@ pub fn get_output_buffer(channel: u32) -> *const f32 {
 #[inline]
 pub unsafe fn input_slice(channel: u32) -> &'static [f32] {
     let ptr = get_input_buffer(channel);
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     let len = (*engine).buffer_size as usize;
     std::slice::from_raw_parts(ptr, len)
 }
@@ -242,7 +409,7 @@ pub unsafe fn input_slice(channel: u32) -> &'static [f32] {
 #[inline]
 pub unsafe fn output_slice_mut(channel: u32) -> &'static mut [f32] {
     let ptr = get_output_buffer(channel) as *mut f32;
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     let len = (*engine).buffer_size as usize;
     std::slice::from_raw_parts_mut(ptr, len)
 }
@@ -253,7 +420,7 @@ pub unsafe fn output_slice_mut(channel: u32) -> &'static mut [f32] {
 /// Engine must be initialized. Work buffer has fixed size (WORK_BUFFER_SIZE).
 #[inline]
 pub unsafe fn work_buffer_1() -> &'static mut [f32] {
-    std::slice::from_raw_parts_mut(WORK1_OFFSET as *mut f32, WORK_BUFFER_SIZE)
+    std::slice::from_raw_parts_mut(region_ptr(WORK1_OFFSET) as *mut f32, WORK_BUFFER_SIZE)
 }
 
 /// Get work buffer 2 as mutable slice
@@ -262,7 +429,7 @@ pub unsafe fn work_buffer_1() -> &'static mut [f32] {
 /// Engine must be initialized. Work buffer has fixed size (WORK_BUFFER_SIZE).
 #[inline]
 pub unsafe fn work_buffer_2() -> &'static mut [f32] {
-    std::slice::from_raw_parts_mut(WORK2_OFFSET as *mut f32, WORK_BUFFER_SIZE)
+    std::slice::from_raw_parts_mut(region_ptr(WORK2_OFFSET) as *mut f32, WORK_BUFFER_SIZE)
 }
 
 // ============================================================================
@@ -275,21 +442,26 @@ pub unsafe fn work_buffer_2() -> &'static mut [f32] {
 /// Mutable pointer to the granular source buffer start
 #[inline]
 pub fn get_granular_source_ptr() -> *mut f32 {
-    GRANULAR_SOURCE_OFFSET as *mut f32
+    region_ptr(GRANULAR_SOURCE_OFFSET) as *mut f32
 }
 
 /// Set granular source length after loading
 /// 
 /// # Arguments
-/// * `length` - Number of samples loaded
+/// * `length` - Number of samples loaded (all channels); 0 marks the
+///   region as unloaded
 /// 
 /// # Safety
 /// Engine must be initialized.
 pub unsafe fn set_granular_source_len(length: u32) {
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     if !engine.is_null() {
         (*engine).granular_source_len = length;
-        (*engine).flags |= FLAG_GRANULAR_READY;
+        if length == 0 {
+            (*engine).flags &= !FLAG_GRANULAR_READY;
+        } else {
+            (*engine).flags |= FLAG_GRANULAR_READY;
+        }
     }
 }
 
@@ -299,9 +471,9 @@ pub unsafe fn set_granular_source_len(length: u32) {
 /// Engine must be initialized and granular source must be loaded.
 #[inline]
 pub unsafe fn granular_source_slice() -> &'static [f32] {
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     let len = (*engine).granular_source_len as usize;
-    std::slice::from_raw_parts(GRANULAR_SOURCE_OFFSET as *const f32, len)
+    std::slice::from_raw_parts(region_ptr(GRANULAR_SOURCE_OFFSET) as *const f32, len)
 }
 
 // ============================================================================
@@ -314,21 +486,26 @@ pub unsafe fn granular_source_slice() -> &'static [f32] {
 /// Mutable pointer to the IR buffer start
 #[inline]
 pub fn get_ir_ptr() -> *mut f32 {
-    IR_OFFSET as *mut f32
+    region_ptr(IR_OFFSET) as *mut f32
 }
 
 /// Set IR length after loading
 /// 
 /// # Arguments
-/// * `length` - Number of samples loaded
+/// * `length` - Number of samples loaded (all channels); 0 marks the
+///   region as unloaded
 /// 
 /// # Safety
 /// Engine must be initialized.
 pub unsafe fn set_ir_len(length: u32) {
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     if !engine.is_null() {
         (*engine).ir_len = length;
-        (*engine).flags |= FLAG_IR_READY;
+        if length == 0 {
+            (*engine).flags &= !FLAG_IR_READY;
+        } else {
+            (*engine).flags |= FLAG_IR_READY;
+        }
     }
 }
 
@@ -338,9 +515,101 @@ pub unsafe fn set_ir_len(length: u32) {
 /// Engine must be initialized and IR must be loaded.
 #[inline]
 pub unsafe fn ir_slice() -> &'static [f32] {
-    let engine = *addr_of!(ENGINE);
+    let engine = engine();
     let len = (*engine).ir_len as usize;
-    std::slice::from_raw_parts(IR_OFFSET as *const f32, len)
+    std::slice::from_raw_parts(region_ptr(IR_OFFSET) as *const f32, len)
+}
+
+/// Get pointer to the slot B IR buffer
+/// 
+/// # Returns
+/// Mutable pointer to the slot B IR buffer start
+#[inline]
+pub fn get_ir_b_ptr() -> *mut f32 {
+    region_ptr(IR_B_OFFSET) as *mut f32
+}
+
+/// Get pointer to the ducking sidechain buffer
+/// 
+/// # Returns
+/// Mutable pointer to the sidechain buffer start (MAX_BUFFER_SIZE samples)
+#[inline]
+pub fn get_sidechain_ptr() -> *mut f32 {
+    region_ptr(SIDECHAIN_OFFSET) as *mut f32
+}
+
+/// Get the first `len` samples of the sidechain buffer
+/// 
+/// # Safety
+/// Engine must be initialized; `len` must not exceed MAX_BUFFER_SIZE.
+#[inline]
+pub unsafe fn sidechain_slice(len: usize) -> &'static [f32] {
+    std::slice::from_raw_parts(region_ptr(SIDECHAIN_OFFSET) as *const f32, len)
+}
+
+/// Get pointer to the spectral mask buffer
+/// 
+/// # Returns
+/// Mutable pointer to the spectral mask buffer start
+/// (MAX_SPECTRAL_MASK_BINS gains)
+#[inline]
+pub fn get_spectral_mask_ptr() -> *mut f32 {
+    region_ptr(SPECTRAL_MASK_OFFSET) as *mut f32
+}
+
+/// Get the first `len` gains of the spectral mask buffer
+/// 
+/// # Safety
+/// `len` must not exceed MAX_SPECTRAL_MASK_BINS.
+#[inline]
+pub unsafe fn spectral_mask_slice(len: usize) -> &'static [f32] {
+    std::slice::from_raw_parts(region_ptr(SPECTRAL_MASK_OFFSET) as *const f32, len)
+}
+
+/// Set slot B IR length after loading
+/// 
+/// # Arguments
+/// * `length` - Number of samples loaded (all channels); 0 marks the
+///   region as unloaded
+/// 
+/// # Safety
+/// Engine must be initialized.
+pub unsafe fn set_ir_b_len(length: u32) {
+    let engine = engine();
+    if !engine.is_null() {
+        (*engine).ir_b_len = length;
+    }
+}
+
+// ============================================================================
+// VOCODER CARRIER BUFFER
+// ============================================================================
+
+/// Get pointer to vocoder carrier buffer
+/// 
+/// # Returns
+/// Mutable pointer to the vocoder carrier buffer start
+#[inline]
+pub fn get_vocoder_carrier_ptr() -> *mut f32 {
+    region_ptr(VOCODER_CARRIER_OFFSET) as *mut f32
+}
+
+// ============================================================================
+// LIVE HISTORY BUFFER
+// ============================================================================
+
+/// Get the live input history ring as a mutable slice
+/// 
+/// Interleaved stereo, MAX_LIVE_HISTORY_FRAMES frames.
+/// 
+/// # Safety
+/// Caller must ensure no other references to the ring exist.
+#[inline]
+pub unsafe fn live_history_slice_mut() -> &'static mut [f32] {
+    core::slice::from_raw_parts_mut(
+        region_ptr(LIVE_HISTORY_OFFSET) as *mut f32,
+        MAX_LIVE_HISTORY_FRAMES * 2,
+    )
 }
 
 // ============================================================================
@@ -354,7 +623,7 @@ pub unsafe fn ir_slice() -> &'static [f32] {
 #[inline]
 pub fn sample_rate() -> f32 {
     unsafe {
-        let engine = *addr_of!(ENGINE);
+        let engine = engine();
         if engine.is_null() { 44100.0 } else { (*engine).sample_rate }
     }
 }
@@ -366,7 +635,7 @@ pub fn sample_rate() -> f32 {
 #[inline]
 pub fn buffer_size() -> u32 {
     unsafe {
-        let engine = *addr_of!(ENGINE);
+        let engine = engine();
         if engine.is_null() { 128 } else { (*engine).buffer_size }
     }
 }
@@ -375,7 +644,7 @@ pub fn buffer_size() -> u32 {
 #[inline]
 pub fn is_initialized() -> bool {
     unsafe {
-        let engine = *addr_of!(ENGINE);
+        let engine = engine();
         !engine.is_null() && ((*engine).flags & FLAG_INITIALIZED) != 0
     }
 }
@@ -384,7 +653,7 @@ pub fn is_initialized() -> bool {
 #[inline]
 pub fn is_granular_ready() -> bool {
     unsafe {
-        let engine = *addr_of!(ENGINE);
+        let engine = engine();
         !engine.is_null() && ((*engine).flags & FLAG_GRANULAR_READY) != 0
     }
 }
@@ -393,28 +662,291 @@ pub fn is_granular_ready() -> bool {
 #[inline]
 pub fn is_ir_ready() -> bool {
     unsafe {
-        let engine = *addr_of!(ENGINE);
+        let engine = engine();
         !engine.is_null() && ((*engine).flags & FLAG_IR_READY) != 0
     }
 }
 
+/// Enable or disable the output DC blocker
+/// 
+/// # Arguments
+/// * `enabled` - Whether granular and convolution outputs are DC blocked
+pub fn set_dc_blocker(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let engine = engine();
+        if !engine.is_null() {
+            if enabled {
+                (*engine).flags |= FLAG_DC_BLOCKER;
+            } else {
+                (*engine).flags &= !FLAG_DC_BLOCKER;
+            }
+        }
+    }
+}
+
+/// Check if the output DC blocker is enabled
+#[inline]
+pub fn is_dc_blocker_enabled() -> bool {
+    unsafe {
+        let engine = engine();
+        !engine.is_null() && ((*engine).flags & FLAG_DC_BLOCKER) != 0
+    }
+}
+
+// ============================================================================
+// MEMORY REPORT
+// ============================================================================
+
+/// Size of a WASM linear memory page
+pub const WASM_PAGE_SIZE: usize = 0x10000;
+
+/// Region usage of one engine, for debugging layout and overflow issues
+/// 
+/// # Memory Layout
+/// Laid out in C format so JS can read it as a Uint32Array. Offsets are
+/// relative to the engine's layout; capacities and usage are in f32 samples.
+#[repr(C)]
+pub struct MemoryReport {
+    /// Offset of the granular source region
+    pub granular_source_offset: u32,
+    /// Granular source region capacity (MAX_GRANULAR_SOURCE_SAMPLES)
+    pub granular_source_capacity: u32,
+    /// Samples of the loaded granular source (0 = none)
+    pub granular_source_used: u32,
+    /// Offset of the IR region
+    pub ir_offset: u32,
+    /// IR region capacity (MAX_IR_SAMPLES)
+    pub ir_capacity: u32,
+    /// Samples of the loaded IR (0 = none)
+    pub ir_used: u32,
+    /// End of the engine's layout (MEMORY_END)
+    pub layout_end: u32,
+    /// Bytes of the engine regions allocated so far
+    pub high_water_offset: u32,
+}
+
+/// Report returned to JS (rewritten on every `memory_report` call)
+static mut REPORT: MemoryReport = MemoryReport {
+    granular_source_offset: 0,
+    granular_source_capacity: 0,
+    granular_source_used: 0,
+    ir_offset: 0,
+    ir_capacity: 0,
+    ir_used: 0,
+    layout_end: 0,
+    high_water_offset: 0,
+};
+
+/// Fill in the memory report for the selected engine
+/// 
+/// # Returns
+/// Pointer to the report, valid until the next call
+pub fn memory_report() -> *const MemoryReport {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let engine = engine();
+        let (granular_used, ir_used) = if engine.is_null() {
+            (0, 0)
+        } else {
+            ((*engine).granular_source_len, (*engine).ir_len)
+        };
+        
+        let report = addr_of_mut!(REPORT);
+        *report = MemoryReport {
+            granular_source_offset: GRANULAR_SOURCE_OFFSET as u32,
+            granular_source_capacity: MAX_GRANULAR_SOURCE_SAMPLES as u32,
+            granular_source_used: granular_used,
+            ir_offset: IR_OFFSET as u32,
+            ir_capacity: MAX_IR_SAMPLES as u32,
+            ir_used,
+            layout_end: MEMORY_END as u32,
+            high_water_offset: required_bytes() as u32,
+        };
+        report
+    }
+}
+
+/// Bytes of the engine regions allocated so far
+/// 
+/// One region per engine slot ever created (regions outlive cleanup), and
+/// at least one, which the first `dsp_init` allocates.
+pub fn required_bytes() -> usize {
+    // SAFETY: Single-threaded WASM context
+    let allocated = unsafe { (*addr_of!(REGIONS)).iter().filter(|&&base| base != 0).count() };
+    allocated.max(1) * ENGINE_REGION_SIZE
+}
+
+/// Number of 64KB WASM pages taken by the engine regions
+pub fn required_memory_pages() -> u32 {
+    required_bytes().div_ceil(WASM_PAGE_SIZE) as u32
+}
+
 // ============================================================================
 // CLEANUP
 // ============================================================================
 
-/// Clean up engine state
+/// Clean up the selected engine's state
 /// 
-/// Note: In WASM, memory isn't truly freed. This just resets state flags
-/// so the engine can be re-initialized if needed.
+/// Note: The engine's region isn't freed. This just resets state flags
+/// and releases the engine's slot so it can be created again.
 pub fn cleanup() {
     unsafe {
-        let engine_ptr = addr_of_mut!(ENGINE);
-        let engine = *engine_ptr;
+        let engine = engine();
         if !engine.is_null() {
             (*engine).flags = 0;
             (*engine).granular_source_len = 0;
             (*engine).ir_len = 0;
+            (*engine).ir_b_len = 0;
+        }
+        (*addr_of_mut!(ENGINES))[current_engine()] = ptr::null_mut();
+    }
+}
+
+// ============================================================================
+// TEST SUPPORT
+// ============================================================================
+
+/// Serialize tests that touch the global engine and module state
+/// 
+/// The DSP modules assume a single-threaded host, but the test harness runs
+/// tests in parallel. Hold this guard for the duration of such a test.
+#[cfg(test)]
+pub(crate) fn test_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Heap allocator that counts allocations made by the current thread
+#[cfg(test)]
+struct CountingAllocator;
+
+#[cfg(test)]
+thread_local! {
+    static ALLOCATIONS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        std::alloc::System.alloc(layout)
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+    
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        std::alloc::System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of heap allocations (and reallocations) the current thread makes in `f`
+/// 
+/// Used to check that audio-path functions don't allocate after warmup.
+#[cfg(test)]
+pub(crate) fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_large_buffers_fit_their_regions() {
+        let _guard = test_lock();
+        
+        init_engine(48000.0, 1024);
+        assert!(is_initialized());
+        unsafe {
+            for channel in 0..2 {
+                assert_eq!(input_slice(channel).len(), 1024);
+                assert_eq!(output_slice_mut(channel).len(), 1024);
+            }
+            
+            // Filling one buffer leaves its neighbours untouched
+            output_slice_mut(0).fill(1.0);
+            assert!(output_slice_mut(1).iter().all(|&x| x == 0.0));
+            assert!(input_slice(1).iter().all(|&x| x == 0.0));
+        }
+        
+        // Every block-sized region ends before the next one starts, and the
+        // last ends before the granular source
+        let regions = [
+            INPUT_L_OFFSET,
+            INPUT_R_OFFSET,
+            OUTPUT_L_OFFSET,
+            OUTPUT_R_OFFSET,
+            WORK1_OFFSET,
+            WORK2_OFFSET,
+            GRANULAR_SOURCE_OFFSET,
+        ];
+        for pair in regions.windows(2) {
+            assert!(pair[0] + BUFFER_BYTES <= pair[1], "region at {:#x} overlaps {:#x}", pair[0], pair[1]);
+        }
+        
+        // Sizes beyond the reserved regions are rejected
+        init_engine(48000.0, MAX_BUFFER_SIZE as u32);
+        assert!(is_initialized());
+        cleanup();
+        init_engine(48000.0, MAX_BUFFER_SIZE as u32 + 1);
+        assert!(!is_initialized());
+        
+        init_engine(48000.0, 128);
+    }
+    
+    #[test]
+    fn test_memory_report_matches_layout() {
+        let _guard = test_lock();
+        set_current_engine(0);
+        init_engine(48000.0, 128);
+        
+        let report = unsafe { &*memory_report() };
+        assert_eq!(report.granular_source_capacity as usize, MAX_GRANULAR_SOURCE_SAMPLES);
+        assert_eq!(report.ir_capacity as usize, MAX_IR_SAMPLES);
+        assert_eq!(report.granular_source_offset as usize, GRANULAR_SOURCE_OFFSET);
+        assert_eq!(report.ir_offset as usize, IR_OFFSET);
+        assert_eq!(report.layout_end as usize, MEMORY_END);
+        assert_eq!((report.granular_source_used, report.ir_used), (0, 0));
+        
+        // Loaded lengths show up as usage, counted over all channels
+        unsafe {
+            set_granular_source_len(1000 * 2);
+            set_ir_len(300);
         }
-        *engine_ptr = ptr::null_mut();
+        let report = unsafe { &*memory_report() };
+        assert_eq!((report.granular_source_used, report.ir_used), (2000, 300));
+        
+        // The page count covers the allocated regions, whole pages each
+        let pages = required_memory_pages() as usize;
+        assert!(pages * WASM_PAGE_SIZE >= MEMORY_END);
+        assert_eq!(pages * WASM_PAGE_SIZE % ENGINE_REGION_SIZE, 0);
+        assert_eq!(report.high_water_offset as usize, pages * WASM_PAGE_SIZE);
+        
+        // Re-initializing keeps the region; a new slot adds one
+        init_engine(48000.0, 256);
+        assert_eq!(required_memory_pages() as usize, pages);
+        set_current_engine(MAX_ENGINES - 1);
+        let fresh = unsafe { (*addr_of!(REGIONS))[MAX_ENGINES - 1] } == 0;
+        init_engine(48000.0, 128);
+        let grown = if fresh { ENGINE_REGION_SIZE / WASM_PAGE_SIZE } else { 0 };
+        assert_eq!(required_memory_pages() as usize, pages + grown);
+        cleanup();
+        assert!(select_engine(MAX_ENGINES as u32 - 1));
+        set_current_engine(0);
+        init_engine(48000.0, 128);
     }
 }
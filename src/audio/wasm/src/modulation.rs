@@ -0,0 +1,407 @@
+//! Modulation Sources
+//!
+//! Low-frequency oscillators for modulation routing (chorus/flanger delay
+//! times, filter sweeps, ...), and an envelope follower for modulating
+//! from a signal's level (sidechain ducking).
+//!
+//! # Shapes
+//! All shapes run from -1 to 1 over a phase of 0..1:
+//! - Sine and Triangle start at 0, rising
+//! - Saw ramps up from -1; Square is +1 for the first half of the cycle
+//! - SampleHold holds a new random value each cycle
+//!
+//! # Aliasing
+//! The jumps of Saw, Square and SampleHold are smoothed with PolyBLEP
+//! residuals, so audio-rate LFOs don't alias harshly. At low rates the
+//! correction only touches the sample next to each jump.
+
+use core::f32::consts::TAU;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Highest LFO frequency as a fraction of the sample rate (keeps the
+/// PolyBLEP regions around each jump from overlapping)
+const MAX_FREQUENCY_RATIO: f32 = 0.25;
+
+/// Envelope follower attack/release time range in milliseconds
+const MIN_FOLLOWER_MS: f32 = 0.1;
+const MAX_FOLLOWER_MS: f32 = 5000.0;
+
+/// Envelope follower level below which the envelope snaps to 0 (-180dB),
+/// so a silent input settles on exactly 0 instead of decaying forever
+const FOLLOWER_FLOOR: f32 = 1e-9;
+
+// ============================================================================
+// LFO
+// ============================================================================
+
+/// LFO waveform
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    SampleHold,
+}
+
+impl LfoShape {
+    /// Shape from its export index (unknown values fall back to Sine)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => LfoShape::Triangle,
+            2 => LfoShape::Saw,
+            3 => LfoShape::Square,
+            4 => LfoShape::SampleHold,
+            _ => LfoShape::Sine,
+        }
+    }
+}
+
+/// Low-frequency oscillator with output in -1..1
+pub struct Lfo {
+    shape: LfoShape,
+    /// Phase in cycles (0..1)
+    phase: f32,
+    /// Phase increment per sample (frequency / sample rate)
+    phase_inc: f32,
+    /// LCG state for SampleHold (same generator as granular's RNG)
+    rng_state: u32,
+    /// SampleHold value of the current cycle, the next one, and the jump
+    /// into the current one
+    held: f32,
+    next: f32,
+    last_jump: f32,
+}
+
+impl Default for Lfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lfo {
+    /// Create a stopped sine LFO at phase 0
+    pub const fn new() -> Self {
+        let first = lcg_next(12345);
+        let second = lcg_next(first);
+        Self {
+            shape: LfoShape::Sine,
+            phase: 0.0,
+            phase_inc: 0.0,
+            rng_state: second,
+            held: to_bipolar(first),
+            next: to_bipolar(second),
+            last_jump: 0.0,
+        }
+    }
+    
+    /// Set frequency in Hz (0 to a quarter of the sample rate)
+    pub fn set_frequency(&mut self, freq: f32, sample_rate: f32) {
+        self.phase_inc = (freq / sample_rate).clamp(0.0, MAX_FREQUENCY_RATIO);
+    }
+    
+    /// Set waveform
+    pub fn set_shape(&mut self, shape: LfoShape) {
+        self.shape = shape;
+    }
+    
+    /// Jump to a phase in cycles (wrapped to 0..1)
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase.rem_euclid(1.0);
+        if self.phase >= 1.0 {
+            self.phase = 0.0;
+        }
+    }
+    
+    /// Generate the next sample (-1 to 1)
+    pub fn process(&mut self) -> f32 {
+        let t = self.phase;
+        let dt = self.phase_inc;
+        
+        let output = match self.shape {
+            LfoShape::Sine => libm::sinf(TAU * t),
+            LfoShape::Triangle => {
+                // Shifted a quarter cycle so it starts at 0 like the sine
+                let shifted = (t + 0.25) % 1.0;
+                1.0 - 4.0 * (shifted - 0.5).abs()
+            }
+            LfoShape::Saw => 2.0 * t - 1.0 - poly_blep(t, dt),
+            LfoShape::Square => {
+                let naive = if t < 0.5 { 1.0 } else { -1.0 };
+                naive + poly_blep(t, dt) - poly_blep((t + 0.5) % 1.0, dt)
+            }
+            LfoShape::SampleHold => {
+                // Each residual is scaled by half its jump (PolyBLEP is
+                // normalized to a jump of 2)
+                let jump = if t < dt { self.last_jump } else { self.next - self.held };
+                self.held + 0.5 * jump * poly_blep(t, dt)
+            }
+        };
+        
+        self.phase += dt;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.last_jump = self.next - self.held;
+            self.held = self.next;
+            self.next = self.random_bipolar();
+        }
+        
+        output
+    }
+    
+    /// Random value in range [-1.0, 1.0)
+    fn random_bipolar(&mut self) -> f32 {
+        self.rng_state = lcg_next(self.rng_state);
+        to_bipolar(self.rng_state)
+    }
+}
+
+// ============================================================================
+// ENVELOPE FOLLOWER
+// ============================================================================
+
+/// Envelope follower detector
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FollowerMode {
+    /// Rectified signal (follows transients)
+    Peak,
+    /// Mean square, reported as its root (follows loudness)
+    Rms,
+}
+
+impl FollowerMode {
+    /// Mode from its export index (unknown values fall back to Peak)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => FollowerMode::Rms,
+            _ => FollowerMode::Peak,
+        }
+    }
+}
+
+/// One-pole envelope follower with separate attack and release times
+/// 
+/// The times are one-pole time constants: a step in level is 63% followed
+/// after the attack (rising) or release (falling) time.
+pub struct EnvelopeFollower {
+    mode: FollowerMode,
+    /// Smoothing coefficients per sample for a rising and a falling level
+    attack_coeff: f32,
+    release_coeff: f32,
+    /// Smoothed level (squared for Rms)
+    level: f32,
+}
+
+impl Default for EnvelopeFollower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvelopeFollower {
+    /// Create a peak follower with instant attack and release
+    pub const fn new() -> Self {
+        Self {
+            mode: FollowerMode::Peak,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            level: 0.0,
+        }
+    }
+    
+    /// Set detector
+    pub fn set_mode(&mut self, mode: FollowerMode) {
+        if mode != self.mode {
+            self.mode = mode;
+            self.level = 0.0;
+        }
+    }
+    
+    /// Detector in use
+    pub fn mode(&self) -> FollowerMode {
+        self.mode
+    }
+    
+    /// Set attack and release times in milliseconds (0.1-5000)
+    pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sample_rate: f32) {
+        self.attack_coeff = time_coeff(attack_ms, sample_rate);
+        self.release_coeff = time_coeff(release_ms, sample_rate);
+    }
+    
+    /// Follow the next input sample, returning the envelope
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let target = match self.mode {
+            FollowerMode::Peak => x.abs(),
+            FollowerMode::Rms => x * x,
+        };
+        let coeff = if target > self.level { self.attack_coeff } else { self.release_coeff };
+        self.level = target + (self.level - target) * coeff;
+        if self.level < FOLLOWER_FLOOR {
+            self.level = 0.0;
+        }
+        self.envelope()
+    }
+    
+    /// Current envelope (linear amplitude)
+    #[inline]
+    pub fn envelope(&self) -> f32 {
+        match self.mode {
+            FollowerMode::Peak => self.level,
+            FollowerMode::Rms => self.level.sqrt(),
+        }
+    }
+    
+    /// Drop the envelope to 0
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+}
+
+/// One-pole coefficient for a time constant in milliseconds
+fn time_coeff(ms: f32, sample_rate: f32) -> f32 {
+    let samples = ms.clamp(MIN_FOLLOWER_MS, MAX_FOLLOWER_MS) * 0.001 * sample_rate;
+    libm::expf(-1.0 / samples)
+}
+
+/// Linear Congruential Generator step (Numerical Recipes parameters)
+#[inline]
+const fn lcg_next(state: u32) -> u32 {
+    state.wrapping_mul(1664525).wrapping_add(1013904223)
+}
+
+/// LCG state as a value in range [-1.0, 1.0)
+#[inline]
+const fn to_bipolar(state: u32) -> f32 {
+    (state as f32) / (u32::MAX as f32) * 2.0 - 1.0
+}
+
+/// PolyBLEP residual for a jump of -2 at phase 0 (e.g. a saw's reset)
+/// 
+/// Nonzero only within one phase increment of the jump.
+#[inline]
+fn poly_blep(t: f32, dt: f32) -> f32 {
+    if t < dt {
+        let x = t / dt;
+        2.0 * x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + 2.0 * x + 1.0
+    } else {
+        0.0
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    /// 93.75Hz at 48kHz (a power of two, so the phase increment is exact)
+    const PERIOD: usize = 512;
+    
+    fn render(shape: LfoShape, cycles: usize) -> Vec<f32> {
+        let mut lfo = Lfo::new();
+        lfo.set_frequency(SAMPLE_RATE / PERIOD as f32, SAMPLE_RATE);
+        lfo.set_shape(shape);
+        (0..PERIOD * cycles).map(|_| lfo.process()).collect()
+    }
+    
+    #[test]
+    fn test_shapes_period_and_range() {
+        for shape in [LfoShape::Sine, LfoShape::Triangle, LfoShape::Saw, LfoShape::Square] {
+            let output = render(shape, 10);
+            
+            // Repeats every period, and not every half period
+            let diff = |lag: usize| {
+                (0..output.len() - lag).fold(0.0f32, |max, n| max.max((output[n + lag] - output[n]).abs()))
+            };
+            assert!(diff(PERIOD) < 1e-3, "{shape:?} not periodic ({})", diff(PERIOD));
+            assert!(diff(PERIOD / 2) > 0.5, "{shape:?} repeats every half period");
+            
+            let min = output.iter().fold(f32::MAX, |min, &x| min.min(x));
+            let max = output.iter().fold(f32::MIN, |max, &x| max.max(x));
+            assert!(min >= -1.0 - 1e-6 && max <= 1.0 + 1e-6, "{shape:?} out of range: {min}..{max}");
+            assert!(min < -0.95 && max > 0.95, "{shape:?} doesn't span the range: {min}..{max}");
+        }
+        
+        // SampleHold: one random value per period, all within range
+        let output = render(LfoShape::SampleHold, 20);
+        let mut values = Vec::new();
+        for cycle in output.chunks(PERIOD) {
+            // Only the samples next to the jumps carry the PolyBLEP residual
+            let plateau = &cycle[1..PERIOD - 1];
+            assert!(plateau.iter().all(|&x| x == plateau[0]), "value changed within a period");
+            assert!(cycle.iter().all(|x| (-1.0..=1.0).contains(x)));
+            values.push(plateau[0]);
+        }
+        assert!(values.windows(2).all(|pair| pair[0] != pair[1]));
+        let spread = values.iter().fold(0.0f32, |max, &x| max.max(x)) - values.iter().fold(0.0f32, |min, &x| min.min(x));
+        assert!(spread > 1.0, "held values barely vary ({spread})");
+    }
+    
+    #[test]
+    fn test_phase_and_bandlimited_jumps() {
+        // set_phase moves the cycle: a quarter cycle in, the sine is at its peak
+        let mut lfo = Lfo::new();
+        lfo.set_frequency(1.0, SAMPLE_RATE);
+        lfo.set_phase(1.25);
+        assert!((lfo.process() - 1.0).abs() < 1e-6);
+        
+        // At an audio rate the saw reset is spread over two samples
+        // instead of one full-scale step
+        let mut lfo = Lfo::new();
+        lfo.set_frequency(SAMPLE_RATE / 37.3, SAMPLE_RATE);
+        lfo.set_shape(LfoShape::Saw);
+        let output: Vec<f32> = (0..1000).map(|_| lfo.process()).collect();
+        let max_step = output.windows(2).fold(0.0f32, |max, pair| max.max((pair[1] - pair[0]).abs()));
+        assert!(max_step < 1.5, "saw jump not smoothed (step {max_step})");
+    }
+    
+    #[test]
+    fn test_envelope_follower_times_and_detectors() {
+        // A 10ms attack reaches 1 - 1/e of a step after 480 samples, a
+        // 100ms release falls to 1/e after 4800
+        let mut follower = EnvelopeFollower::new();
+        follower.set_times(10.0, 100.0, SAMPLE_RATE);
+        let attack: Vec<f32> = (0..480).map(|_| follower.process(1.0)).collect();
+        assert!((attack[479] - 0.632).abs() < 0.01, "attack reached {}", attack[479]);
+        for _ in 0..48000 {
+            follower.process(1.0);
+        }
+        let release: Vec<f32> = (0..4800).map(|_| follower.process(0.0)).collect();
+        assert!((release[4799] - 0.368).abs() < 0.01, "release reached {}", release[4799]);
+        assert!(release.windows(2).all(|pair| pair[1] <= pair[0]));
+        
+        // Silence settles on exactly 0
+        for _ in 0..SAMPLE_RATE as usize * 2 {
+            follower.process(0.0);
+        }
+        assert_eq!(follower.envelope(), 0.0);
+        
+        // A full-scale sine settles near 1 on a fast-attack peak detector
+        // and near its RMS (0.707) on an averaging RMS detector
+        for (mode, attack_ms, release_ms, expected) in [
+            (FollowerMode::Peak, 1.0, 500.0, 1.0),
+            (FollowerMode::Rms, 50.0, 50.0, core::f32::consts::FRAC_1_SQRT_2),
+        ] {
+            let mut follower = EnvelopeFollower::new();
+            follower.set_mode(mode);
+            follower.set_times(attack_ms, release_ms, SAMPLE_RATE);
+            let mut envelope = 0.0;
+            for i in 0..48000 {
+                envelope = follower.process(libm::sinf(TAU * 100.0 * i as f32 / SAMPLE_RATE));
+            }
+            assert!((envelope - expected).abs() < 0.05, "{mode:?} envelope {envelope}");
+        }
+        assert_eq!(FollowerMode::from_index(1), FollowerMode::Rms);
+        assert_eq!(FollowerMode::from_index(9), FollowerMode::Peak);
+    }
+}
@@ -0,0 +1,266 @@
+//! Noise Generator
+//!
+//! Internal noise source for feeding the effects without a sample (e.g.
+//! noise through the reverb for an ambient pad).
+//!
+//! # Colors
+//! - White: flat spectrum, from the same LCG as granular's RNG
+//! - Pink: -3dB/octave (equal energy per octave), Voss-McCartney: the
+//!   sum of PINK_ROWS random values, row k redrawn every 2^k samples
+//! - Brown: -6dB/octave, white noise through a leaky integrator. The leak
+//!   flattens the spectrum below ~15Hz, so the output can't drift off
+//!   into DC.
+//!
+//! Every color is scaled to about the RMS level of white noise (-4.8dBFS).
+//!
+//! # Stereo
+//! The channels run separately seeded generators, so the noise is
+//! uncorrelated between them.
+
+use crate::memory;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Voss-McCartney rows; the slowest is redrawn every 2^(PINK_ROWS - 1)
+/// samples, which keeps the pink slope down to below 1Hz
+const PINK_ROWS: usize = 16;
+
+/// Gain that brings the row sum (plus a white sample) to the RMS of white
+/// noise: 1 / sqrt(PINK_ROWS + 1)
+const PINK_GAIN: f32 = 0.24253563;
+
+/// Brown noise integrator leak per sample
+const BROWN_LEAK: f32 = 0.998;
+
+/// Brown noise integrator input gain: sqrt(1 - BROWN_LEAK²), which gives
+/// the integrator output the RMS of its white input
+const BROWN_STEP: f32 = 0.06321392;
+
+// ============================================================================
+// NOISE GENERATOR
+// ============================================================================
+
+/// Noise color
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NoiseType {
+    White,
+    Pink,
+    Brown,
+}
+
+impl NoiseType {
+    /// Type from its export index (unknown values fall back to White)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => NoiseType::Pink,
+            2 => NoiseType::Brown,
+            _ => NoiseType::White,
+        }
+    }
+}
+
+/// White, pink or brown noise source (one channel)
+pub struct NoiseGenerator {
+    noise_type: NoiseType,
+    /// LCG state (same generator as granular's RNG)
+    rng_state: u32,
+    /// Pink: sample counter choosing the row to redraw, the rows and
+    /// their running sum
+    pink_counter: u32,
+    pink_rows: [f32; PINK_ROWS],
+    pink_sum: f32,
+    /// Brown: integrator state
+    brown: f32,
+}
+
+impl NoiseGenerator {
+    /// Create a white noise generator
+    /// 
+    /// # Arguments
+    /// * `seed` - Initial LCG state (generators with different seeds are
+    ///   uncorrelated)
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            noise_type: NoiseType::White,
+            rng_state: seed,
+            pink_counter: 0,
+            pink_rows: [0.0; PINK_ROWS],
+            pink_sum: 0.0,
+            brown: 0.0,
+        }
+    }
+    
+    pub fn set_type(&mut self, noise_type: NoiseType) {
+        self.noise_type = noise_type;
+    }
+    
+    /// Random value in range [-1.0, 1.0)
+    #[inline]
+    fn white(&mut self) -> f32 {
+        self.rng_state = self.rng_state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (self.rng_state as f32) / (u32::MAX as f32) * 2.0 - 1.0
+    }
+    
+    /// Next noise sample
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        match self.noise_type {
+            NoiseType::White => self.white(),
+            NoiseType::Pink => {
+                // Row k changes when the counter's lowest set bit is bit k
+                self.pink_counter = self.pink_counter.wrapping_add(1);
+                let row = self.pink_counter.trailing_zeros() as usize;
+                if row < PINK_ROWS {
+                    let value = self.white();
+                    self.pink_sum += value - self.pink_rows[row];
+                    self.pink_rows[row] = value;
+                }
+                (self.pink_sum + self.white()) * PINK_GAIN
+            }
+            NoiseType::Brown => {
+                self.brown = self.brown * BROWN_LEAK + self.white() * BROWN_STEP;
+                self.brown
+            }
+        }
+    }
+    
+    /// Fill a buffer with noise
+    pub fn process_into(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.next();
+        }
+    }
+}
+
+/// Left and right noise generators of every engine in the pool
+static mut STATES: [[NoiseGenerator; 2]; memory::MAX_ENGINES] =
+    [const { [NoiseGenerator::new(12345), NoiseGenerator::new(67890)] }; memory::MAX_ENGINES];
+
+/// Noise generators of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut [NoiseGenerator; 2] {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Write noise to the output buffers (the input is ignored)
+/// 
+/// # Arguments
+/// * `noise_type` - Noise color
+/// * `gain` - Output gain (0-1)
+pub fn process(noise_type: NoiseType, gain: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the output buffers don't
+        // overlap the generator state
+        let generators = &mut *state();
+        let gain = gain.clamp(0.0, 1.0);
+        for (channel, generator) in generators.iter_mut().enumerate() {
+            let output = memory::output_slice_mut(channel as u32);
+            generator.set_type(noise_type);
+            generator.process_into(output);
+            for sample in output.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+/// Restore the noise generators to their initial seeds
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = [NoiseGenerator::new(12345), NoiseGenerator::new(67890)];
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustfft::{num_complex::Complex, FftPlanner};
+    
+    const FFT_SIZE: usize = 4096;
+    
+    /// Spectral slope of a noise type in dB per octave, fitted to the
+    /// average power density of octave bands from 94Hz to 12kHz (at 48kHz)
+    fn spectral_slope(noise_type: NoiseType) -> (f32, f32) {
+        let mut generator = NoiseGenerator::new(1);
+        generator.set_type(noise_type);
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        
+        // Welch average of Hann-windowed segments
+        let mut density = vec![0.0f64; FFT_SIZE / 2];
+        let mut samples = vec![0.0f32; FFT_SIZE];
+        let mut total = 0.0f64;
+        let segments = 256;
+        for _ in 0..segments {
+            generator.process_into(&mut samples);
+            total += samples.iter().map(|&x| (x * x) as f64).sum::<f64>();
+            let mut spectrum: Vec<Complex<f32>> = samples
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    let window = 0.5 - 0.5 * (core::f32::consts::TAU * i as f32 / FFT_SIZE as f32).cos();
+                    Complex::new(x * window, 0.0)
+                })
+                .collect();
+            fft.process(&mut spectrum);
+            for (d, bin) in density.iter_mut().zip(&spectrum) {
+                *d += bin.norm_sqr() as f64;
+            }
+        }
+        let rms = (total / (segments * FFT_SIZE) as f64).sqrt() as f32;
+        
+        // Octave bands starting at bin 8 (94Hz) up to bin 1024 (12kHz)
+        let points: Vec<(f64, f64)> = (3..10)
+            .map(|octave| {
+                let band = &density[1 << octave..2 << octave];
+                let mean = band.iter().sum::<f64>() / band.len() as f64;
+                (octave as f64, 10.0 * mean.log10())
+            })
+            .collect();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let slope = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum::<f64>()
+            / points.iter().map(|p| (p.0 - mean_x).powi(2)).sum::<f64>();
+        (slope as f32, rms)
+    }
+    
+    #[test]
+    fn test_noise_spectral_slopes() {
+        for (noise_type, expected) in [(NoiseType::White, 0.0), (NoiseType::Pink, -3.0), (NoiseType::Brown, -6.0)] {
+            let (slope, rms) = spectral_slope(noise_type);
+            assert!((slope - expected).abs() < 0.5, "{noise_type:?} slope {slope}dB/octave");
+            assert!((rms - 0.577).abs() < 0.1, "{noise_type:?} RMS {rms}");
+        }
+    }
+    
+    #[test]
+    fn test_process_writes_uncorrelated_stereo_noise() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        
+        process(NoiseType::Pink, 0.5);
+        let (left, right) = unsafe { (memory::output_slice_mut(0).to_vec(), memory::output_slice_mut(1).to_vec()) };
+        assert!(left.iter().all(|x| x.abs() <= 0.5 * (PINK_ROWS + 1) as f32 * PINK_GAIN));
+        assert_ne!(left, right);
+        
+        process(NoiseType::White, 0.0);
+        assert!(unsafe { memory::output_slice_mut(0).iter().all(|&x| x == 0.0) });
+        assert_eq!(NoiseType::from_index(2), NoiseType::Brown);
+        assert_eq!(NoiseType::from_index(7), NoiseType::White);
+    }
+}
@@ -0,0 +1,312 @@
+//! Oversampling
+//!
+//! Runs a per-sample nonlinearity at 2x or 4x the sample rate, so the
+//! harmonics it generates above the host Nyquist frequency are filtered
+//! out instead of aliasing back into the audible band.
+//!
+//! # Filters
+//! Each 2x stage interpolates and decimates with the same 47-tap half-band
+//! FIR (Kaiser window, beta 8), split into its two polyphase branches:
+//! every other tap is zero and the center tap is 0.5, so a stage costs 12
+//! multiplies per direction and sample. The taps are a fixed table, so
+//! nothing is designed at runtime. The passband is flat to 0.4 of the host
+//! sample rate; images and harmonics above 0.6 are attenuated by 56dB or
+//! more. 4x runs a second 2x stage inside the first.
+//!
+//! # Latency
+//! A stage delays by its filter's group delay on the way up and on the way
+//! down: 23 host samples at 2x. The inner stage of 4x adds 11.5 samples,
+//! rounded up to 12 with one 2x-rate sample of delay, so `latency_samples`
+//! is always a whole number of host samples.
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Nonzero half-band taps on each side of the center tap
+const SIDE_TAPS: usize = 12;
+
+/// Side taps of the half-band filter, from the center outwards (the taps
+/// at odd distances 1, 3, 5, ... from it; they sum to 0.25)
+const HALFBAND: [f32; SIDE_TAPS] = [
+    0.31606004,
+    -0.09953367,
+    0.05323911,
+    -0.03190592,
+    0.019511502,
+    -0.011685276,
+    0.006670786,
+    -0.0035394353,
+    0.0016906355,
+    -0.0006899972,
+    0.00021460227,
+    -3.236779e-5,
+];
+
+/// History length of each polyphase branch (a power of two of at least
+/// 2 * SIDE_TAPS, so positions wrap with a mask)
+const HISTORY: usize = 32;
+const HISTORY_MASK: usize = HISTORY - 1;
+
+const _: () = assert!(HISTORY >= 2 * SIDE_TAPS && HISTORY.is_power_of_two());
+
+/// Latency of one 2x stage (up and down) in samples at its input rate
+const STAGE_LATENCY: u32 = 2 * SIDE_TAPS as u32 - 1;
+
+// ============================================================================
+// HALF-BAND STAGES
+// ============================================================================
+
+/// Weighted sum of the symmetric tap pairs around the center of a branch
+/// history ending at `pos`
+#[inline]
+fn halfband_sum(history: &[f32; HISTORY], pos: usize) -> f32 {
+    let at = |delay: usize| history[pos.wrapping_sub(delay) & HISTORY_MASK];
+    let mut sum = 0.0;
+    for (i, &tap) in HALFBAND.iter().enumerate() {
+        sum += tap * (at(SIDE_TAPS - 1 - i) + at(SIDE_TAPS + i));
+    }
+    sum
+}
+
+/// 2x interpolator
+#[derive(Clone, Copy)]
+struct Upsampler {
+    history: [f32; HISTORY],
+    pos: usize,
+}
+
+impl Upsampler {
+    const fn new() -> Self {
+        Self {
+            history: [0.0; HISTORY],
+            pos: 0,
+        }
+    }
+    
+    /// Two output samples for one input sample
+    #[inline]
+    fn process(&mut self, x: f32) -> [f32; 2] {
+        self.pos = (self.pos + 1) & HISTORY_MASK;
+        self.history[self.pos] = x;
+        // The zero-stuffed input is scaled by 2: the side taps make the
+        // even outputs, the center tap passes the odd ones through
+        let center = self.history[self.pos.wrapping_sub(SIDE_TAPS - 1) & HISTORY_MASK];
+        [2.0 * halfband_sum(&self.history, self.pos), center]
+    }
+}
+
+/// 2x decimator
+#[derive(Clone, Copy)]
+struct Downsampler {
+    /// Even and odd input samples
+    even: [f32; HISTORY],
+    odd: [f32; HISTORY],
+    pos: usize,
+}
+
+impl Downsampler {
+    const fn new() -> Self {
+        Self {
+            even: [0.0; HISTORY],
+            odd: [0.0; HISTORY],
+            pos: 0,
+        }
+    }
+    
+    /// One output sample for two input samples
+    #[inline]
+    fn process(&mut self, [even, odd]: [f32; 2]) -> f32 {
+        self.pos = (self.pos + 1) & HISTORY_MASK;
+        self.even[self.pos] = even;
+        self.odd[self.pos] = odd;
+        // Output at the even phase: the center tap lands on an odd sample,
+        // the side taps on even ones
+        let center = self.odd[self.pos.wrapping_sub(SIDE_TAPS) & HISTORY_MASK];
+        0.5 * center + halfband_sum(&self.even, self.pos)
+    }
+}
+
+// ============================================================================
+// OVERSAMPLER
+// ============================================================================
+
+/// Runs a nonlinearity at 1x, 2x or 4x the sample rate (one channel)
+#[derive(Clone, Copy)]
+pub struct Oversampler {
+    factor: u32,
+    /// [host <-> 2x, 2x <-> 4x] stages
+    up: [Upsampler; 2],
+    down: [Downsampler; 2],
+    /// The 2x-rate sample delayed to align the 4x path
+    align: f32,
+}
+
+impl Oversampler {
+    pub const fn new() -> Self {
+        Self {
+            factor: 1,
+            up: [Upsampler::new(); 2],
+            down: [Downsampler::new(); 2],
+            align: 0.0,
+        }
+    }
+    
+    /// Set the oversampling factor (2 or 4; anything else runs at 1x)
+    /// 
+    /// Changing the factor clears the filters.
+    pub fn set_factor(&mut self, factor: u32) {
+        let factor = match factor {
+            2 | 4 => factor,
+            _ => 1,
+        };
+        if factor != self.factor {
+            self.factor = factor;
+            self.reset();
+        }
+    }
+    
+    pub fn factor(&self) -> u32 {
+        self.factor
+    }
+    
+    /// Delay the oversampler adds, in samples at the host rate
+    pub fn latency_samples(&self) -> u32 {
+        match self.factor {
+            1 => 0,
+            2 => STAGE_LATENCY,
+            _ => STAGE_LATENCY + STAGE_LATENCY.div_ceil(2),
+        }
+    }
+    
+    /// Run `shape` on a buffer at the oversampled rate, in place
+    /// 
+    /// # Arguments
+    /// * `buffer` - Samples at the host rate
+    /// * `shape` - Per-sample nonlinearity, called `factor` times per sample
+    pub fn process(&mut self, buffer: &mut [f32], mut shape: impl FnMut(f32) -> f32) {
+        match self.factor {
+            1 => {
+                for sample in buffer.iter_mut() {
+                    *sample = shape(*sample);
+                }
+            }
+            2 => {
+                for sample in buffer.iter_mut() {
+                    let [a, b] = self.up[0].process(*sample);
+                    *sample = self.down[0].process([shape(a), shape(b)]);
+                }
+            }
+            _ => {
+                for sample in buffer.iter_mut() {
+                    let mut mid = self.up[0].process(*sample);
+                    for x in mid.iter_mut() {
+                        let [a, b] = self.up[1].process(*x);
+                        let y = self.down[1].process([shape(a), shape(b)]);
+                        *x = core::mem::replace(&mut self.align, y);
+                    }
+                    *sample = self.down[0].process(mid);
+                }
+            }
+        }
+    }
+    
+    /// Clear the filters, keeping the factor
+    pub fn reset(&mut self) {
+        let factor = self.factor;
+        *self = Self::new();
+        self.factor = factor;
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::PI;
+    
+    const BLOCK: usize = 128;
+    
+    /// Run `input` through an oversampler in blocks
+    fn render(oversampler: &mut Oversampler, input: &[f32], shape: impl Fn(f32) -> f32) -> Vec<f32> {
+        let mut output = input.to_vec();
+        for block in output.chunks_mut(BLOCK) {
+            oversampler.process(block, &shape);
+        }
+        output
+    }
+    
+    #[test]
+    fn test_latency_and_passband() {
+        assert!((HALFBAND.iter().sum::<f32>() - 0.25).abs() < 1e-6);
+        
+        for (factor, latency) in [(1, 0), (2, 23), (4, 35), (3, 0)] {
+            let mut oversampler = Oversampler::new();
+            oversampler.set_factor(factor);
+            assert_eq!(oversampler.latency_samples(), latency, "{factor}x");
+            
+            // An impulse comes out centered on the reported latency
+            let mut impulse = vec![0.0; 256];
+            impulse[0] = 1.0;
+            let response = render(&mut oversampler, &impulse, |x| x);
+            let peak = (0..response.len()).max_by(|&a, &b| response[a].abs().total_cmp(&response[b].abs())).unwrap();
+            assert_eq!(peak, latency as usize, "{factor}x peak");
+            
+            // DC and a sine at 0.3 of the sample rate pass at unity gain
+            oversampler.reset();
+            for freq in [0.0, 0.3] {
+                let input: Vec<f32> = (0..4096).map(|n| (2.0 * PI * freq * n as f64).cos() as f32).collect();
+                let output = render(&mut oversampler, &input, |x| x);
+                for n in 2048..4096 {
+                    let error = (output[n] - input[n - latency as usize]).abs();
+                    assert!(error < 0.01, "{factor}x, {freq}: error {error} at {n}");
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn test_oversampling_reduces_hard_clip_aliasing() {
+        // 4096 samples hold exactly 419 cycles, so the sine, its harmonics
+        // and their aliases all fall on DFT bins (and never on each other)
+        const LEN: usize = 4096;
+        const CYCLES: usize = 419;
+        let input: Vec<f32> = (0..3 * LEN)
+            .map(|n| (2.0 * PI * (CYCLES * n) as f64 / LEN as f64).sin() as f32)
+            .collect();
+        let hard_clip = |x: f32| (4.0 * x).clamp(-1.0, 1.0);
+        
+        // Energy below 0.4 of the sample rate that isn't the fundamental
+        // or the 3rd harmonic, relative to the fundamental
+        let aliasing = |factor: u32| {
+            let mut oversampler = Oversampler::new();
+            oversampler.set_factor(factor);
+            let output = render(&mut oversampler, &input, hard_clip);
+            let window = &output[2 * LEN..];
+            let power = |bin: usize| {
+                let (mut re, mut im) = (0.0f64, 0.0f64);
+                for (n, &y) in window.iter().enumerate() {
+                    let phase = 2.0 * PI * ((bin * n) % LEN) as f64 / LEN as f64;
+                    re += y as f64 * phase.cos();
+                    im -= y as f64 * phase.sin();
+                }
+                re * re + im * im
+            };
+            let aliased: f64 = (1..LEN * 2 / 5)
+                .filter(|&bin| bin != CYCLES && bin != 3 * CYCLES)
+                .map(power)
+                .sum();
+            aliased / power(CYCLES)
+        };
+        
+        let plain = aliasing(1);
+        let oversampled = aliasing(4);
+        assert!(plain > 1e-3, "1x aliasing {plain}");
+        // Hard clipping has harmonics past any rate, so some still alias at
+        // 4x, but 20dB or more less
+        assert!(oversampled < plain * 0.01, "4x aliasing {oversampled} vs 1x {plain}");
+    }
+}
@@ -0,0 +1,241 @@
+//! Block Profiler
+//!
+//! Times each process call, so hosts can see how close the DSP gets to the
+//! audio callback deadline in the browser (the benchmarks only measure
+//! native cost). Keeps the last, minimum, average and maximum block time.
+//!
+//! # Clock
+//! WASM has no cycle counter, so the host supplies the clock: the module
+//! imports `env.dsp_now_ms`, which the worklet backs with `performance.now`.
+//! Native builds (tests, benchmarks) use `std::time::Instant`.
+//!
+//! # Overhead
+//! Profiling is off by default. Disabled, a block costs one flag check and
+//! the clock is never read.
+
+use crate::memory;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CLOCK
+// ============================================================================
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+extern "C" {
+    /// Host clock in milliseconds (`performance.now`)
+    fn dsp_now_ms() -> f64;
+}
+
+/// Current time in milliseconds from an arbitrary origin
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_ms() -> f64 {
+    // SAFETY: Plain import without arguments or memory access
+    unsafe { dsp_now_ms() }
+}
+
+/// Current time in milliseconds from an arbitrary origin
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now_ms() -> f64 {
+    static ORIGIN: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    ORIGIN.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+// ============================================================================
+// PROFILER STATE
+// ============================================================================
+
+/// Block time statistics of one engine
+struct Profiler {
+    enabled: bool,
+    /// Blocks timed since profiling was enabled
+    blocks: u32,
+    /// Sum of the block times (f64 so long runs don't lose precision)
+    total_ms: f64,
+    last_ms: f32,
+    min_ms: f32,
+    max_ms: f32,
+}
+
+impl Profiler {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            blocks: 0,
+            total_ms: 0.0,
+            last_ms: 0.0,
+            min_ms: 0.0,
+            max_ms: 0.0,
+        }
+    }
+    
+    fn record(&mut self, elapsed_ms: f32) {
+        if self.blocks == 0 {
+            self.min_ms = elapsed_ms;
+            self.max_ms = elapsed_ms;
+        } else {
+            self.min_ms = self.min_ms.min(elapsed_ms);
+            self.max_ms = self.max_ms.max(elapsed_ms);
+        }
+        self.blocks = self.blocks.saturating_add(1);
+        self.total_ms += elapsed_ms as f64;
+        self.last_ms = elapsed_ms;
+    }
+}
+
+/// Profiler state of every engine in the pool
+static mut STATES: [Profiler; memory::MAX_ENGINES] =
+    [const { Profiler::new() }; memory::MAX_ENGINES];
+
+/// Profiler state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Profiler {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROFILING
+// ============================================================================
+
+/// Run one block of processing, timing it if profiling is enabled
+#[inline]
+pub fn measure(process: impl FnOnce()) {
+    // SAFETY: Single-threaded WASM context
+    if !unsafe { (*state()).enabled } {
+        process();
+        return;
+    }
+    
+    let start = now_ms();
+    process();
+    let elapsed_ms = (now_ms() - start) as f32;
+    unsafe {
+        // SAFETY: Single-threaded WASM context; `process` has returned
+        (*state()).record(elapsed_ms.max(0.0));
+    }
+}
+
+/// Switch profiling on or off
+/// 
+/// Enabling starts a fresh set of statistics; disabling keeps the last
+/// ones readable.
+pub fn set_enabled(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &mut *state();
+        if enabled && !st.enabled {
+            *st = Profiler::new();
+        }
+        st.enabled = enabled;
+    }
+}
+
+// ============================================================================
+// REPORT
+// ============================================================================
+
+/// Block time statistics handed to JS
+/// 
+/// Laid out in C format: JS reads `blocks` as a u32 and the times (in
+/// milliseconds) as the four f32s after it.
+#[repr(C)]
+pub struct ProfileReport {
+    /// Blocks timed since profiling was enabled
+    pub blocks: u32,
+    pub last_ms: f32,
+    pub min_ms: f32,
+    pub avg_ms: f32,
+    pub max_ms: f32,
+}
+
+/// Report returned to JS (rewritten on every `profile_report` call)
+static mut REPORT: ProfileReport = ProfileReport {
+    blocks: 0,
+    last_ms: 0.0,
+    min_ms: 0.0,
+    avg_ms: 0.0,
+    max_ms: 0.0,
+};
+
+/// Fill in the profile report for the selected engine
+/// 
+/// # Returns
+/// Pointer to the report, valid until the next call
+pub fn profile_report() -> *const ProfileReport {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &*state();
+        let report = addr_of_mut!(REPORT);
+        *report = ProfileReport {
+            blocks: st.blocks,
+            last_ms: st.last_ms,
+            min_ms: st.min_ms,
+            avg_ms: if st.blocks > 0 { (st.total_ms / st.blocks as f64) as f32 } else { 0.0 },
+            max_ms: st.max_ms,
+        };
+        report
+    }
+}
+
+/// Stop profiling and clear the statistics
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = Profiler::new();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Busy block taking at least `ms` milliseconds
+    fn block(ms: f64) {
+        let start = now_ms();
+        while now_ms() - start < ms {}
+    }
+    
+    #[test]
+    fn test_profiling_toggle_and_statistics() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, 128);
+        set_enabled(true);
+        set_enabled(false);
+        
+        // Disabled: blocks run but aren't counted
+        let mut ran = 0;
+        for _ in 0..3 {
+            measure(|| ran += 1);
+        }
+        assert_eq!(ran, 3);
+        let report = unsafe { &*profile_report() };
+        assert_eq!(report.blocks, 0);
+        
+        set_enabled(true);
+        for ms in [1.0, 3.0, 2.0] {
+            measure(|| block(ms));
+        }
+        let report = unsafe { &*profile_report() };
+        assert_eq!(report.blocks, 3);
+        assert!(report.min_ms >= 1.0 && report.max_ms >= 3.0);
+        assert!(report.min_ms < report.avg_ms && report.avg_ms < report.max_ms);
+        assert!(report.last_ms >= 2.0);
+        
+        // Disabling freezes the statistics, re-enabling starts over
+        set_enabled(false);
+        measure(|| block(1.0));
+        assert_eq!(unsafe { (*profile_report()).blocks }, 3);
+        set_enabled(true);
+        assert_eq!(unsafe { (*profile_report()).blocks }, 0);
+        measure(|| block(0.5));
+        assert_eq!(unsafe { (*profile_report()).blocks }, 1);
+        
+        set_enabled(false);
+    }
+}
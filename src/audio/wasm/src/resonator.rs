@@ -0,0 +1,384 @@
+//! Resonator
+//!
+//! Karplus-Strong style string resonator: a feedback comb filter tuned to
+//! a pitch rings at its fundamental and harmonics. The input excites it
+//! continuously (e.g. noise or a pad turning into a drone), and `pluck`
+//! adds a one-period noise burst like a plucked string.
+//!
+//! # Tuning
+//! The loop delay is `sample_rate / frequency` samples, which is rarely a
+//! whole number: rounding it would put a 1kHz resonator at 48kHz up to 18
+//! cents off. The comb reads the whole samples from its buffer and an
+//! all-pass interpolator adds the fraction, exact at the fundamental. The
+//! damping filter in the loop delays the fundamental too, so its phase
+//! delay is taken off the comb delay.
+//!
+//! # Decay and Damping
+//! The decay is the time the fundamental takes to fall by 60dB. The
+//! feedback is divided by the damping filter's gain at the fundamental,
+//! so damping only shortens the harmonics above it. The feedback is
+//! capped below 1, which limits the longest decay at heavy damping.
+//!
+//! A resonance with loop gain g boosts broadband input energy by
+//! `1 / (1 - g²)`. Continuous input is scaled by the inverse of that
+//! boost averaged across the spectrum, so broadband input keeps roughly
+//! its RMS level at any decay and damping.
+
+use crate::delay::CombFilter;
+use crate::filters::OnePole;
+use crate::memory;
+use crate::noise::NoiseGenerator;
+use core::f32::consts::{PI, TAU};
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Comb buffer length: a period at the lowest frequency at 192kHz, plus
+/// room for the interpolator
+const RESONATOR_CAPACITY: usize = 16384;
+
+/// Frequency range in Hz (also capped at a quarter of the sample rate)
+const MIN_FREQUENCY: f32 = 20.0;
+const MAX_FREQUENCY: f32 = 5000.0;
+
+const _: () = assert!(RESONATOR_CAPACITY as f32 > 192000.0 / MIN_FREQUENCY + 2.0);
+
+/// Decay range in seconds (time to fall by 60dB)
+const MIN_DECAY: f32 = 0.05;
+const MAX_DECAY: f32 = 30.0;
+
+/// Damping cutoff at no damping, as a fraction of the sample rate
+const OPEN_CUTOFF: f32 = 0.45;
+
+/// Damping cutoff at full damping, as a multiple of the fundamental
+const DAMPED_CUTOFF: f32 = 2.0;
+
+/// Frequencies the loop gain is averaged over for the input gain
+const INPUT_GAIN_POINTS: usize = 32;
+
+// ============================================================================
+// RESONATOR
+// ============================================================================
+
+/// Tuned comb resonator (one channel)
+pub struct Resonator {
+    comb: CombFilter<RESONATOR_CAPACITY>,
+    sample_rate: f32,
+    frequency: f32,
+    decay: f32,
+    damping: f32,
+    /// Gain of continuous input
+    input_gain: f32,
+    /// Pluck burst source and the burst samples still to play
+    noise: NoiseGenerator,
+    pluck_remaining: usize,
+    /// Removes the DC the loop can build up (outside the loop, so the
+    /// tuning is unaffected)
+    dc_blocker: OnePole,
+}
+
+impl Resonator {
+    /// Create a resonator at 220Hz with a 2s decay
+    /// 
+    /// # Arguments
+    /// * `seed` - Seed of the pluck noise (differently seeded resonators
+    ///   get uncorrelated plucks)
+    pub const fn new(seed: u32) -> Self {
+        Self {
+            comb: CombFilter::new(),
+            sample_rate: 0.0,
+            frequency: 220.0,
+            decay: 2.0,
+            damping: 0.0,
+            input_gain: 1.0,
+            noise: NoiseGenerator::new(seed),
+            pluck_remaining: 0,
+            dc_blocker: OnePole::new(),
+        }
+    }
+    
+    /// Set the pitch
+    /// 
+    /// # Arguments
+    /// * `frequency` - Fundamental in Hz (20-5000, and at most a quarter of
+    ///   the sample rate)
+    /// * `sample_rate` - Sample rate in Hz
+    pub fn set_frequency(&mut self, frequency: f32, sample_rate: f32) {
+        let frequency = frequency.clamp(MIN_FREQUENCY, MAX_FREQUENCY.min(0.25 * sample_rate));
+        if frequency != self.frequency || sample_rate != self.sample_rate {
+            self.frequency = frequency;
+            self.sample_rate = sample_rate;
+            self.dc_blocker.set_dc_blocker(sample_rate);
+            self.update();
+        }
+    }
+    
+    /// Set the time the fundamental takes to fall by 60dB (0.05-30s)
+    pub fn set_decay(&mut self, seconds: f32) {
+        let seconds = seconds.clamp(MIN_DECAY, MAX_DECAY);
+        if seconds != self.decay {
+            self.decay = seconds;
+            self.update();
+        }
+    }
+    
+    /// Set how much faster the harmonics decay than the fundamental
+    /// 
+    /// # Arguments
+    /// * `amount` - 0 = bright (harmonics ring nearly as long), 1 = dark
+    ///   (cutoff at twice the fundamental)
+    pub fn set_damping(&mut self, amount: f32) {
+        let amount = amount.clamp(0.0, 1.0);
+        if amount != self.damping {
+            self.damping = amount;
+            self.update();
+        }
+    }
+    
+    /// Retune the comb after a parameter change
+    fn update(&mut self) {
+        if self.sample_rate <= 0.0 {
+            return;
+        }
+        let omega = TAU * self.frequency / self.sample_rate;
+        let open = OPEN_CUTOFF * self.sample_rate;
+        let damped = (DAMPED_CUTOFF * self.frequency).min(open);
+        self.comb.set_damping(open * (damped / open).powf(self.damping), self.sample_rate);
+        
+        // The comb takes the damping filter's delay at the fundamental out
+        // of the loop; its gain is made up in the feedback
+        self.comb.set_delay_samples_frac(self.sample_rate / self.frequency);
+        let (damping_gain, _) = self.comb.damping_response(omega);
+        let decay_gain = 10.0f32.powf(-3.0 / (self.decay * self.frequency));
+        self.comb.set_feedback(decay_gain / damping_gain);
+        
+        // Average boost of broadband input across the spectrum
+        let feedback = self.comb.feedback();
+        let boost = (0..INPUT_GAIN_POINTS)
+            .map(|i| {
+                let loop_gain = feedback * self.comb.damping_response(PI * (i as f32 + 0.5) / INPUT_GAIN_POINTS as f32).0;
+                1.0 / (1.0 - loop_gain * loop_gain)
+            })
+            .sum::<f32>()
+            / INPUT_GAIN_POINTS as f32;
+        self.input_gain = boost.sqrt().recip();
+    }
+    
+    /// Excite the resonator with a one-period noise burst
+    pub fn pluck(&mut self) {
+        if self.sample_rate > 0.0 {
+            self.pluck_remaining = (self.sample_rate / self.frequency).round() as usize;
+        }
+    }
+    
+    /// Process a single sample
+    #[inline]
+    pub fn process(&mut self, input: f32) -> f32 {
+        let mut excitation = input * self.input_gain;
+        if self.pluck_remaining > 0 {
+            self.pluck_remaining -= 1;
+            excitation += self.noise.next();
+        }
+        let y = self.comb.process(excitation);
+        self.dc_blocker.process(y)
+    }
+    
+    /// Silence the resonator
+    pub fn clear(&mut self) {
+        self.comb.clear();
+        self.dc_blocker.reset();
+        self.pluck_remaining = 0;
+    }
+}
+
+/// Left and right resonators of every engine in the pool
+static mut STATES: [[Resonator; 2]; memory::MAX_ENGINES] =
+    [const { [Resonator::new(24680), Resonator::new(13579)] }; memory::MAX_ENGINES];
+
+/// Resonators of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut [Resonator; 2] {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process the resonator
+/// 
+/// # Arguments
+/// * `frequency` - Fundamental in Hz (20-5000)
+/// * `decay` - Time for the fundamental to fall by 60dB (0.05-30s)
+/// * `damping` - Harmonic damping (0 = bright, 1 = dark)
+/// * `mix` - Mix between dry (0) and resonator (1) signal
+pub fn process(frequency: f32, decay: f32, damping: f32, mix: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the resonator state
+        let resonators = &mut *state();
+        let sample_rate = memory::sample_rate();
+        let mix = mix.clamp(0.0, 1.0);
+        for (channel, resonator) in resonators.iter_mut().enumerate() {
+            resonator.set_frequency(frequency, sample_rate);
+            resonator.set_decay(decay);
+            resonator.set_damping(damping);
+            let input = memory::input_slice(channel as u32);
+            let output = memory::output_slice_mut(channel as u32);
+            for (y, &x) in output.iter_mut().zip(input) {
+                *y = x * (1.0 - mix) + resonator.process(x) * mix;
+            }
+        }
+    }
+}
+
+/// Pluck both channels (the burst plays from the next processed block)
+pub fn pluck() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        for resonator in (*state()).iter_mut() {
+            resonator.pluck();
+        }
+    }
+}
+
+/// Silence the resonators
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        for resonator in (*state()).iter_mut() {
+            resonator.clear();
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::PI;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    
+    /// Magnitude of the Hann-windowed DTFT of `signal` at `freq` Hz
+    fn magnitude_at(signal: &[f32], freq: f64) -> f64 {
+        let len = signal.len() as f64;
+        let (step_sin, step_cos) = (2.0 * PI * freq / SAMPLE_RATE as f64).sin_cos();
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        // Rotating phasor instead of a sin/cos per sample
+        let (mut c, mut s) = (1.0f64, 0.0f64);
+        for (n, &x) in signal.iter().enumerate() {
+            let window = 0.5 - 0.5 * (2.0 * PI * n as f64 / len).cos();
+            re += x as f64 * window * c;
+            im -= x as f64 * window * s;
+            (c, s) = (c * step_cos - s * step_sin, s * step_cos + c * step_sin);
+        }
+        re.hypot(im)
+    }
+    
+    /// Spectral peak within ±25 cents of `freq`, by golden-section search
+    fn peak_near(signal: &[f32], freq: f64) -> f64 {
+        let ratio = 2.0f64.powf(25.0 / 1200.0);
+        let (mut low, mut high) = (freq / ratio, freq * ratio);
+        let golden = (5.0f64.sqrt() - 1.0) / 2.0;
+        while high - low > freq * 1e-5 {
+            let a = high - golden * (high - low);
+            let b = low + golden * (high - low);
+            if magnitude_at(signal, a) > magnitude_at(signal, b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        0.5 * (low + high)
+    }
+    
+    #[test]
+    fn test_pluck_rings_at_set_frequency() {
+        // Frequencies whose periods are far from whole samples (rounding
+        // the delay of the highest would miss by over 100 cents)
+        for frequency in [55.0, 261.63, 440.0, 1046.5, 3520.0] {
+            for damping in [0.0, 0.5, 1.0] {
+                let mut resonator = Resonator::new(1);
+                resonator.set_frequency(frequency, SAMPLE_RATE);
+                resonator.set_decay(3.0);
+                resonator.set_damping(damping);
+                resonator.pluck();
+                let output: Vec<f32> = (0..SAMPLE_RATE as usize).map(|_| resonator.process(0.0)).collect();
+                
+                let peak = peak_near(&output, frequency as f64);
+                let cents = 1200.0 * (peak / frequency as f64).log2();
+                assert!(cents.abs() < 3.0, "{frequency}Hz, damping {damping}: {cents} cents off");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_decay_and_continuous_excitation() {
+        let mut resonator = Resonator::new(1);
+        resonator.set_frequency(440.0, SAMPLE_RATE);
+        resonator.set_decay(0.5);
+        resonator.pluck();
+        let output: Vec<f32> = (0..SAMPLE_RATE as usize).map(|_| resonator.process(0.0)).collect();
+        
+        // The fundamental falls by 60dB over the decay time (the harmonics
+        // faster), measured in windows 0.5s apart
+        let early = magnitude_at(&output[..4800], 440.0);
+        let late = magnitude_at(&output[24000..28800], 440.0);
+        let drop_db = 20.0 * (late / early).log10();
+        assert!((drop_db + 60.0).abs() < 1.0, "fundamental dropped {drop_db}dB in 0.5s");
+        
+        // Broadband input comes out within 3dB of its level at any decay
+        for (decay, damping) in [(0.1, 0.0), (2.0, 0.0), (20.0, 0.0), (2.0, 1.0), (20.0, 1.0)] {
+            let mut resonator = Resonator::new(1);
+            resonator.set_frequency(220.0, SAMPLE_RATE);
+            resonator.set_decay(decay);
+            resonator.set_damping(damping);
+            let mut noise = NoiseGenerator::new(7);
+            let output: Vec<f32> = (0..4 * SAMPLE_RATE as usize).map(|_| resonator.process(noise.next())).collect();
+            let level = (output[SAMPLE_RATE as usize..].iter().map(|y| y * y).sum::<f32>() / (3.0 * SAMPLE_RATE)).sqrt();
+            assert!(level > 0.577 * 0.7 && level < 0.577 * 1.4, "decay {decay}, damping {damping}: RMS {level}");
+        }
+    }
+    
+    #[test]
+    fn test_process_mixes_dry_and_resonance() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, 128);
+        reset();
+        
+        unsafe {
+            for i in 0..128 {
+                *memory::get_input_buffer(0).add(i) = (i as f32 * 0.1).sin();
+                *memory::get_input_buffer(1).add(i) = (i as f32 * 0.2).sin();
+            }
+        }
+        process(440.0, 1.0, 0.5, 0.0);
+        unsafe {
+            assert_eq!(memory::output_slice_mut(0), memory::input_slice(0));
+            assert_eq!(memory::output_slice_mut(1), memory::input_slice(1));
+        }
+        
+        // A pluck rings on after the input stops
+        unsafe {
+            memory::get_input_buffer(0).write_bytes(0, 128);
+            memory::get_input_buffer(1).write_bytes(0, 128);
+        }
+        pluck();
+        process(440.0, 1.0, 0.5, 1.0);
+        for _ in 0..10 {
+            process(440.0, 1.0, 0.5, 1.0);
+        }
+        let level = unsafe { memory::output_slice_mut(0).iter().fold(0.0f32, |peak, y| peak.max(y.abs())) };
+        assert!(level > 0.05, "ringing level {level}");
+        reset();
+    }
+}
@@ -0,0 +1,430 @@
+//! Saturation
+//!
+//! Memoryless waveshaper with selectable curves. The input is multiplied by
+//! the drive, shaped, then scaled back down so raising the drive adds
+//! harmonics rather than just level.
+//!
+//! # Gain Compensation
+//! The post-gain is `1 / sqrt(drive)`, halfway (in dB) between no
+//! compensation and `1 / drive`. Quiet material, which the curves leave
+//! nearly linear, gets louder by that much; material driven well into the
+//! curve, which the curve holds near ±1, gets quieter by the same amount.
+//! At drive 1 the curve is applied unscaled.
+//!
+//! # Foldback
+//! Foldback reflects the signal at ±1 instead of limiting it: 1.5 folds
+//! down to 0.5, 2.5 to -0.5, and so on with a period of 4. Its output
+//! stays within ±1 at any drive, but high drives fold many times per cycle
+//! and get very bright.
+//!
+//! # Oversampling
+//! The curves can run at 2x or 4x the sample rate (see `Oversampler`) to
+//! keep the harmonics above Nyquist from aliasing. The dry signal is
+//! delayed by the same latency, so the mix doesn't comb filter.
+
+use crate::memory;
+use crate::oversampling::Oversampler;
+use crate::utils;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Drive range (linear pre-gain; 1 = 0dB, 100 = +40dB)
+const MIN_DRIVE: f32 = 1.0;
+const MAX_DRIVE: f32 = 100.0;
+
+/// Dry delay line length (a power of two above the longest oversampling
+/// latency)
+const DRY_DELAY_CAPACITY: usize = 64;
+const DRY_DELAY_MASK: usize = DRY_DELAY_CAPACITY - 1;
+
+// ============================================================================
+// CURVES
+// ============================================================================
+
+/// Transfer curve of the waveshaper
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SaturationCurve {
+    /// `tanh(x)`, the smooth default (matches `utils::soft_clip`)
+    Tanh,
+    /// `1.5 * (x - x³/3)`, flat beyond ±1; mostly odd low harmonics
+    Cubic,
+    /// `2/π * atan(x)`, softer knee than tanh that approaches ±1 slowly
+    Arctan,
+    /// Limits to ±1
+    HardClip,
+    /// Reflects at ±1
+    Foldback,
+}
+
+impl SaturationCurve {
+    /// Curve from its export index (unknown values fall back to Tanh)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => SaturationCurve::Cubic,
+            2 => SaturationCurve::Arctan,
+            3 => SaturationCurve::HardClip,
+            4 => SaturationCurve::Foldback,
+            _ => SaturationCurve::Tanh,
+        }
+    }
+    
+    /// Apply the curve to one sample
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            SaturationCurve::Tanh => libm::tanhf(x),
+            SaturationCurve::Cubic => {
+                if x.abs() >= 1.0 {
+                    x.signum()
+                } else {
+                    1.5 * (x - x * x * x / 3.0)
+                }
+            }
+            SaturationCurve::Arctan => core::f32::consts::FRAC_2_PI * libm::atanf(x),
+            SaturationCurve::HardClip => x.clamp(-1.0, 1.0),
+            SaturationCurve::Foldback => fold(x),
+        }
+    }
+    
+    /// Apply the curve to one sample, tanh from the lookup table
+    /// (`utils::fast_tanh`, within 1e-5 of the exact curve)
+    #[inline]
+    pub fn apply_fast(self, x: f32) -> f32 {
+        match self {
+            SaturationCurve::Tanh => utils::fast_tanh(x),
+            curve => curve.apply(x),
+        }
+    }
+}
+
+/// Reflect `x` into [-1, 1] (a triangle wave of period 4 through the origin)
+#[inline]
+fn fold(x: f32) -> f32 {
+    ((x - 1.0).rem_euclid(4.0) - 2.0).abs() - 1.0
+}
+
+// ============================================================================
+// SATURATOR
+// ============================================================================
+
+/// Waveshaper with drive and gain compensation
+pub struct Saturator {
+    drive: f32,
+    /// Post-gain, `1 / sqrt(drive)`
+    makeup: f32,
+    curve: SaturationCurve,
+    /// Whether the curve uses the table-based approximations
+    fast_math: bool,
+}
+
+impl Saturator {
+    pub const fn new() -> Self {
+        Self {
+            drive: 1.0,
+            makeup: 1.0,
+            curve: SaturationCurve::Tanh,
+            fast_math: false,
+        }
+    }
+    
+    /// Set the drive (linear pre-gain, clamped to 1-100; NaN counts as 1)
+    pub fn set_drive(&mut self, drive: f32) {
+        let drive = if drive.is_nan() { MIN_DRIVE } else { drive.clamp(MIN_DRIVE, MAX_DRIVE) };
+        if drive != self.drive {
+            self.drive = drive;
+            self.makeup = 1.0 / libm::sqrtf(drive);
+        }
+    }
+    
+    /// Set the transfer curve
+    pub fn set_curve(&mut self, curve: SaturationCurve) {
+        self.curve = curve;
+    }
+    
+    /// Select the table-based curve approximations over the exact curves
+    pub fn set_fast_math(&mut self, enabled: bool) {
+        self.fast_math = enabled;
+    }
+    
+    /// Saturate one sample
+    #[inline]
+    pub fn process(&self, x: f32) -> f32 {
+        let shaped = if self.fast_math {
+            self.curve.apply_fast(x * self.drive)
+        } else {
+            self.curve.apply(x * self.drive)
+        };
+        shaped * self.makeup
+    }
+}
+
+// ============================================================================
+// SATURATION STATE
+// ============================================================================
+
+/// Stereo saturation stage
+struct Saturation {
+    saturator: Saturator,
+    oversamplers: [Oversampler; 2],
+    /// Dry input of each channel, delayed to match the oversampling
+    dry: [[f32; DRY_DELAY_CAPACITY]; 2],
+    dry_pos: usize,
+}
+
+impl Saturation {
+    const fn new() -> Self {
+        Self {
+            saturator: Saturator::new(),
+            oversamplers: [Oversampler::new(); 2],
+            dry: [[0.0; DRY_DELAY_CAPACITY]; 2],
+            dry_pos: 0,
+        }
+    }
+}
+
+/// Saturation state of every engine in the pool
+static mut STATES: [Saturation; memory::MAX_ENGINES] =
+    [const { Saturation::new() }; memory::MAX_ENGINES];
+
+/// Saturation state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Saturation {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process the saturation stage
+/// 
+/// # Arguments
+/// * `drive` - Linear pre-gain (1-100)
+/// * `curve` - Transfer curve
+/// * `mix` - Mix between dry (0) and saturated (1) signal
+pub fn process(drive: f32, curve: SaturationCurve, mix: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the saturator state
+        let st = &mut *state();
+        st.saturator.set_drive(drive);
+        st.saturator.set_curve(curve);
+        let mix = mix.clamp(0.0, 1.0);
+        let latency = st.oversamplers[0].latency_samples() as usize;
+        
+        let start_pos = st.dry_pos;
+        let saturator = &st.saturator;
+        for (channel, (oversampler, dry)) in st.oversamplers.iter_mut().zip(st.dry.iter_mut()).enumerate() {
+            let input = memory::input_slice(channel as u32);
+            let output = memory::output_slice_mut(channel as u32);
+            output.copy_from_slice(input);
+            oversampler.process(output, |x| saturator.process(x));
+            
+            let mut pos = start_pos;
+            for (y, &x) in output.iter_mut().zip(input) {
+                pos = (pos + 1) & DRY_DELAY_MASK;
+                dry[pos] = x;
+                let delayed = dry[pos.wrapping_sub(latency) & DRY_DELAY_MASK];
+                *y = delayed * (1.0 - mix) + *y * mix;
+            }
+            st.dry_pos = pos;
+        }
+    }
+}
+
+// ============================================================================
+// PARAMETERS
+// ============================================================================
+
+/// Set the oversampling factor of the curves (1, 2 or 4; others run at 1x)
+/// 
+/// Changing the factor clears the oversampling filters and the dry delay.
+pub fn set_oversampling(factor: u32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        let st = &mut *state();
+        let old_factor = st.oversamplers[0].factor();
+        for oversampler in st.oversamplers.iter_mut() {
+            oversampler.set_factor(factor);
+        }
+        if st.oversamplers[0].factor() != old_factor {
+            st.dry = [[0.0; DRY_DELAY_CAPACITY]; 2];
+        }
+    }
+}
+
+/// Select the table-based curve approximations (see
+/// `SaturationCurve::apply_fast`); off by default
+pub fn set_fast_math(enabled: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).saturator.set_fast_math(enabled);
+    }
+}
+
+/// Delay the saturation stage adds to the output, in samples (the
+/// oversampling latency)
+pub fn latency_samples() -> u32 {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).oversamplers[0].latency_samples()
+    }
+}
+
+/// Restore the saturation stage to its initial state
+/// 
+/// Clears the oversampling filters and the dry delay, and goes back to 1x
+/// oversampling with exact curves.
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = Saturation::new();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+    
+    const BLOCK: usize = 128;
+    
+    fn assert_close(actual: f32, expected: f32, context: &str) {
+        assert!((actual - expected).abs() < 1e-6, "{context}: {actual} != {expected}");
+    }
+    
+    #[test]
+    fn test_curves_at_sample_points() {
+        let cases: [(SaturationCurve, [(f32, f32); 4]); 5] = [
+            (SaturationCurve::Tanh, [(0.0, 0.0), (0.5, 0.462_117_16), (-1.0, -0.761_594_2), (4.0, 0.999_329_3)]),
+            (SaturationCurve::Cubic, [(0.0, 0.0), (0.5, 0.6875), (-1.0, -1.0), (3.0, 1.0)]),
+            (SaturationCurve::Arctan, [(0.0, 0.0), (1.0, 0.5), (-1.0, -0.5), (1e6, 1.0)]),
+            (SaturationCurve::HardClip, [(0.0, 0.0), (0.3, 0.3), (-1.5, -1.0), (7.0, 1.0)]),
+            (SaturationCurve::Foldback, [(0.3, 0.3), (1.0, 1.0), (1.5, 0.5), (-0.7, -0.7)]),
+        ];
+        for (curve, points) in cases {
+            for (x, expected) in points {
+                assert_close(curve.apply(x), expected, &format!("{curve:?}({x})"));
+            }
+        }
+        
+        // Foldback keeps reflecting beyond ±1
+        for (x, expected) in [(2.0, 0.0), (2.5, -0.5), (3.0, -1.0), (4.5, 0.5), (5.0, 1.0), (-1.5, -0.5), (-3.0, 1.0), (-5.5, -0.5)] {
+            assert_close(SaturationCurve::Foldback.apply(x), expected, &format!("fold({x})"));
+        }
+        
+        // The fast curves only swap tanh for its table
+        for (curve, points) in cases {
+            for (x, _) in points {
+                let (exact, fast) = (curve.apply(x), curve.apply_fast(x));
+                if curve == SaturationCurve::Tanh {
+                    assert!((fast - exact).abs() < 1e-5, "fast tanh({x}): {fast} != {exact}");
+                } else {
+                    assert_eq!(fast, exact, "fast {curve:?}({x})");
+                }
+            }
+        }
+        
+        // Export indices
+        for (index, curve) in [SaturationCurve::Tanh, SaturationCurve::Cubic, SaturationCurve::Arctan, SaturationCurve::HardClip, SaturationCurve::Foldback].into_iter().enumerate() {
+            assert_eq!(SaturationCurve::from_index(index as u32), curve);
+        }
+        assert_eq!(SaturationCurve::from_index(99), SaturationCurve::Tanh);
+    }
+    
+    #[test]
+    fn test_tanh_at_unity_drive_matches_soft_clip() {
+        let mut saturator = Saturator::new();
+        saturator.set_drive(1.0);
+        saturator.set_curve(SaturationCurve::Tanh);
+        for i in -40..=40 {
+            let x = i as f32 * 0.1;
+            assert_eq!(saturator.process(x), utils::soft_clip(x), "x = {x}");
+        }
+    }
+    
+    #[test]
+    fn test_drive_is_gain_compensated() {
+        let mut saturator = Saturator::new();
+        saturator.set_curve(SaturationCurve::HardClip);
+        saturator.set_drive(4.0);
+        
+        // Quiet input gains sqrt(4), clipped input is halved
+        assert_close(saturator.process(0.1), 0.2, "quiet");
+        assert_close(saturator.process(0.9), 0.5, "clipped");
+        
+        // The level of a -12dB sine stays within 6dB from clean to square
+        // (uncompensated it would rise by ~15dB)
+        let sine: Vec<f32> = (0..BLOCK).map(|i| 0.25 * (2.0 * core::f32::consts::PI * i as f32 / BLOCK as f32).sin()).collect();
+        for curve in [SaturationCurve::Tanh, SaturationCurve::Cubic, SaturationCurve::Arctan] {
+            saturator.set_curve(curve);
+            let mut levels = Vec::new();
+            for drive in [1.0, 4.0, 16.0] {
+                saturator.set_drive(drive);
+                let buffer: Vec<f32> = sine.iter().map(|&x| saturator.process(x)).collect();
+                levels.push((buffer.iter().map(|y| y * y).sum::<f32>() / BLOCK as f32).sqrt());
+            }
+            let spread = levels.iter().fold(0.0f32, |max, &l| max.max(l)) / levels.iter().fold(f32::MAX, |min, &l| min.min(l));
+            assert!(spread < 2.0, "{curve:?} levels {levels:?}");
+        }
+        
+        // NaN drive falls back to unity
+        saturator.set_drive(f32::NAN);
+        assert_close(saturator.process(0.5), SaturationCurve::Arctan.apply(0.5), "NaN drive");
+    }
+    
+    #[test]
+    fn test_process_mixes_with_dry_input() {
+        let _guard = memory::test_lock();
+        memory::init_engine(48000.0, BLOCK as u32);
+        unsafe {
+            for channel in 0..2 {
+                for i in 0..BLOCK {
+                    *memory::get_input_buffer(channel).add(i) = (i as f32 / BLOCK as f32) * 2.0 - 1.0;
+                }
+            }
+        }
+        let input = unsafe { memory::input_slice(1).to_vec() };
+        
+        process(1.0, SaturationCurve::HardClip, 0.0);
+        assert_eq!(unsafe { memory::output_slice_mut(1).to_vec() }, input);
+        
+        process(9.0, SaturationCurve::Foldback, 1.0);
+        let output = unsafe { memory::output_slice_mut(1).to_vec() };
+        for (&y, &x) in output.iter().zip(&input) {
+            assert_close(y, fold(9.0 * x) / 3.0, "wet");
+        }
+        
+        process(9.0, SaturationCurve::Foldback, 0.25);
+        let output = unsafe { memory::output_slice_mut(0).to_vec() };
+        for (&y, &x) in output.iter().zip(&input) {
+            assert_close(y, 0.75 * x + 0.25 * fold(9.0 * x) / 3.0, "mixed");
+        }
+        
+        // Oversampled, the dry signal is delayed by the reported latency
+        set_oversampling(4);
+        let latency = latency_samples() as usize;
+        assert_eq!(latency, 35);
+        let mut output = Vec::new();
+        for _ in 0..2 {
+            process(4.0, SaturationCurve::Tanh, 0.0);
+            output.extend(unsafe { memory::output_slice_mut(0).to_vec() });
+        }
+        assert!(output[..latency].iter().all(|&y| y == 0.0));
+        assert_eq!(output[latency..BLOCK], input[..BLOCK - latency]);
+        assert_eq!(output[BLOCK..BLOCK + latency], input[BLOCK - latency..]);
+        
+        set_oversampling(1);
+        assert_eq!(latency_samples(), 0);
+    }
+}
@@ -0,0 +1,338 @@
+//! Shimmer Reverb
+//!
+//! Reverb with a pitch shifter in its feedback loop: every trip around the
+//! loop moves the tail up by the shift, so an octave shift stacks rising
+//! octaves over the decaying input, the classic ambient shimmer.
+//!
+//! # Reverb
+//! Freeverb-style: per channel, eight damped feedback combs in parallel
+//! into four all-passes in series. The right channel's delays are spread
+//! by a few samples for width. The size sets the comb feedback and so the
+//! decay (about 1-10s). The comb input is scaled by the inverse of the
+//! combs' broadband energy boost, so the reverb roughly keeps the power
+//! of its input at any size.
+//!
+//! # Feedback Loop
+//! The wet output of each block goes through the spectral pitch shifter,
+//! and the shifted block is added to the next block's reverb input,
+//! scaled by the shimmer amount. The pitch shifter's analysis frame
+//! delays each trip by about the spectral FFT size (2048 samples by
+//! default).
+//!
+//! # Stability
+//! With the reverb at unity power gain and the pitch shifter at about
+//! unity, the loop gain is capped by MAX_SHIMMER_GAIN. That holds for
+//! broadband energy, but the combs all resonate at DC and a shift leaves
+//! DC in place, so the feedback is high-passed. Without a shift the tail
+//! would land on the comb resonances it came from and ring forever, so
+//! the loop gain fades out for shifts below UNISON_SHIFT. A soft clipper
+//! on the feedback bounds whatever is left.
+
+use crate::delay::{AllPassFilter, CombFilter};
+use crate::filters::Biquad;
+use crate::memory;
+use crate::spectral;
+use crate::utils;
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Comb delays in samples at 44.1kHz (Freeverb's tuning)
+const COMB_DELAYS: [usize; NUM_COMBS] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const NUM_COMBS: usize = 8;
+
+/// All-pass delays in samples at 44.1kHz
+const ALLPASS_DELAYS: [usize; NUM_ALLPASSES] = [556, 441, 341, 225];
+const NUM_ALLPASSES: usize = 4;
+
+/// Extra delay of the right channel's combs and all-passes at 44.1kHz
+const STEREO_SPREAD: usize = 23;
+
+/// Sample rate the delays are tuned at
+const TUNING_RATE: f32 = 44100.0;
+
+/// Comb buffer length: the longest comb at 192kHz
+const COMB_CAPACITY: usize = 8192;
+
+const _: () = assert!(((1617 + STEREO_SPREAD) as f32 * 192000.0 / TUNING_RATE) < COMB_CAPACITY as f32);
+
+/// Comb feedback at size 0 and 1
+const MIN_FEEDBACK: f32 = 0.7;
+const MAX_FEEDBACK: f32 = 0.98;
+
+/// Damping cutoff of the comb feedback in Hz (keeps the shimmer octaves
+/// from turning harsh)
+const DAMPING_CUTOFF: f32 = 6000.0;
+
+/// All-pass diffusion coefficient
+const ALLPASS_COEFFICIENT: f32 = 0.5;
+
+/// Loop gain at shimmer amount 1
+const MAX_SHIMMER_GAIN: f32 = 0.8;
+
+/// Feedback high-pass cutoff in Hz
+const FEEDBACK_HIGHPASS: f32 = 200.0;
+
+/// Shift in semitones below which the loop gain fades out
+const UNISON_SHIFT: f32 = 0.1;
+
+// ============================================================================
+// SHIMMER REVERB
+// ============================================================================
+
+/// Stereo reverb with pitch-shifted feedback
+struct Shimmer {
+    combs: [[CombFilter<COMB_CAPACITY>; NUM_COMBS]; 2],
+    allpasses: [[AllPassFilter; NUM_ALLPASSES]; 2],
+    /// Pitch-shifted wet output of the last block (left and right)
+    feedback: [[f32; memory::MAX_BUFFER_SIZE]; 2],
+    /// High-pass on the feedback
+    feedback_filter: Biquad,
+    /// Sample rate the delays are set for (0 = not yet)
+    sample_rate: f32,
+    /// Size the comb feedback is set for
+    size: f32,
+    /// Comb input gain (unity broadband power)
+    input_gain: f32,
+}
+
+impl Shimmer {
+    const fn new() -> Self {
+        Self {
+            combs: [const { [const { CombFilter::new() }; NUM_COMBS] }; 2],
+            allpasses: [const { [const { AllPassFilter::new() }; NUM_ALLPASSES] }; 2],
+            feedback: [[0.0; memory::MAX_BUFFER_SIZE]; 2],
+            feedback_filter: Biquad::new(),
+            sample_rate: 0.0,
+            size: -1.0,
+            input_gain: 0.0,
+        }
+    }
+    
+    /// Scale the delays to a sample rate
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        let scale = sample_rate / TUNING_RATE;
+        for (channel, spread) in [0, STEREO_SPREAD].into_iter().enumerate() {
+            for (comb, delay) in self.combs[channel].iter_mut().zip(COMB_DELAYS) {
+                comb.set_delay_samples(((delay + spread) as f32 * scale).round() as usize);
+                comb.set_damping(DAMPING_CUTOFF, sample_rate);
+            }
+            for (allpass, delay) in self.allpasses[channel].iter_mut().zip(ALLPASS_DELAYS) {
+                allpass.set_delay_samples(((delay + spread) as f32 * scale).round() as usize);
+                allpass.set_coefficient(ALLPASS_COEFFICIENT);
+            }
+        }
+        self.feedback_filter.set_highpass(FEEDBACK_HIGHPASS, 0.707, sample_rate);
+        self.feedback_filter.snap_coefficients();
+        self.sample_rate = sample_rate;
+    }
+    
+    /// Set the size (0-1)
+    fn set_size(&mut self, size: f32) {
+        let size = size.clamp(0.0, 1.0);
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        let feedback = MIN_FEEDBACK + (MAX_FEEDBACK - MIN_FEEDBACK) * size;
+        for comb in self.combs.iter_mut().flatten() {
+            comb.set_feedback(feedback);
+        }
+        // Each comb boosts broadband energy by 1 / (1 - g²) (less with
+        // the damping, so this errs on the quiet side)
+        let boost = NUM_COMBS as f32 / (1.0 - feedback * feedback);
+        self.input_gain = boost.sqrt().recip();
+    }
+    
+    /// Reverb one sample of one channel
+    #[inline]
+    fn process_channel(&mut self, channel: usize, input: f32) -> f32 {
+        let x = input * self.input_gain;
+        let mut y: f32 = self.combs[channel].iter_mut().map(|comb| comb.process(x)).sum();
+        for allpass in self.allpasses[channel].iter_mut() {
+            y = allpass.process(y);
+        }
+        y
+    }
+    
+    fn clear(&mut self) {
+        for comb in self.combs.iter_mut().flatten() {
+            comb.clear();
+        }
+        for allpass in self.allpasses.iter_mut().flatten() {
+            allpass.clear();
+        }
+        for feedback in self.feedback.iter_mut() {
+            feedback.fill(0.0);
+        }
+        self.feedback_filter.reset();
+    }
+}
+
+/// Shimmer reverb of every engine in the pool
+static mut STATES: [Shimmer; memory::MAX_ENGINES] = [const { Shimmer::new() }; memory::MAX_ENGINES];
+
+/// Shimmer reverb of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut Shimmer {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Process the shimmer reverb
+/// 
+/// # Arguments
+/// * `size` - Reverb size (0-1, longer decay)
+/// * `shift` - Feedback pitch shift in semitones (-24 to +24)
+/// * `amount` - Shimmer feedback (0 = plain reverb, 1 = strongest)
+/// * `mix` - Mix between dry (0) and wet (1), equal power
+pub fn process(size: f32, shift: f32, amount: f32, mix: f32) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O and work buffers
+        // don't overlap the shimmer state
+        let shimmer = &mut *state();
+        let sample_rate = memory::sample_rate();
+        if sample_rate != shimmer.sample_rate {
+            shimmer.set_sample_rate(sample_rate);
+        }
+        shimmer.set_size(size);
+        let shift = shift.clamp(-24.0, 24.0);
+        let loop_gain = amount.clamp(0.0, 1.0) * MAX_SHIMMER_GAIN * (shift.abs() / UNISON_SHIFT).min(1.0);
+        let (dry_gain, wet_gain) = utils::equal_power_gains(mix);
+        
+        let len = memory::buffer_size() as usize;
+        let input = [memory::input_slice(0), memory::input_slice(1)];
+        let output = [memory::output_slice_mut(0), memory::output_slice_mut(1)];
+        let (wet_l, wet_r) = (&mut memory::work_buffer_1()[..len], &mut memory::work_buffer_2()[..len]);
+        
+        // Mono reverb input: the dry input plus last block's shifted tail
+        for i in 0..len {
+            let feedback = shimmer.feedback_filter.process(0.5 * (shimmer.feedback[0][i] + shimmer.feedback[1][i]));
+            let feedback = utils::soft_clip(feedback * loop_gain);
+            let x = 0.5 * (input[0][i] + input[1][i]) + feedback;
+            wet_l[i] = shimmer.process_channel(0, x);
+            wet_r[i] = shimmer.process_channel(1, x);
+        }
+        
+        // Shift this block's tail for the next block
+        let [feedback_l, feedback_r] = &mut shimmer.feedback;
+        spectral::pitch_shift_block([&*wet_l, &*wet_r], [&mut feedback_l[..len], &mut feedback_r[..len]], shift, false);
+        
+        for (channel, wet) in [&*wet_l, &*wet_r].into_iter().enumerate() {
+            for ((y, &x), &w) in output[channel].iter_mut().zip(input[channel]).zip(wet) {
+                *y = x * dry_gain + w * wet_gain;
+            }
+        }
+    }
+}
+
+/// Silence the reverb and its feedback
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).clear();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+    use rustfft::{num_complex::Complex, FftPlanner};
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    const BLOCK: usize = 128;
+    
+    /// Left wet output of `blocks` blocks, the input a burst of `excite`
+    /// samples followed by silence
+    fn render(size: f32, shift: f32, amount: f32, blocks: usize, excite: impl Fn(usize) -> f32) -> Vec<f32> {
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        spectral::reset();
+        reset();
+        let mut rendered = Vec::with_capacity(blocks * BLOCK);
+        for b in 0..blocks {
+            unsafe {
+                for i in 0..BLOCK {
+                    let x = excite(b * BLOCK + i);
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            process(size, shift, amount, 1.0);
+            rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+        }
+        rendered
+    }
+    
+    /// Energy within a quarter tone of `freq`
+    fn band_energy(signal: &[f32], freq: f32) -> f32 {
+        let n = signal.len();
+        let mut spectrum: Vec<Complex<f32>> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+        let ratio = 2.0f32.powf(0.5 / 12.0);
+        let bins = (freq / ratio * n as f32 / SAMPLE_RATE) as usize..=(freq * ratio * n as f32 / SAMPLE_RATE) as usize;
+        spectrum[bins].iter().map(|c| c.norm_sqr()).sum()
+    }
+    
+    #[test]
+    fn test_shimmer_builds_octave_above_input() {
+        let _guard = memory::test_lock();
+        
+        // 100ms Hann-windowed 440Hz burst, so the reverb alone has little
+        // energy outside 440Hz
+        let burst = 4800;
+        let tone = |n: usize| {
+            if n < burst {
+                let window = 0.5 - 0.5 * (2.0 * PI * n as f32 / burst as f32).cos();
+                (2.0 * PI * 440.0 * n as f32 / SAMPLE_RATE).sin() * window * 0.5
+            } else {
+                0.0
+            }
+        };
+        
+        // One second into the tail
+        let tail = |output: &[f32]| output[48000..48000 + 32768].to_vec();
+        let plain = tail(&render(0.7, 12.0, 0.0, 640, tone));
+        let shimmer = tail(&render(0.7, 12.0, 1.0, 640, tone));
+        
+        let octave = band_energy(&shimmer, 880.0);
+        assert!(octave > 100.0 * band_energy(&plain, 880.0), "no shimmer an octave up");
+        assert!(octave > 0.1 * band_energy(&shimmer, 440.0), "octave {octave} against the input tone");
+    }
+    
+    #[test]
+    fn test_feedback_stays_bounded() {
+        let _guard = memory::test_lock();
+        
+        // Full-scale noise burst into the largest size at full shimmer
+        let mut rng: u32 = 1;
+        let noise: Vec<f32> = (0..12000)
+            .map(|_| {
+                rng = rng.wrapping_mul(1664525).wrapping_add(1013904223);
+                (rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+            })
+            .collect();
+        for shift in [0.0, 0.005, 7.0, 12.0, -12.0, 24.0] {
+            let output = render(1.0, shift, 1.0, 2250, |n| noise.get(n).copied().unwrap_or(0.0));
+            let rms = |seconds: core::ops::Range<usize>| {
+                let window = &output[seconds.start * 48000..seconds.end * 48000];
+                (window.iter().map(|x| x * x).sum::<f32>() / window.len() as f32).sqrt()
+            };
+            let peak = output.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+            assert!(peak < 1.0, "shift {shift}: peak {peak}");
+            assert!(rms(5..6) < 0.1 * rms(0..1), "shift {shift}: tail didn't decay");
+        }
+    }
+}
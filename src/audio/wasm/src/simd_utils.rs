@@ -13,9 +13,20 @@
 //! # Usage
 //! All functions have automatic fallback to scalar operations
 //! when SIMD is not available (though it always is with our build config).
+//! The SIMD paths can also be switched off at runtime with
+//! `set_simd_enabled` (e.g. to A/B test against scalar); builds without
+//! simd128 ignore the flag and always run scalar.
 
 #[cfg(target_arch = "wasm32")]
 use core::arch::wasm32::*;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::ptr::addr_of;
+use core::ptr::addr_of_mut;
+use crate::utils;
+use rustfft::num_complex::Complex;
+
+/// Runtime SIMD switch (only read when SIMD is compiled in)
+static mut SIMD_ENABLED: bool = true;
 
 // ============================================================================
 // FEATURE DETECTION
@@ -31,6 +42,28 @@ pub const fn simd_available() -> bool {
     { false }
 }
 
+/// Enable or disable the SIMD paths at runtime
+/// 
+/// Has no effect when SIMD is compiled out.
+pub fn set_simd_enabled(enabled: bool) {
+    // SAFETY: Single-threaded WASM context
+    unsafe {
+        *addr_of_mut!(SIMD_ENABLED) = enabled;
+    }
+}
+
+/// Whether buffer operations currently take the SIMD path
+#[inline]
+pub fn simd_enabled() -> bool {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    {
+        // SAFETY: Single-threaded WASM context
+        unsafe { *addr_of!(SIMD_ENABLED) }
+    }
+    #[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+    { false }
+}
+
 // ============================================================================
 // BUFFER OPERATIONS
 // ============================================================================
@@ -43,9 +76,19 @@ pub const fn simd_available() -> bool {
 /// # Arguments
 /// * `buffer` - Mutable slice of f32 samples
 /// * `scale` - Scalar multiplier
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn scale_buffer(buffer: &mut [f32], scale: f32) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return scale_buffer_simd(buffer, scale);
+    }
+    scale_buffer_scalar(buffer, scale)
+}
+
+/// Scale buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn scale_buffer_simd(buffer: &mut [f32], scale: f32) {
     let scale_v = f32x4_splat(scale);
     let chunks = buffer.len() / 4;
     
@@ -68,9 +111,8 @@ pub fn scale_buffer(buffer: &mut [f32], scale: f32) {
 }
 
 /// Scale buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn scale_buffer(buffer: &mut [f32], scale: f32) {
+fn scale_buffer_scalar(buffer: &mut [f32], scale: f32) {
     for sample in buffer.iter_mut() {
         *sample *= scale;
     }
@@ -84,9 +126,19 @@ pub fn scale_buffer(buffer: &mut [f32], scale: f32) {
 /// * `a` - First source buffer
 /// * `b` - Second source buffer  
 /// * `out` - Output buffer (can alias `a` or `b`)
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn add_buffers(a: &[f32], b: &[f32], out: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return add_buffers_simd(a, b, out);
+    }
+    add_buffers_scalar(a, b, out)
+}
+
+/// Add buffers - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn add_buffers_simd(a: &[f32], b: &[f32], out: &mut [f32]) {
     let len = a.len().min(b.len()).min(out.len());
     let chunks = len / 4;
     
@@ -107,9 +159,8 @@ pub fn add_buffers(a: &[f32], b: &[f32], out: &mut [f32]) {
 }
 
 /// Add buffers - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn add_buffers(a: &[f32], b: &[f32], out: &mut [f32]) {
+fn add_buffers_scalar(a: &[f32], b: &[f32], out: &mut [f32]) {
     let len = a.len().min(b.len()).min(out.len());
     for i in 0..len {
         out[i] = a[i] + b[i];
@@ -119,9 +170,19 @@ pub fn add_buffers(a: &[f32], b: &[f32], out: &mut [f32]) {
 /// Mix buffer B into buffer A with gain: a[i] += b[i] * gain
 /// 
 /// Common operation for summing grains, adding reverb, etc.
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn mix_buffer(a: &mut [f32], b: &[f32], gain: f32) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return mix_buffer_simd(a, b, gain);
+    }
+    mix_buffer_scalar(a, b, gain)
+}
+
+/// Mix buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn mix_buffer_simd(a: &mut [f32], b: &[f32], gain: f32) {
     let len = a.len().min(b.len());
     let chunks = len / 4;
     let gain_v = f32x4_splat(gain);
@@ -144,9 +205,8 @@ pub fn mix_buffer(a: &mut [f32], b: &[f32], gain: f32) {
 }
 
 /// Mix buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn mix_buffer(a: &mut [f32], b: &[f32], gain: f32) {
+fn mix_buffer_scalar(a: &mut [f32], b: &[f32], gain: f32) {
     let len = a.len().min(b.len());
     for i in 0..len {
         a[i] += b[i] * gain;
@@ -154,9 +214,19 @@ pub fn mix_buffer(a: &mut [f32], b: &[f32], gain: f32) {
 }
 
 /// Copy buffer using SIMD (faster than memcpy for aligned f32 data)
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn copy_buffer(src: &[f32], dst: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return copy_buffer_simd(src, dst);
+    }
+    copy_buffer_scalar(src, dst)
+}
+
+/// Copy buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn copy_buffer_simd(src: &[f32], dst: &mut [f32]) {
     let len = src.len().min(dst.len());
     let chunks = len / 4;
     
@@ -174,17 +244,26 @@ pub fn copy_buffer(src: &[f32], dst: &mut [f32]) {
 }
 
 /// Copy buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn copy_buffer(src: &[f32], dst: &mut [f32]) {
+fn copy_buffer_scalar(src: &[f32], dst: &mut [f32]) {
     let len = src.len().min(dst.len());
     dst[..len].copy_from_slice(&src[..len]);
 }
 
 /// Clear buffer (fill with zeros) using SIMD
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn clear_buffer(buffer: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return clear_buffer_simd(buffer);
+    }
+    clear_buffer_scalar(buffer)
+}
+
+/// Clear buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn clear_buffer_simd(buffer: &mut [f32]) {
     let zero = f32x4_splat(0.0);
     let chunks = buffer.len() / 4;
     
@@ -201,9 +280,8 @@ pub fn clear_buffer(buffer: &mut [f32]) {
 }
 
 /// Clear buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn clear_buffer(buffer: &mut [f32]) {
+fn clear_buffer_scalar(buffer: &mut [f32]) {
     buffer.fill(0.0);
 }
 
@@ -215,9 +293,19 @@ pub fn clear_buffer(buffer: &mut [f32]) {
 /// 
 /// Linearly interpolates gain from `start_gain` to `end_gain` across the buffer.
 /// Useful for crossfades, fade in/out, and envelope application.
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn apply_gain_ramp(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return apply_gain_ramp_simd(buffer, start_gain, end_gain);
+    }
+    apply_gain_ramp_scalar(buffer, start_gain, end_gain)
+}
+
+/// Apply gain ramp - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn apply_gain_ramp_simd(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
     let len = buffer.len();
     if len == 0 { return; }
     
@@ -255,9 +343,8 @@ pub fn apply_gain_ramp(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
 }
 
 /// Apply gain ramp - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn apply_gain_ramp(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
+fn apply_gain_ramp_scalar(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
     let len = buffer.len();
     if len == 0 { return; }
     
@@ -270,13 +357,77 @@ pub fn apply_gain_ramp(buffer: &mut [f32], start_gain: f32, end_gain: f32) {
     }
 }
 
+/// Equal-power crossfade between two buffers using SIMD
+/// 
+/// out[i] = a[i] * cos(t·π/2) + b[i] * sin(t·π/2), so the summed power of
+/// uncorrelated signals stays constant across the transition.
+/// 
+/// # Arguments
+/// * `a` - Buffer faded out (t = 0 yields exactly `a`)
+/// * `b` - Buffer faded in (t = 1 yields exactly `b`)
+/// * `out` - Output buffer
+/// * `t` - Crossfade position (0-1)
+#[inline]
+pub fn crossfade(a: &[f32], b: &[f32], out: &mut [f32], t: f32) {
+    let (gain_a, gain_b) = utils::equal_power_gains(t);
+    
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return crossfade_simd(a, b, out, gain_a, gain_b);
+    }
+    crossfade_scalar(a, b, out, gain_a, gain_b)
+}
+
+/// Crossfade - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn crossfade_simd(a: &[f32], b: &[f32], out: &mut [f32], gain_a: f32, gain_b: f32) {
+    let len = a.len().min(b.len()).min(out.len());
+    let chunks = len / 4;
+    let gain_a_v = f32x4_splat(gain_a);
+    let gain_b_v = f32x4_splat(gain_b);
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        unsafe {
+            let va = v128_load(a.as_ptr().add(offset) as *const v128);
+            let vb = v128_load(b.as_ptr().add(offset) as *const v128);
+            let mixed = f32x4_add(f32x4_mul(va, gain_a_v), f32x4_mul(vb, gain_b_v));
+            v128_store(out.as_mut_ptr().add(offset) as *mut v128, mixed);
+        }
+    }
+    
+    for i in (chunks * 4)..len {
+        out[i] = a[i] * gain_a + b[i] * gain_b;
+    }
+}
+
+/// Crossfade - scalar fallback
+#[inline]
+fn crossfade_scalar(a: &[f32], b: &[f32], out: &mut [f32], gain_a: f32, gain_b: f32) {
+    let len = a.len().min(b.len()).min(out.len());
+    for i in 0..len {
+        out[i] = a[i] * gain_a + b[i] * gain_b;
+    }
+}
+
 /// Soft clip buffer using tanh approximation
 /// 
 /// Fast approximation: x / (1 + |x|)
 /// Provides gentle saturation without hard clipping.
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn soft_clip_buffer(buffer: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return soft_clip_buffer_simd(buffer);
+    }
+    soft_clip_buffer_scalar(buffer)
+}
+
+/// Soft clip buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn soft_clip_buffer_simd(buffer: &mut [f32]) {
     let one = f32x4_splat(1.0);
     let chunks = buffer.len() / 4;
     
@@ -299,9 +450,8 @@ pub fn soft_clip_buffer(buffer: &mut [f32]) {
 }
 
 /// Soft clip buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn soft_clip_buffer(buffer: &mut [f32]) {
+fn soft_clip_buffer_scalar(buffer: &mut [f32]) {
     for sample in buffer.iter_mut() {
         let x = *sample;
         *sample = x / (1.0 + x.abs());
@@ -309,9 +459,19 @@ pub fn soft_clip_buffer(buffer: &mut [f32]) {
 }
 
 /// Hard clip buffer to [-limit, +limit]
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn hard_clip_buffer(buffer: &mut [f32], limit: f32) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return hard_clip_buffer_simd(buffer, limit);
+    }
+    hard_clip_buffer_scalar(buffer, limit)
+}
+
+/// Hard clip buffer - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn hard_clip_buffer_simd(buffer: &mut [f32], limit: f32) {
     let min_v = f32x4_splat(-limit);
     let max_v = f32x4_splat(limit);
     let chunks = buffer.len() / 4;
@@ -331,9 +491,8 @@ pub fn hard_clip_buffer(buffer: &mut [f32], limit: f32) {
 }
 
 /// Hard clip buffer - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn hard_clip_buffer(buffer: &mut [f32], limit: f32) {
+fn hard_clip_buffer_scalar(buffer: &mut [f32], limit: f32) {
     for sample in buffer.iter_mut() {
         *sample = sample.clamp(-limit, limit);
     }
@@ -342,9 +501,19 @@ pub fn hard_clip_buffer(buffer: &mut [f32], limit: f32) {
 /// Stereo interleave: combine L and R into interleaved buffer
 /// 
 /// Output: [L0, R0, L1, R1, L2, R2, ...]
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn interleave_stereo(left: &[f32], right: &[f32], out: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return interleave_stereo_simd(left, right, out);
+    }
+    interleave_stereo_scalar(left, right, out)
+}
+
+/// Stereo interleave - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn interleave_stereo_simd(left: &[f32], right: &[f32], out: &mut [f32]) {
     let len = left.len().min(right.len()).min(out.len() / 2);
     
     // SIMD: process 4 samples from each channel (8 output samples)
@@ -379,9 +548,8 @@ pub fn interleave_stereo(left: &[f32], right: &[f32], out: &mut [f32]) {
 }
 
 /// Stereo interleave - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn interleave_stereo(left: &[f32], right: &[f32], out: &mut [f32]) {
+fn interleave_stereo_scalar(left: &[f32], right: &[f32], out: &mut [f32]) {
     let len = left.len().min(right.len()).min(out.len() / 2);
     for i in 0..len {
         out[i * 2] = left[i];
@@ -390,9 +558,19 @@ pub fn interleave_stereo(left: &[f32], right: &[f32], out: &mut [f32]) {
 }
 
 /// Stereo deinterleave: split interleaved buffer into L and R
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn deinterleave_stereo(interleaved: &[f32], left: &mut [f32], right: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return deinterleave_stereo_simd(interleaved, left, right);
+    }
+    deinterleave_stereo_scalar(interleaved, left, right)
+}
+
+/// Stereo deinterleave - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn deinterleave_stereo_simd(interleaved: &[f32], left: &mut [f32], right: &mut [f32]) {
     let len = left.len().min(right.len()).min(interleaved.len() / 2);
     let chunks = len / 4;
     
@@ -425,9 +603,8 @@ pub fn deinterleave_stereo(interleaved: &[f32], left: &mut [f32], right: &mut [f
 }
 
 /// Stereo deinterleave - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn deinterleave_stereo(interleaved: &[f32], left: &mut [f32], right: &mut [f32]) {
+fn deinterleave_stereo_scalar(interleaved: &[f32], left: &mut [f32], right: &mut [f32]) {
     let len = left.len().min(right.len()).min(interleaved.len() / 2);
     for i in 0..len {
         left[i] = interleaved[i * 2];
@@ -435,6 +612,148 @@ pub fn deinterleave_stereo(interleaved: &[f32], left: &mut [f32], right: &mut [f
     }
 }
 
+/// Sum stereo to mono: out[i] = (left[i] + right[i]) * 0.5
+#[inline]
+pub fn sum_to_mono(left: &[f32], right: &[f32], out: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return sum_to_mono_simd(left, right, out);
+    }
+    sum_to_mono_scalar(left, right, out)
+}
+
+/// Sum to mono - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn sum_to_mono_simd(left: &[f32], right: &[f32], out: &mut [f32]) {
+    let len = left.len().min(right.len()).min(out.len());
+    let chunks = len / 4;
+    let half = f32x4_splat(0.5);
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        unsafe {
+            let l = v128_load(left.as_ptr().add(offset) as *const v128);
+            let r = v128_load(right.as_ptr().add(offset) as *const v128);
+            let mono = f32x4_mul(f32x4_add(l, r), half);
+            v128_store(out.as_mut_ptr().add(offset) as *mut v128, mono);
+        }
+    }
+    
+    // Scalar remainder
+    for i in (chunks * 4)..len {
+        out[i] = (left[i] + right[i]) * 0.5;
+    }
+}
+
+/// Sum to mono - scalar fallback
+#[inline]
+fn sum_to_mono_scalar(left: &[f32], right: &[f32], out: &mut [f32]) {
+    let len = left.len().min(right.len()).min(out.len());
+    for i in 0..len {
+        out[i] = (left[i] + right[i]) * 0.5;
+    }
+}
+
+/// Spread mono to stereo: copy `mono` into both channels
+#[inline]
+pub fn spread_to_stereo(mono: &[f32], left: &mut [f32], right: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return spread_to_stereo_simd(mono, left, right);
+    }
+    spread_to_stereo_scalar(mono, left, right)
+}
+
+/// Spread to stereo - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn spread_to_stereo_simd(mono: &[f32], left: &mut [f32], right: &mut [f32]) {
+    let len = mono.len().min(left.len()).min(right.len());
+    let chunks = len / 4;
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        unsafe {
+            let v = v128_load(mono.as_ptr().add(offset) as *const v128);
+            v128_store(left.as_mut_ptr().add(offset) as *mut v128, v);
+            v128_store(right.as_mut_ptr().add(offset) as *mut v128, v);
+        }
+    }
+    
+    // Scalar remainder
+    for i in (chunks * 4)..len {
+        left[i] = mono[i];
+        right[i] = mono[i];
+    }
+}
+
+/// Spread to stereo - scalar fallback
+#[inline]
+fn spread_to_stereo_scalar(mono: &[f32], left: &mut [f32], right: &mut [f32]) {
+    let len = mono.len().min(left.len()).min(right.len());
+    left[..len].copy_from_slice(&mono[..len]);
+    right[..len].copy_from_slice(&mono[..len]);
+}
+
+/// Scale the stereo width in place via mid/side
+/// 
+/// mid = (l + r) / 2 is kept and side = (l - r) / 2 is scaled by `width`
+/// (0 = mono, 1 = unchanged, >1 = wider). Width 1 leaves the buffers
+/// untouched, bit for bit.
+#[inline]
+pub fn stereo_width(left: &mut [f32], right: &mut [f32], width: f32) {
+    if width == 1.0 {
+        return;
+    }
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return stereo_width_simd(left, right, width);
+    }
+    stereo_width_scalar(left, right, width)
+}
+
+/// Stereo width - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn stereo_width_simd(left: &mut [f32], right: &mut [f32], width: f32) {
+    let len = left.len().min(right.len());
+    let chunks = len / 4;
+    let half = f32x4_splat(0.5);
+    let side_gain = f32x4_splat(0.5 * width);
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        unsafe {
+            let l = v128_load(left.as_ptr().add(offset) as *const v128);
+            let r = v128_load(right.as_ptr().add(offset) as *const v128);
+            let mid = f32x4_mul(f32x4_add(l, r), half);
+            let side = f32x4_mul(f32x4_sub(l, r), side_gain);
+            v128_store(left.as_mut_ptr().add(offset) as *mut v128, f32x4_add(mid, side));
+            v128_store(right.as_mut_ptr().add(offset) as *mut v128, f32x4_sub(mid, side));
+        }
+    }
+    
+    // Scalar remainder
+    for i in (chunks * 4)..len {
+        let mid = (left[i] + right[i]) * 0.5;
+        let side = (left[i] - right[i]) * (0.5 * width);
+        left[i] = mid + side;
+        right[i] = mid - side;
+    }
+}
+
+/// Stereo width - scalar fallback
+#[inline]
+fn stereo_width_scalar(left: &mut [f32], right: &mut [f32], width: f32) {
+    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+        let mid = (*l + *r) * 0.5;
+        let side = (*l - *r) * (0.5 * width);
+        *l = mid + side;
+        *r = mid - side;
+    }
+}
+
 // ============================================================================
 // FILTER OPERATIONS
 // ============================================================================
@@ -485,9 +804,19 @@ pub fn biquad_process_buffer(
 /// Remove DC offset from buffer using SIMD
 /// 
 /// Subtracts the mean value from all samples.
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn remove_dc_offset(buffer: &mut [f32]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return remove_dc_offset_simd(buffer);
+    }
+    remove_dc_offset_scalar(buffer)
+}
+
+/// Remove DC offset - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn remove_dc_offset_simd(buffer: &mut [f32]) {
     if buffer.is_empty() { return; }
     
     // Calculate sum using SIMD
@@ -535,9 +864,8 @@ pub fn remove_dc_offset(buffer: &mut [f32]) {
 }
 
 /// Remove DC offset - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn remove_dc_offset(buffer: &mut [f32]) {
+fn remove_dc_offset_scalar(buffer: &mut [f32]) {
     if buffer.is_empty() { return; }
     
     let sum: f32 = buffer.iter().sum();
@@ -553,9 +881,19 @@ pub fn remove_dc_offset(buffer: &mut [f32]) {
 // ============================================================================
 
 /// Find peak absolute value in buffer using SIMD
-#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[inline]
 pub fn find_peak(buffer: &[f32]) -> f32 {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return find_peak_simd(buffer);
+    }
+    find_peak_scalar(buffer)
+}
+
+/// Find peak - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn find_peak_simd(buffer: &[f32]) -> f32 {
     if buffer.is_empty() { return 0.0; }
     
     let chunks = buffer.len() / 4;
@@ -587,12 +925,162 @@ pub fn find_peak(buffer: &[f32]) -> f32 {
 }
 
 /// Find peak - scalar fallback
-#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline]
-pub fn find_peak(buffer: &[f32]) -> f32 {
+fn find_peak_scalar(buffer: &[f32]) -> f32 {
     buffer.iter().map(|x| x.abs()).fold(0.0_f32, f32::max)
 }
 
+// ============================================================================
+// RMS METERING
+// ============================================================================
+
+/// Root-mean-square level of buffer using SIMD
+/// 
+/// # Returns
+/// sqrt(mean(x²)), or 0.0 for an empty buffer
+#[inline]
+pub fn rms(buffer: &[f32]) -> f32 {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return rms_simd(buffer);
+    }
+    rms_scalar(buffer)
+}
+
+/// RMS - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn rms_simd(buffer: &[f32]) -> f32 {
+    if buffer.is_empty() { return 0.0; }
+    
+    // Accumulate sum of squares using SIMD
+    let chunks = buffer.len() / 4;
+    let mut sum_v = f32x4_splat(0.0);
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        unsafe {
+            let v = v128_load(buffer.as_ptr().add(offset) as *const v128);
+            sum_v = f32x4_add(sum_v, f32x4_mul(v, v));
+        }
+    }
+    
+    // Horizontal sum
+    let mut total = unsafe {
+        f32x4_extract_lane::<0>(sum_v)
+            + f32x4_extract_lane::<1>(sum_v)
+            + f32x4_extract_lane::<2>(sum_v)
+            + f32x4_extract_lane::<3>(sum_v)
+    };
+    
+    // Add remainder
+    for i in (chunks * 4)..buffer.len() {
+        total += buffer[i] * buffer[i];
+    }
+    
+    (total / buffer.len() as f32).sqrt()
+}
+
+/// RMS - scalar fallback
+#[inline]
+fn rms_scalar(buffer: &[f32]) -> f32 {
+    if buffer.is_empty() { return 0.0; }
+    
+    let sum: f32 = buffer.iter().map(|x| x * x).sum();
+    (sum / buffer.len() as f32).sqrt()
+}
+
+// ============================================================================
+// SPECTRUM OPERATIONS
+// ============================================================================
+
+/// Complex multiply-accumulate using SIMD
+/// 
+/// acc[i] += x[i] * h[i] (the inner loop of partitioned convolution).
+/// Processes 4 complex values (interleaved re/im, two per v128) per
+/// iteration; any length works, the last 1-3 values take a shorter path.
+/// 
+/// # Arguments
+/// * `acc` - Accumulator spectrum
+/// * `x` - Input spectrum
+/// * `h` - Filter spectrum
+#[inline]
+pub fn complex_multiply_accumulate(acc: &mut [Complex<f32>], x: &[Complex<f32>], h: &[Complex<f32>]) {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd_enabled() {
+        return complex_multiply_accumulate_simd(acc, x, h);
+    }
+    complex_multiply_accumulate_scalar(acc, x, h)
+}
+
+/// Multiply-accumulate the two complex values at `offset`
+/// 
+/// # Safety
+/// `offset + 2` must be within all three slices.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline(always)]
+unsafe fn complex_multiply_accumulate_pair(
+    acc: &mut [Complex<f32>],
+    x: &[Complex<f32>],
+    h: &[Complex<f32>],
+    offset: usize,
+) {
+    // (re, im) = (x.re * h.re - x.im * h.im, x.re * h.im + x.im * h.re)
+    let sign = f32x4(-1.0, 1.0, -1.0, 1.0);
+    // Complex<f32> is repr(C) (re, im), so two fill a v128
+    let xv = v128_load(x.as_ptr().add(offset) as *const v128);
+    let hv = v128_load(h.as_ptr().add(offset) as *const v128);
+    let acc_ptr = acc.as_mut_ptr().add(offset) as *mut v128;
+    
+    let x_re = i32x4_shuffle::<0, 0, 2, 2>(xv, xv);
+    let x_im = i32x4_shuffle::<1, 1, 3, 3>(xv, xv);
+    let h_swapped = i32x4_shuffle::<1, 0, 3, 2>(hv, hv);
+    let product = f32x4_add(
+        f32x4_mul(x_re, hv),
+        f32x4_mul(f32x4_mul(x_im, h_swapped), sign),
+    );
+    v128_store(acc_ptr, f32x4_add(v128_load(acc_ptr), product));
+}
+
+/// Complex multiply-accumulate - SIMD path
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline]
+fn complex_multiply_accumulate_simd(acc: &mut [Complex<f32>], x: &[Complex<f32>], h: &[Complex<f32>]) {
+    let len = acc.len().min(x.len()).min(h.len());
+    let quads = len / 4;
+    
+    for i in 0..quads {
+        let offset = i * 4;
+        unsafe {
+            // Two independent pairs per iteration keep both multiply chains busy
+            complex_multiply_accumulate_pair(acc, x, h, offset);
+            complex_multiply_accumulate_pair(acc, x, h, offset + 2);
+        }
+    }
+    
+    // Remainder: one more pair, then a single scalar value
+    let mut remainder_start = quads * 4;
+    if len - remainder_start >= 2 {
+        unsafe {
+            complex_multiply_accumulate_pair(acc, x, h, remainder_start);
+        }
+        remainder_start += 2;
+    }
+    complex_multiply_accumulate_scalar(
+        &mut acc[remainder_start..len],
+        &x[remainder_start..len],
+        &h[remainder_start..len],
+    );
+}
+
+/// Complex multiply-accumulate - scalar fallback
+#[inline]
+fn complex_multiply_accumulate_scalar(acc: &mut [Complex<f32>], x: &[Complex<f32>], h: &[Complex<f32>]) {
+    for ((acc, x), h) in acc.iter_mut().zip(x).zip(h) {
+        *acc += x * h;
+    }
+}
+
 // ============================================================================
 // GRANULAR SYNTHESIS OPTIMIZATION
 // ============================================================================
@@ -603,23 +1091,29 @@ pub fn find_peak(buffer: &[f32]) -> f32 {
 /// Table size of 1024 provides sufficient resolution for smooth envelopes.
 pub const ENVELOPE_TABLE_SIZE: usize = 1024;
 
+/// cos(x) for x in [0, 2π], usable in const context
+/// 
+/// Folds x into [0, π/2] by symmetry, where a Taylor series to x¹⁰ is
+/// accurate to ~5e-7; the series alone diverges over the full period.
+const fn const_cos(x: f64) -> f64 {
+    use core::f64::consts::{FRAC_PI_2, PI, TAU};
+    // cos(x) = cos(2π - x)
+    let x = if x > PI { TAU - x } else { x };
+    // cos(x) = -cos(π - x)
+    let (x, sign) = if x > FRAC_PI_2 { (PI - x, -1.0) } else { (x, 1.0) };
+    let x2 = x * x;
+    let series = 1.0 - x2 / 2.0 * (1.0 - x2 / 12.0 * (1.0 - x2 / 30.0 * (1.0 - x2 / 56.0 * (1.0 - x2 / 90.0))));
+    sign * series
+}
+
 /// Static envelope lookup table - computed once at compile time
 /// Formula: 0.5 - 0.5 * cos(2π * phase) where phase = index / TABLE_SIZE
 pub static ENVELOPE_TABLE: [f32; ENVELOPE_TABLE_SIZE] = {
     let mut table = [0.0f32; ENVELOPE_TABLE_SIZE];
     let mut i = 0;
     while i < ENVELOPE_TABLE_SIZE {
-        // Use polynomial approximation of cos for const fn
-        // cos(x) ≈ 1 - x²/2 + x⁴/24 for small x
-        // For full period: cos(2πx) where x = i/N
-        let phase = (i as f32) / (ENVELOPE_TABLE_SIZE as f32);
-        let x = phase * 2.0 * core::f32::consts::PI;
-        // Taylor series approximation (sufficient for envelope smoothness)
-        let x2 = x * x;
-        let x4 = x2 * x2;
-        let x6 = x4 * x2;
-        let cos_approx = 1.0 - x2 / 2.0 + x4 / 24.0 - x6 / 720.0;
-        table[i] = 0.5 - 0.5 * cos_approx;
+        let phase = (i as f64) / (ENVELOPE_TABLE_SIZE as f64);
+        table[i] = (0.5 - 0.5 * const_cos(phase * core::f64::consts::TAU)) as f32;
         i += 1;
     }
     table
@@ -685,6 +1179,17 @@ pub fn envelope_4_simd(phases: v128) -> v128 {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_envelope_table_matches_hann() {
+        for (i, &value) in ENVELOPE_TABLE.iter().enumerate() {
+            let phase = i as f32 / ENVELOPE_TABLE_SIZE as f32;
+            let expected = 0.5 - 0.5 * (2.0 * core::f32::consts::PI * phase).cos();
+            assert!((value - expected).abs() < 1e-6, "entry {i}: {value} vs {expected}");
+        }
+        assert_eq!(envelope_lookup(0.0), 0.0);
+        assert!((envelope_lookup(0.5) - 1.0).abs() < 1e-6);
+    }
+    
     #[test]
     fn test_scale_buffer() {
         let mut buffer = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
@@ -706,4 +1211,222 @@ mod tests {
         let buffer = [-3.0, 1.0, 5.0, -2.0, 4.0];
         assert_eq!(find_peak(&buffer), 5.0);
     }
+    
+    #[test]
+    fn test_complex_multiply_accumulate() {
+        let x = [Complex::new(1.0, 2.0), Complex::new(0.5, -1.0), Complex::new(-3.0, 0.0)];
+        let h = [Complex::new(3.0, -1.0), Complex::new(2.0, 2.0), Complex::new(0.0, 1.0)];
+        let mut acc = [Complex::new(1.0, 1.0); 3];
+        complex_multiply_accumulate(&mut acc, &x, &h);
+        // (1+2i)(3-i) = 5+5i, (0.5-i)(2+2i) = 3-i, (-3)(i) = -3i
+        assert_eq!(acc, [Complex::new(6.0, 6.0), Complex::new(4.0, 0.0), Complex::new(1.0, -2.0)]);
+    }
+    
+    #[test]
+    fn test_complex_multiply_accumulate_matches_scalar_loop() {
+        // Every remainder after the four-value steps, and spectrum sizes
+        for len in [1, 2, 3, 4, 5, 6, 7, 1025, 4096] {
+            let to_complex = |x: Vec<f32>| x.chunks(2).map(|c| Complex::new(c[0], c[1])).collect::<Vec<_>>();
+            let x = to_complex(signal(2 * len, 1));
+            let h = to_complex(signal(2 * len, 2));
+            let start = to_complex(signal(2 * len, 3));
+            let mut expected = start.clone();
+            for ((acc, x), h) in expected.iter_mut().zip(&x).zip(&h) {
+                *acc += x * h;
+            }
+            let mut acc = start.clone();
+            complex_multiply_accumulate(&mut acc, &x, &h);
+            for (i, (acc, expected)) in acc.iter().zip(&expected).enumerate() {
+                assert!((acc - expected).norm() < 1e-5, "length {len}, value {i}: {acc} vs {expected}");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_sum_to_mono() {
+        let left = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let right = [3.0, 2.0, 1.0, 0.0, -5.0];
+        let mut out = [9.0; 6];
+        sum_to_mono(&left, &right, &mut out);
+        // Length clamps to the shortest slice; the extra output sample is untouched
+        assert_eq!(out, [2.0, 2.0, 2.0, 2.0, 0.0, 9.0]);
+    }
+    
+    #[test]
+    fn test_spread_to_stereo() {
+        let mono = [1.0, -2.0, 3.0, -4.0, 5.0];
+        let mut left = [0.0; 5];
+        let mut right = [9.0; 4];
+        spread_to_stereo(&mono, &mut left, &mut right);
+        assert_eq!(left, [1.0, -2.0, 3.0, -4.0, 0.0]);
+        assert_eq!(right, [1.0, -2.0, 3.0, -4.0]);
+        
+        let mut right = [0.0; 5];
+        spread_to_stereo(&mono, &mut left, &mut right);
+        assert_eq!(left, mono);
+        assert_eq!(right, mono);
+    }
+    
+    #[test]
+    fn test_stereo_width() {
+        let left = [0.25, -1.0, 0.25, 0.75, -0.5];
+        let right = [-0.75, 0.5, 1.0, -0.25, 0.125];
+        
+        // Unity width is bit-exact
+        let (mut l, mut r) = (left, right);
+        stereo_width(&mut l, &mut r, 1.0);
+        assert_eq!((l, r), (left, right));
+        
+        // Zero width collapses to the mid signal; width 2 doubles the side
+        stereo_width(&mut l, &mut r, 0.0);
+        assert_eq!(l, r);
+        assert_eq!(l, [-0.25, -0.25, 0.625, 0.25, -0.1875]);
+        let (mut l, mut r) = (left, right);
+        stereo_width(&mut l, &mut r, 2.0);
+        for i in 0..5 {
+            assert!(((l[i] - r[i]) - 2.0 * (left[i] - right[i])).abs() < 1e-6);
+            assert!(((l[i] + r[i]) - (left[i] + right[i])).abs() < 1e-6);
+        }
+    }
+    
+    #[test]
+    fn test_crossfade_equal_power() {
+        let a = [0.3, -1.0, 0.25, 0.9, -0.6];
+        let b = [-0.7, 0.5, 1.0, -0.2, 0.1];
+        let mut out = [0.0; 5];
+        
+        crossfade(&a, &b, &mut out, 0.0);
+        assert_eq!(out, a);
+        crossfade(&a, &b, &mut out, 1.0);
+        assert_eq!(out, b);
+        
+        // Midpoint: each side at -3dB, so the gains' powers sum to 1
+        let ones = [1.0; 5];
+        let zeros = [0.0; 5];
+        crossfade(&ones, &zeros, &mut out, 0.5);
+        let gain_db = 20.0 * out[4].log10();
+        assert!((gain_db + 3.0103).abs() < 1e-3, "midpoint gain {gain_db} dB");
+        for t in [0.1, 0.25, 0.5, 0.8] {
+            let (gain_a, gain_b) = utils::equal_power_gains(t);
+            assert!((gain_a * gain_a + gain_b * gain_b - 1.0).abs() < 1e-6);
+        }
+        
+        // A sine/cosine law: a third of the way in, b is at sin(π/6) = 0.5
+        crossfade(&zeros, &ones, &mut out, 1.0 / 3.0);
+        assert!((out[0] - 0.5).abs() < 1e-6, "gain {} at t = 1/3", out[0]);
+    }
+    
+    #[test]
+    fn test_rms() {
+        // Full-scale sine over 13 whole periods (1001 samples, not a multiple of 4)
+        let sine: Vec<f32> = (0..1001)
+            .map(|i| (2.0 * core::f32::consts::PI * i as f32 / 77.0).sin())
+            .collect();
+        let level = rms(&sine);
+        assert!((level - core::f32::consts::FRAC_1_SQRT_2).abs() < 1e-4, "sine RMS {level}");
+        
+        // Remainder samples count towards the mean
+        assert!((rms(&[1.0, 1.0, 1.0, 1.0, 6.0]) - 8.0f32.sqrt()).abs() < 1e-6);
+        assert_eq!(rms(&[]), 0.0);
+    }
+    
+    /// Run `f` once on the SIMD path and once on the scalar path
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    fn both_paths<T>(f: impl Fn() -> T) -> (T, T) {
+        set_simd_enabled(true);
+        assert!(simd_enabled());
+        let simd = f();
+        set_simd_enabled(false);
+        let scalar = f();
+        set_simd_enabled(true);
+        (simd, scalar)
+    }
+    
+    /// Deterministic test signal in [-2, 2)
+    fn signal(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state as f32 / u32::MAX as f32) * 4.0 - 2.0
+            })
+            .collect()
+    }
+    
+    // Native builds only have the scalar path; run with
+    // `cargo test --target wasm32-wasip1` (see .cargo/config.toml)
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    #[test]
+    fn test_simd_and_scalar_paths_match() {
+        let _guard = crate::memory::test_lock();
+        
+        for len in [1, 3, 5, 7, 13, 130] {
+            let a = signal(len, 1);
+            let b = signal(len, 2);
+            let stereo = signal(len * 2, 3);
+            
+            // Element-wise operations are bit-identical
+            let (simd, scalar) = both_paths(|| {
+                let mut scaled = a.clone();
+                scale_buffer(&mut scaled, 0.7);
+                let mut sum = vec![0.0; len];
+                add_buffers(&a, &b, &mut sum);
+                let mut mixed = a.clone();
+                mix_buffer(&mut mixed, &b, 0.3);
+                let mut copied = vec![0.0; len];
+                copy_buffer(&a, &mut copied);
+                let mut cleared = a.clone();
+                clear_buffer(&mut cleared);
+                let mut soft = a.clone();
+                soft_clip_buffer(&mut soft);
+                let mut hard = a.clone();
+                hard_clip_buffer(&mut hard, 0.5);
+                let mut interleaved = vec![0.0; len * 2];
+                interleave_stereo(&a, &b, &mut interleaved);
+                let mut left = vec![0.0; len];
+                let mut right = vec![0.0; len];
+                deinterleave_stereo(&stereo, &mut left, &mut right);
+                let mut mono = vec![0.0; len];
+                sum_to_mono(&a, &b, &mut mono);
+                let mut spread_l = vec![0.0; len];
+                let mut spread_r = vec![0.0; len];
+                spread_to_stereo(&a, &mut spread_l, &mut spread_r);
+                let mut faded = vec![0.0; len];
+                crossfade(&a, &b, &mut faded, 0.3);
+                let (mut wide_l, mut wide_r) = (a.clone(), b.clone());
+                stereo_width(&mut wide_l, &mut wide_r, 1.7);
+                let peak = vec![find_peak(&a)];
+                let to_complex = |x: &[f32]| x.chunks(2).map(|c| Complex::new(c[0], c[c.len() - 1])).collect::<Vec<_>>();
+                let mut acc = to_complex(&stereo);
+                complex_multiply_accumulate(&mut acc, &to_complex(&a), &to_complex(&b));
+                let spectrum: Vec<f32> = acc.iter().flat_map(|c| [c.re, c.im]).collect();
+                [faded, scaled, sum, mixed, copied, cleared, soft, hard, interleaved, left, right, mono, spread_l, spread_r, wide_l, wide_r, peak, spectrum]
+            });
+            assert_eq!(simd, scalar, "length {len}");
+            
+            // Ramps and means accumulate in a different order
+            let (simd, scalar) = both_paths(|| {
+                let mut ramp = a.clone();
+                apply_gain_ramp(&mut ramp, 0.2, 1.0);
+                let mut centered = a.clone();
+                remove_dc_offset(&mut centered);
+                centered.push(rms(&a));
+                (ramp, centered)
+            });
+            for (x, y) in simd.0.iter().chain(&simd.1).zip(scalar.0.iter().chain(&scalar.1)) {
+                assert!((x - y).abs() < 1e-5, "length {len}: {x} vs {y}");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_simd_flag_only_applies_when_compiled_in() {
+        let _guard = crate::memory::test_lock();
+        
+        set_simd_enabled(true);
+        assert_eq!(simd_enabled(), simd_available());
+        set_simd_enabled(false);
+        assert!(!simd_enabled());
+        set_simd_enabled(true);
+    }
 }
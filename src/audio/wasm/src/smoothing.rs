@@ -0,0 +1,129 @@
+//! Parameter Smoothing
+//!
+//! One-pole smoothing of parameters that are applied per sample, so
+//! setters automated once per block from JavaScript don't cause zipper
+//! noise. The process loop steps the smoother once per sample.
+//!
+//! # Usage
+//! ```ignore
+//! let mut mix = SmoothedParam::new(0.5);
+//! mix.set_time(10.0, sample_rate);
+//! mix.set_target(1.0);
+//!
+//! for sample in buffer.iter_mut() {
+//!     *sample *= mix.next();
+//! }
+//! ```
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default smoothing time constant in milliseconds
+pub const DEFAULT_SMOOTHING_MS: f32 = 10.0;
+
+/// Sample rate assumed until `set_time` is called
+const DEFAULT_SAMPLE_RATE: f32 = 48000.0;
+
+// ============================================================================
+// SMOOTHED PARAMETER
+// ============================================================================
+
+/// Parameter that glides towards its target with a one-pole lowpass
+#[derive(Clone, Copy)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    /// Fraction of the remaining distance covered per sample
+    alpha: f32,
+}
+
+impl SmoothedParam {
+    /// Create a settled parameter with the default smoothing time
+    pub const fn new(value: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            alpha: smoothing_alpha(DEFAULT_SMOOTHING_MS, DEFAULT_SAMPLE_RATE),
+        }
+    }
+    
+    /// Set the smoothing time constant
+    /// 
+    /// # Arguments
+    /// * `time_ms` - Time to cover ~63% of a change (0 = no smoothing)
+    /// * `sample_rate` - Rate at which `next` is called
+    pub fn set_time(&mut self, time_ms: f32, sample_rate: f32) {
+        self.alpha = smoothing_alpha(time_ms, sample_rate);
+    }
+    
+    /// Set the value to glide towards
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+    }
+    
+    /// Jump straight to a value (for initialization)
+    pub fn set_instant(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+    }
+    
+    /// Jump to the target, skipping the rest of the glide
+    pub fn snap(&mut self) {
+        self.current = self.target;
+    }
+    
+    /// Advance one sample and return the smoothed value
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        self.current += (self.target - self.current) * self.alpha;
+        self.current
+    }
+    
+    /// Current smoothed value
+    #[cfg(test)]
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+}
+
+/// Per-sample coefficient of a one-pole with the given time constant
+/// 
+/// Backward-Euler form, `1 / (1 + tau * sample_rate)`: stable for any time,
+/// 1 (no smoothing) at time 0, and cheap enough for a const context.
+const fn smoothing_alpha(time_ms: f32, sample_rate: f32) -> f32 {
+    let samples = time_ms * 0.001 * sample_rate;
+    if samples > 0.0 { 1.0 / (1.0 + samples) } else { 1.0 }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_smoothing_time_and_instant_bypass() {
+        let mut param = SmoothedParam::new(0.0);
+        param.set_time(10.0, 48000.0);
+        param.set_target(1.0);
+        
+        // Glides monotonically, ~63% of the way after one time constant
+        let mut previous = 0.0;
+        for _ in 0..480 {
+            let value = param.next();
+            assert!(value > previous && value < 1.0);
+            previous = value;
+        }
+        assert!((param.value() - (1.0 - (-1.0f32).exp())).abs() < 0.01, "{}", param.value());
+        
+        param.set_instant(0.25);
+        assert_eq!(param.next(), 0.25);
+        
+        param.set_time(0.0, 48000.0);
+        param.set_target(0.75);
+        assert_eq!(param.next(), 0.75);
+    }
+}
@@ -0,0 +1,226 @@
+//! Soft Reset
+//!
+//! Clears a processor's state (reverb tails, frozen spectra, playing
+//! grains) without a click. Clearing it mid-block would cut the output off
+//! wherever it stands, so a requested reset is deferred to the processor's
+//! next block instead:
+//! 1. That block is rendered as usual and faded out over RESET_FADE_MS
+//!    (or the whole block, if shorter); the rest of it is silent
+//! 2. The processor's state is cleared after the block
+//! 3. Its next block fades in from silence over the same length
+//!
+//! A processor that isn't being run keeps its reset pending until it is.
+
+use crate::{convolution, diffuser, flanger, granular, memory, resonator, shimmer, spectral};
+use core::ptr::addr_of_mut;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Number of processors with a soft reset
+const MAX_PROCESSORS: usize = 7;
+
+/// Length of the fade out before and the fade in after a reset in
+/// milliseconds
+const RESET_FADE_MS: f32 = 5.0;
+
+/// Processors with state a reset clears
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Processor {
+    Granular = 0,
+    /// Shared by the spectral freeze/shift, vocoder, pitch shift and
+    /// spectral gate
+    Spectral = 1,
+    Convolution = 2,
+    Flanger = 3,
+    Resonator = 4,
+    Shimmer = 5,
+    Diffuser = 6,
+}
+
+impl Processor {
+    const ALL: [Processor; MAX_PROCESSORS] = [
+        Processor::Granular,
+        Processor::Spectral,
+        Processor::Convolution,
+        Processor::Flanger,
+        Processor::Resonator,
+        Processor::Shimmer,
+        Processor::Diffuser,
+    ];
+    
+    /// Clear the processor's state at once
+    fn clear(self) {
+        match self {
+            Processor::Granular => granular::reset(),
+            Processor::Spectral => spectral::reset(),
+            Processor::Convolution => convolution::reset(),
+            Processor::Flanger => flanger::reset(),
+            Processor::Resonator => resonator::reset(),
+            Processor::Shimmer => shimmer::reset(),
+            Processor::Diffuser => diffuser::reset(),
+        }
+    }
+}
+
+// ============================================================================
+// STATE
+// ============================================================================
+
+/// Reset progress of one engine's processors
+struct SoftReset {
+    /// Reset requested; the next block fades out
+    pending: [bool; MAX_PROCESSORS],
+    /// State just cleared; the next block fades in
+    fading_in: [bool; MAX_PROCESSORS],
+}
+
+impl SoftReset {
+    const fn new() -> Self {
+        Self {
+            pending: [false; MAX_PROCESSORS],
+            fading_in: [false; MAX_PROCESSORS],
+        }
+    }
+}
+
+/// Soft reset state of every engine in the pool
+static mut STATES: [SoftReset; memory::MAX_ENGINES] =
+    [const { SoftReset::new() }; memory::MAX_ENGINES];
+
+/// Soft reset state of the selected engine
+/// 
+/// # Safety
+/// Single-threaded access only.
+#[inline]
+unsafe fn state() -> *mut SoftReset {
+    addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()])
+}
+
+// ============================================================================
+// CONTROL
+// ============================================================================
+
+/// Reset a processor over its next block
+pub fn request(processor: Processor) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        (*state()).pending[processor as usize] = true;
+    }
+}
+
+/// Reset every processor over its next block
+pub fn request_all() {
+    for processor in Processor::ALL {
+        request(processor);
+    }
+}
+
+/// Drop pending resets and fades, without clearing any processor
+pub fn reset() {
+    unsafe {
+        // SAFETY: Single-threaded WASM context
+        *state() = SoftReset::new();
+    }
+}
+
+// ============================================================================
+// PROCESSING
+// ============================================================================
+
+/// Apply a pending reset to the block a processor just rendered into the
+/// output buffers
+/// 
+/// Call after every block of the processor, before anything else touches
+/// the output. Leaves the block untouched unless a reset is in progress.
+pub fn apply(processor: Processor) {
+    let id = processor as usize;
+    if !memory::is_initialized() {
+        return;
+    }
+    
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the output buffers don't
+        // overlap the soft reset state
+        let st = state();
+        let fade_out = (*st).pending[id];
+        if !fade_out && !(*st).fading_in[id] {
+            return;
+        }
+        
+        let len = memory::buffer_size() as usize;
+        let fade_len = ((RESET_FADE_MS * 0.001 * memory::sample_rate()) as usize).clamp(1, len.max(1));
+        let ramp = |i: usize| (i as f32 / fade_len as f32).min(1.0);
+        for channel in 0..2 {
+            for (i, y) in memory::output_slice_mut(channel).iter_mut().enumerate() {
+                *y *= if fade_out { 1.0 - ramp(i + 1) } else { ramp(i) };
+            }
+        }
+        
+        if fade_out {
+            processor.clear();
+            (*st).pending[id] = false;
+        }
+        (*st).fading_in[id] = fade_out;
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const BLOCK: usize = 128;
+    
+    /// Stand in for a processor that renders a constant 1.0; returns the
+    /// left output after `apply`
+    fn run(processor: Processor) -> Vec<f32> {
+        unsafe {
+            memory::output_slice_mut(0).fill(1.0);
+            memory::output_slice_mut(1).fill(1.0);
+        }
+        apply(processor);
+        unsafe { memory::output_slice_mut(0).to_vec() }
+    }
+    
+    #[test]
+    fn test_reset_fades_out_and_back_in() {
+        let _guard = memory::test_lock();
+        
+        // 5ms at 48kHz is 240 samples, longer than the block
+        memory::init_engine(48000.0, BLOCK as u32);
+        reset();
+        assert!(run(Processor::Diffuser).iter().all(|&y| y == 1.0));
+        
+        request(Processor::Diffuser);
+        let fade_out = run(Processor::Diffuser);
+        assert!(fade_out[0] > 0.99);
+        assert_eq!(fade_out[BLOCK - 1], 0.0);
+        assert!(fade_out.windows(2).all(|w| w[1] < w[0]));
+        
+        let fade_in = run(Processor::Diffuser);
+        assert_eq!(fade_in[0], 0.0);
+        assert!(fade_in[BLOCK - 1] > 0.99);
+        assert!(fade_in.windows(2).all(|w| w[1] > w[0]));
+        assert!(run(Processor::Diffuser).iter().all(|&y| y == 1.0));
+        
+        // A fade shorter than the block leaves the rest silent (out) or
+        // untouched (in); other processors aren't affected
+        memory::init_engine(8000.0, BLOCK as u32);
+        request_all();
+        let fade_out = run(Processor::Granular);
+        assert!(fade_out[40..].iter().all(|&y| y == 0.0));
+        assert!(fade_out[38] > 0.0);
+        let fade_in = run(Processor::Granular);
+        assert!(fade_in[40..].iter().all(|&y| y == 1.0));
+        assert!(fade_in[39] < 1.0);
+        
+        // Cleanup drops the resets still pending
+        reset();
+        assert!(run(Processor::Flanger).iter().all(|&y| y == 1.0));
+    }
+}
@@ -11,35 +11,389 @@
 //! 3. Apply frequency shift by rotating bins
 //! 4. IFFT back to time domain
 //!
+//! The freeze engages over the freeze fade frames after each capture
+//! instead of snapping to the frozen spectrum, and `recapture` takes a
+//! new snapshot while frozen. Frozen bins keep a `phase_drift` share of
+//! the live phase so the freeze doesn't sound static. `set_freeze_motion`
+//! can also fade the frozen magnitudes out (dB per second from each
+//! capture) and move the frozen bins on a random walk each frame, pulled
+//! back towards the capture so they wander without running away.
+//!
+//! The dry/wet mix delays the dry signal by the latency, so it lines up
+//! with the resynthesized signal instead of comb filtering against it.
+//!
+//! An input gate can clean up the analysis before the freeze: bins below
+//! its threshold are attenuated, each bin opening within a frame and
+//! closing over the gate's release time. It is open by default.
+//!
+//! With formant preservation the shift divides the smoothed spectral
+//! envelope out of the magnitudes before moving them and multiplies the
+//! unshifted envelope back in, so voices keep their vowel color. Without
+//! it, formants move with the pitch.
+//!
+//! Besides the semitone shift (scaling every frequency), `shift_hz` moves
+//! every component by a fixed number of Hz after it, turning harmonic
+//! tones inharmonic. Components moved below 0Hz or past Nyquist are
+//! dropped.
+//!
+//! A tilt (dB per octave about a pivot) and low/high shelves can reshape
+//! the output spectrum. Their combined gain per bin is precomputed when
+//! the settings change and multiplied into the magnitudes before
+//! resynthesis.
+//!
+//! Shimmer feedback (`set_feedback`) adds the last block's wet output,
+//! soft-clipped, back into the analysis input, so each trip around the
+//! loop (about a frame long) is shifted again: with +12 semitones the tail
+//! climbs in octaves. The gain ramps across each block, and at 0 the loop
+//! stops feeding and what is left drains out with the frames.
+//!
+//! A bin mask can carve the blended spectrum right after the freeze, so
+//! a frozen pad keeps its full capture while the mask changes. It combines
+//! a comb (`set_comb`: pass bands around the harmonics of a fundamental,
+//! the bins between them attenuated by the depth) with a per-bin gain
+//! mask JS writes to SPECTRAL_MASK_OFFSET (`load_mask`; resampled across
+//! the bins when its length doesn't match the FFT size). The mask applies
+//! before the shift, so its harmonics move with the shifted spectrum.
+//!
+//! Up to MAX_VOICES harmonizer voices (`set_voice`, `set_voice_count`)
+//! turn a drone into a chord: each shifts the frozen/live spectrum by its
+//! own interval, with its own gain and phase tracks, and is added to the
+//! freeze/shift spectrum before the one inverse FFT. The linear shift
+//! applies to the freeze/shift only. Voices add no FFTs, only their
+//! per-bin shift and phase work.
+//!
+//! The resynthesis mode replaces the synthesis phases after freeze and
+//! shift: robot zeroes them about the frame center every frame (a
+//! monotone voice pitched at the hop rate), whisper draws them at random
+//! (a noise-excited whisper).
+//!
+//! # Freeze Slots
+//! By default the frozen spectrum is captured when freeze_amount rises
+//! above 0. `capture` instead stores the spectrum of the next analysis
+//! frame in one of FREEZE_SLOTS slots, to be frozen to later. The slot
+//! position selects a slot, and a fractional position crossfades the
+//! magnitudes of the two slots around it (each bin keeps the phase of the
+//! slot contributing more of its magnitude). Empty slots are skipped;
+//! with no captured slot at the position, freezing falls back to
+//! auto-capture. Captures survive `reset`.
+//!
 //! # Phase Vocoder
 //! Uses overlap-add with phase accumulation for artifact-free resynthesis.
+//! By default every bin accumulates its own phase. With phase locking
+//! (`set_phase_locking`) only the spectral peaks do, and the bins around
+//! each peak keep their analysis phase offset from it, scaled by the shift
+//! (Laroche & Dolson). This keeps transients from smearing and partials
+//! from sounding phasey when shifted.
+//!
+//! # Framing
+//! All effects share one framing: Hann-windowed frames of the FFT size
+//! (2048 by default), one every hop (a quarter frame by default). `set_fft`
+//! selects both; the latency is the FFT size - 1.
+//!
+//! # Channel Vocoder
+//! The input (modulator) imposes its smoothed magnitude envelope onto a
+//! looping carrier loaded at VOCODER_CARRIER_OFFSET:
+//! 1. FFT the carrier frame and divide out its own smoothed envelope (whiten)
+//! 2. Multiply by the modulator's smoothed (optionally formant-shifted) envelope
+//! 3. IFFT and overlap-add, keeping the carrier's phases
+//!
+//! # Pitch Shift
+//! Duration-preserving pitch shift of live input:
+//! 1. Phase vocoder analysis estimates each bin's true frequency
+//! 2. Magnitudes are resampled along the frequency axis by the pitch ratio
+//! 3. Synthesis phases advance at the shifted true frequency; bins around
+//!    each spectral peak are phase-locked to it (identity phase locking)
+//! 4. With formant preservation the smoothed spectral envelope stays fixed
+//!
+//! # Spectral Gate
+//! Bins whose level falls below the threshold are attenuated by a fixed
+//! reduction, each bin independently. Per-bin gains are smoothed across
+//! frames so bins near the threshold don't chatter. Gains are applied to
+//! the complex spectrum directly, so an open gate is an exact passthrough
+//! of the overlap-add reconstruction.
 
 use crate::memory;
-use rustfft::{FftPlanner, num_complex::Complex};
+use crate::simd_utils;
+use crate::utils;
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use std::sync::Arc;
 use core::f32::consts::PI;
-use core::ptr::addr_of_mut;
+use core::ptr::{addr_of, addr_of_mut};
 
 // ============================================================================
 // CONSTANTS
 // ============================================================================
 
-/// FFT size for spectral analysis
-const FFT_SIZE: usize = 2048;
+/// Default FFT size for spectral analysis
+const DEFAULT_FFT_SIZE: usize = 2048;
+
+/// Default overlap: frames covering each sample (hop = FFT size / overlap)
+const DEFAULT_OVERLAP: usize = 4;
+
+/// Selectable FFT sizes (powers of two)
+const MIN_FFT_SIZE: usize = 256;
+const MAX_FFT_SIZE: usize = 8192;
+
+/// Selectable overlaps (powers of two; below 4 the squared Hann windows
+/// no longer overlap-add to a constant)
+const MIN_OVERLAP: usize = 4;
+const MAX_OVERLAP: usize = 8;
+
+/// Minimum vocoder band count (coarsest envelope smoothing)
+const MIN_VOCODER_BANDS: f32 = 4.0;
+
+/// Floor added to the carrier envelope before whitening
+const VOCODER_EPSILON: f32 = 1e-6;
+
+/// Spectral envelope smoothing width for formant preservation at the
+/// default FFT size (~400Hz at 48kHz; scaled with the FFT size)
+const FORMANT_SMOOTHING_BINS: usize = 17;
+
+/// Marker for "no spectral peak" in the peak map
+const NO_PEAK: usize = usize::MAX;
+
+/// Per-frame gain smoothing when a gate bin opens (fast, keeps transients)
+const GATE_OPEN_COEFF: f32 = 0.8;
+
+/// Per-frame gain smoothing when a gate bin closes (slower, avoids chatter)
+const GATE_CLOSE_COEFF: f32 = 0.3;
+
+/// Maximum gate reduction in dB
+const MAX_GATE_REDUCTION_DB: f32 = 120.0;
+
+/// Input gate release time range and default in milliseconds
+const MIN_GATE_RELEASE_MS: f32 = 1.0;
+const MAX_GATE_RELEASE_MS: f32 = 5000.0;
+const DEFAULT_GATE_RELEASE_MS: f32 = 100.0;
+
+/// Spectral tilt range in dB per octave
+const MAX_TILT_DB_PER_OCTAVE: f32 = 12.0;
+
+/// Largest boost or cut of the tilt/shelf EQ at any bin, and of each shelf
+const MAX_EQ_GAIN_DB: f32 = 24.0;
+
+/// Lowest EQ pivot and shelf frequency
+const MIN_EQ_FREQ_HZ: f32 = 20.0;
+
+/// Default tilt pivot and shelf frequencies
+const DEFAULT_TILT_PIVOT_HZ: f32 = 1000.0;
+const DEFAULT_LOW_SHELF_HZ: f32 = 200.0;
+const DEFAULT_HIGH_SHELF_HZ: f32 = 4000.0;
+
+/// Number of freeze capture slots
+pub const FREEZE_SLOTS: usize = 4;
+
+/// Frames the freeze engages over after a capture by default
+const DEFAULT_FREEZE_FADE_FRAMES: u32 = 4;
 
-/// Hop size (overlap factor of 4)
-const HOP_SIZE: usize = FFT_SIZE / 4;
+/// Longest freeze engagement in frames
+const MAX_FREEZE_FADE_FRAMES: u32 = 64;
 
-/// Number of frequency bins (FFT_SIZE / 2 + 1)
-const NUM_BINS: usize = FFT_SIZE / 2 + 1;
+/// Share of the live phase frozen bins keep by default
+const DEFAULT_PHASE_DRIFT: f32 = 0.1;
+
+/// Highest frozen magnitude decay in dB per second
+const MAX_FREEZE_DECAY_DB: f32 = 120.0;
+
+/// Freeze drift random walk steps per frame at drift 1 (magnitude in dB,
+/// phase in radians)
+const MAX_DRIFT_STEP_DB: f32 = 1.0;
+const MAX_DRIFT_STEP_PHASE: f32 = 0.2;
+
+/// Share of the freeze drift offsets kept each frame (pulls the walk back
+/// towards the captured spectrum)
+const DRIFT_LEAK: f32 = 0.95;
+
+/// Highest shimmer feedback gain
+const MAX_FEEDBACK: f32 = 0.9;
+
+/// Number of harmonizer voices
+pub const MAX_VOICES: usize = 4;
+
+/// Harmonizer voice shift range in semitones
+const MAX_VOICE_SEMITONES: f32 = 24.0;
+
+/// Lowest comb fundamental in Hz
+const MIN_COMB_HZ: f32 = 20.0;
+
+/// Comb pass band width range in bins
+const MIN_COMB_WIDTH_BINS: f32 = 0.5;
+const MAX_COMB_WIDTH_BINS: f32 = 64.0;
+
+// The mask region holds a mask for every FFT size
+const _: () = assert!(MAX_FFT_SIZE / 2 < memory::MAX_SPECTRAL_MASK_BINS);
 
 // ============================================================================
 // SPECTRAL STATE
 // ============================================================================
 
+/// Resynthesis phase handling of the spectral freeze effect
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpectralMode {
+    /// Phase vocoder phases
+    Normal,
+    /// Zero phase (about the frame center) every frame
+    Robot,
+    /// Random phase every frame
+    Whisper,
+}
+
+impl SpectralMode {
+    /// Mode from its export index (unknown values fall back to Normal)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => SpectralMode::Robot,
+            2 => SpectralMode::Whisper,
+            _ => SpectralMode::Normal,
+        }
+    }
+}
+
+/// Settings of the spectral freeze effect's input gate
+#[derive(Clone, Copy)]
+struct InputGate {
+    /// Threshold in dBFS (-inf = open)
+    threshold_db: f32,
+    /// Attenuation of gated bins in dB
+    reduction_db: f32,
+    /// Time for a gated bin to close
+    release_ms: f32,
+}
+
+/// Settings of the spectral freeze effect's tilt/shelf EQ
+#[derive(Clone, Copy)]
+struct SpectralEq {
+    /// Tilt in dB per octave about the pivot (positive = brighter)
+    tilt_db: f32,
+    pivot_hz: f32,
+    /// Low shelf corner and gain (half the gain at the corner)
+    low_shelf_hz: f32,
+    low_shelf_db: f32,
+    /// High shelf corner and gain (half the gain at the corner)
+    high_shelf_hz: f32,
+    high_shelf_db: f32,
+}
+
+impl SpectralEq {
+    /// Whether every bin's gain is 0dB
+    fn is_flat(&self) -> bool {
+        self.tilt_db == 0.0 && self.low_shelf_db == 0.0 && self.high_shelf_db == 0.0
+    }
+    
+    /// Gain at a frequency in dB
+    fn gain_db(&self, freq: f32) -> f32 {
+        let tilt = self.tilt_db * libm::log2f(freq / self.pivot_hz);
+        let low = self.low_shelf_db / (1.0 + (freq / self.low_shelf_hz).powi(2));
+        let high = self.high_shelf_db / (1.0 + (self.high_shelf_hz / freq).powi(2));
+        (tilt + low + high).clamp(-MAX_EQ_GAIN_DB, MAX_EQ_GAIN_DB)
+    }
+}
+
+/// Settings of the spectral freeze effect's comb mask
+#[derive(Clone, Copy)]
+struct SpectralComb {
+    /// Fundamental whose harmonics pass
+    fundamental_hz: f32,
+    /// Width of each pass band at half gain, in bins
+    width_bins: f32,
+    /// Attenuation between the pass bands (0 = off, 1 = removed)
+    depth: f32,
+}
+
+impl SpectralComb {
+    /// Gain of a bin at a fundamental of `fundamental_bins` bins
+    fn gain(&self, bin: f32, fundamental_bins: f32) -> f32 {
+        // Distance to the nearest harmonic (DC isn't one)
+        let harmonic = (bin / fundamental_bins).round().max(1.0);
+        let distance = (bin - harmonic * fundamental_bins).abs();
+        let pass = if distance < self.width_bins {
+            0.5 + 0.5 * (PI * distance / self.width_bins).cos()
+        } else {
+            0.0
+        };
+        1.0 - self.depth * (1.0 - pass)
+    }
+}
+
+/// Per-frame input gate constants
+#[derive(Clone, Copy)]
+struct GateFrame {
+    /// Bin magnitude below which bins close
+    threshold: f32,
+    /// Gain of a closed bin
+    floor_gain: f32,
+    /// Per-frame smoothing when a bin closes
+    close_coeff: f32,
+}
+
+/// A captured spectrum to freeze to (left and right)
+struct FreezeSlot {
+    mag: [Vec<f32>; 2],
+    phase: [Vec<f32>; 2],
+    /// Capture requested for the next analysis frame
+    pending: bool,
+    /// Whether the slot holds a capture
+    captured: bool,
+}
+
+impl FreezeSlot {
+    fn new(num_bins: usize) -> Self {
+        Self {
+            mag: [vec![0.0; num_bins], vec![0.0; num_bins]],
+            phase: [vec![0.0; num_bins], vec![0.0; num_bins]],
+            pending: false,
+            captured: false,
+        }
+    }
+}
+
+/// Settings of a harmonizer voice
+#[derive(Clone, Copy)]
+struct HarmonyVoice {
+    /// Pitch ratio to the freeze output (2^(semitones / 12))
+    ratio: f32,
+    /// Linear gain
+    gain: f32,
+}
+
+/// Phase vocoder phases of a harmonizer voice (left and right)
+struct VoicePhases {
+    prev_phase: [Vec<f32>; 2],
+    synth_phase: [Vec<f32>; 2],
+}
+
+impl VoicePhases {
+    fn new(num_bins: usize) -> Self {
+        Self {
+            prev_phase: [vec![0.0; num_bins], vec![0.0; num_bins]],
+            synth_phase: [vec![0.0; num_bins], vec![0.0; num_bins]],
+        }
+    }
+    
+    /// Previous analysis and accumulated synthesis phases of a channel
+    fn phase_tracks(&mut self, channel: usize) -> [&mut [f32]; 2] {
+        [&mut self.prev_phase[channel], &mut self.synth_phase[channel]]
+    }
+    
+    fn clear(&mut self) {
+        for phases in self.prev_phase.iter_mut().chain(self.synth_phase.iter_mut()) {
+            phases.fill(0.0);
+        }
+    }
+}
+
 /// Spectral processing state
 struct SpectralState {
-    /// FFT planner
-    planner: FftPlanner<f32>,
+    /// Analysis frame length, hop between frames and bin count
+    /// (fft_size / 2 + 1)
+    fft_size: usize,
+    hop_size: usize,
+    num_bins: usize,
+    /// Forward/inverse FFT plans (planned once per FFT size)
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    /// Scratch for in-place FFTs (sized for both plans)
+    fft_scratch: Vec<Complex<f32>>,
     /// Input accumulation buffer
     input_buffer_l: Vec<f32>,
     input_buffer_r: Vec<f32>,
@@ -62,59 +416,290 @@ struct SpectralState {
     /// Phase accumulator for resynthesis
     synth_phase_l: Vec<f32>,
     synth_phase_r: Vec<f32>,
-    /// Window function
+    /// Analysis window (Hann)
     window: Vec<f32>,
+    /// Synthesis window: the analysis window scaled so the overlap-added
+    /// frames have unity gain at every FFT size and overlap
+    synthesis_window: Vec<f32>,
     /// Freeze state (true when frozen)
     is_frozen: bool,
+    /// Freeze engagement since the last capture (0-1)
+    freeze_fade: f32,
+    /// Frames the freeze engages over after a capture (0 = instantly)
+    freeze_fade_frames: u32,
+    /// Share of the live phase frozen bins keep (0 = fully frozen)
+    phase_drift: f32,
+    /// Frozen magnitude decay in dB per second (0 = held)
+    freeze_decay: f32,
+    /// Random walk of the frozen bins (0-1, 0 = static)
+    freeze_drift: f32,
+    /// Level the frozen magnitudes have decayed to since the last capture
+    freeze_level: f32,
+    /// Random walk offsets of the frozen bins (magnitude in dB, phase in
+    /// radians)
+    drift_mag_l: Vec<f32>,
+    drift_mag_r: Vec<f32>,
+    drift_phase_l: Vec<f32>,
+    drift_phase_r: Vec<f32>,
+    /// Lock the freeze/shift synthesis phases around spectral peaks
+    phase_locking: bool,
+    /// Whisper phase RNG (LCG) state
+    rng_state: u32,
+    /// Freeze/shift wet output of the last block, fed back into the
+    /// analysis input for shimmer
+    feedback_l: Vec<f32>,
+    feedback_r: Vec<f32>,
+    /// Shimmer feedback gain setting and the gain the last block ended at
+    feedback: f32,
+    feedback_gain: f32,
+    /// Harmonizer voices, the first `voice_count` of them sounding
+    voices: [HarmonyVoice; MAX_VOICES],
+    voice_count: usize,
+    voice_phases: Vec<VoicePhases>,
+    /// Dry signal delay lines (latency long) and their shared position
+    dry_delay_l: Vec<f32>,
+    dry_delay_r: Vec<f32>,
+    dry_pos: usize,
+    /// Explicitly captured spectra
+    freeze_slots: Vec<FreezeSlot>,
+    /// Vocoder carrier length in frames (0 = no carrier loaded)
+    carrier_frames: usize,
+    /// Vocoder carrier channel count (1 or 2)
+    carrier_channels: u32,
+    /// Vocoder carrier read position in frames (loops over the carrier)
+    carrier_pos: usize,
+    /// Vocoder carrier spectrum for the current frame
+    carrier_spectrum: Vec<Complex<f32>>,
+    /// Vocoder spectral envelopes (modulator, carrier) and smoothing scratch
+    mod_env: Vec<f32>,
+    carrier_env: Vec<f32>,
+    envelope_scratch: Vec<f32>,
+    /// Pitch shifter analysis phase of the previous frame
+    shift_prev_phase_l: Vec<f32>,
+    shift_prev_phase_r: Vec<f32>,
+    /// Pitch shifter synthesis phase accumulator
+    shift_synth_phase_l: Vec<f32>,
+    shift_synth_phase_r: Vec<f32>,
+    /// Per-frame analysis scratch (magnitude, phase, true frequency in bins)
+    analysis_mag: Vec<f32>,
+    analysis_phase: Vec<f32>,
+    analysis_freq: Vec<f32>,
+    /// Per-frame frequency-shifted magnitude and phase scratch
+    shifted_mag: Vec<f32>,
+    shifted_phase: Vec<f32>,
+    /// Spectral peak owning each bin (for phase locking)
+    peak_of: Vec<usize>,
+    /// Smoothed spectral envelope for formant preservation
+    spectral_env: Vec<f32>,
+    /// Smoothed per-bin spectral gate gains
+    gate_gain_l: Vec<f32>,
+    gate_gain_r: Vec<f32>,
+    /// Spectral freeze input gate settings and smoothed per-bin gains
+    input_gate: InputGate,
+    input_gate_gain_l: Vec<f32>,
+    input_gate_gain_r: Vec<f32>,
+    /// Tilt/shelf EQ settings and their per-bin gains (recomputed when
+    /// the settings change)
+    eq: SpectralEq,
+    eq_gains: Vec<f32>,
+    /// Comb settings, the loaded per-bin mask (empty = none) and their
+    /// combined per-bin gains (recomputed when either changes)
+    comb: SpectralComb,
+    mask: Vec<f32>,
+    mask_gains: Vec<f32>,
     /// Initialized flag
     initialized: bool,
 }
 
-/// Global spectral state
-static mut STATE: Option<SpectralState> = None;
+/// Per-engine spectral state, created on first use
+static mut STATES: [Option<SpectralState>; memory::MAX_ENGINES] = [const { None }; memory::MAX_ENGINES];
 
 // ============================================================================
 // INITIALIZATION
 // ============================================================================
 
-/// Ensure spectral state is initialized
+impl SpectralState {
+    /// Allocate the state for an FFT size and overlap (powers of two)
+    fn new(fft_size: usize, overlap: usize) -> Self {
+        let num_bins = fft_size / 2 + 1;
+        
+        // Hann window, used for analysis and synthesis
+        let window: Vec<f32> = (0..fft_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / fft_size as f32).cos())
+            .collect();
+        let hop_size = fft_size / overlap;
+        let synthesis_scale = 1.0 / (fft_size as f32 * cola_gain(&window, hop_size));
+        let synthesis_window = window.iter().map(|w| w * synthesis_scale).collect();
+        
+        let mut planner = FftPlanner::new();
+        let fft = planner.plan_fft_forward(fft_size);
+        let ifft = planner.plan_fft_inverse(fft_size);
+        let scratch_len = fft.get_inplace_scratch_len().max(ifft.get_inplace_scratch_len());
+        
+        Self {
+            fft_size,
+            hop_size,
+            num_bins,
+            fft,
+            ifft,
+            fft_scratch: vec![Complex::new(0.0, 0.0); scratch_len],
+            input_buffer_l: vec![0.0; fft_size],
+            input_buffer_r: vec![0.0; fft_size],
+            // A frame overlap-adds from any offset in the block
+            output_buffer_l: vec![0.0; fft_size + memory::MAX_BUFFER_SIZE],
+            output_buffer_r: vec![0.0; fft_size + memory::MAX_BUFFER_SIZE],
+            input_pos: 0,
+            fft_buffer: vec![Complex::new(0.0, 0.0); fft_size],
+            ifft_buffer: vec![Complex::new(0.0, 0.0); fft_size],
+            frozen_mag_l: vec![0.0; num_bins],
+            frozen_mag_r: vec![0.0; num_bins],
+            frozen_phase_l: vec![0.0; num_bins],
+            frozen_phase_r: vec![0.0; num_bins],
+            prev_phase_l: vec![0.0; num_bins],
+            prev_phase_r: vec![0.0; num_bins],
+            synth_phase_l: vec![0.0; num_bins],
+            synth_phase_r: vec![0.0; num_bins],
+            window,
+            synthesis_window,
+            is_frozen: false,
+            freeze_fade: 0.0,
+            freeze_fade_frames: DEFAULT_FREEZE_FADE_FRAMES,
+            phase_drift: DEFAULT_PHASE_DRIFT,
+            freeze_decay: 0.0,
+            freeze_drift: 0.0,
+            freeze_level: 1.0,
+            drift_mag_l: vec![0.0; num_bins],
+            drift_mag_r: vec![0.0; num_bins],
+            drift_phase_l: vec![0.0; num_bins],
+            drift_phase_r: vec![0.0; num_bins],
+            phase_locking: false,
+            rng_state: 12345,
+            feedback_l: vec![0.0; memory::MAX_BUFFER_SIZE],
+            feedback_r: vec![0.0; memory::MAX_BUFFER_SIZE],
+            feedback: 0.0,
+            feedback_gain: 0.0,
+            voices: [HarmonyVoice { ratio: 1.0, gain: 1.0 }; MAX_VOICES],
+            voice_count: 0,
+            voice_phases: (0..MAX_VOICES).map(|_| VoicePhases::new(num_bins)).collect(),
+            dry_delay_l: vec![0.0; fft_size - 1],
+            dry_delay_r: vec![0.0; fft_size - 1],
+            dry_pos: 0,
+            freeze_slots: (0..FREEZE_SLOTS).map(|_| FreezeSlot::new(num_bins)).collect(),
+            carrier_frames: 0,
+            carrier_channels: 1,
+            carrier_pos: 0,
+            carrier_spectrum: vec![Complex::new(0.0, 0.0); fft_size],
+            mod_env: vec![0.0; num_bins],
+            carrier_env: vec![0.0; num_bins],
+            envelope_scratch: vec![0.0; num_bins],
+            shift_prev_phase_l: vec![0.0; num_bins],
+            shift_prev_phase_r: vec![0.0; num_bins],
+            shift_synth_phase_l: vec![0.0; num_bins],
+            shift_synth_phase_r: vec![0.0; num_bins],
+            analysis_mag: vec![0.0; num_bins],
+            analysis_phase: vec![0.0; num_bins],
+            analysis_freq: vec![0.0; num_bins],
+            shifted_mag: vec![0.0; num_bins],
+            shifted_phase: vec![0.0; num_bins],
+            peak_of: vec![NO_PEAK; num_bins],
+            spectral_env: vec![0.0; num_bins],
+            gate_gain_l: vec![1.0; num_bins],
+            gate_gain_r: vec![1.0; num_bins],
+            input_gate: InputGate {
+                threshold_db: f32::NEG_INFINITY,
+                reduction_db: 0.0,
+                release_ms: DEFAULT_GATE_RELEASE_MS,
+            },
+            input_gate_gain_l: vec![1.0; num_bins],
+            input_gate_gain_r: vec![1.0; num_bins],
+            eq: SpectralEq {
+                tilt_db: 0.0,
+                pivot_hz: DEFAULT_TILT_PIVOT_HZ,
+                low_shelf_hz: DEFAULT_LOW_SHELF_HZ,
+                low_shelf_db: 0.0,
+                high_shelf_hz: DEFAULT_HIGH_SHELF_HZ,
+                high_shelf_db: 0.0,
+            },
+            eq_gains: vec![1.0; num_bins],
+            comb: SpectralComb {
+                fundamental_hz: 110.0,
+                width_bins: 1.0,
+                depth: 0.0,
+            },
+            // Loading a mask doesn't allocate
+            mask: Vec::with_capacity(memory::MAX_SPECTRAL_MASK_BINS),
+            mask_gains: vec![1.0; num_bins],
+            initialized: true,
+        }
+    }
+}
+
+/// Overlap-add gain of a window applied at analysis and synthesis
+/// 
+/// The squared window summed over the frames covering each sample, averaged
+/// over a hop (constant for the Hann window at overlap 4 and above: 3/8 of
+/// the overlap). Dividing the synthesis window by it (and by the FFT size,
+/// for the unnormalized inverse FFT) makes a pass-through frame unity gain.
+fn cola_gain(window: &[f32], hop_size: usize) -> f32 {
+    let sum: f32 = window.iter().map(|w| w * w).sum();
+    sum / hop_size as f32
+}
+
+/// Ensure the selected engine's spectral state is initialized
 fn ensure_state() -> &'static mut SpectralState {
     unsafe {
         // SAFETY: Single-threaded WASM context, using raw pointer for Rust 2024
-        let state_ptr = addr_of_mut!(STATE);
+        let state_ptr = addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]);
         if (*state_ptr).is_none() {
-            // Create Hann window
-            let mut window = vec![0.0; FFT_SIZE];
-            for i in 0..FFT_SIZE {
-                window[i] = 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos();
-            }
-            
-            *state_ptr = Some(SpectralState {
-                planner: FftPlanner::new(),
-                input_buffer_l: vec![0.0; FFT_SIZE],
-                input_buffer_r: vec![0.0; FFT_SIZE],
-                output_buffer_l: vec![0.0; FFT_SIZE * 2],
-                output_buffer_r: vec![0.0; FFT_SIZE * 2],
-                input_pos: 0,
-                fft_buffer: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-                ifft_buffer: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-                frozen_mag_l: vec![0.0; NUM_BINS],
-                frozen_mag_r: vec![0.0; NUM_BINS],
-                frozen_phase_l: vec![0.0; NUM_BINS],
-                frozen_phase_r: vec![0.0; NUM_BINS],
-                prev_phase_l: vec![0.0; NUM_BINS],
-                prev_phase_r: vec![0.0; NUM_BINS],
-                synth_phase_l: vec![0.0; NUM_BINS],
-                synth_phase_r: vec![0.0; NUM_BINS],
-                window,
-                is_frozen: false,
-                initialized: true,
-            });
+            *state_ptr = Some(SpectralState::new(DEFAULT_FFT_SIZE, DEFAULT_OVERLAP));
         }
         (*state_ptr).as_mut().unwrap()
     }
 }
 
+/// Select the FFT size and overlap of the spectral effects
+/// 
+/// Larger frames resolve low frequencies better, smaller ones smear
+/// transients less. Reallocates the state: phases, frozen spectra and
+/// freeze slots are cleared (the vocoder carrier, freeze settings and
+/// bin mask stay). The latency changes to the new FFT size.
+/// 
+/// # Arguments
+/// * `fft_size` - Analysis frame length (256-8192, rounded up to a power
+///   of two)
+/// * `overlap` - Frames covering each sample (4 or 8; hop = fft_size /
+///   overlap)
+/// 
+/// # Returns
+/// The FFT size in effect
+pub fn set_fft(fft_size: u32, overlap: u32) -> u32 {
+    let fft_size = (fft_size as usize).clamp(MIN_FFT_SIZE, MAX_FFT_SIZE).next_power_of_two();
+    let overlap = (overlap as usize).clamp(MIN_OVERLAP, MAX_OVERLAP).next_power_of_two();
+    let state = ensure_state();
+    if fft_size != state.fft_size || state.hop_size != fft_size / overlap {
+        let mut resized = SpectralState::new(fft_size, overlap);
+        resized.carrier_frames = state.carrier_frames;
+        resized.carrier_channels = state.carrier_channels;
+        resized.carrier_pos = state.carrier_pos;
+        resized.freeze_fade_frames = state.freeze_fade_frames;
+        resized.phase_drift = state.phase_drift;
+        resized.freeze_decay = state.freeze_decay;
+        resized.freeze_drift = state.freeze_drift;
+        resized.phase_locking = state.phase_locking;
+        resized.feedback = state.feedback;
+        resized.voices = state.voices;
+        resized.voice_count = state.voice_count;
+        resized.input_gate = state.input_gate;
+        resized.eq = state.eq;
+        resized.comb = state.comb;
+        resized.mask = core::mem::take(&mut state.mask);
+        update_eq_gains(&mut resized);
+        update_mask_gains(&mut resized);
+        *state = resized;
+    }
+    fft_size as u32
+}
+
 // ============================================================================
 // PROCESSING
 // ============================================================================
@@ -124,89 +709,426 @@ fn ensure_state() -> &'static mut SpectralState {
 /// # Arguments
 /// * `freeze_amount` - Amount of spectral freeze (0 = none, 1 = full freeze)
 /// * `shift` - Frequency shift in semitones (-24 to +24)
-pub fn process(freeze_amount: f32, shift: f32) {
+/// * `slot_position` - Freeze slot to freeze to (fractional values blend
+///   neighbouring slots; negative = auto-capture)
+/// * `dry_wet` - Mix between the latency-aligned dry (0) and wet (1)
+///   signal, linear
+/// * `mode` - Resynthesis phase handling
+/// * `formant_preserve` - Keep the spectral envelope in place while the
+///   shift moves the harmonics
+/// * `shift_hz` - Linear frequency shift in Hz, after the semitone shift
+///   (±Nyquist; components moved below 0Hz or past Nyquist are dropped)
+pub fn process(
+    freeze_amount: f32,
+    shift: f32,
+    slot_position: f32,
+    dry_wet: f32,
+    mode: SpectralMode,
+    formant_preserve: bool,
+    shift_hz: f32,
+) {
     let state = ensure_state();
     
     let freeze_amount = freeze_amount.clamp(0.0, 1.0);
+    // NaN (e.g. an omitted argument from JS) reads as fully wet
+    let dry_wet = if dry_wet.is_nan() { 1.0 } else { dry_wet.clamp(0.0, 1.0) };
     let shift = shift.clamp(-24.0, 24.0);
     
     // Calculate pitch shift ratio
     let shift_ratio = 2.0_f32.powf(shift / 12.0);
     
+    // Linear shift in bins
+    let nyquist = memory::sample_rate() * 0.5;
+    let shift_hz = if shift_hz.is_nan() { 0.0 } else { shift_hz.clamp(-nyquist, nyquist) };
+    let shift_bins = shift_hz * state.fft_size as f32 / memory::sample_rate();
+    
+    let gate = gate_frame_constants(state, state.input_gate);
+    
+    // Frozen magnitude decay per frame
+    let decay_gain = utils::db_to_linear(-state.freeze_decay * state.hop_size as f32 / memory::sample_rate());
+    
+    // With a captured slot to freeze to, the frozen spectrum comes from the
+    // slots and auto-capture is off
+    let slot_weights = slot_weights(&state.freeze_slots, slot_position);
+    
     unsafe {
-        let buffer_size = memory::buffer_size() as usize;
-        let input_l = memory::input_slice(0);
-        let input_r = memory::input_slice(1);
-        let output_l = memory::output_slice_mut(0);
-        let output_r = memory::output_slice_mut(1);
-        
-        // Process sample by sample
-        for i in 0..buffer_size {
-            // Add input to buffer
-            state.input_buffer_l[state.input_pos] = input_l[i];
-            state.input_buffer_r[state.input_pos] = input_r[i];
-            state.input_pos += 1;
-            
-            // Process when we have a full hop
-            if state.input_pos >= HOP_SIZE {
-                // Shift input buffer
-                for j in 0..(FFT_SIZE - HOP_SIZE) {
-                    state.input_buffer_l[j] = state.input_buffer_l[j + HOP_SIZE];
-                    state.input_buffer_r[j] = state.input_buffer_r[j + HOP_SIZE];
+        // SAFETY: Single-threaded WASM context; the work buffers don't
+        // overlap the I/O buffers or the spectral state
+        let len = memory::buffer_size() as usize;
+        let mut input = [memory::input_slice(0), memory::input_slice(1)];
+        if state.feedback > 0.0 || state.feedback_gain > 0.0 {
+            // Shimmer: analyze the input plus the last block's wet output,
+            // the gain ramping from where the last block ended
+            let (start, end) = (state.feedback_gain, state.feedback);
+            let mixed = [&mut memory::work_buffer_1()[..len], &mut memory::work_buffer_2()[..len]];
+            for ((mixed, &dry), feedback) in mixed.into_iter().zip(&input).zip([&state.feedback_l, &state.feedback_r]) {
+                for (i, y) in mixed.iter_mut().enumerate() {
+                    let gain = start + (end - start) * (i + 1) as f32 / len as f32;
+                    *y = dry[i] + utils::soft_clip(feedback[i] * gain);
                 }
-                state.input_pos = FFT_SIZE - HOP_SIZE;
-                
-                // Process left channel
-                process_frame(
-                    &state.input_buffer_l,
-                    &mut state.output_buffer_l,
-                    &mut state.fft_buffer,
-                    &mut state.ifft_buffer,
-                    &mut state.frozen_mag_l,
-                    &mut state.frozen_phase_l,
-                    &mut state.prev_phase_l,
-                    &mut state.synth_phase_l,
-                    &state.window,
-                    freeze_amount,
-                    shift_ratio,
-                    &mut state.planner,
-                    &mut state.is_frozen,
-                );
-                
-                // Process right channel
-                let mut is_frozen_dummy = state.is_frozen;
-                process_frame(
-                    &state.input_buffer_r,
-                    &mut state.output_buffer_r,
-                    &mut state.fft_buffer,
-                    &mut state.ifft_buffer,
-                    &mut state.frozen_mag_r,
-                    &mut state.frozen_phase_r,
-                    &mut state.prev_phase_r,
-                    &mut state.synth_phase_r,
-                    &state.window,
-                    freeze_amount,
-                    shift_ratio,
-                    &mut state.planner,
-                    &mut is_frozen_dummy,
-                );
             }
+            input = [&memory::work_buffer_1()[..len], &memory::work_buffer_2()[..len]];
+            state.feedback_gain = end;
+        }
+        let output = [memory::output_slice_mut(0), memory::output_slice_mut(1)];
+        run_frames_on(state, input, output, |state, offset| {
+            let mut is_frozen = state.is_frozen;
+            if let Some(weights) = slot_weights {
+                fill_from_slots(&state.freeze_slots, weights, 0, &mut state.frozen_mag_l, &mut state.frozen_phase_l);
+                fill_from_slots(&state.freeze_slots, weights, 1, &mut state.frozen_mag_r, &mut state.frozen_phase_r);
+                is_frozen = true;
+            }
+            
+            // Engage the freeze gradually from each capture
+            if freeze_amount > 0.0 && !is_frozen {
+                state.freeze_fade = 0.0;
+            }
+            let fade_step = 1.0 / state.freeze_fade_frames.max(1) as f32;
+            state.freeze_fade = (state.freeze_fade + fade_step).min(1.0);
+            let frame_freeze = freeze_amount * state.freeze_fade;
+            
+            // One latch for both channels: they capture the same frame, so
+            // the stereo image holds through the freeze
+            let capture = frame_freeze > 0.0 && !is_frozen;
             
-            // Read from output buffer
-            output_l[i] = state.output_buffer_l[i];
-            output_r[i] = state.output_buffer_r[i];
+            // The frozen magnitudes decay from each capture (slots from
+            // when the freeze engaged)
+            state.freeze_level = if frame_freeze == 0.0 || capture { 1.0 } else { state.freeze_level * decay_gain };
+            let eq_gains = (!state.eq.is_flat()).then_some(&state.eq_gains[..]);
+            let mask_gains = (state.comb.depth > 0.0 || !state.mask.is_empty()).then_some(&state.mask_gains[..]);
+            
+            // Process left channel
+            process_frame(
+                &state.input_buffer_l,
+                &mut state.output_buffer_l[offset..],
+                &mut state.fft_buffer,
+                &mut state.ifft_buffer,
+                &mut state.frozen_mag_l,
+                &mut state.frozen_phase_l,
+                &mut state.drift_mag_l,
+                &mut state.drift_phase_l,
+                &mut state.prev_phase_l,
+                &mut state.synth_phase_l,
+                &mut state.analysis_mag,
+                &mut state.analysis_phase,
+                &mut state.shifted_mag,
+                &mut state.shifted_phase,
+                &mut state.spectral_env,
+                &mut state.peak_of,
+                &mut state.input_gate_gain_l,
+                gate,
+                eq_gains,
+                mask_gains,
+                &state.window,
+                &state.synthesis_window,
+                state.hop_size,
+                frame_freeze,
+                state.phase_drift,
+                state.freeze_level,
+                state.freeze_drift,
+                shift_ratio,
+                shift_bins,
+                formant_preserve,
+                state.phase_locking,
+                mode,
+                &mut state.rng_state,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+                capture,
+                &mut state.freeze_slots,
+                &state.voices[..state.voice_count],
+                &mut state.voice_phases,
+                0,
+            );
+            
+            // Process right channel
+            process_frame(
+                &state.input_buffer_r,
+                &mut state.output_buffer_r[offset..],
+                &mut state.fft_buffer,
+                &mut state.ifft_buffer,
+                &mut state.frozen_mag_r,
+                &mut state.frozen_phase_r,
+                &mut state.drift_mag_r,
+                &mut state.drift_phase_r,
+                &mut state.prev_phase_r,
+                &mut state.synth_phase_r,
+                &mut state.analysis_mag,
+                &mut state.analysis_phase,
+                &mut state.shifted_mag,
+                &mut state.shifted_phase,
+                &mut state.spectral_env,
+                &mut state.peak_of,
+                &mut state.input_gate_gain_r,
+                gate,
+                eq_gains,
+                mask_gains,
+                &state.window,
+                &state.synthesis_window,
+                state.hop_size,
+                frame_freeze,
+                state.phase_drift,
+                state.freeze_level,
+                state.freeze_drift,
+                shift_ratio,
+                shift_bins,
+                formant_preserve,
+                state.phase_locking,
+                mode,
+                &mut state.rng_state,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+                capture,
+                &mut state.freeze_slots,
+                &state.voices[..state.voice_count],
+                &mut state.voice_phases,
+                1,
+            );
+            
+            // Leaving slot mode re-arms auto-capture
+            state.is_frozen = frame_freeze > 0.0 && slot_weights.is_none();
+            for slot in state.freeze_slots.iter_mut().filter(|slot| slot.pending) {
+                slot.pending = false;
+                slot.captured = true;
+            }
+        });
+        state.feedback_l[..len].copy_from_slice(memory::output_slice_mut(0));
+        state.feedback_r[..len].copy_from_slice(memory::output_slice_mut(1));
+        mix_dry(state, dry_wet);
+    }
+}
+
+/// Mix the input, delayed by the latency, into the wet output
+/// 
+/// The wet signal is the input resynthesized, so the two are correlated
+/// and mix linearly. The delay lines run at every mix so changing it
+/// stays aligned.
+/// 
+/// # Safety
+/// Engine must be initialized (reads input and writes output buffers).
+unsafe fn mix_dry(state: &mut SpectralState, dry_wet: f32) {
+    let len = state.dry_delay_l.len();
+    let mut end = state.dry_pos;
+    for (channel, delay) in [&mut state.dry_delay_l, &mut state.dry_delay_r].into_iter().enumerate() {
+        let mut pos = state.dry_pos;
+        let input = memory::input_slice(channel as u32);
+        let output = memory::output_slice_mut(channel as u32);
+        for (&x, y) in input.iter().zip(output.iter_mut()) {
+            let dry = delay[pos];
+            delay[pos] = x;
+            pos = (pos + 1) % len;
+            *y = dry * (1.0 - dry_wet) + *y * dry_wet;
         }
+        end = pos;
+    }
+    state.dry_pos = end;
+}
+
+/// Slots and weights the frozen spectrum is blended from, or None when
+/// there is no captured slot at the position (auto-capture)
+fn slot_weights(slots: &[FreezeSlot], position: f32) -> Option<[(usize, f32); 2]> {
+    // Negative or NaN positions select auto-capture
+    if position.is_nan() || position < 0.0 {
+        return None;
+    }
+    let position = position.min((FREEZE_SLOTS - 1) as f32);
+    let a = position as usize;
+    let b = (a + 1).min(FREEZE_SLOTS - 1);
+    let t = position - a as f32;
+    match (slots[a].captured, slots[b].captured) {
+        (true, true) => Some([(a, 1.0 - t), (b, t)]),
+        (true, false) => Some([(a, 1.0), (b, 0.0)]),
+        (false, true) => Some([(a, 0.0), (b, 1.0)]),
+        (false, false) => None,
+    }
+}
+
+/// Write one channel's blend of two slots into a frozen spectrum
+fn fill_from_slots(
+    slots: &[FreezeSlot],
+    [(a, weight_a), (b, weight_b)]: [(usize, f32); 2],
+    channel: usize,
+    frozen_mag: &mut [f32],
+    frozen_phase: &mut [f32],
+) {
+    let (slot_a, slot_b) = (&slots[a], &slots[b]);
+    for i in 0..frozen_mag.len() {
+        let (part_a, part_b) = (slot_a.mag[channel][i] * weight_a, slot_b.mag[channel][i] * weight_b);
+        frozen_mag[i] = part_a + part_b;
+        // Interpolated phases would cancel, so each bin keeps the phase of
+        // the slot contributing most of its magnitude
+        frozen_phase[i] = if part_a >= part_b { slot_a.phase[channel][i] } else { slot_b.phase[channel][i] };
+    }
+}
+
+/// Capture the spectrum of the next analysis frame into a freeze slot
+/// 
+/// Replaces the slot's previous capture. Out-of-range slots are ignored.
+pub fn capture(slot: u32) {
+    if let Some(slot) = ensure_state().freeze_slots.get_mut(slot as usize) {
+        slot.pending = true;
+    }
+}
+
+/// Replace the auto-captured frozen spectrum with the next analysis frame
+/// 
+/// Only affects auto-capture while frozen (the next freeze captures
+/// anyway). The new snapshot engages over the freeze fade frames.
+pub fn recapture() {
+    ensure_state().is_frozen = false;
+}
+
+/// Set how the freeze engages and moves
+/// 
+/// # Arguments
+/// * `fade_frames` - Frames the freeze engages over after a capture
+///   (0-64, 0 = instantly)
+/// * `phase_drift` - Share of the live phase frozen bins keep (0-1,
+///   0 = fully static)
+pub fn set_freeze(fade_frames: u32, phase_drift: f32) {
+    let state = ensure_state();
+    state.freeze_fade_frames = fade_frames.min(MAX_FREEZE_FADE_FRAMES);
+    state.phase_drift = phase_drift.clamp(0.0, 1.0);
+}
+
+/// Set how the frozen spectrum fades and wanders
+/// 
+/// # Arguments
+/// * `decay_db_per_second` - Frozen magnitude decay from each capture
+///   (0-120dB per second, 0 = held)
+/// * `drift` - Random walk of the frozen bin magnitudes and phases each
+///   frame (0-1, 0 = static; at 1 steps of up to 1dB and 0.2 radians)
+pub fn set_freeze_motion(decay_db_per_second: f32, drift: f32) {
+    let state = ensure_state();
+    state.freeze_decay = if decay_db_per_second.is_nan() { 0.0 } else { decay_db_per_second.clamp(0.0, MAX_FREEZE_DECAY_DB) };
+    state.freeze_drift = if drift.is_nan() { 0.0 } else { drift.clamp(0.0, 1.0) };
+}
+
+/// Select the freeze/shift phase vocoder
+/// 
+/// # Arguments
+/// * `enabled` - Lock the phases of the bins around each spectral peak to
+///   the peak (identity phase locking) instead of advancing every bin on
+///   its own
+pub fn set_phase_locking(enabled: bool) {
+    ensure_state().phase_locking = enabled;
+}
+
+/// Set the shimmer feedback of the freeze/shift
+/// 
+/// # Arguments
+/// * `amount` - Gain of the wet output fed back into the analysis input
+///   (0-0.9, 0 = off); each trip around the loop is shifted again
+pub fn set_feedback(amount: f32) {
+    ensure_state().feedback = if amount.is_nan() { 0.0 } else { amount.clamp(0.0, MAX_FEEDBACK) };
+}
+
+/// Set a harmonizer voice of the freeze/shift
+/// 
+/// # Arguments
+/// * `index` - Voice (0 to MAX_VOICES - 1; others are ignored)
+/// * `semitones` - Shift of the voice from the frozen/live spectrum (-24
+///   to +24), independent of the freeze/shift's own shift
+/// * `gain` - Linear gain of the voice (0-1)
+pub fn set_voice(index: u32, semitones: f32, gain: f32) {
+    if let Some(voice) = ensure_state().voices.get_mut(index as usize) {
+        let semitones = if semitones.is_nan() { 0.0 } else { semitones.clamp(-MAX_VOICE_SEMITONES, MAX_VOICE_SEMITONES) };
+        voice.ratio = 2.0_f32.powf(semitones / 12.0);
+        voice.gain = if gain.is_nan() { 0.0 } else { gain.clamp(0.0, 1.0) };
+    }
+}
+
+/// Set how many harmonizer voices sound
+/// 
+/// Each sounding voice adds its per-bin shift and phase work to every
+/// frame (no FFTs).
+/// 
+/// # Arguments
+/// * `count` - Voices 0 to count - 1 sound (0-MAX_VOICES, 0 = off)
+pub fn set_voice_count(count: u32) {
+    let state = ensure_state();
+    let count = (count as usize).min(MAX_VOICES);
+    // Voices coming in start their phase tracks afresh
+    for phases in &mut state.voice_phases[state.voice_count.min(count)..count] {
+        phases.clear();
+    }
+    state.voice_count = count;
+}
+
+/// Empty a freeze slot (freezing at it falls back to auto-capture)
+pub fn release(slot: u32) {
+    if let Some(slot) = ensure_state().freeze_slots.get_mut(slot as usize) {
+        slot.pending = false;
+        slot.captured = false;
+    }
+}
+
+/// Whether a freeze slot holds a capture
+pub fn slot_captured(slot: u32) -> bool {
+    ensure_state().freeze_slots.get(slot as usize).is_some_and(|slot| slot.captured)
+}
+
+/// Run one audio block through the STFT framing shared by all spectral effects
+/// 
+/// Accumulates input into the analysis buffers and calls `frame_fn` every
+/// hop once a full frame is available. `frame_fn`
+/// receives the block offset of the sample that completed the frame and
+/// must overlap-add its output starting at that offset of the output buffers.
+/// 
+/// # Safety
+/// Engine must be initialized (reads input and writes output buffers).
+unsafe fn run_frames<F>(state: &mut SpectralState, frame_fn: F)
+where
+    F: FnMut(&mut SpectralState, usize),
+{
+    let input = [memory::input_slice(0), memory::input_slice(1)];
+    let output = [memory::output_slice_mut(0), memory::output_slice_mut(1)];
+    run_frames_on(state, input, output, frame_fn);
+}
+
+/// `run_frames` on a block of other buffers (left and right, all the same
+/// length)
+fn run_frames_on<F>(
+    state: &mut SpectralState,
+    [input_l, input_r]: [&[f32]; 2],
+    [output_l, output_r]: [&mut [f32]; 2],
+    mut frame_fn: F,
+) where
+    F: FnMut(&mut SpectralState, usize),
+{
+    let buffer_size = input_l.len();
+    
+    // Process sample by sample
+    for i in 0..buffer_size {
+        // Add input to buffer
+        state.input_buffer_l[state.input_pos] = input_l[i];
+        state.input_buffer_r[state.input_pos] = input_r[i];
+        state.input_pos += 1;
         
-        // Shift output buffer
-        for j in 0..(state.output_buffer_l.len() - buffer_size) {
-            state.output_buffer_l[j] = state.output_buffer_l[j + buffer_size];
-            state.output_buffer_r[j] = state.output_buffer_r[j + buffer_size];
-        }
-        for j in (state.output_buffer_l.len() - buffer_size)..state.output_buffer_l.len() {
-            state.output_buffer_l[j] = 0.0;
-            state.output_buffer_r[j] = 0.0;
+        // Process when the analysis frame is full
+        if state.input_pos >= state.fft_size {
+            frame_fn(state, i);
+            
+            // Slide the analysis frame forward by one hop
+            let hop = state.hop_size;
+            state.input_buffer_l.copy_within(hop.., 0);
+            state.input_buffer_r.copy_within(hop.., 0);
+            state.input_pos = state.fft_size - hop;
         }
+        
+        // Read from output buffer
+        output_l[i] = state.output_buffer_l[i];
+        output_r[i] = state.output_buffer_r[i];
     }
+    
+    // Shift output buffer
+    let len = state.output_buffer_l.len();
+    state.output_buffer_l.copy_within(buffer_size.., 0);
+    state.output_buffer_r.copy_within(buffer_size.., 0);
+    state.output_buffer_l[len - buffer_size..].fill(0.0);
+    state.output_buffer_r[len - buffer_size..].fill(0.0);
 }
 
 /// Process one spectral frame
@@ -218,92 +1140,249 @@ fn process_frame(
     ifft_buffer: &mut [Complex<f32>],
     frozen_mag: &mut [f32],
     frozen_phase: &mut [f32],
+    drift_mag: &mut [f32],
+    drift_phase: &mut [f32],
     prev_phase: &mut [f32],
     synth_phase: &mut [f32],
+    current_mag: &mut [f32],
+    current_phase: &mut [f32],
+    shifted_mag: &mut [f32],
+    shifted_phase: &mut [f32],
+    envelope: &mut [f32],
+    peak_of: &mut [usize],
+    gate_gains: &mut [f32],
+    gate: GateFrame,
+    eq_gains: Option<&[f32]>,
+    mask_gains: Option<&[f32]>,
     window: &[f32],
+    synthesis_window: &[f32],
+    hop_size: usize,
     freeze_amount: f32,
+    phase_drift: f32,
+    freeze_level: f32,
+    freeze_drift: f32,
     shift_ratio: f32,
-    planner: &mut FftPlanner<f32>,
-    is_frozen: &mut bool,
+    shift_bins: f32,
+    formant_preserve: bool,
+    phase_locking: bool,
+    mode: SpectralMode,
+    rng_state: &mut u32,
+    fft: &dyn Fft<f32>,
+    ifft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
+    capture: bool,
+    freeze_slots: &mut [FreezeSlot],
+    voices: &[HarmonyVoice],
+    voice_phases: &mut [VoicePhases],
+    channel: usize,
 ) {
-    let fft = planner.plan_fft_forward(FFT_SIZE);
-    let ifft = planner.plan_fft_inverse(FFT_SIZE);
+    let fft_size = fft_buffer.len();
+    let num_bins = current_mag.len();
     
     // Apply window and copy to FFT buffer
-    for i in 0..FFT_SIZE {
+    for i in 0..fft_size {
         fft_buffer[i] = Complex::new(input[i] * window[i], 0.0);
     }
     
     // FFT
-    fft.process(fft_buffer);
+    fft.process_with_scratch(fft_buffer, scratch);
     
     // Extract magnitude and phase
-    let mut current_mag = vec![0.0f32; NUM_BINS];
-    let mut current_phase = vec![0.0f32; NUM_BINS];
-    
-    for i in 0..NUM_BINS {
+    for i in 0..num_bins {
         let re = fft_buffer[i].re;
         let im = fft_buffer[i].im;
         current_mag[i] = (re * re + im * im).sqrt();
         current_phase[i] = im.atan2(re);
     }
     
+    // Input gate, ahead of the captures
+    for (mag, gain) in current_mag.iter_mut().zip(gate_gains.iter_mut()) {
+        update_gate_gain(gain, *mag, gate.threshold, gate.floor_gain, gate.close_coeff);
+        *mag *= *gain;
+    }
+    
+    // Store requested captures
+    for slot in freeze_slots.iter_mut().filter(|slot| slot.pending) {
+        slot.mag[channel].copy_from_slice(current_mag);
+        slot.phase[channel].copy_from_slice(current_phase);
+    }
+    
     // Handle freeze
     if freeze_amount > 0.0 {
-        if !*is_frozen {
+        if capture {
             // Capture frozen spectrum
-            frozen_mag.copy_from_slice(&current_mag);
-            frozen_phase.copy_from_slice(&current_phase);
-            *is_frozen = true;
+            frozen_mag.copy_from_slice(current_mag);
+            frozen_phase.copy_from_slice(current_phase);
+            drift_mag.fill(0.0);
+            drift_phase.fill(0.0);
+        }
+        
+        // Random walk of the frozen bins, leaking back to the capture (at
+        // drift 0 the offsets just leak away)
+        for (mag, phase) in drift_mag.iter_mut().zip(drift_phase.iter_mut()) {
+            *mag *= DRIFT_LEAK;
+            *phase *= DRIFT_LEAK;
+            if freeze_drift > 0.0 {
+                *mag += random_bipolar(rng_state) * freeze_drift * MAX_DRIFT_STEP_DB;
+                *phase += random_bipolar(rng_state) * freeze_drift * MAX_DRIFT_STEP_PHASE;
+            }
         }
         
         // Blend current with frozen
-        for i in 0..NUM_BINS {
-            current_mag[i] = current_mag[i] * (1.0 - freeze_amount) + frozen_mag[i] * freeze_amount;
+        let phase_hold = freeze_amount * (1.0 - phase_drift);
+        for i in 0..num_bins {
+            let drift_gain = if drift_mag[i] != 0.0 { utils::fast_db_to_linear(drift_mag[i]) } else { 1.0 };
+            let frozen = frozen_mag[i] * freeze_level * drift_gain;
+            current_mag[i] = current_mag[i] * (1.0 - freeze_amount) + frozen * freeze_amount;
             // Keep phase evolving slightly for more natural sound
-            current_phase[i] = current_phase[i] * (1.0 - freeze_amount * 0.9) 
-                             + frozen_phase[i] * freeze_amount * 0.9;
+            current_phase[i] = current_phase[i] * (1.0 - phase_hold) + (frozen_phase[i] + drift_phase[i]) * phase_hold;
+        }
+    }
+    
+    // Bin mask on the blended spectrum (the capture stays whole)
+    if let Some(gains) = mask_gains {
+        for (mag, gain) in current_mag.iter_mut().zip(gains) {
+            *mag *= gain;
         }
-    } else {
-        *is_frozen = false;
     }
     
-    // Apply frequency shift
-    let mut shifted_mag = vec![0.0f32; NUM_BINS];
-    let mut shifted_phase = vec![0.0f32; NUM_BINS];
+    // Spectral envelope for formant preservation, shared by the voices
+    let shifting = (shift_ratio - 1.0).abs() > 0.001 || shift_bins != 0.0;
+    if formant_preserve && (shifting || !voices.is_empty()) {
+        smooth_bins(current_mag, envelope, formant_smoothing_width(fft_size));
+    }
     
-    if (shift_ratio - 1.0).abs() > 0.001 {
-        // Shift bins
-        for i in 0..NUM_BINS {
-            let src_bin = i as f32 / shift_ratio;
-            let src_bin_int = src_bin as usize;
-            let frac = src_bin - src_bin_int as f32;
-            
-            if src_bin_int < NUM_BINS - 1 {
-                // Linear interpolation
-                shifted_mag[i] = current_mag[src_bin_int] * (1.0 - frac) 
-                               + current_mag[src_bin_int + 1] * frac;
-                
-                // Phase interpolation (with unwrapping)
-                let p1 = current_phase[src_bin_int];
-                let p2 = current_phase[src_bin_int + 1];
-                shifted_phase[i] = p1 + (p2 - p1) * frac;
-            } else if src_bin_int < NUM_BINS {
-                shifted_mag[i] = current_mag[src_bin_int];
-                shifted_phase[i] = current_phase[src_bin_int];
+    // Phase locking: the accumulated phase only at the (shifted) peaks; the
+    // bins around each peak keep their analysis offset from it, scaled by
+    // the shift so they stay aligned in time (every bin still accumulates,
+    // so a peak moving to any bin finds a phase track)
+    if phase_locking {
+        find_peaks(current_mag, peak_of);
+    }
+    
+    // The freeze/shift voice, then the harmonizer voices: each shifts the
+    // same spectrum and accumulates its own phases, and all of them are
+    // resynthesized together
+    ifft_buffer[..num_bins].fill(Complex::new(0.0, 0.0));
+    let main_voice = (shift_ratio, shift_bins, 1.0, prev_phase, synth_phase);
+    let harmony_voices = voices.iter().zip(voice_phases.iter_mut()).map(|(voice, phases)| {
+        let [prev_phase, synth_phase] = phases.phase_tracks(channel);
+        (voice.ratio, 0.0, voice.gain, prev_phase, synth_phase)
+    });
+    for (ratio, bins, gain, prev_phase, synth_phase) in core::iter::once(main_voice).chain(harmony_voices) {
+        shift_spectrum(current_mag, current_phase, envelope, shifted_mag, shifted_phase, ratio, bins, formant_preserve);
+        
+        // Tilt/shelf EQ on the output bins
+        if let Some(gains) = eq_gains {
+            for (mag, gain) in shifted_mag.iter_mut().zip(gains) {
+                *mag *= gain;
             }
         }
-    } else {
-        shifted_mag.copy_from_slice(&current_mag);
-        shifted_phase.copy_from_slice(&current_phase);
+        
+        accumulate_phases(shifted_phase, prev_phase, synth_phase, ratio, bins, phase_locking, hop_size);
+        add_voice(
+            ifft_buffer,
+            shifted_mag,
+            synth_phase,
+            current_phase,
+            peak_of,
+            ratio,
+            bins,
+            gain,
+            phase_locking,
+            mode,
+            rng_state,
+        );
     }
     
-    // Phase vocoder: accumulate phase
-    let hop_phase = 2.0 * PI * HOP_SIZE as f32 / FFT_SIZE as f32;
+    // Mirror for negative frequencies
+    for i in 1..num_bins - 1 {
+        ifft_buffer[fft_size - i] = ifft_buffer[i].conj();
+    }
+    
+    // IFFT
+    ifft.process_with_scratch(ifft_buffer, scratch);
+    
+    // Overlap-add with window
+    for i in 0..fft_size {
+        output[i] += ifft_buffer[i].re * synthesis_window[i];
+    }
+}
+
+/// Shift a spectrum by a ratio, then offset it by a linear shift in bins
+/// 
+/// Bins past the shifted range stay silent. With formant preservation the
+/// magnitudes are whitened by `envelope` at the source and recolored at
+/// the destination.
+#[allow(clippy::too_many_arguments)]
+fn shift_spectrum(
+    mag: &[f32],
+    phase: &[f32],
+    envelope: &[f32],
+    shifted_mag: &mut [f32],
+    shifted_phase: &mut [f32],
+    shift_ratio: f32,
+    shift_bins: f32,
+    formant_preserve: bool,
+) {
+    let num_bins = mag.len();
+    if (shift_ratio - 1.0).abs() <= 0.001 && shift_bins == 0.0 {
+        shifted_mag.copy_from_slice(mag);
+        shifted_phase.copy_from_slice(phase);
+        return;
+    }
+    
+    shifted_mag.fill(0.0);
+    shifted_phase.fill(0.0);
+    for i in 0..num_bins {
+        let src_bin = (i as f32 - shift_bins) / shift_ratio;
+        if src_bin < 0.0 {
+            // Would come from below 0Hz
+            continue;
+        }
+        let src_bin_int = src_bin as usize;
+        let frac = src_bin - src_bin_int as f32;
+        
+        if src_bin_int < num_bins - 1 {
+            // Linear interpolation
+            shifted_mag[i] = mag[src_bin_int] * (1.0 - frac) + mag[src_bin_int + 1] * frac;
+            
+            // Phase interpolation (with unwrapping)
+            let p1 = phase[src_bin_int];
+            let p2 = phase[src_bin_int + 1];
+            shifted_phase[i] = p1 + (p2 - p1) * frac;
+        } else if src_bin_int < num_bins {
+            shifted_mag[i] = mag[src_bin_int];
+            shifted_phase[i] = phase[src_bin_int];
+        }
+        
+        // Whitened by the envelope at the source, recolored by the
+        // envelope at the destination
+        if formant_preserve && src_bin_int < num_bins {
+            shifted_mag[i] *= envelope[i] / (sample_bins(envelope, src_bin) + VOCODER_EPSILON);
+        }
+    }
+}
+
+/// Phase vocoder: advance a voice's synthesis phases by the shifted true
+/// frequency of each bin
+fn accumulate_phases(
+    shifted_phase: &[f32],
+    prev_phase: &mut [f32],
+    synth_phase: &mut [f32],
+    shift_ratio: f32,
+    shift_bins: f32,
+    phase_locking: bool,
+    hop_size: usize,
+) {
+    let fft_size = (shifted_phase.len() - 1) * 2;
+    let hop_phase = 2.0 * PI * hop_size as f32 / fft_size as f32;
     
-    for i in 0..NUM_BINS {
-        // Expected phase advance
-        let expected_phase = prev_phase[i] + i as f32 * hop_phase;
+    for i in 0..shifted_phase.len() {
+        // Expected phase advance (with phase locking, of the source bin the
+        // shifted phase was taken from)
+        let src_bin = if phase_locking { (i as f32 - shift_bins) / shift_ratio } else { i as f32 };
+        let expected_phase = prev_phase[i] + src_bin * hop_phase;
         
         // Phase deviation
         let phase_diff = shifted_phase[i] - expected_phase;
@@ -312,44 +1391,773 @@ fn process_frame(
         let wrapped = phase_diff - (phase_diff / (2.0 * PI)).round() * 2.0 * PI;
         
         // True frequency
-        let true_freq = i as f32 + wrapped / hop_phase;
+        let true_freq = src_bin + wrapped / hop_phase;
         
         // Accumulate synthesis phase
-        synth_phase[i] += true_freq * hop_phase * shift_ratio;
+        synth_phase[i] += true_freq * hop_phase * shift_ratio + shift_bins * hop_phase;
         
         prev_phase[i] = shifted_phase[i];
     }
-    
-    // Reconstruct complex spectrum
-    for i in 0..NUM_BINS {
-        let mag = shifted_mag[i];
-        let phase = synth_phase[i];
-        ifft_buffer[i] = Complex::new(mag * phase.cos(), mag * phase.sin());
-        
-        // Mirror for negative frequencies
-        if i > 0 && i < NUM_BINS - 1 {
-            ifft_buffer[FFT_SIZE - i] = ifft_buffer[i].conj();
-        }
-    }
-    
-    // IFFT
-    ifft.process(ifft_buffer);
-    
-    // Overlap-add with window
-    let scale = 1.0 / FFT_SIZE as f32;
-    for i in 0..FFT_SIZE {
-        output[i] += ifft_buffer[i].re * window[i] * scale;
+}
+
+/// Add a voice's shifted magnitudes at its synthesis phases to the
+/// positive-frequency bins of the spectrum
+/// 
+/// The accumulated phases keep running under robot and whisper, so
+/// switching back is seamless.
+#[allow(clippy::too_many_arguments)]
+fn add_voice(
+    spectrum: &mut [Complex<f32>],
+    shifted_mag: &[f32],
+    synth_phase: &[f32],
+    current_phase: &[f32],
+    peak_of: &[usize],
+    shift_ratio: f32,
+    shift_bins: f32,
+    gain: f32,
+    phase_locking: bool,
+    mode: SpectralMode,
+    rng_state: &mut u32,
+) {
+    let num_bins = shifted_mag.len();
+    for i in 0..num_bins {
+        let mag = shifted_mag[i] * gain;
+        let phase = match mode {
+            SpectralMode::Normal if phase_locking => {
+                // Sources below 0Hz saturate to bin 0 (their bins are
+                // silent anyway)
+                let src = ((i as f32 - shift_bins) / shift_ratio).round() as usize;
+                match peak_of.get(src).copied().unwrap_or(NO_PEAK) {
+                    NO_PEAK => synth_phase[i],
+                    peak => {
+                        let target = ((peak as f32 * shift_ratio + shift_bins).round() as usize).min(num_bins - 1);
+                        synth_phase[target] + wrap_phase(current_phase[src] - current_phase[peak]) * shift_ratio
+                    }
+                }
+            }
+            SpectralMode::Normal => synth_phase[i],
+            // Zero phase about the frame center, where the window peaks
+            SpectralMode::Robot => (i % 2) as f32 * PI,
+            SpectralMode::Whisper => random_bipolar(rng_state) * PI,
+        };
+        spectrum[i] += Complex::new(mag * phase.cos(), mag * phase.sin());
     }
 }
 
 // ============================================================================
-// UTILITY
+// VOCODER
 // ============================================================================
 
-/// Reset spectral state
-pub fn reset() {
-    // SAFETY: Single-threaded WASM context
-    let state_ptr = unsafe { addr_of_mut!(STATE) };
+/// Load the carrier signal for the channel vocoder
+/// 
+/// # Arguments
+/// * `_ptr` - Pointer (not used, samples are at VOCODER_CARRIER_OFFSET)
+/// * `length` - Number of sample frames
+/// * `channels` - Number of channels (1 or 2, stereo is mixed to mono)
+/// 
+/// # Note
+/// The actual samples are written to WASM memory by JavaScript at
+/// VOCODER_CARRIER_OFFSET before calling this function. Lengths beyond
+/// the region are truncated.
+pub fn load_vocoder_carrier(_ptr: *const f32, length: u32, channels: u32) {
+    let state = ensure_state();
+    let channels = channels.clamp(1, 2);
+    let max_frames = memory::MAX_VOCODER_CARRIER_SAMPLES / channels as usize;
+    
+    state.carrier_frames = (length as usize).min(max_frames);
+    state.carrier_channels = channels;
+    state.carrier_pos = 0;
+}
+
+/// Process the channel vocoder
+/// 
+/// The input is the modulator; its spectral envelope shapes the carrier
+/// loaded with `load_vocoder_carrier`. The carrier loops seamlessly when
+/// it is shorter than the input stream. Without a carrier the output is silent.
+/// 
+/// # Arguments
+/// * `bands` - Envelope resolution (4 to the bin count); fewer bands =
+///   more smoothing across bins
+/// * `formant_shift` - Shift of the modulator envelope in semitones (-12 to +12)
+pub fn process_vocoder(bands: f32, formant_shift: f32) {
+    let state = ensure_state();
+    
+    let bands = bands.clamp(MIN_VOCODER_BANDS, state.num_bins as f32);
+    let smoothing_width = ((state.num_bins as f32 / bands) as usize).max(1);
+    let formant_ratio = 2.0_f32.powf(formant_shift.clamp(-12.0, 12.0) / 12.0);
+    
+    unsafe {
+        if state.carrier_frames == 0 {
+            simd_utils::clear_buffer(memory::output_slice_mut(0));
+            simd_utils::clear_buffer(memory::output_slice_mut(1));
+            return;
+        }
+        
+        let carrier = std::slice::from_raw_parts(
+            memory::get_vocoder_carrier_ptr() as *const f32,
+            state.carrier_frames * state.carrier_channels as usize,
+        );
+        let block_start = state.carrier_pos;
+        
+        run_frames(state, |state, offset| {
+            // The carrier is analyzed once per frame and shared by both channels
+            analyze_carrier(state, carrier, block_start + offset, smoothing_width);
+            
+            vocode_frame(
+                &state.input_buffer_l,
+                &mut state.output_buffer_l[offset..],
+                &mut state.fft_buffer,
+                &mut state.ifft_buffer,
+                &state.carrier_spectrum,
+                &state.carrier_env,
+                &mut state.mod_env,
+                &mut state.envelope_scratch,
+                &state.window,
+                &state.synthesis_window,
+                smoothing_width,
+                formant_ratio,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+            );
+            vocode_frame(
+                &state.input_buffer_r,
+                &mut state.output_buffer_r[offset..],
+                &mut state.fft_buffer,
+                &mut state.ifft_buffer,
+                &state.carrier_spectrum,
+                &state.carrier_env,
+                &mut state.mod_env,
+                &mut state.envelope_scratch,
+                &state.window,
+                &state.synthesis_window,
+                smoothing_width,
+                formant_ratio,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+            );
+        });
+        
+        let buffer_size = memory::buffer_size() as usize;
+        state.carrier_pos = (state.carrier_pos + buffer_size) % state.carrier_frames;
+    }
+}
+
+/// FFT the carrier frame ending at `end_pos` and compute its envelope
+/// 
+/// Reads wrap around the carrier, so a looping carrier has no seam.
+fn analyze_carrier(
+    state: &mut SpectralState,
+    carrier: &[f32],
+    end_pos: usize,
+    smoothing_width: usize,
+) {
+    let frames = state.carrier_frames;
+    let stereo = state.carrier_channels == 2;
+    // First frame of the analysis window, wrapped into the carrier
+    let start = (end_pos + 1 + frames - state.fft_size % frames) % frames;
+    
+    for (i, (bin, w)) in state.carrier_spectrum.iter_mut().zip(&state.window).enumerate() {
+        let idx = (start + i) % frames;
+        let sample = if stereo {
+            (carrier[idx * 2] + carrier[idx * 2 + 1]) * 0.5
+        } else {
+            carrier[idx]
+        };
+        *bin = Complex::new(sample * w, 0.0);
+    }
+    
+    state.fft.process_with_scratch(&mut state.carrier_spectrum, &mut state.fft_scratch);
+    
+    for (mag, c) in state.envelope_scratch.iter_mut().zip(&state.carrier_spectrum) {
+        *mag = c.norm();
+    }
+    smooth_bins(&state.envelope_scratch, &mut state.carrier_env, smoothing_width);
+}
+
+/// Apply one channel's modulator envelope to the current carrier spectrum
+#[allow(clippy::too_many_arguments)]
+fn vocode_frame(
+    input: &[f32],
+    output: &mut [f32],
+    fft_buffer: &mut [Complex<f32>],
+    ifft_buffer: &mut [Complex<f32>],
+    carrier_spectrum: &[Complex<f32>],
+    carrier_env: &[f32],
+    mod_env: &mut [f32],
+    mag_scratch: &mut [f32],
+    window: &[f32],
+    synthesis_window: &[f32],
+    smoothing_width: usize,
+    formant_ratio: f32,
+    fft: &dyn Fft<f32>,
+    ifft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
+) {
+    // Analyze modulator
+    for ((bin, x), w) in fft_buffer.iter_mut().zip(input).zip(window) {
+        *bin = Complex::new(x * w, 0.0);
+    }
+    fft.process_with_scratch(fft_buffer, scratch);
+    
+    for (mag, c) in mag_scratch.iter_mut().zip(fft_buffer.iter()) {
+        *mag = c.norm();
+    }
+    smooth_bins(mag_scratch, mod_env, smoothing_width);
+    
+    // Whitened carrier times (formant-shifted) modulator envelope
+    let (fft_size, num_bins) = (fft_buffer.len(), mod_env.len());
+    for i in 0..num_bins {
+        let env = sample_bins(mod_env, i as f32 / formant_ratio);
+        let gain = env / (carrier_env[i] + VOCODER_EPSILON);
+        ifft_buffer[i] = carrier_spectrum[i] * gain;
+        
+        // Mirror for negative frequencies
+        if i > 0 && i < num_bins - 1 {
+            ifft_buffer[fft_size - i] = ifft_buffer[i].conj();
+        }
+    }
+    
+    ifft.process_with_scratch(ifft_buffer, scratch);
+    
+    // Overlap-add with window
+    for ((out, c), w) in output.iter_mut().zip(ifft_buffer.iter()).zip(synthesis_window) {
+        *out += c.re * w;
+    }
+}
+
+/// Moving-average smoothing across bins (centered box of `width` bins)
+fn smooth_bins(src: &[f32], dst: &mut [f32], width: usize) {
+    let len = src.len();
+    let half = width / 2;
+    let mut sum = 0.0;
+    let mut lo = 0;
+    let mut hi = 0;
+    
+    // Running sum over [lo, hi) tracking the window around each bin
+    for (i, out) in dst.iter_mut().enumerate().take(len) {
+        let want_lo = i.saturating_sub(half);
+        let want_hi = (i + half + 1).min(len);
+        while hi < want_hi {
+            sum += src[hi];
+            hi += 1;
+        }
+        while lo < want_lo {
+            sum -= src[lo];
+            lo += 1;
+        }
+        *out = sum.max(0.0) / (hi - lo) as f32;
+    }
+}
+
+/// Read a bin array at a fractional bin position (linear interpolation)
+/// 
+/// Positions past the last bin read as 0.0.
+#[inline]
+fn sample_bins(bins: &[f32], pos: f32) -> f32 {
+    let idx = pos as usize;
+    if idx + 1 < bins.len() {
+        let frac = pos - idx as f32;
+        bins[idx] + (bins[idx + 1] - bins[idx]) * frac
+    } else if idx < bins.len() {
+        bins[idx]
+    } else {
+        0.0
+    }
+}
+
+// ============================================================================
+// PITCH SHIFT
+// ============================================================================
+
+/// Process duration-preserving pitch shift
+/// 
+/// # Arguments
+/// * `semitones` - Pitch shift in semitones (-24 to +24)
+/// * `formant_preserve` - Keep the spectral envelope in place while the
+///   harmonics move (avoids the "chipmunk" effect on voices)
+pub fn process_pitch_shift(semitones: f32, formant_preserve: bool) {
+    unsafe {
+        // SAFETY: Single-threaded WASM context; the I/O buffers don't
+        // overlap the spectral state
+        let input = [memory::input_slice(0), memory::input_slice(1)];
+        let output = [memory::output_slice_mut(0), memory::output_slice_mut(1)];
+        pitch_shift_block(input, output, semitones, formant_preserve);
+    }
+}
+
+/// Pitch shift a block of other buffers (left and right, each one engine
+/// block long)
+/// 
+/// Shares the framing and phase state of `process_pitch_shift`, so the
+/// two shouldn't run in the same block. The shimmer reverb shifts its
+/// feedback with this.
+pub fn pitch_shift_block(input: [&[f32]; 2], output: [&mut [f32]; 2], semitones: f32, formant_preserve: bool) {
+    let state = ensure_state();
+    let ratio = 2.0_f32.powf(semitones.clamp(-24.0, 24.0) / 12.0);
+    
+    run_frames_on(state, input, output, |state, offset| {
+        pitch_shift_frame(
+            &state.input_buffer_l,
+            &mut state.output_buffer_l[offset..],
+            &mut state.fft_buffer,
+            &mut state.ifft_buffer,
+            &mut state.shift_prev_phase_l,
+            &mut state.shift_synth_phase_l,
+            &mut state.analysis_mag,
+            &mut state.analysis_phase,
+            &mut state.analysis_freq,
+            &mut state.peak_of,
+            &mut state.spectral_env,
+            &state.window,
+            &state.synthesis_window,
+            state.hop_size,
+            ratio,
+            formant_preserve,
+            &*state.fft,
+            &*state.ifft,
+            &mut state.fft_scratch,
+        );
+        pitch_shift_frame(
+            &state.input_buffer_r,
+            &mut state.output_buffer_r[offset..],
+            &mut state.fft_buffer,
+            &mut state.ifft_buffer,
+            &mut state.shift_prev_phase_r,
+            &mut state.shift_synth_phase_r,
+            &mut state.analysis_mag,
+            &mut state.analysis_phase,
+            &mut state.analysis_freq,
+            &mut state.peak_of,
+            &mut state.spectral_env,
+            &state.window,
+            &state.synthesis_window,
+            state.hop_size,
+            ratio,
+            formant_preserve,
+            &*state.fft,
+            &*state.ifft,
+            &mut state.fft_scratch,
+        );
+    });
+}
+
+/// Pitch shift one channel's spectral frame
+#[allow(clippy::too_many_arguments)]
+fn pitch_shift_frame(
+    input: &[f32],
+    output: &mut [f32],
+    fft_buffer: &mut [Complex<f32>],
+    ifft_buffer: &mut [Complex<f32>],
+    prev_phase: &mut [f32],
+    synth_phase: &mut [f32],
+    mag: &mut [f32],
+    phase: &mut [f32],
+    true_freq: &mut [f32],
+    peak_of: &mut [usize],
+    envelope: &mut [f32],
+    window: &[f32],
+    synthesis_window: &[f32],
+    hop_size: usize,
+    ratio: f32,
+    formant_preserve: bool,
+    fft: &dyn Fft<f32>,
+    ifft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
+) {
+    for ((bin, x), w) in fft_buffer.iter_mut().zip(input).zip(window) {
+        *bin = Complex::new(x * w, 0.0);
+    }
+    fft.process_with_scratch(fft_buffer, scratch);
+    
+    // Analysis: magnitude, phase and true frequency (in bins) of each bin
+    let (fft_size, num_bins) = (fft_buffer.len(), mag.len());
+    let hop_phase = 2.0 * PI * hop_size as f32 / fft_size as f32;
+    for i in 0..num_bins {
+        let c = fft_buffer[i];
+        mag[i] = c.norm();
+        phase[i] = c.im.atan2(c.re);
+        
+        let deviation = wrap_phase(phase[i] - prev_phase[i] - i as f32 * hop_phase);
+        true_freq[i] = i as f32 + deviation / hop_phase;
+        prev_phase[i] = phase[i];
+    }
+    
+    find_peaks(mag, peak_of);
+    if formant_preserve {
+        smooth_bins(mag, envelope, formant_smoothing_width(fft_size));
+    }
+    
+    // Advance every output bin at the shifted frequency of its source bin,
+    // so a peak landing on any bin finds a continuous phase track
+    for (i, acc) in synth_phase.iter_mut().enumerate() {
+        let src = (i as f32 / ratio).round() as usize;
+        let freq = if src < num_bins { true_freq[src] } else { i as f32 / ratio };
+        *acc = wrap_phase(*acc + freq * ratio * hop_phase);
+    }
+    
+    // Resynthesis: resampled magnitudes, phases locked to the owning peak
+    for i in 0..num_bins {
+        let src_pos = i as f32 / ratio;
+        let src = src_pos.round() as usize;
+        
+        ifft_buffer[i] = if src < num_bins {
+            let mut m = sample_bins(mag, src_pos);
+            if formant_preserve {
+                m *= envelope[i] / (sample_bins(envelope, src_pos) + VOCODER_EPSILON);
+            }
+            
+            let p = match peak_of[src] {
+                NO_PEAK => synth_phase[i],
+                peak => {
+                    // Phase of the shifted peak plus this bin's analysis offset from it
+                    let target = ((peak as f32 * ratio).round() as usize).min(num_bins - 1);
+                    synth_phase[target] + phase[src] - phase[peak]
+                }
+            };
+            Complex::from_polar(m, p)
+        } else {
+            Complex::new(0.0, 0.0)
+        };
+        
+        // Mirror for negative frequencies
+        if i > 0 && i < num_bins - 1 {
+            ifft_buffer[fft_size - i] = ifft_buffer[i].conj();
+        }
+    }
+    
+    ifft.process_with_scratch(ifft_buffer, scratch);
+    
+    // Overlap-add with window
+    for ((out, c), w) in output.iter_mut().zip(ifft_buffer.iter()).zip(synthesis_window) {
+        *out += c.re * w;
+    }
+}
+
+/// Envelope smoothing width in bins for formant preservation
+/// 
+/// Same width in Hz at any FFT size (odd, so the box stays centered).
+#[inline]
+fn formant_smoothing_width(fft_size: usize) -> usize {
+    (FORMANT_SMOOTHING_BINS * fft_size / DEFAULT_FFT_SIZE) | 1
+}
+
+/// Map each bin to its nearest local magnitude maximum
+/// 
+/// Bins are assigned to the closest peak; NO_PEAK when the frame has none.
+fn find_peaks(mag: &[f32], peak_of: &mut [usize]) {
+    let len = mag.len();
+    let is_peak = |i: usize| {
+        mag[i] > 0.0
+            && (i == 0 || mag[i] > mag[i - 1])
+            && (i + 1 == len || mag[i] >= mag[i + 1])
+    };
+    
+    // Forward pass: nearest peak at or below each bin
+    let mut last = NO_PEAK;
+    for (i, owner) in peak_of.iter_mut().enumerate().take(len) {
+        if is_peak(i) {
+            last = i;
+        }
+        *owner = last;
+    }
+    
+    // Backward pass: take the peak above when it is closer
+    let mut next = NO_PEAK;
+    for i in (0..len).rev() {
+        if is_peak(i) {
+            next = i;
+        }
+        if next != NO_PEAK && (peak_of[i] == NO_PEAK || next - i < i - peak_of[i]) {
+            peak_of[i] = next;
+        }
+    }
+}
+
+/// Next value of the LCG in [-1, 1)
+#[inline]
+fn random_bipolar(rng_state: &mut u32) -> f32 {
+    *rng_state = rng_state.wrapping_mul(1664525).wrapping_add(1013904223);
+    *rng_state as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Wrap a phase to [-π, π]
+#[inline]
+fn wrap_phase(phase: f32) -> f32 {
+    phase - (phase / (2.0 * PI)).round() * 2.0 * PI
+}
+
+// ============================================================================
+// SPECTRAL GATE
+// ============================================================================
+
+/// Process spectral noise gate
+/// 
+/// # Arguments
+/// * `threshold_db` - Bin level below which bins are attenuated, in dBFS
+///   (-inf opens the gate fully)
+/// * `reduction_db` - Attenuation applied to gated bins (0 to 120dB)
+pub fn process_spectral_gate(threshold_db: f32, reduction_db: f32) {
+    let state = ensure_state();
+    
+    let threshold = bin_threshold(state, threshold_db);
+    let floor_gain = utils::db_to_linear(-reduction_db.clamp(0.0, MAX_GATE_REDUCTION_DB));
+    
+    unsafe {
+        run_frames(state, |state, offset| {
+            gate_frame(
+                &state.input_buffer_l,
+                &mut state.output_buffer_l[offset..],
+                &mut state.fft_buffer,
+                &mut state.gate_gain_l,
+                &state.window,
+                &state.synthesis_window,
+                threshold,
+                floor_gain,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+            );
+            gate_frame(
+                &state.input_buffer_r,
+                &mut state.output_buffer_r[offset..],
+                &mut state.fft_buffer,
+                &mut state.gate_gain_r,
+                &state.window,
+                &state.synthesis_window,
+                threshold,
+                floor_gain,
+                &*state.fft,
+                &*state.ifft,
+                &mut state.fft_scratch,
+            );
+        });
+    }
+}
+
+/// Move a bin's gate gain one frame toward open or closed
+/// 
+/// Opens quickly to keep transients; closes at `close_coeff` per frame.
+#[inline]
+fn update_gate_gain(gain: &mut f32, magnitude: f32, threshold: f32, floor_gain: f32, close_coeff: f32) {
+    let target = if magnitude < threshold { floor_gain } else { 1.0 };
+    let coeff = if target > *gain { GATE_OPEN_COEFF } else { close_coeff };
+    *gain += (target - *gain) * coeff;
+    // Settle exactly so a fully open gate is a true passthrough
+    if (target - *gain).abs() < 1e-6 {
+        *gain = target;
+    }
+}
+
+/// Bin threshold of a level in dBFS
+/// 
+/// Bin magnitude of a full-scale sine is window_sum / 2.
+fn bin_threshold(state: &SpectralState, threshold_db: f32) -> f32 {
+    let window_sum: f32 = state.window.iter().sum();
+    utils::db_to_linear(threshold_db) * window_sum * 0.5
+}
+
+/// Per-frame constants of the freeze effect's input gate
+fn gate_frame_constants(state: &SpectralState, gate: InputGate) -> GateFrame {
+    let release_samples = gate.release_ms * 0.001 * memory::sample_rate();
+    GateFrame {
+        threshold: bin_threshold(state, gate.threshold_db),
+        floor_gain: utils::db_to_linear(-gate.reduction_db),
+        close_coeff: 1.0 - (-(state.hop_size as f32) / release_samples).exp(),
+    }
+}
+
+/// Set the spectral freeze effect's input gate
+/// 
+/// Cleans up the analysis (e.g. a noisy field recording) before it is
+/// frozen or shifted. Separate from the standalone spectral gate effect.
+/// 
+/// # Arguments
+/// * `threshold_db` - Bin level below which bins are attenuated, in dBFS
+///   (-inf opens the gate)
+/// * `reduction_db` - Attenuation applied to gated bins (0 to 120dB)
+/// * `release_ms` - Time for a gated bin to close (1-5000ms; bins open
+///   within a frame)
+pub fn set_input_gate(threshold_db: f32, reduction_db: f32, release_ms: f32) {
+    let state = ensure_state();
+    state.input_gate = InputGate {
+        threshold_db: if threshold_db.is_nan() { f32::NEG_INFINITY } else { threshold_db },
+        reduction_db: reduction_db.clamp(0.0, MAX_GATE_REDUCTION_DB),
+        release_ms: release_ms.clamp(MIN_GATE_RELEASE_MS, MAX_GATE_RELEASE_MS),
+    };
+}
+
+/// Tilt the spectral freeze effect's output spectrum
+/// 
+/// # Arguments
+/// * `db_per_octave` - Tilt about the pivot (-12 to +12dB per octave,
+///   positive = brighter; 0 = off). Bin gains are capped at ±24dB.
+/// * `pivot_hz` - Frequency the tilt leaves unchanged (20Hz-Nyquist)
+pub fn set_tilt(db_per_octave: f32, pivot_hz: f32) {
+    let state = ensure_state();
+    let nyquist = memory::sample_rate() * 0.5;
+    state.eq.tilt_db = if db_per_octave.is_nan() {
+        0.0
+    } else {
+        db_per_octave.clamp(-MAX_TILT_DB_PER_OCTAVE, MAX_TILT_DB_PER_OCTAVE)
+    };
+    state.eq.pivot_hz = if pivot_hz.is_nan() { DEFAULT_TILT_PIVOT_HZ } else { pivot_hz.clamp(MIN_EQ_FREQ_HZ, nyquist) };
+    update_eq_gains(state);
+}
+
+/// Set the spectral freeze effect's low and high shelves
+/// 
+/// Each shelf moves its side of the spectrum by its gain, with half the
+/// gain at the corner frequency.
+/// 
+/// # Arguments
+/// * `low_hz` - Low shelf corner (20Hz-Nyquist)
+/// * `low_db` - Low shelf gain (±24dB, 0 = off)
+/// * `high_hz` - High shelf corner (20Hz-Nyquist)
+/// * `high_db` - High shelf gain (±24dB, 0 = off)
+pub fn set_shelves(low_hz: f32, low_db: f32, high_hz: f32, high_db: f32) {
+    let state = ensure_state();
+    let nyquist = memory::sample_rate() * 0.5;
+    let freq = |hz: f32, default: f32| if hz.is_nan() { default } else { hz.clamp(MIN_EQ_FREQ_HZ, nyquist) };
+    let gain = |db: f32| if db.is_nan() { 0.0 } else { db.clamp(-MAX_EQ_GAIN_DB, MAX_EQ_GAIN_DB) };
+    state.eq.low_shelf_hz = freq(low_hz, DEFAULT_LOW_SHELF_HZ);
+    state.eq.low_shelf_db = gain(low_db);
+    state.eq.high_shelf_hz = freq(high_hz, DEFAULT_HIGH_SHELF_HZ);
+    state.eq.high_shelf_db = gain(high_db);
+    update_eq_gains(state);
+}
+
+/// Recompute the per-bin EQ gains from the EQ settings
+fn update_eq_gains(state: &mut SpectralState) {
+    let bin_hz = memory::sample_rate() / state.fft_size as f32;
+    let eq = state.eq;
+    for (i, gain) in state.eq_gains.iter_mut().enumerate() {
+        // DC takes the first bin's gain (the tilt has no value at 0Hz)
+        *gain = utils::db_to_linear(eq.gain_db(i.max(1) as f32 * bin_hz));
+    }
+}
+
+/// Set the comb of the spectral freeze effect's bin mask
+/// 
+/// Bins around each harmonic of the fundamental pass, with a raised
+/// cosine falloff; the bins between the harmonics are attenuated by the
+/// depth.
+/// 
+/// # Arguments
+/// * `fundamental_hz` - Fundamental whose harmonics pass (20Hz-Nyquist)
+/// * `width_bins` - Width of each pass band at half gain (0.5-64 bins)
+/// * `depth` - Attenuation between the pass bands (0-1, 0 = off, 1 = only
+///   the pass bands sound)
+pub fn set_comb(fundamental_hz: f32, width_bins: f32, depth: f32) {
+    let state = ensure_state();
+    let nyquist = memory::sample_rate() * 0.5;
+    state.comb = SpectralComb {
+        fundamental_hz: if fundamental_hz.is_nan() { nyquist } else { fundamental_hz.clamp(MIN_COMB_HZ, nyquist) },
+        width_bins: if width_bins.is_nan() { 1.0 } else { width_bins.clamp(MIN_COMB_WIDTH_BINS, MAX_COMB_WIDTH_BINS) },
+        depth: if depth.is_nan() { 0.0 } else { depth.clamp(0.0, 1.0) },
+    };
+    update_mask_gains(state);
+}
+
+/// Load the per-bin gain mask of the spectral freeze effect
+/// 
+/// Call after writing the gains (0 = bin removed, 1 = passed; one per bin
+/// from DC up) to the SPECTRAL_MASK_OFFSET region. A mask of another
+/// length than the FFT's bins is stretched across them, so it keeps its
+/// frequencies (relative to Nyquist) through `set_fft`.
+/// 
+/// # Arguments
+/// * `num_bins` - Gains written (0 = clear the mask)
+/// 
+/// # Returns
+/// LOAD_OK, or LOAD_TRUNCATED when `num_bins` exceeds
+/// MAX_SPECTRAL_MASK_BINS (the gains past it are ignored)
+pub fn load_mask(num_bins: u32) -> u32 {
+    let len = (num_bins as usize).min(memory::MAX_SPECTRAL_MASK_BINS);
+    let state = ensure_state();
+    state.mask.clear();
+    // SAFETY: `len` is within the mask region
+    let gains = unsafe { memory::spectral_mask_slice(len) };
+    state.mask.extend(gains.iter().map(|gain| if gain.is_nan() { 0.0 } else { gain.clamp(0.0, 1.0) }));
+    update_mask_gains(state);
+    if len < num_bins as usize { memory::LOAD_TRUNCATED } else { memory::LOAD_OK }
+}
+
+/// Recompute the per-bin mask gains from the comb and the loaded mask
+fn update_mask_gains(state: &mut SpectralState) {
+    let fundamental_bins = state.comb.fundamental_hz * state.fft_size as f32 / memory::sample_rate();
+    let stretch = state.mask.len().saturating_sub(1) as f32 / (state.num_bins - 1) as f32;
+    for (i, gain) in state.mask_gains.iter_mut().enumerate() {
+        *gain = if state.comb.depth > 0.0 { state.comb.gain(i as f32, fundamental_bins) } else { 1.0 };
+        if !state.mask.is_empty() {
+            *gain *= sample_bins(&state.mask, i as f32 * stretch);
+        }
+    }
+}
+
+/// Gate one channel's spectral frame
+#[allow(clippy::too_many_arguments)]
+fn gate_frame(
+    input: &[f32],
+    output: &mut [f32],
+    fft_buffer: &mut [Complex<f32>],
+    gains: &mut [f32],
+    window: &[f32],
+    synthesis_window: &[f32],
+    threshold: f32,
+    floor_gain: f32,
+    fft: &dyn Fft<f32>,
+    ifft: &dyn Fft<f32>,
+    scratch: &mut [Complex<f32>],
+) {
+    for ((bin, x), w) in fft_buffer.iter_mut().zip(input).zip(window) {
+        *bin = Complex::new(x * w, 0.0);
+    }
+    fft.process_with_scratch(fft_buffer, scratch);
+    
+    let (fft_size, num_bins) = (fft_buffer.len(), gains.len());
+    for (i, gain) in gains.iter_mut().enumerate() {
+        update_gate_gain(gain, fft_buffer[i].norm(), threshold, floor_gain, GATE_CLOSE_COEFF);
+        
+        // Scale both halves of the spectrum to keep it conjugate-symmetric
+        fft_buffer[i] *= *gain;
+        if i > 0 && i < num_bins - 1 {
+            fft_buffer[fft_size - i] *= *gain;
+        }
+    }
+    
+    ifft.process_with_scratch(fft_buffer, scratch);
+    
+    // Overlap-add with window
+    for ((out, c), w) in output.iter_mut().zip(fft_buffer.iter()).zip(synthesis_window) {
+        *out += c.re * w;
+    }
+}
+
+// ============================================================================
+// UTILITY
+// ============================================================================
+
+/// Latency of the spectral effects in samples
+/// 
+/// A frame's output starts at the sample that completes it, so the first
+/// sample of every frame comes out FFT size - 1 samples after it went in.
+/// All spectral effects share this framing.
+/// 
+/// Reads the FFT size without creating the state (the default applies
+/// until the first spectral call).
+pub fn latency_samples() -> u32 {
+    // SAFETY: Single-threaded WASM context
+    let state_ptr = unsafe { addr_of!((*addr_of!(STATES))[memory::current_engine()]) };
+    let fft_size = unsafe { (*state_ptr).as_ref() }.map_or(DEFAULT_FFT_SIZE, |state| state.fft_size);
+    (fft_size - 1) as u32
+}
+
+/// Reset spectral state
+pub fn reset() {
+    // SAFETY: Single-threaded WASM context
+    let state_ptr = unsafe { addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]) };
     if let Some(state) = unsafe { (*state_ptr).as_mut() } {
         state.input_buffer_l.fill(0.0);
         state.input_buffer_r.fill(0.0);
@@ -364,6 +2172,1148 @@ pub fn reset() {
         state.synth_phase_l.fill(0.0);
         state.synth_phase_r.fill(0.0);
         state.input_pos = 0;
+        state.feedback_l.fill(0.0);
+        state.feedback_r.fill(0.0);
+        state.voice_phases.iter_mut().for_each(VoicePhases::clear);
         state.is_frozen = false;
+        state.freeze_fade = 0.0;
+        state.freeze_level = 1.0;
+        state.drift_mag_l.fill(0.0);
+        state.drift_mag_r.fill(0.0);
+        state.drift_phase_l.fill(0.0);
+        state.drift_phase_r.fill(0.0);
+        state.dry_delay_l.fill(0.0);
+        state.dry_delay_r.fill(0.0);
+        state.dry_pos = 0;
+        state.carrier_pos = 0;
+        state.shift_prev_phase_l.fill(0.0);
+        state.shift_prev_phase_r.fill(0.0);
+        state.shift_synth_phase_l.fill(0.0);
+        state.shift_synth_phase_r.fill(0.0);
+        state.gate_gain_l.fill(1.0);
+        state.gate_gain_r.fill(1.0);
+        state.input_gate_gain_l.fill(1.0);
+        state.input_gate_gain_r.fill(1.0);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SAMPLE_RATE: f32 = 48000.0;
+    const BLOCK: usize = 128;
+    
+    /// Deterministic white noise in [-1, 1)
+    fn noise(len: usize) -> Vec<f32> {
+        let mut state: u32 = 1;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+                (state as f32 / u32::MAX as f32) * 2.0 - 1.0
+            })
+            .collect()
+    }
+    
+    /// Spectral energy of the signal within [lo_hz, hi_hz]
+    fn band_energy(signal: &[f32], lo_hz: f32, hi_hz: f32) -> f32 {
+        let n = signal.len();
+        let mut spectrum: Vec<Complex<f32>> =
+            signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+        
+        let bin_hz = SAMPLE_RATE / n as f32;
+        spectrum
+            .iter()
+            .enumerate()
+            .take(n / 2)
+            .filter(|(k, _)| (lo_hz..=hi_hz).contains(&(*k as f32 * bin_hz)))
+            .map(|(_, c)| c.norm_sqr())
+            .sum()
+    }
+    
+    /// Fraction of the signal's energy within [lo_hz, hi_hz]
+    fn band_energy_ratio(signal: &[f32], lo_hz: f32, hi_hz: f32) -> f32 {
+        band_energy(signal, lo_hz, hi_hz) / band_energy(signal, 0.0, SAMPLE_RATE)
+    }
+    
+    #[test]
+    fn test_vocoder_concentrates_energy_at_modulator_frequency() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        
+        // Noise carrier, shorter than the stream so it has to loop
+        let carrier = noise(3000);
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_vocoder_carrier_ptr(), carrier.len())
+                .copy_from_slice(&carrier);
+        }
+        load_vocoder_carrier(core::ptr::null(), carrier.len() as u32, 1);
+        
+        // 1kHz sine modulator
+        let freq = 1000.0;
+        let blocks = 64;
+        let mut rendered = Vec::with_capacity(blocks * BLOCK);
+        for b in 0..blocks {
+            unsafe {
+                let input_l = memory::get_input_buffer(0);
+                let input_r = memory::get_input_buffer(1);
+                for i in 0..BLOCK {
+                    let t = (b * BLOCK + i) as f32 / SAMPLE_RATE;
+                    let x = (2.0 * PI * freq * t).sin() * 0.5;
+                    *input_l.add(i) = x;
+                    *input_r.add(i) = x;
+                }
+            }
+            process_vocoder(256.0, 0.0);
+            rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+        }
+        
+        // Analyze the steady-state tail
+        let tail = &rendered[rendered.len() - DEFAULT_FFT_SIZE..];
+        let energy: f32 = tail.iter().map(|x| x * x).sum();
+        assert!(energy > 1e-6, "vocoder output is silent");
+        
+        let ratio = band_energy_ratio(tail, freq - 250.0, freq + 250.0);
+        assert!(ratio > 0.8, "only {ratio} of the energy is near the modulator");
+        // Reference: the raw noise carrier spreads its energy across the spectrum
+        assert!(band_energy_ratio(&carrier[..DEFAULT_FFT_SIZE], freq - 250.0, freq + 250.0) < 0.1);
+    }
+    
+    /// Frequency of the strongest bin in the signal's spectrum
+    fn dominant_frequency(signal: &[f32]) -> f32 {
+        let n = signal.len();
+        let mut spectrum: Vec<Complex<f32>> =
+            signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut spectrum);
+        
+        let (peak, _) = spectrum[..n / 2]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.norm_sqr().total_cmp(&b.1.norm_sqr()))
+            .unwrap();
+        peak as f32 * SAMPLE_RATE / n as f32
+    }
+    
+    #[test]
+    fn test_pitch_shift_octave_up() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        
+        let freq = 440.0;
+        let blocks = 96;
+        let mut rendered = Vec::with_capacity(blocks * BLOCK);
+        for b in 0..blocks {
+            unsafe {
+                let input_l = memory::get_input_buffer(0);
+                let input_r = memory::get_input_buffer(1);
+                for i in 0..BLOCK {
+                    let t = (b * BLOCK + i) as f32 / SAMPLE_RATE;
+                    let x = (2.0 * PI * freq * t).sin() * 0.5;
+                    *input_l.add(i) = x;
+                    *input_r.add(i) = x;
+                }
+            }
+            process_pitch_shift(12.0, false);
+            rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+        }
+        
+        // Steady-state tail, long enough for ~12Hz resolution
+        let tail = &rendered[rendered.len() - 4096..];
+        let energy: f32 = tail.iter().map(|x| x * x).sum();
+        assert!(energy > 1e-3, "pitch shift output is silent");
+        
+        let dominant = dominant_frequency(tail);
+        assert!((dominant - 880.0).abs() < 25.0, "dominant frequency {dominant}Hz, expected ~880Hz");
+    }
+    
+    /// Render a 1kHz tone plus white noise through `effect`, returning the left output
+    fn render_tone_with_noise(blocks: usize, mut effect: impl FnMut()) -> Vec<f32> {
+        let noise = noise(blocks * BLOCK);
+        let mut rendered = Vec::with_capacity(blocks * BLOCK);
+        for b in 0..blocks {
+            unsafe {
+                for i in 0..BLOCK {
+                    let n = b * BLOCK + i;
+                    let t = n as f32 / SAMPLE_RATE;
+                    let x = (2.0 * PI * 1000.0 * t).sin() * 0.5 + noise[n] * 0.05;
+                    *memory::get_input_buffer(0).add(i) = x;
+                    *memory::get_input_buffer(1).add(i) = x;
+                }
+            }
+            effect();
+            rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+        }
+        rendered
+    }
+    
+    #[test]
+    fn test_spectral_gate_reduces_noise_floor() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        reset();
+        let open = render_tone_with_noise(256, || process_spectral_gate(f32::NEG_INFINITY, 20.0));
+        reset();
+        let gated = render_tone_with_noise(256, || process_spectral_gate(-40.0, 20.0));
+        
+        // Hann-windowed tail keeps the tone's leakage out of the noise band
+        let tail = |x: &[f32]| {
+            let n = 8192;
+            x[x.len() - n..]
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v * (0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos()))
+                .collect::<Vec<f32>>()
+        };
+        let (open, gated) = (tail(&open), tail(&gated));
+        let noise_floor = |x: &[f32]| band_energy(x, 0.0, 800.0) + band_energy(x, 1200.0, SAMPLE_RATE);
+        let tone = |x: &[f32]| band_energy(x, 800.0, 1200.0);
+        
+        // Noise floor drops by the requested 20dB, the tone passes untouched
+        let noise_drop = 10.0 * (noise_floor(&open) / noise_floor(&gated)).log10();
+        assert!((noise_drop - 20.0).abs() < 1.0, "noise floor dropped {noise_drop}dB");
+        let tone_change = 10.0 * (tone(&gated) / tone(&open)).log10();
+        assert!(tone_change.abs() < 0.5, "tone changed by {tone_change}dB");
+    }
+    
+    #[test]
+    fn test_latency_does_not_create_state() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        // SAFETY: The test lock is held
+        let state_ptr = unsafe { addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]) };
+        let saved = unsafe { (*state_ptr).take() };
+        
+        assert_eq!(latency_samples(), DEFAULT_FFT_SIZE as u32 - 1);
+        assert!(unsafe { (*state_ptr).is_none() });
+        set_fft(1024, 4);
+        assert_eq!(latency_samples(), 1023);
+        
+        unsafe { *state_ptr = saved };
+    }
+    
+    #[test]
+    fn test_spectral_processing_does_not_allocate() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        let carrier = noise(4096);
+        unsafe {
+            std::slice::from_raw_parts_mut(memory::get_vocoder_carrier_ptr(), carrier.len())
+                .copy_from_slice(&carrier);
+        }
+        load_vocoder_carrier(core::ptr::null(), carrier.len() as u32, 1);
+        
+        let input = noise(BLOCK);
+        let run_all = || {
+            for _ in 0..(DEFAULT_FFT_SIZE / BLOCK) {
+                unsafe {
+                    for (i, &x) in input.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(0.5, 7.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                process(1.0, 0.0, 1.5, 1.0, SpectralMode::Normal, false, 0.0);
+                process_vocoder(64.0, 3.0);
+                process_pitch_shift(-5.0, true);
+                process_spectral_gate(-30.0, 20.0);
+            }
+        };
+        
+        // Warmup creates the state (plans, buffers); after that no frame allocates
+        run_all();
+        let allocations = memory::count_allocations(run_all);
+        assert_eq!(allocations, 0, "spectral processing allocated {allocations} times");
+    }
+    
+    /// Render a sine per channel (left, right) through `effect`, returning
+    /// the left and right output
+    fn render_tone(freqs: [f32; 2], blocks: usize, mut effect: impl FnMut(usize)) -> (Vec<f32>, Vec<f32>) {
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for b in 0..blocks {
+            unsafe {
+                for i in 0..BLOCK {
+                    let t = (b * BLOCK + i) as f32 / SAMPLE_RATE;
+                    for (channel, freq) in freqs.into_iter().enumerate() {
+                        *memory::get_input_buffer(channel as u32).add(i) = (2.0 * PI * freq * t).sin() * 0.5;
+                    }
+                }
+            }
+            effect(b);
+            unsafe {
+                left.extend_from_slice(memory::output_slice_mut(0));
+                right.extend_from_slice(memory::output_slice_mut(1));
+            }
+        }
+        (left, right)
+    }
+    
+    #[test]
+    fn test_freeze_slots_capture_and_blend() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        for slot in 0..FREEZE_SLOTS as u32 {
+            release(slot);
+        }
+        
+        // Capture 750Hz into slot 0 and 3kHz into slot 2; the capture lands
+        // on the next analysis frame. A frozen frame repeats every hop, so
+        // the tones fit a whole number of cycles into one
+        for (freq, slot) in [(750.0, 0), (3000.0, 2)] {
+            render_tone([freq; 2], 40, |b| {
+                if b == 32 {
+                    capture(slot);
+                    assert!(!slot_captured(slot));
+                }
+                process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+            });
+            assert!(slot_captured(slot));
+        }
+        assert!(!slot_captured(1) && !slot_captured(FREEZE_SLOTS as u32));
+        
+        // Freezing at a slot plays its capture whatever the input, in both
+        // channels; slot 1 is empty, so position 1 uses slot 2
+        let tail = 4096;
+        for (position, expected) in [(0.0, 750.0), (1.0, 3000.0), (2.0, 3000.0)] {
+            let (left, right) =
+                render_tone([2000.0; 2], 48, |_| process(1.0, 0.0, position, 1.0, SpectralMode::Normal, false, 0.0));
+            for output in [&left, &right] {
+                let dominant = dominant_frequency(&output[output.len() - tail..]);
+                assert!((dominant - expected).abs() < 50.0, "slot {position}: {dominant}Hz, expected {expected}Hz");
+            }
+        }
+        
+        // Between two captured slots both spectra play
+        render_tone([1500.0; 2], 40, |b| {
+            if b == 32 {
+                capture(1);
+            }
+            process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+        });
+        let (left, _) = render_tone([2000.0; 2], 48, |_| process(1.0, 0.0, 0.5, 1.0, SpectralMode::Normal, false, 0.0));
+        let window = &left[left.len() - tail..];
+        for freq in [750.0, 1500.0] {
+            let ratio = band_energy_ratio(window, freq - 100.0, freq + 100.0);
+            assert!(ratio > 0.2, "{freq}Hz holds {ratio} of the blend");
+        }
+        
+        // Without captures at the position, freezing auto-captures the input
+        for slot in 0..FREEZE_SLOTS as u32 {
+            release(slot);
+            assert!(!slot_captured(slot));
+        }
+        let (left, _) = render_tone([2000.0; 2], 48, |_| process(1.0, 0.0, 0.0, 1.0, SpectralMode::Normal, false, 0.0));
+        let dominant = dominant_frequency(&left[left.len() - tail..]);
+        assert!((dominant - 2000.0).abs() < 50.0, "auto-capture: {dominant}Hz");
+    }
+    
+    #[test]
+    fn test_freeze_holds_both_channels() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        
+        // Freeze different tones per channel, then swap the input tones.
+        // Both channels must keep their captured tone; a frozen frame
+        // repeats every hop, so the tones fit whole cycles into one
+        render_tone([750.0, 1500.0], 40, |b| {
+            process(if b < 36 { 0.0 } else { 1.0 }, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0)
+        });
+        let (left, right) = render_tone([1500.0, 750.0], 64, |_| process(1.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0));
+        
+        let window = 2048;
+        for (output, frozen) in [(&left, 750.0), (&right, 1500.0)] {
+            let ratios: Vec<f32> = [output.len() - 2 * window, output.len() - window]
+                .iter()
+                .map(|&start| {
+                    let segment = &output[start..start + window];
+                    let dominant = dominant_frequency(segment);
+                    assert!((dominant - frozen).abs() < 50.0, "{dominant}Hz, frozen {frozen}Hz");
+                    band_energy_ratio(segment, frozen - 100.0, frozen + 100.0)
+                })
+                .collect();
+            assert!(ratios[0] > 0.9, "{frozen}Hz holds {} of the output", ratios[0]);
+            assert!((ratios[0] - ratios[1]).abs() < 0.05, "{frozen}Hz spectrum kept changing: {ratios:?}");
+        }
+    }
+    
+    #[test]
+    fn test_long_freeze_keeps_stereo_image() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        
+        // Two tones shared by the channels (the right one quieter) over
+        // independent noise, frozen after 40 blocks and held for 2 seconds
+        let blocks = 40 + 750;
+        let noise_l = noise(blocks * BLOCK);
+        let noise_r: Vec<f32> = noise_l.iter().rev().copied().collect();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for b in 0..blocks {
+            unsafe {
+                for i in 0..BLOCK {
+                    let n = b * BLOCK + i;
+                    let t = n as f32 / SAMPLE_RATE;
+                    let tones = (2.0 * PI * 440.0 * t).sin() * 0.4 + (2.0 * PI * 1100.0 * t).sin() * 0.2;
+                    *memory::get_input_buffer(0).add(i) = tones + noise_l[n] * 0.1;
+                    *memory::get_input_buffer(1).add(i) = tones * 0.8 + noise_r[n] * 0.1;
+                }
+            }
+            process(if b < 40 { 0.0 } else { 1.0 }, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+            unsafe {
+                left.extend_from_slice(memory::output_slice_mut(0));
+                right.extend_from_slice(memory::output_slice_mut(1));
+            }
+        }
+        
+        // The channels stay as correlated at the end of the freeze as at
+        // its start
+        let correlation = |start: usize| {
+            let (l, r) = (&left[start..start + 8192], &right[start..start + 8192]);
+            let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+            dot(l, r) / (dot(l, l) * dot(r, r)).sqrt()
+        };
+        let early = correlation(60 * BLOCK);
+        let late = correlation(left.len() - 8192);
+        assert!(early > 0.9, "frozen correlation {early}");
+        assert!(late > early - 0.02, "correlation drifted from {early} to {late}");
+    }
+    
+    #[test]
+    fn test_phase_locking_keeps_transients_compact() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // An impulse every 1000 samples through the frequency shift; share
+        // of each period's energy within 64 samples of its loudest sample,
+        // averaged over the settled periods
+        let period = 1000;
+        let concentration = |phase_locking: bool, shift: f32| {
+            reset();
+            set_phase_locking(phase_locking);
+            let mut output = Vec::new();
+            for b in 0..160 {
+                unsafe {
+                    for i in 0..BLOCK {
+                        let x = if (b * BLOCK + i).is_multiple_of(period) { 1.0 } else { 0.0 };
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(0.0, shift, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            let periods: Vec<f32> = output[4 * DEFAULT_FFT_SIZE..]
+                .chunks_exact(period)
+                .map(|chunk| {
+                    let energy: Vec<f32> = chunk.iter().map(|x| x * x).collect();
+                    let loudest = energy.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+                    let near = &energy[loudest.saturating_sub(64)..(loudest + 64).min(period)];
+                    near.iter().sum::<f32>() / energy.iter().sum::<f32>()
+                })
+                .collect();
+            periods.iter().sum::<f32>() / periods.len() as f32
+        };
+        
+        // Unshifted, both pass the clicks through
+        assert!(concentration(true, 0.0) > 0.99);
+        
+        // Shifted, the locked bins keep each click together
+        for shift in [3.0, 7.0, -5.0] {
+            let (free, locked) = (concentration(false, shift), concentration(true, shift));
+            assert!(locked > 1.3 * free, "{shift} semitones: {free} free, {locked} locked");
+        }
+        set_phase_locking(false);
+    }
+    
+    #[test]
+    fn test_recapture_replaces_frozen_spectrum() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        reset();
+        set_freeze(4, DEFAULT_PHASE_DRIFT);
+        let peak_bin = |mag: &[f32]| mag.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+        let bin_of = |freq: f32| (freq * DEFAULT_FFT_SIZE as f32 / SAMPLE_RATE).round() as usize;
+        
+        // Freezing 750Hz engages over four frames (from the first frame
+        // after the freeze; until then the unfrozen fade stays)
+        let mut fades = Vec::new();
+        render_tone([750.0; 2], 60, |b| {
+            process(if b < 36 { 0.0 } else { 1.0 }, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+            if b >= 36 {
+                fades.push(ensure_state().freeze_fade);
+            }
+        });
+        fades.dedup();
+        assert_eq!(fades, [1.0, 0.25, 0.5, 0.75, 1.0]);
+        
+        // The frozen spectrum stays through an input change until recaptured,
+        // then holds the frame after the capture
+        render_tone([1500.0; 2], 40, |b| {
+            if b == 24 {
+                let state = ensure_state();
+                assert_eq!(peak_bin(&state.frozen_mag_l), bin_of(750.0));
+                recapture();
+            }
+            process(1.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+        });
+        let state = ensure_state();
+        assert_eq!(peak_bin(&state.frozen_mag_l), bin_of(1500.0));
+        assert_eq!(peak_bin(&state.frozen_mag_r), bin_of(1500.0));
+        assert_eq!(state.freeze_fade, 1.0);
+        
+        let (left, _) = render_tone([3000.0; 2], 48, |_| process(1.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0));
+        let dominant = dominant_frequency(&left[left.len() - 2048..]);
+        assert!((dominant - 1500.0).abs() < 50.0, "recaptured: {dominant}Hz");
+    }
+    
+    #[test]
+    fn test_freeze_decay_and_drift() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        set_freeze(0, 0.0);
+        
+        // Level in dB of each 1024-sample stretch of the left output, the
+        // freeze (of 750Hz at 48kHz) engaged from block 32 and settled from
+        // 1s on
+        let render = |sample_rate: f32, decay: f32, drift: f32| -> Vec<f32> {
+            memory::init_engine(sample_rate, BLOCK as u32);
+            reset();
+            set_freeze_motion(decay, drift);
+            let blocks = 5 * sample_rate as usize / BLOCK;
+            let (left, _) = render_tone([750.0; 2], blocks, |b| {
+                process(if b < 32 { 0.0 } else { 1.0 }, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+            });
+            left[sample_rate as usize..]
+                .chunks(1024)
+                .map(|chunk| utils::linear_to_db((chunk.iter().map(|x| x * x).sum::<f32>() / chunk.len() as f32).sqrt()))
+                .collect()
+        };
+        // Held by default
+        let held = render(SAMPLE_RATE, 0.0, 0.0);
+        let spread = held.iter().fold(0.0f32, |spread, &db| spread.max((db - held[0]).abs()));
+        assert!(spread < 0.1, "held freeze moved by {spread}dB");
+        
+        // 20dB per second at any sample rate
+        for sample_rate in [44100.0, SAMPLE_RATE, 96000.0] {
+            let decayed = render(sample_rate, 20.0, 0.0);
+            let per_second = sample_rate / 1024.0;
+            let drop = decayed[per_second as usize] - decayed[(2.0 * per_second) as usize];
+            assert!((drop - 20.0).abs() < 0.5, "{sample_rate}Hz: decayed {drop}dB in a second");
+        }
+        
+        // Drift moves the level around but keeps it near the capture
+        let drifting = render(SAMPLE_RATE, 0.0, 1.0);
+        let mean = drifting.iter().sum::<f32>() / drifting.len() as f32;
+        let deviation = (drifting.iter().map(|db| (db - mean) * (db - mean)).sum::<f32>() / drifting.len() as f32).sqrt();
+        assert!(deviation > 0.2, "drift deviation {deviation}dB");
+        assert!((mean - held[0]).abs() < 3.0, "drifting mean {mean}dB against {}dB", held[0]);
+        assert!(drifting.iter().all(|db| (db - held[0]).abs() < 10.0), "drift ran away");
+        
+        set_freeze_motion(0.0, 0.0);
+        set_freeze(DEFAULT_FREEZE_FADE_FRAMES, DEFAULT_PHASE_DRIFT);
+    }
+    
+    #[test]
+    fn test_dry_wet_aligns_dry_with_wet() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        let render = |dry_wet: f32| {
+            reset();
+            render_tone_with_noise(48, || process(0.0, 0.0, -1.0, dry_wet, SpectralMode::Normal, false, 0.0))
+        };
+        let input = render_tone_with_noise(48, || unsafe {
+            memory::output_slice_mut(0).copy_from_slice(memory::input_slice(0));
+        });
+        let (dry, wet, half) = (render(0.0), render(1.0), render(0.5));
+        
+        // Fully dry is the input delayed by the latency
+        let latency = latency_samples() as usize;
+        assert!(dry[..latency].iter().all(|&x| x == 0.0));
+        assert_eq!(dry[latency..], input[..input.len() - latency]);
+        
+        // Unfrozen and unshifted, the wet signal is the dry one at unity
+        // gain, so the mix adds them up in phase
+        let settled = 2 * DEFAULT_FFT_SIZE;
+        for n in settled..input.len() {
+            assert!((wet[n] - dry[n]).abs() < 1e-3, "sample {n}: wet {} dry {}", wet[n], dry[n]);
+            assert!((half[n] - 0.5 * (dry[n] + wet[n])).abs() < 1e-6, "sample {n}");
+        }
+    }
+    
+    #[test]
+    fn test_linear_shift_moves_components_by_hz() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // 600Hz and 1000Hz: a linear shift breaks their 3:5 ratio
+        let render = |shift: f32, shift_hz: f32| {
+            reset();
+            let (left, right) = render_tone([600.0, 1000.0], 96, |_| {
+                process(0.0, shift, -1.0, 1.0, SpectralMode::Normal, false, shift_hz)
+            });
+            (left[left.len() - 4096..].to_vec(), right[right.len() - 4096..].to_vec())
+        };
+        let (low, high) = render(0.0, 300.0);
+        assert!((dominant_frequency(&low) - 900.0).abs() < 25.0, "{}Hz", dominant_frequency(&low));
+        assert!((dominant_frequency(&high) - 1300.0).abs() < 25.0, "{}Hz", dominant_frequency(&high));
+        
+        // The ratio shift comes first: an octave up, then 300Hz
+        let (low, _) = render(12.0, 300.0);
+        assert!((dominant_frequency(&low) - 1500.0).abs() < 25.0, "{}Hz", dominant_frequency(&low));
+        
+        // Shifted below 0Hz, the component is dropped rather than folded back
+        let (low, high) = render(0.0, -800.0);
+        let rms = |x: &[f32]| (x.iter().map(|v| v * v).sum::<f32>() / x.len() as f32).sqrt();
+        assert!(rms(&low) < 0.01, "{} left below 0Hz", rms(&low));
+        assert!((dominant_frequency(&high) - 200.0).abs() < 25.0, "{}Hz", dominant_frequency(&high));
+    }
+    
+    #[test]
+    fn test_tilt_and_shelves_shape_the_spectrum() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // White noise through the effect, unfrozen and unshifted
+        let input = noise(96 * BLOCK);
+        let render = || {
+            reset();
+            let mut output = Vec::new();
+            for block in input.chunks(BLOCK) {
+                unsafe {
+                    for (i, &x) in block.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            output[output.len() - 8192..].to_vec()
+        };
+        let flat = render();
+        
+        // Level change of a band against the flat output
+        let band_db = |output: &[f32], lo: f32, hi: f32| {
+            10.0 * libm::log10f(band_energy(output, lo, hi) / band_energy(&flat, lo, hi))
+        };
+        
+        // +6dB/octave about 1kHz: an octave and a half either side of the
+        // pivot (354Hz, 2828Hz) moves by ∓9dB, the pivot stays
+        set_tilt(6.0, 1000.0);
+        let tilted = render();
+        for (lo, hi, expected) in [(330.0, 380.0, -9.0), (950.0, 1050.0, 0.0), (2650.0, 3000.0, 9.0)] {
+            let db = band_db(&tilted, lo, hi);
+            assert!((db - expected).abs() < 1.5, "{lo}-{hi}Hz: {db}dB, expected {expected}dB");
+        }
+        
+        // A +12dB low shelf at 200Hz lifts the lows and leaves the highs
+        set_tilt(0.0, 1000.0);
+        set_shelves(200.0, 12.0, DEFAULT_HIGH_SHELF_HZ, 0.0);
+        let shelved = render();
+        let lows = band_db(&shelved, 50.0, 100.0);
+        assert!((lows - 11.0).abs() < 1.5, "lows {lows}dB");
+        assert!(band_db(&shelved, 4000.0, 8000.0).abs() < 0.5);
+        
+        // Flat settings leave the output untouched; out-of-range tilts clamp
+        set_shelves(DEFAULT_LOW_SHELF_HZ, 0.0, DEFAULT_HIGH_SHELF_HZ, 0.0);
+        assert_eq!(render(), flat);
+        set_tilt(30.0, f32::NAN);
+        assert_eq!(ensure_state().eq.tilt_db, MAX_TILT_DB_PER_OCTAVE);
+        assert_eq!(ensure_state().eq.pivot_hz, DEFAULT_TILT_PIVOT_HZ);
+        set_tilt(0.0, DEFAULT_TILT_PIVOT_HZ);
+    }
+    
+    #[test]
+    fn test_bin_mask_carves_frozen_spectrum() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // White noise through the effect, `setup` called before each block
+        let input = noise(160 * BLOCK);
+        let render = |freeze: f32, mut setup: Box<dyn FnMut(usize)>| {
+            reset();
+            let mut output = Vec::new();
+            for (b, block) in input.chunks(BLOCK).enumerate() {
+                setup(b);
+                unsafe {
+                    for (i, &x) in block.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(freeze, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            output
+        };
+        let tail = |output: &[f32]| output[output.len() - 8192..].to_vec();
+        let flat = tail(&render(0.0, Box::new(|_| {})));
+        let band_db = |output: &[f32], reference: &[f32], lo: f32, hi: f32| {
+            10.0 * libm::log10f(band_energy(output, lo, hi) / band_energy(reference, lo, hi))
+        };
+        
+        // A full-depth comb at 1kHz keeps the harmonics and removes the
+        // bins between them
+        let combed = tail(&render(0.0, Box::new(|b| {
+            if b == 0 {
+                set_comb(1000.0, 2.0, 1.0);
+            }
+        })));
+        for harmonic in [1000.0, 2000.0, 3000.0] {
+            let db = band_db(&combed, &flat, harmonic - 10.0, harmonic + 10.0);
+            assert!(db > -4.0, "{harmonic}Hz harmonic at {db}dB");
+        }
+        for between in [1500.0, 2500.0] {
+            let db = band_db(&combed, &flat, between - 100.0, between + 100.0);
+            assert!(db < -30.0, "{between}Hz at {db}dB");
+        }
+        
+        // Comb a frozen pad, then switch the comb off: the capture is
+        // untouched, so the output goes back to the uncombed freeze
+        set_comb(1000.0, 2.0, 0.0);
+        let frozen = render(1.0, Box::new(|_| {}));
+        let carved = render(1.0, Box::new(|b| match b {
+            40 => set_comb(1000.0, 2.0, 1.0),
+            70 => set_comb(1000.0, 2.0, 0.0),
+            _ => {}
+        }));
+        let (from, to) = (56 * BLOCK, 70 * BLOCK);
+        let db = band_db(&carved[from..to], &frozen[from..to], 1400.0, 1600.0);
+        assert!(db < -20.0, "frozen 1500Hz at {db}dB under the comb");
+        assert_eq!(tail(&carved), tail(&frozen));
+        
+        // A loaded mask passing the bins below 4kHz (at 1025 bins), and
+        // the same mask at half the resolution, stretched across the bins
+        let lowpass = |num_bins: usize| {
+            let gains = unsafe { std::slice::from_raw_parts_mut(memory::get_spectral_mask_ptr(), num_bins) };
+            for (i, gain) in gains.iter_mut().enumerate() {
+                *gain = if i as f32 * SAMPLE_RATE * 0.5 / (num_bins - 1) as f32 <= 4000.0 { 1.0 } else { 0.0 };
+            }
+            assert_eq!(load_mask(num_bins as u32), memory::LOAD_OK);
+        };
+        for num_bins in [1025, 513] {
+            lowpass(num_bins);
+            let masked = tail(&render(0.0, Box::new(|_| {})));
+            let (low, high) = (band_db(&masked, &flat, 500.0, 3500.0), band_db(&masked, &flat, 6000.0, 12000.0));
+            assert!(low.abs() < 0.5 && high < -40.0, "{num_bins} bins: {low}dB below, {high}dB above 4kHz");
+        }
+        
+        // The mask survives set_fft
+        set_fft(4096, 4);
+        let masked = tail(&render(0.0, Box::new(|_| {})));
+        assert!(band_db(&masked, &flat, 6000.0, 12000.0) < -40.0);
+        set_fft(DEFAULT_FFT_SIZE as u32, DEFAULT_OVERLAP as u32);
+        
+        // Oversized masks are truncated; an empty one clears the mask
+        assert_eq!(load_mask(memory::MAX_SPECTRAL_MASK_BINS as u32 + 1), memory::LOAD_TRUNCATED);
+        assert_eq!(load_mask(0), memory::LOAD_OK);
+        assert_eq!(tail(&render(0.0, Box::new(|_| {}))), flat);
+    }
+    
+    #[test]
+    fn test_robot_and_whisper_modes() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        let render = |mode: SpectralMode, shift: f32| {
+            reset();
+            let output = render_tone_with_noise(64, || process(0.0, shift, -1.0, 1.0, mode, false, 0.0));
+            output[output.len() - 4096..].to_vec()
+        };
+        let correlation = |a: &[f32], b: &[f32]| {
+            let dot = |x: &[f32], y: &[f32]| x.iter().zip(y).map(|(x, y)| x * y).sum::<f32>();
+            dot(a, b) / (dot(a, a) * dot(b, b)).sqrt()
+        };
+        let hop = DEFAULT_FFT_SIZE / DEFAULT_OVERLAP;
+        
+        // Robot frames all have the same phases, so the output repeats every
+        // hop; the 1kHz tone doesn't
+        let normal = render(SpectralMode::Normal, 0.0);
+        let robot = render(SpectralMode::Robot, 0.0);
+        let repeat = |x: &[f32]| correlation(&x[hop..], &x[..x.len() - hop]);
+        assert!(repeat(&robot) > 0.9, "robot repeats at {}", repeat(&robot));
+        assert!(repeat(&normal) < 0.5, "normal repeats at {}", repeat(&normal));
+        
+        // Whisper keeps the magnitudes, but overlapping frames with random
+        // phases beat against each other: the steady tone turns into noise
+        // with a fluctuating level
+        let whisper = render(SpectralMode::Whisper, 0.0);
+        let fluctuation = |x: &[f32]| {
+            let levels: Vec<f32> = x
+                .chunks(BLOCK)
+                .map(|c| (c.iter().map(|v| v * v).sum::<f32>() / BLOCK as f32).sqrt())
+                .collect();
+            let mean = levels.iter().sum::<f32>() / levels.len() as f32;
+            (levels.iter().map(|l| (l - mean).powi(2)).sum::<f32>() / levels.len() as f32).sqrt() / mean
+        };
+        let (steady, whispered) = (fluctuation(&normal), fluctuation(&whisper));
+        assert!(whispered > 0.15 && whispered > 5.0 * steady, "level fluctuation {whispered} vs {steady}");
+        let ratio = band_energy_ratio(&whisper, 900.0, 1100.0);
+        assert!(ratio > 0.5, "whisper holds {ratio} at 1kHz");
+        
+        // Both follow the shift
+        for mode in [SpectralMode::Robot, SpectralMode::Whisper] {
+            let ratio = band_energy_ratio(&render(mode, 12.0), 1900.0, 2100.0);
+            assert!(ratio > 0.5, "{mode:?} holds {ratio} at 2kHz");
+        }
+        assert_eq!(SpectralMode::from_index(2), SpectralMode::Whisper);
+        assert_eq!(SpectralMode::from_index(9), SpectralMode::Normal);
+    }
+    
+    #[test]
+    fn test_formant_preserving_shift_keeps_envelope_peaks() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // Whispered vowel: noise through formant resonances at 700Hz and
+        // 1800Hz. Noise averages out the phase vocoder's per-partial level
+        // errors, so the averaged output spectrum shows the envelope.
+        const SEGMENT: usize = 4096;
+        let excitation = noise(320 * BLOCK);
+        let mut formant_1 = crate::filters::Biquad::bandpass(700.0, 4.0, SAMPLE_RATE);
+        let mut formant_2 = crate::filters::Biquad::bandpass(1800.0, 6.0, SAMPLE_RATE);
+        let vowel: Vec<f32> = excitation
+            .iter()
+            .map(|&x| formant_1.process(x) + 0.6 * formant_2.process(x) + 0.02 * x)
+            .collect();
+        
+        // Power spectrum of the output, averaged over Hann-windowed segments
+        // past the first 8192 samples
+        let render = |formant_preserve: bool| {
+            reset();
+            let mut rendered = Vec::with_capacity(vowel.len());
+            for block in vowel.chunks(BLOCK) {
+                unsafe {
+                    for (i, &x) in block.iter().enumerate() {
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process(0.0, 7.0, -1.0, 1.0, SpectralMode::Normal, formant_preserve, 0.0);
+                rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            let fft = FftPlanner::new().plan_fft_forward(SEGMENT);
+            let mut power = vec![0.0f32; SEGMENT / 2];
+            for segment in rendered[8192..].chunks_exact(SEGMENT) {
+                let mut spectrum: Vec<Complex<f32>> = segment
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &x)| Complex::new(x * (0.5 - 0.5 * (2.0 * PI * i as f32 / SEGMENT as f32).cos()), 0.0))
+                    .collect();
+                fft.process(&mut spectrum);
+                for (p, c) in power.iter_mut().zip(&spectrum) {
+                    *p += c.norm_sqr();
+                }
+            }
+            power
+        };
+        // Center of the loudest 200Hz band within [lo_hz, hi_hz]
+        let envelope_peak = |power: &[f32], lo_hz: f32, hi_hz: f32| {
+            let bin_hz = SAMPLE_RATE / SEGMENT as f32;
+            (0..=((hi_hz - lo_hz) / 25.0) as usize)
+                .map(|step| lo_hz + step as f32 * 25.0)
+                .map(|center| {
+                    let bins = ((center - 100.0) / bin_hz) as usize..((center + 100.0) / bin_hz) as usize;
+                    (center, power[bins].iter().sum::<f32>())
+                })
+                .fold((0.0, 0.0), |best, band| if band.1 > best.1 { band } else { best })
+                .0
+        };
+        
+        // +7 semitones moves the spectrum by 1.5x; the formants stay
+        let preserved = render(true);
+        let first = envelope_peak(&preserved, 400.0, 1300.0);
+        let second = envelope_peak(&preserved, 1400.0, 3200.0);
+        assert!((first - 700.0).abs() <= 100.0, "first formant moved to {first}Hz");
+        assert!((second - 1800.0).abs() <= 150.0, "second formant moved to {second}Hz");
+        
+        // Without preservation they move with the shift (to ~1050Hz and ~2700Hz)
+        let shifted = render(false);
+        let first = envelope_peak(&shifted, 400.0, 1300.0);
+        let second = envelope_peak(&shifted, 1400.0, 3200.0);
+        assert!((first - 1050.0).abs() <= 150.0, "first formant at {first}Hz");
+        assert!((second - 2700.0).abs() <= 300.0, "second formant at {second}Hz");
+        reset();
+    }
+    
+    #[test]
+    fn test_fft_size_sets_latency_and_keeps_level() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // An open gate passes an impulse through at the latency, at unity
+        // gain
+        let impulse_at = 2 * MAX_FFT_SIZE;
+        for (size, overlap) in [(1024, 4), (4096, 4), (512, 8), (8192, 8), (2048, 4)] {
+            assert_eq!(set_fft(size, overlap), size);
+            assert_eq!(latency_samples(), size - 1);
+            
+            let mut output = Vec::new();
+            for b in 0..(impulse_at + MAX_FFT_SIZE) / BLOCK {
+                unsafe {
+                    for i in 0..BLOCK {
+                        let x = if b * BLOCK + i == impulse_at { 0.5 } else { 0.0 };
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                process_spectral_gate(f32::NEG_INFINITY, 60.0);
+                output.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            let (peak_at, peak) = output
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+                .unwrap();
+            assert_eq!(peak_at, impulse_at + size as usize - 1, "{size}/{overlap}");
+            assert!((peak - 0.5).abs() < 1e-3, "{size}/{overlap}: gain {}", peak / 0.5);
+        }
+        
+        // The pitch shifter follows the framing
+        for size in [1024, 4096] {
+            set_fft(size, 4);
+            let (left, _) = render_tone([440.0; 2], 96, |_| process_pitch_shift(12.0, false));
+            let dominant = dominant_frequency(&left[left.len() - 4096..]);
+            assert!((dominant - 880.0).abs() < 25.0, "{size}: dominant frequency {dominant}Hz");
+        }
+        
+        // Sizes round up to powers of two within range, overlaps to 4 or 8
+        assert_eq!(set_fft(3000, 5), 4096);
+        assert_eq!(ensure_state().hop_size, 512);
+        assert_eq!(set_fft(100, 1), MIN_FFT_SIZE as u32);
+        assert_eq!(ensure_state().hop_size, MIN_FFT_SIZE / 4);
+        assert_eq!(set_fft(u32::MAX, 64), MAX_FFT_SIZE as u32);
+        set_fft(DEFAULT_FFT_SIZE as u32, DEFAULT_OVERLAP as u32);
+    }
+    
+    #[test]
+    fn test_pass_through_is_unity_gain() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // Shift 0, freeze 0, fully wet: the sine comes out at its input
+        // level (0.5 amplitude) at either overlap
+        for overlap in [4, 8] {
+            set_fft(DEFAULT_FFT_SIZE as u32, overlap);
+            reset();
+            let (left, right) = render_tone([440.0, 1000.0], 96, |_| {
+                process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0)
+            });
+            for output in [left, right] {
+                let steady = &output[output.len() - 4096..];
+                let rms = (steady.iter().map(|x| x * x).sum::<f32>() / steady.len() as f32).sqrt();
+                let gain_db = utils::linear_to_db(rms * 2.0f32.sqrt() / 0.5);
+                assert!(gain_db.abs() < 0.1, "overlap {overlap}: {gain_db}dB");
+            }
+        }
+        set_fft(DEFAULT_FFT_SIZE as u32, DEFAULT_OVERLAP as u32);
+    }
+    
+    #[test]
+    fn test_freeze_input_gate_reduces_noise_floor_and_releases() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        let render = || {
+            reset();
+            render_tone_with_noise(256, || process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0))
+        };
+        
+        set_input_gate(f32::NEG_INFINITY, 20.0, 100.0);
+        let open = render();
+        set_input_gate(-40.0, 20.0, 100.0);
+        let gated = render();
+        
+        // Noise floor drops by the requested 20dB, the tone passes untouched
+        let tail = |x: &[f32]| {
+            let n = 8192;
+            x[x.len() - n..]
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v * (0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos()))
+                .collect::<Vec<f32>>()
+        };
+        let (open, gated) = (tail(&open), tail(&gated));
+        let noise_floor = |x: &[f32]| band_energy(x, 0.0, 800.0) + band_energy(x, 1200.0, SAMPLE_RATE);
+        let tone = |x: &[f32]| band_energy(x, 800.0, 1200.0);
+        let noise_drop = 10.0 * (noise_floor(&open) / noise_floor(&gated)).log10();
+        assert!((noise_drop - 20.0).abs() < 1.0, "noise floor dropped {noise_drop}dB");
+        let tone_change = 10.0 * (tone(&gated) / tone(&open)).log10();
+        assert!(tone_change.abs() < 0.5, "tone changed by {tone_change}dB");
+        
+        // Once the tone stops, its bin closes over the release time
+        let tone_bin = (1000.0 * DEFAULT_FFT_SIZE as f32 / SAMPLE_RATE).round() as usize;
+        for (release_ms, closed) in [(10.0, true), (2000.0, false)] {
+            set_input_gate(-40.0, 20.0, release_ms);
+            render();
+            assert_eq!(ensure_state().input_gate_gain_l[tone_bin], 1.0);
+            // 200ms of silence
+            render_tone([0.0; 2], 75, |_| process(0.0, 0.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0));
+            let gain = ensure_state().input_gate_gain_l[tone_bin];
+            assert_eq!(gain < 0.2, closed, "{release_ms}ms release: gain {gain}");
+        }
+        set_input_gate(f32::NEG_INFINITY, 0.0, DEFAULT_GATE_RELEASE_MS);
+    }
+    
+    #[test]
+    fn test_open_spectral_gate_is_exact_passthrough() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        reset();
+        let gated = render_tone_with_noise(48, || process_spectral_gate(f32::NEG_INFINITY, 60.0));
+        
+        // Reference: plain analysis/resynthesis with no spectral modification
+        reset();
+        let reference = render_tone_with_noise(48, || unsafe {
+            run_frames(ensure_state(), |state, offset| {
+                for (input, output) in [
+                    (&state.input_buffer_l, &mut state.output_buffer_l),
+                    (&state.input_buffer_r, &mut state.output_buffer_r),
+                ] {
+                    for ((bin, x), w) in state.fft_buffer.iter_mut().zip(input).zip(&state.window) {
+                        *bin = Complex::new(x * w, 0.0);
+                    }
+                    state.fft.process_with_scratch(&mut state.fft_buffer, &mut state.fft_scratch);
+                    state.ifft.process_with_scratch(&mut state.fft_buffer, &mut state.fft_scratch);
+                    for ((out, c), w) in output[offset..]
+                        .iter_mut()
+                        .zip(state.fft_buffer.iter())
+                        .zip(&state.synthesis_window)
+                    {
+                        *out += c.re * w;
+                    }
+                }
+            });
+        });
+        
+        assert!(gated.iter().any(|&x| x != 0.0));
+        assert_eq!(gated, reference);
+    }
+    
+    #[test]
+    fn test_feedback_stacks_octaves_and_drains() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // 100ms Hann-windowed 440Hz burst, then silence; the feedback drops
+        // to 0 from block `drain_at`
+        let burst = 4800;
+        let render = |feedback: f32, excite: &dyn Fn(usize) -> f32, blocks: usize, drain_at: usize| {
+            reset();
+            set_feedback(feedback);
+            let mut rendered = Vec::with_capacity(blocks * BLOCK);
+            for b in 0..blocks {
+                unsafe {
+                    for i in 0..BLOCK {
+                        let x = excite(b * BLOCK + i);
+                        *memory::get_input_buffer(0).add(i) = x;
+                        *memory::get_input_buffer(1).add(i) = x;
+                    }
+                }
+                if b == drain_at {
+                    set_feedback(0.0);
+                }
+                process(0.0, 12.0, -1.0, 1.0, SpectralMode::Normal, false, 0.0);
+                rendered.extend_from_slice(unsafe { memory::output_slice_mut(0) });
+            }
+            rendered
+        };
+        let tone = |n: usize| {
+            if n < burst {
+                let window = 0.5 - 0.5 * (2.0 * PI * n as f32 / burst as f32).cos();
+                (2.0 * PI * 440.0 * n as f32 / SAMPLE_RATE).sin() * window * 0.5
+            } else {
+                0.0
+            }
+        };
+        
+        // Without feedback the shifted burst is over by 0.25s; with it the
+        // tail keeps climbing: two trips around the loop reach 1760Hz
+        let tail = |x: &[f32]| x[12000..12000 + 8192].to_vec();
+        let plain = tail(&render(0.0, &tone, 200, usize::MAX));
+        let shimmer = tail(&render(0.7, &tone, 200, usize::MAX));
+        let energy = |x: &[f32]| x.iter().map(|v| v * v).sum::<f32>();
+        assert!(energy(&plain) < 1e-6, "plain tail energy {}", energy(&plain));
+        let two_octaves = band_energy(&shimmer, 1660.0, 1860.0);
+        assert!(two_octaves > 0.1, "1760Hz energy {two_octaves}");
+        assert!(band_energy(&shimmer, 3420.0, 3620.0) > 1e-3 * two_octaves, "no third octave");
+        
+        // Full-scale noise into the most feedback stays bounded, and once
+        // the feedback is off the loop drains within a few frames
+        let noise = noise(12000);
+        let excite = |n: usize| noise.get(n).copied().unwrap_or(0.0);
+        let drain_at = 120;
+        let output = render(MAX_FEEDBACK, &excite, 300, drain_at);
+        let peak = output.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+        assert!(peak < 4.0, "peak {peak}");
+        let ringing = output[(drain_at - 8) * BLOCK..drain_at * BLOCK].iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+        assert!(ringing > 0.01, "loop already silent before the drain ({ringing})");
+        let drained = &output[drain_at * BLOCK + 3 * DEFAULT_FFT_SIZE..];
+        assert!(drained.iter().all(|x| x.abs() < 1e-4), "loop still ringing after the drain");
+        set_feedback(0.0);
+    }
+    
+    #[test]
+    fn test_harmonizer_voices_add_shifted_copies() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // A 220Hz drone with a fifth and an octave at half gain
+        let render = |shift: f32, voices: u32| {
+            set_voice_count(voices);
+            reset();
+            render_tone([220.0, 330.0], 64, |_| process(0.0, shift, -1.0, 1.0, SpectralMode::Normal, false, 0.0))
+        };
+        set_voice(0, 7.0, 1.0);
+        set_voice(1, 12.0, 0.5);
+        set_voice(2, -12.0, 1.0);
+        let chord = render(0.0, 2);
+        
+        // Each voice sounds like the freeze/shift shifted by its interval,
+        // all of them summed (the third voice is past the count)
+        let (root, fifth, octave) = (render(0.0, 0), render(7.0, 0), render(12.0, 0));
+        for (channel, chord) in [&chord.0, &chord.1].into_iter().enumerate() {
+            let parts = [&root, &fifth, &octave].map(|(left, right)| if channel == 0 { left } else { right });
+            assert!(parts[1].iter().any(|x| x.abs() > 0.1));
+            for (n, &y) in chord.iter().enumerate() {
+                let expected = parts[0][n] + parts[1][n] + 0.5 * parts[2][n];
+                assert!((y - expected).abs() < 1e-3, "channel {channel} sample {n}: {y} vs {expected}");
+            }
+        }
+        
+        // The count and voice index are bounded; voices survive set_fft
+        set_voice_count(MAX_VOICES as u32 + 1);
+        set_voice(MAX_VOICES as u32, 12.0, 1.0);
+        set_fft(4096, 4);
+        assert_eq!(ensure_state().voice_count, MAX_VOICES);
+        assert_eq!(ensure_state().voices[0].gain, 1.0);
+        
+        set_fft(DEFAULT_FFT_SIZE as u32, DEFAULT_OVERLAP as u32);
+        set_voice_count(0);
+        for index in 0..MAX_VOICES as u32 {
+            set_voice(index, 0.0, 1.0);
+        }
+    }
+    
+    #[test]
+    fn test_reset_before_first_use_doesnt_allocate() {
+        let _guard = memory::test_lock();
+        memory::init_engine(SAMPLE_RATE, BLOCK as u32);
+        
+        // SAFETY: Single-threaded test, holding the test lock
+        let state_ptr = unsafe { addr_of_mut!((*addr_of_mut!(STATES))[memory::current_engine()]) };
+        unsafe { *state_ptr = None };
+        reset();
+        assert!(unsafe { (*state_ptr).is_none() });
     }
 }
@@ -5,6 +5,9 @@
 //! - dB/linear conversion
 //! - Frequency/pitch conversion
 //! - Clipping and saturation
+//! - Table-based fast tanh, exp2 and log2
+//! - Equal-power crossfade gains and pan laws
+//! - Windowed-sinc sample-rate conversion
 
 /// Linear interpolation between two values
 /// 
@@ -17,6 +20,21 @@ pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
 
+/// 4-point cubic interpolation (Catmull-Rom spline)
+/// 
+/// # Arguments
+/// * `y0`..`y3` - Samples at positions -1, 0, 1, 2
+/// * `t` - Fractional position between `y1` and `y2` (0.0 to 1.0)
+#[inline]
+pub fn cubic_interp(y0: f32, y1: f32, y2: f32, y3: f32, t: f32) -> f32 {
+    let c0 = y1;
+    let c1 = 0.5 * (y2 - y0);
+    let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+    
+    ((c3 * t + c2) * t + c1) * t + c0
+}
+
 /// Convert decibels to linear amplitude
 /// 
 /// # Arguments
@@ -62,3 +80,426 @@ pub fn soft_clip(x: f32) -> f32 {
 pub fn hard_clip(x: f32, limit: f32) -> f32 {
     x.max(-limit).min(limit)
 }
+
+/// Entries of the tanh table, spanning [0, TANH_RANGE]
+const TANH_TABLE_SIZE: usize = 1024;
+
+/// Input beyond which `fast_tanh` holds its last entry (tanh(8) is within
+/// 3e-7 of 1)
+const TANH_RANGE: f32 = 8.0;
+
+/// Entries of the exp2 and log2 tables, spanning one octave
+const EXP2_TABLE_SIZE: usize = 256;
+const LOG2_TABLE_SIZE: usize = 256;
+
+/// e^x, usable in const context
+/// 
+/// Taylor series of e^(x/64) (accurate to f64 precision for |x| <= 16),
+/// squared six times.
+const fn const_exp(x: f64) -> f64 {
+    let r = x / 64.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut n = 1;
+    while n <= 12 {
+        term *= r / n as f64;
+        sum += term;
+        n += 1;
+    }
+    let mut i = 0;
+    while i < 6 {
+        sum *= sum;
+        i += 1;
+    }
+    sum
+}
+
+/// ln(y) for y in [1, 2], usable in const context
+/// 
+/// Series of 2·atanh((y - 1) / (y + 1)), whose argument stays within 1/3.
+const fn const_ln(y: f64) -> f64 {
+    let z = (y - 1.0) / (y + 1.0);
+    let z2 = z * z;
+    let mut sum = 0.0;
+    let mut power = z;
+    let mut n = 0;
+    while n < 20 {
+        sum += power / (2 * n + 1) as f64;
+        power *= z2;
+        n += 1;
+    }
+    2.0 * sum
+}
+
+/// tanh(x) at TANH_TABLE_SIZE + 1 points over [0, TANH_RANGE]
+static TANH_TABLE: [f32; TANH_TABLE_SIZE + 1] = {
+    let mut table = [0.0f32; TANH_TABLE_SIZE + 1];
+    let mut i = 0;
+    while i <= TANH_TABLE_SIZE {
+        let e = const_exp(2.0 * TANH_RANGE as f64 * i as f64 / TANH_TABLE_SIZE as f64);
+        table[i] = ((e - 1.0) / (e + 1.0)) as f32;
+        i += 1;
+    }
+    table
+};
+
+/// 2^f at EXP2_TABLE_SIZE + 1 points over [0, 1]
+static EXP2_TABLE: [f32; EXP2_TABLE_SIZE + 1] = {
+    let mut table = [0.0f32; EXP2_TABLE_SIZE + 1];
+    let mut i = 0;
+    while i <= EXP2_TABLE_SIZE {
+        table[i] = const_exp(core::f64::consts::LN_2 * i as f64 / EXP2_TABLE_SIZE as f64) as f32;
+        i += 1;
+    }
+    table
+};
+
+/// log2(1 + m) at LOG2_TABLE_SIZE + 1 points over [0, 1]
+static LOG2_TABLE: [f32; LOG2_TABLE_SIZE + 1] = {
+    let mut table = [0.0f32; LOG2_TABLE_SIZE + 1];
+    let mut i = 0;
+    while i <= LOG2_TABLE_SIZE {
+        table[i] = (const_ln(1.0 + i as f64 / LOG2_TABLE_SIZE as f64) / core::f64::consts::LN_2) as f32;
+        i += 1;
+    }
+    table
+};
+
+/// Linearly interpolated table read at a position in entries
+/// 
+/// `pos` must be within [0, table length - 1].
+#[inline]
+fn table_lerp(table: &[f32], pos: f32) -> f32 {
+    let index = (pos as usize).min(table.len() - 2);
+    lerp(table[index], table[index + 1], pos - index as f32)
+}
+
+/// Table-based tanh
+/// 
+/// Linear interpolation keeps the curve continuous (no steps between
+/// entries). Max absolute error vs `libm::tanhf`: 1e-5 (well below
+/// audible distortion: a full-scale sine picks up under 0.01% THD from
+/// it). Inputs beyond ±8 return ±tanh(8).
+#[inline]
+pub fn fast_tanh(x: f32) -> f32 {
+    let pos = x.abs().min(TANH_RANGE) * (TANH_TABLE_SIZE as f32 / TANH_RANGE);
+    table_lerp(&TANH_TABLE, pos).copysign(x)
+}
+
+/// Table-based 2^x
+/// 
+/// Splits off the integer octave (applied to the exponent bits) and
+/// interpolates the fraction, so it is continuous across octaves. Max
+/// relative error vs `libm::exp2f`: 2e-6. Inputs are clamped to
+/// [-126, 127], the normal f32 range.
+#[inline]
+pub fn fast_exp2(x: f32) -> f32 {
+    let x = x.clamp(-126.0, 127.0);
+    let octave = libm::floorf(x);
+    let scale = f32::from_bits(((octave as i32 + 127) as u32) << 23);
+    table_lerp(&EXP2_TABLE, (x - octave) * EXP2_TABLE_SIZE as f32) * scale
+}
+
+/// Table-based log2
+/// 
+/// Reads the octave from the exponent bits and interpolates the mantissa,
+/// so it is continuous across octaves. Max absolute error vs
+/// `libm::log2f`: 5e-6. Inputs below the smallest normal f32 (including 0
+/// and negative values) read as it, returning -126.
+#[inline]
+pub fn fast_log2(x: f32) -> f32 {
+    let bits = x.max(f32::MIN_POSITIVE).to_bits();
+    let octave = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000) - 1.0;
+    octave as f32 + table_lerp(&LOG2_TABLE, mantissa * LOG2_TABLE_SIZE as f32)
+}
+
+/// `db_to_linear` via `fast_exp2` (max relative error 1e-5)
+#[inline]
+pub fn fast_db_to_linear(db: f32) -> f32 {
+    // 10^(db/20) = 2^(db * log2(10) / 20)
+    fast_exp2(db * (core::f32::consts::LOG2_10 / 20.0))
+}
+
+/// `linear_to_db` via `fast_log2` (max absolute error 1e-4dB)
+#[inline]
+pub fn fast_linear_to_db(linear: f32) -> f32 {
+    // 20 * log10(x) = 20 * log10(2) * log2(x)
+    (20.0 * core::f32::consts::LOG10_2) * fast_log2(linear.max(1e-10))
+}
+
+/// Equal-power dry/wet gains for a mix amount
+/// 
+/// The squared gains sum to 1, so uncorrelated signals (a dry signal and
+/// its reverb, say) keep their loudness across the mix instead of dipping
+/// ~3dB at the center like a linear crossfade. The gains are cos/sin of
+/// `mix * π/2`, with exact endpoints (cos(π/2) isn't 0.0 in f32).
+/// 
+/// # Arguments
+/// * `mix` - Mix between dry (0) and wet (1)
+/// 
+/// # Returns
+/// `(dry_gain, wet_gain)`
+#[inline]
+pub fn equal_power_gains(mix: f32) -> (f32, f32) {
+    if mix <= 0.0 {
+        (1.0, 0.0)
+    } else if mix >= 1.0 {
+        (0.0, 1.0)
+    } else {
+        let angle = mix * core::f32::consts::FRAC_PI_2;
+        (libm::cosf(angle), libm::sinf(angle))
+    }
+}
+
+/// Stereo pan law: the level of each channel with the signal centered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanLaw {
+    /// -3dB at center: constant power, for uncorrelated sources
+    ConstantPower,
+    /// -4.5dB at center: halfway between constant power and linear
+    Compromise,
+    /// -6dB at center: constant amplitude, for correlated (mono) sources
+    Linear,
+}
+
+impl PanLaw {
+    /// Law from its export index (unknown values fall back to ConstantPower)
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => PanLaw::Compromise,
+            2 => PanLaw::Linear,
+            _ => PanLaw::ConstantPower,
+        }
+    }
+}
+
+/// Constant-power (-3dB) pan gains
+/// 
+/// # Arguments
+/// * `pan` - Pan position (-1 = left, 0 = center, 1 = right), clamped
+/// 
+/// # Returns
+/// `(left_gain, right_gain)`
+#[inline]
+pub fn pan_constant_power(pan: f32) -> (f32, f32) {
+    equal_power_gains((pan.clamp(-1.0, 1.0) + 1.0) * 0.5)
+}
+
+/// Pan gains for a pan law
+/// 
+/// # Arguments
+/// * `pan` - Pan position (-1 = left, 0 = center, 1 = right), clamped
+/// * `law` - Level of each channel at center
+/// 
+/// # Returns
+/// `(left_gain, right_gain)`, 1 on the side panned to
+#[inline]
+pub fn pan_gains(pan: f32, law: PanLaw) -> (f32, f32) {
+    let right = (pan.clamp(-1.0, 1.0) + 1.0) * 0.5;
+    match law {
+        PanLaw::ConstantPower => pan_constant_power(pan),
+        // Geometric mean of the constant-power and linear gains
+        PanLaw::Compromise => {
+            let (left_power, right_power) = pan_constant_power(pan);
+            ((left_power * (1.0 - right)).sqrt(), (right_power * right).sqrt())
+        }
+        PanLaw::Linear => (1.0 - right, right),
+    }
+}
+
+/// Half-width of the resampling kernel in input samples (at unity cutoff)
+const RESAMPLE_HALF_TAPS: usize = 8;
+
+/// Windowed-sinc sample-rate converter
+/// 
+/// Resamples interleaved audio with a Hann-windowed sinc kernel. The kernel
+/// widens and its cutoff drops when downsampling, so content above the new
+/// Nyquist frequency is filtered out instead of aliasing. Not real-time
+/// safe; intended for load-time conversion.
+#[derive(Clone, Copy, Debug)]
+pub struct Resampler {
+    /// Output rate / input rate
+    ratio: f32,
+    /// Kernel cutoff relative to the input Nyquist frequency
+    cutoff: f32,
+    /// Kernel half-width in input samples
+    half_width: isize,
+}
+
+impl Resampler {
+    /// Create a converter
+    /// 
+    /// # Arguments
+    /// * `ratio` - Output rate / input rate
+    pub fn new(ratio: f32) -> Self {
+        let cutoff = ratio.min(1.0);
+        Self {
+            ratio,
+            cutoff,
+            half_width: (RESAMPLE_HALF_TAPS as f32 / cutoff).ceil() as isize,
+        }
+    }
+    
+    /// Frames a full conversion of `in_frames` input frames produces
+    #[inline]
+    pub fn output_frames(&self, in_frames: usize) -> usize {
+        (in_frames as f64 * self.ratio as f64).round() as usize
+    }
+    
+    /// Resample interleaved audio
+    /// 
+    /// # Arguments
+    /// * `input` - Interleaved input samples
+    /// * `output` - Interleaved output buffer (bounds the output length)
+    /// * `channels` - Number of interleaved channels
+    /// 
+    /// # Returns
+    /// Number of frames written to `output`; fewer than `output_frames`
+    /// if the output buffer is too short
+    pub fn process(&self, input: &[f32], output: &mut [f32], channels: usize) -> usize {
+        let in_frames = input.len() / channels;
+        let out_frames = self.output_frames(in_frames).min(output.len() / channels);
+        let step = 1.0 / self.ratio as f64;
+        let (cutoff, half_width) = (self.cutoff, self.half_width);
+        
+        for frame in 0..out_frames {
+            let pos = frame as f64 * step;
+            let center = pos.floor() as isize;
+            let frac = (pos - center as f64) as f32;
+            let out = &mut output[frame * channels..(frame + 1) * channels];
+            out.fill(0.0);
+            
+            let mut weight_sum = 0.0;
+            for k in (1 - half_width)..=half_width {
+                let idx = center + k;
+                if idx < 0 || idx as usize >= in_frames {
+                    continue;
+                }
+                
+                let x = k as f32 - frac;
+                let window = 0.5 + 0.5 * libm::cosf(core::f32::consts::PI * x / half_width as f32);
+                let weight = cutoff * sinc(cutoff * x) * window;
+                weight_sum += weight;
+                
+                let src = &input[idx as usize * channels..(idx as usize + 1) * channels];
+                for (o, s) in out.iter_mut().zip(src) {
+                    *o += s * weight;
+                }
+            }
+            
+            // Normalize for unity DC gain (also compensates truncated edge taps)
+            if weight_sum.abs() > 1e-6 {
+                for o in out.iter_mut() {
+                    *o /= weight_sum;
+                }
+            }
+        }
+        
+        out_frames
+    }
+}
+
+/// Normalized sinc: sin(πx) / (πx)
+#[inline]
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        1.0
+    } else {
+        let px = core::f32::consts::PI * x;
+        libm::sinf(px) / px
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fast_approximations_within_error_bounds() {
+        // Dense sweeps over the useful ranges; the worst error stays within
+        // the documented bounds, and no neighbouring outputs jump
+        let sweep = |lo: f32, hi: f32| (0..=200_000).map(move |i| lo + (hi - lo) * i as f32 / 200_000.0);
+        let mut worst = [0.0f32; 3];
+        let mut previous = fast_tanh(-10.0);
+        for x in sweep(-10.0, 10.0) {
+            let y = fast_tanh(x);
+            worst[0] = worst[0].max((y - libm::tanhf(x)).abs());
+            assert!((y - previous).abs() <= 1e-4 + 1e-6, "tanh steps at {x}");
+            previous = y;
+        }
+        for x in sweep(-40.0, 40.0) {
+            worst[1] = worst[1].max((fast_exp2(x) / libm::exp2f(x) - 1.0).abs());
+        }
+        for x in sweep(-30.0, 30.0) {
+            let x = libm::exp2f(x);
+            worst[2] = worst[2].max((fast_log2(x) - libm::log2f(x)).abs());
+        }
+        assert!(worst[0] < 1e-5, "tanh error {}", worst[0]);
+        assert!(worst[1] < 2e-6, "exp2 error {}", worst[1]);
+        assert!(worst[2] < 5e-6, "log2 error {}", worst[2]);
+        
+        // Exact at the table points, odd, and clamped outside the ranges
+        assert_eq!(fast_tanh(0.0), 0.0);
+        assert_eq!(fast_tanh(-2.5), -fast_tanh(2.5));
+        assert_eq!(fast_tanh(100.0), fast_tanh(TANH_RANGE));
+        assert_eq!(fast_exp2(0.0), 1.0);
+        assert_eq!(fast_exp2(3.0), 8.0);
+        assert_eq!(fast_exp2(-1.0), 0.5);
+        assert_eq!(fast_log2(1.0), 0.0);
+        assert_eq!(fast_log2(1024.0), 10.0);
+        assert_eq!(fast_log2(0.0), -126.0);
+        
+        // dB helpers track the exact ones
+        for db in [-120.0, -60.0, -6.0, 0.0, 12.0, 24.0] {
+            assert!((fast_db_to_linear(db) / db_to_linear(db) - 1.0).abs() < 1e-5, "{db}dB");
+            assert!((fast_linear_to_db(db_to_linear(db)) - db).abs() < 1e-3, "{db}dB");
+        }
+        
+        // A driven sine (peaks at tanh(4)) distorts far below 0.5% THD
+        // from the approximation
+        let (mut error, mut signal) = (0.0f32, 0.0f32);
+        for n in 0..4800 {
+            let x = 4.0 * libm::sinf(core::f32::consts::TAU * n as f32 / 480.0);
+            let exact = libm::tanhf(x);
+            error += (fast_tanh(x) - exact).powi(2);
+            signal += exact * exact;
+        }
+        assert!((error / signal).sqrt() < 1e-4, "error {}", (error / signal).sqrt());
+    }
+    
+    #[test]
+    fn test_pan_laws() {
+        // Center level of each law: -3dB, -4.5dB and -6dB on both channels
+        for (law, center_db) in [(PanLaw::ConstantPower, -3.01), (PanLaw::Compromise, -4.52), (PanLaw::Linear, -6.02)] {
+            let (left, right) = pan_gains(0.0, law);
+            assert_eq!(left, right, "{law:?}");
+            assert!((linear_to_db(left) - center_db).abs() < 0.01, "{law:?}: {}dB", linear_to_db(left));
+            
+            // Hard pans reach unity on one side and silence on the other,
+            // and out-of-range pans clamp
+            assert_eq!(pan_gains(-1.0, law), (1.0, 0.0));
+            assert_eq!(pan_gains(1.0, law), (0.0, 1.0));
+            assert_eq!(pan_gains(3.0, law), pan_gains(1.0, law));
+        }
+        let (left, right) = pan_constant_power(0.0);
+        assert!((left - core::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((left * left + right * right - 1.0).abs() < 1e-6);
+        
+        // Constant power across the range, linear sums to 1
+        for i in -10..=10 {
+            let pan = i as f32 / 10.0;
+            let (left, right) = pan_constant_power(pan);
+            assert!((left * left + right * right - 1.0).abs() < 1e-6);
+            let (left, right) = pan_gains(pan, PanLaw::Linear);
+            assert!((left + right - 1.0).abs() < 1e-6);
+            
+            // Compromise gains in dB sit halfway between the other two laws
+            let [power, compromise, linear] = [PanLaw::ConstantPower, PanLaw::Compromise, PanLaw::Linear].map(|law| pan_gains(pan, law).0);
+            if linear > 0.0 {
+                let halfway = (linear_to_db(power) + linear_to_db(linear)) / 2.0;
+                assert!((linear_to_db(compromise) - halfway).abs() < 1e-3, "pan {pan}");
+            }
+        }
+    }
+}
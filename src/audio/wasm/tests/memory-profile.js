@@ -219,7 +219,7 @@ export async function runWasmMemoryTest(wasmPath, durationSeconds = CONFIG.durat
         
         // Run test with granular processing
         return await runMemoryTest(durationSeconds, () => {
-            wasm.dsp_process_granular(256, 50.0, 0.1, 0.5, 0.05);
+            wasm.dsp_process_granular(0, 256, 50.0, 0.1, 0.5, 0.05);
         });
         
     } catch (error) {
@@ -8,12 +8,17 @@
  * 
  * # Memory Layout (must match memory.rs constants)
  * - 0x0000: Engine State (256 bytes)
- * - 0x0100: Input Buffer L (512 samples = 2KB)
- * - 0x0300: Input Buffer R (512 samples = 2KB)
- * - 0x0500: Output Buffer L (512 samples = 2KB)
- * - 0x0700: Output Buffer R (512 samples = 2KB)
- * - 0x1900: Granular Source Buffer
+ * - 0x0100: Input Buffer L (2048 samples = 8KB)
+ * - 0x2100: Input Buffer R (2048 samples = 8KB)
+ * - 0x4100: Output Buffer L (2048 samples = 8KB)
+ * - 0x6100: Output Buffer R (2048 samples = 8KB)
+ * - 0xC100: Granular Source Buffer
  * - 0x380000: IR Buffer
+ * - 0x570000: Vocoder Carrier Buffer
+ * - 0x750000: Live History Ring (written by WASM)
+ * - 0x8C7000: IR Slot B Buffer
+ * Offsets are for the first engine (handle 0); other engines repeat the
+ * layout further up, so region addresses are queried per handle.
  * 
  * @important NO ALLOCATIONS IN process() CALLBACK!
  */
@@ -21,11 +26,32 @@
 // Memory layout constants (must match Rust memory.rs)
 const MEMORY_LAYOUT = {
     INPUT_L_OFFSET: 0x0100,
-    INPUT_R_OFFSET: 0x0300,
-    OUTPUT_L_OFFSET: 0x0500,
-    OUTPUT_R_OFFSET: 0x0700,
-    GRANULAR_SOURCE_OFFSET: 0x1900,
+    INPUT_R_OFFSET: 0x2100,
+    OUTPUT_L_OFFSET: 0x4100,
+    OUTPUT_R_OFFSET: 0x6100,
+    GRANULAR_SOURCE_OFFSET: 0xC100,
     IR_OFFSET: 0x380000,
+    VOCODER_CARRIER_OFFSET: 0x570000,
+    LIVE_HISTORY_OFFSET: 0x750000,
+    IR_B_OFFSET: 0x8C7000,
+    SIDECHAIN_OFFSET: 0xA9BC00,
+    SPECTRAL_MASK_OFFSET: 0xA9DC00,
+    MAX_GRANULAR_SOURCE_SAMPLES: 44100 * 10 * 2,
+    MAX_IR_SAMPLES: 48000 * 5 * 2,
+    MAX_SPECTRAL_MASK_BINS: 8192 / 2 + 1,
+};
+
+// Frames of a streamed IR written per process() call
+const IR_CHUNK_FRAMES = 4096;
+
+// Spectral freeze capture slots (FREEZE_SLOTS in spectral.rs)
+const SPECTRAL_FREEZE_SLOTS = 4;
+
+// dsp_load_granular_source / dsp_load_ir status codes
+const LoadStatus = {
+    OK: 0,
+    TRUNCATED: 1,
+    REJECTED: 2,
 };
 
 // Effect types matching Rust implementation
@@ -34,6 +60,14 @@ const EffectType = {
     GRANULAR: 1,
     CONVOLUTION: 2,
     SPECTRAL: 3,
+    FLANGER: 4,
+    VOCODER: 5,
+    PITCH_SHIFT: 6,
+    SPECTRAL_GATE: 7,
+    SATURATION: 8,
+    RESONATOR: 9,
+    SHIMMER: 10,
+    DIFFUSER: 11,
 };
 
 class WasmDspProcessor extends AudioWorkletProcessor {
@@ -60,6 +94,17 @@ class WasmDspProcessor extends AudioWorkletProcessor {
         this.inputPtrR = 0;
         this.outputPtrL = 0;
         this.outputPtrR = 0;
+        this.granularSourcePtr = 0;
+        this.irPtr = 0;
+        this.irSlotBPtr = 0;
+        this.sidechainPtr = 0;
+        this.spectralMaskPtr = 0;
+        
+        /** IR being streamed into WASM memory ({ samples, channels, total, written }) */
+        this.irStream = null;
+        
+        /** Handle of this processor's engine (from dsp_init) */
+        this.engineHandle = 0;
         
         // ====================================================================
         // PROCESSING STATE
@@ -75,17 +120,27 @@ class WasmDspProcessor extends AudioWorkletProcessor {
         this.params = {
             // Granular synthesis parameters
             grainSize: 256,       // 64-4096 samples
-            density: 20.0,        // 1-100 grains/sec
+            density: 20.0,        // 1-100000 grains/sec
             pitchSpread: 0.1,     // 0-1
             position: 0.5,        // 0-1 (playback position in source)
             spray: 0.05,          // 0-1 (position randomization)
             
             // Convolution parameters
             dryWet: 0.5,          // 0-1
+            predelayMs: 0.0,      // 0-200 ms
+            earlyLateSplitMs: 80.0, // 0-1000 ms
+            earlyGain: 1.0,       // 0-2
+            lateGain: 1.0,        // 0-2
+            irBlend: 0.0,         // 0-1 (slot 0 IR to slot 1 IR)
             
             // Spectral parameters
             freezeAmount: 0.0,    // 0-1
             frequencyShift: 0.0,  // -24 to +24 semitones
+            freezeSlot: -1.0,     // 0-3 blends captured slots, -1 = auto-capture
+            spectralDryWet: 1.0,  // 0-1 (dry delayed to match the latency)
+            spectralMode: 0,      // 0 = normal, 1 = robot, 2 = whisper
+            formantPreserve: false, // keep the spectral envelope when shifting
+            frequencyShiftHz: 0.0, // linear shift after the semitone shift (Hz)
         };
         
         // ====================================================================
@@ -120,19 +175,294 @@ class WasmDspProcessor extends AudioWorkletProcessor {
                 
             case 'set-effect':
                 this.currentEffect = data.effect;
+                // Crossfade from the previous effect over the next blocks
+                if (this.initialized) {
+                    this.exports.dsp_set_active_effect(this.engineHandle, data.effect);
+                }
+                break;
+                
+            case 'set-effect-switch-blocks':
+                if (this.initialized) {
+                    this.exports.dsp_set_effect_switch_blocks(this.engineHandle, data.blocks);
+                }
                 break;
                 
             case 'set-params':
                 // Merge new params with existing (allows partial updates)
                 Object.assign(this.params, data.params);
+                // Predelay is engine state rather than a per-block argument
+                if (this.initialized && data.params.predelayMs !== undefined) {
+                    this.exports.dsp_set_convolution_predelay(this.engineHandle, data.params.predelayMs);
+                }
+                if (this.initialized && data.params.earlyLateSplitMs !== undefined) {
+                    this.exports.dsp_set_convolution_split(this.engineHandle, data.params.earlyLateSplitMs);
+                }
+                if (this.initialized && (data.params.earlyGain !== undefined || data.params.lateGain !== undefined)) {
+                    this.exports.dsp_set_convolution_early_late(
+                        this.engineHandle,
+                        this.params.earlyGain,
+                        this.params.lateGain,
+                    );
+                }
                 break;
                 
             case 'load-granular-source':
-                this.loadGranularSource(data.samples, data.channels);
+                this.loadGranularSource(data.samples, data.channels, data.sampleRate);
                 break;
                 
             case 'load-ir':
-                this.loadIR(data.samples, data.channels);
+                this.loadIR(data.samples, data.channels, data.slot ?? 0, data.sampleRate);
+                break;
+                
+            case 'stream-ir':
+                // Long IRs: written a chunk per process() call
+                this.streamIR(data.samples, data.channels);
+                break;
+                
+            case 'set-ir-normalization':
+                if (this.initialized) {
+                    this.exports.dsp_set_ir_normalization(this.engineHandle, data.mode);
+                }
+                break;
+                
+            case 'set-ir-decay':
+                if (this.initialized) {
+                    this.exports.dsp_set_ir_decay(this.engineHandle, data.amount, data.trimSeconds);
+                }
+                break;
+                
+            case 'set-ir-filters':
+                if (this.initialized) {
+                    this.exports.dsp_set_ir_filters(this.engineHandle, data.lowCutHz, data.highCutHz);
+                }
+                break;
+                
+            case 'set-convolution-block':
+                // Rebuilds the reverb's partitions; the latency changes
+                if (this.initialized) {
+                    this.exports.dsp_set_convolution_block(this.engineHandle, data.size);
+                }
+                break;
+                
+            case 'set-spectral-fft':
+                // Reallocates the spectral effects; the latency changes
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_fft(this.engineHandle, data.size, data.overlap);
+                }
+                break;
+                
+            case 'set-ducking':
+                if (this.initialized) {
+                    this.exports.dsp_set_ducking(
+                        this.engineHandle,
+                        data.amount,
+                        data.attackMs,
+                        data.releaseMs,
+                        data.detector
+                    );
+                }
+                break;
+                
+            case 'set-convolution-compressor':
+                // Ratio 1 bypasses the compressor
+                if (this.initialized) {
+                    this.exports.dsp_set_convolution_compressor(
+                        this.engineHandle,
+                        data.thresholdDb,
+                        data.ratio,
+                        data.attackMs,
+                        data.releaseMs,
+                        data.makeupDb,
+                        data.detector
+                    );
+                }
+                break;
+                
+            case 'set-spectral-gate':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_gate(
+                        this.engineHandle,
+                        data.thresholdDb,
+                        data.reductionDb,
+                        data.releaseMs
+                    );
+                }
+                break;
+                
+            case 'set-spectral-freeze':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_freeze(this.engineHandle, data.fadeFrames, data.phaseDrift);
+                }
+                break;
+                
+            case 'set-spectral-freeze-motion':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_freeze_motion(this.engineHandle, data.decayDbPerSecond, data.drift);
+                }
+                break;
+                
+            case 'set-spectral-tilt':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_tilt(this.engineHandle, data.dbPerOctave, data.pivotHz);
+                }
+                break;
+                
+            case 'set-spectral-shelves':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_shelves(
+                        this.engineHandle,
+                        data.lowHz,
+                        data.lowDb,
+                        data.highHz,
+                        data.highDb
+                    );
+                }
+                break;
+                
+            case 'set-spectral-phase-locking':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_phase_locking(this.engineHandle, data.enabled ? 1 : 0);
+                }
+                break;
+                
+            case 'set-spectral-feedback':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_feedback(this.engineHandle, data.amount);
+                }
+                break;
+                
+            case 'set-spectral-voice':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_voice(this.engineHandle, data.index, data.semitones, data.gain);
+                }
+                break;
+                
+            case 'set-spectral-voice-count':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_voice_count(this.engineHandle, data.count);
+                }
+                break;
+                
+            case 'set-spectral-comb':
+                if (this.initialized) {
+                    this.exports.dsp_set_spectral_comb(
+                        this.engineHandle,
+                        data.fundamentalHz,
+                        data.widthBins,
+                        data.depth
+                    );
+                }
+                break;
+                
+            case 'load-spectral-mask':
+                this.loadSpectralMask(data.gains);
+                break;
+                
+            case 'spectral-recapture':
+                // Replaces the frozen spectrum on the next analysis frame
+                if (this.initialized) {
+                    this.exports.dsp_spectral_recapture(this.engineHandle);
+                }
+                break;
+                
+            case 'spectral-capture':
+                // Stored on the spectral effect's next analysis frame
+                if (this.initialized) {
+                    this.exports.dsp_spectral_capture(this.engineHandle, data.slot);
+                }
+                break;
+                
+            case 'spectral-release':
+                if (this.initialized) {
+                    this.exports.dsp_spectral_release(this.engineHandle, data.slot);
+                }
+                break;
+                
+            case 'reset-convolution':
+                // Drop the reverb tail (e.g. when the transport stops),
+                // fading out over the next block
+                if (this.initialized) {
+                    this.exports.dsp_reset_convolution(this.engineHandle);
+                }
+                break;
+                
+            case 'reset-granular':
+                if (this.initialized) {
+                    this.exports.dsp_reset_granular(this.engineHandle);
+                }
+                break;
+                
+            case 'reset-spectral':
+                if (this.initialized) {
+                    this.exports.dsp_reset_spectral(this.engineHandle);
+                }
+                break;
+                
+            case 'reset-all':
+                if (this.initialized) {
+                    this.exports.dsp_reset_all(this.engineHandle);
+                }
+                break;
+                
+            case 'set-effect-enabled':
+                // Disabled effects pass audio through without processing
+                if (this.initialized) {
+                    this.exports.dsp_set_effect_enabled(this.engineHandle, data.effectId, data.enabled ? 1 : 0);
+                }
+                break;
+                
+            case 'set-dc-blocker':
+                if (this.initialized) {
+                    this.exports.dsp_set_dc_blocker(this.engineHandle, data.enabled ? 1 : 0);
+                }
+                break;
+                
+            case 'set-limiter':
+                if (this.initialized) {
+                    this.exports.dsp_set_limiter(this.engineHandle, data.thresholdDb, data.enabled ? 1 : 0);
+                }
+                break;
+                
+            case 'gain-reduction':
+                if (this.initialized) {
+                    this.port.postMessage({
+                        type: 'gain-reduction',
+                        gainReductionDb: this.exports.dsp_get_gain_reduction_db(this.engineHandle),
+                    });
+                }
+                break;
+                
+            case 'latency':
+                if (this.initialized) {
+                    this.port.postMessage({
+                        type: 'latency',
+                        latencySamples: this.exports.dsp_get_latency_samples(this.engineHandle, this.currentEffect),
+                    });
+                }
+                break;
+                
+            case 'spectral-slots':
+                if (this.initialized) {
+                    const capturedSlots = [];
+                    for (let slot = 0; slot < SPECTRAL_FREEZE_SLOTS; slot++) {
+                        capturedSlots.push(this.exports.dsp_spectral_slot_captured(this.engineHandle, slot) !== 0);
+                    }
+                    this.port.postMessage({ type: 'spectral-slots', capturedSlots });
+                }
+                break;
+                
+            case 'memory-report':
+                this.postMemoryReport();
+                break;
+                
+            case 'set-profiling':
+                if (this.initialized) {
+                    this.exports.dsp_set_profiling(this.engineHandle, data.enabled ? 1 : 0);
+                }
+                break;
+                
+            case 'profile-report':
+                this.postProfileReport();
                 break;
                 
             default:
@@ -151,11 +481,12 @@ class WasmDspProcessor extends AudioWorkletProcessor {
     async initWasm(module, config) {
         try {
             // Create WASM memory
-            // Initial: 8MB (128 pages × 64KB), Max: 16MB (256 pages)
-            // Must be large enough for all buffers defined in memory.rs
+            // Initial: 10MB (160 pages × 64KB), Max: 64MB (1024 pages)
+            // dsp_init allocates the engine's buffers defined in memory.rs from
+            // the heap (~11MB per engine), growing memory as needed
             this.wasmMemory = new WebAssembly.Memory({
-                initial: 128,
-                maximum: 256,
+                initial: 160,
+                maximum: 1024,
                 shared: false, // SharedArrayBuffer requires COOP/COEP headers
             });
             
@@ -178,6 +509,9 @@ class WasmDspProcessor extends AudioWorkletProcessor {
                     logf: Math.log,
                     fmodf: (a, b) => a % b,
                     
+                    // Clock for the block profiler (dsp_set_profiling)
+                    dsp_now_ms: () => (globalThis.performance ? performance.now() : currentTime * 1000),
+                    
                     // Panic handler (if Rust panics, log it)
                     __wbindgen_throw: (ptr, len) => {
                         // This would require reading string from WASM memory
@@ -192,21 +526,28 @@ class WasmDspProcessor extends AudioWorkletProcessor {
             
             // Initialize DSP engine with sample rate and buffer size
             // sampleRate is a global in AudioWorkletGlobalScope
-            const statePtr = this.exports.dsp_init(
+            // The returned handle selects the engine in every other call
+            const handle = this.exports.dsp_init(
                 config.sampleRate || sampleRate,
                 config.bufferSize || 128
             );
             
-            if (statePtr === 0) {
-                throw new Error('DSP engine initialization failed (returned null)');
+            if (handle < 0) {
+                throw new Error('DSP engine initialization failed (no engine handle)');
             }
+            this.engineHandle = handle;
             
             // Get buffer pointers from WASM
             // These are byte offsets we use to read/write audio data
-            this.inputPtrL = this.exports.dsp_get_input_ptr(0);
-            this.inputPtrR = this.exports.dsp_get_input_ptr(1);
-            this.outputPtrL = this.exports.dsp_get_output_ptr(0);
-            this.outputPtrR = this.exports.dsp_get_output_ptr(1);
+            this.inputPtrL = this.exports.dsp_get_input_ptr(handle, 0);
+            this.inputPtrR = this.exports.dsp_get_input_ptr(handle, 1);
+            this.outputPtrL = this.exports.dsp_get_output_ptr(handle, 0);
+            this.outputPtrR = this.exports.dsp_get_output_ptr(handle, 1);
+            this.granularSourcePtr = this.exports.dsp_get_granular_source_ptr(handle);
+            this.irPtr = this.exports.dsp_get_ir_ptr(handle);
+            this.irSlotBPtr = this.exports.dsp_get_ir_slot_ptr(handle, 1);
+            this.sidechainPtr = this.exports.dsp_get_sidechain_ptr(handle);
+            this.spectralMaskPtr = this.exports.dsp_get_spectral_mask_ptr(handle);
             
             // Create reusable Float32Array view into WASM memory
             // This view spans the entire linear memory
@@ -218,6 +559,7 @@ class WasmDspProcessor extends AudioWorkletProcessor {
             this.port.postMessage({ type: 'initialized' });
             
             console.log('[WasmDspProcessor] WASM initialized successfully', {
+                handle,
                 sampleRate: config.sampleRate || sampleRate,
                 bufferSize: config.bufferSize || 128,
                 inputPtrL: this.inputPtrL,
@@ -239,9 +581,11 @@ class WasmDspProcessor extends AudioWorkletProcessor {
     
     /**
      * Load source audio for granular synthesis.
-     * Writes interleaved samples to WASM memory at GRANULAR_SOURCE_OFFSET.
+     * Writes interleaved samples to WASM memory in the engine's granular source region.
+     * When sourceRate is given, the source is resampled to the engine
+     * rate on the WASM side (a no-op copy when the rates already match).
      */
-    loadGranularSource(samples, channels) {
+    loadGranularSource(samples, channels, sourceRate) {
         if (!this.initialized) {
             console.warn('[WasmDspProcessor] Cannot load source: not initialized');
             return;
@@ -253,32 +597,81 @@ class WasmDspProcessor extends AudioWorkletProcessor {
         }
         
         // Write samples to WASM memory at granular source offset
-        // Offset is in bytes, but memoryView is Float32Array (4 bytes each)
-        const sourceOffset = MEMORY_LAYOUT.GRANULAR_SOURCE_OFFSET / 4;
-        this.memoryView.set(samples, sourceOffset);
+        // Offset is in bytes, but memoryView is Float32Array (4 bytes each).
+        // Only whole frames that fit the region are written so an oversized
+        // source can't spill into the IR buffer; Rust reports the truncation.
+        const sourceOffset = this.granularSourcePtr >>> 2;
+        const maxSamples = MEMORY_LAYOUT.MAX_GRANULAR_SOURCE_SAMPLES
+            - (MEMORY_LAYOUT.MAX_GRANULAR_SOURCE_SAMPLES % channels);
+        this.memoryView.set(
+            samples.length > maxSamples ? samples.subarray(0, maxSamples) : samples,
+            sourceOffset
+        );
         
-        // Tell Rust about the loaded source
-        this.exports.dsp_load_granular_source(
-            MEMORY_LAYOUT.GRANULAR_SOURCE_OFFSET, // byte offset
+        // Tell Rust about the loaded source (0 = already at the engine rate)
+        const status = this.exports.dsp_load_granular_source(
+            this.engineHandle,
+            this.granularSourcePtr,                // byte offset
             samples.length / channels,             // sample count per channel
-            channels                               // channel count
+            channels,                              // channel count
+            sourceRate || 0                        // source sample rate
         );
         
-        console.log(`[WasmDspProcessor] Loaded granular source: ${samples.length} samples, ${channels} channels`);
+        if (status === LoadStatus.REJECTED) {
+            console.warn(`[WasmDspProcessor] Granular source rejected: ${channels} channels`);
+            return;
+        }
+        
+        const length = this.exports.dsp_granular_source_frames(this.engineHandle);
+        if (status === LoadStatus.TRUNCATED) {
+            console.warn(`[WasmDspProcessor] Granular source truncated to ${length} frames`);
+        }
+        console.log(`[WasmDspProcessor] Loaded granular source: ${length} frames, ${channels} channels`);
         
         // Notify main thread
         this.port.postMessage({ 
             type: 'granular-source-loaded',
-            length: samples.length,
+            length: length * channels,
             channels: channels,
+            frames: length,
+            durationMs: this.exports.dsp_granular_source_duration_ms(this.engineHandle),
+            truncated: status === LoadStatus.TRUNCATED,
         });
     }
     
+    /**
+     * Load the per-bin gain mask of the spectral freeze (one gain per bin
+     * from DC up; an empty mask clears it).
+     */
+    loadSpectralMask(gains) {
+        if (!this.initialized) {
+            console.warn('[WasmDspProcessor] Cannot load spectral mask: not initialized');
+            return;
+        }
+        
+        // Refresh memory view in case memory grew
+        if (this.memoryView.buffer !== this.wasmMemory.buffer) {
+            this.memoryView = new Float32Array(this.wasmMemory.buffer);
+        }
+        
+        // Only the gains that fit the region are written; Rust reports the
+        // truncation
+        const written = gains.length > MEMORY_LAYOUT.MAX_SPECTRAL_MASK_BINS
+            ? gains.subarray(0, MEMORY_LAYOUT.MAX_SPECTRAL_MASK_BINS)
+            : gains;
+        this.memoryView.set(written, this.spectralMaskPtr >>> 2);
+        const status = this.exports.dsp_load_spectral_mask(this.engineHandle, this.spectralMaskPtr, gains.length);
+        if (status === LoadStatus.TRUNCATED) {
+            console.warn(`[WasmDspProcessor] Spectral mask truncated to ${written.length} bins`);
+        }
+    }
+    
     /**
      * Load impulse response for convolution reverb.
-     * Writes interleaved samples to WASM memory at IR_OFFSET.
+     * Writes interleaved samples to WASM memory in the IR region of the
+     * slot (0 or 1; irBlend crossfades between them).
      */
-    loadIR(samples, channels) {
+    loadIR(samples, channels, slot, sourceRate) {
         if (!this.initialized) {
             console.warn('[WasmDspProcessor] Cannot load IR: not initialized');
             return;
@@ -289,24 +682,160 @@ class WasmDspProcessor extends AudioWorkletProcessor {
             this.memoryView = new Float32Array(this.wasmMemory.buffer);
         }
         
-        // Write samples to WASM memory at IR offset
-        const irOffset = MEMORY_LAYOUT.IR_OFFSET / 4;
-        this.memoryView.set(samples, irOffset);
-        
-        // Tell Rust about the loaded IR
-        this.exports.dsp_load_ir(
-            MEMORY_LAYOUT.IR_OFFSET,    // byte offset
+        // Write samples to WASM memory at IR offset. Only whole frames that
+        // fit the region are written so an oversized IR can't spill into the
+        // FFT scratch area; Rust reports the truncation.
+        // A slot 0 load replaces a streamed one still in progress
+        if (slot === 0) {
+            this.irStream = null;
+        }
+        const irPtr = slot === 1 ? this.irSlotBPtr : this.irPtr;
+        const irOffset = irPtr >>> 2;
+        const maxSamples = MEMORY_LAYOUT.MAX_IR_SAMPLES
+            - (MEMORY_LAYOUT.MAX_IR_SAMPLES % channels);
+        const written = samples.length > maxSamples ? samples.subarray(0, maxSamples) : samples;
+        this.memoryView.set(written, irOffset);
+        
+        // Tell Rust about the loaded IR (resampled on the WASM side when
+        // its rate is known; 0 = already at the engine rate)
+        const status = this.exports.dsp_load_ir_slot(
+            this.engineHandle,
+            slot,
+            irPtr,                      // byte offset
             samples.length / channels,   // sample count per channel
-            channels                      // channel count
+            channels,                     // channel count
+            sourceRate || 0               // source sample rate
         );
         
-        console.log(`[WasmDspProcessor] Loaded IR: ${samples.length} samples, ${channels} channels`);
+        if (status === LoadStatus.REJECTED) {
+            console.warn(`[WasmDspProcessor] IR rejected: ${channels} channels`);
+            return;
+        }
+        if (status === LoadStatus.TRUNCATED) {
+            console.warn(`[WasmDspProcessor] IR truncated to ${written.length / channels} frames`);
+        }
+        console.log(`[WasmDspProcessor] Loaded IR: ${written.length} samples, ${channels} channels`);
         
         // Notify main thread
         this.port.postMessage({ 
             type: 'ir-loaded',
-            length: samples.length,
+            slot,
+            length: written.length,
             channels: channels,
+            // Gain of the slot 0 IR (slot 1 gains are not exported)
+            normalizationGain: slot === 0 ? this.exports.dsp_ir_normalization_gain(this.engineHandle) : undefined,
+            truncated: status === LoadStatus.TRUNCATED,
+        });
+    }
+    
+    /**
+     * Start streaming an impulse response into the engine's IR region.
+     * process() writes IR_CHUNK_FRAMES per call; the current IR keeps
+     * playing until the last chunk is in.
+     */
+    streamIR(samples, channels) {
+        if (!this.initialized) {
+            console.warn('[WasmDspProcessor] Cannot stream IR: not initialized');
+            return;
+        }
+        
+        const status = this.exports.dsp_begin_ir_load(this.engineHandle, samples.length / channels, channels);
+        if (status === LoadStatus.REJECTED) {
+            console.warn(`[WasmDspProcessor] IR rejected: ${channels} channels`);
+            return;
+        }
+        // Only whole frames that fit the region are streamed
+        const maxSamples = MEMORY_LAYOUT.MAX_IR_SAMPLES
+            - (MEMORY_LAYOUT.MAX_IR_SAMPLES % channels);
+        const total = Math.min(samples.length, maxSamples);
+        this.irStream = { samples, channels, total, written: 0 };
+    }
+    
+    /**
+     * Write the next chunk of a streamed IR (called from process(), so it
+     * copies sample by sample instead of allocating a subarray).
+     */
+    appendIRChunk() {
+        const stream = this.irStream;
+        const start = stream.written;
+        const end = Math.min(start + IR_CHUNK_FRAMES * stream.channels, stream.total);
+        const irOffset = this.irPtr >>> 2;
+        for (let i = start; i < end; i++) {
+            this.memoryView[irOffset + i] = stream.samples[i];
+        }
+        stream.written = end;
+        this.exports.dsp_append_ir_chunk(
+            this.engineHandle,
+            this.irPtr + start * 4,
+            (end - start) / stream.channels,
+        );
+        if (end < stream.total) {
+            return;
+        }
+        
+        const status = this.exports.dsp_finish_ir_load(this.engineHandle);
+        this.irStream = null;
+        this.port.postMessage({
+            type: 'ir-loaded',
+            slot: 0,
+            length: stream.total,
+            channels: stream.channels,
+            truncated: status === LoadStatus.TRUNCATED,
+        });
+    }
+    
+    /**
+     * Post the engine's region capacities and usage to the main thread.
+     * Field order matches MemoryReport in memory.rs.
+     */
+    postMemoryReport() {
+        if (!this.initialized) {
+            console.warn('[WasmDspProcessor] Cannot report memory: not initialized');
+            return;
+        }
+        
+        const ptr = this.exports.dsp_memory_report(this.engineHandle);
+        const fields = new Uint32Array(this.wasmMemory.buffer, ptr, 8);
+        
+        this.port.postMessage({
+            type: 'memory-report',
+            report: {
+                granularSourceOffset: fields[0],
+                granularSourceCapacity: fields[1],
+                granularSourceUsed: fields[2],
+                irOffset: fields[3],
+                irCapacity: fields[4],
+                irUsed: fields[5],
+                layoutEnd: fields[6],
+                highWaterOffset: fields[7],
+                requiredPages: this.exports.dsp_required_memory_pages(),
+                currentPages: this.wasmMemory.buffer.byteLength / 65536,
+            },
+        });
+    }
+    
+    /**
+     * Post the engine's block time statistics (see dsp_set_profiling).
+     */
+    postProfileReport() {
+        if (!this.initialized) {
+            console.warn('[WasmDspProcessor] Cannot report profile: not initialized');
+            return;
+        }
+        
+        const ptr = this.exports.dsp_profile_report(this.engineHandle);
+        const blocks = new Uint32Array(this.wasmMemory.buffer, ptr, 1)[0];
+        const times = new Float32Array(this.wasmMemory.buffer, ptr + 4, 4);
+        
+        this.port.postMessage({
+            type: 'profile-report',
+            report: {
+                blocks,
+                lastMs: times[0],
+                minMs: times[1],
+                avgMs: times[2],
+                maxMs: times[3],
+            },
         });
     }
     
@@ -353,6 +882,11 @@ class WasmDspProcessor extends AudioWorkletProcessor {
             this.memoryView = new Float32Array(this.wasmMemory.buffer);
         }
         
+        // Continue a streamed IR load
+        if (this.irStream) {
+            this.appendIRChunk();
+        }
+        
         // ====================================================================
         // WRITE INPUT TO WASM MEMORY
         // ====================================================================
@@ -365,6 +899,14 @@ class WasmDspProcessor extends AudioWorkletProcessor {
         this.memoryView.set(input[0], inL);
         this.memoryView.set(input[1] || input[0], inR); // Mono → duplicate
         
+        // Sidechain (second input, left channel) for ducking; without a
+        // connected sidechain nothing is loaded and ducking is a no-op
+        const sidechain = inputs[1];
+        if (sidechain && sidechain[0]) {
+            this.memoryView.set(sidechain[0], this.sidechainPtr >>> 2);
+            this.exports.dsp_load_sidechain(this.engineHandle, this.sidechainPtr, numSamples);
+        }
+        
         // ====================================================================
         // CALL WASM PROCESSING FUNCTION
         // ====================================================================
@@ -372,6 +914,7 @@ class WasmDspProcessor extends AudioWorkletProcessor {
         switch (this.currentEffect) {
             case EffectType.GRANULAR:
                 this.exports.dsp_process_granular(
+                    this.engineHandle,
                     this.params.grainSize,
                     this.params.density,
                     this.params.pitchSpread,
@@ -381,25 +924,30 @@ class WasmDspProcessor extends AudioWorkletProcessor {
                 break;
                 
             case EffectType.CONVOLUTION:
-                this.exports.dsp_process_convolution(this.params.dryWet);
+                this.exports.dsp_process_convolution(
+                    this.engineHandle,
+                    this.params.dryWet,
+                    this.params.irBlend,
+                );
                 break;
                 
             case EffectType.SPECTRAL:
                 this.exports.dsp_process_spectral(
+                    this.engineHandle,
                     this.params.freezeAmount,
-                    this.params.frequencyShift
+                    this.params.frequencyShift,
+                    this.params.freezeSlot,
+                    this.params.spectralDryWet,
+                    this.params.spectralMode,
+                    this.params.formantPreserve ? 1 : 0,
+                    this.params.frequencyShiftHz
                 );
                 break;
                 
             case EffectType.BYPASS:
             default:
-                // Copy input to output directly in WASM memory (bypass)
-                {
-                    const outL = this.outputPtrL >>> 2;
-                    const outR = this.outputPtrR >>> 2;
-                    this.memoryView.copyWithin(outL, inL, inL + numSamples);
-                    this.memoryView.copyWithin(outR, inR, inR + numSamples);
-                }
+                // Copy input to output in WASM memory (through the limiter)
+                this.exports.dsp_process_passthrough(this.engineHandle);
                 break;
         }
         